@@ -1227,7 +1227,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                 )
                 .await;
 
-                let memory_in_mb: u64 = (*ISOLATE_MAX_USER_HEAP_SIZE / (1 << 20))
+                let memory_in_mb: u64 = (ISOLATE_MAX_USER_HEAP_SIZE.get() / (1 << 20))
                     .try_into()
                     .unwrap();
 
@@ -1412,7 +1412,8 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     execution_time: start.elapsed(),
                     environment: module.environment,
                     memory_in_mb: match module.environment {
-                        ModuleEnvironment::Isolate => (*ISOLATE_MAX_USER_HEAP_SIZE / (1 << 20))
+                        ModuleEnvironment::Isolate => (ISOLATE_MAX_USER_HEAP_SIZE.get()
+                            / (1 << 20))
                             .try_into()
                             .unwrap(),
                         // This isn't correct but we don't have a value to use here.
@@ -1516,8 +1517,9 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                             result_for_logging = Some(Ok(HttpActionStatusCode(h.status)));
                             response_streamer.send_part(HttpActionResponsePart::Head(h))?;
                         },
-                        HttpActionResponsePart::BodyChunk(bytes) => {
-                            response_streamer.send_part(HttpActionResponsePart::BodyChunk(bytes))?;
+                        part @ (HttpActionResponsePart::BodyChunk(_)
+                            | HttpActionResponsePart::WebSocketMessage(_)) => {
+                            response_streamer.send_part(part)?;
                         }
                     }
                 },
@@ -1533,8 +1535,9 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     result_for_logging = Some(Ok(HttpActionStatusCode(h.status)));
                     response_streamer.send_part(HttpActionResponsePart::Head(h))?;
                 },
-                HttpActionResponsePart::BodyChunk(bytes) => {
-                    response_streamer.send_part(HttpActionResponsePart::BodyChunk(bytes))?;
+                part @ (HttpActionResponsePart::BodyChunk(_)
+                | HttpActionResponsePart::WebSocketMessage(_)) => {
+                    response_streamer.send_part(part)?;
                 },
             }
         }
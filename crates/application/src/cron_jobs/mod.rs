@@ -192,7 +192,7 @@ impl<RT: Runtime> CronJobExecutor<RT> {
                     break;
                 }
                 metrics::log_cron_job_execution_lag(now - job.next_ts);
-                if running_job_ids.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
+                if running_job_ids.len() >= SCHEDULED_JOB_EXECUTION_PARALLELISM.get() {
                     // We are due to execute the next job, but we can't because of
                     // parallelism limits. We should break after logging the lag
                     // here, and then wake up in few seconds to log the lag again
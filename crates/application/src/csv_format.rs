@@ -0,0 +1,653 @@
+//! CSV encoding of tables for snapshot export and import.
+//!
+//! Exports write one column per field path, flattening nested objects up to
+//! `CsvExportOptions::max_depth` and joining the path's field names with
+//! `CsvExportOptions::path_separator`. Since the header has to come first,
+//! the columns are collected with a pass over the table before any rows are
+//! written. Cells hold the clean export JSON for their value, with strings
+//! written without quotes; null and missing fields are both empty.
+//!
+//! Imports do the reverse: headers are (optionally) split into paths of
+//! nested fields, and cells are parsed according to the column's type.
+//! Non-finite floats aren't JSON numbers, so rows holding them come with an
+//! `ExportContext` to decode them.
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+
+use anyhow::Context;
+use bytes::Bytes;
+use common::document::{
+    ResolvedDocument,
+    CREATION_TIME_FIELD,
+    ID_FIELD,
+};
+use errors::ErrorMetadata;
+use model::{
+    exports::types::CsvExportOptions,
+    snapshot_imports::types::{
+        CsvColumnType,
+        CsvImportOptions,
+        CsvNullHandling,
+    },
+};
+use serde_json::{
+    json,
+    Map as JsonMap,
+    Number as JsonNumber,
+    Value as JsonValue,
+};
+use shape_inference::{
+    export_context::{
+        ExportContext,
+        GeneratedSchema,
+    },
+    CountedShape,
+    ProdConfigWithOptionalFields,
+};
+use value::{
+    export::ValueFormat,
+    ConvexObject,
+    ConvexValue,
+    FieldName,
+};
+
+use crate::snapshot_import::ImportError;
+
+/// Collects the columns for a table's CSV export from its documents.
+pub struct CsvColumnsBuilder {
+    options: CsvExportOptions,
+    paths: BTreeSet<Vec<FieldName>>,
+}
+
+impl CsvColumnsBuilder {
+    pub fn new(options: CsvExportOptions) -> Self {
+        Self {
+            options,
+            paths: BTreeSet::new(),
+        }
+    }
+
+    pub fn observe(&mut self, object: &ConvexObject) {
+        let mut path = vec![];
+        self.observe_inner(object, &mut path);
+    }
+
+    fn observe_inner(&mut self, object: &ConvexObject, path: &mut Vec<FieldName>) {
+        for (field, value) in object.iter() {
+            path.push(field.clone());
+            match value {
+                ConvexValue::Object(nested) if self.flattens(nested, path) => {
+                    self.observe_inner(nested, path)
+                },
+                _ => {
+                    self.paths.insert(path.clone());
+                },
+            }
+            path.pop();
+        }
+    }
+
+    fn flattens(&self, object: &ConvexObject, path: &[FieldName]) -> bool {
+        // Empty objects don't have any fields to hold columns, so they're
+        // written as JSON.
+        path.len() <= self.options.max_depth as usize && object.iter().next().is_some()
+    }
+
+    pub fn build(self) -> anyhow::Result<CsvTableWriter> {
+        // If a field is an object in some documents and not in others, write
+        // the whole field as a single column.
+        let mut paths: Vec<Vec<FieldName>> = vec![];
+        for path in self.paths {
+            if let Some(last) = paths.last()
+                && path.starts_with(last)
+            {
+                continue;
+            }
+            // The header couldn't be split back into the same path on import.
+            if let Some(field) = path
+                .iter()
+                .find(|field| field.contains(self.options.path_separator))
+            {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "CsvFieldContainsPathSeparator",
+                    format!(
+                        "Field name \"{field}\" contains the CSV path separator \"{}\"",
+                        self.options.path_separator
+                    ),
+                ));
+            }
+            paths.push(path);
+        }
+        let (mut columns, rest): (Vec<_>, Vec<_>) = paths.into_iter().partition(|path| {
+            path.len() == 1
+                && (path[0] == FieldName::from(ID_FIELD.clone())
+                    || path[0] == FieldName::from(CREATION_TIME_FIELD.clone()))
+        });
+        // `_id` before `_creationTime`.
+        columns.reverse();
+        columns.extend(rest);
+        Ok(CsvTableWriter {
+            column_indexes: columns
+                .iter()
+                .enumerate()
+                .map(|(i, path)| (path.clone(), i))
+                .collect(),
+            columns,
+            options: self.options,
+        })
+    }
+}
+
+/// Writes a table's documents as CSV rows.
+pub struct CsvTableWriter {
+    options: CsvExportOptions,
+    columns: Vec<Vec<FieldName>>,
+    column_indexes: BTreeMap<Vec<FieldName>, usize>,
+}
+
+impl CsvTableWriter {
+    pub fn header(&self) -> Bytes {
+        let separator = self.options.path_separator.to_string();
+        let headers = self.columns.iter().map(|path| {
+            path.iter()
+                .map(|field| field.to_string())
+                .collect::<Vec<_>>()
+                .join(&separator)
+        });
+        encode_row(headers)
+    }
+
+    pub fn write(&self, doc: ResolvedDocument) -> anyhow::Result<Bytes> {
+        let mut cells = vec![String::new(); self.columns.len()];
+        self.write_inner(&doc.into_value().0, &mut vec![], &mut cells)?;
+        Ok(encode_row(cells))
+    }
+
+    fn write_inner(
+        &self,
+        object: &ConvexObject,
+        path: &mut Vec<FieldName>,
+        cells: &mut [String],
+    ) -> anyhow::Result<()> {
+        for (field, value) in object.iter() {
+            path.push(field.clone());
+            match (value, self.column_indexes.get(&path[..])) {
+                (_, Some(i)) => cells[*i] = encode_cell(value),
+                (ConvexValue::Object(nested), None) => self.write_inner(nested, path, cells)?,
+                (_, None) => anyhow::bail!("{path:?} isn't a CSV column"),
+            }
+            path.pop();
+        }
+        Ok(())
+    }
+}
+
+fn encode_cell(value: &ConvexValue) -> String {
+    match value {
+        ConvexValue::Null => String::new(),
+        value => match value.clone().export(ValueFormat::ConvexCleanJSON) {
+            JsonValue::String(s) => s,
+            json => json.to_string(),
+        },
+    }
+}
+
+fn encode_row(cells: impl IntoIterator<Item = String>) -> Bytes {
+    let mut row = String::new();
+    for (i, cell) in cells.into_iter().enumerate() {
+        if i > 0 {
+            row.push(',');
+        }
+        if cell.contains([',', '"', '\n', '\r']) {
+            row.push('"');
+            row.push_str(&cell.replace('"', "\"\""));
+            row.push('"');
+        } else {
+            row.push_str(&cell);
+        }
+    }
+    row.push('\n');
+    row.into()
+}
+
+struct CsvColumn {
+    header: String,
+    path: Vec<FieldName>,
+    column_type: Option<CsvColumnType>,
+}
+
+/// Parses CSV rows into clean export JSON objects.
+pub struct CsvRowParser {
+    columns: Vec<CsvColumn>,
+    null_handling: CsvNullHandling,
+}
+
+impl CsvRowParser {
+    pub fn new<'a>(
+        headers: impl IntoIterator<Item = &'a str>,
+        options: &CsvImportOptions,
+    ) -> anyhow::Result<Self> {
+        let mut columns = vec![];
+        for header in headers {
+            let header = header.trim_matches(' ');
+            let path = options
+                .field_path(header)
+                .into_iter()
+                .map(|field| {
+                    field
+                        .parse::<FieldName>()
+                        .map_err(|e| ImportError::CsvInvalidHeader(header.to_string(), e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            columns.push(CsvColumn {
+                header: header.to_string(),
+                path,
+                column_type: options.column_types.get(header).copied(),
+            });
+        }
+        for header in options.column_types.keys() {
+            if !columns.iter().any(|column| &column.header == header) {
+                anyhow::bail!(ImportError::CsvUnknownColumn(header.clone()));
+            }
+        }
+        let mut sorted: Vec<_> = columns.iter().collect();
+        sorted.sort_by(|a, b| a.path.cmp(&b.path));
+        for pair in sorted.windows(2) {
+            if pair[1].path.starts_with(&pair[0].path) {
+                anyhow::bail!(ImportError::CsvConflictingHeaders(
+                    pair[0].header.clone(),
+                    pair[1].header.clone()
+                ));
+            }
+        }
+        Ok(Self {
+            columns,
+            null_handling: options.null_handling,
+        })
+    }
+
+    /// Int64 and bytes cells are imported as strings, like in clean export
+    /// JSON, so they need a generated schema to be decoded.
+    pub fn generated_schema(
+        &self,
+    ) -> anyhow::Result<Option<GeneratedSchema<ProdConfigWithOptionalFields>>> {
+        let mut fields = vec![];
+        for column in &self.columns {
+            let value = match column.column_type {
+                Some(CsvColumnType::Int64) => ConvexValue::Int64(0),
+                Some(CsvColumnType::Bytes) => ConvexValue::try_from(Vec::<u8>::new())?,
+                Some(
+                    CsvColumnType::String
+                    | CsvColumnType::Float64
+                    | CsvColumnType::Boolean
+                    | CsvColumnType::Json,
+                )
+                | None => continue,
+            };
+            fields.push((&column.path[..], value));
+        }
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        let shape =
+            CountedShape::<ProdConfigWithOptionalFields>::empty().insert(&sample_object(fields)?);
+        Ok(Some(GeneratedSchema::new((&shape).into())))
+    }
+
+    /// Parses a row into a clean export JSON object, along with the context
+    /// to decode it with.
+    pub fn parse_row<'a>(
+        &self,
+        lineno: usize,
+        cells: impl IntoIterator<Item = &'a str>,
+    ) -> anyhow::Result<(JsonValue, ExportContext)> {
+        let cells: Vec<_> = cells.into_iter().collect();
+        if cells.len() != self.columns.len() {
+            anyhow::bail!(ImportError::CsvRowMissingFields(lineno));
+        }
+        let mut object = JsonMap::new();
+        let mut hints = BTreeMap::new();
+        for (column, cell) in self.columns.iter().zip(cells) {
+            let Some((value, context)) = self
+                .parse_cell(column.column_type, cell)
+                .map_err(|e| ImportError::CsvInvalidCell(lineno, column.header.clone(), e))?
+            else {
+                continue;
+            };
+            let (last, parents) = column.path.split_last().context("empty field path")?;
+            let mut fields = &mut object;
+            for parent in parents {
+                fields = fields
+                    .entry(parent.to_string())
+                    .or_insert_with(|| JsonValue::Object(JsonMap::new()))
+                    .as_object_mut()
+                    .context("CSV headers conflict")?;
+            }
+            fields.insert(last.to_string(), value);
+            if !context.is_infer() {
+                insert_hint(&mut hints, &column.path, context)?;
+            }
+        }
+        let context = if hints.is_empty() {
+            ExportContext::Infer
+        } else {
+            ExportContext::Object(hints)
+        };
+        Ok((JsonValue::Object(object), context))
+    }
+
+    fn parse_cell(
+        &self,
+        column_type: Option<CsvColumnType>,
+        cell: &str,
+    ) -> anyhow::Result<Option<(JsonValue, ExportContext)>> {
+        if cell.is_empty() {
+            match self.null_handling {
+                CsvNullHandling::EmptyString => {},
+                CsvNullHandling::Null => return Ok(Some((JsonValue::Null, ExportContext::Infer))),
+                CsvNullHandling::Omit => return Ok(None),
+            }
+        }
+        let value = match column_type {
+            None => return Ok(Some(parse_csv_cell(cell))),
+            Some(CsvColumnType::String) => JsonValue::String(cell.to_string()),
+            Some(CsvColumnType::Float64) => {
+                let value: f64 = cell
+                    .parse()
+                    .with_context(|| format!("{cell:?} isn't a float64"))?;
+                return Ok(Some(float_cell(value)));
+            },
+            Some(CsvColumnType::Int64) => {
+                let value: i64 = cell
+                    .parse()
+                    .with_context(|| format!("{cell:?} isn't an int64"))?;
+                JsonValue::String(value.to_string())
+            },
+            Some(CsvColumnType::Boolean) => match cell {
+                "true" | "TRUE" | "True" => JsonValue::Bool(true),
+                "false" | "FALSE" | "False" => JsonValue::Bool(false),
+                _ => anyhow::bail!("{cell:?} isn't a boolean"),
+            },
+            Some(CsvColumnType::Bytes) => {
+                base64::decode(cell).with_context(|| format!("{cell:?} isn't base64"))?;
+                JsonValue::String(cell.to_string())
+            },
+            Some(CsvColumnType::Json) => serde_json::from_str(cell)?,
+        };
+        Ok(Some((value, ExportContext::Infer)))
+    }
+}
+
+// Without a column type, we only parse out floats and strings. Spellings of
+// non-finite floats other than the clean export's (e.g. "inf" or "nan") are
+// left as strings.
+fn parse_csv_cell(s: &str) -> (JsonValue, ExportContext) {
+    match s.parse::<f64>() {
+        Ok(r) if r.is_finite() || matches!(s, "NaN" | "Infinity" | "-Infinity") => float_cell(r),
+        _ => (json!(s), ExportContext::Infer),
+    }
+}
+
+/// Non-finite floats are written as their clean export strings, with a
+/// context so they aren't decoded as strings.
+fn float_cell(value: f64) -> (JsonValue, ExportContext) {
+    if let Some(number) = JsonNumber::from_f64(value) {
+        (JsonValue::Number(number), ExportContext::Infer)
+    } else if value.is_nan() {
+        (
+            json!("NaN"),
+            ExportContext::Float64NaN {
+                nan_le_bytes: value.to_le_bytes(),
+            },
+        )
+    } else if value > 0.0 {
+        (json!("Infinity"), ExportContext::Float64Inf)
+    } else {
+        (json!("-Infinity"), ExportContext::Float64Inf)
+    }
+}
+
+fn insert_hint(
+    hints: &mut BTreeMap<FieldName, ExportContext>,
+    path: &[FieldName],
+    context: ExportContext,
+) -> anyhow::Result<()> {
+    let (last, parents) = path.split_last().context("empty field path")?;
+    let mut hints = hints;
+    for parent in parents {
+        let ExportContext::Object(nested) = hints
+            .entry(parent.clone())
+            .or_insert_with(|| ExportContext::Object(BTreeMap::new()))
+        else {
+            anyhow::bail!("CSV headers conflict");
+        };
+        hints = nested;
+    }
+    hints.insert(last.clone(), context);
+    Ok(())
+}
+
+/// Builds an object with the given values at the given paths. The paths must
+/// not be prefixes of each other.
+fn sample_object(fields: Vec<(&[FieldName], ConvexValue)>) -> anyhow::Result<ConvexObject> {
+    let mut object = BTreeMap::new();
+    let mut nested: BTreeMap<FieldName, Vec<_>> = BTreeMap::new();
+    for (path, value) in fields {
+        match path {
+            [] => anyhow::bail!("empty field path"),
+            [field] => {
+                object.insert(field.clone(), value);
+            },
+            [field, rest @ ..] => nested.entry(field.clone()).or_default().push((rest, value)),
+        }
+    }
+    for (field, fields) in nested {
+        object.insert(field, ConvexValue::Object(sample_object(fields)?));
+    }
+    object.try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        document::{
+            CreationTime,
+            ResolvedDocument,
+        },
+        testing::TestIdGenerator,
+    };
+    use errors::ErrorMetadataAnyhowExt;
+    use futures::StreamExt;
+    use maplit::btreemap;
+    use model::{
+        exports::types::CsvExportOptions,
+        snapshot_imports::types::{
+            CsvColumnType,
+            CsvImportOptions,
+            CsvNullHandling,
+        },
+    };
+    use runtime::testing::TestRuntime;
+    use shape_inference::{
+        export_context::GeneratedSchema,
+        ProdConfigWithOptionalFields,
+    };
+    use value::{
+        assert_obj,
+        ConvexValue,
+    };
+
+    use super::{
+        CsvColumnsBuilder,
+        CsvRowParser,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_csv_roundtrip(_rt: TestRuntime) -> anyhow::Result<()> {
+        let mut id_generator = TestIdGenerator::new();
+        let table_name = "users".parse()?;
+        let docs = vec![
+            ResolvedDocument::new(
+                id_generator.user_generate(&table_name),
+                CreationTime::ONE,
+                assert_obj!(
+                    "name" => "alice, \"al\"",
+                    "age" => 30,
+                    "score" => 1.5,
+                    "admin" => true,
+                    "avatar" => vec![1u8, 2, 3],
+                    "address" => {"city" => "SF", "geo" => {"lat" => 37.7}},
+                    "tags" => ["a", "b"],
+                ),
+            )?,
+            ResolvedDocument::new(
+                id_generator.user_generate(&table_name),
+                CreationTime::ONE,
+                assert_obj!(
+                    "name" => "bob\nsmith",
+                    "age" => 31,
+                    "score" => f64::NEG_INFINITY,
+                    "admin" => false,
+                    "avatar" => vec![4u8],
+                    "address" => {"city" => "NYC"},
+                    "tags" => [],
+                ),
+            )?,
+            ResolvedDocument::new(
+                id_generator.user_generate(&table_name),
+                CreationTime::ONE,
+                assert_obj!(
+                    "name" => "carol",
+                    "age" => 32,
+                    "score" => f64::NAN,
+                    "admin" => false,
+                    "avatar" => vec![5u8],
+                    "address" => {"city" => "LA"},
+                    "tags" => ["c"],
+                ),
+            )?,
+        ];
+        let options = CsvExportOptions {
+            path_separator: '/',
+            max_depth: 1,
+        };
+        let mut columns = CsvColumnsBuilder::new(options);
+        for doc in &docs {
+            columns.observe(doc.value());
+        }
+        let writer = columns.build()?;
+        let mut csv = writer.header().to_vec();
+        for doc in &docs {
+            csv.extend(writer.write(doc.clone())?);
+        }
+
+        let mut reader = csv_async::AsyncReader::from_reader(&csv[..]);
+        let headers: Vec<String> = reader.headers().await?.iter().map(String::from).collect();
+        assert_eq!(
+            headers,
+            vec![
+                "_id",
+                "_creationTime",
+                "address/city",
+                "address/geo",
+                "admin",
+                "age",
+                "avatar",
+                "name",
+                "score",
+                "tags",
+            ]
+        );
+        let options = CsvImportOptions {
+            path_separator: Some('/'),
+            column_types: btreemap! {
+                "_id".to_string() => CsvColumnType::String,
+                "address/city".to_string() => CsvColumnType::String,
+                "address/geo".to_string() => CsvColumnType::Json,
+                "admin".to_string() => CsvColumnType::Boolean,
+                "age".to_string() => CsvColumnType::Int64,
+                "avatar".to_string() => CsvColumnType::Bytes,
+                "name".to_string() => CsvColumnType::String,
+                "score".to_string() => CsvColumnType::Float64,
+                "tags".to_string() => CsvColumnType::Json,
+            },
+            null_handling: CsvNullHandling::Omit,
+            ..CsvImportOptions::default()
+        };
+        let parser = CsvRowParser::new(headers.iter().map(String::as_str), &options)?;
+        let generated_schema = parser.generated_schema()?;
+        let mut records = reader.records().enumerate();
+        let mut objects = vec![];
+        while let Some((i, record)) = records.next().await {
+            let (row, context) = parser.parse_row(i + 1, record?.iter())?;
+            objects.push(GeneratedSchema::apply_with_context(
+                generated_schema.as_ref(),
+                row,
+                context,
+            )?);
+        }
+        let expected: Vec<_> = docs
+            .into_iter()
+            .map(|doc| ConvexValue::Object(doc.into_value().0))
+            .collect();
+        assert_eq!(objects, expected);
+        Ok(())
+    }
+    #[test]
+    fn test_csv_untyped_non_finite_floats() -> anyhow::Result<()> {
+        let options = CsvImportOptions {
+            path_separator: Some('/'),
+            ..CsvImportOptions::default()
+        };
+        let parser = CsvRowParser::new(["x", "nested/y", "name"], &options)?;
+        let rows = [["NaN", "-Infinity", "nan"], ["1.5", "Infinity", "inf"]];
+        let mut objects = vec![];
+        for (i, row) in rows.into_iter().enumerate() {
+            let (row, context) = parser.parse_row(i + 1, row)?;
+            objects.push(
+                GeneratedSchema::<ProdConfigWithOptionalFields>::apply_with_context(
+                    None, row, context,
+                )?,
+            );
+        }
+        assert_eq!(
+            objects,
+            vec![
+                ConvexValue::Object(assert_obj!(
+                    "x" => f64::NAN,
+                    "nested" => {"y" => f64::NEG_INFINITY},
+                    "name" => "nan",
+                )),
+                ConvexValue::Object(assert_obj!(
+                    "x" => 1.5,
+                    "nested" => {"y" => f64::INFINITY},
+                    "name" => "inf",
+                )),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_field_with_path_separator() -> anyhow::Result<()> {
+        let mut id_generator = TestIdGenerator::new();
+        let table_name = "users".parse()?;
+        let doc = ResolvedDocument::new(
+            id_generator.user_generate(&table_name),
+            CreationTime::ONE,
+            assert_obj!("address" => {"city/state" => "SF/CA"}),
+        )?;
+        let mut columns = CsvColumnsBuilder::new(CsvExportOptions {
+            path_separator: '/',
+            max_depth: 1,
+        });
+        columns.observe(doc.value());
+        let Err(err) = columns.build() else {
+            anyhow::bail!("expected the field to be rejected");
+        };
+        assert!(err.is_bad_request());
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ use std::{
         BTreeMap,
         BTreeSet,
     },
+    ops::Bound,
     sync::Arc,
     time::Duration,
 };
@@ -28,15 +29,23 @@ use common::{
     },
     errors::report_error,
     execution_context::ExecutionId,
+    knobs::INCREMENTAL_EXPORT_ROWS_PER_SECOND,
     maybe_val,
+    persistence::{
+        DocumentStream,
+        RetentionValidator,
+        TimestampRange,
+    },
     query::{
         IndexRange,
         IndexRangeExpression,
         Order,
     },
     runtime::{
+        new_rate_limiter,
         try_join_buffer_unordered,
         try_join_buffered,
+        RateLimiter,
         Runtime,
     },
     types::{
@@ -56,16 +65,23 @@ use database::{
     TableSummary,
     Transaction,
 };
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
 use futures::{
     channel::mpsc,
     pin_mut,
+    select_biased,
     stream::BoxStream,
     try_join,
     AsyncWriteExt,
     Future,
+    FutureExt,
     StreamExt,
     TryStreamExt,
 };
+use governor::Quota;
 use keybroker::Identity;
 use mime2ext::mime2ext;
 use model::{
@@ -75,8 +91,10 @@ use model::{
             ExportFormat,
             ExportObjectKeys,
         },
+        ExportScheduleModel,
         EXPORTS_BY_STATE_AND_TS_INDEX,
         EXPORTS_STATE_FIELD,
+        EXPORTS_TABLE,
         EXPORTS_TS_FIELD,
     },
     file_storage::{
@@ -98,6 +116,7 @@ use shape_inference::{
         ExportContext,
         GeneratedSchema,
     },
+    ProdConfigWithOptionalFields,
     ShapeConfig,
 };
 use storage::{
@@ -116,19 +135,29 @@ use usage_tracking::{
 use value::{
     export::ValueFormat,
     id_v6::DeveloperDocumentId,
+    InternalDocumentId,
+    InternalId,
     TableNamespace,
     TableNumber,
     TabletId,
     VirtualTableMapping,
 };
 
-use crate::metrics::{
-    export_timer,
-    log_worker_starting,
+use crate::{
+    csv_format::CsvColumnsBuilder,
+    metrics::{
+        export_timer,
+        log_worker_starting,
+    },
+    parquet_format::ParquetTableWriter,
 };
 
 const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
 const MAX_BACKOFF: Duration = Duration::from_secs(900); // 15 minutes
+
+// How many changed documents incremental exports read at a time.
+const INCREMENTAL_EXPORT_BATCH_SIZE: usize = 1000;
+
 static BEGIN_JSON_ARRAY: Bytes = Bytes::from_static("[\n".as_bytes());
 static BETWEEN_DOCUMENTS: Bytes = Bytes::from_static(",\n".as_bytes());
 static END_JSON_ARRAY: Bytes = Bytes::from_static("\n]\n".as_bytes());
@@ -142,7 +171,16 @@ static README_MD_CONTENTS: &str = r#"# Welcome to your Convex snapshot export!
 This ZIP file contains a snapshot of the tables in your Convex deployment.
 
 Documents for each table are listed as lines of JSON in
-<table_name>/documents.jsonl files.
+<table_name>/documents.jsonl files, or stored in <table_name>/documents.parquet
+files for Parquet exports.
+
+Incremental exports also contain a _manifest.json file. They only include the
+documents that changed after the export at its baseTs, and list the IDs of
+deleted documents in <table_name>/deleted.jsonl files. Tables listed in
+fullTables are included in full. To restore an incremental export, import its
+base export and then each later increment in order, replacing the existing
+tables. Tables that aren't listed in the manifest's tables were deleted since
+baseTs, so importing an increment deletes them too.
 
 For details on the format and how to use this snapshot with npx convex import,
 check out [the docs](https://docs.convex.dev/database/import-export/export) or
@@ -247,14 +285,82 @@ impl<RT: Runtime> ExportWorker<RT> {
             },
             (None, None) => {
                 tracing::info!("No exports requested or in progress.");
+                self.request_scheduled_export().await?;
             },
         }
+        // Also wake up when the next scheduled export is due.
+        let next_schedule_ts = ExportScheduleModel::new(&mut tx)
+            .next()
+            .await?
+            .map(|schedule| schedule.next_ts);
+        let now = self.runtime.generate_timestamp()?;
+        let next_schedule_future = match next_schedule_ts {
+            Some(next_ts) if next_ts > now => self.runtime.wait(next_ts - now).left_future(),
+            Some(_) => self.runtime.wait(Duration::ZERO).left_future(),
+            None => std::future::pending().right_future(),
+        };
         let token = tx.into_token()?;
         let subscription = self.database.subscribe(token).await?;
-        subscription.wait_for_invalidation().await;
+        select_biased! {
+            _ = subscription.wait_for_invalidation().fuse() => {},
+            _ = next_schedule_future.fuse() => {},
+        }
         Ok(())
     }
 
+    /// If an export schedule is due, request its export.
+    async fn request_scheduled_export(&mut self) -> anyhow::Result<()> {
+        let now = self.runtime.generate_timestamp()?;
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let Some(schedule) = ExportScheduleModel::new(&mut tx).next().await? else {
+            return Ok(());
+        };
+        if schedule.next_ts > now {
+            return Ok(());
+        }
+        let mut base_ts = schedule.next_base_ts();
+        if let Some(ts) = base_ts
+            && !self.base_in_retention(ts).await?
+        {
+            tracing::warn!("Base of incremental export at {ts} is no longer retained");
+            base_ts = None;
+        }
+        let export = Export::requested(ExportFormat::Zip {
+            include_storage: schedule.include_storage,
+            parquet: schedule.parquet,
+            base_ts,
+        });
+        let export_id = SystemMetadataModel::new_global(&mut tx)
+            .insert(&EXPORTS_TABLE, export.try_into()?)
+            .await?;
+        ExportScheduleModel::new(&mut tx)
+            .start_run(schedule.id(), export_id, now)
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "export_worker_request_scheduled")
+            .await?;
+        tracing::info!("Requested scheduled export");
+        Ok(())
+    }
+
+    /// Whether the changes since `base_ts` are still retained in every user
+    /// table, so an incremental export over them can succeed.
+    async fn base_in_retention(&self, base_ts: Timestamp) -> anyhow::Result<bool> {
+        let retention_validator = self.database.retention_validator();
+        let snapshot = self.database.latest_snapshot()?;
+        for (tablet_id, ..) in snapshot.table_registry.iter_active_user_tables() {
+            match retention_validator
+                .validate_table_document_snapshot(tablet_id, base_ts)
+                .await
+            {
+                Ok(()) => {},
+                Err(e) if e.is_out_of_retention() => return Ok(false),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+
     pub async fn export_in_state(
         tx: &mut Transaction<RT>,
         export_state: &str,
@@ -302,6 +408,12 @@ impl<RT: Runtime> ExportWorker<RT> {
                 Ok(()) => {
                     return Ok(());
                 },
+                // Retrying won't help, e.g. if the base of an incremental export is no
+                // longer retained.
+                Err(e) if e.is_bad_request() => {
+                    tracing::warn!("Export failed: {e:#}");
+                    return self.mark_failed(export).await;
+                },
                 Err(mut e) => {
                     report_error(&mut e);
                     let delay = self.runtime.with_rng(|rng| self.backoff.fail(rng));
@@ -312,6 +424,24 @@ impl<RT: Runtime> ExportWorker<RT> {
         }
     }
 
+    async fn mark_failed(&mut self, export: ParsedDocument<Export>) -> anyhow::Result<()> {
+        let Export::InProgress { start_ts, .. } = *export else {
+            anyhow::bail!("Can only fail an export that is in_progress");
+        };
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let failed_export = (*export).clone().failed(start_ts, *tx.begin_timestamp())?;
+        SystemMetadataModel::new_global(&mut tx)
+            .replace(export.id(), failed_export.try_into()?)
+            .await?;
+        ExportScheduleModel::new(&mut tx)
+            .fail_run(export.id())
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "export_worker_mark_failed")
+            .await?;
+        Ok(())
+    }
+
     async fn upload_tables(
         runtime: &RT,
         storage: Arc<dyn Storage>,
@@ -374,7 +504,11 @@ impl<RT: Runtime> ExportWorker<RT> {
             tables.iter().map(|(tablet_id, ..)| *tablet_id).collect();
 
         match format {
-            ExportFormat::Zip { include_storage } => {
+            ExportFormat::Zip {
+                include_storage,
+                parquet,
+                base_ts,
+            } => {
                 // Start upload.
                 let mut upload = storage.start_upload().await?;
                 let (sender, receiver) = mpsc::channel::<Bytes>(1);
@@ -382,20 +516,117 @@ impl<RT: Runtime> ExportWorker<RT> {
                 let writer = ChannelWriter::new(sender, 5 * (1 << 20));
                 let usage = FunctionUsageTracker::new();
 
-                let zipper = self.construct_zip_snapshot(
-                    writer,
-                    tables.clone(),
-                    ts,
-                    by_id_indexes,
-                    system_tables,
-                    virtual_tables,
-                    include_storage,
-                    usage.clone(),
-                );
+                let zipper = async {
+                    match base_ts {
+                        Some(base_ts) => {
+                            if include_storage {
+                                anyhow::bail!(ErrorMetadata::bad_request(
+                                    "IncrementalExportWithStorage",
+                                    "Incremental exports can't include file storage",
+                                ));
+                            }
+                            self.construct_incremental_zip_snapshot(
+                                writer,
+                                tables.clone(),
+                                ts,
+                                base_ts,
+                                by_id_indexes,
+                                system_tables,
+                                parquet,
+                                usage.clone(),
+                            )
+                            .await
+                        },
+                        None => {
+                            self.construct_zip_snapshot(
+                                writer,
+                                tables.clone(),
+                                ts,
+                                by_id_indexes,
+                                system_tables,
+                                virtual_tables,
+                                include_storage,
+                                parquet,
+                                usage.clone(),
+                            )
+                            .await
+                        },
+                    }
+                };
                 let (_, ()) = try_join!(uploader, zipper)?;
                 let object_keys = ExportObjectKeys::Zip(upload.complete().await?);
                 Ok((*ts, object_keys, usage))
             },
+            ExportFormat::Parquet => {
+                let mut table_object_keys = BTreeMap::new();
+                for (tablet_id, (_, table_name, table_summary)) in tables {
+                    let by_id = by_id_indexes
+                        .get(&tablet_id)
+                        .ok_or_else(|| anyhow::anyhow!("no by_id index for {} found", tablet_id))?;
+                    let generated_schema = self
+                        .generated_schema(ts, tablet_id, *by_id, &table_summary)
+                        .await?;
+                    let mut writer = ParquetTableWriter::new(&generated_schema)?;
+                    let mut upload = storage.start_upload().await?;
+
+                    let table_iterator = self.database.table_iterator(ts, 1000, None);
+                    let stream = table_iterator.stream_documents_in_table(tablet_id, *by_id, None);
+                    pin_mut!(stream);
+                    while let Some((doc, _ts)) = stream.try_next().await? {
+                        if let Some(row_group) = writer.write(doc)? {
+                            upload.write(row_group).await?;
+                        }
+                    }
+                    upload.write(writer.complete()?).await?;
+                    table_object_keys.insert(table_name, upload.complete().await?);
+                }
+                tracing::info!(
+                    "Export succeeded! {} snapshots written to storage. Format: {format:?}",
+                    tablet_ids.len()
+                );
+                Ok((
+                    *ts,
+                    ExportObjectKeys::ByTable(table_object_keys),
+                    FunctionUsageTracker::new(),
+                ))
+            },
+            ExportFormat::Csv(options) => {
+                let mut table_object_keys = BTreeMap::new();
+                for (tablet_id, (_, table_name, _)) in tables {
+                    let by_id = by_id_indexes
+                        .get(&tablet_id)
+                        .ok_or_else(|| anyhow::anyhow!("no by_id index for {} found", tablet_id))?;
+                    // The header comes first, so find all of the columns before
+                    // writing any rows.
+                    let mut columns = CsvColumnsBuilder::new(options);
+                    let table_iterator = self.database.table_iterator(ts, 1000, None);
+                    let stream = table_iterator.stream_documents_in_table(tablet_id, *by_id, None);
+                    pin_mut!(stream);
+                    while let Some((doc, _ts)) = stream.try_next().await? {
+                        columns.observe(doc.value());
+                    }
+                    let writer = columns.build()?;
+                    let mut upload = storage.start_upload().await?;
+                    upload.write(writer.header()).await?;
+
+                    let table_iterator = self.database.table_iterator(ts, 1000, None);
+                    let stream = table_iterator.stream_documents_in_table(tablet_id, *by_id, None);
+                    pin_mut!(stream);
+                    while let Some((doc, _ts)) = stream.try_next().await? {
+                        upload.write(writer.write(doc)?).await?;
+                    }
+                    table_object_keys.insert(table_name, upload.complete().await?);
+                }
+                tracing::info!(
+                    "Export succeeded! {} snapshots written to storage. Format: {format:?}",
+                    tablet_ids.len()
+                );
+                Ok((
+                    *ts,
+                    ExportObjectKeys::ByTable(table_object_keys),
+                    FunctionUsageTracker::new(),
+                ))
+            },
             ExportFormat::CleanJsonl | ExportFormat::InternalJson => {
                 let mut table_uploads = Self::upload_tables(
                     &self.runtime,
@@ -463,6 +694,7 @@ impl<RT: Runtime> ExportWorker<RT> {
         system_tables: BTreeMap<TableName, TabletId>,
         virtual_tables: VirtualTableMapping,
         include_storage: bool,
+        parquet: bool,
         usage: FunctionUsageTracker,
     ) -> anyhow::Result<()> {
         let mut zip_snapshot_upload = ZipSnapshotUpload::new(&mut writer).await?;
@@ -582,18 +814,12 @@ impl<RT: Runtime> ExportWorker<RT> {
                 .get(tablet_id)
                 .ok_or_else(|| anyhow::anyhow!("no by_id index for {} found", tablet_id))?;
 
-            let mut generated_schema = GeneratedSchema::new(table_summary.inferred_type().into());
-            if ExportContext::is_ambiguous(table_summary.inferred_type()) {
-                let table_iterator = self.database.table_iterator(snapshot_ts, 1000, None);
-                let stream = table_iterator.stream_documents_in_table(*tablet_id, *by_id, None);
-                pin_mut!(stream);
-                while let Some((doc, _ts)) = stream.try_next().await? {
-                    generated_schema.insert(doc.value(), doc.developer_id());
-                }
-            }
+            let generated_schema = self
+                .generated_schema(snapshot_ts, *tablet_id, *by_id, &table_summary)
+                .await?;
 
             let mut table_upload = zip_snapshot_upload
-                .start_table(table_name.clone(), generated_schema)
+                .start_table(table_name.clone(), generated_schema, parquet)
                 .await?;
 
             let table_iterator = self.database.table_iterator(snapshot_ts, 1000, None);
@@ -614,6 +840,272 @@ impl<RT: Runtime> ExportWorker<RT> {
         Ok(())
     }
 
+    /// Like `construct_zip_snapshot`, but only includes the documents that
+    /// changed after `base_ts`, so it has to be imported on top of the export
+    /// taken at `base_ts`. Tables that were created or replaced since then are
+    /// included in full.
+    async fn construct_incremental_zip_snapshot(
+        &self,
+        mut writer: ChannelWriter,
+        tables: BTreeMap<TabletId, (TableNumber, TableName, TableSummary)>,
+        snapshot_ts: RepeatableTimestamp,
+        base_ts: Timestamp,
+        by_id_indexes: BTreeMap<TabletId, IndexId>,
+        system_tables: BTreeMap<TableName, TabletId>,
+        parquet: bool,
+        usage: FunctionUsageTracker,
+    ) -> anyhow::Result<()> {
+        if base_ts > *snapshot_ts {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidIncrementalExportBase",
+                format!("Can't export the changes since {base_ts}, which is in the future"),
+            ));
+        }
+        let rate_limiter = new_rate_limiter(
+            self.runtime.clone(),
+            Quota::per_second(*INCREMENTAL_EXPORT_ROWS_PER_SECOND),
+        );
+
+        let tables_tablet_id = system_tables
+            .get(&TABLES_TABLE)
+            .context("_tables does not exist")?;
+
+        // Find the documents that changed with a single pass over the log,
+        // keeping only the ids of the exported tables' documents and of their
+        // `_tables` entries. Their revisions at `snapshot_ts` are read when
+        // they're written.
+        let mut changed_ids: BTreeMap<TabletId, BTreeSet<InternalId>> = BTreeMap::new();
+        let mut stream = self.document_log(base_ts, snapshot_ts, &rate_limiter)?;
+        while let Some((_, id, _)) = stream.try_next().await? {
+            let tablet_id = *id.table();
+            if !tables.contains_key(&tablet_id) && tablet_id != *tables_tablet_id {
+                continue;
+            }
+            changed_ids
+                .entry(tablet_id)
+                .or_default()
+                .insert(id.internal_id());
+        }
+        drop(stream);
+
+        // A table's `_tables` entry changes when it's created or replaced, e.g. by
+        // an import, so none of its documents at `base_ts` can be reused.
+        let full_tablet_ids: BTreeSet<_> = changed_ids
+            .get(tables_tablet_id)
+            .into_iter()
+            .flatten()
+            .map(|id| TabletId(*id))
+            .collect();
+        let changed_tablet_ids: BTreeSet<_> = tables
+            .keys()
+            .filter(|tablet_id| {
+                full_tablet_ids.contains(tablet_id) || changed_ids.contains_key(tablet_id)
+            })
+            .copied()
+            .collect();
+
+        let mut zip_snapshot_upload = ZipSnapshotUpload::new(&mut writer).await?;
+        let manifest = IncrementalExportManifest {
+            base_ts: base_ts.into(),
+            snapshot_ts: (*snapshot_ts).into(),
+            tables: tables
+                .values()
+                .map(|(_, table_name, _)| table_name.to_string())
+                .collect(),
+            full_tables: changed_tablet_ids
+                .iter()
+                .filter(|tablet_id| full_tablet_ids.contains(tablet_id))
+                .map(|tablet_id| tables[tablet_id].1.to_string())
+                .collect(),
+        };
+        zip_snapshot_upload
+            .write_full_file(
+                INCREMENTAL_EXPORT_MANIFEST_PATH.to_string(),
+                &serde_json::to_string_pretty(&manifest)?,
+            )
+            .await?;
+
+        {
+            // _tables, listing only the tables with changes.
+            let mut table_upload = zip_snapshot_upload
+                .start_system_table(TABLES_TABLE.clone())
+                .await?;
+            let mut user_table_numbers_and_names: Vec<_> = changed_tablet_ids
+                .iter()
+                .map(|tablet_id| {
+                    let (table_number, table_name, _) = &tables[tablet_id];
+                    (table_number, table_name)
+                })
+                .collect();
+            user_table_numbers_and_names.sort();
+            for (table_number, table_name) in user_table_numbers_and_names {
+                table_upload
+                    .write_json_line(json!({
+                        "name": table_name.clone(),
+                        "id": *table_number,
+                    }))
+                    .await?;
+            }
+            table_upload.complete().await?;
+        }
+
+        for tablet_id in changed_tablet_ids.iter() {
+            let (table_number, table_name, table_summary) =
+                tables.get(tablet_id).expect("table should have details");
+
+            if full_tablet_ids.contains(tablet_id) {
+                let by_id = by_id_indexes
+                    .get(tablet_id)
+                    .ok_or_else(|| anyhow::anyhow!("no by_id index for {} found", tablet_id))?;
+                let generated_schema = self
+                    .generated_schema(snapshot_ts, *tablet_id, *by_id, table_summary)
+                    .await?;
+                let mut table_upload = zip_snapshot_upload
+                    .start_table(table_name.clone(), generated_schema, parquet)
+                    .await?;
+                let table_iterator = self.database.table_iterator(snapshot_ts, 1000, None);
+                let stream = table_iterator.stream_documents_in_table(*tablet_id, *by_id, None);
+                pin_mut!(stream);
+                while let Some((doc, _ts)) = stream.try_next().await? {
+                    usage.track_database_egress_size(
+                        table_name.to_string(),
+                        doc.size() as u64,
+                        false,
+                    );
+                    table_upload.write(doc).await?;
+                }
+                table_upload.complete().await?;
+                continue;
+            }
+
+            let ids: Vec<_> = changed_ids
+                .remove(tablet_id)
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+
+            // The table's shape covers the changed documents, so only they need
+            // overrides.
+            let mut generated_schema = GeneratedSchema::new(table_summary.inferred_type().into());
+            if ExportContext::is_ambiguous(table_summary.inferred_type()) {
+                for batch in ids.chunks(INCREMENTAL_EXPORT_BATCH_SIZE) {
+                    for doc in self
+                        .changed_documents(*tablet_id, batch, snapshot_ts)
+                        .await?
+                        .into_values()
+                    {
+                        generated_schema.insert(doc.value(), doc.developer_id());
+                    }
+                }
+            }
+
+            let mut table_upload = zip_snapshot_upload
+                .start_table(table_name.clone(), generated_schema, parquet)
+                .await?;
+            let mut deleted = String::new();
+            for batch in ids.chunks(INCREMENTAL_EXPORT_BATCH_SIZE) {
+                let mut docs = self
+                    .changed_documents(*tablet_id, batch, snapshot_ts)
+                    .await?;
+                for internal_id in batch {
+                    match docs.remove(internal_id) {
+                        Some(doc) => {
+                            usage.track_database_egress_size(
+                                table_name.to_string(),
+                                doc.size() as u64,
+                                false,
+                            );
+                            table_upload.write(doc).await?;
+                        },
+                        None => {
+                            let id = DeveloperDocumentId::new(*table_number, *internal_id);
+                            deleted.push_str(&serde_json::to_string(&json!({"_id": id.encode()}))?);
+                            deleted.push('\n');
+                        },
+                    }
+                }
+            }
+            table_upload.complete().await?;
+            zip_snapshot_upload
+                .write_full_file(format!("{table_name}/deleted.jsonl"), &deleted)
+                .await?;
+        }
+
+        // Complete upload.
+        zip_snapshot_upload.complete().await?;
+        writer.compat_write().close().await?;
+        Ok(())
+    }
+
+    /// The revisions of documents in all tables after `base_ts`, up to
+    /// `snapshot_ts`.
+    fn document_log<'a>(
+        &'a self,
+        base_ts: Timestamp,
+        snapshot_ts: RepeatableTimestamp,
+        rate_limiter: &'a RateLimiter<RT>,
+    ) -> anyhow::Result<DocumentStream<'a>> {
+        let range = TimestampRange::new((Bound::Excluded(base_ts), Bound::Included(*snapshot_ts)))?;
+        Ok(self
+            .database
+            .load_documents(range, Order::Asc, rate_limiter)
+            .map_err(move |e| {
+                if e.is_out_of_retention() {
+                    ErrorMetadata::bad_request(
+                        "IncrementalExportBaseTooOld",
+                        format!(
+                            "The changes since {base_ts} are no longer retained, so they can't be \
+                             exported incrementally. Request a full export instead."
+                        ),
+                    )
+                    .into()
+                } else {
+                    e
+                }
+            })
+            .boxed())
+    }
+
+    /// The documents in `ids` that exist at `snapshot_ts`.
+    async fn changed_documents(
+        &self,
+        tablet_id: TabletId,
+        ids: &[InternalId],
+        snapshot_ts: RepeatableTimestamp,
+    ) -> anyhow::Result<BTreeMap<InternalId, ResolvedDocument>> {
+        let docs = self
+            .database
+            .documents_at(
+                snapshot_ts,
+                ids.iter()
+                    .map(|internal_id| InternalDocumentId::new(tablet_id, *internal_id)),
+            )
+            .await?;
+        Ok(docs
+            .into_iter()
+            .map(|(id, doc)| (id.internal_id(), doc))
+            .collect())
+    }
+
+    async fn generated_schema(
+        &self,
+        snapshot_ts: RepeatableTimestamp,
+        tablet_id: TabletId,
+        by_id: IndexId,
+        table_summary: &TableSummary,
+    ) -> anyhow::Result<GeneratedSchema<ProdConfigWithOptionalFields>> {
+        let mut generated_schema = GeneratedSchema::new(table_summary.inferred_type().into());
+        if ExportContext::is_ambiguous(table_summary.inferred_type()) {
+            let table_iterator = self.database.table_iterator(snapshot_ts, 1000, None);
+            let stream = table_iterator.stream_documents_in_table(tablet_id, by_id, None);
+            pin_mut!(stream);
+            while let Some((doc, _ts)) = stream.try_next().await? {
+                generated_schema.insert(doc.value(), doc.developer_id());
+            }
+        }
+        Ok(generated_schema)
+    }
+
     async fn export_and_mark_complete(
         &mut self,
         export: ParsedDocument<Export>,
@@ -628,6 +1120,13 @@ impl<RT: Runtime> ExportWorker<RT> {
         SystemMetadataModel::new_global(&mut tx)
             .replace(export.id(), completed_export.try_into()?)
             .await?;
+        let base_ts = match export.format() {
+            ExportFormat::Zip { base_ts, .. } => base_ts,
+            _ => None,
+        };
+        ExportScheduleModel::new(&mut tx)
+            .complete_run(export.id(), ts, base_ts)
+            .await?;
         self.database
             .commit_with_write_source(tx, "export_worker_mark_complete")
             .await?;
@@ -649,6 +1148,14 @@ struct TableUpload {
 
 impl TableUpload {
     async fn new(storage: Arc<dyn Storage>, format: ExportFormat) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            format != ExportFormat::Parquet,
+            "Parquet exports are written with ParquetTableWriter"
+        );
+        anyhow::ensure!(
+            !matches!(format, ExportFormat::Csv(_)),
+            "CSV exports are written with CsvTableWriter"
+        );
         let mut upload = storage.start_upload().await?;
         if format == ExportFormat::InternalJson {
             upload.write(BEGIN_JSON_ARRAY.clone()).await?;
@@ -662,16 +1169,20 @@ impl TableUpload {
 
     async fn write(mut self, doc: ResolvedDocument) -> anyhow::Result<Self> {
         let json = match self.format {
-            ExportFormat::CleanJsonl | ExportFormat::Zip { .. } => {
-                doc.export(ValueFormat::ConvexCleanJSON)
-            },
+            ExportFormat::CleanJsonl
+            | ExportFormat::Zip { .. }
+            | ExportFormat::Parquet
+            | ExportFormat::Csv(_) => doc.export(ValueFormat::ConvexCleanJSON),
             ExportFormat::InternalJson => doc.export(ValueFormat::ConvexEncodedJSON),
         };
         if !self.empty {
             // Between documents.
             match self.format {
                 ExportFormat::InternalJson => self.upload.write(BETWEEN_DOCUMENTS.clone()).await?,
-                ExportFormat::CleanJsonl | ExportFormat::Zip { .. } => {},
+                ExportFormat::CleanJsonl
+                | ExportFormat::Zip { .. }
+                | ExportFormat::Parquet
+                | ExportFormat::Csv(_) => {},
             }
         }
         self.empty = false;
@@ -681,9 +1192,10 @@ impl TableUpload {
 
         // After documents.
         match self.format {
-            ExportFormat::CleanJsonl | ExportFormat::Zip { .. } => {
-                self.upload.write(AFTER_DOCUMENTS_CLEAN.clone()).await?
-            },
+            ExportFormat::CleanJsonl
+            | ExportFormat::Zip { .. }
+            | ExportFormat::Parquet
+            | ExportFormat::Csv(_) => self.upload.write(AFTER_DOCUMENTS_CLEAN.clone()).await?,
             ExportFormat::InternalJson => {},
         }
 
@@ -698,6 +1210,22 @@ impl TableUpload {
     }
 }
 
+/// Serializes a generated schema in the `generated_schema.jsonl` format: the
+/// inferred shape on the first line, followed by one line per override.
+pub(crate) fn encode_generated_schema<T: ShapeConfig>(
+    generated_schema: &GeneratedSchema<T>,
+) -> anyhow::Result<String> {
+    let mut encoded = serde_json::to_string(&generated_schema.inferred_shape.to_string())?;
+    encoded.push('\n');
+    for (override_id, override_export_context) in generated_schema.overrides.iter() {
+        let override_json =
+            json!({override_id.encode(): JsonValue::from(override_export_context.clone())});
+        encoded.push_str(&serde_json::to_string(&override_json)?);
+        encoded.push('\n');
+    }
+    Ok(encoded)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FileStorageZipMetadata {
@@ -711,10 +1239,28 @@ pub struct FileStorageZipMetadata {
     pub internal_id: Option<String>,
 }
 
+/// Path of the manifest in incremental ZIP exports.
+pub const INCREMENTAL_EXPORT_MANIFEST_PATH: &str = "_manifest.json";
+
+/// Describes an incremental ZIP export, which has to be imported on top of the
+/// export taken at `base_ts`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncrementalExportManifest {
+    pub base_ts: i64,
+    pub snapshot_ts: i64,
+    /// All user tables at `snapshot_ts`. Tables that aren't listed were
+    /// deleted since `base_ts`, so they're deleted on import.
+    pub tables: Vec<String>,
+    /// Tables that are included in full instead of as changes since `base_ts`.
+    pub full_tables: Vec<String>,
+}
+
 // 'a is lifetime of entire zip file writer.
 // 'b is lifetime of entry writer for a single table.
 struct ZipSnapshotTableUpload<'a, 'b> {
     entry_writer: EntryStreamWriter<'b, &'a mut ChannelWriter>,
+    parquet_writer: Option<ParquetTableWriter>,
 }
 
 impl<'a, 'b> ZipSnapshotTableUpload<'a, 'b> {
@@ -726,10 +1272,38 @@ impl<'a, 'b> ZipSnapshotTableUpload<'a, 'b> {
         let builder = ZipEntryBuilder::new(source_path.clone(), Compression::Deflate)
             .unix_permissions(ZIP_ENTRY_PERMISSIONS);
         let entry_writer = zip_writer.write_entry_stream(builder.build()).await?;
-        Ok(Self { entry_writer })
+        Ok(Self {
+            entry_writer,
+            parquet_writer: None,
+        })
+    }
+
+    async fn new_parquet(
+        zip_writer: &'b mut ZipFileWriter<&'a mut ChannelWriter>,
+        table_name: TableName,
+        parquet_writer: ParquetTableWriter,
+    ) -> anyhow::Result<Self> {
+        let source_path = format!("{table_name}/documents.parquet");
+        // Parquet column chunks are already compressed.
+        let builder = ZipEntryBuilder::new(source_path.clone(), Compression::Stored)
+            .unix_permissions(ZIP_ENTRY_PERMISSIONS);
+        let entry_writer = zip_writer.write_entry_stream(builder.build()).await?;
+        Ok(Self {
+            entry_writer,
+            parquet_writer: Some(parquet_writer),
+        })
     }
 
     async fn write(&mut self, doc: ResolvedDocument) -> anyhow::Result<()> {
+        if let Some(parquet_writer) = &mut self.parquet_writer {
+            if let Some(row_group) = parquet_writer.write(doc)? {
+                self.entry_writer
+                    .compat_mut_write()
+                    .write_all(&row_group)
+                    .await?;
+            }
+            return Ok(());
+        }
         let json = doc.export(ValueFormat::ConvexCleanJSON);
         self.write_json_line(json).await
     }
@@ -744,7 +1318,13 @@ impl<'a, 'b> ZipSnapshotTableUpload<'a, 'b> {
         Ok(())
     }
 
-    async fn complete(self) -> anyhow::Result<()> {
+    async fn complete(mut self) -> anyhow::Result<()> {
+        if let Some(parquet_writer) = self.parquet_writer.take() {
+            self.entry_writer
+                .compat_mut_write()
+                .write_all(&parquet_writer.complete()?)
+                .await?;
+        }
         self.entry_writer.close().await?;
         Ok(())
     }
@@ -795,11 +1375,17 @@ impl<'a> ZipSnapshotUpload<'a> {
         &mut self,
         table_name: TableName,
         generated_schema: GeneratedSchema<T>,
+        parquet: bool,
     ) -> anyhow::Result<ZipSnapshotTableUpload<'a, '_>> {
-        self.write_generated_schema(&table_name, generated_schema)
+        self.write_generated_schema(&table_name, &generated_schema)
             .await?;
 
-        ZipSnapshotTableUpload::new(&mut self.writer, table_name).await
+        if parquet {
+            let parquet_writer = ParquetTableWriter::new(&generated_schema)?;
+            ZipSnapshotTableUpload::new_parquet(&mut self.writer, table_name, parquet_writer).await
+        } else {
+            ZipSnapshotTableUpload::new(&mut self.writer, table_name).await
+        }
     }
 
     /// System tables have known shape, so we don't need to serialize it.
@@ -814,27 +1400,16 @@ impl<'a> ZipSnapshotUpload<'a> {
     async fn write_generated_schema<T: ShapeConfig>(
         &mut self,
         table_name: &TableName,
-        generated_schema: GeneratedSchema<T>,
+        generated_schema: &GeneratedSchema<T>,
     ) -> anyhow::Result<()> {
         let generated_schema_path = format!("{table_name}/generated_schema.jsonl");
         let builder = ZipEntryBuilder::new(generated_schema_path.clone(), Compression::Deflate)
             .unix_permissions(ZIP_ENTRY_PERMISSIONS);
         let mut entry_writer = self.writer.write_entry_stream(builder.build()).await?;
-        let generated_schema_str = generated_schema.inferred_shape.to_string();
         entry_writer
             .compat_mut_write()
-            .write_all(serde_json::to_string(&generated_schema_str)?.as_bytes())
+            .write_all(encode_generated_schema(generated_schema)?.as_bytes())
             .await?;
-        entry_writer.compat_mut_write().write_all(b"\n").await?;
-        for (override_id, override_export_context) in generated_schema.overrides.into_iter() {
-            let override_json =
-                json!({override_id.encode(): JsonValue::from(override_export_context)});
-            entry_writer
-                .compat_mut_write()
-                .write_all(serde_json::to_string(&override_json)?.as_bytes())
-                .await?;
-            entry_writer.compat_mut_write().write_all(b"\n").await?;
-        }
         entry_writer.close().await?;
         Ok(())
     }
@@ -849,6 +1424,11 @@ impl<'a> ZipSnapshotUpload<'a> {
 mod tests {
     use std::{
         collections::BTreeMap,
+        io::{
+            Seek,
+            SeekFrom,
+            Write,
+        },
         str,
         sync::Arc,
         time::Duration,
@@ -861,14 +1441,22 @@ mod tests {
             ParsedDocument,
             ResolvedDocument,
         },
+        query::{
+            Order,
+            Query,
+        },
+        runtime::Runtime,
         types::{
             ConvexOrigin,
+            MemberId,
             TableName,
+            Timestamp,
         },
         value::ConvexObject,
     };
     use database::{
         test_helpers::DbFixtures,
+        ResolvedQuery,
         TableModel,
         UserFacingModel,
     };
@@ -876,20 +1464,39 @@ mod tests {
         FileStorage,
         TransactionalFileStorage,
     };
+    use futures::{
+        stream,
+        StreamExt,
+    };
     use headers::ContentType;
-    use keybroker::Identity;
+    use keybroker::{
+        AdminIdentity,
+        Identity,
+    };
     use model::{
-        exports::types::{
-            Export,
-            ExportFormat,
-            ExportObjectKeys,
+        cron_jobs::types::CronSchedule,
+        exports::{
+            types::{
+                CsvExportOptions,
+                Export,
+                ExportFormat,
+                ExportObjectKeys,
+            },
+            ExportScheduleModel,
         },
         file_storage::types::FileStorageEntry,
+        snapshot_imports::types::{
+            ImportFormat,
+            ImportMode,
+        },
         test_helpers::DbFixturesWithModel,
     };
     use must_let::must_let;
     use runtime::testing::TestRuntime;
-    use serde_json::json;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
     use storage::{
         LocalDirStorage,
         Storage,
@@ -906,9 +1513,16 @@ mod tests {
 
     use super::{
         ExportWorker,
+        IncrementalExportManifest,
         TableUpload,
     };
-    use crate::export_worker::README_MD_CONTENTS;
+    use crate::{
+        export_worker::README_MD_CONTENTS,
+        parquet_format::ParquetTableReader,
+        snapshot_import::do_import,
+        test_helpers::ApplicationTestExt,
+        Application,
+    };
 
     #[convex_macro::test_runtime]
     async fn test_export(rt: TestRuntime) -> anyhow::Result<()> {
@@ -1039,6 +1653,8 @@ mod tests {
         let (_, object_keys, usage) = export_worker
             .export_inner(ExportFormat::Zip {
                 include_storage: true,
+                parquet: false,
+                base_ts: None,
             })
             .await?;
         must_let!(let ExportObjectKeys::Zip(object_key) = object_keys);
@@ -1132,6 +1748,8 @@ mod tests {
         let (_, object_keys, usage) = export_worker
             .export_inner(ExportFormat::Zip {
                 include_storage: true,
+                parquet: false,
+                base_ts: None,
             })
             .await?;
         must_let!(let ExportObjectKeys::Zip(object_key) = object_keys);
@@ -1163,8 +1781,567 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn test_export_incremental_zip(rt: TestRuntime) -> anyhow::Result<()> {
+        let DbFixtures { db, .. } = DbFixtures::new_with_model(&rt).await?;
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let file_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let mut export_worker =
+            ExportWorker::new_test(rt, db.clone(), storage.clone(), file_storage);
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let updated_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert("table_0".parse()?, assert_obj!("foo" => 1))
+            .await?;
+        let deleted_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert("table_0".parse()?, assert_obj!("foo" => 1))
+            .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert("table_1".parse()?, assert_obj!("foo" => 1))
+            .await?;
+        db.commit(tx).await?;
+        let (base_ts, ..) = export_worker
+            .export_inner(ExportFormat::Zip {
+                include_storage: false,
+                parquet: false,
+                base_ts: None,
+            })
+            .await?;
+
+        // Change table_0, leave table_1 alone, and create table_2.
+        let mut tx = db.begin(Identity::system()).await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .replace(updated_id, assert_obj!("foo" => 2))
+            .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .delete(deleted_id)
+            .await?;
+        let new_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert("table_2".parse()?, assert_obj!("foo" => 3))
+            .await?;
+        db.commit(tx).await?;
+
+        let (snapshot_ts, object_keys, _) = export_worker
+            .export_inner(ExportFormat::Zip {
+                include_storage: false,
+                parquet: false,
+                base_ts: Some(base_ts),
+            })
+            .await?;
+        must_let!(let ExportObjectKeys::Zip(object_key) = object_keys);
+
+        let storage_stream = storage
+            .get(&object_key)
+            .await?
+            .context("object missing from storage")?;
+        let stored_bytes = storage_stream.collect_as_bytes().await?;
+        let mut zip_reader = async_zip::read::mem::ZipFileReader::new(&stored_bytes).await?;
+        let mut zip_entries = BTreeMap::new();
+        let filenames: Vec<_> = zip_reader
+            .entries()
+            .into_iter()
+            .map(|entry| entry.filename().to_string())
+            .collect();
+        for (i, filename) in filenames.into_iter().enumerate() {
+            let entry_reader = zip_reader.entry_reader(i).await?;
+            let entry_contents = String::from_utf8(entry_reader.read_to_end_crc().await?)?;
+            zip_entries.insert(filename, entry_contents);
+        }
+
+        assert_eq!(
+            zip_entries.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec![
+                "README.md",
+                "_manifest.json",
+                "_tables/documents.jsonl",
+                "table_0/deleted.jsonl",
+                "table_0/documents.jsonl",
+                "table_0/generated_schema.jsonl",
+                "table_2/documents.jsonl",
+                "table_2/generated_schema.jsonl",
+            ]
+        );
+        let manifest: IncrementalExportManifest =
+            serde_json::from_str(&zip_entries["_manifest.json"])?;
+        assert_eq!(manifest.base_ts, i64::from(base_ts));
+        assert_eq!(manifest.snapshot_ts, i64::from(snapshot_ts));
+        assert_eq!(
+            manifest.tables,
+            vec![
+                "table_0".to_string(),
+                "table_1".to_string(),
+                "table_2".to_string()
+            ]
+        );
+        assert_eq!(manifest.full_tables, vec!["table_2".to_string()]);
+        assert_eq!(
+            zip_entries["_tables/documents.jsonl"],
+            format!(
+                "{}\n{}\n",
+                json!({"name": "table_0", "id": 10001}),
+                json!({"name": "table_2", "id": 10003}),
+            ),
+        );
+        assert_eq!(
+            zip_entries["table_0/deleted.jsonl"],
+            format!("{}\n", json!({"_id": deleted_id.encode()})),
+        );
+        let documents: Vec<JsonValue> = zip_entries["table_0/documents.jsonl"]
+            .lines()
+            .map(serde_json::from_str)
+            .try_collect()?;
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0]["_id"], json!(updated_id.encode()));
+        assert_eq!(documents[0]["foo"], json!(2));
+        let documents: Vec<JsonValue> = zip_entries["table_2/documents.jsonl"]
+            .lines()
+            .map(serde_json::from_str)
+            .try_collect()?;
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0]["_id"], json!(new_id.encode()));
+
+        Ok(())
+    }
+
+    /// Reads a Parquet export as clean export JSON documents by `_id`.
+    async fn read_parquet(bytes: &[u8]) -> anyhow::Result<BTreeMap<String, JsonValue>> {
+        let mut file = tempfile::tempfile()?;
+        file.write_all(bytes)?;
+        file.seek(SeekFrom::Start(0))?;
+        let mut reader = ParquetTableReader::new(file).await?;
+        assert!(reader.generated_schema().is_some());
+        let mut documents = BTreeMap::new();
+        while let Some(batch) = reader.next_batch().await? {
+            for document in batch {
+                let id = document["_id"].as_str().context("missing _id")?.to_string();
+                documents.insert(id, document);
+            }
+        }
+        Ok(documents)
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_export_parquet(rt: TestRuntime) -> anyhow::Result<()> {
+        let DbFixtures { db, .. } = DbFixtures::new_with_model(&rt).await?;
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let file_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let mut export_worker =
+            ExportWorker::new_test(rt, db.clone(), storage.clone(), file_storage);
+
+        let table: TableName = "table_0".parse()?;
+        let mut expected = BTreeMap::new();
+        let mut tx = db.begin(Identity::system()).await?;
+        for value in [
+            assert_obj!("foo" => 1, "bar" => "a"),
+            assert_obj!("foo" => 2, "baz" => [1, "1"]),
+        ] {
+            let id = UserFacingModel::new_root_for_test(&mut tx)
+                .insert(table.clone(), value)
+                .await?;
+            let doc = UserFacingModel::new_root_for_test(&mut tx)
+                .get(id, None)
+                .await?
+                .unwrap();
+            let tablet_id = tx
+                .table_mapping()
+                .namespace(TableNamespace::test_user())
+                .inject_table_id()(doc.table())?
+            .tablet_id;
+            let doc = doc.to_resolved(tablet_id);
+            expected.insert(
+                doc.developer_id().encode(),
+                doc.export(ValueFormat::ConvexCleanJSON),
+            );
+        }
+        db.commit(tx).await?;
+
+        let (_, object_keys, _) = export_worker.export_inner(ExportFormat::Parquet).await?;
+        must_let!(let ExportObjectKeys::ByTable(tables) = object_keys);
+        assert_eq!(tables.keys().collect::<Vec<_>>(), vec![&table]);
+        let stored_bytes = storage
+            .get(&tables[&table])
+            .await?
+            .context("object missing from storage")?
+            .collect_as_bytes()
+            .await?;
+        assert_eq!(read_parquet(&stored_bytes).await?, expected);
+
+        // ZIP exports can hold Parquet files too.
+        let (_, object_keys, _) = export_worker
+            .export_inner(ExportFormat::Zip {
+                include_storage: false,
+                parquet: true,
+                base_ts: None,
+            })
+            .await?;
+        must_let!(let ExportObjectKeys::Zip(object_key) = object_keys);
+        let stored_bytes = storage
+            .get(&object_key)
+            .await?
+            .context("object missing from storage")?
+            .collect_as_bytes()
+            .await?;
+        let mut zip_reader = async_zip::read::mem::ZipFileReader::new(&stored_bytes).await?;
+        let filenames: Vec<_> = zip_reader
+            .entries()
+            .into_iter()
+            .map(|entry| entry.filename().to_string())
+            .collect();
+        assert!(!filenames.contains(&"table_0/documents.jsonl".to_string()));
+        let i = filenames
+            .iter()
+            .position(|filename| filename == "table_0/documents.parquet")
+            .context("missing table_0/documents.parquet")?;
+        let entry_bytes = zip_reader.entry_reader(i).await?.read_to_end_crc().await?;
+        assert_eq!(read_parquet(&entry_bytes).await?, expected);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_import_parquet_zip_export(rt: TestRuntime) -> anyhow::Result<()> {
+        let application = Application::new_for_tests(&rt).await?;
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let file_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let mut export_worker = ExportWorker::new_test(
+            rt,
+            application.database.clone(),
+            storage.clone(),
+            file_storage,
+        );
+        let table: TableName = "table_0".parse()?;
+        let mut tx = application.begin(Identity::system()).await?;
+        let deleted_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table.clone(), assert_obj!("foo" => 1, "bar" => "a"))
+            .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table.clone(), assert_obj!("foo" => 2, "baz" => [1, "1"]))
+            .await?;
+        application.commit_test(tx).await?;
+        let expected = table_documents(&application, &table).await?;
+
+        let (_, object_keys, _) = export_worker
+            .export_inner(ExportFormat::Zip {
+                include_storage: false,
+                parquet: true,
+                base_ts: None,
+            })
+            .await?;
+        must_let!(let ExportObjectKeys::Zip(object_key) = object_keys);
+        let stored_bytes = storage
+            .get(&object_key)
+            .await?
+            .context("object missing from storage")?
+            .collect_as_bytes()
+            .await?;
+
+        // Change the table, then restore it from the export.
+        let mut tx = application.begin(Identity::system()).await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .delete(deleted_id)
+            .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table.clone(), assert_obj!("foo" => 3))
+            .await?;
+        application.commit_test(tx).await?;
+        do_import(
+            &application,
+            Identity::InstanceAdmin(AdminIdentity::new_for_test_only(
+                "test".to_string(),
+                MemberId(1),
+            )),
+            ImportFormat::Zip,
+            ImportMode::Replace,
+            stream::once(async move { anyhow::Ok(stored_bytes) }).boxed(),
+        )
+        .await?;
+        assert_eq!(table_documents(&application, &table).await?, expected);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_import_incremental_zip_export(rt: TestRuntime) -> anyhow::Result<()> {
+        let application = Application::new_for_tests(&rt).await?;
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let file_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let mut export_worker = ExportWorker::new_test(
+            rt,
+            application.database.clone(),
+            storage.clone(),
+            file_storage,
+        );
+        let identity = Identity::InstanceAdmin(AdminIdentity::new_for_test_only(
+            "test".to_string(),
+            MemberId(1),
+        ));
+        let tables: Vec<TableName> = ["table_0", "table_1", "table_2"]
+            .into_iter()
+            .map(str::parse)
+            .try_collect()?;
+        let deleted_table: TableName = "table_3".parse()?;
+        let new_table: TableName = "table_4".parse()?;
+
+        let mut tx = application.begin(Identity::system()).await?;
+        let updated_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(tables[0].clone(), assert_obj!("foo" => 1))
+            .await?;
+        let deleted_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(tables[0].clone(), assert_obj!("foo" => 1))
+            .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(tables[1].clone(), assert_obj!("foo" => 1))
+            .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(deleted_table.clone(), assert_obj!("foo" => 1))
+            .await?;
+        application.commit_test(tx).await?;
+        let (base_ts, base_bytes) = zip_export(&mut export_worker, &storage, None).await?;
+
+        // Change table_0, leave table_1 alone, create table_2 and delete table_3.
+        let mut tx = application.begin(Identity::system()).await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .replace(updated_id, assert_obj!("foo" => 2))
+            .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .delete(deleted_id)
+            .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(tables[2].clone(), assert_obj!("foo" => 3))
+            .await?;
+        application.commit_test(tx).await?;
+        application
+            .delete_tables(&identity, vec![deleted_table.clone()])
+            .await?;
+        let mut expected = BTreeMap::new();
+        for table in &tables {
+            expected.insert(table.clone(), table_documents(&application, table).await?);
+        }
+        let (_, increment_bytes) = zip_export(&mut export_worker, &storage, Some(base_ts)).await?;
+
+        // Make more changes, then restore the state at the increment from the
+        // base and the increment.
+        let mut tx = application.begin(Identity::system()).await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(tables[0].clone(), assert_obj!("foo" => 4))
+            .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(new_table.clone(), assert_obj!("foo" => 4))
+            .await?;
+        application.commit_test(tx).await?;
+        for bytes in [base_bytes, increment_bytes] {
+            do_import(
+                &application,
+                identity.clone(),
+                ImportFormat::Zip,
+                ImportMode::Replace,
+                stream::once(async move { anyhow::Ok(bytes) }).boxed(),
+            )
+            .await?;
+        }
+        for table in &tables {
+            assert_eq!(table_documents(&application, table).await?, expected[table]);
+        }
+        let snapshot = application.latest_snapshot()?;
+        for table in [deleted_table, new_table] {
+            assert!(!snapshot
+                .table_registry
+                .table_exists(TableNamespace::test_user(), &table));
+        }
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_scheduled_exports(rt: TestRuntime) -> anyhow::Result<()> {
+        let DbFixtures { db, .. } = DbFixtures::new_with_model(&rt).await?;
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let file_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let mut export_worker =
+            ExportWorker::new_test(rt.clone(), db.clone(), storage, file_storage);
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let schedule_id = ExportScheduleModel::new(&mut tx)
+            .create(
+                CronSchedule::Interval { seconds: 60 },
+                false,
+                false,
+                true,
+                2,
+                rt.generate_timestamp()?,
+            )
+            .await?;
+        db.commit(tx).await?;
+
+        // Chains of two exports: full, incremental, full, incremental.
+        let mut export_ids = vec![];
+        for (i, incremental) in [false, true, false, true].into_iter().enumerate() {
+            let mut tx = db.begin(Identity::system()).await?;
+            UserFacingModel::new_root_for_test(&mut tx)
+                .insert("table_0".parse()?, assert_obj!("foo" => i as i64))
+                .await?;
+            db.commit(tx).await?;
+            rt.advance_time(Duration::from_secs(60)).await;
+            // The first run requests the scheduled export, and the second runs
+            // it.
+            export_worker.run().await?;
+            export_worker.run().await?;
+
+            let mut tx = db.begin(Identity::system()).await?;
+            let schedule = ExportScheduleModel::new(&mut tx)
+                .get(schedule_id)
+                .await?
+                .context("schedule missing")?
+                .into_value();
+            assert_eq!(schedule.pending_export, None);
+            let scheduled = schedule.exports.last().context("export not recorded")?;
+            assert_eq!(scheduled.base_ts.is_some(), incremental);
+            export_ids.push(scheduled.export_id);
+        }
+
+        // Completing the second chain expired the first one.
+        let mut tx = db.begin(Identity::system()).await?;
+        let schedule = ExportScheduleModel::new(&mut tx)
+            .get(schedule_id)
+            .await?
+            .context("schedule missing")?
+            .into_value();
+        assert_eq!(
+            schedule
+                .exports
+                .iter()
+                .map(|export| export.export_id)
+                .collect::<Vec<_>>(),
+            export_ids[2..]
+        );
+        let now = u64::from(*tx.begin_timestamp());
+        for (i, export_id) in export_ids.into_iter().enumerate() {
+            let export_id = export_id.to_resolved(
+                &tx.table_mapping()
+                    .namespace(TableNamespace::Global)
+                    .inject_table_id(),
+            )?;
+            let export: ParsedDocument<Export> = tx
+                .get(export_id)
+                .await?
+                .context("export missing")?
+                .try_into()?;
+            must_let!(let Export::Completed { expiration_ts, .. } = export.into_value());
+            assert_eq!(expiration_ts <= now, i < 2);
+        }
+        Ok(())
+    }
+
+    /// Runs a ZIP export, returning its snapshot timestamp and contents.
+    async fn zip_export(
+        export_worker: &mut ExportWorker<TestRuntime>,
+        storage: &Arc<dyn Storage>,
+        base_ts: Option<Timestamp>,
+    ) -> anyhow::Result<(Timestamp, Bytes)> {
+        let (ts, object_keys, _) = export_worker
+            .export_inner(ExportFormat::Zip {
+                include_storage: false,
+                parquet: false,
+                base_ts,
+            })
+            .await?;
+        must_let!(let ExportObjectKeys::Zip(object_key) = object_keys);
+        let bytes = storage
+            .get(&object_key)
+            .await?
+            .context("object missing from storage")?
+            .collect_as_bytes()
+            .await?;
+        Ok((ts, bytes))
+    }
+
+    /// Returns the table's documents as clean export JSON by `_id`.
+    async fn table_documents(
+        application: &Application<TestRuntime>,
+        table: &TableName,
+    ) -> anyhow::Result<BTreeMap<String, JsonValue>> {
+        let mut tx = application.begin(Identity::system()).await?;
+        let query = Query::full_table_scan(table.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(&mut tx, TableNamespace::test_user(), query)?;
+        let mut documents = BTreeMap::new();
+        while let Some(doc) = query_stream.next(&mut tx, None).await? {
+            documents.insert(
+                doc.developer_id().encode(),
+                doc.export(ValueFormat::ConvexCleanJSON),
+            );
+        }
+        Ok(documents)
+    }
+
     // Regression test: previously we were trying to export documents from deleted
     // tables and table_mapping was failing.
+    #[convex_macro::test_runtime]
+    async fn test_export_csv(rt: TestRuntime) -> anyhow::Result<()> {
+        let DbFixtures { db, .. } = DbFixtures::new_with_model(&rt).await?;
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let file_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let mut export_worker =
+            ExportWorker::new_test(rt, db.clone(), storage.clone(), file_storage);
+
+        let table: TableName = "table_0".parse()?;
+        let mut expected = BTreeMap::new();
+        let mut tx = db.begin(Identity::system()).await?;
+        for (value, cells) in [
+            (
+                assert_obj!("foo" => 1, "nested" => {"bar" => "a,b"}, "score" => f64::NAN),
+                ["1", "a,b", "NaN"],
+            ),
+            (
+                assert_obj!("foo" => 2, "nested" => {"bar" => "c"}),
+                ["2", "c", ""],
+            ),
+        ] {
+            let id = UserFacingModel::new_root_for_test(&mut tx)
+                .insert(table.clone(), value)
+                .await?;
+            let doc = UserFacingModel::new_root_for_test(&mut tx)
+                .get(id, None)
+                .await?
+                .unwrap();
+            let tablet_id = tx
+                .table_mapping()
+                .namespace(TableNamespace::test_user())
+                .inject_table_id()(doc.table())?
+            .tablet_id;
+            let doc = doc.to_resolved(tablet_id);
+            let id = doc.developer_id().encode();
+            let creation_time =
+                doc.export(ValueFormat::ConvexCleanJSON)["_creationTime"].to_string();
+            let mut row = vec![id.clone(), creation_time];
+            row.extend(cells.map(String::from));
+            expected.insert(id, row);
+        }
+        db.commit(tx).await?;
+
+        let (_, object_keys, _) = export_worker
+            .export_inner(ExportFormat::Csv(CsvExportOptions::default()))
+            .await?;
+        must_let!(let ExportObjectKeys::ByTable(tables) = object_keys);
+        assert_eq!(tables.keys().collect::<Vec<_>>(), vec![&table]);
+        let stored_bytes = storage
+            .get(&tables[&table])
+            .await?
+            .context("object missing from storage")?
+            .collect_as_bytes()
+            .await?;
+        let mut reader = csv_async::AsyncReader::from_reader(&stored_bytes[..]);
+        let headers: Vec<String> = reader.headers().await?.iter().map(String::from).collect();
+        assert_eq!(
+            headers,
+            vec!["_id", "_creationTime", "foo", "nested.bar", "score"]
+        );
+        let mut rows = BTreeMap::new();
+        let mut records = reader.records();
+        while let Some(record) = records.next().await {
+            let row: Vec<String> = record?.iter().map(String::from).collect();
+            rows.insert(row[0].clone(), row);
+        }
+        assert_eq!(rows, expected);
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     async fn test_export_with_table_delete(rt: TestRuntime) -> anyhow::Result<()> {
         let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
@@ -16,7 +16,10 @@ use std::{
         Arc,
         LazyLock,
     },
-    time::SystemTime,
+    time::{
+        Duration,
+        SystemTime,
+    },
 };
 
 use anyhow::Context;
@@ -50,6 +53,7 @@ use common::{
     document::{
         DocumentUpdate,
         ParsedDocument,
+        ResolvedDocument,
         CREATION_TIME_FIELD_PATH,
     },
     errors::{
@@ -57,22 +61,29 @@ use common::{
         JsError,
     },
     http::fetch::FetchClient,
+    index::IndexKeyBytes,
     knobs::{
+        DOCUMENT_HISTORY_PAGE_SIZE,
         MAX_JOBS_CANCEL_BATCH,
         SNAPSHOT_LIST_LIMIT,
+        SUGGEST_SCHEMA_MAX_CHECKED_DOCUMENTS,
     },
     log_lines::LogLines,
     log_streaming::LogSender,
     paths::FieldPath,
     pause::PauseClient,
     persistence::Persistence,
+    query::Query,
     query_journal::QueryJournal,
     runtime::{
         Runtime,
         SpawnHandle,
         UnixTimestamp,
     },
-    schemas::DatabaseSchema,
+    schemas::{
+        suggest::SuggestSchemaOptions,
+        DatabaseSchema,
+    },
     types::{
         env_var_limit_met,
         env_var_name_not_unique,
@@ -100,7 +111,9 @@ use database::{
     unauthorized_error,
     Database,
     DocumentDeltas,
+    DocumentHistoryPage,
     FastForwardIndexWorker,
+    HistoricalQueryPage,
     IndexModel,
     IndexWorker,
     OccRetryStats,
@@ -131,8 +144,11 @@ use function_log::{
 use function_runner::FunctionRunner;
 use futures::{
     channel::oneshot,
+    pin_mut,
     stream::BoxStream,
     Stream,
+    StreamExt,
+    TryStreamExt,
 };
 use headers::{
     ContentLength,
@@ -154,6 +170,7 @@ use isolate::{
     CONVEX_SITE,
 };
 use keybroker::{
+    AdminPermission,
     Identity,
     InstanceSecret,
     KeyBroker,
@@ -177,6 +194,7 @@ use model::{
         },
         ConfigModel,
     },
+    cron_jobs::types::CronSchedule,
     deployment_audit_log::{
         types::DeploymentAuditLogEvent,
         DeploymentAuditLogModel,
@@ -191,8 +209,11 @@ use model::{
             Export,
             ExportFormat,
             ExportObjectKeys,
+            ExportSchedule,
         },
+        ExportScheduleModel,
         EXPORTS_TABLE,
+        EXPORT_SCHEDULES_TABLE,
     },
     external_packages::{
         types::{
@@ -202,6 +223,11 @@ use model::{
         ExternalPackagesModel,
     },
     file_storage::FileStorageId,
+    migrations::{
+        types::Migration,
+        MigrationModel,
+        MIGRATIONS_TABLE,
+    },
     modules::{
         module_versions::{
             AnalyzedModule,
@@ -209,6 +235,7 @@ use model::{
         },
         ModuleModel,
     },
+    revoked_admin_keys::RevokedAdminKeysModel,
     scheduled_jobs::SchedulerModel,
     session_requests::types::SessionRequestIdentifier,
     snapshot_imports::types::{
@@ -220,6 +247,10 @@ use model::{
         upload_download::upload_package,
         SourcePackageModel,
     },
+    table_restores::{
+        types::TableRestore,
+        TableRestoreModel,
+    },
     udf_config::{
         types::UdfConfig,
         UdfConfigModel,
@@ -295,27 +326,33 @@ use crate::{
         UdfRate,
     },
     log_visibility::LogVisibility,
+    migration_worker::MigrationWorker,
     module_cache::ModuleCache,
     redaction::{
         RedactedJsError,
         RedactedLogLines,
     },
     snapshot_import::SnapshotImportWorker,
+    table_restore_worker::TableRestoreWorker,
 };
 
 pub mod api;
 pub mod application_function_runner;
 mod cache;
 pub mod cron_jobs;
+mod csv_format;
 mod export_worker;
 pub mod function_log;
 pub mod log_visibility;
 mod metrics;
+mod migration_worker;
 mod module_cache;
+mod parquet_format;
 pub mod redaction;
 pub mod scheduled_jobs;
 mod schema_worker;
 pub mod snapshot_import;
+mod table_restore_worker;
 mod table_summary_worker;
 pub mod valid_identifier;
 
@@ -334,6 +371,15 @@ pub struct ConfigMetadataAndSchema {
     pub schema: Option<DatabaseSchema>,
 }
 
+/// A schema suggested from the shapes of the existing documents.
+pub struct SuggestedSchema {
+    pub schema: DatabaseSchema,
+    /// Whether every document was checked against `schema`. Only the first
+    /// `SUGGEST_SCHEMA_MAX_CHECKED_DOCUMENTS` documents of each table are, so
+    /// a schema that isn't fully validated may still fail to push.
+    pub fully_validated: bool,
+}
+
 #[derive(Clone)]
 pub struct ApplyConfigArgs {
     pub auth_module: Option<ModuleConfig>,
@@ -459,6 +505,8 @@ pub struct Application<RT: Runtime> {
     schema_worker: Arc<Mutex<RT::Handle>>,
     snapshot_import_worker: Arc<Mutex<RT::Handle>>,
     export_worker: Arc<Mutex<RT::Handle>>,
+    migration_worker: Arc<Mutex<RT::Handle>>,
+    table_restore_worker: Arc<Mutex<RT::Handle>>,
     log_sender: Arc<dyn LogSender>,
     log_visibility: Arc<dyn LogVisibility<RT>>,
     module_cache: ModuleCache<RT>,
@@ -492,6 +540,8 @@ impl<RT: Runtime> Clone for Application<RT> {
             schema_worker: self.schema_worker.clone(),
             snapshot_import_worker: self.snapshot_import_worker.clone(),
             export_worker: self.export_worker.clone(),
+            migration_worker: self.migration_worker.clone(),
+            table_restore_worker: self.table_restore_worker.clone(),
             log_sender: self.log_sender.clone(),
             log_visibility: self.log_visibility.clone(),
             module_cache: self.module_cache.clone(),
@@ -630,6 +680,21 @@ impl<RT: Runtime> Application<RT> {
             runtime.spawn("snapshot_import_worker", snapshot_import_worker),
         ));
 
+        let migration_worker = MigrationWorker::new(
+            runtime.clone(),
+            database.clone(),
+            runner.clone(),
+            function_log.clone(),
+        );
+        let migration_worker = Arc::new(Mutex::new(
+            runtime.spawn("migration_worker", migration_worker),
+        ));
+
+        let table_restore_worker = TableRestoreWorker::new(runtime.clone(), database.clone());
+        let table_restore_worker = Arc::new(Mutex::new(
+            runtime.spawn("table_restore_worker", table_restore_worker),
+        ));
+
         Ok(Self {
             runtime,
             database,
@@ -654,6 +719,8 @@ impl<RT: Runtime> Application<RT> {
             schema_worker,
             export_worker,
             snapshot_import_worker,
+            migration_worker,
+            table_restore_worker,
             log_sender,
             log_visibility,
             module_cache,
@@ -866,6 +933,7 @@ impl<RT: Runtime> Application<RT> {
         journal: Option<Option<String>>,
         caller: FunctionCaller,
     ) -> anyhow::Result<RedactedQueryReturn> {
+        check_admin_key_scope(&identity, AdminPermission::ReadData, &path)?;
         let persistence_version = self.database.persistence_version();
         let block_logging = self
             .log_visibility
@@ -939,6 +1007,7 @@ impl<RT: Runtime> Application<RT> {
         caller: FunctionCaller,
         pause_client: PauseClient,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>> {
+        check_admin_key_scope(&identity, AdminPermission::WriteData, &path)?;
         let block_logging = self
             .log_visibility
             .should_redact_logs_and_error(
@@ -1001,6 +1070,7 @@ impl<RT: Runtime> Application<RT> {
         identity: Identity,
         caller: FunctionCaller,
     ) -> anyhow::Result<Result<RedactedActionReturn, RedactedActionError>> {
+        check_admin_key_scope(&identity, AdminPermission::WriteData, &name)?;
         let block_logging = self
             .log_visibility
             .should_redact_logs_and_error(
@@ -1228,13 +1298,17 @@ impl<RT: Runtime> Application<RT> {
         }
     }
 
+    /// Requests a snapshot export. Without a format, the table-by-table JSON
+    /// format supported by the deployment's NPM version is used.
     pub async fn request_export(
         &self,
         identity: Identity,
-        zip: bool,
-        include_storage: bool,
+        format: Option<ExportFormat>,
     ) -> anyhow::Result<()> {
         anyhow::ensure!(identity.is_admin(), unauthorized_error("request_export"));
+        identity.check_admin_permission(AdminPermission::Export)?;
+        // Exports include every table and file storage.
+        identity.check_admin_all_tables()?;
         let snapshot = self.latest_snapshot()?;
         let user_table_count = snapshot.table_registry.user_table_names().count();
         if user_table_count == 0 {
@@ -1249,10 +1323,9 @@ impl<RT: Runtime> Application<RT> {
         let export_in_progress = ExportWorker::export_in_state(&mut tx, "in_progress").await?;
         match (export_requested, export_in_progress) {
             (None, None) => {
-                let format = if zip {
-                    ExportFormat::Zip { include_storage }
-                } else {
-                    match UdfConfigModel::new(&mut tx).get().await? {
+                let format = match format {
+                    Some(format) => format,
+                    None => match UdfConfigModel::new(&mut tx).get().await? {
                         Some(udf_config) => {
                             // Maintain legacy internal export format for older NPM versions
                             if udf_config.server_version
@@ -1265,8 +1338,32 @@ impl<RT: Runtime> Application<RT> {
                         },
                         // They haven't pushed functions yet - give them clean export.
                         None => ExportFormat::CleanJsonl,
-                    }
+                    },
                 };
+                if let ExportFormat::Zip {
+                    include_storage,
+                    base_ts: Some(base_ts),
+                    ..
+                } = format
+                {
+                    if include_storage {
+                        anyhow::bail!(ErrorMetadata::bad_request(
+                            "IncrementalExportWithStorage",
+                            "Incremental exports can't include file storage",
+                        ));
+                    }
+                    if ExportWorker::completed_export_at_ts(&mut tx, base_ts)
+                        .await?
+                        .is_none()
+                    {
+                        anyhow::bail!(ErrorMetadata::bad_request(
+                            "IncrementalExportBaseNotFound",
+                            format!(
+                                "There is no completed export at {base_ts} to export changes since"
+                            ),
+                        ));
+                    }
+                }
                 SystemMetadataModel::new_global(&mut tx)
                     .insert(&EXPORTS_TABLE, Export::requested(format).try_into()?)
                     .await?;
@@ -1284,6 +1381,80 @@ impl<RT: Runtime> Application<RT> {
         Ok(())
     }
 
+    /// Schedule ZIP exports on `cron_schedule`. The exports are requested by
+    /// the [`ExportWorker`].
+    pub async fn create_export_schedule(
+        &self,
+        identity: Identity,
+        cron_schedule: CronSchedule,
+        include_storage: bool,
+        parquet: bool,
+        incremental: bool,
+        retention_count: u64,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        anyhow::ensure!(
+            identity.is_admin(),
+            unauthorized_error("create_export_schedule")
+        );
+        identity.check_admin_permission(AdminPermission::Export)?;
+        // Scheduled exports include every table, like requested ones.
+        identity.check_admin_all_tables()?;
+        let now = self.runtime.generate_timestamp()?;
+        let mut tx = self.begin(identity).await?;
+        let id = ExportScheduleModel::new(&mut tx)
+            .create(
+                cron_schedule,
+                include_storage,
+                parquet,
+                incremental,
+                retention_count,
+                now,
+            )
+            .await?;
+        self.commit(tx, "create_export_schedule").await?;
+        Ok(id.into())
+    }
+
+    pub async fn delete_export_schedule(
+        &self,
+        identity: Identity,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            identity.is_admin(),
+            unauthorized_error("delete_export_schedule")
+        );
+        identity.check_admin_permission(AdminPermission::Export)?;
+        identity.check_admin_all_tables()?;
+        let mut tx = self.begin(identity).await?;
+        let table_mapping = tx.table_mapping().namespace(TableNamespace::Global);
+        if !table_mapping.number_matches_name(*id.table(), &EXPORT_SCHEDULES_TABLE) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidExportScheduleId",
+                format!("{} isn't an export schedule ID", id.encode()),
+            ));
+        }
+        let id = id.to_resolved(&table_mapping.inject_table_id())?;
+        ExportScheduleModel::new(&mut tx).delete(id).await?;
+        self.commit(tx, "delete_export_schedule").await?;
+        Ok(())
+    }
+
+    /// All export schedules, soonest first.
+    pub async fn list_export_schedules(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<Vec<ParsedDocument<ExportSchedule>>> {
+        anyhow::ensure!(
+            identity.is_admin(),
+            unauthorized_error("list_export_schedules")
+        );
+        identity.check_admin_permission(AdminPermission::Export)?;
+        identity.check_admin_all_tables()?;
+        let mut tx = self.begin(identity).await?;
+        ExportScheduleModel::new(&mut tx).list().await
+    }
+
     pub async fn get_zip_export(
         &self,
         identity: Identity,
@@ -1353,7 +1524,19 @@ impl<RT: Runtime> Application<RT> {
                 ))?
                 .try_into()?;
             match export.into_value() {
-                Export::Completed { object_keys, .. } => get_object_key(object_keys)?,
+                Export::Completed {
+                    object_keys,
+                    expiration_ts,
+                    ..
+                } => {
+                    if expiration_ts <= u64::from(*tx.begin_timestamp()) {
+                        anyhow::bail!(ErrorMetadata::not_found(
+                            "ExportExpired",
+                            format!("The requested export {snapshot_ts} has expired"),
+                        ));
+                    }
+                    get_object_key(object_keys)?
+                },
                 Export::Failed { .. } | Export::InProgress { .. } | Export::Requested { .. } => {
                     anyhow::bail!(ErrorMetadata::bad_request(
                         "ExportNotComplete",
@@ -1772,6 +1955,7 @@ impl<RT: Runtime> Application<RT> {
                 "Only an admin of the deployment can import"
             ));
         }
+        identity.check_admin_permission(AdminPermission::WriteData)?;
         let upload = self
             .snapshot_imports_storage
             .start_client_driven_upload()
@@ -1792,6 +1976,7 @@ impl<RT: Runtime> Application<RT> {
                 "Only an admin of the deployment can import"
             ));
         }
+        identity.check_admin_permission(AdminPermission::WriteData)?;
         let part_token = self
             .snapshot_imports_storage
             .upload_part(upload_token, part_number, part)
@@ -1813,6 +1998,7 @@ impl<RT: Runtime> Application<RT> {
                 "Only an admin of the deployment can import"
             ));
         }
+        identity.check_admin_permission(AdminPermission::WriteData)?;
         let object_key = self
             .snapshot_imports_storage
             .finish_client_driven_upload(upload_token, part_tokens)
@@ -2089,6 +2275,8 @@ impl<RT: Runtime> Application<RT> {
         identity: &Identity,
         table_names: Vec<TableName>,
     ) -> anyhow::Result<u64> {
+        identity.check_admin_permission(AdminPermission::WriteData)?;
+        identity.check_admin_component("")?;
         let mut tx = self.begin(identity.clone()).await?;
         let mut count = 0;
         for table_name in table_names {
@@ -2096,6 +2284,7 @@ impl<RT: Runtime> Application<RT> {
                 !table_name.is_system(),
                 "cannot delete system table {table_name}"
             );
+            identity.check_admin_table(&table_name)?;
             let mut table_model = TableModel::new(&mut tx);
             count += table_model
                 .count(TableNamespace::by_component_TODO(), &table_name)
@@ -2268,9 +2457,393 @@ impl<RT: Runtime> Application<RT> {
             },
             AuthenticationToken::None => Identity::Unknown,
         };
+        self.check_admin_key_not_revoked(&identity).await?;
         Ok(identity)
     }
 
+    /// Fails if `identity` was authenticated with an admin key that has since
+    /// been revoked.
+    pub async fn check_admin_key_not_revoked(&self, identity: &Identity) -> anyhow::Result<()> {
+        if identity.admin_key_id().is_none() {
+            return Ok(());
+        }
+        let mut tx = self.begin(Identity::system()).await?;
+        RevokedAdminKeysModel::new(&mut tx)
+            .check_not_revoked(identity)
+            .await
+    }
+
+    /// Revoke the admin key with `key_id` so it can no longer authenticate.
+    /// Only keys with full access may revoke keys.
+    pub async fn revoke_admin_key(&self, identity: Identity, key_id: String) -> anyhow::Result<()> {
+        anyhow::ensure!(identity.is_admin(), unauthorized_error("revoke_admin_key"));
+        identity.check_admin_full_access()?;
+        let mut tx = self.begin(identity).await?;
+        RevokedAdminKeysModel::new(&mut tx).revoke(key_id).await?;
+        self.commit(tx, "revoke_admin_key").await?;
+        Ok(())
+    }
+
+    /// Suggest a schema for the root component's tables from the shapes
+    /// inferred for their documents. The first
+    /// `SUGGEST_SCHEMA_MAX_CHECKED_DOCUMENTS` documents of each table are
+    /// checked against the suggestion before it's returned, and the result
+    /// says whether that covered every document. Tables the admin key can't
+    /// access are left out.
+    pub async fn suggest_schema(
+        &self,
+        identity: Identity,
+        options: SuggestSchemaOptions,
+    ) -> anyhow::Result<SuggestedSchema> {
+        anyhow::ensure!(identity.is_admin(), unauthorized_error("suggest_schema"));
+        identity.check_admin_permission(AdminPermission::ReadData)?;
+        identity.check_admin_component("")?;
+        let namespace = TableNamespace::by_component_TODO();
+        let mut tx = self.begin(identity.clone()).await?;
+        let ts = tx.begin_timestamp();
+        let table_mapping = tx.table_mapping().namespace(namespace);
+        let virtual_table_mapping = tx.virtual_table_mapping().clone();
+        let by_id_indexes = IndexModel::new(&mut tx).by_id_indexes().await?;
+
+        let snapshot = self.database.snapshot(ts)?;
+        let shapes = table_mapping
+            .iter_active_user_tables()
+            .filter(|(_, _, table_name)| identity.allows_admin_table(table_name))
+            .map(|(_, _, table_name)| {
+                (
+                    table_name.clone(),
+                    snapshot.table_summary(table_name).inferred_type().clone(),
+                )
+            })
+            .collect();
+        let schema = DatabaseSchema::suggest_from_shapes(
+            shapes,
+            &table_mapping,
+            &virtual_table_mapping,
+            &options,
+        );
+
+        let max_checked_documents = *SUGGEST_SCHEMA_MAX_CHECKED_DOCUMENTS;
+        let mut fully_validated = true;
+        for table_name in schema.tables.keys() {
+            let table_id = table_mapping.id(table_name)?;
+            let by_id = by_id_indexes.get(&table_id.tablet_id).ok_or_else(|| {
+                anyhow::anyhow!("Failed to find id index for table id {table_id}")
+            })?;
+            let table_iterator = self.database.table_iterator(ts, 1000, None);
+            let stream = table_iterator
+                .stream_documents_in_table(table_id.tablet_id, *by_id, None)
+                .take(max_checked_documents + 1);
+            pin_mut!(stream);
+            let mut num_checked = 0;
+            while let Some((doc, _ts)) = stream.try_next().await? {
+                if num_checked == max_checked_documents {
+                    fully_validated = false;
+                    break;
+                }
+                num_checked += 1;
+                if let Err(schema_error) = schema.check_existing_document(
+                    &doc,
+                    table_name.clone(),
+                    &table_mapping,
+                    &virtual_table_mapping,
+                ) {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "SuggestedSchemaMismatch",
+                        format!(
+                            "Couldn't suggest a schema that matches all existing documents: \
+                             {schema_error}"
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(SuggestedSchema {
+            schema,
+            fully_validated,
+        })
+    }
+
+    /// Start migrating `table_name` by passing its documents to the mutation
+    /// `transform`, `batch_size` at a time. The migration runs in the
+    /// background; see [`MigrationWorker`].
+    pub async fn start_migration(
+        &self,
+        identity: Identity,
+        table_name: TableName,
+        transform: CanonicalizedUdfPath,
+        batch_size: u64,
+        dry_run: bool,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        anyhow::ensure!(identity.is_admin(), unauthorized_error("start_migration"));
+        identity.check_admin_permission(AdminPermission::WriteData)?;
+        identity.check_admin_component("")?;
+        identity.check_admin_table(&table_name)?;
+        let mut tx = self.begin(identity).await?;
+        if let Some(restore) = TableRestoreModel::new(&mut tx)
+            .active()
+            .await?
+            .into_iter()
+            .find(|restore| {
+                restore
+                    .tables
+                    .iter()
+                    .any(|table| table.table_name == table_name)
+            })
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "MigrationDuringRestore",
+                format!(
+                    "Table \"{table_name}\" can't be migrated while restore {} is running on it",
+                    restore.developer_id().encode()
+                ),
+            ));
+        }
+        let id = MigrationModel::new(&mut tx)
+            .start(table_name, transform, batch_size, dry_run)
+            .await?;
+        self.commit(tx, "start_migration").await?;
+        Ok(id.into())
+    }
+
+    pub async fn cancel_migration(
+        &self,
+        identity: Identity,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(identity.is_admin(), unauthorized_error("cancel_migration"));
+        identity.check_admin_permission(AdminPermission::WriteData)?;
+        identity.check_admin_component("")?;
+        let mut tx = self.begin(identity.clone()).await?;
+        let table_mapping = tx.table_mapping().namespace(TableNamespace::Global);
+        if !table_mapping.number_matches_name(*id.table(), &MIGRATIONS_TABLE) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidMigrationId",
+                format!("{} isn't a migration ID", id.encode()),
+            ));
+        }
+        let id = id.to_resolved(&table_mapping.inject_table_id())?;
+        let mut model = MigrationModel::new(&mut tx);
+        if let Some(migration) = model.get(id).await? {
+            identity.check_admin_table(&migration.table_name)?;
+        }
+        model.cancel(id).await?;
+        self.commit(tx, "cancel_migration").await?;
+        Ok(())
+    }
+
+    /// All migrations with their progress, most recently started first.
+    pub async fn list_migrations(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<Vec<ParsedDocument<Migration>>> {
+        anyhow::ensure!(identity.is_admin(), unauthorized_error("list_migrations"));
+        identity.check_admin_permission(AdminPermission::ReadData)?;
+        let mut tx = self.begin(identity.clone()).await?;
+        let migrations = MigrationModel::new(&mut tx).list().await?;
+        Ok(migrations
+            .into_iter()
+            .filter(|migration| identity.allows_admin_table(&migration.table_name))
+            .collect())
+    }
+
+    /// Lists the documents in `table_name` as they were at `ts`, which must
+    /// be within the retention window.
+    pub async fn list_historical_documents(
+        &self,
+        identity: Identity,
+        ts: Timestamp,
+        table_name: TableName,
+        cursor: Option<DeveloperDocumentId>,
+    ) -> anyhow::Result<SnapshotPage> {
+        anyhow::ensure!(
+            identity.is_admin(),
+            unauthorized_error("list_historical_documents")
+        );
+        identity.check_admin_permission(AdminPermission::ReadData)?;
+        self.database
+            .list_historical_documents(identity, ts, table_name, cursor, *SNAPSHOT_LIST_LIMIT)
+            .await
+    }
+
+    /// Runs `query` against the documents as they were at `ts`, which must be
+    /// within the retention window.
+    pub async fn query_historical_documents(
+        &self,
+        identity: Identity,
+        ts: Timestamp,
+        query: Query,
+        cursor: Option<IndexKeyBytes>,
+    ) -> anyhow::Result<HistoricalQueryPage> {
+        anyhow::ensure!(
+            identity.is_admin(),
+            unauthorized_error("query_historical_documents")
+        );
+        identity.check_admin_permission(AdminPermission::ReadData)?;
+        self.database
+            .query_historical_documents(identity, ts, query, cursor, *SNAPSHOT_LIST_LIMIT)
+            .await
+    }
+
+    pub async fn get_historical_document(
+        &self,
+        identity: Identity,
+        ts: Timestamp,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<(Timestamp, ResolvedDocument)>> {
+        anyhow::ensure!(
+            identity.is_admin(),
+            unauthorized_error("get_historical_document")
+        );
+        identity.check_admin_permission(AdminPermission::ReadData)?;
+        self.database
+            .get_historical_document(identity, ts, id)
+            .await
+    }
+
+    /// Lists a page of the document's revisions, newest first, going back as
+    /// far as the retention window.
+    pub async fn document_history(
+        &self,
+        identity: Identity,
+        id: DeveloperDocumentId,
+        cursor: Option<Timestamp>,
+    ) -> anyhow::Result<DocumentHistoryPage> {
+        anyhow::ensure!(identity.is_admin(), unauthorized_error("document_history"));
+        identity.check_admin_permission(AdminPermission::ReadData)?;
+        self.database
+            .document_history(identity, id, cursor, *DOCUMENT_HISTORY_PAGE_SIZE)
+            .await
+    }
+
+    /// Start restoring `table_names` to their state at `ts` by writing the
+    /// documents that changed since then as new revisions. The restore runs
+    /// in the background; see [`TableRestoreWorker`]. Tables being migrated
+    /// can't be restored, since the migration would overwrite the restored
+    /// documents.
+    pub async fn start_table_restore(
+        &self,
+        identity: Identity,
+        ts: Timestamp,
+        table_names: BTreeSet<TableName>,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        anyhow::ensure!(identity.is_admin(), unauthorized_error("restore_tables"));
+        identity.check_admin_permission(AdminPermission::WriteData)?;
+        identity.check_admin_component("")?;
+        if table_names.is_empty() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "RestoreNoTables",
+                "Pass at least one table to restore"
+            ));
+        }
+        self.database
+            .check_restore_tables(identity.clone(), ts, &table_names)
+            .await?;
+        let mut tx = self.begin(identity).await?;
+        if let Some(migration) = MigrationModel::new(&mut tx)
+            .active()
+            .await?
+            .into_iter()
+            .find(|migration| table_names.contains(&migration.table_name))
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "RestoreDuringMigration",
+                format!(
+                    "Table \"{}\" can't be restored while migration {} is running on it",
+                    migration.table_name,
+                    migration.developer_id().encode()
+                ),
+            ));
+        }
+        let id = TableRestoreModel::new(&mut tx)
+            .start(ts, table_names)
+            .await?;
+        self.commit(tx, "start_table_restore").await?;
+        Ok(id.into())
+    }
+
+    /// The restores of tables the identity can access, with their progress,
+    /// most recently started first.
+    pub async fn list_table_restores(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<Vec<ParsedDocument<TableRestore>>> {
+        anyhow::ensure!(
+            identity.is_admin(),
+            unauthorized_error("list_table_restores")
+        );
+        identity.check_admin_permission(AdminPermission::ReadData)?;
+        let mut tx = self.begin(identity.clone()).await?;
+        let restores = TableRestoreModel::new(&mut tx).list().await?;
+        Ok(restores
+            .into_iter()
+            .filter(|restore| {
+                restore
+                    .tables
+                    .iter()
+                    .all(|table| identity.allows_admin_table(&table.table_name))
+            })
+            .collect())
+    }
+
+    /// Returns each user table's document retention override, or `None` for
+    /// tables that use the deployment default.
+    pub async fn table_document_retention(
+        &self,
+        identity: Identity,
+    ) -> anyhow::Result<BTreeMap<TableName, Option<Duration>>> {
+        anyhow::ensure!(
+            identity.is_admin(),
+            unauthorized_error("table_document_retention")
+        );
+        identity.check_admin_permission(AdminPermission::ReadData)?;
+        identity.check_admin_component("")?;
+        let mut tx = self.begin(identity.clone()).await?;
+        let namespace = TableNamespace::by_component_TODO();
+        let table_names: Vec<_> = tx
+            .table_mapping()
+            .namespace(namespace)
+            .iter_active_user_tables()
+            .filter(|(_, _, table_name)| identity.allows_admin_table(table_name))
+            .map(|(_, _, table_name)| table_name.clone())
+            .collect();
+        let mut retention = BTreeMap::new();
+        for table_name in table_names {
+            let table_retention = TableModel::new(&mut tx)
+                .document_retention(namespace, &table_name)
+                .await?;
+            retention.insert(table_name, table_retention);
+        }
+        Ok(retention)
+    }
+
+    /// Sets how long a table keeps superseded revisions of its documents, or
+    /// resets it to the deployment default with `None`.
+    pub async fn set_table_document_retention(
+        &self,
+        identity: Identity,
+        table_name: TableName,
+        document_retention: Option<Duration>,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            identity.is_admin(),
+            unauthorized_error("set_table_document_retention")
+        );
+        identity.check_admin_permission(AdminPermission::WriteData)?;
+        identity.check_admin_component("")?;
+        identity.check_admin_table(&table_name)?;
+        let mut tx = self.begin(identity).await?;
+        TableModel::new(&mut tx)
+            .set_document_retention(
+                TableNamespace::by_component_TODO(),
+                &table_name,
+                document_retention,
+            )
+            .await?;
+        self.commit(tx, "set_table_document_retention").await?;
+        Ok(())
+    }
+
     pub async fn udf_rate(
         &self,
         identity: Identity,
@@ -2362,14 +2935,15 @@ impl<RT: Runtime> Application<RT> {
         identity: Identity,
     ) -> anyhow::Result<()> {
         loop {
+            let batch_size = MAX_JOBS_CANCEL_BATCH.get();
             let count = self
                 .execute_with_audit_log_events_and_occ_retries(
                     identity.clone(),
                     "application_cancel_all_jobs",
-                    |tx| Self::_cancel_all_jobs(tx, path.clone(), *MAX_JOBS_CANCEL_BATCH).into(),
+                    |tx| Self::_cancel_all_jobs(tx, path.clone(), batch_size).into(),
                 )
                 .await?;
-            if count < *MAX_JOBS_CANCEL_BATCH {
+            if count < batch_size {
                 break;
             }
         }
@@ -2557,6 +3131,8 @@ impl<RT: Runtime> Application<RT> {
         self.search_and_vector_bootstrap_worker.lock().shutdown();
         self.export_worker.lock().shutdown();
         self.snapshot_import_worker.lock().shutdown();
+        self.migration_worker.lock().shutdown();
+        self.table_restore_worker.lock().shutdown();
         self.runner.shutdown().await?;
         self.scheduled_job_runner.shutdown();
         self.cron_job_executor.lock().shutdown();
@@ -2569,3 +3145,17 @@ impl<RT: Runtime> Application<RT> {
 // Newer clients get a clean export in JSONL format
 static MAX_UDF_SERVER_VERSION_WITHOUT_CLEAN_EXPORT: LazyLock<Version> =
     LazyLock::new(|| Version::parse("1.3.999").unwrap());
+
+/// Admin keys may be limited to some operations and components. Check that
+/// the key behind `identity`, if any, may run `path`.
+fn check_admin_key_scope(
+    identity: &Identity,
+    permission: AdminPermission,
+    path: &ComponentFunctionPath,
+) -> anyhow::Result<()> {
+    if let Some(scope) = identity.admin_scope() {
+        scope.check_permission(permission)?;
+        scope.check_component(&String::from(path.component.clone()))?;
+    }
+    Ok(())
+}
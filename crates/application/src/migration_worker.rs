@@ -0,0 +1,467 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    bootstrap_model::schema::SchemaState,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    errors::report_error,
+    execution_context::ExecutionContext,
+    knobs::MIGRATION_BATCH_DELAY,
+    runtime::{
+        Runtime,
+        RuntimeInstant,
+    },
+    schemas::DatabaseSchema,
+    types::FunctionCaller,
+    RequestId,
+};
+use database::{
+    Database,
+    IndexModel,
+    SchemaModel,
+    Transaction,
+    UserFacingModel,
+};
+use errors::ErrorMetadataAnyhowExt;
+use futures::{
+    pin_mut,
+    Future,
+    TryStreamExt,
+};
+use keybroker::Identity;
+use model::migrations::{
+    types::{
+        Migration,
+        MigrationState,
+    },
+    MigrationModel,
+};
+use sync_types::backoff::Backoff;
+use usage_tracking::FunctionUsageTracker;
+use value::{
+    obj,
+    ConvexArray,
+    ConvexValue,
+    ResolvedDocumentId,
+    TableNamespace,
+};
+
+use crate::{
+    application_function_runner::ApplicationFunctionRunner,
+    function_log::FunctionExecutionLog,
+    metrics::log_worker_starting,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Runs the migrations in `_migrations` one batch at a time, oldest first.
+/// Progress is committed with each batch, so a migration resumes from its
+/// last committed batch after a restart.
+pub struct MigrationWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    runner: Arc<ApplicationFunctionRunner<RT>>,
+    function_log: FunctionExecutionLog<RT>,
+    backoff: Backoff,
+}
+
+impl<RT: Runtime> MigrationWorker<RT> {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        runtime: RT,
+        database: Database<RT>,
+        runner: Arc<ApplicationFunctionRunner<RT>>,
+        function_log: FunctionExecutionLog<RT>,
+    ) -> impl Future<Output = ()> + Send {
+        let mut worker = Self {
+            runtime,
+            database,
+            runner,
+            function_log,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+        };
+        async move {
+            loop {
+                if let Err(e) = worker.run().await {
+                    report_error(&mut e.context("MigrationWorker died"));
+                    let delay = worker.runtime.with_rng(|rng| worker.backoff.fail(rng));
+                    worker.runtime.wait(delay).await;
+                } else {
+                    worker.backoff.reset();
+                }
+            }
+        }
+    }
+
+    /// Run a batch of the oldest active migration, or wait for one to start.
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        let status = log_worker_starting("Migration");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let active = MigrationModel::new(&mut tx).active().await?;
+        if let Some(migration) = active.into_iter().next() {
+            let (id, migration) = migration.into_id_and_value();
+            match migration.state {
+                MigrationState::Running => self.transform_batch(id).await?,
+                MigrationState::Verifying => self.verify_batch(id).await?,
+                _ => anyhow::bail!("Migration {id} isn't active"),
+            }
+            drop(status);
+            self.runtime.wait(*MIGRATION_BATCH_DELAY).await;
+            return Ok(());
+        }
+        drop(status);
+        let token = tx.into_token()?;
+        let subscription = self.database.subscribe(token).await?;
+        subscription.wait_for_invalidation().await;
+        Ok(())
+    }
+
+    /// Pass the next batch of documents to the migration's transform and
+    /// replace them with its results, unless it's a dry run. Moves on to
+    /// verifying once the whole table has been transformed.
+    async fn transform_batch(&self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let Some(migration) = self.active_migration(&mut tx, id).await? else {
+            return Ok(());
+        };
+        let documents = match self.next_batch(&mut tx, &migration).await? {
+            Ok(documents) => documents,
+            Err(error) => return self.fail(tx, id, error).await,
+        };
+        let Some(last) = documents.last() else {
+            let mut model = MigrationModel::new(&mut tx);
+            if migration.dry_run {
+                // Nothing was written, so there's nothing new to verify.
+                model.complete(id).await?;
+            } else {
+                model.start_verifying(id).await?;
+            }
+            self.database
+                .commit_with_write_source(tx, "migration_transformed")
+                .await?;
+            return Ok(());
+        };
+        let cursor = last.developer_id();
+
+        let start = self.runtime.monotonic_now();
+        let caller = FunctionCaller::Migration;
+        let context = ExecutionContext::new(RequestId::new(), &caller);
+        let path = CanonicalizedComponentFunctionPath {
+            component: ComponentPath::root(),
+            udf_path: migration.transform.clone(),
+        };
+        let values = documents
+            .iter()
+            .map(|document| ConvexValue::Object(document.clone().into_value().0))
+            .collect::<Vec<_>>();
+        let arguments = ConvexArray::try_from(vec![ConvexValue::Object(obj!(
+            "documents" => ConvexArray::try_from(values)?
+        )?)])?;
+        let (mut tx, outcome) = self
+            .runner
+            .run_mutation_no_udf_log(
+                tx,
+                path,
+                arguments,
+                caller.allowed_visibility(),
+                context.clone(),
+            )
+            .await?;
+        let result = outcome.result.clone();
+        self.function_log.log_mutation(
+            outcome,
+            tx.take_stats(),
+            start.elapsed(),
+            caller,
+            FunctionUsageTracker::new(),
+            context,
+        );
+        let replacements = match result {
+            Ok(result) => match result.unpack() {
+                ConvexValue::Array(replacements) if replacements.len() == documents.len() => {
+                    replacements
+                },
+                _ => {
+                    let error = format!(
+                        "Transform {} must return an array with an object or null for each of its \
+                         {} documents",
+                        migration.transform,
+                        documents.len()
+                    );
+                    return self.fail_in_new_transaction(id, error).await;
+                },
+            },
+            Err(e) => {
+                let error = format!("Transform {} failed: {e}", migration.transform);
+                return self.fail_in_new_transaction(id, error).await;
+            },
+        };
+
+        let namespace = TableNamespace::by_component_TODO();
+        let schema = target_schema(&mut tx, namespace).await?;
+        let mut progress = migration.progress.clone();
+        progress.documents_processed += documents.len() as u64;
+        for (document, replacement) in documents.iter().zip(replacements) {
+            let value = match replacement {
+                ConvexValue::Null => continue,
+                ConvexValue::Object(value) => value,
+                v => {
+                    let error = format!(
+                        "Transform {} returned {v} for document {}, expected an object or null",
+                        migration.transform,
+                        document.developer_id().encode()
+                    );
+                    return self.fail_in_new_transaction(id, error).await;
+                },
+            };
+            progress.documents_changed += 1;
+            let new_document = match document.replace_value(value.clone()) {
+                Ok(new_document) => new_document,
+                Err(e) => {
+                    let error = format!(
+                        "Transform {} returned an invalid document for {}: {}",
+                        migration.transform,
+                        document.developer_id().encode(),
+                        e.user_facing_message()
+                    );
+                    return self.fail_in_new_transaction(id, error).await;
+                },
+            };
+            if let Some(schema) = &schema
+                && let Err(e) = schema.check_existing_document(
+                    &new_document,
+                    migration.table_name.clone(),
+                    &tx.table_mapping().namespace(namespace),
+                    tx.virtual_table_mapping(),
+                )
+            {
+                progress.documents_invalid += 1;
+                progress.first_invalid.get_or_insert_with(|| e.to_string());
+            }
+            if !migration.dry_run
+                && let Err(e) = UserFacingModel::new(&mut tx, namespace)
+                    .replace(document.developer_id(), value)
+                    .await
+            {
+                if !e.is_deterministic_user_error() {
+                    return Err(e);
+                }
+                let error = format!(
+                    "Failed to replace document {}: {}",
+                    document.developer_id().encode(),
+                    e.user_facing_message()
+                );
+                return self.fail_in_new_transaction(id, error).await;
+            }
+        }
+
+        if migration.dry_run {
+            // Throw away the batch's writes, including any the transform made itself, and
+            // only record its progress.
+            tx = self.database.begin(Identity::system()).await?;
+            if self.active_migration(&mut tx, id).await?.is_none() {
+                return Ok(());
+            }
+        }
+        MigrationModel::new(&mut tx)
+            .record_batch(id, cursor, progress)
+            .await?;
+        if let Err(e) = self
+            .database
+            .commit_with_write_source(tx, "migration_batch")
+            .await
+        {
+            if !e.is_deterministic_user_error() {
+                return Err(e);
+            }
+            let error = format!("Failed to write batch: {}", e.user_facing_message());
+            return self.fail_in_new_transaction(id, error).await;
+        }
+        Ok(())
+    }
+
+    /// Check the next batch of documents against the target schema. Once the
+    /// whole table has been checked, the migration completes if every document
+    /// matches and fails otherwise.
+    async fn verify_batch(&self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let Some(migration) = self.active_migration(&mut tx, id).await? else {
+            return Ok(());
+        };
+        let namespace = TableNamespace::by_component_TODO();
+        let Some(schema) = target_schema(&mut tx, namespace).await? else {
+            // Without a schema, every document is valid.
+            MigrationModel::new(&mut tx).complete(id).await?;
+            self.database
+                .commit_with_write_source(tx, "migration_verified")
+                .await?;
+            return Ok(());
+        };
+        let documents = match self.next_batch(&mut tx, &migration).await? {
+            Ok(documents) => documents,
+            Err(error) => return self.fail(tx, id, error).await,
+        };
+        let mut progress = migration.progress.clone();
+        let Some(last) = documents.last() else {
+            if progress.documents_invalid > 0 {
+                let error = format!(
+                    "{} documents in table \"{}\" don't match the schema. The first one: {}",
+                    progress.documents_invalid,
+                    migration.table_name,
+                    progress.first_invalid.unwrap_or_default(),
+                );
+                return self.fail(tx, id, error).await;
+            }
+            MigrationModel::new(&mut tx).complete(id).await?;
+            self.database
+                .commit_with_write_source(tx, "migration_verified")
+                .await?;
+            return Ok(());
+        };
+        let cursor = last.developer_id();
+        let table_mapping = tx.table_mapping().namespace(namespace);
+        for document in &documents {
+            progress.documents_verified += 1;
+            if let Err(e) = schema.check_existing_document(
+                document,
+                migration.table_name.clone(),
+                &table_mapping,
+                tx.virtual_table_mapping(),
+            ) {
+                progress.documents_invalid += 1;
+                progress.first_invalid.get_or_insert_with(|| e.to_string());
+            }
+        }
+        MigrationModel::new(&mut tx)
+            .record_batch(id, cursor, progress)
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "migration_verify_batch")
+            .await?;
+        Ok(())
+    }
+
+    /// The migration with `id` if it's still active as of `tx`.
+    async fn active_migration(
+        &self,
+        tx: &mut Transaction<RT>,
+        id: ResolvedDocumentId,
+    ) -> anyhow::Result<Option<Migration>> {
+        let migration = MigrationModel::new(tx)
+            .get(id)
+            .await?
+            .map(ParsedDocument::into_value)
+            .filter(|migration| migration.state.is_active());
+        Ok(migration)
+    }
+
+    /// The next `batch_size` documents after the migration's cursor, read
+    /// with a [`database::table_iteration::TableIterator`] at `tx`'s snapshot
+    /// and then through `tx`, so writes to them conflict with the batch.
+    /// Returns an error message if the table no longer exists.
+    async fn next_batch(
+        &self,
+        tx: &mut Transaction<RT>,
+        migration: &Migration,
+    ) -> anyhow::Result<Result<Vec<ResolvedDocument>, String>> {
+        let table_mapping = tx
+            .table_mapping()
+            .namespace(TableNamespace::by_component_TODO());
+        let Ok(table_id) = table_mapping.id(&migration.table_name) else {
+            return Ok(Err(format!(
+                "Table \"{}\" was deleted",
+                migration.table_name
+            )));
+        };
+        let cursor = match migration.cursor {
+            Some(cursor) if *cursor.table() == table_id.table_number => {
+                Some(cursor.to_resolved(&table_mapping.inject_table_id())?)
+            },
+            Some(_) => {
+                return Ok(Err(format!(
+                    "Table \"{}\" was replaced",
+                    migration.table_name
+                )))
+            },
+            None => None,
+        };
+        let by_id = IndexModel::new(tx)
+            .by_id_index_metadata(table_id.tablet_id)
+            .await?
+            .id()
+            .internal_id();
+        let batch_size = migration.batch_size as usize;
+        let table_iterator = self
+            .database
+            .table_iterator(tx.begin_timestamp(), batch_size, None);
+        let stream = table_iterator.stream_documents_in_table(table_id.tablet_id, by_id, cursor);
+        pin_mut!(stream);
+        let mut documents = vec![];
+        while documents.len() < batch_size {
+            let Some((document, _ts)) = stream.try_next().await? else {
+                break;
+            };
+            if let Some(document) = tx.get(document.id()).await? {
+                documents.push(document);
+            }
+        }
+        Ok(Ok(documents))
+    }
+
+    async fn fail(
+        &self,
+        mut tx: Transaction<RT>,
+        id: ResolvedDocumentId,
+        error: String,
+    ) -> anyhow::Result<()> {
+        tracing::warn!("Migration {id} failed: {error}");
+        MigrationModel::new(&mut tx).fail(id, error).await?;
+        self.database
+            .commit_with_write_source(tx, "migration_failed")
+            .await?;
+        Ok(())
+    }
+
+    /// Fail the migration without committing anything the current batch
+    /// wrote.
+    async fn fail_in_new_transaction(
+        &self,
+        id: ResolvedDocumentId,
+        error: String,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        if self.active_migration(&mut tx, id).await?.is_none() {
+            return Ok(());
+        }
+        self.fail(tx, id, error).await
+    }
+}
+
+/// The schema a migration's results are checked against: the schema being
+/// pushed if there is one, otherwise the active schema.
+async fn target_schema<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+) -> anyhow::Result<Option<DatabaseSchema>> {
+    let mut model = SchemaModel::new(tx, namespace);
+    for state in [
+        SchemaState::Pending,
+        SchemaState::Validated,
+        SchemaState::Active,
+    ] {
+        if let Some((_id, schema)) = model.get_by_state(state).await? {
+            return Ok(Some(schema));
+        }
+    }
+    Ok(None)
+}
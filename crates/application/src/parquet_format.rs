@@ -0,0 +1,723 @@
+//! Parquet encoding of tables for snapshot export and import.
+//!
+//! Each table is written as one Parquet file whose Arrow schema is derived
+//! from the table's inferred shape. Top-level fields with a single scalar
+//! shape get a typed column, and all other fields (unions, arrays, nested
+//! objects, ...) are written as clean export JSON strings. Tables whose shape
+//! isn't a single object only get typed columns for the system fields, with
+//! the entire document written as JSON. Documents that don't fit the typed
+//! columns, e.g. because the shape was inferred before they were written, are
+//! also written as JSON.
+//!
+//! The table's `GeneratedSchema` is stored in the Arrow schema metadata, so
+//! importing a Parquet export recovers the original Convex values.
+use std::{
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    fs::File,
+    io::Write,
+    mem,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use arrow::{
+    array::{
+        Array,
+        ArrayRef,
+        ArrowPrimitiveType,
+        AsArray,
+        BinaryBuilder,
+        BooleanBuilder,
+        Float64Builder,
+        Int64Builder,
+        StringBuilder,
+    },
+    datatypes::{
+        DataType,
+        Field,
+        Float32Type,
+        Float64Type,
+        Int16Type,
+        Int32Type,
+        Int64Type,
+        Int8Type,
+        Schema,
+        SchemaRef,
+        UInt16Type,
+        UInt32Type,
+        UInt64Type,
+        UInt8Type,
+    },
+    record_batch::RecordBatch,
+};
+use bytes::Bytes;
+use common::{
+    document::{
+        ResolvedDocument,
+        CREATION_TIME_FIELD,
+        ID_FIELD,
+    },
+    knobs::PARQUET_EXPORT_ROW_GROUP_BYTES,
+    tokio::task::spawn_blocking,
+};
+use parking_lot::Mutex;
+use parquet::{
+    arrow::{
+        arrow_reader::{
+            ParquetRecordBatchReader,
+            ParquetRecordBatchReaderBuilder,
+        },
+        ArrowWriter,
+    },
+    basic::{
+        Compression,
+        ZstdLevel,
+    },
+    file::properties::WriterProperties,
+};
+use serde_json::{
+    Map as JsonMap,
+    Value as JsonValue,
+};
+use shape_inference::{
+    export_context::GeneratedSchema,
+    Shape,
+    ShapeConfig,
+    ShapeCounter,
+    ShapeEnum,
+};
+use value::{
+    export::ValueFormat,
+    ConvexValue,
+    FieldName,
+    Namespace,
+};
+
+use crate::{
+    export_worker::encode_generated_schema,
+    snapshot_import::ImportError,
+};
+
+/// Schema metadata key holding the table's generated schema, encoded the same
+/// way as `generated_schema.jsonl` in ZIP exports.
+const GENERATED_SCHEMA_METADATA_KEY: &str = "convex:generated_schema";
+/// Field metadata key marking string columns that hold clean export JSON.
+const ENCODING_METADATA_KEY: &str = "convex:encoding";
+/// The column holds the JSON for a single field.
+const JSON_ENCODING: &str = "json";
+/// The column holds the JSON for the entire document, for documents that
+/// don't fit the typed columns.
+const DOCUMENT_ENCODING: &str = "document";
+/// System fields can't be user-defined, so this can't collide with a field.
+const DOCUMENT_COLUMN: &str = "_document";
+
+const IMPORT_BATCH_SIZE: usize = 1024;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ColumnType {
+    Int64,
+    Float64,
+    Boolean,
+    String,
+    Bytes,
+    Json,
+}
+
+impl ColumnType {
+    fn for_shape<C: ShapeConfig, S: ShapeCounter>(shape: &Shape<C, S>) -> Self {
+        match shape.variant() {
+            ShapeEnum::Int64 => Self::Int64,
+            ShapeEnum::NegativeInf
+            | ShapeEnum::PositiveInf
+            | ShapeEnum::NegativeZero
+            | ShapeEnum::NaN
+            | ShapeEnum::NormalFloat64
+            | ShapeEnum::Float64 => Self::Float64,
+            ShapeEnum::Boolean => Self::Boolean,
+            ShapeEnum::StringLiteral(_)
+            | ShapeEnum::Id(_)
+            | ShapeEnum::FieldName
+            | ShapeEnum::String => Self::String,
+            ShapeEnum::Bytes => Self::Bytes,
+            ShapeEnum::Never
+            | ShapeEnum::Null
+            | ShapeEnum::Array(_)
+            | ShapeEnum::Set(_)
+            | ShapeEnum::Map(_)
+            | ShapeEnum::Object(_)
+            | ShapeEnum::Record(_)
+            | ShapeEnum::Union(_)
+            | ShapeEnum::Unknown => Self::Json,
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            Self::Int64 => DataType::Int64,
+            Self::Float64 => DataType::Float64,
+            Self::Boolean => DataType::Boolean,
+            Self::String | Self::Json => DataType::Utf8,
+            Self::Bytes => DataType::Binary,
+        }
+    }
+}
+
+struct Column {
+    name: FieldName,
+    column_type: ColumnType,
+    nullable: bool,
+}
+
+impl Column {
+    fn arrow_field(&self) -> Field {
+        let field = Field::new(
+            self.name.to_string(),
+            self.column_type.data_type(),
+            self.nullable,
+        );
+        if self.column_type == ColumnType::Json {
+            field.with_metadata(HashMap::from([(
+                ENCODING_METADATA_KEY.to_string(),
+                JSON_ENCODING.to_string(),
+            )]))
+        } else {
+            field
+        }
+    }
+}
+
+/// Returns the typed columns for a table with the given shape, with system
+/// fields first.
+fn table_columns<C: ShapeConfig, S: ShapeCounter>(shape: &Shape<C, S>) -> Vec<Column> {
+    let ShapeEnum::Object(object_shape) = shape.variant() else {
+        return vec![
+            Column {
+                name: ID_FIELD.clone().into(),
+                column_type: ColumnType::String,
+                nullable: false,
+            },
+            Column {
+                name: CREATION_TIME_FIELD.clone().into(),
+                column_type: ColumnType::Float64,
+                nullable: false,
+            },
+        ];
+    };
+    let mut columns: Vec<_> = object_shape
+        .fields()
+        .iter()
+        .map(|(name, field)| {
+            let name: FieldName = name.clone().into();
+            Column {
+                // Documents that don't fit the shape leave user fields null.
+                nullable: field.optional || !name.is_system(),
+                column_type: ColumnType::for_shape(&field.value_shape),
+                name,
+            }
+        })
+        .collect();
+    columns.sort_by_key(|column| !column.name.is_system());
+    columns
+}
+
+enum ColumnBuilder {
+    Int64(Int64Builder),
+    Float64(Float64Builder),
+    Boolean(BooleanBuilder),
+    String(StringBuilder),
+    Bytes(BinaryBuilder),
+    Json(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::Int64 => Self::Int64(Int64Builder::new()),
+            ColumnType::Float64 => Self::Float64(Float64Builder::new()),
+            ColumnType::Boolean => Self::Boolean(BooleanBuilder::new()),
+            ColumnType::String => Self::String(StringBuilder::new()),
+            ColumnType::Bytes => Self::Bytes(BinaryBuilder::new()),
+            ColumnType::Json => Self::Json(StringBuilder::new()),
+        }
+    }
+
+    /// Whether `value` can be written to this column.
+    fn accepts(&self, value: Option<&ConvexValue>) -> bool {
+        matches!(
+            (self, value),
+            (_, None)
+                | (Self::Int64(_), Some(ConvexValue::Int64(_)))
+                | (Self::Float64(_), Some(ConvexValue::Float64(_)))
+                | (Self::Boolean(_), Some(ConvexValue::Boolean(_)))
+                | (Self::String(_), Some(ConvexValue::String(_)))
+                | (Self::Bytes(_), Some(ConvexValue::Bytes(_)))
+                | (Self::Json(_), Some(_))
+        )
+    }
+
+    fn append(&mut self, value: Option<ConvexValue>) -> anyhow::Result<()> {
+        match (self, value) {
+            (Self::Int64(builder), Some(ConvexValue::Int64(i))) => builder.append_value(i),
+            (Self::Float64(builder), Some(ConvexValue::Float64(f))) => builder.append_value(f),
+            (Self::Boolean(builder), Some(ConvexValue::Boolean(b))) => builder.append_value(b),
+            (Self::String(builder), Some(ConvexValue::String(s))) => builder.append_value(&*s),
+            (Self::Bytes(builder), Some(ConvexValue::Bytes(b))) => builder.append_value(&*b),
+            (Self::Json(builder), Some(value)) => builder.append_value(serde_json::to_string(
+                &value.export(ValueFormat::ConvexCleanJSON),
+            )?),
+            (builder, None) => builder.append_null(),
+            (_, Some(value)) => anyhow::bail!(
+                "{} value doesn't match the table's inferred shape",
+                value.type_name()
+            ),
+        }
+        Ok(())
+    }
+
+    fn append_null(&mut self) {
+        match self {
+            Self::Int64(builder) => builder.append_null(),
+            Self::Float64(builder) => builder.append_null(),
+            Self::Boolean(builder) => builder.append_null(),
+            Self::String(builder) | Self::Json(builder) => builder.append_null(),
+            Self::Bytes(builder) => builder.append_null(),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Self::Int64(builder) => Arc::new(builder.finish()),
+            Self::Float64(builder) => Arc::new(builder.finish()),
+            Self::Boolean(builder) => Arc::new(builder.finish()),
+            Self::String(builder) | Self::Json(builder) => Arc::new(builder.finish()),
+            Self::Bytes(builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// `ArrowWriter` output that can be drained while the writer is still open.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Bytes {
+        Bytes::from(mem::take(&mut *self.0.lock()))
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes a table as a Parquet file. Documents are buffered into a row group
+/// until they exceed `PARQUET_EXPORT_ROW_GROUP_BYTES`, at which point the
+/// encoded row group is handed back to the caller to upload.
+pub struct ParquetTableWriter {
+    schema: SchemaRef,
+    columns: Vec<(FieldName, ColumnBuilder)>,
+    document_column: StringBuilder,
+    writer: ArrowWriter<SharedBuffer>,
+    buffer: SharedBuffer,
+    buffered_bytes: usize,
+}
+
+impl ParquetTableWriter {
+    pub fn new<T: ShapeConfig>(generated_schema: &GeneratedSchema<T>) -> anyhow::Result<Self> {
+        let columns = table_columns(&generated_schema.inferred_shape);
+        let mut fields: Vec<_> = columns.iter().map(Column::arrow_field).collect();
+        fields.push(
+            Field::new(DOCUMENT_COLUMN, DataType::Utf8, true).with_metadata(HashMap::from([(
+                ENCODING_METADATA_KEY.to_string(),
+                DOCUMENT_ENCODING.to_string(),
+            )])),
+        );
+        let schema = Arc::new(Schema::new(fields).with_metadata(HashMap::from([(
+            GENERATED_SCHEMA_METADATA_KEY.to_string(),
+            encode_generated_schema(generated_schema)?,
+        )])));
+        let props = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let buffer = SharedBuffer::default();
+        let writer = ArrowWriter::try_new(buffer.clone(), schema.clone(), Some(props))?;
+        Ok(Self {
+            schema,
+            columns: columns
+                .into_iter()
+                .map(|column| (column.name, ColumnBuilder::new(column.column_type)))
+                .collect(),
+            document_column: StringBuilder::new(),
+            writer,
+            buffer,
+            buffered_bytes: 0,
+        })
+    }
+
+    /// Adds a document to the current row group. Returns the encoded row
+    /// group once it's large enough to be flushed.
+    pub fn write(&mut self, doc: ResolvedDocument) -> anyhow::Result<Option<Bytes>> {
+        self.buffered_bytes += doc.size();
+        let object = doc.into_value().0;
+        let mut fields = BTreeMap::from(object.clone());
+        let mut fits_columns = true;
+        for (name, builder) in self.columns.iter_mut() {
+            let value = fields.remove(name);
+            if builder.accepts(value.as_ref()) {
+                builder.append(value)?;
+            } else {
+                fits_columns = false;
+                builder.append_null();
+            }
+        }
+        // Fields without a column, or with a value of another type, are kept
+        // by writing the entire document as JSON.
+        if fits_columns && fields.is_empty() {
+            self.document_column.append_null();
+        } else {
+            let json = object.export(ValueFormat::ConvexCleanJSON);
+            self.document_column
+                .append_value(serde_json::to_string(&json)?);
+        }
+        if self.buffered_bytes < *PARQUET_EXPORT_ROW_GROUP_BYTES {
+            return Ok(None);
+        }
+        self.write_row_group()?;
+        Ok(Some(self.buffer.take()))
+    }
+
+    /// Writes any buffered documents and the file footer, returning the rest
+    /// of the file.
+    pub fn complete(mut self) -> anyhow::Result<Bytes> {
+        if self.buffered_bytes > 0 {
+            self.write_row_group()?;
+        }
+        self.writer.close()?;
+        Ok(self.buffer.take())
+    }
+
+    fn write_row_group(&mut self) -> anyhow::Result<()> {
+        let mut arrays: Vec<_> = self
+            .columns
+            .iter_mut()
+            .map(|(_, builder)| builder.finish())
+            .collect();
+        arrays.push(Arc::new(self.document_column.finish()));
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+        self.writer.write(&batch)?;
+        self.writer.flush()?;
+        self.buffered_bytes = 0;
+        Ok(())
+    }
+}
+
+/// Reads a Parquet file as clean export JSON objects, one record batch at a
+/// time. Reading and decoding the file blocks, so it happens on the blocking
+/// thread pool.
+pub struct ParquetTableReader {
+    schema: SchemaRef,
+    /// `None` while a batch is being read, or after reading one failed.
+    reader: Option<ParquetRecordBatchReader>,
+}
+
+impl ParquetTableReader {
+    pub async fn new(file: File) -> anyhow::Result<Self> {
+        spawn_blocking(move || {
+            let builder = ParquetRecordBatchReaderBuilder::try_new(file)
+                .map_err(|e| ImportError::NotParquet(e.into()))?;
+            let schema = builder.schema().clone();
+            let reader = builder
+                .with_batch_size(IMPORT_BATCH_SIZE)
+                .build()
+                .map_err(|e| ImportError::NotParquet(e.into()))?;
+            anyhow::Ok(Self {
+                schema,
+                reader: Some(reader),
+            })
+        })
+        .await?
+    }
+
+    /// The generated schema written by a Parquet export, if this file has one.
+    pub fn generated_schema(&self) -> Option<&str> {
+        self.schema
+            .metadata()
+            .get(GENERATED_SCHEMA_METADATA_KEY)
+            .map(String::as_str)
+    }
+
+    pub async fn next_batch(&mut self) -> anyhow::Result<Option<Vec<JsonValue>>> {
+        let mut reader = self
+            .reader
+            .take()
+            .context("Parquet file can't be read after an error")?;
+        let schema = self.schema.clone();
+        let (reader, batch) = spawn_blocking(move || {
+            let batch = read_batch(&schema, &mut reader);
+            (reader, batch)
+        })
+        .await?;
+        let batch = batch?;
+        self.reader = Some(reader);
+        Ok(batch)
+    }
+}
+
+fn read_batch(
+    schema: &SchemaRef,
+    reader: &mut ParquetRecordBatchReader,
+) -> anyhow::Result<Option<Vec<JsonValue>>> {
+    let Some(batch) = reader
+        .next()
+        .transpose()
+        .map_err(|e| ImportError::NotParquet(e.into()))?
+    else {
+        return Ok(None);
+    };
+    // Clean export JSON encodes int64s as strings, which can only be decoded
+    // with a generated schema. Other files get numbers instead.
+    let int64_as_string = schema
+        .metadata()
+        .contains_key(GENERATED_SCHEMA_METADATA_KEY);
+    let mut rows = vec![JsonMap::new(); batch.num_rows()];
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        let values = column_values(field, column, int64_as_string)?;
+        let is_document = field
+            .metadata()
+            .get(ENCODING_METADATA_KEY)
+            .is_some_and(|encoding| encoding == DOCUMENT_ENCODING);
+        for (row, value) in rows.iter_mut().zip(values) {
+            match value {
+                Some(JsonValue::Object(document)) if is_document => {
+                    for (field_name, value) in document {
+                        row.entry(field_name).or_insert(value);
+                    }
+                },
+                Some(value) => {
+                    row.insert(field.name().clone(), value);
+                },
+                None => {},
+            }
+        }
+    }
+    Ok(Some(rows.into_iter().map(JsonValue::Object).collect()))
+}
+
+fn column_values(
+    field: &Field,
+    column: &ArrayRef,
+    int64_as_string: bool,
+) -> anyhow::Result<Vec<Option<JsonValue>>> {
+    let is_json = field.metadata().contains_key(ENCODING_METADATA_KEY);
+    let values = match column.data_type() {
+        DataType::Utf8 if is_json => column
+            .as_string::<i32>()
+            .iter()
+            .map(|s| {
+                s.map(serde_json::from_str::<JsonValue>)
+                    .transpose()
+                    .map_err(|e| ImportError::ParquetInvalidJson(field.name().clone(), e))
+            })
+            .collect::<Result<_, _>>()?,
+        DataType::Utf8 => column
+            .as_string::<i32>()
+            .iter()
+            .map(|s| s.map(|s| JsonValue::String(s.to_string())))
+            .collect(),
+        DataType::LargeUtf8 => column
+            .as_string::<i64>()
+            .iter()
+            .map(|s| s.map(|s| JsonValue::String(s.to_string())))
+            .collect(),
+        DataType::Binary => column
+            .as_binary::<i32>()
+            .iter()
+            .map(|b| b.map(bytes_value).transpose())
+            .collect::<anyhow::Result<_>>()?,
+        DataType::LargeBinary => column
+            .as_binary::<i64>()
+            .iter()
+            .map(|b| b.map(bytes_value).transpose())
+            .collect::<anyhow::Result<_>>()?,
+        DataType::Boolean => column
+            .as_boolean()
+            .iter()
+            .map(|b| b.map(JsonValue::Bool))
+            .collect(),
+        DataType::Int64 if int64_as_string => {
+            primitive_values::<Int64Type>(column, ConvexValue::from)
+        },
+        DataType::Int64 => primitive_values::<Int64Type>(column, |i| (i as f64).into()),
+        DataType::Int32 => primitive_values::<Int32Type>(column, |i| (i as f64).into()),
+        DataType::Int16 => primitive_values::<Int16Type>(column, |i| (i as f64).into()),
+        DataType::Int8 => primitive_values::<Int8Type>(column, |i| (i as f64).into()),
+        DataType::UInt64 => primitive_values::<UInt64Type>(column, |i| (i as f64).into()),
+        DataType::UInt32 => primitive_values::<UInt32Type>(column, |i| (i as f64).into()),
+        DataType::UInt16 => primitive_values::<UInt16Type>(column, |i| (i as f64).into()),
+        DataType::UInt8 => primitive_values::<UInt8Type>(column, |i| (i as f64).into()),
+        DataType::Float64 => primitive_values::<Float64Type>(column, ConvexValue::from),
+        DataType::Float32 => primitive_values::<Float32Type>(column, |f| (f as f64).into()),
+        data_type => anyhow::bail!(ImportError::ParquetUnsupportedColumn(
+            field.name().clone(),
+            data_type.to_string()
+        )),
+    };
+    Ok(values)
+}
+
+fn primitive_values<T: ArrowPrimitiveType>(
+    column: &ArrayRef,
+    to_value: impl Fn(T::Native) -> ConvexValue,
+) -> Vec<Option<JsonValue>> {
+    column
+        .as_primitive::<T>()
+        .iter()
+        .map(|v| v.map(|v| to_value(v).export(ValueFormat::ConvexCleanJSON)))
+        .collect()
+}
+
+fn bytes_value(bytes: &[u8]) -> anyhow::Result<JsonValue> {
+    Ok(ConvexValue::try_from(bytes.to_vec())?.export(ValueFormat::ConvexCleanJSON))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{
+        Seek,
+        SeekFrom,
+        Write,
+    };
+
+    use common::{
+        document::{
+            CreationTime,
+            ResolvedDocument,
+        },
+        testing::TestIdGenerator,
+    };
+    use runtime::testing::TestRuntime;
+    use shape_inference::{
+        export_context::GeneratedSchema,
+        CountedShape,
+        ProdConfigWithOptionalFields,
+        ShapeEnum,
+        StructuralShape,
+    };
+    use value::{
+        assert_obj,
+        export::ValueFormat,
+    };
+
+    use super::{
+        ParquetTableReader,
+        ParquetTableWriter,
+    };
+
+    async fn roundtrip(
+        generated_schema: &GeneratedSchema<ProdConfigWithOptionalFields>,
+        docs: &[ResolvedDocument],
+    ) -> anyhow::Result<()> {
+        let mut writer = ParquetTableWriter::new(generated_schema)?;
+        let mut file = tempfile::tempfile()?;
+        for doc in docs {
+            if let Some(row_group) = writer.write(doc.clone())? {
+                file.write_all(&row_group)?;
+            }
+        }
+        file.write_all(&writer.complete()?)?;
+        file.seek(SeekFrom::Start(0))?;
+
+        let mut reader = ParquetTableReader::new(file).await?;
+        assert!(reader.generated_schema().is_some());
+        let mut rows = vec![];
+        while let Some(batch) = reader.next_batch().await? {
+            rows.extend(batch);
+        }
+        let expected: Vec<_> = docs
+            .iter()
+            .map(|doc| doc.clone().export(ValueFormat::ConvexCleanJSON))
+            .collect();
+        assert_eq!(rows, expected);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_parquet_roundtrip(_rt: TestRuntime) -> anyhow::Result<()> {
+        let mut id_generator = TestIdGenerator::new();
+        let table_name = "users".parse()?;
+        let docs = vec![
+            ResolvedDocument::new(
+                id_generator.user_generate(&table_name),
+                CreationTime::ONE,
+                assert_obj!(
+                    "name" => "alice",
+                    "age" => 30,
+                    "score" => 1.5,
+                    "avatar" => vec![1u8, 2, 3],
+                    "tags" => ["admin", 7],
+                ),
+            )?,
+            ResolvedDocument::new(
+                id_generator.user_generate(&table_name),
+                CreationTime::ONE,
+                assert_obj!(
+                    "name" => "bob",
+                    "age" => 31,
+                    "score" => f64::NAN,
+                    "avatar" => Vec::<u8>::new(),
+                    "tags" => [],
+                    "nickname" => "b",
+                ),
+            )?,
+        ];
+        let shape = docs.iter().fold(
+            CountedShape::<ProdConfigWithOptionalFields>::empty(),
+            |shape, doc| shape.insert(doc.value()),
+        );
+        assert!(matches!(shape.variant(), ShapeEnum::Object(_)));
+        roundtrip(&GeneratedSchema::new((&shape).into()), &docs).await?;
+
+        // Tables without a single object shape are written as JSON documents.
+        let unknown = GeneratedSchema::new(StructuralShape::new(ShapeEnum::Unknown));
+        roundtrip(&unknown, &docs).await?;
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_parquet_document_not_matching_shape(_rt: TestRuntime) -> anyhow::Result<()> {
+        let mut id_generator = TestIdGenerator::new();
+        let table_name = "users".parse()?;
+        let first = ResolvedDocument::new(
+            id_generator.user_generate(&table_name),
+            CreationTime::ONE,
+            assert_obj!("name" => "alice", "age" => 30),
+        )?;
+        let shape = CountedShape::<ProdConfigWithOptionalFields>::empty().insert(first.value());
+        // Documents written after the shape was inferred can have fields of
+        // another type, or fields that aren't in the shape at all.
+        let docs = vec![
+            first,
+            ResolvedDocument::new(
+                id_generator.user_generate(&table_name),
+                CreationTime::ONE,
+                assert_obj!("name" => "bob", "age" => "unknown"),
+            )?,
+            ResolvedDocument::new(
+                id_generator.user_generate(&table_name),
+                CreationTime::ONE,
+                assert_obj!("name" => "carol", "age" => 32, "nickname" => "c"),
+            )?,
+        ];
+        roundtrip(&GeneratedSchema::new((&shape).into()), &docs).await?;
+        Ok(())
+    }
+}
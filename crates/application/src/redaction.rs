@@ -298,7 +298,7 @@ pub mod tests {
         for part in http_response_parts {
             match part {
                 HttpActionResponsePart::BodyChunk(b) => body_bytes.extend(b),
-                HttpActionResponsePart::Head(_) => (),
+                HttpActionResponsePart::Head(_) | HttpActionResponsePart::WebSocketMessage(_) => (),
             }
         }
         let json = serde_json::from_slice(&body_bytes).unwrap();
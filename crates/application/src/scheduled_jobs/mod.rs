@@ -239,8 +239,10 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
 
     async fn run(&mut self, backoff: &mut Backoff) -> anyhow::Result<()> {
         tracing::info!("Starting scheduled job executor");
+        // The parallelism knob can change while we're running, in which case
+        // finished jobs may briefly wait for room in the channel.
         let (job_finished_tx, mut job_finished_rx) =
-            mpsc::channel(*SCHEDULED_JOB_EXECUTION_PARALLELISM);
+            mpsc::channel(SCHEDULED_JOB_EXECUTION_PARALLELISM.get());
         let mut running_job_ids = HashSet::new();
         // Some if there's at least one pending job. May be in the past!
         let mut next_job_ready_time = None;
@@ -258,7 +260,7 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
                 // If the backend is stopped we shouldn't poll. Our subscription will notify us
                 // when the backend is started again.
                 None
-            } else if running_job_ids.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
+            } else if running_job_ids.len() >= SCHEDULED_JOB_EXECUTION_PARALLELISM.get() {
                 // A scheduled job may have been added, but we can't do anything because we're
                 // still running jobs at our concurrency limit.
                 next_job_ready_time
@@ -344,7 +346,7 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
             // caught up, we can sleep until the timestamp. If we're behind and
             // at our concurrency limit, we can use the timestamp to log how far
             // behind we get.
-            if next_ts > now || running_job_ids.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
+            if next_ts > now || running_job_ids.len() >= SCHEDULED_JOB_EXECUTION_PARALLELISM.get() {
                 return Ok(Some(next_ts));
             }
 
@@ -823,7 +825,7 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
                 )],
                 order: Order::Asc,
             })
-            .limit(*SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE);
+            .limit(SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE.get());
             let mut query_stream =
                 ResolvedQuery::new(&mut tx, TableNamespace::by_component_TODO(), index_query)?;
 
@@ -844,8 +846,8 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
                         anyhow::bail!("Could not get completed_ts of finished scheduled job");
                     },
                 };
-                if completed_ts.add(*SCHEDULED_JOB_RETENTION)? > now {
-                    next_job_wait = Some(completed_ts.add(*SCHEDULED_JOB_RETENTION)? - now);
+                if completed_ts.add(SCHEDULED_JOB_RETENTION.get())? > now {
+                    next_job_wait = Some(completed_ts.add(SCHEDULED_JOB_RETENTION.get())? - now);
                     break;
                 }
                 jobs_to_delete.push(job.id());
@@ -1,11 +1,21 @@
-use std::time::Duration;
+use std::{
+    collections::BTreeMap,
+    time::Duration,
+};
 
 use common::{
     backoff::Backoff,
     bootstrap_model::schema::SchemaState,
     errors::report_error,
     runtime::Runtime,
-    schemas::DatabaseSchema,
+    schemas::{
+        DatabaseSchema,
+        SchemaValidationError,
+    },
+    types::{
+        IndexId,
+        RepeatableTimestamp,
+    },
 };
 use database::{
     Database,
@@ -26,6 +36,16 @@ use metrics::{
     log_document_validated,
     schema_validation_timer,
 };
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+    NamespacedTableMapping,
+    ResolvedDocumentId,
+    TableIdentifier,
+    TableNamespace,
+    TabletId,
+    TabletIdAndTableNumber,
+};
 
 use crate::metrics::log_worker_starting;
 
@@ -36,6 +56,9 @@ const MAX_BACKOFF: Duration = Duration::from_secs(5);
 const INITIAL_COMMIT_BACKOFF: Duration = Duration::from_millis(10);
 const MAX_COMMIT_BACKOFF: Duration = Duration::from_secs(2);
 const MAX_COMMIT_FAILURES: u32 = 3;
+/// How many referenced IDs are looked up at once when checking a new
+/// schema's references.
+const REFERENCE_CHECK_BATCH_SIZE: usize = 1000;
 
 pub struct SchemaWorker<RT: Runtime> {
     runtime: RT,
@@ -125,7 +148,7 @@ impl<RT: Runtime> SchemaWorker<RT> {
         {
             let tables_to_check = DatabaseSchema::tables_to_validate(
                 &db_schema,
-                active_schema,
+                active_schema.clone(),
                 &table_mapping,
                 &virtual_table_mapping,
                 &|table_name| snapshot.table_summary(table_name).inferred_type().clone(),
@@ -153,38 +176,28 @@ impl<RT: Runtime> SchemaWorker<RT> {
                         &table_mapping,
                         &virtual_table_mapping,
                     ) {
-                        let mut backoff = Backoff::new(INITIAL_COMMIT_BACKOFF, MAX_COMMIT_BACKOFF);
-                        while backoff.failures() < MAX_COMMIT_FAILURES {
-                            let mut tx = self.database.begin(Identity::system()).await?;
-                            SchemaModel::new(&mut tx, namespace)
-                                .mark_failed(id, schema_error.clone())
-                                .await?;
-                            if let Err(e) = self
-                                .database
-                                .commit_with_write_source(tx, "schema_worker_mark_failed")
-                                .await
-                            {
-                                if e.is_occ() {
-                                    let delay = self.runtime.with_rng(|rng| backoff.fail(rng));
-                                    tracing::error!(
-                                        "Schema worker failed to commit ({e}), retrying after \
-                                         {delay:?}"
-                                    );
-                                    self.runtime.wait(delay).await;
-                                } else {
-                                    return Err(e);
-                                }
-                            } else {
-                                break;
-                            }
-                        }
-
+                        self.mark_failed(namespace, id, schema_error).await?;
                         tracing::info!("Schema is invalid");
                         timer.finish_developer_error();
                         return Ok(());
                     }
                 }
             }
+            if let Some(schema_error) = self
+                .find_dangling_reference(
+                    &db_schema,
+                    active_schema.as_ref(),
+                    &table_mapping,
+                    &by_id_indexes,
+                    ts,
+                )
+                .await?
+            {
+                self.mark_failed(namespace, id, schema_error).await?;
+                tracing::info!("Schema is invalid");
+                timer.finish_developer_error();
+                return Ok(());
+            }
             let mut tx = self.database.begin(Identity::system()).await?;
             if let Err(error) = SchemaModel::new(&mut tx, namespace)
                 .mark_validated(id)
@@ -209,6 +222,160 @@ impl<RT: Runtime> SchemaWorker<RT> {
         subscription.wait_for_invalidation().await;
         Ok(())
     }
+
+    async fn mark_failed(
+        &self,
+        namespace: TableNamespace,
+        id: ResolvedDocumentId,
+        schema_error: SchemaValidationError,
+    ) -> anyhow::Result<()> {
+        let mut backoff = Backoff::new(INITIAL_COMMIT_BACKOFF, MAX_COMMIT_BACKOFF);
+        while backoff.failures() < MAX_COMMIT_FAILURES {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            SchemaModel::new(&mut tx, namespace)
+                .mark_failed(id, schema_error.clone())
+                .await?;
+            if let Err(e) = self
+                .database
+                .commit_with_write_source(tx, "schema_worker_mark_failed")
+                .await
+            {
+                if e.is_occ() {
+                    let delay = self.runtime.with_rng(|rng| backoff.fail(rng));
+                    tracing::error!(
+                        "Schema worker failed to commit ({e}), retrying after {delay:?}"
+                    );
+                    self.runtime.wait(delay).await;
+                } else {
+                    return Err(e);
+                }
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the existing documents against the references `db_schema` adds
+    /// to the active schema, returning the first document that references a
+    /// missing one.
+    async fn find_dangling_reference(
+        &self,
+        db_schema: &DatabaseSchema,
+        active_schema: Option<&DatabaseSchema>,
+        table_mapping: &NamespacedTableMapping,
+        by_id_indexes: &BTreeMap<TabletId, IndexId>,
+        ts: RepeatableTimestamp,
+    ) -> anyhow::Result<Option<SchemaValidationError>> {
+        if !db_schema.schema_validation {
+            return Ok(None);
+        }
+        for (table_name, definition) in &db_schema.tables {
+            for reference in definition.references.values() {
+                let already_enforced = active_schema
+                    .filter(|active_schema| active_schema.schema_validation)
+                    .and_then(|active_schema| active_schema.tables.get(table_name))
+                    .and_then(|active| active.references.get(&reference.field))
+                    .is_some_and(|active| active.referenced_table == reference.referenced_table);
+                if already_enforced {
+                    continue;
+                }
+                let Some(table_id) = table_mapping.id_and_number_if_exists(table_name) else {
+                    continue;
+                };
+
+                let referenced_table_id =
+                    table_mapping.id_and_number_if_exists(&reference.referenced_table);
+
+                // Look up the referenced IDs a batch at a time, so memory doesn't grow
+                // with the size of the table.
+                let mut batch = vec![];
+                let stream = self
+                    .database
+                    .table_iterator(ts, 1000, None)
+                    .stream_documents_in_table(
+                        table_id.tablet_id,
+                        by_id_index(by_id_indexes, table_id.tablet_id)?,
+                        None,
+                    );
+                pin_mut!(stream);
+                loop {
+                    let doc = stream.try_next().await?;
+                    if let Some((doc, _ts)) = &doc
+                        && let Some(ConvexValue::String(value)) = doc.value().get(&*reference.field)
+                        && let Ok(referenced_id) = DeveloperDocumentId::decode(value)
+                    {
+                        batch.push((doc.developer_id(), referenced_id));
+                    }
+                    if batch.len() < REFERENCE_CHECK_BATCH_SIZE && doc.is_some() {
+                        continue;
+                    }
+                    let dangling = self
+                        .find_missing_reference(referenced_table_id, ts, &batch)
+                        .await?;
+                    if let Some((id, referenced_id)) = dangling {
+                        return Ok(Some(SchemaValidationError::DanglingReference {
+                            table_name: table_name.clone(),
+                            id,
+                            field: reference.field.clone(),
+                            referenced_table: reference.referenced_table.clone(),
+                            referenced_id,
+                        }));
+                    }
+                    batch.clear();
+                    if doc.is_none() {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the first `(id, referenced_id)` pair in `batch` whose
+    /// referenced document doesn't exist in `referenced_table_id` at `ts`.
+    async fn find_missing_reference(
+        &self,
+        referenced_table_id: Option<TabletIdAndTableNumber>,
+        ts: RepeatableTimestamp,
+        batch: &[(DeveloperDocumentId, DeveloperDocumentId)],
+    ) -> anyhow::Result<Option<(DeveloperDocumentId, DeveloperDocumentId)>> {
+        let Some(referenced_table_id) = referenced_table_id else {
+            return Ok(batch.first().copied());
+        };
+        let to_internal_id = |referenced_id: &DeveloperDocumentId| {
+            (*referenced_id.table() == referenced_table_id.table_number).then(|| {
+                referenced_table_id
+                    .tablet_id
+                    .id(referenced_id.internal_id())
+            })
+        };
+        let existing = self
+            .database
+            .documents_exist_at(
+                ts,
+                batch
+                    .iter()
+                    .filter_map(|(_, referenced_id)| to_internal_id(referenced_id)),
+            )
+            .await?;
+        Ok(batch
+            .iter()
+            .find(|(_, referenced_id)| {
+                !to_internal_id(referenced_id).is_some_and(|id| existing.contains(&id))
+            })
+            .copied())
+    }
+}
+
+fn by_id_index(
+    by_id_indexes: &BTreeMap<TabletId, IndexId>,
+    tablet_id: TabletId,
+) -> anyhow::Result<IndexId> {
+    by_id_indexes
+        .get(&tablet_id)
+        .copied()
+        .ok_or_else(|| anyhow::anyhow!("Failed to find id index for table id {tablet_id}"))
 }
 
 #[cfg(test)]
@@ -258,6 +425,8 @@ mod tests {
             indexes: btreemap! {},
             search_indexes: btreemap! {},
             vector_indexes: btreemap! {},
+            aggregate_indexes: btreemap! {},
+            references: btreemap! {},
             document_type: Some(DocumentSchema::Any),
         };
         let db_schema = DatabaseSchema {
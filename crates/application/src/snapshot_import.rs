@@ -4,6 +4,12 @@ use std::{
         BTreeSet,
         HashSet,
     },
+    fs::File,
+    io::{
+        Seek,
+        SeekFrom,
+        Write,
+    },
     pin::Pin,
     str::FromStr,
     sync::{
@@ -47,9 +53,16 @@ use common::{
     },
     pause::PauseClient,
     runtime::Runtime,
-    schemas::DatabaseSchema,
+    schemas::{
+        validator::{
+            LiteralValidator,
+            Validator,
+        },
+        DatabaseSchema,
+        DocumentSchema,
+    },
+    tokio::task::spawn_blocking,
     types::{
-        FieldName,
         MemberId,
         ObjectKey,
         TableName,
@@ -98,7 +111,10 @@ use humansize::{
     FormatSize,
     BINARY,
 };
-use keybroker::Identity;
+use keybroker::{
+    AdminPermission,
+    Identity,
+};
 use model::{
     deployment_audit_log::{
         types::DeploymentAuditLogEvent,
@@ -111,6 +127,8 @@ use model::{
     },
     snapshot_imports::{
         types::{
+            CsvColumnType,
+            CsvImportOptions,
             ImportFormat,
             ImportMode,
             ImportState,
@@ -120,10 +138,7 @@ use model::{
     },
 };
 use regex::Regex;
-use serde_json::{
-    json,
-    Value as JsonValue,
-};
+use serde_json::Value as JsonValue;
 use shape_inference::{
     export_context::{
         ExportContext,
@@ -168,17 +183,25 @@ use value::{
 };
 
 use crate::{
-    export_worker::FileStorageZipMetadata,
+    csv_format::CsvRowParser,
+    export_worker::{
+        FileStorageZipMetadata,
+        IncrementalExportManifest,
+    },
     metrics::{
         log_snapshot_import_age,
         log_worker_starting,
         snapshot_import_timer,
     },
+    parquet_format::ParquetTableReader,
     Application,
 };
 
-static IMPORT_SIZE_LIMIT: LazyLock<String> =
-    LazyLock::new(|| (*TRANSACTION_MAX_USER_WRITE_SIZE_BYTES.format_size(BINARY)).to_string());
+fn import_size_limit() -> String {
+    TRANSACTION_MAX_USER_WRITE_SIZE_BYTES
+        .get()
+        .format_size(BINARY)
+}
 
 const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
 const MAX_BACKOFF: Duration = Duration::from_secs(60);
@@ -359,6 +382,8 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
         let (_, mut objects) = self.parse_import(snapshot_import.id()).await?;
         // Find all tables being written to.
         let mut count_by_table: BTreeMap<TableName, u64> = BTreeMap::new();
+        let mut deleted_by_increment: BTreeMap<TableName, usize> = BTreeMap::new();
+        let mut manifest = None;
         let mut current_table = None;
         let mut lineno = 0;
         while let Some(object) = objects.try_next().await? {
@@ -368,7 +393,8 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
                     count_by_table.entry(table_name.clone()).or_default();
                     current_table = Some(table_name);
                 },
-                ImportUnit::Object(exported_value) => {
+                ImportUnit::Object(exported_value)
+                | ImportUnit::ObjectWithContext(exported_value, _) => {
                     lineno += 1;
                     let Some(current_table) = &current_table else {
                         continue;
@@ -395,6 +421,18 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
                         *count += 1;
                     }
                 },
+                ImportUnit::Increment(table_name, deleted_ids) => {
+                    if mode != ImportMode::Replace {
+                        anyhow::bail!(ImportError::IncrementalImportRequiresReplace);
+                    }
+                    deleted_by_increment.insert(table_name, deleted_ids.len());
+                },
+                ImportUnit::Manifest(incremental_manifest) => {
+                    if mode != ImportMode::Replace {
+                        anyhow::bail!(ImportError::IncrementalImportRequiresReplace);
+                    }
+                    manifest = Some(incremental_manifest);
+                },
                 // Ignore storage file chunks and generated schemas.
                 ImportUnit::StorageFileChunk(..) | ImportUnit::GeneratedSchema(..) => {},
             }
@@ -412,9 +450,11 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
             if !table_name.is_system() {
                 let table_summary = db_snapshot.table_summary(table_name);
                 let to_delete = match mode {
-                    ImportMode::Replace => {
+                    ImportMode::Replace => match deleted_by_increment.get(table_name) {
+                        // An increment only deletes the documents it lists.
+                        Some(deleted) => (*deleted).min(table_summary.num_values()),
                         // Overwriting nonempty user table.
-                        table_summary.num_values()
+                        None => table_summary.num_values(),
                     },
                     ImportMode::Append => 0,
                     ImportMode::RequireEmpty if table_summary.num_values() > 0 => {
@@ -456,7 +496,30 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
                 );
             }
         }
-        let mut require_manual_confirmation = false;
+        if let Some(manifest) = &manifest {
+            // Tables that were deleted since the base export are deleted.
+            for (_, namespace, _, table_name) in
+                db_snapshot.table_registry.iter_active_user_tables()
+            {
+                if namespace == TableNamespace::by_component_TODO()
+                    && !manifest.tables.contains(&table_name.to_string())
+                {
+                    let existing = db_snapshot.table_summary(table_name).num_values();
+                    table_changes.insert(
+                        table_name.clone(),
+                        TableChange {
+                            added: 0,
+                            deleted: existing,
+                            existing,
+                            unit: "",
+                        },
+                    );
+                }
+            }
+        }
+        // The import is only correct on top of the export it's based on, which
+        // we can't check, so always confirm increments.
+        let mut require_manual_confirmation = manifest.is_some();
 
         // Looks like:
         /*
@@ -527,6 +590,13 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
                 ));
             }
         }
+        if let Some(manifest) = manifest {
+            message_lines.push(format!(
+                "This is an incremental export of the changes from {} to {}. Only import it on \
+                 top of the export from {}.",
+                manifest.base_ts, manifest.snapshot_ts, manifest.base_ts
+            ));
+        }
         Ok((message_lines, require_manual_confirmation))
     }
 
@@ -623,7 +693,7 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
 
         let usage = FunctionUsageTracker::new();
 
-        let (table_mapping_for_import, total_documents_imported) = import_objects(
+        let (table_mapping_for_import, total_documents_imported, manifest) = import_objects(
             &self.database,
             &self.file_storage,
             Identity::system(),
@@ -657,6 +727,7 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
             snapshot_import.member_id,
             initial_schemas,
             table_mapping_for_import,
+            manifest.as_ref(),
             usage,
             DeploymentAuditLogEvent::SnapshotImport {
                 table_names,
@@ -701,7 +772,8 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
         let initial_schemas = schemas_for_import(&mut tx).await?;
 
         let objects = match format {
-            ImportFormat::Csv(table_name) => {
+            ImportFormat::Csv(table_name, options) => {
+                check_csv_column_types(&table_name, &options, &mut tx).await?;
                 remap_empty_string_by_schema(table_name, &mut tx, objects).await?
             },
             _ => objects,
@@ -733,7 +805,11 @@ pub enum ImportError {
     #[error("Import wasn't valid UTF8: {0}")]
     NotUtf8(std::io::Error),
 
-    #[error("Import is too large for JSON ({0} bytes > maximum {}). Consider converting data to JSONLines", *IMPORT_SIZE_LIMIT)]
+    #[error(
+        "Import is too large for JSON ({0} bytes > maximum {}). Consider converting data to \
+         JSONLines",
+        import_size_limit()
+    )]
     JsonArrayTooLarge(usize),
 
     #[error("CSV file doesn't have headers")]
@@ -748,6 +824,22 @@ pub enum ImportError {
     #[error("CSV row {0} doesn't have all of the fields in the header")]
     CsvRowMissingFields(usize),
 
+    #[error("CSV row {0} has an invalid value in column {1:?}: {2:#}")]
+    CsvInvalidCell(usize, String, anyhow::Error),
+
+    #[error(
+        "CSV headers {0:?} and {1:?} can't both be imported, since one is nested in the other"
+    )]
+    CsvConflictingHeaders(String, String),
+
+    #[error("CSV column type given for {0:?}, which isn't in the CSV header")]
+    CsvUnknownColumn(String),
+
+    #[error(
+        "CSV column {0:?} can't be imported as {1}, since the schema for table {2} expects {3}"
+    )]
+    CsvColumnTypeMismatch(String, CsvColumnType, TableName, String),
+
     #[error("Row {0} wasn't valid JSON: {1}")]
     JsonInvalidRow(usize, serde_json::Error),
 
@@ -762,6 +854,24 @@ pub enum ImportError {
 
     #[error("Not valid JSON: {0}")]
     NotJson(serde_json::Error),
+
+    #[error("Not a valid Parquet file: {0}")]
+    NotParquet(anyhow::Error),
+
+    #[error("Parquet column {0:?} has unsupported type {1}")]
+    ParquetUnsupportedColumn(String, String),
+
+    #[error("Parquet column {0:?} contains invalid JSON: {1}")]
+    ParquetInvalidJson(String, serde_json::Error),
+
+    #[error("Incremental exports can only be imported with --replace")]
+    IncrementalImportRequiresReplace,
+
+    #[error(
+        "Table {0} doesn't exist, so the incremental export's changes to it can't be applied. \
+         Import the export it's based on first."
+    )]
+    IncrementMissingBase(TableName),
 }
 
 impl ImportError {
@@ -778,15 +888,31 @@ impl ImportError {
 #[derive(Debug)]
 enum ImportUnit {
     Object(JsonValue),
+    /// An object from a format that carries its own export context, e.g. a
+    /// CSV row with non-finite floats, since generated schema overrides are
+    /// keyed by `_id`.
+    ObjectWithContext(JsonValue, ExportContext),
     NewTable(TableName),
     GeneratedSchema(TableName, GeneratedSchema<ProdConfigWithOptionalFields>),
     StorageFileChunk(DeveloperDocumentId, Bytes),
+    /// The table's objects are changes from an incremental export, applied
+    /// on top of the existing table. The existing documents that aren't
+    /// imported or deleted are kept.
+    Increment(TableName, BTreeSet<DeveloperDocumentId>),
+    /// The import is an incremental export, described by its manifest.
+    Manifest(IncrementalExportManifest),
 }
 
 static GENERATED_SCHEMA_PATTERN: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?:.*/)?([^/]+)/generated_schema\.jsonl$").unwrap());
 static DOCUMENTS_PATTERN: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?:.*/)?([^/]+)/documents\.jsonl$").unwrap());
+static DOCUMENTS_PARQUET_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:.*/)?([^/]+)/documents\.parquet$").unwrap());
+static DELETED_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:.*/)?([^/]+)/deleted\.jsonl$").unwrap());
+static MANIFEST_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:.*/)?_manifest\.json$").unwrap());
 // _storage/(ID) with optional ignored prefix and extension like
 // snapshot/_storage/(ID).png
 static STORAGE_FILE_PATTERN: LazyLock<Regex> =
@@ -813,41 +939,30 @@ where
     Fut: Future<Output = anyhow::Result<StorageObjectReader>> + 'a,
 {
     match format {
-        ImportFormat::Csv(table_name) => {
+        ImportFormat::Csv(table_name, options) => {
             let reader = stream_body().await?;
-            yield ImportUnit::NewTable(table_name);
-            let mut reader = csv_async::AsyncReader::from_reader(reader);
+            let mut reader = csv_async::AsyncReaderBuilder::new()
+                .delimiter(u8::try_from(options.delimiter)?)
+                .quote(u8::try_from(options.quote)?)
+                .create_reader(reader);
             if !reader.has_headers() {
                 anyhow::bail!(ImportError::CsvMissingHeaders);
             }
-            let field_names = {
-                let headers = reader.headers().await?;
-                headers
-                    .iter()
-                    .map(|s| {
-                        let trimmed = s.trim_matches(' ');
-                        let field_name = FieldName::from_str(trimmed)
-                            .map_err(|e| ImportError::CsvInvalidHeader(trimmed.to_string(), e))?;
-                        Ok(field_name)
-                    })
-                    .collect::<anyhow::Result<Vec<_>>>()?
-            };
+            let parser = CsvRowParser::new(reader.headers().await?.iter(), &options)?;
+            if let Some(generated_schema) = parser.generated_schema()? {
+                yield ImportUnit::GeneratedSchema(table_name.clone(), generated_schema);
+            }
+            yield ImportUnit::NewTable(table_name);
             let mut enumerate_rows = reader.records().enumerate();
             while let Some((i, row_r)) = enumerate_rows.next().await {
                 let lineno = i + 1;
-                let parsed_row = row_r
-                    .map_err(|e| ImportError::CsvInvalidRow(lineno, e))?
-                    .iter()
-                    .map(parse_csv_cell)
-                    .collect::<Vec<JsonValue>>();
-                let mut obj = BTreeMap::new();
-                if field_names.len() != parsed_row.len() {
-                    anyhow::bail!(ImportError::CsvRowMissingFields(lineno));
-                }
-                for (field_name, value) in field_names.iter().zip(parsed_row.into_iter()) {
-                    obj.insert(field_name.to_string(), value);
+                let row = row_r.map_err(|e| ImportError::CsvInvalidRow(lineno, e))?;
+                let (object, export_context) = parser.parse_row(lineno, row.iter())?;
+                if export_context.is_infer() {
+                    yield ImportUnit::Object(object);
+                } else {
+                    yield ImportUnit::ObjectWithContext(object, export_context);
                 }
-                yield ImportUnit::Object(serde_json::to_value(obj)?);
             }
         },
         ImportFormat::JsonLines(table_name) => {
@@ -874,9 +989,9 @@ where
             yield ImportUnit::NewTable(table_name);
             let mut buf = Vec::new();
             let mut truncated_reader =
-                reader.take((*TRANSACTION_MAX_USER_WRITE_SIZE_BYTES as u64) + 1);
+                reader.take((TRANSACTION_MAX_USER_WRITE_SIZE_BYTES.get() as u64) + 1);
             truncated_reader.read_to_end(&mut buf).await?;
-            if buf.len() > *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES {
+            if buf.len() > TRANSACTION_MAX_USER_WRITE_SIZE_BYTES.get() {
                 anyhow::bail!(ImportError::JsonArrayTooLarge(buf.len()));
             }
             let v: serde_json::Value =
@@ -886,6 +1001,26 @@ where
                 yield ImportUnit::Object(value.clone());
             }
         },
+        ImportFormat::Parquet(table_name) => {
+            let reader = stream_body().await?;
+            let mut parquet_reader = ParquetTableReader::new(spool_to_file(reader).await?).await?;
+            // Parquet exports carry their generated schema, which we need to
+            // decode the clean export JSON values exactly.
+            if let Some(generated_schema) = parquet_reader.generated_schema() {
+                let generated_schema = parse_generated_schema(
+                    &format!("{table_name}/documents.parquet"),
+                    BufReader::new(generated_schema.as_bytes()),
+                )
+                .await?;
+                yield ImportUnit::GeneratedSchema(table_name.clone(), generated_schema);
+            }
+            yield ImportUnit::NewTable(table_name);
+            while let Some(objects) = parquet_reader.next_batch().await? {
+                for object in objects {
+                    yield ImportUnit::Object(object);
+                }
+            }
+        },
         ImportFormat::Zip => {
             let mut reader = stream_body().await?.compat();
             let mut zip_reader = ZipFileReader::new(&mut reader)
@@ -906,9 +1041,13 @@ where
                 // Each generated schema must be parsed before the corresponding
                 // table/documents.jsonl file, so we correctly infer types from
                 // export-formatted JsonValues.
+                // d. _manifest.json and user_table/deleted.jsonl, for
+                //    incremental exports.
                 let mut table_metadata = vec![];
                 let mut storage_metadata = vec![];
                 let mut generated_schemas = vec![];
+                let mut manifest = None;
+                let mut deleted_ids = BTreeMap::new();
                 for (i, filename) in filenames.iter().enumerate() {
                     let documents_table_name = parse_documents_jsonl_table_name(filename)?;
                     if let Some(table_name) = &documents_table_name
@@ -947,8 +1086,24 @@ where
                             parse_generated_schema(filename, entry_reader).await?;
                         generated_schemas
                             .push(ImportUnit::GeneratedSchema(table_name, generated_schema));
+                    } else if MANIFEST_PATTERN.is_match(filename) {
+                        let entry_reader =
+                            zip_reader.entry_reader(i).await.map_err(map_zip_error)?;
+                        let mut buf = Vec::new();
+                        entry_reader.compat().read_to_end(&mut buf).await?;
+                        let parsed_manifest: IncrementalExportManifest =
+                            serde_json::from_slice(&buf).map_err(ImportError::NotJson)?;
+                        manifest = Some(parsed_manifest);
+                    } else if let Some(table_name) = parse_table_name(&DELETED_PATTERN, filename)? {
+                        let entry_reader =
+                            zip_reader.entry_reader(i).await.map_err(map_zip_error)?;
+                        let entry_reader = BufReader::new(entry_reader.compat());
+                        deleted_ids.insert(table_name, parse_deleted_ids(entry_reader).await?);
                     }
                 }
+                if let Some(manifest) = &manifest {
+                    yield ImportUnit::Manifest(manifest.clone());
+                }
                 for table_unit in table_metadata {
                     yield table_unit;
                 }
@@ -998,6 +1153,23 @@ where
                 for generated_schema_unit in generated_schemas {
                     yield generated_schema_unit;
                 }
+                if let Some(manifest) = manifest {
+                    // Tables that aren't included in full are changes on top
+                    // of the existing tables.
+                    for filename in filenames.iter() {
+                        let table_name = match parse_documents_jsonl_table_name(filename)? {
+                            Some(table_name) => Some(table_name),
+                            None => parse_table_name(&DOCUMENTS_PARQUET_PATTERN, filename)?,
+                        };
+                        if let Some(table_name) = table_name
+                            && !table_name.is_system()
+                            && !manifest.full_tables.contains(&table_name.to_string())
+                        {
+                            let deleted = deleted_ids.remove(&table_name).unwrap_or_default();
+                            yield ImportUnit::Increment(table_name, deleted);
+                        }
+                    }
+                }
             }
 
             // Second pass: user tables.
@@ -1011,6 +1183,21 @@ where
                     while let Some(unit) = stream.try_next().await? {
                         yield unit;
                     }
+                } else if let Some(table_name) =
+                    parse_table_name(&DOCUMENTS_PARQUET_PATTERN, filename)?
+                    && !table_name.is_system()
+                {
+                    tracing::info!("importing zip file containing parquet table {table_name}");
+                    let entry_reader = zip_reader.entry_reader(i).await.map_err(map_zip_error)?;
+                    let mut parquet_reader =
+                        ParquetTableReader::new(spool_to_file(entry_reader.compat()).await?)
+                            .await?;
+                    yield ImportUnit::NewTable(table_name);
+                    while let Some(objects) = parquet_reader.next_batch().await? {
+                        for object in objects {
+                            yield ImportUnit::Object(object);
+                        }
+                    }
                 }
             }
         },
@@ -1018,7 +1205,11 @@ where
 }
 
 fn parse_documents_jsonl_table_name(filename: &str) -> anyhow::Result<Option<TableName>> {
-    DOCUMENTS_PATTERN
+    parse_table_name(&DOCUMENTS_PATTERN, filename)
+}
+
+fn parse_table_name(pattern: &Regex, filename: &str) -> anyhow::Result<Option<TableName>> {
+    pattern
         .captures(filename)
         .map(|captures| {
             let table_name_str = captures
@@ -1054,6 +1245,40 @@ async fn parse_documents_jsonl<R: TokioAsyncRead + Unpin>(entry_reader: ZipEntry
     }
 }
 
+/// Parses the IDs in a deleted.jsonl file from an incremental export.
+async fn parse_deleted_ids<R: AsyncRead + Unpin>(
+    mut entry_reader: BufReader<R>,
+) -> anyhow::Result<BTreeSet<DeveloperDocumentId>> {
+    let mut deleted_ids = BTreeSet::new();
+    let mut line = String::new();
+    let mut lineno = 1;
+    while entry_reader
+        .read_line(&mut line)
+        .await
+        .map_err(ImportError::NotUtf8)?
+        > 0
+    {
+        let v: serde_json::Value =
+            serde_json::from_str(&line).map_err(|e| ImportError::JsonInvalidRow(lineno, e))?;
+        let id = v
+            .get(&**ID_FIELD)
+            .and_then(|id| id.as_str())
+            .with_context(|| {
+                ImportError::InvalidConvexValue(
+                    lineno,
+                    anyhow::anyhow!("deleted document requires _id"),
+                )
+            })?;
+        deleted_ids.insert(
+            DeveloperDocumentId::decode(id)
+                .map_err(|e| ImportError::InvalidConvexValue(lineno, e.into()))?,
+        );
+        line.clear();
+        lineno += 1;
+    }
+    Ok(deleted_ids)
+}
+
 async fn parse_generated_schema<'a, T: ShapeConfig, R: AsyncRead + Unpin>(
     filename: &str,
     mut entry_reader: BufReader<R>,
@@ -1114,12 +1339,26 @@ async fn parse_generated_schema<'a, T: ShapeConfig, R: AsyncRead + Unpin>(
     Ok(generated_schema)
 }
 
-// For now, we only parse out floats and strings in CSV files.
-fn parse_csv_cell(s: &str) -> JsonValue {
-    if let Ok(r) = s.parse::<f64>() {
-        return json!(r);
+/// Parquet readers need random access to the file, so copy it somewhere
+/// seekable instead of buffering all of it in memory. File writes block, so
+/// they happen on the blocking thread pool.
+async fn spool_to_file<R: AsyncRead + Unpin>(mut reader: R) -> anyhow::Result<File> {
+    let mut file = spawn_blocking(tempfile::tempfile).await??;
+    let mut buf = vec![0u8; 1 << 20];
+    while let bytes_read = reader.read(&mut buf).await?
+        && bytes_read > 0
+    {
+        (file, buf) = spawn_blocking(move || {
+            file.write_all(&buf[..bytes_read])?;
+            anyhow::Ok((file, buf))
+        })
+        .await??;
     }
-    json!(s)
+    spawn_blocking(move || {
+        file.seek(SeekFrom::Start(0))?;
+        anyhow::Ok(file)
+    })
+    .await?
 }
 
 pub async fn upload_import_file<RT: Runtime>(
@@ -1132,6 +1371,7 @@ pub async fn upload_import_file<RT: Runtime>(
     if !identity.is_admin() {
         anyhow::bail!(ImportError::Unauthorized);
     }
+    check_import_scope(&identity, &format)?;
     let object_key = application.upload_snapshot_import(body_stream).await?;
     store_uploaded_import(application, identity, format, mode, object_key).await
 }
@@ -1143,6 +1383,7 @@ pub async fn store_uploaded_import<RT: Runtime>(
     mode: ImportMode,
     object_key: ObjectKey,
 ) -> anyhow::Result<DeveloperDocumentId> {
+    check_import_scope(&identity, &format)?;
     let (_, id, _) = application
         .database
         .execute_with_overloaded_retries(
@@ -1164,6 +1405,21 @@ pub async fn store_uploaded_import<RT: Runtime>(
     Ok(id.into())
 }
 
+/// Checks that the admin key behind `identity`, if any, may write every table
+/// an import in `format` can write to. Imports write to the root component.
+fn check_import_scope(identity: &Identity, format: &ImportFormat) -> anyhow::Result<()> {
+    identity.check_admin_permission(AdminPermission::WriteData)?;
+    identity.check_admin_component("")?;
+    match format {
+        ImportFormat::Csv(table_name, _)
+        | ImportFormat::JsonLines(table_name)
+        | ImportFormat::JsonArray(table_name)
+        | ImportFormat::Parquet(table_name) => identity.check_admin_table(table_name),
+        // ZIP imports can write to any table and to file storage.
+        ImportFormat::Zip => identity.check_admin_all_tables(),
+    }
+}
+
 pub async fn perform_import<RT: Runtime>(
     application: &Application<RT>,
     identity: Identity,
@@ -1175,7 +1431,7 @@ pub async fn perform_import<RT: Runtime>(
     application
         .database
         .execute_with_overloaded_retries(
-            identity,
+            identity.clone(),
             FunctionUsageTracker::new(),
             PauseClient::new(),
             "snapshot_import_perform",
@@ -1187,6 +1443,10 @@ pub async fn perform_import<RT: Runtime>(
                             .inject_table_id(),
                     )?;
                     let mut import_model = SnapshotImportModel::new(tx);
+                    // The import may have been uploaded with a different key.
+                    if let Some(snapshot_import) = import_model.get(import_id).await? {
+                        check_import_scope(&identity, &snapshot_import.format)?;
+                    }
                     import_model.confirm_import(import_id).await?;
                     Ok(())
                 }
@@ -1307,7 +1567,7 @@ pub async fn clear_tables<RT: Runtime>(
     .boxed()
     .peekable();
 
-    let (table_mapping_for_import, _) = import_objects(
+    let (table_mapping_for_import, ..) = import_objects(
         &application.database,
         &application.file_storage,
         identity.clone(),
@@ -1325,6 +1585,7 @@ pub async fn clear_tables<RT: Runtime>(
         None,
         initial_schemas,
         table_mapping_for_import,
+        None,
         usage,
         DeploymentAuditLogEvent::ClearTables,
     )
@@ -1385,9 +1646,11 @@ async fn import_objects<RT: Runtime>(
     objects: Peekable<BoxStream<'_, anyhow::Result<ImportUnit>>>,
     usage: FunctionUsageTracker,
     import_id: Option<ResolvedDocumentId>,
-) -> anyhow::Result<(TableMapping, usize)> {
+) -> anyhow::Result<(TableMapping, usize, Option<IncrementalExportManifest>)> {
     pin_mut!(objects);
     let mut generated_schemas = BTreeMap::new();
+    let mut increments = BTreeMap::new();
+    let mut manifest = None;
 
     let mut table_mapping_for_import = TableMapping::new();
     let mut total_num_documents = 0;
@@ -1399,6 +1662,8 @@ async fn import_objects<RT: Runtime>(
         mode,
         objects.as_mut(),
         &mut generated_schemas,
+        &mut increments,
+        &mut manifest,
         &mut table_mapping_for_import,
         usage.clone(),
         import_id,
@@ -1407,7 +1672,7 @@ async fn import_objects<RT: Runtime>(
     {
         total_num_documents += num_documents;
     }
-    Ok((table_mapping_for_import, total_num_documents))
+    Ok((table_mapping_for_import, total_num_documents, manifest))
 }
 
 /// The case where a schema can become invalid:
@@ -1538,6 +1803,7 @@ async fn finalize_import<RT: Runtime>(
     member_id_override: Option<MemberId>,
     initial_schemas: Vec<Option<(ResolvedDocumentId, DatabaseSchema)>>,
     table_mapping_for_import: TableMapping,
+    manifest: Option<&IncrementalExportManifest>,
     usage: FunctionUsageTracker,
     audit_log_event: DeploymentAuditLogEvent,
 ) -> anyhow::Result<(Timestamp, u64)> {
@@ -1563,7 +1829,29 @@ async fn finalize_import<RT: Runtime>(
                 async {
                     let mut documents_deleted = 0;
                     schema_constraints.validate(tx).await?;
+                    // Incremental imports delete the tables that were deleted
+                    // since the base export.
+                    let deleted_tables: Vec<_> = match manifest {
+                        Some(manifest) => tx
+                            .table_mapping()
+                            .iter_active_user_tables()
+                            .filter(|(_, namespace, _, table_name)| {
+                                *namespace == TableNamespace::by_component_TODO()
+                                    && !manifest.tables.contains(&table_name.to_string())
+                            })
+                            .map(|(_, _, _, table_name)| table_name.clone())
+                            .collect(),
+                        None => vec![],
+                    };
                     let mut table_model = TableModel::new(tx);
+                    for table_name in deleted_tables {
+                        documents_deleted += table_model
+                            .count(TableNamespace::by_component_TODO(), &table_name)
+                            .await?;
+                        table_model
+                            .delete_table(TableNamespace::by_component_TODO(), table_name)
+                            .await?;
+                    }
                     for (table_id, _, table_number, table_name) in table_mapping_for_import.iter() {
                         documents_deleted += table_model
                             .activate_table(table_id, table_name, table_number, &tables_in_import)
@@ -1912,16 +2200,47 @@ async fn import_single_table<RT: Runtime>(
     mode: ImportMode,
     mut objects: Pin<&mut Peekable<BoxStream<'_, anyhow::Result<ImportUnit>>>>,
     generated_schemas: &mut BTreeMap<TableName, GeneratedSchema<ProdConfigWithOptionalFields>>,
+    increments: &mut BTreeMap<TableName, BTreeSet<DeveloperDocumentId>>,
+    manifest: &mut Option<IncrementalExportManifest>,
     table_mapping_for_import: &mut TableMapping,
     usage: FunctionUsageTracker,
     import_id: Option<ResolvedDocumentId>,
 ) -> anyhow::Result<Option<usize>> {
-    while let Some(ImportUnit::GeneratedSchema(table_name, generated_schema)) = objects
+    while let Some(unit) = objects
         .as_mut()
-        .try_next_if(|line| matches!(line, ImportUnit::GeneratedSchema(_, _)))
+        .try_next_if(|line| {
+            matches!(
+                line,
+                ImportUnit::GeneratedSchema(_, _)
+                    | ImportUnit::Increment(_, _)
+                    | ImportUnit::Manifest(_)
+            )
+        })
         .await?
     {
-        generated_schemas.insert(table_name, generated_schema);
+        match unit {
+            ImportUnit::GeneratedSchema(table_name, generated_schema) => {
+                generated_schemas.insert(table_name, generated_schema);
+            },
+            ImportUnit::Increment(table_name, deleted_ids) => {
+                if mode != ImportMode::Replace {
+                    anyhow::bail!(ImportError::IncrementalImportRequiresReplace);
+                }
+                increments.insert(table_name, deleted_ids);
+            },
+            ImportUnit::Manifest(incremental_manifest) => {
+                if mode != ImportMode::Replace {
+                    anyhow::bail!(ImportError::IncrementalImportRequiresReplace);
+                }
+                *manifest = Some(incremental_manifest);
+            },
+            ImportUnit::Object(_)
+            | ImportUnit::ObjectWithContext(..)
+            | ImportUnit::NewTable(_)
+            | ImportUnit::StorageFileChunk(..) => {
+                unreachable!("only generated schemas, increments and manifests are peeked")
+            },
+        }
     }
     let mut table_name = match objects.try_next().await? {
         Some(ImportUnit::NewTable(table_name)) => table_name,
@@ -1997,6 +2316,8 @@ async fn import_single_table<RT: Runtime>(
     }
 
     let mut num_objects = 0;
+    let deleted_ids = increments.remove(&table_name);
+    let mut imported_ids = BTreeSet::new();
 
     let mut tx = database.begin(identity.clone()).await?;
     let mut table_mapping_for_schema = tx.table_mapping().clone();
@@ -2004,25 +2325,54 @@ async fn import_single_table<RT: Runtime>(
     let mut objects_to_insert = vec![];
     let mut objects_to_insert_size = 0;
     // Peek so we don't pop ImportUnit::NewTable items.
-    while let Some(ImportUnit::Object(exported_value)) = objects
+    while let Some(unit) = objects
         .as_mut()
-        .try_next_if(|line| matches!(line, ImportUnit::Object(_)))
+        .try_next_if(|line| {
+            matches!(
+                line,
+                ImportUnit::Object(_) | ImportUnit::ObjectWithContext(..)
+            )
+        })
         .await?
     {
         let row_number = num_objects + 1;
-        let convex_value = GeneratedSchema::<ProdConfigWithOptionalFields>::apply(
-            &mut generated_schema,
-            exported_value,
-        )
+        let convex_value = match unit {
+            ImportUnit::Object(exported_value) => {
+                GeneratedSchema::<ProdConfigWithOptionalFields>::apply(
+                    &mut generated_schema,
+                    exported_value,
+                )
+            },
+            ImportUnit::ObjectWithContext(exported_value, export_context) => {
+                GeneratedSchema::<ProdConfigWithOptionalFields>::apply_with_context(
+                    generated_schema.as_deref(),
+                    exported_value,
+                    export_context,
+                )
+            },
+            ImportUnit::NewTable(_)
+            | ImportUnit::GeneratedSchema(..)
+            | ImportUnit::StorageFileChunk(..)
+            | ImportUnit::Increment(..)
+            | ImportUnit::Manifest(_) => unreachable!("only objects are peeked"),
+        }
         .map_err(|e| ImportError::InvalidConvexValue(row_number, e))?;
         let ConvexValue::Object(convex_object) = convex_value else {
             anyhow::bail!(ImportError::NotAnObject(row_number));
         };
+        if deleted_ids.is_some()
+            && let Some(ConvexValue::String(id)) = convex_object.get(&**ID_FIELD)
+        {
+            imported_ids.insert(
+                DeveloperDocumentId::decode(id)
+                    .map_err(|e| ImportError::InvalidConvexValue(row_number, e.into()))?,
+            );
+        }
         objects_to_insert_size += convex_object.size();
         objects_to_insert.push(convex_object);
 
-        if objects_to_insert_size > *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES / 2
-            || objects_to_insert.len() > *TRANSACTION_MAX_NUM_USER_WRITES / 2
+        if objects_to_insert_size > TRANSACTION_MAX_USER_WRITE_SIZE_BYTES.get() / 2
+            || objects_to_insert.len() > TRANSACTION_MAX_NUM_USER_WRITES.get() / 2
         {
             insert_import_objects(
                 database,
@@ -2059,10 +2409,24 @@ async fn import_single_table<RT: Runtime>(
         &table_name,
         table_id,
         &table_mapping_for_schema,
-        usage,
+        usage.clone(),
     )
     .await?;
 
+    if let Some(deleted_ids) = deleted_ids {
+        imported_ids.extend(deleted_ids);
+        num_objects += copy_unchanged_documents(
+            database,
+            identity,
+            &table_name,
+            table_id,
+            &imported_ids,
+            &table_mapping_for_schema,
+            usage,
+        )
+        .await?;
+    }
+
     if let Some(import_id) = import_id {
         add_checkpoint_message(
             database,
@@ -2079,6 +2443,75 @@ async fn import_single_table<RT: Runtime>(
     Ok(Some(num_objects))
 }
 
+/// Copies the documents in the existing `table_name` that an incremental
+/// import doesn't change into the table being imported.
+async fn copy_unchanged_documents<RT: Runtime>(
+    database: &Database<RT>,
+    identity: &Identity,
+    table_name: &TableName,
+    table_id: TabletIdAndTableNumber,
+    changed_ids: &BTreeSet<DeveloperDocumentId>,
+    table_mapping_for_schema: &TableMapping,
+    usage: FunctionUsageTracker,
+) -> anyhow::Result<usize> {
+    let mut tx = database.begin(identity.clone()).await?;
+    let Some(existing_table_id) = tx
+        .table_mapping()
+        .namespace(TableNamespace::by_component_TODO())
+        .id_and_number_if_exists(table_name)
+    else {
+        anyhow::bail!(ImportError::IncrementMissingBase(table_name.clone()));
+    };
+    let by_id = *IndexModel::new(&mut tx)
+        .by_id_indexes()
+        .await?
+        .get(&existing_table_id.tablet_id)
+        .with_context(|| format!("no by_id index for {table_name} found"))?;
+    let table_iterator = database.table_iterator(tx.begin_timestamp(), 1000, None);
+    let stream = table_iterator.stream_documents_in_table(existing_table_id.tablet_id, by_id, None);
+    pin_mut!(stream);
+
+    let mut num_copied = 0;
+    let mut objects_to_insert = vec![];
+    let mut objects_to_insert_size = 0;
+    while let Some((doc, _ts)) = stream.try_next().await? {
+        if changed_ids.contains(&doc.developer_id()) {
+            continue;
+        }
+        let convex_object = doc.into_value().0;
+        objects_to_insert_size += convex_object.size();
+        objects_to_insert.push(convex_object);
+        num_copied += 1;
+
+        if objects_to_insert_size > TRANSACTION_MAX_USER_WRITE_SIZE_BYTES.get() / 2
+            || objects_to_insert.len() > TRANSACTION_MAX_NUM_USER_WRITES.get() / 2
+        {
+            insert_import_objects(
+                database,
+                identity,
+                std::mem::take(&mut objects_to_insert),
+                table_name,
+                table_id,
+                table_mapping_for_schema,
+                usage.clone(),
+            )
+            .await?;
+            objects_to_insert_size = 0;
+        }
+    }
+    insert_import_objects(
+        database,
+        identity,
+        objects_to_insert,
+        table_name,
+        table_id,
+        table_mapping_for_schema,
+        usage,
+    )
+    .await?;
+    Ok(num_copied)
+}
+
 async fn insert_import_objects<RT: Runtime>(
     database: &Database<RT>,
     identity: &Identity,
@@ -2256,7 +2689,7 @@ async fn table_number_for_import(
 ) -> Option<TableNumber> {
     let first_object = objects.peek().await?.as_ref().ok();
     match first_object? {
-        ImportUnit::Object(object) => {
+        ImportUnit::Object(object) | ImportUnit::ObjectWithContext(object, _) => {
             let object = object.as_object()?;
             let first_id = object.get(&**ID_FIELD)?;
             let JsonValue::String(id) = first_id else {
@@ -2268,6 +2701,8 @@ async fn table_number_for_import(
         ImportUnit::NewTable(_) => None,
         ImportUnit::GeneratedSchema(..) => None,
         ImportUnit::StorageFileChunk(..) => None,
+        ImportUnit::Increment(..) => None,
+        ImportUnit::Manifest(_) => None,
     }
 }
 
@@ -2297,11 +2732,17 @@ async fn remap_empty_string_by_schema<'a, RT: Runtime>(
             .map_ok(move |object| match object {
                 unit @ ImportUnit::NewTable(_)
                 | unit @ ImportUnit::GeneratedSchema(..)
-                | unit @ ImportUnit::StorageFileChunk(..) => unit,
+                | unit @ ImportUnit::StorageFileChunk(..)
+                | unit @ ImportUnit::Increment(..)
+                | unit @ ImportUnit::Manifest(_) => unit,
                 ImportUnit::Object(mut object) => ImportUnit::Object({
                     remove_empty_string_optional_entries(&optional_fields, &mut object);
                     object
                 }),
+                ImportUnit::ObjectWithContext(mut object, export_context) => {
+                    remove_empty_string_optional_entries(&optional_fields, &mut object);
+                    ImportUnit::ObjectWithContext(object, export_context)
+                },
             })
             .boxed())
     } else {
@@ -2309,6 +2750,123 @@ async fn remap_empty_string_by_schema<'a, RT: Runtime>(
     }
 }
 
+/// Fails the import if a CSV column has an explicit type that the table's
+/// schema doesn't allow at the column's field path.
+async fn check_csv_column_types<RT: Runtime>(
+    table_name: &TableName,
+    options: &CsvImportOptions,
+    tx: &mut Transaction<RT>,
+) -> anyhow::Result<()> {
+    if options.column_types.is_empty() {
+        return Ok(());
+    }
+    let Some((_, schema)) = SchemaModel::new(tx, TableNamespace::by_component_TODO())
+        .get_by_state(SchemaState::Active)
+        .await?
+    else {
+        return Ok(());
+    };
+    let Some(DocumentSchema::Union(object_validators)) = schema
+        .tables
+        .get(table_name)
+        .and_then(|table_schema| table_schema.document_type.clone())
+    else {
+        return Ok(());
+    };
+    for (header, column_type) in &options.column_types {
+        let path = options.field_path(header);
+        let mut validators: Vec<&Validator> = vec![];
+        for object_validator in &object_validators {
+            if let Some((first, rest)) = path.split_first()
+                && let Ok(field) = first.parse::<IdentifierFieldName>()
+                && let Some(field_validator) = object_validator.0.get(&field)
+            {
+                validators_at_path(field_validator.validator(), rest, &mut validators);
+            }
+        }
+        // Fields that aren't in the schema fail schema validation regardless
+        // of their type.
+        if validators.is_empty()
+            || validators
+                .iter()
+                .any(|validator| csv_column_type_matches(*column_type, validator))
+        {
+            continue;
+        }
+        let expected = validators
+            .iter()
+            .map(|validator| validator.to_string())
+            .collect::<Vec<_>>()
+            .join(" | ");
+        anyhow::bail!(ImportError::CsvColumnTypeMismatch(
+            header.clone(),
+            *column_type,
+            table_name.clone(),
+            expected,
+        ));
+    }
+    Ok(())
+}
+
+fn validators_at_path<'a>(
+    validator: &'a Validator,
+    path: &[String],
+    validators: &mut Vec<&'a Validator>,
+) {
+    let Some((first, rest)) = path.split_first() else {
+        validators.push(validator);
+        return;
+    };
+    match validator {
+        Validator::Object(object_validator) => {
+            if let Ok(field) = first.parse::<IdentifierFieldName>()
+                && let Some(field_validator) = object_validator.0.get(&field)
+            {
+                validators_at_path(field_validator.validator(), rest, validators);
+            }
+        },
+        Validator::Union(options) => {
+            for option in options {
+                validators_at_path(option, path, validators);
+            }
+        },
+        Validator::Record(_, values) => validators_at_path(values, rest, validators),
+        Validator::Constrained(inner, _) => validators_at_path(inner, path, validators),
+        Validator::Any => validators.push(validator),
+        Validator::Id(_)
+        | Validator::Null
+        | Validator::Float64
+        | Validator::Int64
+        | Validator::Boolean
+        | Validator::String
+        | Validator::Bytes
+        | Validator::Literal(_)
+        | Validator::Array(_)
+        | Validator::Set(_)
+        | Validator::Map(..) => {},
+    }
+}
+
+fn csv_column_type_matches(column_type: CsvColumnType, validator: &Validator) -> bool {
+    match (column_type, validator) {
+        (CsvColumnType::Json, _) | (_, Validator::Any) => true,
+        (_, Validator::Union(options)) => options
+            .iter()
+            .any(|option| csv_column_type_matches(column_type, option)),
+        (_, Validator::Constrained(inner, _)) => csv_column_type_matches(column_type, inner),
+        (CsvColumnType::String, Validator::String | Validator::Id(_))
+        | (CsvColumnType::String, Validator::Literal(LiteralValidator::String(_)))
+        | (CsvColumnType::Float64, Validator::Float64)
+        | (CsvColumnType::Float64, Validator::Literal(LiteralValidator::Float64(_)))
+        | (CsvColumnType::Int64, Validator::Int64)
+        | (CsvColumnType::Int64, Validator::Literal(LiteralValidator::Int64(_)))
+        | (CsvColumnType::Boolean, Validator::Boolean)
+        | (CsvColumnType::Boolean, Validator::Literal(LiteralValidator::Boolean(_)))
+        | (CsvColumnType::Bytes, Validator::Bytes) => true,
+        _ => false,
+    }
+}
+
 fn remove_empty_string_optional_entries(
     optional_fields: &HashSet<IdentifierFieldName>,
     object: &mut JsonValue,
@@ -2334,15 +2892,34 @@ fn remove_empty_string_optional_entries(
 #[cfg(test)]
 mod tests {
     use std::{
-        collections::BTreeMap,
+        collections::{
+            BTreeMap,
+            BTreeSet,
+        },
         str::FromStr,
         sync::Arc,
     };
 
     use anyhow::Context;
+    use arrow::{
+        array::{
+            Int64Array,
+            StringArray,
+        },
+        datatypes::{
+            DataType,
+            Field,
+            Schema,
+        },
+        record_batch::RecordBatch,
+    };
     use bytes::Bytes;
     use common::{
         bootstrap_model::index::{
+            database_index::{
+                DatabaseIndexState,
+                DeveloperDatabaseIndexConfig,
+            },
             IndexConfig,
             IndexMetadata,
         },
@@ -2389,8 +2966,17 @@ mod tests {
         Identity,
     };
     use maplit::btreemap;
-    use model::snapshot_imports::types::ImportState;
+    use model::{
+        deployment_audit_log::types::DeploymentAuditLogEvent,
+        snapshot_imports::types::{
+            CsvColumnType,
+            CsvImportOptions,
+            CsvNullHandling,
+            ImportState,
+        },
+    };
     use must_let::must_let;
+    use parquet::arrow::ArrowWriter;
     use runtime::testing::TestRuntime;
     use serde_json::{
         json,
@@ -2416,8 +3002,10 @@ mod tests {
 
     use super::{
         do_import,
+        finalize_import,
         import_objects,
         parse_objects,
+        schemas_for_import,
         ImportFormat,
         ImportMode,
         ImportUnit,
@@ -2531,9 +3119,12 @@ mod tests {
             .filter_map(|line| async move {
                 match line {
                     Ok(super::ImportUnit::Object(object)) => Some(Ok(object)),
+                    Ok(super::ImportUnit::ObjectWithContext(object, _)) => Some(Ok(object)),
                     Ok(super::ImportUnit::NewTable(_)) => None,
                     Ok(super::ImportUnit::GeneratedSchema(..)) => None,
                     Ok(super::ImportUnit::StorageFileChunk(..)) => None,
+                    Ok(super::ImportUnit::Increment(..)) => None,
+                    Ok(super::ImportUnit::Manifest(_)) => None,
                     Err(e) => Some(Err(e)),
                 }
             })
@@ -2552,8 +3143,12 @@ a,b,c
 1,a string i guess,1.2
 5.10,-100,"a string in quotes"
 "#;
-        let objects =
-            run_parse_objects(rt, ImportFormat::Csv("table".parse().unwrap()), test1).await?;
+        let objects = run_parse_objects(
+            rt,
+            ImportFormat::Csv("table".parse()?, CsvImportOptions::default()),
+            test1,
+        )
+        .await?;
         let expected = vec![
             json!({
                 "a": 1.,
@@ -2598,8 +3193,12 @@ _id,value
 a,b,c,d
 "",,"""",""""""
 "#;
-        let objects =
-            run_parse_objects(rt, ImportFormat::Csv("table".parse().unwrap()), test1).await?;
+        let objects = run_parse_objects(
+            rt,
+            ImportFormat::Csv("table".parse()?, CsvImportOptions::default()),
+            test1,
+        )
+        .await?;
         let expected = vec![json!({
             "a": "",
             "b": "",
@@ -2610,6 +3209,169 @@ a,b,c,d
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn test_csv_options(rt: TestRuntime) -> anyhow::Result<()> {
+        let test1 = r#"
+name;Age;address.city;tags;active
+'Smith; Jo';42;'SF';["a"];true
+;7;;[];FALSE
+"#;
+        let options = CsvImportOptions {
+            delimiter: ';',
+            quote: '\'',
+            header_mapping: btreemap! { "Age".to_string() => "age".to_string() },
+            path_separator: Some('.'),
+            column_types: btreemap! {
+                "name".to_string() => CsvColumnType::String,
+                "Age".to_string() => CsvColumnType::Int64,
+                "tags".to_string() => CsvColumnType::Json,
+                "active".to_string() => CsvColumnType::Boolean,
+            },
+            null_handling: CsvNullHandling::Null,
+        };
+        let objects =
+            run_parse_objects(rt, ImportFormat::Csv("table".parse()?, options), test1).await?;
+        let expected = vec![
+            json!({
+                "name": "Smith; Jo",
+                "age": "42",
+                "address": {"city": "SF"},
+                "tags": ["a"],
+                "active": true,
+            }),
+            json!({
+                "name": null,
+                "age": "7",
+                "address": {"city": null},
+                "tags": [],
+                "active": false,
+            }),
+        ];
+        assert_eq!(objects, expected);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_csv_invalid_options(rt: TestRuntime) -> anyhow::Result<()> {
+        let test1 = r#"
+a,a.b
+1,2
+"#;
+        let conflicting = CsvImportOptions {
+            path_separator: Some('.'),
+            ..CsvImportOptions::default()
+        };
+        let err = run_parse_objects(
+            rt.clone(),
+            ImportFormat::Csv("table".parse()?, conflicting),
+            test1,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("can't both be imported"), "{err}");
+
+        let invalid_cell = CsvImportOptions {
+            column_types: btreemap! { "a".to_string() => CsvColumnType::Boolean },
+            ..CsvImportOptions::default()
+        };
+        let test2 = r#"
+a,b
+1,2
+"#;
+        let err = run_parse_objects(rt, ImportFormat::Csv("table".parse()?, invalid_cell), test2)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("isn't a boolean"), "{err}");
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn import_csv_with_column_types(rt: TestRuntime) -> anyhow::Result<()> {
+        let app = Application::new_for_tests(&rt).await?;
+        let table_name = "table1";
+        let test_csv = r#"
+count,bytes,score,note
+10,AQID,1,
+-3,,2.5,hi
+"#;
+        let options = CsvImportOptions {
+            column_types: btreemap! {
+                "count".to_string() => CsvColumnType::Int64,
+                "bytes".to_string() => CsvColumnType::Bytes,
+                "score".to_string() => CsvColumnType::Float64,
+                "note".to_string() => CsvColumnType::String,
+            },
+            null_handling: CsvNullHandling::Omit,
+            ..CsvImportOptions::default()
+        };
+        run_csv_import_with_options(&app, table_name, test_csv, options).await?;
+
+        let objects =
+            load_fields_as_maps(&app, table_name, vec!["count", "bytes", "score", "note"]).await?;
+        assert_eq!(
+            objects,
+            vec![
+                btreemap!(
+                    "count" => ConvexValue::Int64(10),
+                    "bytes" => ConvexValue::try_from(vec![1u8, 2, 3])?,
+                    "score" => ConvexValue::Float64(1.),
+                ),
+                btreemap!(
+                    "count" => ConvexValue::Int64(-3),
+                    "score" => ConvexValue::Float64(2.5),
+                    "note" => assert_val!("hi"),
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn import_csv_with_column_type_not_in_schema_fails(
+        rt: TestRuntime,
+    ) -> anyhow::Result<()> {
+        let app = Application::new_for_tests(&rt).await?;
+        let table_name = "table1";
+        let test_csv = r#"
+a,b
+1,2
+"#;
+        let schema = db_schema!(
+            table_name => DocumentSchema::Union(
+                vec![
+                    object_validator!(
+                        "a" => FieldValidator::required_field_type(Validator::String),
+                        "b" => FieldValidator::required_field_type(Validator::Union(vec![
+                            Validator::Int64,
+                            Validator::Null,
+                        ])),
+                    )
+                ]
+            )
+        );
+        activate_schema(&app, schema).await?;
+
+        let options = CsvImportOptions {
+            column_types: btreemap! {
+                "a".to_string() => CsvColumnType::Int64,
+                "b".to_string() => CsvColumnType::Int64,
+            },
+            ..CsvImportOptions::default()
+        };
+        let err = run_csv_import_with_options(&app, table_name, test_csv, options)
+            .await
+            .unwrap_err();
+        assert!(err.is_bad_request());
+        assert!(
+            err.to_string().contains(
+                "CSV column \"a\" can't be imported as int64, since the schema for table table1 \
+                 expects v.string()"
+            ),
+            "{err}"
+        );
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     #[ignore]
     async fn import_huge_csv(rt: TestRuntime) -> anyhow::Result<()> {
@@ -2812,7 +3574,7 @@ a
         let import_id = upload_import_file(
             &app,
             new_admin_id(),
-            ImportFormat::Csv(table_name.parse()?),
+            ImportFormat::Csv(table_name.parse()?, CsvImportOptions::default()),
             ImportMode::Replace,
             stream_from_str(test_csv),
         )
@@ -3037,6 +3799,108 @@ a
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn import_checks_unique_indexes(rt: TestRuntime) -> anyhow::Result<()> {
+        let app = Application::new_for_tests(&rt).await?;
+        let table_name: TableName = "table1".parse()?;
+        let identity = new_admin_id();
+        let index_name = IndexName::new(table_name.clone(), "by_a".parse()?)?;
+        {
+            let mut tx = app.begin(identity.clone()).await?;
+            IndexModel::new(&mut tx)
+                .add_application_index(
+                    TableNamespace::test_user(),
+                    IndexMetadata::new_database_index(
+                        index_name.clone(),
+                        DeveloperDatabaseIndexConfig {
+                            fields: vec!["a".parse()?].try_into()?,
+                            unique: true,
+                            filter: None,
+                        },
+                        DatabaseIndexState::Enabled,
+                    ),
+                )
+                .await?;
+            app.commit_test(tx).await?;
+        }
+
+        // Replacing the table imports into a new table with a copy of the index.
+        let err = run_csv_import(&app, &table_name, "a\n\"x\"\n\"x\"\n")
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "UniqueIndexViolation");
+
+        run_csv_import(&app, &table_name, "a\n\"x\"\n").await?;
+        // Appending checks against the documents already in the table.
+        let err = do_import(
+            &app,
+            identity,
+            ImportFormat::Csv(table_name.clone(), CsvImportOptions::default()),
+            ImportMode::Append,
+            stream_from_str("a\n\"x\"\n"),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.short_msg(), "UniqueIndexViolation");
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_import_parquet(rt: TestRuntime) -> anyhow::Result<()> {
+        let app = Application::new_for_tests(&rt).await?;
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec![Some("x"), None])),
+            ],
+        )?;
+        let mut file = vec![];
+        let mut writer = ArrowWriter::try_new(&mut file, schema, None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        do_import(
+            &app,
+            new_admin_id(),
+            ImportFormat::Parquet("table1".parse()?),
+            ImportMode::Replace,
+            stream::iter(vec![anyhow::Ok(Bytes::from(file))]).boxed(),
+        )
+        .await?;
+        // Without a generated schema, integers are imported as numbers and
+        // null cells are left out.
+        let mut documents = load_fields_as_maps(&app, "table1", vec!["a", "b"]).await?;
+        documents.sort_by_key(|document| document["a"].clone());
+        assert_eq!(
+            documents,
+            vec![
+                btreemap!(
+                    "a" => assert_val!(1.),
+                    "b" => assert_val!("x"),
+                ),
+                btreemap!("a" => assert_val!(2.)),
+            ]
+        );
+
+        // Other files aren't Parquet.
+        let err = do_import(
+            &app,
+            new_admin_id(),
+            ImportFormat::Parquet("table1".parse()?),
+            ImportMode::Replace,
+            stream_from_str("a,b\n1,x\n"),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.is_bad_request(), "{err:?}");
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     async fn test_import_counts_bandwidth(rt: TestRuntime) -> anyhow::Result<()> {
         let app = Application::new_for_tests(&rt).await?;
@@ -3080,6 +3944,103 @@ a
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn test_import_increment(rt: TestRuntime) -> anyhow::Result<()> {
+        let app = Application::new_for_tests(&rt).await?;
+        let table_name: TableName = "table1".parse()?;
+        let identity = new_admin_id();
+
+        let mut tx = app.begin(identity.clone()).await?;
+        let unchanged_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table_name.clone(), assert_obj!("foo" => "unchanged"))
+            .await?;
+        let updated_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table_name.clone(), assert_obj!("foo" => "old"))
+            .await?;
+        let deleted_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table_name.clone(), assert_obj!("foo" => "deleted"))
+            .await?;
+        app.commit_test(tx).await?;
+
+        let objects = stream::iter(vec![
+            Ok(ImportUnit::Increment(
+                table_name.clone(),
+                [deleted_id].into_iter().collect(),
+            )),
+            Ok(ImportUnit::NewTable(table_name.clone())),
+            Ok(ImportUnit::Object(
+                json!({"_id": updated_id.encode(), "foo": "new"}),
+            )),
+        ])
+        .boxed()
+        .peekable();
+        let mut tx = app.begin(identity.clone()).await?;
+        let initial_schemas = schemas_for_import(&mut tx).await?;
+        drop(tx);
+        let usage = FunctionUsageTracker::new();
+        let (table_mapping_for_import, num_documents, _) = import_objects(
+            &app.database,
+            &app.file_storage,
+            identity.clone(),
+            ImportMode::Replace,
+            objects,
+            usage.clone(),
+            None,
+        )
+        .await?;
+        assert_eq!(num_documents, 2);
+        finalize_import(
+            &app.database,
+            &app.usage_tracking,
+            identity,
+            None,
+            initial_schemas,
+            table_mapping_for_import,
+            None,
+            usage,
+            DeploymentAuditLogEvent::ClearTables,
+        )
+        .await?;
+
+        let mut documents = load_fields_as_maps(&app, "table1", vec!["_id", "foo"]).await?;
+        documents.sort_by_key(|document| document["foo"].clone());
+        assert_eq!(
+            documents,
+            vec![
+                btreemap!(
+                    "_id" => assert_val!(updated_id.encode()),
+                    "foo" => assert_val!("new"),
+                ),
+                btreemap!(
+                    "_id" => assert_val!(unchanged_id.encode()),
+                    "foo" => assert_val!("unchanged"),
+                ),
+            ]
+        );
+
+        // Increments can't be appended.
+        let objects = stream::iter(vec![
+            Ok(ImportUnit::Increment(table_name.clone(), BTreeSet::new())),
+            Ok(ImportUnit::NewTable(table_name)),
+        ])
+        .boxed()
+        .peekable();
+        let err = import_objects(
+            &app.database,
+            &app.file_storage,
+            new_admin_id(),
+            ImportMode::Append,
+            objects,
+            FunctionUsageTracker::new(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("--replace"), "{err:?}");
+
+        Ok(())
+    }
+
     async fn activate_schema<RT: Runtime>(
         app: &Application<RT>,
         schema: DatabaseSchema,
@@ -3145,11 +4106,20 @@ a
         app: &Application<TestRuntime>,
         table_name: &str,
         input: &str,
+    ) -> anyhow::Result<()> {
+        run_csv_import_with_options(app, table_name, input, CsvImportOptions::default()).await
+    }
+
+    async fn run_csv_import_with_options(
+        app: &Application<TestRuntime>,
+        table_name: &str,
+        input: &str,
+        options: CsvImportOptions,
     ) -> anyhow::Result<()> {
         do_import(
             app,
             new_admin_id(),
-            ImportFormat::Csv(table_name.parse()?),
+            ImportFormat::Csv(table_name.parse()?, options),
             ImportMode::Replace,
             stream_from_str(input),
         )
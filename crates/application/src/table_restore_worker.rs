@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use common::{
+    document::ParsedDocument,
+    errors::report_error,
+    knobs::TABLE_RESTORE_BATCH_DELAY,
+    runtime::Runtime,
+};
+use database::{
+    Database,
+    Transaction,
+};
+use errors::ErrorMetadataAnyhowExt;
+use futures::Future;
+use keybroker::Identity;
+use model::table_restores::{
+    types::{
+        TableRestore,
+        TableRestoreProgress,
+        TableRestoreState,
+    },
+    TableRestoreModel,
+};
+use sync_types::backoff::Backoff;
+use value::ResolvedDocumentId;
+
+use crate::metrics::log_worker_starting;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Runs the restores in `_table_restores` one batch at a time, oldest first.
+/// Each batch is committed along with the restore's progress, so a restore
+/// resumes from its last committed batch after a restart.
+pub struct TableRestoreWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    backoff: Backoff,
+}
+
+impl<RT: Runtime> TableRestoreWorker<RT> {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let mut worker = Self {
+            runtime,
+            database,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+        };
+        async move {
+            loop {
+                if let Err(e) = worker.run().await {
+                    report_error(&mut e.context("TableRestoreWorker died"));
+                    let delay = worker.runtime.with_rng(|rng| worker.backoff.fail(rng));
+                    worker.runtime.wait(delay).await;
+                } else {
+                    worker.backoff.reset();
+                }
+            }
+        }
+    }
+
+    /// Run a batch of the oldest running restore, or wait for one to start.
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        let status = log_worker_starting("TableRestore");
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let active = TableRestoreModel::new(&mut tx).active().await?;
+        if let Some(restore) = active.into_iter().next() {
+            self.restore_batch(restore.id()).await?;
+            drop(status);
+            self.runtime.wait(*TABLE_RESTORE_BATCH_DELAY).await;
+            return Ok(());
+        }
+        drop(status);
+        let token = tx.into_token()?;
+        let subscription = self.database.subscribe(token).await?;
+        subscription.wait_for_invalidation().await;
+        Ok(())
+    }
+
+    /// Restore the next batch of the restore's current table and record its
+    /// progress in the same transaction.
+    async fn restore_batch(&self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let Some(restore) = self.running_restore(&mut tx, id).await? else {
+            return Ok(());
+        };
+        let Some(table) = restore.current_table().cloned() else {
+            anyhow::bail!("Restore {id} has no tables left");
+        };
+        let batch = match self
+            .database
+            .restore_table_batch(&mut tx, restore.ts, &table.table_name, restore.cursor)
+            .await
+        {
+            Ok(batch) => batch,
+            Err(e) if e.is_deterministic_user_error() => {
+                let error = format!(
+                    "Failed to restore table \"{}\": {}",
+                    table.table_name,
+                    e.user_facing_message()
+                );
+                return self.fail(id, error).await;
+            },
+            Err(e) => return Err(e),
+        };
+        let progress = TableRestoreProgress {
+            done: batch.done,
+            inserted: table.inserted + batch.counts.inserted,
+            replaced: table.replaced + batch.counts.replaced,
+            deleted: table.deleted + batch.counts.deleted,
+            ..table
+        };
+        let cursor = if batch.done { None } else { batch.cursor };
+        TableRestoreModel::new(&mut tx)
+            .record_batch(id, cursor, progress)
+            .await?;
+        if let Err(e) = self
+            .database
+            .commit_with_write_source(tx, "restore_tables")
+            .await
+        {
+            if !e.is_deterministic_user_error() {
+                return Err(e);
+            }
+            let error = format!(
+                "Failed to write batch of table \"{}\": {}",
+                table.table_name,
+                e.user_facing_message()
+            );
+            return self.fail(id, error).await;
+        }
+        Ok(())
+    }
+
+    /// The restore with `id` if it's still running as of `tx`.
+    async fn running_restore(
+        &self,
+        tx: &mut Transaction<RT>,
+        id: ResolvedDocumentId,
+    ) -> anyhow::Result<Option<TableRestore>> {
+        let restore = TableRestoreModel::new(tx)
+            .get(id)
+            .await?
+            .map(ParsedDocument::into_value)
+            .filter(|restore| restore.state == TableRestoreState::Running);
+        Ok(restore)
+    }
+
+    /// Fail the restore without committing anything the current batch wrote.
+    async fn fail(&self, id: ResolvedDocumentId, error: String) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        if self.running_restore(&mut tx, id).await?.is_none() {
+            return Ok(());
+        }
+        tracing::warn!("Restore {id} failed: {error}");
+        TableRestoreModel::new(&mut tx).fail(id, error).await?;
+        self.database
+            .commit_with_write_source(tx, "restore_failed")
+            .await?;
+        Ok(())
+    }
+}
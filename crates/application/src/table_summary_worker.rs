@@ -17,6 +17,7 @@ use common::{
     },
 };
 use database::{
+    aggregate_index_snapshot,
     table_summary::write_snapshot,
     Database,
     TableSummaryWriter,
@@ -94,6 +95,17 @@ impl<RT: Runtime> TableSummaryWorker<RT> {
         tracing::info!("Writing table summary checkpoint");
         let snapshot = writer.compute_from_last_checkpoint().await?;
         write_snapshot(self.persistence.as_ref(), &snapshot).await?;
+        // Aggregate index deltas are maintained in memory the same way, so flush them
+        // to their persisted counts and checkpoint what's left on the same
+        // schedule to bound how much of the log they replay at startup.
+        let num_flushed = aggregate_index_snapshot::flush(&self.database).await?;
+        tracing::info!("Flushed {num_flushed} aggregate index counts");
+        tracing::info!("Writing aggregate index checkpoint");
+        aggregate_index_snapshot::write_snapshot(
+            self.persistence.as_ref(),
+            &self.database.aggregate_index_snapshot(),
+        )
+        .await?;
         *last_write_info = Some(LastWriteInfo {
             observed_commits: commits_since_load,
             ts: now,
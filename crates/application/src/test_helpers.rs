@@ -16,7 +16,10 @@ use common::{
     bootstrap_model::index::database_index::IndexedFields,
     components::ComponentId,
     db_schema,
-    http::fetch::StaticFetchClient,
+    http::fetch::{
+        FetchClient,
+        StaticFetchClient,
+    },
     knobs::ACTION_USER_TIMEOUT,
     log_streaming::NoopLogSender,
     pause::{
@@ -96,6 +99,9 @@ pub struct ApplicationFixtureArgs {
     pub tp: Option<TestPersistence>,
     pub snapshot_import_pause_client: Option<PauseClient>,
     pub scheduled_jobs_pause_client: PauseClient,
+    /// Used for `fetch` in actions. Defaults to an empty
+    /// [`StaticFetchClient`].
+    pub fetch_client: Option<Arc<dyn FetchClient>>,
 }
 
 impl ApplicationFixtureArgs {
@@ -191,7 +197,9 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
             StorageUseCase::SnapshotImports,
         )?);
 
-        let fetch_client = Arc::new(StaticFetchClient::new());
+        let fetch_client = args
+            .fetch_client
+            .unwrap_or_else(|| Arc::new(StaticFetchClient::new()));
         let function_runner = Arc::new(
             InProcessFunctionRunner::new(
                 DEV_INSTANCE_NAME.into(),
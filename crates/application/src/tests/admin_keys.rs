@@ -0,0 +1,42 @@
+use common::types::MemberId;
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::AdminKeyScope;
+use runtime::testing::TestRuntime;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_scoped_admin_key_cant_revoke_keys(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let key_broker = application.key_broker();
+    let full = key_broker.check_admin_key(&key_broker.issue_admin_key(MemberId(0)).to_string())?;
+    let scoped = key_broker.check_admin_key(
+        &key_broker
+            .issue_scoped_admin_key(MemberId(0), AdminKeyScope::deploy_only(), None)
+            .to_string(),
+    )?;
+    let full_key_id = full.admin_key_id().unwrap().to_string();
+    let scoped_key_id = scoped.admin_key_id().unwrap().to_string();
+
+    // A key with only some permissions can't revoke a key with more, even
+    // though it may deploy.
+    let err = application
+        .revoke_admin_key(scoped.clone(), full_key_id)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "AdminKeyNotFullAccess");
+    application.check_admin_key_not_revoked(&full).await?;
+
+    application
+        .revoke_admin_key(full.clone(), scoped_key_id)
+        .await?;
+    assert!(application
+        .check_admin_key_not_revoked(&scoped)
+        .await
+        .is_err());
+    application.check_admin_key_not_revoked(&full).await?;
+    Ok(())
+}
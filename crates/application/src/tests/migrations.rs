@@ -0,0 +1,223 @@
+use std::time::Duration;
+
+use common::{
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::MemberId,
+};
+use database::{
+    ResolvedQuery,
+    UserFacingModel,
+};
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::{
+    AdminKeyScope,
+    AdminPermission,
+    Identity,
+};
+use model::migrations::{
+    types::{
+        Migration,
+        MigrationProgress,
+        MigrationState,
+    },
+    MigrationModel,
+};
+use runtime::testing::TestRuntime;
+use value::{
+    assert_obj,
+    ConvexValue,
+    ResolvedDocumentId,
+    TableNamespace,
+};
+
+use crate::{
+    test_helpers::{
+        ApplicationTestExt,
+        OBJECTS_TABLE,
+    },
+    Application,
+};
+
+async fn start_migration(
+    application: &Application<TestRuntime>,
+    transform: &str,
+    dry_run: bool,
+) -> anyhow::Result<ResolvedDocumentId> {
+    let mut tx = application.begin(Identity::system()).await?;
+    for i in 0..5i64 {
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(OBJECTS_TABLE.clone(), assert_obj!("a" => i))
+            .await?;
+    }
+    let id = MigrationModel::new(&mut tx)
+        .start(OBJECTS_TABLE.clone(), transform.parse()?, 2, dry_run)
+        .await?;
+    application.commit_test(tx).await?;
+    Ok(id)
+}
+
+async fn get_migration(
+    application: &Application<TestRuntime>,
+    id: ResolvedDocumentId,
+) -> anyhow::Result<Migration> {
+    let mut tx = application.begin(Identity::system()).await?;
+    let migration = MigrationModel::new(&mut tx).get(id).await?.unwrap();
+    Ok(migration.into_value())
+}
+
+async fn field_values(
+    application: &Application<TestRuntime>,
+    field: &str,
+) -> anyhow::Result<Vec<Option<ConvexValue>>> {
+    let mut tx = application.begin(Identity::system()).await?;
+    let query = Query::full_table_scan(OBJECTS_TABLE.clone(), Order::Asc);
+    let mut query_stream = ResolvedQuery::new(&mut tx, TableNamespace::test_user(), query)?;
+    let mut values = vec![];
+    while let Some(document) = query_stream.next(&mut tx, None).await? {
+        values.push(document.value().get(field).cloned());
+    }
+    Ok(values)
+}
+
+#[convex_macro::test_runtime]
+async fn test_migration(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+    let id = start_migration(&application, "migrations:renameAToB", false).await?;
+
+    rt.wait(Duration::from_secs(100)).await;
+    let migration = get_migration(&application, id).await?;
+    assert_eq!(migration.state, MigrationState::Completed);
+    assert_eq!(migration.progress.documents_processed, 5);
+    assert_eq!(migration.progress.documents_changed, 5);
+    assert_eq!(migration.progress.documents_verified, 5);
+    assert!(field_values(&application, "a")
+        .await?
+        .iter()
+        .all(|a| a.is_none()));
+    assert!(field_values(&application, "b")
+        .await?
+        .iter()
+        .all(|b| b.is_some()));
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_migration_dry_run(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+    let id = start_migration(&application, "migrations:renameAToB", true).await?;
+
+    rt.wait(Duration::from_secs(100)).await;
+    let migration = get_migration(&application, id).await?;
+    assert_eq!(migration.state, MigrationState::Completed);
+    assert_eq!(migration.progress.documents_processed, 5);
+    assert_eq!(migration.progress.documents_changed, 5);
+    assert!(field_values(&application, "a")
+        .await?
+        .iter()
+        .all(|a| a.is_some()));
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_migration_transform_fails(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+    let id = start_migration(&application, "migrations:throwError", false).await?;
+
+    rt.wait(Duration::from_secs(100)).await;
+    let migration = get_migration(&application, id).await?;
+    let MigrationState::Failed { error } = migration.state else {
+        anyhow::bail!("Expected migration to fail, got {:?}", migration.state);
+    };
+    assert!(error.contains("Oh no"), "{error}");
+    assert_eq!(migration.progress.documents_processed, 0);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_migration_resumes_from_cursor(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut ids = vec![];
+    for i in 0..5i64 {
+        let id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(OBJECTS_TABLE.clone(), assert_obj!("a" => i))
+            .await?;
+        ids.push(id);
+    }
+    ids.sort();
+    // Persist progress as if a worker had transformed the first two documents
+    // before restarting. The worker only reads its position from the
+    // migration, so it picks up after the cursor.
+    let mut model = MigrationModel::new(&mut tx);
+    let id = model
+        .start(
+            OBJECTS_TABLE.clone(),
+            "migrations:renameAToB".parse()?,
+            2,
+            false,
+        )
+        .await?;
+    model
+        .record_batch(
+            id,
+            ids[1],
+            MigrationProgress {
+                documents_processed: 2,
+                documents_changed: 2,
+                ..Default::default()
+            },
+        )
+        .await?;
+    application.commit_test(tx).await?;
+
+    rt.wait(Duration::from_secs(100)).await;
+    let migration = get_migration(&application, id).await?;
+    assert_eq!(migration.state, MigrationState::Completed);
+    assert_eq!(migration.progress.documents_processed, 5);
+    assert_eq!(migration.progress.documents_changed, 5);
+    // The documents up to the cursor weren't transformed again.
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    for (i, id) in ids.into_iter().enumerate() {
+        let document = model.get(id, None).await?.unwrap();
+        assert_eq!(document.value().get("a").is_some(), i < 2);
+    }
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_migration_checks_admin_key_scope(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let key_broker = application.key_broker();
+    for (scope, expected) in [
+        (AdminKeyScope::deploy_only(), "AdminKeyMissingPermission"),
+        (
+            AdminKeyScope::with_permissions([AdminPermission::WriteData])
+                .restrict_tables(["other".to_string()]),
+            "AdminKeyTableNotAllowed",
+        ),
+    ] {
+        let key = key_broker.issue_scoped_admin_key(MemberId(0), scope, None);
+        let identity = key_broker.check_admin_key(&key.to_string())?;
+        let err = application
+            .start_migration(
+                identity,
+                OBJECTS_TABLE.clone(),
+                "migrations:renameAToB".parse()?,
+                2,
+                false,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), expected);
+    }
+    Ok(())
+}
@@ -1,14 +1,18 @@
+mod admin_keys;
 mod analyze;
 mod auth_config;
 mod components;
 mod cron_jobs;
 mod environment_variables;
+mod migrations;
 mod mutation;
 mod occ_retries;
 mod returns_validation;
 mod scheduled_jobs;
 mod schema;
 mod source_package;
+mod table_restores;
+mod table_retention;
 
 const NODE_SOURCE: &str = r#"
 var nodeFunction = () => {};
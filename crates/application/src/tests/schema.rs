@@ -1,10 +1,21 @@
 use common::{
     bootstrap_model::index::database_index::IndexedFields,
-    types::ModuleEnvironment,
+    knobs::SUGGEST_SCHEMA_MAX_CHECKED_DOCUMENTS,
+    schemas::suggest::SuggestSchemaOptions,
+    types::{
+        MemberId,
+        ModuleEnvironment,
+    },
 };
+use database::UserFacingModel;
 use errors::ErrorMetadataAnyhowExt;
+use keybroker::{
+    AdminKeyScope,
+    Identity,
+};
 use model::config::types::ModuleConfig;
 use runtime::testing::TestRuntime;
+use value::assert_obj;
 
 use crate::{
     test_helpers::ApplicationTestExt,
@@ -208,3 +219,72 @@ export default new SchemaDefinition()
 
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_suggest_schema_respects_table_scope(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    for table in ["allowed", "hidden"] {
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table.parse()?, assert_obj!("a" => 1))
+            .await?;
+    }
+    application.commit_test(tx).await?;
+
+    let key_broker = application.key_broker();
+    let scope = AdminKeyScope::read_only().restrict_tables(["allowed".to_string()]);
+    let key = key_broker.issue_scoped_admin_key(MemberId(0), scope, None);
+    let identity = key_broker.check_admin_key(&key.to_string())?;
+    let suggested = application
+        .suggest_schema(identity, SuggestSchemaOptions::default())
+        .await?;
+    assert!(suggested.fully_validated);
+    assert_eq!(
+        suggested
+            .schema
+            .tables
+            .keys()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>(),
+        vec!["allowed"]
+    );
+
+    let scope = AdminKeyScope::read_only().restrict_components(["child".to_string()]);
+    let key = key_broker.issue_scoped_admin_key(MemberId(0), scope, None);
+    let identity = key_broker.check_admin_key(&key.to_string())?;
+    let err = application
+        .suggest_schema(identity, SuggestSchemaOptions::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "AdminKeyComponentNotAllowed");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_suggest_schema_reports_unchecked_documents(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    for _ in 0..*SUGGEST_SCHEMA_MAX_CHECKED_DOCUMENTS {
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert("table".parse()?, assert_obj!("a" => 1))
+            .await?;
+    }
+    application.commit_test(tx).await?;
+    let key = application.key_broker().issue_admin_key(MemberId(0));
+    let identity = application.key_broker().check_admin_key(&key.to_string())?;
+    let suggested = application
+        .suggest_schema(identity.clone(), SuggestSchemaOptions::default())
+        .await?;
+    assert!(suggested.fully_validated);
+
+    let mut tx = application.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert("table".parse()?, assert_obj!("a" => 1))
+        .await?;
+    application.commit_test(tx).await?;
+    let suggested = application
+        .suggest_schema(identity, SuggestSchemaOptions::default())
+        .await?;
+    assert!(!suggested.fully_validated);
+    Ok(())
+}
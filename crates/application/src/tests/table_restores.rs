@@ -0,0 +1,147 @@
+use std::{
+    collections::BTreeSet,
+    time::Duration,
+};
+
+use common::{
+    runtime::Runtime,
+    types::MemberId,
+};
+use database::{
+    TableModel,
+    UserFacingModel,
+};
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::{
+    AdminKeyScope,
+    Identity,
+};
+use model::table_restores::{
+    types::TableRestoreState,
+    TableRestoreModel,
+};
+use runtime::testing::TestRuntime;
+use value::{
+    assert_obj,
+    assert_val,
+    TableNamespace,
+};
+
+use crate::{
+    test_helpers::{
+        ApplicationTestExt,
+        OBJECTS_TABLE,
+    },
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_table_restore(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut ids = vec![];
+    for i in 0..5i64 {
+        let id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(OBJECTS_TABLE.clone(), assert_obj!("a" => i))
+            .await?;
+        ids.push(id);
+    }
+    let ts = application.commit_test(tx).await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    model.delete(ids[0]).await?;
+    model.replace(ids[1], assert_obj!("a" => 100)).await?;
+    let inserted = model
+        .insert(OBJECTS_TABLE.clone(), assert_obj!("a" => 5))
+        .await?;
+    application.commit_test(tx).await?;
+
+    let key_broker = application.key_broker();
+    let key = key_broker.issue_scoped_admin_key(MemberId(0), AdminKeyScope::full(), None);
+    let identity = key_broker.check_admin_key(&key.to_string())?;
+    application
+        .start_table_restore(
+            identity.clone(),
+            ts,
+            BTreeSet::from([OBJECTS_TABLE.clone()]),
+        )
+        .await?;
+
+    rt.wait(Duration::from_secs(100)).await;
+    let restores = application.list_table_restores(identity).await?;
+    let [restore] = &restores[..] else {
+        anyhow::bail!("Expected one restore: {restores:?}");
+    };
+    assert_eq!(restore.state, TableRestoreState::Completed);
+    let [table] = &restore.tables[..] else {
+        anyhow::bail!("Expected one table: {:?}", restore.tables);
+    };
+    assert!(table.done);
+    assert_eq!((table.inserted, table.replaced, table.deleted), (1, 1, 1));
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    for (i, id) in ids.into_iter().enumerate() {
+        let document = model.get(id, None).await?.unwrap();
+        assert_eq!(document.value().get("a"), Some(&assert_val!(i as i64)));
+    }
+    assert!(model.get(inserted, None).await?.is_none());
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_table_restore_fails_once_table_is_replaced(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(OBJECTS_TABLE.clone(), assert_obj!("a" => 1))
+        .await?;
+    let ts = application.commit_test(tx).await?;
+
+    // The table is deleted along with starting the restore, so the worker
+    // fails it with the reason instead of retrying.
+    let mut tx = application.begin(Identity::system()).await?;
+    let id = TableRestoreModel::new(&mut tx)
+        .start(ts, BTreeSet::from([OBJECTS_TABLE.clone()]))
+        .await?;
+    TableModel::new(&mut tx)
+        .delete_table(TableNamespace::test_user(), OBJECTS_TABLE.clone())
+        .await?;
+    application.commit_test(tx).await?;
+
+    rt.wait(Duration::from_secs(100)).await;
+    let mut tx = application.begin(Identity::system()).await?;
+    let restore = TableRestoreModel::new(&mut tx).get(id).await?.unwrap();
+    let TableRestoreState::Failed { error } = &restore.state else {
+        anyhow::bail!("Expected restore to fail, got {:?}", restore.state);
+    };
+    assert!(error.contains("deleted or replaced"), "{error}");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_table_restore_checks_admin_key_scope(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(OBJECTS_TABLE.clone(), assert_obj!("a" => 1))
+        .await?;
+    let ts = application.commit_test(tx).await?;
+    let key_broker = application.key_broker();
+    for (scope, expected) in [
+        (AdminKeyScope::read_only(), "AdminKeyMissingPermission"),
+        (
+            AdminKeyScope::full().restrict_tables(["other".to_string()]),
+            "AdminKeyTableNotAllowed",
+        ),
+    ] {
+        let key = key_broker.issue_scoped_admin_key(MemberId(0), scope, None);
+        let identity = key_broker.check_admin_key(&key.to_string())?;
+        let err = application
+            .start_table_restore(identity, ts, BTreeSet::from([OBJECTS_TABLE.clone()]))
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), expected);
+    }
+    Ok(())
+}
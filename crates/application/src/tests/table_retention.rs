@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use common::types::MemberId;
+use database::UserFacingModel;
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::{
+    AdminKeyScope,
+    Identity,
+};
+use runtime::testing::TestRuntime;
+use value::assert_obj;
+
+use crate::{
+    test_helpers::{
+        ApplicationTestExt,
+        OBJECTS_TABLE,
+    },
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_table_retention_checks_admin_key_scope(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(OBJECTS_TABLE.clone(), assert_obj!("a" => 1))
+        .await?;
+    application.commit_test(tx).await?;
+    let key_broker = application.key_broker();
+    let retention = Duration::from_secs(30 * 24 * 60 * 60);
+
+    let key = key_broker.issue_scoped_admin_key(
+        MemberId(0),
+        AdminKeyScope::full().restrict_tables(["other".to_string()]),
+        None,
+    );
+    let identity = key_broker.check_admin_key(&key.to_string())?;
+    let err = application
+        .set_table_document_retention(identity.clone(), OBJECTS_TABLE.clone(), Some(retention))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "AdminKeyTableNotAllowed");
+    // Tables outside of the key's scope aren't listed.
+    assert!(application
+        .table_document_retention(identity)
+        .await?
+        .is_empty());
+
+    let key = key_broker.issue_scoped_admin_key(MemberId(0), AdminKeyScope::full(), None);
+    let identity = key_broker.check_admin_key(&key.to_string())?;
+    application
+        .set_table_document_retention(identity.clone(), OBJECTS_TABLE.clone(), Some(retention))
+        .await?;
+    assert_eq!(
+        application.table_document_retention(identity).await?[&*OBJECTS_TABLE],
+        Some(retention)
+    );
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::ConvexValue;
+
+use crate::{
+    bootstrap_model::index::database_index::IndexedFields,
+    document::ResolvedDocument,
+    paths::FieldPath,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct DeveloperAggregateIndexConfig {
+    /// Ordered field(s) whose values group documents. Aggregates can be read
+    /// for any range of these fields, so an index on `["org", "status"]` can
+    /// answer both "per org" and "per org and status".
+    pub fields: IndexedFields,
+    /// Numeric field to sum and take the min/max of. Documents where this
+    /// field is missing or not a number only contribute to the count.
+    pub value_field: Option<FieldPath>,
+}
+
+impl DeveloperAggregateIndexConfig {
+    /// The value `document` contributes to the sum/min/max of this index.
+    pub fn summarized_value(&self, document: &ResolvedDocument) -> Option<f64> {
+        let value_field = self.value_field.as_ref()?;
+        match document.value().0.get_path(value_field)? {
+            ConvexValue::Float64(f) if !f.is_nan() => Some(*f),
+            ConvexValue::Int64(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedDeveloperAggregateIndexConfig {
+    fields: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value_field: Option<String>,
+}
+
+impl TryFrom<DeveloperAggregateIndexConfig> for SerializedDeveloperAggregateIndexConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(config: DeveloperAggregateIndexConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            fields: Vec::<FieldPath>::from(config.fields)
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            value_field: config.value_field.map(String::from),
+        })
+    }
+}
+
+impl TryFrom<SerializedDeveloperAggregateIndexConfig> for DeveloperAggregateIndexConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(config: SerializedDeveloperAggregateIndexConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            fields: config
+                .fields
+                .into_iter()
+                .map(|p| p.parse())
+                .collect::<anyhow::Result<Vec<FieldPath>>>()?
+                .try_into()?,
+            value_field: config.value_field.map(|p| p.parse()).transpose()?,
+        })
+    }
+}
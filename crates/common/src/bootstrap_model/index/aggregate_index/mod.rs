@@ -0,0 +1,6 @@
+mod index_config;
+
+pub use self::index_config::{
+    DeveloperAggregateIndexConfig,
+    SerializedDeveloperAggregateIndexConfig,
+};
@@ -4,7 +4,12 @@ use serde::{
 };
 
 use super::indexed_fields::IndexedFields;
-use crate::paths::FieldPath;
+use crate::{
+    document::ResolvedDocument,
+    json::JsonExpression,
+    paths::FieldPath,
+    query::Expression,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
@@ -12,12 +17,39 @@ pub struct DeveloperDatabaseIndexConfig {
     /// Ordered field(s) to index. The "unindexed" primary key ordering of
     /// documents by [`DocumentId`] is represented by an empty vector.
     pub fields: IndexedFields,
+    /// Whether at most one document may have any given combination of values
+    /// for `fields`. Documents missing any of the indexed fields are exempt.
+    pub unique: bool,
+    /// If set, only documents for which this expression evaluates to `true`
+    /// are indexed. Queries may only use the index if their range implies
+    /// the filter.
+    pub filter: Option<Expression>,
+}
+
+impl DeveloperDatabaseIndexConfig {
+    /// Whether `document` has entries in this index. Documents where the
+    /// filter fails to evaluate to a boolean are excluded rather than failing
+    /// the write.
+    pub fn includes(&self, document: &ResolvedDocument) -> bool {
+        let Some(ref filter) = self.filter else {
+            return true;
+        };
+        filter
+            .eval(&document.value().0)
+            .and_then(|result| result.into_boolean())
+            .unwrap_or(false)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SerializedDeveloperDatabaseIndexConfig {
     fields: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unique: Option<bool>,
+    /// JSON-serialized [`JsonExpression`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filter: Option<String>,
 }
 
 impl TryFrom<DeveloperDatabaseIndexConfig> for SerializedDeveloperDatabaseIndexConfig {
@@ -29,6 +61,11 @@ impl TryFrom<DeveloperDatabaseIndexConfig> for SerializedDeveloperDatabaseIndexC
                 .into_iter()
                 .map(String::from)
                 .collect(),
+            unique: config.unique.then_some(true),
+            filter: config
+                .filter
+                .map(|filter| serde_json::to_string(&JsonExpression::from(filter)))
+                .transpose()?,
         })
     }
 }
@@ -44,6 +81,13 @@ impl TryFrom<SerializedDeveloperDatabaseIndexConfig> for DeveloperDatabaseIndexC
                 .map(|p| p.parse())
                 .collect::<anyhow::Result<Vec<FieldPath>>>()?
                 .try_into()?,
+            unique: config.unique.unwrap_or(false),
+            filter: config
+                .filter
+                .map(|filter| {
+                    Expression::try_from(serde_json::from_str::<JsonExpression>(&filter)?)
+                })
+                .transpose()?,
         })
     }
 }
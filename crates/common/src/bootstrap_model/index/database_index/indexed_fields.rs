@@ -158,3 +158,15 @@ impl From<IndexedFields> for Vec<FieldPathProto> {
             .collect()
     }
 }
+
+impl TryFrom<Vec<FieldPathProto>> for IndexedFields {
+    type Error = anyhow::Error;
+
+    fn try_from(fields: Vec<FieldPathProto>) -> anyhow::Result<Self> {
+        fields
+            .into_iter()
+            .map(FieldPath::try_from)
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .try_into()
+    }
+}
@@ -7,6 +7,10 @@ use serde::{
 use value::codegen_convex_serialization;
 
 use super::{
+    aggregate_index::{
+        DeveloperAggregateIndexConfig,
+        SerializedDeveloperAggregateIndexConfig,
+    },
     database_index::{
         DeveloperDatabaseIndexConfig,
         SerializedDeveloperDatabaseIndexConfig,
@@ -33,6 +37,9 @@ pub enum DeveloperIndexConfig {
     Search(DeveloperSearchIndexConfig),
 
     Vector(DeveloperVectorIndexConfig),
+
+    /// Count/sum/min/max per index key prefix.
+    Aggregate(DeveloperAggregateIndexConfig),
 }
 
 impl From<IndexConfig> for DeveloperIndexConfig {
@@ -47,6 +54,9 @@ impl From<IndexConfig> for DeveloperIndexConfig {
             IndexConfig::Vector {
                 developer_config, ..
             } => DeveloperIndexConfig::Vector(developer_config),
+            IndexConfig::Aggregate {
+                developer_config, ..
+            } => DeveloperIndexConfig::Aggregate(developer_config),
         }
     }
 }
@@ -66,6 +76,10 @@ enum SerializedDeveloperIndexConfig {
         #[serde(flatten)]
         config: SerializedDeveloperVectorIndexConfig,
     },
+    Aggregate {
+        #[serde(flatten)]
+        config: SerializedDeveloperAggregateIndexConfig,
+    },
 }
 
 impl TryFrom<DeveloperIndexConfig> for SerializedDeveloperIndexConfig {
@@ -82,6 +96,9 @@ impl TryFrom<DeveloperIndexConfig> for SerializedDeveloperIndexConfig {
             DeveloperIndexConfig::Vector(config) => Self::Vector {
                 config: config.try_into()?,
             },
+            DeveloperIndexConfig::Aggregate(config) => Self::Aggregate {
+                config: config.try_into()?,
+            },
         })
     }
 }
@@ -96,6 +113,9 @@ impl TryFrom<SerializedDeveloperIndexConfig> for DeveloperIndexConfig {
             },
             SerializedDeveloperIndexConfig::Search { config } => Self::Search(config.try_into()?),
             SerializedDeveloperIndexConfig::Vector { config } => Self::Vector(config.try_into()?),
+            SerializedDeveloperIndexConfig::Aggregate { config } => {
+                Self::Aggregate(config.try_into()?)
+            },
         })
     }
 }
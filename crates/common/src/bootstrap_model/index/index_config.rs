@@ -7,6 +7,10 @@ use serde::{
 use value::codegen_convex_serialization;
 
 use super::{
+    aggregate_index::{
+        DeveloperAggregateIndexConfig,
+        SerializedDeveloperAggregateIndexConfig,
+    },
     database_index::{
         DatabaseIndexState,
         DeveloperDatabaseIndexConfig,
@@ -52,6 +56,19 @@ pub enum IndexConfig {
         developer_config: DeveloperVectorIndexConfig,
         on_disk_state: VectorIndexState,
     },
+
+    /// Count and numeric sum/min/max over index ranges. Aggregate indexes
+    /// have no persisted entries; they persist a B-tree of the counts per
+    /// distinct index key and value in `_aggregate_index_counts` instead,
+    /// whose internal nodes hold the aggregates of their subtrees, with the
+    /// changes since they were last written maintained in memory on every
+    /// commit.
+    Aggregate {
+        developer_config: DeveloperAggregateIndexConfig,
+
+        /// Whether the aggregates have been computed or not.
+        on_disk_state: DatabaseIndexState,
+    },
 }
 
 impl IndexConfig {
@@ -66,6 +83,9 @@ impl IndexConfig {
             IndexConfig::Vector { on_disk_state, .. } => {
                 matches!(on_disk_state, VectorIndexState::SnapshottedAt(_))
             },
+            IndexConfig::Aggregate { on_disk_state, .. } => {
+                matches!(on_disk_state, DatabaseIndexState::Enabled)
+            },
         }
     }
 
@@ -80,6 +100,9 @@ impl IndexConfig {
             IndexConfig::Vector { on_disk_state, .. } => {
                 matches!(on_disk_state, VectorIndexState::Backfilling(_))
             },
+            IndexConfig::Aggregate { on_disk_state, .. } => {
+                matches!(on_disk_state, DatabaseIndexState::Backfilling(_))
+            },
         }
     }
 
@@ -112,6 +135,15 @@ impl IndexConfig {
                     ..
                 },
             ) => developer_config == config_to_compare,
+            (
+                IndexConfig::Aggregate {
+                    developer_config, ..
+                },
+                IndexConfig::Aggregate {
+                    developer_config: config_to_compare,
+                    ..
+                },
+            ) => developer_config == config_to_compare,
             (..) => false,
         }
     }
@@ -129,7 +161,9 @@ impl IndexConfig {
     /// on other index types will panic.
     pub fn estimate_pricing_size_bytes(&self) -> anyhow::Result<u64> {
         match self {
-            IndexConfig::Database { .. } | IndexConfig::Search { .. } => {
+            IndexConfig::Database { .. }
+            | IndexConfig::Search { .. }
+            | IndexConfig::Aggregate { .. } => {
                 // TODO(sam): We should support this for all index types in the future. Right
                 // now search indexes are free and we estimate the size of
                 // database indexes. Both of those could instead track usage in their metadata,
@@ -174,6 +208,12 @@ pub enum SerializedIndexConfig {
         developer_config: SerializedDeveloperVectorIndexConfig,
         on_disk_state: SerializedVectorIndexState,
     },
+    #[serde(rename_all = "camelCase")]
+    Aggregate {
+        #[serde(flatten)]
+        developer_config: SerializedDeveloperAggregateIndexConfig,
+        on_disk_state: SerializedDatabaseIndexState,
+    },
 }
 
 impl TryFrom<IndexConfig> for SerializedIndexConfig {
@@ -202,6 +242,13 @@ impl TryFrom<IndexConfig> for SerializedIndexConfig {
                 developer_config: developer_config.try_into()?,
                 on_disk_state: on_disk_state.try_into()?,
             },
+            IndexConfig::Aggregate {
+                developer_config,
+                on_disk_state,
+            } => SerializedIndexConfig::Aggregate {
+                developer_config: developer_config.try_into()?,
+                on_disk_state: on_disk_state.try_into()?,
+            },
         })
     }
 }
@@ -232,6 +279,13 @@ impl TryFrom<SerializedIndexConfig> for IndexConfig {
                 developer_config: developer_config.try_into()?,
                 on_disk_state: on_disk_state.try_into()?,
             },
+            SerializedIndexConfig::Aggregate {
+                developer_config,
+                on_disk_state,
+            } => IndexConfig::Aggregate {
+                developer_config: developer_config.try_into()?,
+                on_disk_state: on_disk_state.try_into()?,
+            },
         })
     }
 }
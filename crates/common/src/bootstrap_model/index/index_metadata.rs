@@ -16,6 +16,7 @@ use value::{
 };
 
 use super::{
+    aggregate_index::DeveloperAggregateIndexConfig,
     database_index::{
         DatabaseIndexBackfillState,
         DatabaseIndexState,
@@ -67,15 +68,43 @@ impl<T: TableIdentifier> IndexMetadata<T> {
         index_created_lower_bound: Timestamp,
         name: GenericIndexName<T>,
         fields: IndexedFields,
+    ) -> Self {
+        Self::new_backfilling_database_index(
+            index_created_lower_bound,
+            name,
+            DeveloperDatabaseIndexConfig {
+                fields,
+                unique: false,
+                filter: None,
+            },
+        )
+    }
+
+    pub fn new_backfilling_database_index(
+        index_created_lower_bound: Timestamp,
+        name: GenericIndexName<T>,
+        developer_config: DeveloperDatabaseIndexConfig,
+    ) -> Self {
+        Self::new_database_index(
+            name,
+            developer_config,
+            DatabaseIndexState::Backfilling(DatabaseIndexBackfillState {
+                index_created_lower_bound,
+                retention_started: false,
+            }),
+        )
+    }
+
+    pub fn new_database_index(
+        name: GenericIndexName<T>,
+        developer_config: DeveloperDatabaseIndexConfig,
+        on_disk_state: DatabaseIndexState,
     ) -> Self {
         Self {
             name,
             config: IndexConfig::Database {
-                developer_config: DeveloperDatabaseIndexConfig { fields },
-                on_disk_state: DatabaseIndexState::Backfilling(DatabaseIndexBackfillState {
-                    index_created_lower_bound,
-                    retention_started: false,
-                }),
+                developer_config,
+                on_disk_state,
             },
         }
     }
@@ -132,16 +161,35 @@ impl<T: TableIdentifier> IndexMetadata<T> {
         }
     }
 
-    pub fn new_enabled(name: GenericIndexName<T>, fields: IndexedFields) -> Self {
+    pub fn new_backfilling_aggregate_index(
+        index_created_lower_bound: Timestamp,
+        name: GenericIndexName<T>,
+        developer_config: DeveloperAggregateIndexConfig,
+    ) -> Self {
         Self {
             name,
-            config: IndexConfig::Database {
-                developer_config: DeveloperDatabaseIndexConfig { fields },
-                on_disk_state: DatabaseIndexState::Enabled,
+            config: IndexConfig::Aggregate {
+                developer_config,
+                on_disk_state: DatabaseIndexState::Backfilling(DatabaseIndexBackfillState {
+                    index_created_lower_bound,
+                    retention_started: false,
+                }),
             },
         }
     }
 
+    pub fn new_enabled(name: GenericIndexName<T>, fields: IndexedFields) -> Self {
+        Self::new_database_index(
+            name,
+            DeveloperDatabaseIndexConfig {
+                fields,
+                unique: false,
+                filter: None,
+            },
+            DatabaseIndexState::Enabled,
+        )
+    }
+
     pub fn is_database_index(&self) -> bool {
         matches!(self.config, IndexConfig::Database { .. })
     }
@@ -154,6 +202,10 @@ impl<T: TableIdentifier> IndexMetadata<T> {
         matches!(self.config, IndexConfig::Vector { .. })
     }
 
+    pub fn is_aggregate_index(&self) -> bool {
+        matches!(self.config, IndexConfig::Aggregate { .. })
+    }
+
     pub fn map_table<U: TableIdentifier>(
         self,
         f: &impl Fn(T) -> anyhow::Result<U>,
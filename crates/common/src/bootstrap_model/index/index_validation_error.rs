@@ -16,6 +16,15 @@ pub fn empty_index(table_name: &TableName, index: &IndexSchema) -> ErrorMetadata
         format!("In table \"{table_name}\" index \"{index}\" must have at least one field."),
     )
 }
+pub fn empty_aggregate_index(table_name: &TableName, index: &IndexDescriptor) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "EmptyIndex",
+        format!(
+            "In table \"{table_name}\" aggregate index \"{index}\" must have at least one field \
+             or a value field."
+        ),
+    )
+}
 pub fn fields_not_unique_within_index(field: &FieldPath) -> ErrorMetadata {
     ErrorMetadata::bad_request(
         "FieldsNotUniqueWithinIndex",
@@ -1,3 +1,4 @@
+pub mod aggregate_index;
 pub mod database_index;
 mod developer_index_config;
 mod index_config;
@@ -1,4 +1,7 @@
-use std::sync::LazyLock;
+use std::{
+    sync::LazyLock,
+    time::Duration,
+};
 
 use serde::{
     Deserialize,
@@ -58,6 +61,18 @@ pub struct TableMetadata {
         proptest(value = "TableNamespace::Global")
     )]
     pub namespace: TableNamespace,
+    /// How long superseded revisions of the table's documents are kept,
+    /// overriding `DOCUMENT_RETENTION_DELAY` in either direction. A retention
+    /// shorter than `INDEX_RETENTION_DELAY` applies to the table's index
+    /// entries too.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::option::of(proptest::strategy::Strategy::prop_map(\
+                        proptest::prelude::any::<u32>(), |s| Duration::from_secs(s as u64)))"
+        )
+    )]
+    pub document_retention: Option<Duration>,
 }
 
 impl TableMetadata {
@@ -73,6 +88,7 @@ impl TableMetadata {
             number,
             state: TableState::Active,
             namespace,
+            document_retention: None,
         }
     }
 
@@ -87,6 +103,7 @@ impl TableMetadata {
             number,
             state,
             namespace,
+            document_retention: None,
         }
     }
 }
@@ -98,6 +115,11 @@ struct SerializedTableMetadata {
     state: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     namespace: Option<SerializedTableNamespace>,
+    #[serde(
+        rename = "documentRetentionSeconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    document_retention_seconds: Option<i64>,
 }
 
 impl TryFrom<TableMetadata> for SerializedTableMetadata {
@@ -113,6 +135,10 @@ impl TryFrom<TableMetadata> for SerializedTableMetadata {
                 TableState::Hidden => "hidden".to_owned(),
             },
             namespace: table_namespace_to_serialized(m.namespace)?,
+            document_retention_seconds: m
+                .document_retention
+                .map(|retention| i64::try_from(retention.as_secs()))
+                .transpose()?,
         })
     }
 }
@@ -131,6 +157,10 @@ impl TryFrom<SerializedTableMetadata> for TableMetadata {
                 s => anyhow::bail!("invalid table state {s}"),
             },
             namespace: table_namespace_from_serialized(m.namespace)?,
+            document_retention: m
+                .document_retention_seconds
+                .map(|seconds| anyhow::Ok(Duration::from_secs(u64::try_from(seconds)?)))
+                .transpose()?,
         })
     }
 }
@@ -204,6 +234,7 @@ mod tests {
                 number: 1017.try_into()?,
                 state: TableState::Hidden,
                 namespace: TableNamespace::Global,
+                document_retention: None,
             }
         );
         Ok(())
@@ -216,6 +247,7 @@ mod tests {
             number: 1017.try_into()?,
             state: TableState::Active,
             namespace: TableNamespace::Global,
+            document_retention: None,
         };
         let serialized: ConvexObject = table.try_into()?;
         assert_eq!(
@@ -1,6 +1,9 @@
-use std::collections::{
-    BTreeMap,
-    HashMap,
+use std::{
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    sync::Arc,
 };
 
 use async_trait::async_trait;
@@ -11,17 +14,24 @@ use futures::{
 };
 use http::StatusCode;
 use reqwest::{
+    dns::Resolve,
     redirect,
     Body,
     Proxy,
     Url,
 };
 
-use crate::http::{
-    HttpRequestStream,
-    HttpResponseStream,
+use crate::{
+    components::CanonicalizedComponentFunctionPath,
+    http::{
+        HttpRequestStream,
+        HttpResponseStream,
+    },
 };
 
+pub mod cassette;
+pub mod egress;
+
 /// Http client used for fetch syscall.
 #[async_trait]
 pub trait FetchClient: Send + Sync {
@@ -39,6 +49,15 @@ pub trait FetchClient: Send + Sync {
         request: HttpRequestStream,
         purpose: InternalFetchPurpose,
     ) -> anyhow::Result<HttpResponseStream>;
+
+    /// Returns a client to use for requests made by `path`, if this client
+    /// applies per-function limits.
+    fn for_function(
+        &self,
+        _path: &CanonicalizedComponentFunctionPath,
+    ) -> Option<Arc<dyn FetchClient>> {
+        None
+    }
 }
 
 #[derive(Clone)]
@@ -49,7 +68,30 @@ pub struct ProxiedFetchClient {
 
 impl ProxiedFetchClient {
     pub fn new(proxy_url: Option<Url>, client_id: String) -> Self {
-        let mut builder = reqwest::Client::builder().redirect(redirect::Policy::none());
+        Self::from_builder(reqwest::Client::builder(), proxy_url, client_id)
+    }
+
+    /// Like [`Self::new`], but hosts are resolved by `resolver`, which can
+    /// refuse to return addresses the client shouldn't connect to. Hosts in
+    /// requests sent through a proxy are resolved by the proxy instead.
+    pub fn with_dns_resolver<R: Resolve + 'static>(
+        proxy_url: Option<Url>,
+        client_id: String,
+        resolver: Arc<R>,
+    ) -> Self {
+        Self::from_builder(
+            reqwest::Client::builder().dns_resolver(resolver),
+            proxy_url,
+            client_id,
+        )
+    }
+
+    fn from_builder(
+        builder: reqwest::ClientBuilder,
+        proxy_url: Option<Url>,
+        client_id: String,
+    ) -> Self {
+        let mut builder = builder.redirect(redirect::Policy::none());
         // It's okay to panic on these errors, as they indicate a serious programming
         // error -- building the reqwest client is expected to be infallible.
         if let Some(proxy_url) = proxy_url {
@@ -0,0 +1,578 @@
+//! A [`FetchClient`] that records real HTTP interactions to a cassette file and
+//! replays them later, so tests that `fetch` don't need network access.
+//!
+//! Record a cassette once by wrapping a real client (e.g. a
+//! [`super::ProxiedFetchClient`]) with [`CassetteFetchClient::record`], check
+//! in the file, and replay it with [`CassetteFetchClient::replay`]. Each
+//! recorded interaction is replayed at most once, and requests matching
+//! several interactions get them in the order they were recorded.
+//!
+//! Credentials are kept out of checked in cassettes by recording the values of
+//! [`DEFAULT_REDACTED_HEADERS`] (or the headers passed to
+//! [`CassetteFetchClient::with_redacted_headers`]) as [`REDACTED`].
+use std::{
+    path::{
+        Path,
+        PathBuf,
+    },
+    str::FromStr,
+    sync::{
+        Arc,
+        LazyLock,
+    },
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{
+    stream::{
+        self,
+        BoxStream,
+        Fuse,
+    },
+    StreamExt,
+    TryStreamExt,
+};
+use http::{
+    header::{
+        AUTHORIZATION,
+        COOKIE,
+        PROXY_AUTHORIZATION,
+        SET_COOKIE,
+    },
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+    StatusCode,
+};
+use parking_lot::Mutex;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use url::Url;
+
+use super::{
+    FetchClient,
+    InternalFetchPurpose,
+};
+use crate::http::{
+    HttpRequestStream,
+    HttpResponseStream,
+};
+
+/// Recorded in place of the values of redacted headers.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers whose values are redacted unless overridden with
+/// [`CassetteFetchClient::with_redacted_headers`].
+pub static DEFAULT_REDACTED_HEADERS: LazyLock<Vec<HeaderName>> = LazyLock::new(|| {
+    vec![
+        AUTHORIZATION,
+        PROXY_AUTHORIZATION,
+        COOKIE,
+        SET_COOKIE,
+        HeaderName::from_static("api-key"),
+        HeaderName::from_static("x-api-key"),
+        HeaderName::from_static("x-goog-api-key"),
+    ]
+});
+
+/// Which parts of a request must be equal to a recorded request for it to
+/// be replayed. By default, requests match on method, URL and body.
+#[derive(Clone, Debug)]
+pub struct CassetteMatcher {
+    pub method: bool,
+    pub url: bool,
+    /// Only these headers are compared, since most requests carry headers
+    /// that change between runs (e.g. dates or request IDs).
+    pub headers: Vec<HeaderName>,
+    pub body: bool,
+}
+
+impl Default for CassetteMatcher {
+    fn default() -> Self {
+        Self {
+            method: true,
+            url: true,
+            headers: vec![],
+            body: true,
+        }
+    }
+}
+
+impl CassetteMatcher {
+    fn matches(&self, request: &RecordedRequest, recorded: &RecordedRequest) -> bool {
+        (!self.method || request.method == recorded.method)
+            && (!self.url || request.url == recorded.url)
+            && (!self.body || request.body == recorded.body)
+            && self.headers.iter().all(|name| {
+                let values = |r: &RecordedRequest| -> Vec<String> {
+                    r.headers
+                        .iter()
+                        .filter(|(n, _)| n.eq_ignore_ascii_case(name.as_str()))
+                        .map(|(_, v)| v.clone())
+                        .collect()
+                };
+                values(request) == values(recorded)
+            })
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: RecordedBody,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    url: Option<String>,
+    /// Kept as separate chunks so streamed responses replay the same way.
+    chunks: Vec<RecordedBody>,
+}
+
+/// Bodies are stored as text when possible to keep cassettes readable.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RecordedBody {
+    Text(String),
+    Base64(String),
+}
+
+impl From<&[u8]> for RecordedBody {
+    fn from(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(s) => Self::Text(s.to_owned()),
+            Err(_) => Self::Base64(base64::encode(bytes)),
+        }
+    }
+}
+
+impl TryFrom<&RecordedBody> for Bytes {
+    type Error = anyhow::Error;
+
+    fn try_from(body: &RecordedBody) -> anyhow::Result<Self> {
+        Ok(match body {
+            RecordedBody::Text(s) => Bytes::from(s.clone()),
+            RecordedBody::Base64(s) => Bytes::from(base64::decode(s)?),
+        })
+    }
+}
+
+fn record_headers(
+    headers: &HeaderMap,
+    redacted: &[HeaderName],
+) -> anyhow::Result<Vec<(String, String)>> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            if redacted.contains(name) {
+                return Ok((name.to_string(), REDACTED.to_owned()));
+            }
+            let value = value
+                .to_str()
+                .with_context(|| format!("Can't record non-ASCII header {name}"))?;
+            Ok((name.to_string(), value.to_owned()))
+        })
+        .collect()
+}
+
+fn replay_headers(headers: &[(String, String)]) -> anyhow::Result<HeaderMap> {
+    let mut out = HeaderMap::new();
+    for (name, value) in headers {
+        out.append(HeaderName::from_str(name)?, HeaderValue::from_str(value)?);
+    }
+    Ok(out)
+}
+
+enum Mode {
+    Record {
+        inner: Arc<dyn FetchClient>,
+        // Held while writing the file so concurrent saves can't overwrite a newer
+        // cassette with an older one.
+        cassette: Arc<tokio::sync::Mutex<Cassette>>,
+    },
+    Replay {
+        interactions: Mutex<Vec<Option<Interaction>>>,
+    },
+}
+
+pub struct CassetteFetchClient {
+    path: PathBuf,
+    mode: Mode,
+    matcher: CassetteMatcher,
+    redacted_headers: Vec<HeaderName>,
+}
+
+impl CassetteFetchClient {
+    /// Sends requests through `inner` and (over)writes every interaction to
+    /// the cassette at `path` once its response body has been read to the end.
+    pub fn record(inner: Arc<dyn FetchClient>, path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_owned(),
+            mode: Mode::Record {
+                inner,
+                cassette: Arc::new(tokio::sync::Mutex::new(Cassette::default())),
+            },
+            matcher: CassetteMatcher::default(),
+            redacted_headers: DEFAULT_REDACTED_HEADERS.clone(),
+        }
+    }
+
+    /// Answers requests from the cassette at `path` without any network
+    /// access. Requests that don't match an unused recorded interaction fail.
+    pub fn replay(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let contents = std::fs::read(&path)
+            .with_context(|| format!("Failed to read cassette {}", path.display()))?;
+        let cassette: Cassette = serde_json::from_slice(&contents)
+            .with_context(|| format!("Invalid cassette {}", path.display()))?;
+        Ok(Self {
+            path,
+            mode: Mode::Replay {
+                interactions: Mutex::new(cassette.interactions.into_iter().map(Some).collect()),
+            },
+            matcher: CassetteMatcher::default(),
+            redacted_headers: DEFAULT_REDACTED_HEADERS.clone(),
+        })
+    }
+
+    pub fn with_matcher(mut self, matcher: CassetteMatcher) -> Self {
+        self.matcher = matcher;
+        self
+    }
+
+    /// Replaces [`DEFAULT_REDACTED_HEADERS`]. Requests are redacted the same
+    /// way when replaying, so matching on a redacted header only checks that
+    /// it's present.
+    pub fn with_redacted_headers(mut self, headers: Vec<HeaderName>) -> Self {
+        self.redacted_headers = headers;
+        self
+    }
+
+    async fn handle(
+        &self,
+        request: HttpRequestStream,
+        purpose: Option<InternalFetchPurpose>,
+    ) -> anyhow::Result<HttpResponseStream> {
+        let HttpRequestStream {
+            headers,
+            url,
+            method,
+            body,
+        } = request;
+        let body: Vec<Bytes> = body.try_collect().await?;
+        let body = body.concat();
+        let recorded_request = RecordedRequest {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: record_headers(&headers, &self.redacted_headers)?,
+            body: RecordedBody::from(&body[..]),
+        };
+        match &self.mode {
+            Mode::Record { inner, cassette } => {
+                let request = HttpRequestStream {
+                    headers,
+                    url,
+                    method,
+                    body: Box::pin(stream::once(async move { Ok(Bytes::from(body)) })),
+                };
+                let response = match purpose {
+                    Some(purpose) => inner.internal_fetch(request, purpose).await?,
+                    None => inner.fetch(request).await?,
+                };
+                let recorded_response = RecordedResponse {
+                    status: response.status.as_u16(),
+                    headers: record_headers(&response.headers, &self.redacted_headers)?,
+                    url: response.url.as_ref().map(|url| url.to_string()),
+                    chunks: vec![],
+                };
+                let Some(body) = response.body else {
+                    save(&self.path, cassette, recorded_request, recorded_response).await?;
+                    return Ok(response);
+                };
+                // Pass chunks through as they arrive, and save the interaction
+                // once the body completes.
+                let recording = Recording {
+                    body: body.fuse(),
+                    request: Some(recorded_request),
+                    response: recorded_response,
+                    path: self.path.clone(),
+                    cassette: cassette.clone(),
+                };
+                let body = stream::unfold(recording, |mut recording| async move {
+                    match recording.body.next().await {
+                        Some(Ok(chunk)) => {
+                            recording
+                                .response
+                                .chunks
+                                .push(RecordedBody::from(&chunk[..]));
+                            Some((Ok(chunk), recording))
+                        },
+                        Some(Err(e)) => {
+                            // Don't record responses we couldn't read fully.
+                            recording.request = None;
+                            Some((Err(e), recording))
+                        },
+                        None => {
+                            let request = recording.request.take()?;
+                            let response = recording.response.clone();
+                            match save(&recording.path, &recording.cassette, request, response)
+                                .await
+                            {
+                                Ok(()) => None,
+                                Err(e) => Some((Err(e), recording)),
+                            }
+                        },
+                    }
+                })
+                .boxed();
+                Ok(HttpResponseStream {
+                    body: Some(body),
+                    ..response
+                })
+            },
+            Mode::Replay { interactions } => {
+                let interaction = {
+                    let mut interactions = interactions.lock();
+                    interactions
+                        .iter_mut()
+                        .find(|i| {
+                            i.as_ref().is_some_and(|i| {
+                                self.matcher.matches(&recorded_request, &i.request)
+                            })
+                        })
+                        .and_then(|i| i.take())
+                        .with_context(|| {
+                            format!(
+                                "No unused interaction in cassette {} matches {} {}",
+                                self.path.display(),
+                                recorded_request.method,
+                                recorded_request.url,
+                            )
+                        })?
+                };
+                let response = interaction.response;
+                let chunks = response
+                    .chunks
+                    .iter()
+                    .map(Bytes::try_from)
+                    .collect::<Vec<_>>();
+                Ok(HttpResponseStream {
+                    status: StatusCode::from_u16(response.status)?,
+                    headers: replay_headers(&response.headers)?,
+                    url: response.url.map(|url| Url::parse(&url)).transpose()?,
+                    body: Some(stream::iter(chunks).boxed()),
+                })
+            },
+        }
+    }
+}
+
+struct Recording {
+    body: Fuse<BoxStream<'static, anyhow::Result<Bytes>>>,
+    /// `None` once the interaction has been saved or shouldn't be.
+    request: Option<RecordedRequest>,
+    response: RecordedResponse,
+    path: PathBuf,
+    cassette: Arc<tokio::sync::Mutex<Cassette>>,
+}
+
+async fn save(
+    path: &Path,
+    cassette: &tokio::sync::Mutex<Cassette>,
+    request: RecordedRequest,
+    response: RecordedResponse,
+) -> anyhow::Result<()> {
+    let mut cassette = cassette.lock().await;
+    cassette
+        .interactions
+        .push(Interaction { request, response });
+    tokio::fs::write(path, serde_json::to_vec_pretty(&*cassette)?)
+        .await
+        .with_context(|| format!("Failed to write cassette {}", path.display()))
+}
+
+#[async_trait]
+impl FetchClient for CassetteFetchClient {
+    async fn fetch(&self, request: HttpRequestStream) -> anyhow::Result<HttpResponseStream> {
+        self.handle(request, None).await
+    }
+
+    async fn internal_fetch(
+        &self,
+        request: HttpRequestStream,
+        purpose: InternalFetchPurpose,
+    ) -> anyhow::Result<HttpResponseStream> {
+        self.handle(request, Some(purpose)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use futures::{
+        stream,
+        FutureExt,
+        StreamExt,
+        TryStreamExt,
+    };
+    use http::{
+        header::{
+            AUTHORIZATION,
+            SET_COOKIE,
+        },
+        HeaderMap,
+        HeaderValue,
+        Method,
+        StatusCode,
+    };
+
+    use super::{
+        CassetteFetchClient,
+        CassetteMatcher,
+        REDACTED,
+    };
+    use crate::http::{
+        fetch::{
+            FetchClient,
+            StaticFetchClient,
+        },
+        HttpRequest,
+        HttpResponseStream,
+    };
+
+    fn post(body: &str) -> HttpRequest {
+        HttpRequest {
+            headers: HeaderMap::new(),
+            url: "https://example.com/stream".parse().unwrap(),
+            method: Method::POST,
+            body: Some(body.as_bytes().to_vec()),
+        }
+    }
+
+    async fn fetch_chunks(
+        client: &CassetteFetchClient,
+        request: HttpRequest,
+    ) -> anyhow::Result<(StatusCode, Vec<Bytes>)> {
+        let response = client.fetch(request.into()).await?;
+        let chunks = response.body.unwrap().try_collect().await?;
+        Ok((response.status, chunks))
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() -> anyhow::Result<()> {
+        let chunks = vec![Bytes::from("first"), Bytes::from(vec![0xff, 0x00])];
+        let mut inner = StaticFetchClient::new();
+        let chunks_ = chunks.clone();
+        inner.register_http_route(
+            "https://example.com/stream".parse()?,
+            Method::POST,
+            move |_| {
+                let chunks = chunks_.clone();
+                async move {
+                    Ok(HttpResponseStream {
+                        status: StatusCode::ACCEPTED,
+                        headers: HeaderMap::new(),
+                        url: None,
+                        body: Some(stream::iter(chunks.into_iter().map(Ok)).boxed()),
+                    })
+                }
+                .boxed()
+            },
+        );
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("cassette.json");
+
+        let recorder = CassetteFetchClient::record(Arc::new(inner), &path);
+        assert_eq!(
+            fetch_chunks(&recorder, post("hello")).await?,
+            (StatusCode::ACCEPTED, chunks.clone())
+        );
+
+        // Streamed chunks are replayed as they were recorded, once.
+        let replayer = CassetteFetchClient::replay(&path)?;
+        assert!(fetch_chunks(&replayer, post("goodbye")).await.is_err());
+        assert_eq!(
+            fetch_chunks(&replayer, post("hello")).await?,
+            (StatusCode::ACCEPTED, chunks.clone())
+        );
+        assert!(fetch_chunks(&replayer, post("hello")).await.is_err());
+
+        let replayer = CassetteFetchClient::replay(&path)?.with_matcher(CassetteMatcher {
+            body: false,
+            ..Default::default()
+        });
+        assert_eq!(
+            fetch_chunks(&replayer, post("goodbye")).await?,
+            (StatusCode::ACCEPTED, chunks)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_redacts_credentials() -> anyhow::Result<()> {
+        let mut inner = StaticFetchClient::new();
+        inner.register_http_route("https://example.com/stream".parse()?, Method::POST, |_| {
+            async move {
+                let mut headers = HeaderMap::new();
+                headers.insert(SET_COOKIE, HeaderValue::from_static("session=hunter2"));
+                Ok(HttpResponseStream {
+                    status: StatusCode::OK,
+                    headers,
+                    url: None,
+                    body: Some(stream::once(async { Ok(Bytes::from("ok")) }).boxed()),
+                })
+            }
+            .boxed()
+        });
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("cassette.json");
+        let with_key = |key: &'static str| {
+            let mut request = post("hello");
+            request
+                .headers
+                .insert(AUTHORIZATION, HeaderValue::from_static(key));
+            request
+                .headers
+                .insert("x-api-key", HeaderValue::from_static(key));
+            request
+        };
+
+        let recorder = CassetteFetchClient::record(Arc::new(inner), &path);
+        fetch_chunks(&recorder, with_key("Bearer sk-secret")).await?;
+        let contents = std::fs::read_to_string(&path)?;
+        assert!(!contents.contains("sk-secret"));
+        assert!(!contents.contains("hunter2"));
+        assert!(contents.contains(REDACTED));
+
+        // Redacted headers match as long as they're present.
+        let replayer = CassetteFetchClient::replay(&path)?.with_matcher(CassetteMatcher {
+            headers: vec![AUTHORIZATION],
+            ..Default::default()
+        });
+        assert!(fetch_chunks(&replayer, post("hello")).await.is_err());
+        let response = replayer.fetch(with_key("Bearer other").into()).await?;
+        assert_eq!(response.headers.get(SET_COOKIE).unwrap(), REDACTED);
+        Ok(())
+    }
+}
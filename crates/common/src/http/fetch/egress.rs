@@ -0,0 +1,792 @@
+//! Deployment-level restrictions on where actions can `fetch`.
+//!
+//! The policy is enforced by [`EgressPolicyFetchClient`] for actions running in
+//! the isolate, and is sent along with every request to the node executor,
+//! which enforces the same rules for Node actions. Node actions run arbitrary
+//! code in the executor's process, so there it's a best-effort check; see
+//! `node-executor/src/egress.ts` for what it doesn't cover.
+use std::{
+    fmt,
+    net::{
+        IpAddr,
+        Ipv4Addr,
+        Ipv6Addr,
+        SocketAddr,
+    },
+    num::NonZeroU32,
+    str::FromStr,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use errors::ErrorMetadata;
+use futures::{
+    stream,
+    StreamExt,
+};
+use governor::Quota;
+use http::header::CONTENT_LENGTH;
+use metrics::{
+    log_counter_with_labels,
+    register_convex_counter,
+    StaticMetricLabel,
+};
+use reqwest::dns::{
+    Addrs,
+    Name,
+    Resolve,
+    Resolving,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use url::Url;
+
+use super::{
+    FetchClient,
+    InternalFetchPurpose,
+    ProxiedFetchClient,
+};
+use crate::{
+    components::CanonicalizedComponentFunctionPath,
+    http::{
+        HttpRequestStream,
+        HttpResponseStream,
+    },
+    runtime::{
+        new_keyed_rate_limiter,
+        KeyedRateLimiter,
+        Runtime,
+    },
+};
+
+/// Where actions are allowed to send requests. Deny rules always win over
+/// allow rules.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EgressPolicy {
+    /// Host patterns like `api.example.com` or `*.example.com`. If this or
+    /// `allowed_cidrs` is nonempty, requests must match one of them.
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    #[serde(default)]
+    pub denied_hosts: Vec<String>,
+    /// Allowed address ranges. Addresses in these ranges are allowed even if
+    /// they're private.
+    #[serde(default)]
+    pub allowed_cidrs: Vec<Cidr>,
+    #[serde(default)]
+    pub denied_cidrs: Vec<Cidr>,
+    /// If nonempty, requests must be to one of these ports.
+    #[serde(default)]
+    pub allowed_ports: Vec<u16>,
+    /// Block loopback, private, link-local and other non-public addresses
+    /// unless they're in `allowed_cidrs`.
+    #[serde(default = "default_block_private_addresses")]
+    pub block_private_addresses: bool,
+    /// Responses with larger bodies fail once they reach the limit.
+    #[serde(default)]
+    pub max_response_bytes: Option<u64>,
+    /// Maximum number of requests each function can start per minute.
+    #[serde(default)]
+    pub max_requests_per_minute_per_function: Option<NonZeroU32>,
+}
+
+fn default_block_private_addresses() -> bool {
+    true
+}
+
+/// An IP address range like `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        let (network, addr, bits) = match (self.addr, addr) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => {
+                (u32::from(network) as u128, u32::from(addr) as u128, 32)
+            },
+            (IpAddr::V6(network), IpAddr::V6(addr)) => (u128::from(network), u128::from(addr), 128),
+            (IpAddr::V6(_), IpAddr::V4(addr)) => {
+                return self.contains(IpAddr::V6(addr.to_ipv6_mapped()))
+            },
+            (IpAddr::V4(_), IpAddr::V6(addr)) => match addr.to_ipv4_mapped() {
+                Some(addr) => return self.contains(IpAddr::V4(addr)),
+                None => return false,
+            },
+        };
+        let shift = bits - self.prefix_len as u32;
+        shift >= bits || (network >> shift) == (addr >> shift)
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (addr.parse::<IpAddr>()?, Some(prefix_len.parse::<u8>()?)),
+            None => (s.parse::<IpAddr>()?, None),
+        };
+        let max_prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len = prefix_len.unwrap_or(max_prefix_len);
+        anyhow::ensure!(
+            prefix_len <= max_prefix_len,
+            "Invalid prefix length in CIDR {s}"
+        );
+        Ok(Self { addr, prefix_len })
+    }
+}
+
+impl TryFrom<String> for Cidr {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> anyhow::Result<Self> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl From<Cidr> for String {
+    fn from(cidr: Cidr) -> Self {
+        cidr.to_string()
+    }
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    let host = host.trim_end_matches('.');
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .strip_suffix(suffix)
+            .is_some_and(|prefix| prefix.ends_with('.')),
+        None => pattern.eq_ignore_ascii_case(host),
+    }
+}
+
+/// Addresses that aren't reachable on the public internet.
+fn is_private(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(addr) => is_private_v4(addr),
+        IpAddr::V6(addr) => match addr.to_ipv4_mapped() {
+            Some(addr) => is_private_v4(addr),
+            None => is_private_v6(addr),
+        },
+    }
+}
+
+fn is_private_v4(addr: Ipv4Addr) -> bool {
+    let [a, b, ..] = addr.octets();
+    addr.is_private()
+        || addr.is_loopback()
+        || addr.is_link_local()
+        || addr.is_unspecified()
+        || addr.is_broadcast()
+        || addr.is_multicast()
+        || addr.is_documentation()
+        // "This network" and carrier-grade NAT.
+        || a == 0
+        || (a == 100 && (b & 0b1100_0000) == 64)
+}
+
+fn is_private_v6(addr: Ipv6Addr) -> bool {
+    let first = addr.segments()[0];
+    addr.is_loopback()
+        || addr.is_unspecified()
+        || addr.is_multicast()
+        // Unique local and link-local.
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+}
+
+register_convex_counter!(
+    COMMON_EGRESS_POLICY_VIOLATIONS_TOTAL,
+    "Count of outbound requests blocked by the egress policy",
+    &["reason"]
+);
+
+fn violation(reason: &'static str, msg: String) -> anyhow::Error {
+    log_counter_with_labels(
+        &COMMON_EGRESS_POLICY_VIOLATIONS_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("reason", reason)],
+    );
+    tracing::warn!("Blocked outbound request: {msg}");
+    ErrorMetadata::bad_request(
+        "EgressPolicyViolation",
+        format!("{msg}. Outbound requests are restricted by this deployment's egress policy."),
+    )
+    .into()
+}
+
+impl EgressPolicy {
+    fn allows_any_host(&self) -> bool {
+        self.allowed_hosts.is_empty() && self.allowed_cidrs.is_empty()
+    }
+
+    /// Checks everything about `url` that doesn't depend on DNS. Returns
+    /// whether the host itself is on the allowlist.
+    pub fn check_url(&self, url: &Url) -> anyhow::Result<bool> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(violation(
+                "scheme",
+                format!("Requests with scheme {} aren't allowed", url.scheme()),
+            ));
+        }
+        let host = url.host_str().context("URL has no host")?;
+        let port = url.port_or_known_default().context("URL has no port")?;
+        if !self.allowed_ports.is_empty() && !self.allowed_ports.contains(&port) {
+            return Err(violation(
+                "port",
+                format!("Requests to port {port} aren't allowed"),
+            ));
+        }
+        if self.denied_hosts.iter().any(|p| host_matches(p, host)) {
+            return Err(violation(
+                "host",
+                format!("Requests to {host} aren't allowed"),
+            ));
+        }
+        Ok(self.host_allowed(host))
+    }
+
+    fn host_allowed(&self, host: &str) -> bool {
+        self.allowed_hosts.iter().any(|p| host_matches(p, host))
+    }
+
+    /// Like [`Self::check`], but only checks the host's addresses if it's an
+    /// IP address. Other hosts must be checked when they're resolved.
+    pub fn check_without_dns(&self, url: &Url) -> anyhow::Result<()> {
+        let host_allowed = self.check_url(url)?;
+        let host = url.host_str().context("URL has no host")?;
+        match url.host() {
+            Some(url::Host::Ipv4(addr)) => self.check_address(host, addr.into(), host_allowed),
+            Some(url::Host::Ipv6(addr)) => self.check_address(host, addr.into(), host_allowed),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks an address `host` resolved to.
+    pub fn check_address(
+        &self,
+        host: &str,
+        addr: IpAddr,
+        host_allowed: bool,
+    ) -> anyhow::Result<()> {
+        if self.denied_cidrs.iter().any(|c| c.contains(addr)) {
+            return Err(violation(
+                "address",
+                format!("Requests to {host} ({addr}) aren't allowed"),
+            ));
+        }
+        let address_allowed = self.allowed_cidrs.iter().any(|c| c.contains(addr));
+        if !host_allowed && !address_allowed && !self.allows_any_host() {
+            return Err(violation(
+                "not_allowed",
+                format!("{host} ({addr}) isn't on the list of allowed hosts"),
+            ));
+        }
+        if self.block_private_addresses && is_private(addr) && !address_allowed {
+            return Err(violation(
+                "private_address",
+                format!("Requests to private address {addr} ({host}) aren't allowed"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks `url` and every address its host resolves to.
+    pub async fn check(&self, url: &Url) -> anyhow::Result<()> {
+        let host_allowed = self.check_url(url)?;
+        let host = url.host_str().context("URL has no host")?;
+        let port = url.port_or_known_default().context("URL has no port")?;
+        let addrs: Vec<IpAddr> = match url.host() {
+            Some(url::Host::Ipv4(addr)) => vec![addr.into()],
+            Some(url::Host::Ipv6(addr)) => vec![addr.into()],
+            _ => tokio::net::lookup_host((host, port))
+                .await
+                .with_context(|| format!("Failed to resolve {host}"))?
+                .map(|addr| addr.ip())
+                .collect(),
+        };
+        for addr in addrs {
+            self.check_address(host, addr, host_allowed)?;
+        }
+        Ok(())
+    }
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Resolves hosts for [`EgressPolicyFetchClient`] and fails unless the policy
+/// allows every address, so connections only go to addresses that were
+/// checked.
+struct EgressPolicyResolver {
+    policy: Arc<EgressPolicy>,
+    /// The proxy's host, which isn't subject to the policy.
+    proxy_host: Option<String>,
+}
+
+impl Resolve for EgressPolicyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = self.policy.clone();
+        let exempt = self.proxy_host.as_deref() == Some(name.as_str());
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0)).await?.collect();
+            if !exempt {
+                let host_allowed = policy.host_allowed(host);
+                for addr in &addrs {
+                    // Keep the `ErrorMetadata` so `fetch` can surface it.
+                    policy
+                        .check_address(host, addr.ip(), host_allowed)
+                        .map_err(|e| -> BoxError {
+                            match e.downcast::<ErrorMetadata>() {
+                                Ok(e) => Box::new(e),
+                                Err(e) => e.into(),
+                            }
+                        })?;
+                }
+            }
+            Ok::<Addrs, BoxError>(Box::new(addrs.into_iter()))
+        })
+    }
+}
+
+/// Enforces an [`EgressPolicy`] on requests made by actions. Internal fetches
+/// aren't restricted.
+///
+/// Without a proxy, hosts are resolved and checked once and the connection
+/// goes to the checked addresses. With a proxy, the proxy resolves hosts
+/// itself, so it also needs to block private addresses to protect against
+/// DNS rebinding.
+pub struct EgressPolicyFetchClient<RT: Runtime> {
+    inner: Arc<dyn FetchClient>,
+    policy: Arc<EgressPolicy>,
+    rate_limiter: Option<Arc<KeyedRateLimiter<String, RT>>>,
+    function: Option<String>,
+    /// Whether to check the addresses hosts resolve to before fetching,
+    /// because `inner` doesn't check them when it connects.
+    resolve_before_fetch: bool,
+}
+
+impl<RT: Runtime> EgressPolicyFetchClient<RT> {
+    pub fn new(rt: RT, proxy_url: Option<Url>, client_id: String, policy: EgressPolicy) -> Self {
+        let policy = Arc::new(policy);
+        let resolver = EgressPolicyResolver {
+            policy: policy.clone(),
+            proxy_host: proxy_url
+                .as_ref()
+                .and_then(|url| url.host_str())
+                .map(str::to_owned),
+        };
+        let resolve_before_fetch = proxy_url.is_some();
+        let inner = Arc::new(ProxiedFetchClient::with_dns_resolver(
+            proxy_url,
+            client_id,
+            Arc::new(resolver),
+        ));
+        Self::with_inner(rt, inner, policy, resolve_before_fetch)
+    }
+
+    fn with_inner(
+        rt: RT,
+        inner: Arc<dyn FetchClient>,
+        policy: Arc<EgressPolicy>,
+        resolve_before_fetch: bool,
+    ) -> Self {
+        let rate_limiter = policy
+            .max_requests_per_minute_per_function
+            .map(|limit| Arc::new(new_keyed_rate_limiter(rt, Quota::per_minute(limit))));
+        Self {
+            inner,
+            policy,
+            rate_limiter,
+            function: None,
+            resolve_before_fetch,
+        }
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> FetchClient for EgressPolicyFetchClient<RT> {
+    async fn fetch(&self, request: HttpRequestStream) -> anyhow::Result<HttpResponseStream> {
+        if let (Some(rate_limiter), Some(function)) = (&self.rate_limiter, &self.function) {
+            if rate_limiter.check_key(function).is_err() {
+                return Err(violation(
+                    "rate_limit",
+                    format!("{function} exceeded its limit of outbound requests per minute"),
+                ));
+            }
+        }
+        if self.resolve_before_fetch {
+            self.policy.check(&request.url).await?;
+        } else {
+            self.policy.check_without_dns(&request.url)?;
+        }
+        let mut response = self.inner.fetch(request).await.map_err(|e| {
+            // Surface policy violations from the resolver rather than the
+            // connection error wrapping them.
+            match e.chain().find_map(|e| e.downcast_ref::<ErrorMetadata>()) {
+                Some(em) => em.clone().into(),
+                None => e,
+            }
+        })?;
+        let Some(max_bytes) = self.policy.max_response_bytes else {
+            return Ok(response);
+        };
+        let content_length = response
+            .headers
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        let too_large = move || {
+            violation(
+                "response_size",
+                format!("Response is larger than the limit of {max_bytes} bytes"),
+            )
+        };
+        if content_length.is_some_and(|len| len > max_bytes) {
+            return Err(too_large());
+        }
+        if let Some(body) = response.body.take() {
+            let body = stream::unfold(
+                (body, 0u64, false),
+                move |(mut body, mut read, failed)| async move {
+                    if failed {
+                        return None;
+                    }
+                    match body.next().await? {
+                        Ok(chunk) => {
+                            read += chunk.len() as u64;
+                            if read > max_bytes {
+                                Some((Err(too_large()), (body, read, true)))
+                            } else {
+                                Some((Ok(chunk), (body, read, false)))
+                            }
+                        },
+                        Err(e) => Some((Err(e), (body, read, failed))),
+                    }
+                },
+            );
+            response.body = Some(body.boxed());
+        }
+        Ok(response)
+    }
+
+    async fn internal_fetch(
+        &self,
+        request: HttpRequestStream,
+        purpose: InternalFetchPurpose,
+    ) -> anyhow::Result<HttpResponseStream> {
+        self.inner.internal_fetch(request, purpose).await
+    }
+
+    fn for_function(
+        &self,
+        path: &CanonicalizedComponentFunctionPath,
+    ) -> Option<Arc<dyn FetchClient>> {
+        let function = if path.component.is_root() {
+            path.udf_path.to_string()
+        } else {
+            format!("{}/{}", String::from(path.component.clone()), path.udf_path)
+        };
+        Some(Arc::new(Self {
+            inner: self.inner.clone(),
+            policy: self.policy.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            function: Some(function),
+            resolve_before_fetch: self.resolve_before_fetch,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::IpAddr,
+        sync::Arc,
+        time::Duration,
+    };
+
+    use errors::ErrorMetadataAnyhowExt;
+    use futures::FutureExt;
+    use http::{
+        header::CONTENT_LENGTH,
+        HeaderMap,
+        Method,
+        StatusCode,
+    };
+    use reqwest::dns::{
+        Name,
+        Resolve,
+    };
+    use url::Url;
+
+    use super::{
+        Cidr,
+        EgressPolicy,
+        EgressPolicyFetchClient,
+        EgressPolicyResolver,
+    };
+    use crate::{
+        components::CanonicalizedComponentFunctionPath,
+        http::{
+            fetch::{
+                FetchClient,
+                StaticFetchClient,
+            },
+            HttpRequest,
+            HttpRequestStream,
+            HttpResponse,
+            HttpResponseStream,
+        },
+        runtime::testing::TestDriver,
+    };
+
+    fn static_client() -> anyhow::Result<Arc<dyn FetchClient>> {
+        let mut client = StaticFetchClient::new();
+        for (path, len, declare_length) in [
+            ("small", 5, false),
+            ("large", 100, false),
+            ("declared", 100, true),
+        ] {
+            client.register_http_route(
+                format!("https://api.example.com/{path}").parse()?,
+                Method::GET,
+                move |_: HttpRequestStream| {
+                    async move {
+                        let mut headers = HeaderMap::new();
+                        if declare_length {
+                            headers.insert(CONTENT_LENGTH, len.into());
+                        }
+                        let body = vec![b'a'; len];
+                        Ok(HttpResponse::new(StatusCode::OK, headers, Some(body), None).into())
+                    }
+                    .boxed()
+                },
+            );
+        }
+        Ok(Arc::new(client))
+    }
+
+    async fn get(client: &dyn FetchClient, url: &str) -> anyhow::Result<Vec<u8>> {
+        let request = HttpRequest {
+            headers: HeaderMap::new(),
+            url: url.parse()?,
+            method: Method::GET,
+            body: None,
+        };
+        let response: HttpResponseStream = client.fetch(request.into()).await?;
+        Ok(response
+            .into_http_response()
+            .await?
+            .body
+            .unwrap_or_default())
+    }
+
+    fn function_path(
+        component: &str,
+        udf_path: &str,
+    ) -> anyhow::Result<CanonicalizedComponentFunctionPath> {
+        Ok(CanonicalizedComponentFunctionPath {
+            component: component.parse()?,
+            udf_path: udf_path.parse()?,
+        })
+    }
+
+    fn check(policy: &EgressPolicy, url: &str, addr: &str) -> anyhow::Result<()> {
+        let url: Url = url.parse()?;
+        let host_allowed = policy.check_url(&url)?;
+        policy.check_address(
+            url.host_str().unwrap(),
+            addr.parse::<IpAddr>()?,
+            host_allowed,
+        )
+    }
+
+    #[test]
+    fn test_cidr() -> anyhow::Result<()> {
+        let cidr: Cidr = "10.0.0.0/8".parse()?;
+        assert!(cidr.contains("10.1.2.3".parse()?));
+        assert!(cidr.contains("::ffff:10.1.2.3".parse()?));
+        assert!(!cidr.contains("11.0.0.1".parse()?));
+        let cidr: Cidr = "2001:db8::/32".parse()?;
+        assert!(cidr.contains("2001:db8::1".parse()?));
+        assert!(!cidr.contains("2001:db9::1".parse()?));
+        assert!("0.0.0.0/0".parse::<Cidr>()?.contains("8.8.8.8".parse()?));
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert_eq!(serde_json::to_string(&cidr)?, "\"2001:db8::/32\"");
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_policy_blocks_private_addresses() -> anyhow::Result<()> {
+        let policy: EgressPolicy = serde_json::from_str("{}")?;
+        check(&policy, "https://example.com", "93.184.216.34")?;
+        for addr in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "::1",
+            "fd00::1",
+        ] {
+            assert!(
+                check(&policy, "https://example.com", addr).is_err(),
+                "{addr}"
+            );
+        }
+        assert!(check(&policy, "ftp://example.com", "93.184.216.34").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_allow_and_deny_lists() -> anyhow::Result<()> {
+        let policy: EgressPolicy = serde_json::from_value(serde_json::json!({
+            "allowedHosts": ["*.example.com"],
+            "deniedHosts": ["secret.example.com"],
+            "allowedCidrs": ["10.1.0.0/16"],
+            "deniedCidrs": ["93.184.216.0/24"],
+            "allowedPorts": [443, 8443],
+        }))?;
+        check(&policy, "https://api.example.com", "1.2.3.4")?;
+        check(&policy, "https://api.example.com:8443", "1.2.3.4")?;
+        check(&policy, "https://internal.corp", "10.1.2.3")?;
+        // Denied host, address, and port.
+        assert!(check(&policy, "https://secret.example.com", "1.2.3.4").is_err());
+        assert!(check(&policy, "https://api.example.com", "93.184.216.34").is_err());
+        assert!(check(&policy, "http://api.example.com", "1.2.3.4").is_err());
+        // Not on the allowlist.
+        assert!(check(&policy, "https://example.org", "1.2.3.4").is_err());
+        assert!(check(&policy, "https://example.com", "1.2.3.4").is_err());
+        // Allowed hosts still can't resolve to private addresses.
+        assert!(check(&policy, "https://api.example.com", "127.0.0.1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_response_bytes() -> anyhow::Result<()> {
+        let td = TestDriver::new();
+        let policy: EgressPolicy = serde_json::from_value(serde_json::json!({
+            "maxResponseBytes": 10,
+        }))?;
+        let client =
+            EgressPolicyFetchClient::with_inner(td.rt(), static_client()?, Arc::new(policy), false);
+        td.run_until(async {
+            assert_eq!(
+                get(&client, "https://api.example.com/small").await?.len(),
+                5
+            );
+            for path in ["large", "declared"] {
+                let err = get(&client, &format!("https://api.example.com/{path}"))
+                    .await
+                    .unwrap_err();
+                assert!(err.is_bad_request(), "{err:?}");
+                assert_eq!(err.short_msg(), "EgressPolicyViolation");
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_rate_limit_per_function() -> anyhow::Result<()> {
+        let td = TestDriver::new();
+        let rt = td.rt();
+        let policy: EgressPolicy = serde_json::from_value(serde_json::json!({
+            "maxRequestsPerMinutePerFunction": 2,
+        }))?;
+        let client = EgressPolicyFetchClient::with_inner(
+            rt.clone(),
+            static_client()?,
+            Arc::new(policy),
+            false,
+        );
+        let url = "https://api.example.com/small";
+        td.run_until(async {
+            let a = client
+                .for_function(&function_path("", "a.js:run")?)
+                .unwrap();
+            let b = client
+                .for_function(&function_path("", "b.js:run")?)
+                .unwrap();
+            // Same function path, but in a component.
+            let component_a = client
+                .for_function(&function_path("child", "a.js:run")?)
+                .unwrap();
+            get(&*a, url).await?;
+            get(&*a, url).await?;
+            let err = get(&*a, url).await.unwrap_err();
+            assert_eq!(err.short_msg(), "EgressPolicyViolation");
+            // Other functions have their own limits.
+            get(&*b, url).await?;
+            get(&*component_a, url).await?;
+            // Clients for the same function share a limit.
+            let a_again = client
+                .for_function(&function_path("", "a.js:run")?)
+                .unwrap();
+            assert!(get(&*a_again, url).await.is_err());
+            rt.advance_time(Duration::from_secs(60)).await;
+            get(&*a, url).await?;
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_policy_checked_without_dns() -> anyhow::Result<()> {
+        let td = TestDriver::new();
+        let policy: EgressPolicy = serde_json::from_value(serde_json::json!({
+            "deniedHosts": ["api.example.com"],
+        }))?;
+        let client =
+            EgressPolicyFetchClient::with_inner(td.rt(), static_client()?, Arc::new(policy), false);
+        td.run_until(async {
+            let err = get(&client, "https://api.example.com/small")
+                .await
+                .unwrap_err();
+            assert_eq!(err.short_msg(), "EgressPolicyViolation");
+            // IP addresses are checked before fetching since they aren't resolved.
+            let err = get(&client, "http://127.0.0.1/small").await.unwrap_err();
+            assert_eq!(err.short_msg(), "EgressPolicyViolation");
+            Ok(())
+        })
+    }
+
+    #[tokio::test]
+    async fn test_resolver_checks_addresses() -> anyhow::Result<()> {
+        let policy: Arc<EgressPolicy> = Arc::new(serde_json::from_str("{}")?);
+        let resolver = EgressPolicyResolver {
+            policy: policy.clone(),
+            proxy_host: None,
+        };
+        let Err(err) = resolver.resolve("localhost".parse::<Name>().unwrap()).await else {
+            panic!("Resolved localhost to an allowed address");
+        };
+        let err = err
+            .downcast_ref::<errors::ErrorMetadata>()
+            .expect("Expected an egress policy violation");
+        assert_eq!(err.short_msg, "EgressPolicyViolation");
+
+        // The proxy can be on a private address.
+        let resolver = EgressPolicyResolver {
+            policy,
+            proxy_host: Some("localhost".to_owned()),
+        };
+        let Ok(addrs) = resolver.resolve("localhost".parse::<Name>().unwrap()).await else {
+            panic!("Failed to resolve the proxy's host");
+        };
+        assert!(addrs.into_iter().all(|addr| addr.ip().is_loopback()));
+        Ok(())
+    }
+}
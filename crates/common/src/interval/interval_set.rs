@@ -4,10 +4,7 @@ use std::{
     ops::Bound,
 };
 
-use pb::common::{
-    interval::End as EndProto,
-    Interval as IntervalProto,
-};
+use pb::common::Interval as IntervalProto;
 use value::heap_size::{
     HeapSize,
     WithHeapSize,
@@ -33,19 +30,7 @@ impl From<IntervalSet> for Vec<IntervalProto> {
     fn from(set: IntervalSet) -> Self {
         set.intervals
             .into_iter()
-            .map(|(start, end)| {
-                let start = match start {
-                    Start::Included(b) => b.to_vec(),
-                };
-                let end = match end {
-                    End::Unbounded => EndProto::AfterAll(()),
-                    End::Excluded(e) => EndProto::Exclusive(e.to_vec()),
-                };
-                IntervalProto {
-                    start_inclusive: start,
-                    end: Some(end),
-                }
-            })
+            .map(|(start, end)| Interval { start, end }.into())
             .collect()
     }
 }
@@ -56,15 +41,7 @@ impl TryFrom<Vec<IntervalProto>> for IntervalSet {
     fn try_from(intervals: Vec<IntervalProto>) -> anyhow::Result<Self> {
         let mut set = IntervalSet::new();
         for interval in intervals {
-            let start = Start::Included(interval.start_inclusive.into());
-            let end = match interval.end {
-                None => return Err(anyhow::anyhow!("Interval missing end")),
-                Some(end) => match end {
-                    EndProto::AfterAll(()) => End::Unbounded,
-                    EndProto::Exclusive(end) => End::Excluded(end.into()),
-                },
-            };
-            set.add(Interval { start, end });
+            set.add(interval.try_into()?);
         }
         Ok(set)
     }
@@ -23,6 +23,11 @@ use std::ops::{
     RangeBounds,
 };
 
+use pb::common::{
+    interval::End as EndProto,
+    Interval as IntervalProto,
+};
+
 pub use self::{
     bounds::{
         End,
@@ -156,6 +161,38 @@ impl Interval {
     }
 }
 
+impl From<Interval> for IntervalProto {
+    fn from(Interval { start, end }: Interval) -> Self {
+        let start = match start {
+            Start::Included(b) => b.to_vec(),
+        };
+        let end = match end {
+            End::Unbounded => EndProto::AfterAll(()),
+            End::Excluded(e) => EndProto::Exclusive(e.to_vec()),
+        };
+        IntervalProto {
+            start_inclusive: start,
+            end: Some(end),
+        }
+    }
+}
+
+impl TryFrom<IntervalProto> for Interval {
+    type Error = anyhow::Error;
+
+    fn try_from(interval: IntervalProto) -> anyhow::Result<Self> {
+        let start = Start::Included(interval.start_inclusive.into());
+        let end = match interval.end {
+            None => return Err(anyhow::anyhow!("Interval missing end")),
+            Some(end) => match end {
+                EndProto::AfterAll(()) => End::Unbounded,
+                EndProto::Exclusive(end) => End::Excluded(end.into()),
+            },
+        };
+        Ok(Interval { start, end })
+    }
+}
+
 impl RangeBounds<[u8]> for &Interval {
     fn start_bound(&self) -> Bound<&[u8]> {
         let Start::Included(ref s) = self.start;
@@ -107,6 +107,15 @@ impl TryFrom<JsonIndexRangeExpression> for IndexRangeExpression {
     }
 }
 
+impl TryFrom<JsonValue> for IndexRangeExpression {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonValue) -> Result<Self> {
+        let json_range_expression: JsonIndexRangeExpression = serde_json::from_value(value)?;
+        json_range_expression.try_into()
+    }
+}
+
 impl From<IndexRangeExpression> for JsonIndexRangeExpression {
     fn from(range_expression: IndexRangeExpression) -> Self {
         match range_expression {
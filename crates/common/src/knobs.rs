@@ -9,7 +9,8 @@
 //! safely for a backend if needed.
 //!
 //! When running locally, these knobs can all be overridden with an environment
-//! variable.
+//! variable. The knobs listed in [`LIVE_KNOBS`] can also be changed while the
+//! backend is running, see [`crate::live_knobs`].
 #![deny(missing_docs)]
 
 use std::{
@@ -20,7 +21,14 @@ use std::{
 
 use cmd_util::env::env_config;
 
-use crate::minitrace_helpers::SamplingConfig;
+use crate::{
+    live_knobs::{
+        positive,
+        DynamicKnob,
+        LiveKnob,
+    },
+    minitrace_helpers::SamplingConfig,
+};
 
 /// This exists solely to allow knobs to have separate defaults for local
 /// execution and prod (running in Nomad). Don't export this outside of
@@ -42,6 +50,40 @@ fn prod_override<T>(local_value: T, prod_value: T) -> T {
     local_value
 }
 
+/// Knobs that are safe to change while the backend is running. Their
+/// consumers re-read them on every use. Knobs that size thread pools,
+/// semaphores or caches at startup, like the `APPLICATION_MAX_CONCURRENT_*`
+/// limits, still need a restart.
+pub static LIVE_KNOBS: &[&dyn DynamicKnob] = &[
+    // Function and transaction limits
+    &FUNCTION_MAX_ARGS_SIZE,
+    &FUNCTION_MAX_RESULT_SIZE,
+    &MAX_SYSCALL_BATCH_SIZE,
+    &TRANSACTION_MAX_NUM_SCHEDULED,
+    &TRANSACTION_MAX_NUM_USER_WRITES,
+    &TRANSACTION_MAX_READ_SET_INTERVALS,
+    &TRANSACTION_MAX_READ_SIZE_BYTES,
+    &TRANSACTION_MAX_READ_SIZE_ROWS,
+    &TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES,
+    &TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
+    // Isolate heap sizes
+    &ISOLATE_MAX_HEAP_EXTRA_SIZE,
+    &ISOLATE_MAX_USER_HEAP_SIZE,
+    // Scheduled jobs
+    &MAX_JOBS_CANCEL_BATCH,
+    &SCHEDULED_JOB_EXECUTION_PARALLELISM,
+    &SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE,
+    &SCHEDULED_JOB_RETENTION,
+    // Retention
+    &DOCUMENT_RETENTION_DELAY,
+    &DOCUMENT_RETENTION_DRY_RUN,
+    &DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS,
+    &INDEX_RETENTION_DELAY,
+    &RETENTION_DELETES_ENABLED,
+    &RETENTION_DELETE_BATCH,
+    &RETENTION_DOCUMENT_DELETES_ENABLED,
+];
+
 /// Set a consistent thread stack size regardless of environment. This is
 /// 2x Rust's default: https://doc.rust-lang.org/nightly/std/thread/index.html#stack-size
 pub static RUNTIME_STACK_SIZE: LazyLock<usize> =
@@ -97,6 +139,33 @@ pub static DOCUMENT_DELTAS_LIMIT: LazyLock<usize> =
 pub static SNAPSHOT_LIST_LIMIT: LazyLock<usize> =
     LazyLock::new(|| env_config("SNAPSHOT_LIST_LIMIT", 1024));
 
+/// Max number of revisions returned per page of a document's history. Each
+/// revision is a separate read from persistence.
+pub static DOCUMENT_HISTORY_PAGE_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("DOCUMENT_HISTORY_PAGE_SIZE", 50));
+
+/// Max number of documents per table checked against a suggested schema. The
+/// suggestion is built from table summaries covering every document, so only
+/// the first documents of each table in `_id` order are checked to bound the
+/// cost of the request, and the response says whether any were left out.
+pub static SUGGEST_SCHEMA_MAX_CHECKED_DOCUMENTS: LazyLock<usize> =
+    LazyLock::new(|| env_config("SUGGEST_SCHEMA_MAX_CHECKED_DOCUMENTS", 1000));
+
+/// Approximate number of document bytes buffered before a row group is flushed
+/// when writing a Parquet snapshot export. This bounds the memory used per
+/// table being exported.
+pub static PARQUET_EXPORT_ROW_GROUP_BYTES: LazyLock<usize> =
+    LazyLock::new(|| env_config("PARQUET_EXPORT_ROW_GROUP_BYTES", 16 << 20));
+
+/// Maximum number of document log entries read per second while finding the
+/// documents that changed for an incremental snapshot export.
+pub static INCREMENTAL_EXPORT_ROWS_PER_SECOND: LazyLock<NonZeroU32> = LazyLock::new(|| {
+    env_config(
+        "INCREMENTAL_EXPORT_ROWS_PER_SECOND",
+        NonZeroU32::new(10000).unwrap(),
+    )
+});
+
 /// Enables the log streaming worker.
 pub static ENABLE_LOG_STREAMING: LazyLock<bool> =
     LazyLock::new(|| env_config("ENABLE_LOG_STREAMING", true));
@@ -178,23 +247,29 @@ pub static HTTP_SERVER_MAX_CONCURRENT_REQUESTS: LazyLock<usize> =
     LazyLock::new(|| env_config("HTTP_SERVER_MAX_CONCURRENT_REQUESTS", 1024));
 
 /// Max number of user writes in a transaction
-pub static TRANSACTION_MAX_NUM_USER_WRITES: LazyLock<usize> =
-    LazyLock::new(|| env_config("TRANSACTION_MAX_NUM_USER_WRITES", 8192));
+pub static TRANSACTION_MAX_NUM_USER_WRITES: LiveKnob<usize> =
+    LiveKnob::with_validator("TRANSACTION_MAX_NUM_USER_WRITES", 8192, positive);
 
 /// Max size of user writes in a transaction, in bytes
-pub static TRANSACTION_MAX_USER_WRITE_SIZE_BYTES: LazyLock<usize> = LazyLock::new(|| {
-    env_config("TRANSACTION_MAX_USER_WRITE_SIZE_BYTES", 1 << 23) // 8 MiB
-});
+pub static TRANSACTION_MAX_USER_WRITE_SIZE_BYTES: LiveKnob<usize> = LiveKnob::with_validator(
+    "TRANSACTION_MAX_USER_WRITE_SIZE_BYTES",
+    1 << 23, // 8 MiB
+    positive,
+);
 
 /// Maximum size in bytes of arguments to a function.
-pub static FUNCTION_MAX_ARGS_SIZE: LazyLock<usize> = LazyLock::new(|| {
-    env_config("FUNCTION_MAX_ARGS_SIZE", 1 << 23) // 8 MiB
-});
+pub static FUNCTION_MAX_ARGS_SIZE: LiveKnob<usize> = LiveKnob::with_validator(
+    "FUNCTION_MAX_ARGS_SIZE",
+    1 << 23, // 8 MiB
+    positive,
+);
 
 /// Maximum size in bytes of the result of a function.
-pub static FUNCTION_MAX_RESULT_SIZE: LazyLock<usize> = LazyLock::new(|| {
-    env_config("FUNCTION_MAX_RESULT_SIZE", 1 << 23) // 8 MiB
-});
+pub static FUNCTION_MAX_RESULT_SIZE: LiveKnob<usize> = LiveKnob::with_validator(
+    "FUNCTION_MAX_RESULT_SIZE",
+    1 << 23, // 8 MiB
+    positive,
+);
 
 /// When a function exceeds FUNCTION_LIMIT_WARNING_RATIO * a corresponding
 /// limit value, we add a warning log line.
@@ -217,59 +292,61 @@ pub static TRANSACTION_MAX_SYSTEM_WRITE_SIZE_BYTES: LazyLock<usize> = LazyLock::
 });
 
 /// Maximum number of scheduled transactions.
-pub static TRANSACTION_MAX_NUM_SCHEDULED: LazyLock<usize> =
-    LazyLock::new(|| env_config("TRANSACTION_MAX_NUM_SCHEDULED", 1000));
+pub static TRANSACTION_MAX_NUM_SCHEDULED: LiveKnob<usize> =
+    LiveKnob::with_validator("TRANSACTION_MAX_NUM_SCHEDULED", 1000, positive);
 
 /// Maximum number of scheduled jobs to cancel in a single transaction.
-pub static MAX_JOBS_CANCEL_BATCH: LazyLock<usize> =
-    LazyLock::new(|| env_config("MAX_JOBS_CANCEL_BATCH", 1000));
+pub static MAX_JOBS_CANCEL_BATCH: LiveKnob<usize> =
+    LiveKnob::with_validator("MAX_JOBS_CANCEL_BATCH", 1000, positive);
 
 /// Maximum size of the arguments to a scheduled function.
-pub static TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES: LazyLock<usize> =
-    LazyLock::new(|| {
-        env_config(
-            "TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES",
-            1 << 23,
-        ) // 8 MiB
-    });
+pub static TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES: LiveKnob<usize> =
+    LiveKnob::with_validator(
+        "TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES",
+        1 << 23, // 8 MiB
+        positive,
+    );
 
 /// Number of scheduled jobs that can execute in parallel.
 // Note that the current algorithm for executing ready jobs has up to
 // SCHEDULED_JOB_EXECUTION_PARALLELISM overhead for every executed job, so we
 // don't want to set this number too high.
-pub static SCHEDULED_JOB_EXECUTION_PARALLELISM: LazyLock<usize> =
-    LazyLock::new(|| env_config("SCHEDULED_JOB_EXECUTION_PARALLELISM", 10));
+pub static SCHEDULED_JOB_EXECUTION_PARALLELISM: LiveKnob<usize> =
+    LiveKnob::with_validator("SCHEDULED_JOB_EXECUTION_PARALLELISM", 10, positive);
 
 /// How long completed scheduled jobs are kept before getting garbage collected.
-pub static SCHEDULED_JOB_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
-    Duration::from_secs(env_config(
-        "SCHEDULED_JOB_RETENTION",
-        60 * 60 * 24 * 7, // 1 week
-    ))
-});
+pub static SCHEDULED_JOB_RETENTION: LiveKnob<Duration> = LiveKnob::new(
+    "SCHEDULED_JOB_RETENTION",
+    Duration::from_secs(60 * 60 * 24 * 7), // 1 week
+);
 
 /// Maximum number of scheduled jobs to garbage collect in a single transaction
-pub static SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE: LazyLock<usize> =
-    LazyLock::new(|| env_config("SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE", 1000));
+pub static SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE: LiveKnob<usize> = LiveKnob::with_validator(
+    "SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE",
+    1000,
+    positive,
+);
 
 /// Maximum number of syscalls that can run in a batch together when
 /// awaited in parallel. Higher values improve latency, while lower ones
 /// protect one isolate from hogging database connections.
-pub static MAX_SYSCALL_BATCH_SIZE: LazyLock<usize> =
-    LazyLock::new(|| env_config("MAX_SYSCALL_BATCH_SIZE", 16));
+pub static MAX_SYSCALL_BATCH_SIZE: LiveKnob<usize> =
+    LiveKnob::with_validator("MAX_SYSCALL_BATCH_SIZE", 16, positive);
 
 /// Number of rows that can be read in a transaction.
-pub static TRANSACTION_MAX_READ_SIZE_ROWS: LazyLock<usize> =
-    LazyLock::new(|| env_config("TRANSACTION_MAX_READ_SIZE_ROWS", 16384));
+pub static TRANSACTION_MAX_READ_SIZE_ROWS: LiveKnob<usize> =
+    LiveKnob::with_validator("TRANSACTION_MAX_READ_SIZE_ROWS", 16384, positive);
 
 /// Number of bytes that can be read in a transaction.
-pub static TRANSACTION_MAX_READ_SIZE_BYTES: LazyLock<usize> = LazyLock::new(|| {
-    env_config("TRANSACTION_MAX_READ_SIZE_BYTES", 1 << 23) // 8 MiB
-});
+pub static TRANSACTION_MAX_READ_SIZE_BYTES: LiveKnob<usize> = LiveKnob::with_validator(
+    "TRANSACTION_MAX_READ_SIZE_BYTES",
+    1 << 23, // 8 MiB
+    positive,
+);
 
 /// Maximum number of intervals that can be read in a transcation.
-pub static TRANSACTION_MAX_READ_SET_INTERVALS: LazyLock<usize> =
-    LazyLock::new(|| env_config("TRANSACTION_MAX_READ_SET_INTERVALS", 4096));
+pub static TRANSACTION_MAX_READ_SET_INTERVALS: LiveKnob<usize> =
+    LiveKnob::with_validator("TRANSACTION_MAX_READ_SET_INTERVALS", 4096, positive);
 
 /// Write max_repeatable_ts if there have been no commits for this duration,
 /// to allow reads to stay fresh.
@@ -309,8 +386,11 @@ pub static RETENTION_DELETE_PARALLEL: LazyLock<usize> =
 /// performance problems in UDFs if there are many tombstones.
 ///
 /// Smaller window means we break snapshot reads faster.
-pub static INDEX_RETENTION_DELAY: LazyLock<Duration> =
-    LazyLock::new(|| Duration::from_secs(env_config("INDEX_RETENTION_DELAY", 4 * 60)));
+///
+/// Retention bounds only move forward, so raising this while the backend is
+/// running pauses index retention until the window has grown to the new size.
+pub static INDEX_RETENTION_DELAY: LiveKnob<Duration> =
+    LiveKnob::new("INDEX_RETENTION_DELAY", Duration::from_secs(4 * 60));
 
 /// DOCUMENT_RETENTION_DELAY determines the size of the document retention
 /// window.
@@ -319,8 +399,24 @@ pub static INDEX_RETENTION_DELAY: LazyLock<Duration> =
 /// to be valid.
 ///
 /// Smaller window means we keep less historical data around.
-pub static DOCUMENT_RETENTION_DELAY: LazyLock<Duration> = LazyLock::new(|| {
-    Duration::from_secs(env_config("DOCUMENT_RETENTION_DELAY", 60 * 60 * 24 * 90))
+///
+/// Like [`INDEX_RETENTION_DELAY`], raising this while the backend is running
+/// pauses document retention rather than restoring deleted revisions.
+pub static DOCUMENT_RETENTION_DELAY: LiveKnob<Duration> = LiveKnob::new(
+    "DOCUMENT_RETENTION_DELAY",
+    Duration::from_secs(60 * 60 * 24 * 90),
+);
+
+/// Shortest retention a table can set instead of
+/// [`DOCUMENT_RETENTION_DELAY`]. A table with a retention shorter than
+/// [`INDEX_RETENTION_DELAY`] also has its index entries deleted sooner, so
+/// snapshot reads of it break sooner.
+pub static MIN_TABLE_RETENTION: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("MIN_TABLE_RETENTION", 60)));
+
+/// Longest retention a table can set instead of [`DOCUMENT_RETENTION_DELAY`].
+pub static MAX_TABLE_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config("MAX_TABLE_RETENTION", 60 * 60 * 24 * 365 * 5))
 });
 
 /// Resets DocumentRetentionConfirmedDeletedTimestamp to Timestamp::MIN
@@ -359,6 +455,11 @@ pub static INDEX_BACKFILL_CHUNK_RATE: LazyLock<usize> =
 pub static INDEX_BACKFILL_CHUNK_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("INDEX_BACKFILL_CHUNK_SIZE", 256));
 
+/// How many aggregate index counts to write to persistence within a single
+/// database transaction when flushing their in-memory deltas.
+pub static AGGREGATE_INDEX_FLUSH_CHUNK_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("AGGREGATE_INDEX_FLUSH_CHUNK_SIZE", 256));
+
 /// Chunk size of index entries when reading from persistence.
 pub static RETENTION_READ_CHUNK: LazyLock<usize> =
     LazyLock::new(|| env_config("RETENTION_READ_CHUNK", 128));
@@ -368,16 +469,16 @@ pub static RETENTION_DELETE_CHUNK: LazyLock<usize> =
     LazyLock::new(|| env_config("RETENTION_DELETE_CHUNK", 128));
 
 /// Batch size of index entries to delete between checkpoints.
-pub static RETENTION_DELETE_BATCH: LazyLock<usize> =
-    LazyLock::new(|| env_config("RETENTION_DELETE_BATCH", 10000));
+pub static RETENTION_DELETE_BATCH: LiveKnob<usize> =
+    LiveKnob::with_validator("RETENTION_DELETE_BATCH", 10000, positive);
 
 /// Whether retention deletes are enabled.
-pub static RETENTION_DELETES_ENABLED: LazyLock<bool> =
-    LazyLock::new(|| env_config("RETENTION_DELETES_ENABLED", true));
+pub static RETENTION_DELETES_ENABLED: LiveKnob<bool> =
+    LiveKnob::new("RETENTION_DELETES_ENABLED", true);
 
 /// Whether retention document deletes are enabled.
-pub static RETENTION_DOCUMENT_DELETES_ENABLED: LazyLock<bool> =
-    LazyLock::new(|| env_config("RETENTION_DOCUMENT_DELETES_ENABLED", false));
+pub static RETENTION_DOCUMENT_DELETES_ENABLED: LiveKnob<bool> =
+    LiveKnob::new("RETENTION_DOCUMENT_DELETES_ENABLED", false);
 
 /// Enable or disable failing insert/update/deletes when retention is behind.
 pub static RETENTION_FAIL_ENABLED: LazyLock<bool> =
@@ -406,12 +507,12 @@ pub static DOCUMENT_RETENTION_BATCH_INTERVAL_SECONDS: LazyLock<Duration> = LazyL
 /// Maximum scanned documents within a single run for document retention unless
 /// there are a bunch of writes at single timestamp. Then, we go until there are
 /// no more writes at that timestamp.
-pub static DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS: LazyLock<usize> =
-    LazyLock::new(|| env_config("DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS", 500));
+pub static DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS: LiveKnob<usize> =
+    LiveKnob::with_validator("DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS", 500, positive);
 
 /// Whether or not we run document retention in dry run mode
-pub static DOCUMENT_RETENTION_DRY_RUN: LazyLock<bool> =
-    LazyLock::new(|| env_config("DOCUMENT_RETENTION_DRY_RUN", true));
+pub static DOCUMENT_RETENTION_DRY_RUN: LiveKnob<bool> =
+    LiveKnob::new("DOCUMENT_RETENTION_DRY_RUN", true);
 
 /// Size at which a search index will be queued for snapshotting.
 pub static SEARCH_INDEX_SIZE_SOFT_LIMIT: LazyLock<usize> =
@@ -677,6 +778,16 @@ pub static APPLICATION_MAX_CONCURRENT_V8_ACTIONS: LazyLock<usize> =
 pub static APPLICATION_MAX_CONCURRENT_NODE_ACTIONS: LazyLock<usize> =
     LazyLock::new(|| env_config("APPLICATION_MAX_CONCURRENT_NODE_ACTIONS", 16));
 
+/// Maximum number of idle `node` processes the local node executor keeps warm
+/// for running node actions. Busy processes don't count towards the limit.
+pub static NODE_EXECUTOR_MAX_IDLE_WORKERS: LazyLock<usize> =
+    LazyLock::new(|| env_config("NODE_EXECUTOR_MAX_IDLE_WORKERS", 4));
+
+/// Maximum number of `node` processes, busy or idle, the local node executor
+/// runs at once. Further node actions wait for a process to free up.
+pub static NODE_EXECUTOR_MAX_WORKERS: LazyLock<usize> =
+    LazyLock::new(|| env_config("NODE_EXECUTOR_MAX_WORKERS", 16));
+
 /// Number of threads to execute V8 actions.
 ///
 /// Http actions are not sent through FunctionRunner implementations. This is a
@@ -695,14 +806,37 @@ pub static APPLICATION_MAX_CONCURRENT_HTTP_ACTIONS: LazyLock<usize> = LazyLock::
     )
 });
 
+/// Maximum size of a message, or a single frame of one, that a client can send
+/// over a WebSocket accepted by an HTTP action. Larger messages close the
+/// connection.
+pub static HTTP_ACTION_WEB_SOCKET_MAX_MESSAGE_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("HTTP_ACTION_WEB_SOCKET_MAX_MESSAGE_SIZE", 1 << 20)); // 1 MiB
+
+/// Number of messages buffered in each direction of a WebSocket accepted by an
+/// HTTP action. Once the buffer of messages from the client is full, the
+/// backend stops reading from the socket until the action catches up. Once
+/// the buffer of messages to the client is full, the client isn't keeping up
+/// and the connection is dropped.
+pub static HTTP_ACTION_WEB_SOCKET_BUFFER_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("HTTP_ACTION_WEB_SOCKET_BUFFER_SIZE", 32));
+
 /// Set a 64MB limit on the heap size.
-pub static ISOLATE_MAX_USER_HEAP_SIZE: LazyLock<usize> =
-    LazyLock::new(|| env_config("ISOLATE_MAX_USER_HEAP_SIZE", 1 << 26));
+///
+/// Changes apply to isolates created afterwards. Raising it recycles existing
+/// isolates, which no longer have enough heap available.
+pub static ISOLATE_MAX_USER_HEAP_SIZE: LiveKnob<usize> =
+    LiveKnob::with_validator("ISOLATE_MAX_USER_HEAP_SIZE", 1 << 26, positive);
+
+/// Maximum number of `CompressionStream`s and `DecompressionStream`s a single
+/// function execution can have open at once. Each holds a (de)compressor and
+/// its buffered output outside the V8 heap.
+pub static ISOLATE_MAX_OPEN_COMPRESSION_STREAMS: LazyLock<usize> =
+    LazyLock::new(|| env_config("ISOLATE_MAX_OPEN_COMPRESSION_STREAMS", 64));
 
 /// Allow for some objects to persist between contexts, not necessarily created
 /// by the UDF.
-pub static ISOLATE_MAX_HEAP_EXTRA_SIZE: LazyLock<usize> =
-    LazyLock::new(|| env_config("ISOLATE_MAX_HEAP_EXTRA_SIZE", 1 << 25));
+pub static ISOLATE_MAX_HEAP_EXTRA_SIZE: LiveKnob<usize> =
+    LiveKnob::new("ISOLATE_MAX_HEAP_EXTRA_SIZE", 1 << 25);
 
 /// Chunk sizes: 1, 2, 3, ..., MAX_DYNAMIC_SMART_CHUNK_SIZE incrementing by 1.
 /// These chunk sizes allow small (common) batches to be handled in a single
@@ -1035,3 +1169,13 @@ pub static USE_MULTI_SEGMENT_SEARCH_QUERY: LazyLock<bool> =
 /// Read modules from SourcePackage instead of _module_versions.
 pub static READ_MODULES_FROM_SOURCE_PACKAGE: LazyLock<bool> =
     LazyLock::new(|| env_config("READ_MODULES_FROM_SOURCE_PACKAGE", false));
+
+/// How long the migration worker waits between batches, limiting the rate at
+/// which a migration rewrites a table.
+pub static MIGRATION_BATCH_DELAY: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("MIGRATION_BATCH_DELAY", 1)));
+
+/// How long the table restore worker waits between batches, limiting the rate
+/// at which a restore rewrites a table.
+pub static TABLE_RESTORE_BATCH_DELAY: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("TABLE_RESTORE_BATCH_DELAY", 1)));
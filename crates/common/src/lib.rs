@@ -51,6 +51,7 @@ pub mod is_canceled;
 pub mod json;
 pub mod json_schemas;
 pub mod knobs;
+pub mod live_knobs;
 pub mod log_lines;
 pub mod log_streaming;
 pub mod metrics;
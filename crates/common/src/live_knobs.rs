@@ -0,0 +1,332 @@
+//! Knobs that can be changed while the backend is running.
+//!
+//! Most knobs in [`crate::knobs`] are read once from the environment, so
+//! changing them requires a restart. Knobs declared as a [`LiveKnob`] can also
+//! be overridden at runtime (e.g. from a config file watched by
+//! `config_loader`), so their consumers must call [`LiveKnob::get`] every time
+//! they need the value instead of caching it.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    sync::OnceLock,
+    time::Duration,
+};
+
+use anyhow::Context;
+use errors::ErrorMetadata;
+use parking_lot::RwLock;
+use serde::Serialize;
+
+/// Where the effective value of a [`LiveKnob`] comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnobSource {
+    Default,
+    Environment,
+    Override,
+}
+
+/// The effective value of a live knob, for listing in admin endpoints.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnobValue {
+    pub name: &'static str,
+    pub value: String,
+    pub source: KnobSource,
+}
+
+/// Types a [`LiveKnob`] can hold, parsed from the environment and overrides.
+pub trait KnobType: Clone + Debug + Send + Sync + Sized {
+    fn parse_knob(s: &str) -> anyhow::Result<Self>;
+}
+
+macro_rules! from_str_knob_type {
+    ($($t:ty),*) => {
+        $(
+            impl KnobType for $t {
+                fn parse_knob(s: &str) -> anyhow::Result<Self> {
+                    s.parse().map_err(|e| anyhow::anyhow!("{e:?}"))
+                }
+            }
+        )*
+    };
+}
+
+from_str_knob_type!(bool, u32, u64, usize, f64);
+
+/// Durations are whole seconds, like the `Duration` knobs read with
+/// `env_config`.
+impl KnobType for Duration {
+    fn parse_knob(s: &str) -> anyhow::Result<Self> {
+        Ok(Duration::from_secs(u64::parse_knob(s)?))
+    }
+}
+
+/// A knob whose value can be overridden while the backend is running. The
+/// initial value is read from the environment variable with the knob's name,
+/// like [`cmd_util::env::env_config`].
+pub struct LiveKnob<T> {
+    name: &'static str,
+    default: T,
+    validate: fn(&T) -> anyhow::Result<()>,
+    initial: OnceLock<(T, KnobSource)>,
+    override_value: RwLock<Option<T>>,
+}
+
+fn accept_any<T>(_: &T) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Validator for knobs like batch sizes and concurrency limits, where zero
+/// would stop all progress.
+pub fn positive(value: &usize) -> anyhow::Result<()> {
+    anyhow::ensure!(*value > 0, "must be greater than zero");
+    Ok(())
+}
+
+impl<T> LiveKnob<T> {
+    pub const fn new(name: &'static str, default: T) -> Self {
+        Self::with_validator(name, default, accept_any::<T>)
+    }
+
+    /// Like [`LiveKnob::new`], but values from the environment or overrides
+    /// are rejected if `validate` fails.
+    pub const fn with_validator(
+        name: &'static str,
+        default: T,
+        validate: fn(&T) -> anyhow::Result<()>,
+    ) -> Self {
+        Self {
+            name,
+            default,
+            validate,
+            initial: OnceLock::new(),
+            override_value: parking_lot::const_rwlock(None),
+        }
+    }
+}
+
+impl<T: KnobType> LiveKnob<T> {
+    /// Returns the current value of the knob. Don't hold on to the result
+    /// longer than needed, since it may change at any time.
+    pub fn get(&self) -> T {
+        if let Some(value) = &*self.override_value.read() {
+            return value.clone();
+        }
+        self.initial().0.clone()
+    }
+
+    fn initial(&self) -> &(T, KnobSource) {
+        self.initial.get_or_init(|| {
+            let Ok(s) = std::env::var(self.name) else {
+                return (self.default.clone(), KnobSource::Default);
+            };
+            match self.parse(&s) {
+                Ok(value) => {
+                    tracing::info!("Overriding {} to {value:?} from environment", self.name);
+                    (value, KnobSource::Environment)
+                },
+                Err(e) => {
+                    tracing::warn!(
+                        "Invalid value {s} for {}, falling back to {:?}: {e:#}",
+                        self.name,
+                        self.default
+                    );
+                    (self.default.clone(), KnobSource::Default)
+                },
+            }
+        })
+    }
+
+    fn parse(&self, s: &str) -> anyhow::Result<T> {
+        let value = T::parse_knob(s.trim())?;
+        (self.validate)(&value)?;
+        Ok(value)
+    }
+}
+
+/// Type-erased [`LiveKnob`], so knobs of different types can be listed in one
+/// registry.
+pub trait DynamicKnob: Sync {
+    fn name(&self) -> &'static str;
+
+    /// Checks that `value` parses and is in range, without changing the knob.
+    fn validate(&self, value: &str) -> anyhow::Result<()>;
+
+    /// Replaces the override for this knob, or clears it if `value` is `None`.
+    fn set_override(&self, value: Option<&str>) -> anyhow::Result<()>;
+
+    fn value(&self) -> KnobValue;
+}
+
+impl<T: KnobType> DynamicKnob for LiveKnob<T> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn validate(&self, value: &str) -> anyhow::Result<()> {
+        self.parse(value).map(|_| ())
+    }
+
+    fn set_override(&self, value: Option<&str>) -> anyhow::Result<()> {
+        let value = value.map(|s| self.parse(s)).transpose()?;
+        *self.override_value.write() = value;
+        Ok(())
+    }
+
+    fn value(&self) -> KnobValue {
+        let (value, source) = match &*self.override_value.read() {
+            Some(value) => (value.clone(), KnobSource::Override),
+            None => self.initial().clone(),
+        };
+        KnobValue {
+            name: self.name,
+            value: format!("{value:?}"),
+            source,
+        }
+    }
+}
+
+/// Replaces the overrides of all knobs in `registry` with `overrides`, a map
+/// from knob name to its unparsed value. Knobs missing from `overrides` go
+/// back to their initial value. If any override names an unknown knob or
+/// fails to validate, no knob is changed.
+pub fn apply_overrides(
+    registry: &[&'static dyn DynamicKnob],
+    overrides: &BTreeMap<String, String>,
+) -> anyhow::Result<()> {
+    for (name, value) in overrides {
+        let knob = registry
+            .iter()
+            .find(|knob| knob.name() == name)
+            .with_context(|| {
+                ErrorMetadata::bad_request(
+                    "UnknownLiveKnob",
+                    format!("{name} isn't a knob that can be changed without a restart"),
+                )
+            })?;
+        knob.validate(value).with_context(|| {
+            ErrorMetadata::bad_request(
+                "InvalidKnobValue",
+                format!("Invalid value {value:?} for knob {name}"),
+            )
+        })?;
+    }
+    for knob in registry {
+        knob.set_override(overrides.get(knob.name()).map(|s| s.as_str()))?;
+    }
+    Ok(())
+}
+
+/// Lists the effective values of all knobs in `registry`.
+pub fn knob_values(registry: &[&'static dyn DynamicKnob]) -> Vec<KnobValue> {
+    registry.iter().map(|knob| knob.value()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use maplit::btreemap;
+
+    use super::{
+        apply_overrides,
+        knob_values,
+        positive,
+        DynamicKnob,
+        KnobSource,
+        KnobValue,
+        LiveKnob,
+    };
+
+    static TEST_BATCH_SIZE: LiveKnob<usize> =
+        LiveKnob::with_validator("LIVE_KNOBS_TEST_BATCH_SIZE", 100, positive);
+    static TEST_ENABLED: LiveKnob<bool> = LiveKnob::new("LIVE_KNOBS_TEST_ENABLED", false);
+    static REGISTRY: &[&dyn DynamicKnob] = &[&TEST_BATCH_SIZE, &TEST_ENABLED];
+
+    static TEST_DELAY: LiveKnob<Duration> =
+        LiveKnob::new("LIVE_KNOBS_TEST_DELAY", Duration::from_secs(60));
+    static DURATION_REGISTRY: &[&dyn DynamicKnob] = &[&TEST_DELAY];
+
+    #[test]
+    fn test_apply_overrides() -> anyhow::Result<()> {
+        assert_eq!(TEST_BATCH_SIZE.get(), 100);
+        apply_overrides(
+            REGISTRY,
+            &btreemap! {
+                "LIVE_KNOBS_TEST_BATCH_SIZE".to_string() => "20".to_string(),
+                "LIVE_KNOBS_TEST_ENABLED".to_string() => " true\n".to_string(),
+            },
+        )?;
+        assert_eq!(TEST_BATCH_SIZE.get(), 20);
+        assert!(TEST_ENABLED.get());
+        assert_eq!(
+            knob_values(REGISTRY),
+            vec![
+                KnobValue {
+                    name: "LIVE_KNOBS_TEST_BATCH_SIZE",
+                    value: "20".to_string(),
+                    source: KnobSource::Override,
+                },
+                KnobValue {
+                    name: "LIVE_KNOBS_TEST_ENABLED",
+                    value: "true".to_string(),
+                    source: KnobSource::Override,
+                },
+            ]
+        );
+
+        // Invalid overrides are rejected as a whole.
+        for overrides in [
+            btreemap! {
+                "LIVE_KNOBS_TEST_BATCH_SIZE".to_string() => "0".to_string(),
+                "LIVE_KNOBS_TEST_ENABLED".to_string() => "false".to_string(),
+            },
+            btreemap! {
+                "LIVE_KNOBS_TEST_BATCH_SIZE".to_string() => "ten".to_string(),
+            },
+            btreemap! {
+                "LIVE_KNOBS_TEST_UNKNOWN".to_string() => "1".to_string(),
+            },
+        ] {
+            assert!(apply_overrides(REGISTRY, &overrides).is_err());
+            assert_eq!(TEST_BATCH_SIZE.get(), 20);
+            assert!(TEST_ENABLED.get());
+        }
+
+        // Removing an override goes back to the initial value.
+        apply_overrides(
+            REGISTRY,
+            &btreemap! {
+                "LIVE_KNOBS_TEST_ENABLED".to_string() => "true".to_string(),
+            },
+        )?;
+        assert_eq!(TEST_BATCH_SIZE.get(), 100);
+        assert_eq!(TEST_BATCH_SIZE.value().source, KnobSource::Default);
+        assert!(TEST_ENABLED.get());
+        Ok(())
+    }
+
+    #[test]
+    fn test_duration_knob() -> anyhow::Result<()> {
+        assert_eq!(TEST_DELAY.get(), Duration::from_secs(60));
+        apply_overrides(
+            DURATION_REGISTRY,
+            &btreemap! {
+                "LIVE_KNOBS_TEST_DELAY".to_string() => "300".to_string(),
+            },
+        )?;
+        assert_eq!(TEST_DELAY.get(), Duration::from_secs(300));
+        // Durations are whole seconds.
+        assert!(apply_overrides(
+            DURATION_REGISTRY,
+            &btreemap! {
+                "LIVE_KNOBS_TEST_DELAY".to_string() => "5m".to_string(),
+            },
+        )
+        .is_err());
+        assert_eq!(TEST_DELAY.get(), Duration::from_secs(300));
+        Ok(())
+    }
+}
@@ -17,7 +17,10 @@ use async_trait::async_trait;
 use enum_iterator::Sequence;
 use futures::{
     future,
-    stream::BoxStream,
+    stream::{
+        self,
+        BoxStream,
+    },
     try_join,
     StreamExt,
     TryStreamExt,
@@ -96,6 +99,22 @@ pub enum PersistenceGlobalKey {
     /// deletes entries at a timestamp.
     DocumentRetentionConfirmedDeletedTimestamp,
 
+    /// Minimum snapshot that is retained for each table that keeps its
+    /// history for less than the index retention, keyed by tablet id.
+    TableRetentionMinSnapshotTimestamps,
+
+    /// Cursors of index retention for the tables in
+    /// `TableRetentionMinSnapshotTimestamps`, keyed by tablet id.
+    TableRetentionConfirmedDeletedTimestamps,
+
+    /// Minimum timestamp for valid write-ahead log of each table that
+    /// overrides the default document retention, keyed by tablet id.
+    TableDocumentRetentionMinSnapshotTimestamps,
+
+    /// Cursors of document retention for the tables in
+    /// `TableDocumentRetentionMinSnapshotTimestamps`, keyed by tablet id.
+    TableDocumentRetentionConfirmedDeletedTimestamps,
+
     /// Maximum snapshot that is repeatable. All future commits will have
     /// timestamp > this timestamp.
     MaxRepeatableTimestamp,
@@ -103,6 +122,10 @@ pub enum PersistenceGlobalKey {
     /// Latest snapshot of all tables' summaries, cached to speed up startup.
     TableSummary,
 
+    /// Latest checkpoint of the in-memory aggregate index deltas, cached to
+    /// speed up startup.
+    AggregateIndexes,
+
     /// Internal id of _tables.by_id index, for bootstrapping.
     TablesByIdIndex,
     /// Internal id of _tables table, for bootstrapping.
@@ -126,8 +149,21 @@ impl From<PersistenceGlobalKey> for String {
             PersistenceGlobalKey::DocumentRetentionConfirmedDeletedTimestamp => {
                 "document_confirmed_deleted_ts".to_string()
             },
+            PersistenceGlobalKey::TableRetentionMinSnapshotTimestamps => {
+                "table_min_snapshot_ts".to_string()
+            },
+            PersistenceGlobalKey::TableRetentionConfirmedDeletedTimestamps => {
+                "table_confirmed_deleted_ts".to_string()
+            },
+            PersistenceGlobalKey::TableDocumentRetentionMinSnapshotTimestamps => {
+                "table_document_min_snapshot_ts".to_string()
+            },
+            PersistenceGlobalKey::TableDocumentRetentionConfirmedDeletedTimestamps => {
+                "table_document_confirmed_deleted_ts".to_string()
+            },
             PersistenceGlobalKey::MaxRepeatableTimestamp => "max_repeatable_ts".to_string(),
             PersistenceGlobalKey::TableSummary => "table_summary_v2".to_string(),
+            PersistenceGlobalKey::AggregateIndexes => "aggregate_indexes".to_string(),
             PersistenceGlobalKey::TablesByIdIndex => "tables_by_id".to_string(),
             PersistenceGlobalKey::IndexByIdIndex => "index_by_id".to_string(),
             // NB: For compatibility, these are referred to as "table_id"s, not "tablet_id"s.
@@ -145,8 +181,17 @@ impl FromStr for PersistenceGlobalKey {
             "confirmed_deleted_ts" => Ok(Self::RetentionConfirmedDeletedTimestamp),
             "document_min_snapshot_ts" => Ok(Self::DocumentRetentionMinSnapshotTimestamp),
             "document_confirmed_deleted_ts" => Ok(Self::DocumentRetentionConfirmedDeletedTimestamp),
+            "table_min_snapshot_ts" => Ok(Self::TableRetentionMinSnapshotTimestamps),
+            "table_confirmed_deleted_ts" => Ok(Self::TableRetentionConfirmedDeletedTimestamps),
+            "table_document_min_snapshot_ts" => {
+                Ok(Self::TableDocumentRetentionMinSnapshotTimestamps)
+            },
+            "table_document_confirmed_deleted_ts" => {
+                Ok(Self::TableDocumentRetentionConfirmedDeletedTimestamps)
+            },
             "max_repeatable_ts" => Ok(Self::MaxRepeatableTimestamp),
             "table_summary_v2" => Ok(Self::TableSummary),
+            "aggregate_indexes" => Ok(Self::AggregateIndexes),
             "tables_by_id" => Ok(Self::TablesByIdIndex),
             "tables_table_id" => Ok(Self::TablesTabletId),
             "index_by_id" => Ok(Self::IndexByIdIndex),
@@ -294,6 +339,25 @@ pub trait RetentionValidator: Sync + Send {
     /// Call validate_document_snapshot *after* reading at the snapshot, to
     /// confirm the documents log is valid at this snapshot.
     async fn validate_document_snapshot(&self, ts: Timestamp) -> anyhow::Result<()>;
+    /// Like validate_snapshot, but for reading a single table's indexes,
+    /// which may have a shorter retention than the default.
+    async fn validate_table_snapshot(
+        &self,
+        _tablet_id: TabletId,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        self.validate_snapshot(ts).await
+    }
+    /// Like validate_document_snapshot, but for reading a single table's
+    /// documents log, which may have a shorter or longer retention than the
+    /// default.
+    async fn validate_table_document_snapshot(
+        &self,
+        _tablet_id: TabletId,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        self.validate_document_snapshot(ts).await
+    }
     async fn min_snapshot_ts(&self) -> anyhow::Result<Timestamp>;
     async fn min_document_snapshot_ts(&self) -> anyhow::Result<Timestamp>;
 
@@ -330,8 +394,20 @@ pub trait PersistenceReader: Send + Sync + 'static {
         page_size: u32,
         retention_validator: Arc<dyn RetentionValidator>,
     ) -> DocumentStream<'_> {
+        let validate = {
+            let retention_validator = retention_validator.clone();
+            stream::once(async move {
+                retention_validator
+                    .validate_table_document_snapshot(tablet_id, range.min_timestamp_inclusive())
+                    .await
+            })
+            .try_filter_map(|()| future::ready(Ok(None)))
+        };
+        let retention_validator =
+            Arc::new(TableRetentionValidator::new(retention_validator, tablet_id));
         self.load_documents(range, order, page_size, retention_validator)
             .try_filter(move |(_, doc_id, _)| future::ready(*doc_id.table() == tablet_id))
+            .chain(validate)
             .boxed()
     }
 
@@ -706,6 +782,74 @@ impl PersistenceSnapshot {
     }
 }
 
+/// Validates reads of a single table against the table's own retention, so
+/// reads of a table that keeps its history longer than the default can go
+/// further back.
+pub struct TableRetentionValidator {
+    retention_validator: Arc<dyn RetentionValidator>,
+    tablet_id: TabletId,
+}
+
+impl TableRetentionValidator {
+    pub fn new(retention_validator: Arc<dyn RetentionValidator>, tablet_id: TabletId) -> Self {
+        Self {
+            retention_validator,
+            tablet_id,
+        }
+    }
+}
+
+#[async_trait]
+impl RetentionValidator for TableRetentionValidator {
+    fn optimistic_validate_snapshot(&self, ts: Timestamp) -> anyhow::Result<()> {
+        self.retention_validator.optimistic_validate_snapshot(ts)
+    }
+
+    async fn validate_snapshot(&self, ts: Timestamp) -> anyhow::Result<()> {
+        self.retention_validator
+            .validate_table_snapshot(self.tablet_id, ts)
+            .await
+    }
+
+    async fn validate_document_snapshot(&self, ts: Timestamp) -> anyhow::Result<()> {
+        self.retention_validator
+            .validate_table_document_snapshot(self.tablet_id, ts)
+            .await
+    }
+
+    async fn validate_table_snapshot(
+        &self,
+        tablet_id: TabletId,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        self.retention_validator
+            .validate_table_snapshot(tablet_id, ts)
+            .await
+    }
+
+    async fn validate_table_document_snapshot(
+        &self,
+        tablet_id: TabletId,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        self.retention_validator
+            .validate_table_document_snapshot(tablet_id, ts)
+            .await
+    }
+
+    async fn min_snapshot_ts(&self) -> anyhow::Result<Timestamp> {
+        self.retention_validator.min_snapshot_ts().await
+    }
+
+    async fn min_document_snapshot_ts(&self) -> anyhow::Result<Timestamp> {
+        self.retention_validator.min_document_snapshot_ts().await
+    }
+
+    fn fail_if_falling_behind(&self) -> anyhow::Result<()> {
+        self.retention_validator.fail_if_falling_behind()
+    }
+}
+
 /// Test-only snapshot validator that doesn't validate anything.
 /// Prod and most tests should use (Follower|Leader)RetentionManager,
 #[derive(Clone, Copy)]
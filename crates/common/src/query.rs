@@ -180,16 +180,26 @@ pub struct IndexRange {
 }
 
 impl IndexRange {
+    /// Compile the range into an interval of the index with `indexed_fields`.
+    /// If the index is partial, `index_filter` is its filter, and the range
+    /// must imply it.
     pub fn compile(
         self,
         indexed_fields: IndexedFields,
+        index_filter: Option<&Expression>,
         virtual_table_number_map: Option<VirtualTableNumberMap>,
     ) -> anyhow::Result<Interval> {
         let index_name = self.index_name.clone();
+        let split = self.split()?;
+        if let Some(index_filter) = index_filter
+            && !split.implies(index_filter)
+        {
+            anyhow::bail!(range_does_not_imply_filter_error(&index_name));
+        }
         let SplitIndexRange {
             equalities,
             inequality,
-        } = self.split()?.map_values(|field, v| {
+        } = split.map_values(|field, v| {
             if field == &*ID_FIELD_PATH {
                 map_id_value_to_tablet(v, virtual_table_number_map)
             } else {
@@ -440,6 +450,118 @@ impl SplitIndexRange {
     }
 }
 
+impl SplitIndexRange {
+    /// Whether every document in the range satisfies `filter`. This only
+    /// understands `&&`, `||` and comparisons between a field and a literal,
+    /// so it may return false for ranges that do imply the filter.
+    fn implies(&self, filter: &Expression) -> bool {
+        match filter {
+            Expression::Literal(MaybeValue(Some(ConvexValue::Boolean(b)))) => *b,
+            Expression::And(conjuncts) => conjuncts.iter().all(|e| self.implies(e)),
+            Expression::Or(disjuncts) => disjuncts.iter().any(|e| self.implies(e)),
+            _ => {
+                let Some((field_path, comparison, literal)) =
+                    FieldComparison::from_expression(filter)
+                else {
+                    return false;
+                };
+                if let Some(value) = self.equalities.get(field_path) {
+                    return comparison.holds(&value.0, &literal.0);
+                }
+                let Some(ref inequality) = self.inequality else {
+                    return false;
+                };
+                if inequality.field_path != *field_path {
+                    return false;
+                }
+                let literal = &literal.0;
+                // Index ranges order `undefined` before all values, so a lower bound
+                // excludes it but an upper bound doesn't.
+                let above = |bound: &Bound<ConvexValue>, inclusive: bool| match bound {
+                    Bound::Included(start) if inclusive => Some(start) >= literal.as_ref(),
+                    Bound::Included(start) => Some(start) > literal.as_ref(),
+                    Bound::Excluded(start) => Some(start) >= literal.as_ref(),
+                    Bound::Unbounded => false,
+                };
+                let below = |bound: &Bound<ConvexValue>, inclusive: bool| match bound {
+                    Bound::Included(end) if inclusive => Some(end) <= literal.as_ref(),
+                    Bound::Included(end) => Some(end) < literal.as_ref(),
+                    Bound::Excluded(end) => Some(end) <= literal.as_ref(),
+                    Bound::Unbounded => false,
+                };
+                match comparison {
+                    FieldComparison::Eq => false,
+                    FieldComparison::Neq => {
+                        above(&inequality.start, false) || below(&inequality.end, false)
+                    },
+                    FieldComparison::Gt => above(&inequality.start, false),
+                    FieldComparison::Gte => above(&inequality.start, true),
+                    FieldComparison::Lt => below(&inequality.end, false),
+                    FieldComparison::Lte => below(&inequality.end, true),
+                }
+            },
+        }
+    }
+}
+
+/// A comparison of a field against a literal, normalized so the field is on
+/// the left.
+#[derive(Clone, Copy)]
+enum FieldComparison {
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+impl FieldComparison {
+    fn from_expression(expression: &Expression) -> Option<(&FieldPath, Self, &MaybeValue)> {
+        let (comparison, l, r) = match expression {
+            Expression::Eq(l, r) => (Self::Eq, l, r),
+            Expression::Neq(l, r) => (Self::Neq, l, r),
+            Expression::Lt(l, r) => (Self::Lt, l, r),
+            Expression::Lte(l, r) => (Self::Lte, l, r),
+            Expression::Gt(l, r) => (Self::Gt, l, r),
+            Expression::Gte(l, r) => (Self::Gte, l, r),
+            _ => return None,
+        };
+        match (&**l, &**r) {
+            (Expression::Field(field_path), Expression::Literal(literal)) => {
+                Some((field_path, comparison, literal))
+            },
+            (Expression::Literal(literal), Expression::Field(field_path)) => {
+                Some((field_path, comparison.flip(), literal))
+            },
+            _ => None,
+        }
+    }
+
+    fn flip(self) -> Self {
+        match self {
+            Self::Eq => Self::Eq,
+            Self::Neq => Self::Neq,
+            Self::Lt => Self::Gt,
+            Self::Lte => Self::Gte,
+            Self::Gt => Self::Lt,
+            Self::Gte => Self::Lte,
+        }
+    }
+
+    /// Evaluates `l <op> r` the same way `Expression::eval` does.
+    fn holds(self, l: &Option<ConvexValue>, r: &Option<ConvexValue>) -> bool {
+        match self {
+            Self::Eq => l == r,
+            Self::Neq => l != r,
+            Self::Lt => l < r,
+            Self::Lte => l <= r,
+            Self::Gt => l > r,
+            Self::Gte => l >= r,
+        }
+    }
+}
+
 struct IndexInequality {
     field_path: FieldPath,
     start: Bound<ConvexValue>,
@@ -520,6 +642,17 @@ fn invalid_index_range(
     )
 }
 
+fn range_does_not_imply_filter_error(index_name: &IndexName) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "IndexRangeDoesNotImplyFilter",
+        format!(
+            "{index_name} is a partial index, so it only contains documents matching its filter. \
+             The index range must restrict the indexed fields so that every document in it \
+             matches the filter, for example with `.eq()` on the fields the filter compares."
+        ),
+    )
+}
+
 fn field_not_in_index_error(
     index_name: &IndexName,
     field_path: &FieldPath,
@@ -1414,6 +1547,59 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_range_implies_partial_index_filter() -> anyhow::Result<()> {
+        let indexed_fields: IndexedFields =
+            vec!["status".parse()?, "priority".parse()?].try_into()?;
+        let status_is_open = Expression::field_eq_literal("status".parse()?, val!("open"));
+        let high_priority = Expression::Gte(
+            Box::new(Expression::Field("priority".parse()?)),
+            Box::new(Expression::Literal(maybe_val!(5))),
+        );
+        let compile = |range: Vec<IndexRangeExpression>, filter: &Expression| {
+            IndexRange {
+                index_name: "tasks.by_status".parse()?,
+                range,
+                order: Order::Asc,
+            }
+            .compile(indexed_fields.clone(), Some(filter), None)
+        };
+
+        let open = IndexRangeExpression::Eq("status".parse()?, maybe_val!("open"));
+        compile(vec![open.clone()], &status_is_open)?;
+        assert!(compile(vec![], &status_is_open).is_err());
+        assert!(compile(
+            vec![IndexRangeExpression::Eq(
+                "status".parse()?,
+                maybe_val!("closed")
+            )],
+            &status_is_open
+        )
+        .is_err());
+
+        let both = Expression::and(status_is_open.clone(), high_priority.clone());
+        compile(
+            vec![
+                open.clone(),
+                IndexRangeExpression::Gt("priority".parse()?, val!(7)),
+            ],
+            &both,
+        )?;
+        compile(
+            vec![
+                open.clone(),
+                IndexRangeExpression::Gte("priority".parse()?, val!(5)),
+            ],
+            &both,
+        )?;
+        assert!(compile(
+            vec![open, IndexRangeExpression::Gt("priority".parse()?, val!(3))],
+            &both,
+        )
+        .is_err());
+        Ok(())
+    }
+
     proptest! {
             #![proptest_config(
             ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
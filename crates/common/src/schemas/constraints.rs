@@ -0,0 +1,302 @@
+//! Constraints on the values a [`Validator`] accepts beyond their type, e.g.
+//! a string's length or a number's range.
+
+use std::{
+    cmp::Ordering,
+    fmt::{
+        self,
+        Display,
+    },
+};
+
+use errors::ErrorMetadata;
+use regex::Regex;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use value::{
+    export::ValueFormat,
+    ConvexValue,
+};
+
+use super::validator::Validator;
+
+/// Constraints a value must satisfy on top of matching its validator's type.
+/// Which constraints are allowed depends on the validator; see
+/// [`ValueConstraints::check_applies_to`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ValueConstraints {
+    /// Minimum number of characters in a string, bytes in `v.bytes()` or
+    /// elements in an array or set.
+    pub min_length: Option<u64>,
+    /// Maximum number of characters in a string, bytes in `v.bytes()` or
+    /// elements in an array or set.
+    pub max_length: Option<u64>,
+    /// Regular expression a string must match somewhere. Anchor it with `^`
+    /// and `$` to match the whole string.
+    pub pattern: Option<ConstraintPattern>,
+    /// Inclusive lower bound on a number or bigint, of the same type.
+    pub minimum: Option<ConvexValue>,
+    /// Inclusive upper bound on a number or bigint, of the same type.
+    pub maximum: Option<ConvexValue>,
+    /// Value filled in when an optional object field is missing.
+    pub default: Option<ConvexValue>,
+}
+
+/// A compiled [`ValueConstraints::pattern`], compared by its source.
+#[derive(Clone, Debug)]
+pub struct ConstraintPattern(Regex);
+
+impl ConstraintPattern {
+    pub fn new(pattern: &str) -> anyhow::Result<Self> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            invalid_constraint(format!("`pattern` {pattern:?} is not a valid regex: {e}"))
+        })?;
+        Ok(Self(regex))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    pub fn is_match(&self, s: &str) -> bool {
+        self.0.is_match(s)
+    }
+}
+
+impl PartialEq for ConstraintPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for ConstraintPattern {}
+
+impl PartialOrd for ConstraintPattern {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ConstraintPattern {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+pub fn invalid_constraint(message: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidValidatorConstraint", message)
+}
+
+impl ValueConstraints {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Checks that each constraint makes sense for values of `validator`,
+    /// e.g. that `pattern` is only used with `v.string()`. Doesn't check
+    /// `default`, which [`Validator::constrained`] checks against the
+    /// constrained validator.
+    pub fn check_applies_to(&self, validator: &Validator) -> anyhow::Result<()> {
+        if matches!(validator, Validator::Constrained(..)) {
+            anyhow::bail!(invalid_constraint(format!(
+                "Constraints can't be nested, found constraints on `{validator}`"
+            )));
+        }
+        if self.min_length.is_some() || self.max_length.is_some() {
+            anyhow::ensure!(
+                matches!(
+                    validator,
+                    Validator::String | Validator::Bytes | Validator::Array(_) | Validator::Set(_)
+                ),
+                invalid_constraint(format!(
+                    "`minLength` and `maxLength` only apply to strings, bytes, arrays and sets, \
+                     not `{validator}`"
+                ))
+            );
+            if let (Some(min_length), Some(max_length)) = (self.min_length, self.max_length) {
+                anyhow::ensure!(
+                    min_length <= max_length,
+                    invalid_constraint(format!(
+                        "`minLength` ({min_length}) is greater than `maxLength` ({max_length})"
+                    ))
+                );
+            }
+        }
+        if self.pattern.is_some() {
+            anyhow::ensure!(
+                matches!(validator, Validator::String),
+                invalid_constraint(format!(
+                    "`pattern` only applies to strings, not `{validator}`"
+                ))
+            );
+        }
+        for (name, bound) in [("minimum", &self.minimum), ("maximum", &self.maximum)] {
+            let Some(bound) = bound else {
+                continue;
+            };
+            let valid = match (validator, bound) {
+                (Validator::Float64, ConvexValue::Float64(f)) => !f.is_nan(),
+                (Validator::Int64, ConvexValue::Int64(_)) => true,
+                _ => false,
+            };
+            anyhow::ensure!(
+                valid,
+                invalid_constraint(format!(
+                    "`{name}` must be a number for `v.float64()` or a bigint for `v.int64()`, \
+                     found `{bound}` for `{validator}`"
+                ))
+            );
+        }
+        if let (Some(minimum), Some(maximum)) = (&self.minimum, &self.maximum) {
+            anyhow::ensure!(
+                compare_numbers(minimum, maximum) != Some(Ordering::Greater),
+                invalid_constraint(format!(
+                    "`minimum` ({minimum}) is greater than `maximum` ({maximum})"
+                ))
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the first constraint `value` violates, formatted as in
+    /// [`Display`], or `None` if it satisfies all of them. `value` is assumed
+    /// to match the constrained validator's type.
+    pub fn violation(&self, value: &ConvexValue) -> Option<String> {
+        if let Some(length) = length(value) {
+            if let Some(min_length) = self.min_length
+                && length < min_length
+            {
+                return Some(format!("minLength: {min_length}"));
+            }
+            if let Some(max_length) = self.max_length
+                && length > max_length
+            {
+                return Some(format!("maxLength: {max_length}"));
+            }
+        }
+        if let Some(pattern) = &self.pattern
+            && let ConvexValue::String(s) = value
+            && !pattern.is_match(s)
+        {
+            return Some(format!("pattern: {}", quote(pattern.as_str())));
+        }
+        if let Some(minimum) = &self.minimum
+            && !matches!(
+                compare_numbers(value, minimum),
+                Some(Ordering::Greater | Ordering::Equal)
+            )
+        {
+            return Some(format!("minimum: {minimum}"));
+        }
+        if let Some(maximum) = &self.maximum
+            && !matches!(
+                compare_numbers(value, maximum),
+                Some(Ordering::Less | Ordering::Equal)
+            )
+        {
+            return Some(format!("maximum: {maximum}"));
+        }
+        None
+    }
+
+    /// Adds the JSON Schema keywords for these constraints to `json_schema`,
+    /// the schema of the constrained `validator`. Constraints that don't
+    /// translate to the JSON representation of `validator` in `value_format`
+    /// are left out.
+    pub fn add_to_json_schema(
+        &self,
+        validator: &Validator,
+        value_format: ValueFormat,
+        json_schema: &mut JsonValue,
+    ) {
+        let Some(json_schema) = json_schema.as_object_mut() else {
+            return;
+        };
+        match validator {
+            Validator::String => {
+                if let Some(min_length) = self.min_length {
+                    json_schema.insert("minLength".to_string(), json!(min_length));
+                }
+                if let Some(max_length) = self.max_length {
+                    json_schema.insert("maxLength".to_string(), json!(max_length));
+                }
+                if let Some(pattern) = &self.pattern {
+                    json_schema.insert("pattern".to_string(), json!(pattern.as_str()));
+                }
+            },
+            Validator::Array(_) => {
+                if let Some(min_length) = self.min_length {
+                    json_schema.insert("minItems".to_string(), json!(min_length));
+                }
+                if let Some(max_length) = self.max_length {
+                    json_schema.insert("maxItems".to_string(), json!(max_length));
+                }
+            },
+            Validator::Float64 => {
+                for (name, bound) in [("minimum", &self.minimum), ("maximum", &self.maximum)] {
+                    if let Some(ConvexValue::Float64(f)) = bound
+                        && let Some(number) = serde_json::Number::from_f64(*f)
+                    {
+                        json_schema.insert(name.to_string(), JsonValue::Number(number));
+                    }
+                }
+            },
+            _ => {},
+        }
+        if let Some(default) = &self.default {
+            json_schema.insert("default".to_string(), default.clone().export(value_format));
+        }
+    }
+}
+
+impl Display for ValueConstraints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut entries = vec![];
+        if let Some(min_length) = self.min_length {
+            entries.push(format!("minLength: {min_length}"));
+        }
+        if let Some(max_length) = self.max_length {
+            entries.push(format!("maxLength: {max_length}"));
+        }
+        if let Some(pattern) = &self.pattern {
+            entries.push(format!("pattern: {}", quote(pattern.as_str())));
+        }
+        if let Some(minimum) = &self.minimum {
+            entries.push(format!("minimum: {minimum}"));
+        }
+        if let Some(maximum) = &self.maximum {
+            entries.push(format!("maximum: {maximum}"));
+        }
+        if let Some(default) = &self.default {
+            entries.push(format!("default: {default}"));
+        }
+        write!(f, "{{ {} }}", entries.join(", "))
+    }
+}
+
+fn length(value: &ConvexValue) -> Option<u64> {
+    let length = match value {
+        ConvexValue::String(s) => s.chars().count(),
+        ConvexValue::Bytes(b) => b.len(),
+        ConvexValue::Array(a) => a.len(),
+        ConvexValue::Set(s) => s.len(),
+        _ => return None,
+    };
+    Some(length as u64)
+}
+
+/// Compares two numbers of the same type. `NaN` isn't comparable, so it
+/// violates every bound.
+fn compare_numbers(left: &ConvexValue, right: &ConvexValue) -> Option<Ordering> {
+    match (left, right) {
+        (ConvexValue::Float64(l), ConvexValue::Float64(r)) => l.partial_cmp(r),
+        (ConvexValue::Int64(l), ConvexValue::Int64(r)) => Some(l.cmp(r)),
+        _ => None,
+    }
+}
+
+fn quote(s: &str) -> String {
+    JsonValue::String(s.to_string()).to_string()
+}
@@ -24,12 +24,17 @@ use value::{
 };
 
 use super::{
+    constraints::{
+        ConstraintPattern,
+        ValueConstraints,
+    },
     validator::{
         FieldValidator,
         LiteralValidator,
         ObjectValidator,
         Validator,
     },
+    AggregateIndexSchema,
     DatabaseSchema,
     DocumentSchema,
     IndexSchema,
@@ -45,9 +50,16 @@ use crate::{
         },
         vector_index::VectorDimensions,
     },
-    json::invalid_json,
+    json::{
+        invalid_json,
+        JsonExpression,
+    },
+    query::Expression,
     schemas::{
+        invalid_reference,
         invalid_top_level_type_in_schema,
+        OnDelete,
+        ReferenceSchema,
         SearchIndexSchema,
         TableDefinition,
         MAX_INDEXES_PER_TABLE,
@@ -133,9 +145,48 @@ struct TableDefinitionJson {
     indexes: Vec<JsonValue>,
     search_indexes: Option<Vec<JsonValue>>,
     vector_indexes: Option<Vec<JsonValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aggregate_indexes: Option<Vec<JsonValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    references: Option<Vec<ReferenceSchemaJson>>,
     document_type: Option<JsonValue>,
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReferenceSchemaJson {
+    field_name: String,
+    on_delete: OnDeleteJson,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum OnDeleteJson {
+    Restrict,
+    Cascade,
+    SetNull,
+}
+
+impl From<OnDeleteJson> for OnDelete {
+    fn from(on_delete: OnDeleteJson) -> Self {
+        match on_delete {
+            OnDeleteJson::Restrict => OnDelete::Restrict,
+            OnDeleteJson::Cascade => OnDelete::Cascade,
+            OnDeleteJson::SetNull => OnDelete::SetNull,
+        }
+    }
+}
+
+impl From<OnDelete> for OnDeleteJson {
+    fn from(on_delete: OnDelete) -> Self {
+        match on_delete {
+            OnDelete::Restrict => OnDeleteJson::Restrict,
+            OnDelete::Cascade => OnDeleteJson::Cascade,
+            OnDelete::SetNull => OnDeleteJson::SetNull,
+        }
+    }
+}
+
 // Collect the index names separately from the deduplicating map so that we can
 // complain complain about duplicate names
 fn parse_names_and_indexes<T: TryFrom<JsonValue, Error = anyhow::Error>>(
@@ -179,6 +230,7 @@ impl TryFrom<JsonValue> for TableDefinition {
         let j: TableDefinitionJson = serde_json::from_value(value).with_context(invalid_json)?;
         let search_indexes = j.search_indexes.unwrap_or_default();
         let vector_indexes = j.vector_indexes.unwrap_or_default();
+        let aggregate_indexes = j.aggregate_indexes.unwrap_or_default();
 
         let document_type = j.document_type.map(|t| t.try_into()).transpose()?;
 
@@ -191,7 +243,9 @@ impl TryFrom<JsonValue> for TableDefinition {
             index_validation_error::table_name_reserved(&table_name)
         );
 
-        if j.indexes.len() + vector_indexes.len() + search_indexes.len() > MAX_INDEXES_PER_TABLE {
+        if j.indexes.len() + vector_indexes.len() + search_indexes.len() + aggregate_indexes.len()
+            > MAX_INDEXES_PER_TABLE
+        {
             anyhow::bail!(index_validation_error::too_many_indexes(
                 &table_name,
                 MAX_INDEXES_PER_TABLE
@@ -233,12 +287,46 @@ impl TryFrom<JsonValue> for TableDefinition {
             |index1, index2| vector_field_not_unique(&table_name, index1, index2),
         )?;
 
+        let (aggregate_index_names, aggregate_indexes): (Vec<_>, BTreeMap<_, _>) =
+            parse_names_and_indexes(
+                &table_name,
+                aggregate_indexes,
+                |idx: &AggregateIndexSchema| &idx.index_descriptor,
+            )?;
+        for schema in aggregate_indexes.values() {
+            if schema.fields.is_empty() && schema.value_field.is_none() {
+                anyhow::bail!(index_validation_error::empty_aggregate_index(
+                    &table_name,
+                    &schema.index_descriptor
+                ));
+            }
+        }
+
         let all_index_names: Vec<_> = index_names
             .into_iter()
             .chain(search_index_names)
             .chain(vector_index_names)
+            .chain(aggregate_index_names)
             .collect();
 
+        let mut references = BTreeMap::new();
+        for reference in j.references.unwrap_or_default() {
+            let field: IdentifierFieldName = reference.field_name.parse()?;
+            let reference = ReferenceSchema::new(
+                &table_name,
+                field.clone(),
+                reference.on_delete.into(),
+                document_type.as_ref(),
+            )?;
+            if references.insert(field.clone(), reference).is_some() {
+                anyhow::bail!(invalid_reference(
+                    &table_name,
+                    &field,
+                    "the field has more than one reference"
+                ));
+            }
+        }
+
         let mut seen: HashSet<_> = HashSet::new();
         for index_name in all_index_names.into_iter() {
             // Validate the name
@@ -252,13 +340,28 @@ impl TryFrom<JsonValue> for TableDefinition {
             }
         }
 
-        Ok(Self {
+        let table_definition = Self {
             table_name,
             indexes,
             search_indexes,
             vector_indexes,
+            aggregate_indexes,
+            references,
             document_type,
-        })
+        };
+        for field in table_definition.references.keys() {
+            if table_definition.reference_index(field).is_none() {
+                anyhow::bail!(invalid_reference(
+                    &table_definition.table_name,
+                    field,
+                    &format!(
+                        "add an index whose first field is \"{field}\" so the documents \
+                         referencing a deleted document can be found"
+                    )
+                ));
+            }
+        }
+        Ok(table_definition)
     }
 }
 
@@ -271,6 +374,8 @@ impl TryFrom<TableDefinition> for JsonValue {
             indexes,
             search_indexes,
             vector_indexes,
+            aggregate_indexes,
+            references,
             document_type,
         }: TableDefinition,
     ) -> anyhow::Result<Self> {
@@ -292,11 +397,37 @@ impl TryFrom<TableDefinition> for JsonValue {
                 .map(JsonValue::try_from)
                 .collect::<anyhow::Result<Vec<_>>>()?,
         );
+        // Only emitted when present so existing schemas round-trip unchanged.
+        let aggregate_indexes = if aggregate_indexes.is_empty() {
+            None
+        } else {
+            Some(
+                aggregate_indexes
+                    .into_values()
+                    .map(JsonValue::try_from)
+                    .collect::<anyhow::Result<Vec<_>>>()?,
+            )
+        };
+        let references = if references.is_empty() {
+            None
+        } else {
+            Some(
+                references
+                    .into_values()
+                    .map(|reference| ReferenceSchemaJson {
+                        field_name: String::from(reference.field),
+                        on_delete: reference.on_delete.into(),
+                    })
+                    .collect(),
+            )
+        };
         Ok(serde_json::to_value(TableDefinitionJson {
             table_name,
             indexes,
             search_indexes,
             vector_indexes,
+            aggregate_indexes,
+            references,
             document_type,
         })?)
     }
@@ -307,6 +438,10 @@ impl TryFrom<TableDefinition> for JsonValue {
 struct IndexSchemaJson {
     index_descriptor: String,
     fields: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unique: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filter: Option<JsonExpression>,
 }
 
 impl TryFrom<JsonValue> for IndexSchema {
@@ -331,6 +466,14 @@ impl TryFrom<JsonValue> for IndexSchema {
         Ok(Self {
             index_descriptor,
             fields,
+            unique: j.unique.unwrap_or(false),
+            filter: j.filter.map(Expression::try_from).transpose().map_err(
+                |e: anyhow::Error| {
+                    e.wrap_error_message(|s| {
+                        format!("In filter of index \"{index_descriptor}\": {s}")
+                    })
+                },
+            )?,
         })
     }
 }
@@ -342,6 +485,8 @@ impl TryFrom<IndexSchema> for JsonValue {
         IndexSchema {
             index_descriptor,
             fields,
+            unique,
+            filter,
         }: IndexSchema,
     ) -> anyhow::Result<Self> {
         let index_schema_json = IndexSchemaJson {
@@ -350,11 +495,80 @@ impl TryFrom<IndexSchema> for JsonValue {
                 .into_iter()
                 .map(String::from)
                 .collect::<Vec<_>>(),
+            unique: unique.then_some(true),
+            filter: filter.map(JsonExpression::from),
         };
         Ok(serde_json::to_value(index_schema_json)?)
     }
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AggregateIndexSchemaJson {
+    index_descriptor: String,
+    fields: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value_field: Option<String>,
+}
+
+impl TryFrom<JsonValue> for AggregateIndexSchema {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        let j: AggregateIndexSchemaJson =
+            serde_json::from_value(value).with_context(invalid_json)?;
+        let index_descriptor = j.index_descriptor.parse()?;
+        let fields = j
+            .fields
+            .into_iter()
+            .map(|p| {
+                p.parse().with_context(|| {
+                    index_validation_error::invalid_index_field(&index_descriptor, &p)
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .try_into()
+            .map_err(|e: anyhow::Error| {
+                e.wrap_error_message(|s| format!("In index \"{index_descriptor}\": {s}"))
+            })?;
+        let value_field = j
+            .value_field
+            .map(|f| {
+                f.parse().with_context(|| {
+                    index_validation_error::invalid_index_field(&index_descriptor, &f)
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            index_descriptor,
+            fields,
+            value_field,
+        })
+    }
+}
+
+impl TryFrom<AggregateIndexSchema> for JsonValue {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        AggregateIndexSchema {
+            index_descriptor,
+            fields,
+            value_field,
+        }: AggregateIndexSchema,
+    ) -> anyhow::Result<Self> {
+        let aggregate_index_schema_json = AggregateIndexSchemaJson {
+            index_descriptor: String::from(index_descriptor),
+            fields: Vec::<FieldPath>::from(fields)
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>(),
+            value_field: value_field.map(String::from),
+        };
+        Ok(serde_json::to_value(aggregate_index_schema_json)?)
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct VectorIndexSchemaJson {
@@ -673,9 +887,18 @@ impl TryFrom<ValidatorJson> for Validator {
 impl TryFrom<JsonValue> for Validator {
     type Error = anyhow::Error;
 
-    fn try_from(value: JsonValue) -> anyhow::Result<Self> {
+    fn try_from(mut value: JsonValue) -> anyhow::Result<Self> {
+        // Constraints can be set on any validator type, so they sit next to the `type`
+        // tag.
+        let constraints = value
+            .as_object_mut()
+            .and_then(|object| object.remove("constraints"));
         let schema_type_json: ValidatorJson = serde_json::from_value(value)?;
-        schema_type_json.try_into()
+        let validator = schema_type_json.try_into()?;
+        match constraints {
+            Some(constraints) => Validator::constrained(validator, constraints.try_into()?),
+            None => Ok(validator),
+        }
     }
 }
 
@@ -726,11 +949,82 @@ impl TryFrom<Validator> for JsonValue {
                     .collect::<anyhow::Result<Vec<_>>>()?,
             },
             Validator::Any => ValidatorJson::Any,
+            Validator::Constrained(validator, constraints) => {
+                let mut json = JsonValue::try_from(*validator)?;
+                json.as_object_mut()
+                    .context("Validator JSON must be an object")?
+                    .insert("constraints".to_string(), constraints.try_into()?);
+                return Ok(json);
+            },
         };
         Ok(serde_json::to_value(schema_type)?)
     }
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ValueConstraintsJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    minimum: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    maximum: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<JsonValue>,
+}
+
+impl TryFrom<JsonValue> for ValueConstraints {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonValue) -> anyhow::Result<Self> {
+        let constraints_json: ValueConstraintsJson =
+            serde_json::from_value(value).context("Invalid validator constraints")?;
+        Ok(ValueConstraints {
+            min_length: constraints_json.min_length,
+            max_length: constraints_json.max_length,
+            pattern: constraints_json
+                .pattern
+                .map(|pattern| ConstraintPattern::new(&pattern))
+                .transpose()?,
+            minimum: constraints_json
+                .minimum
+                .map(ConvexValue::try_from)
+                .transpose()?,
+            maximum: constraints_json
+                .maximum
+                .map(ConvexValue::try_from)
+                .transpose()?,
+            default: constraints_json
+                .default
+                .map(ConvexValue::try_from)
+                .transpose()?,
+        })
+    }
+}
+
+impl TryFrom<ValueConstraints> for JsonValue {
+    type Error = anyhow::Error;
+
+    fn try_from(constraints: ValueConstraints) -> anyhow::Result<JsonValue> {
+        let constraints_json = ValueConstraintsJson {
+            min_length: constraints.min_length,
+            max_length: constraints.max_length,
+            pattern: constraints
+                .pattern
+                .map(|pattern| pattern.as_str().to_string()),
+            minimum: constraints.minimum.map(JsonValue::from),
+            maximum: constraints.maximum.map(JsonValue::from),
+            default: constraints.default.map(JsonValue::from),
+        };
+        Ok(serde_json::to_value(constraints_json)?)
+    }
+}
+
 impl TryFrom<JsonValue> for LiteralValidator {
     type Error = anyhow::Error;
 
@@ -48,13 +48,16 @@ use crate::{
     },
     document::ResolvedDocument,
     paths::FieldPath,
+    query::Expression,
     types::{
         IndexDescriptor,
         TableName,
     },
 };
 
+pub mod constraints;
 pub mod json;
+pub mod suggest;
 #[cfg(test)]
 mod tests;
 pub mod validator;
@@ -94,6 +97,45 @@ pub enum SchemaValidationError {
         table_in_schema: TableName,
         table_name: TableName,
     },
+    #[display(
+        fmt = "Index \"{index_descriptor}\" on table \"{table_name}\" is unique, but existing \
+               documents have the same values for its fields: {}",
+        "format_duplicate_ids(duplicates)"
+    )]
+    UniqueIndexHasDuplicates {
+        table_name: TableName,
+        index_descriptor: IndexDescriptor,
+        /// Groups of documents that share the same indexed values. This may
+        /// be a sample rather than every duplicate in the table.
+        duplicates: Vec<Vec<DeveloperDocumentId>>,
+    },
+    #[display(
+        fmt = "Document with ID \"{}\" in table \"{table_name}\" references document \"{}\" in \
+               field \"{field}\", which doesn't exist in table \"{referenced_table}\"",
+        "id.encode()",
+        "referenced_id.encode()"
+    )]
+    DanglingReference {
+        table_name: TableName,
+        id: DeveloperDocumentId,
+        field: IdentifierFieldName,
+        referenced_table: TableName,
+        referenced_id: DeveloperDocumentId,
+    },
+}
+
+fn format_duplicate_ids(duplicates: &[Vec<DeveloperDocumentId>]) -> String {
+    duplicates
+        .iter()
+        .map(|ids| {
+            let ids: Vec<_> = ids
+                .iter()
+                .map(|id| format!("\"{}\"", id.encode()))
+                .collect();
+            format!("[{}]", ids.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 #[derive(derive_more::Display, Debug, Clone, PartialEq)]
@@ -173,6 +215,8 @@ macro_rules! db_schema {
                         indexes: Default::default(),
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
+                        aggregate_indexes: Default::default(),
+                        references: Default::default(),
                         document_type: Some($document_schema),
                     };
                     tables.insert(table_name, table_def);
@@ -204,6 +248,8 @@ macro_rules! db_schema_not_validated {
                         indexes: Default::default(),
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
+                        aggregate_indexes: Default::default(),
+                        references: Default::default(),
                         document_type: Some($document_schema),
                     };
                     tables.insert(table_name, table_def);
@@ -257,6 +303,8 @@ macro_rules! db_schema_with_vector_indexes {
                         indexes: Default::default(),
                         search_indexes: Default::default(),
                         vector_indexes,
+                        aggregate_indexes: Default::default(),
+                        references: Default::default(),
                         document_type: Some($document_schema),
                     };
                     tables.insert(table_name, table_def);
@@ -534,6 +582,8 @@ pub struct TableDefinition {
     pub indexes: BTreeMap<IndexDescriptor, IndexSchema>,
     pub search_indexes: BTreeMap<IndexDescriptor, SearchIndexSchema>,
     pub vector_indexes: BTreeMap<IndexDescriptor, VectorIndexSchema>,
+    pub aggregate_indexes: BTreeMap<IndexDescriptor, AggregateIndexSchema>,
+    pub references: BTreeMap<IdentifierFieldName, ReferenceSchema>,
     pub document_type: Option<DocumentSchema>,
 }
 
@@ -570,10 +620,35 @@ impl TableDefinition {
 
         let vector_index_fields = self.vector_fields();
 
+        let aggregate_index_fields =
+            self.aggregate_indexes
+                .iter()
+                .flat_map(|(index_descriptor, aggregate_index_schema)| {
+                    aggregate_index_schema
+                        .fields
+                        .iter()
+                        .chain(aggregate_index_schema.value_field.iter())
+                        .map(move |field_path| (index_descriptor, field_path))
+                });
+
         index_fields
             .chain(search_index_fields)
             .chain(search_index_filter_fields)
             .chain(vector_index_fields)
+            .chain(aggregate_index_fields)
+    }
+
+    /// The index used to find the documents referencing a deleted document
+    /// through `field`: the first unfiltered database index whose first field
+    /// is `field`.
+    pub fn reference_index(&self, field: &IdentifierFieldName) -> Option<&IndexSchema> {
+        self.indexes.values().find(|index| {
+            index.filter.is_none()
+                && index
+                    .fields
+                    .first()
+                    .is_some_and(|first| first.fields() == std::slice::from_ref(field))
+        })
     }
 
     pub fn vector_fields(&self) -> impl Iterator<Item = (&IndexDescriptor, &FieldPath)> {
@@ -598,6 +673,7 @@ impl proptest::arbitrary::Arbitrary for TableDefinition {
             prop::collection::vec(any::<IndexSchema>(), 0..6),
             prop::collection::vec(any::<SearchIndexSchema>(), 0..3),
             prop::collection::vec(any::<VectorIndexSchema>(), 0..3),
+            prop::collection::vec(any::<AggregateIndexSchema>(), 0..2),
             any_with::<Option<DocumentSchema>>((
                 prop::option::Probability::default(),
                 all_table_names,
@@ -605,14 +681,24 @@ impl proptest::arbitrary::Arbitrary for TableDefinition {
         )
             .prop_filter_map(
                 "index names must be unique",
-                move |(indexes, search_indexes, vector_indexes, document_type)| {
+                move |(
+                    indexes,
+                    search_indexes,
+                    vector_indexes,
+                    aggregate_indexes,
+                    document_type,
+                )| {
                     let index_descriptors: BTreeSet<_> = indexes
                         .iter()
                         .map(|i| &i.index_descriptor)
                         .chain(search_indexes.iter().map(|i| &i.index_descriptor))
                         .chain(vector_indexes.iter().map(|i| &i.index_descriptor))
+                        .chain(aggregate_indexes.iter().map(|i| &i.index_descriptor))
                         .collect();
-                    let expected = indexes.len() + search_indexes.len() + vector_indexes.len();
+                    let expected = indexes.len()
+                        + search_indexes.len()
+                        + vector_indexes.len()
+                        + aggregate_indexes.len();
                     assert!(index_descriptors.len() <= expected);
                     if index_descriptors.len() == expected {
                         Some(Self {
@@ -629,6 +715,11 @@ impl proptest::arbitrary::Arbitrary for TableDefinition {
                                 .into_iter()
                                 .map(|i| (i.index_descriptor.clone(), i))
                                 .collect(),
+                            aggregate_indexes: aggregate_indexes
+                                .into_iter()
+                                .map(|i| (i.index_descriptor.clone(), i))
+                                .collect(),
+                            references: BTreeMap::new(),
                             document_type,
                         })
                     } else {
@@ -644,6 +735,125 @@ impl proptest::arbitrary::Arbitrary for TableDefinition {
 pub struct IndexSchema {
     pub index_descriptor: IndexDescriptor,
     pub fields: IndexedFields,
+    pub unique: bool,
+    pub filter: Option<Expression>,
+}
+
+/// An index maintaining count and, if `value_field` is set, the sum/min/max
+/// of that field for every prefix of `fields`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct AggregateIndexSchema {
+    pub index_descriptor: IndexDescriptor,
+    pub fields: IndexedFields,
+    pub value_field: Option<FieldPath>,
+}
+
+/// What happens to the documents referencing a document when it's deleted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum OnDelete {
+    /// Fail the delete while any document references it.
+    Restrict,
+    /// Delete the referencing documents too.
+    Cascade,
+    /// Set the referencing field to `null`.
+    SetNull,
+}
+
+/// A top-level `v.id(...)` field whose referenced document must exist.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReferenceSchema {
+    pub field: IdentifierFieldName,
+    /// The table named in the field's `v.id(...)` validator.
+    pub referenced_table: TableName,
+    pub on_delete: OnDelete,
+}
+
+impl ReferenceSchema {
+    /// Resolves the referenced table from the field's validator in
+    /// `document_type`. Every object in the document type must declare
+    /// `field` as `v.id(table)`, optionally unioned with `v.null()`, and it
+    /// must allow `null` for [`OnDelete::SetNull`].
+    pub fn new(
+        table_name: &TableName,
+        field: IdentifierFieldName,
+        on_delete: OnDelete,
+        document_type: Option<&DocumentSchema>,
+    ) -> anyhow::Result<Self> {
+        let objects = match document_type {
+            Some(DocumentSchema::Union(objects)) if !objects.is_empty() => objects,
+            _ => anyhow::bail!(invalid_reference(
+                table_name,
+                &field,
+                "references require the table to have a document schema"
+            )),
+        };
+        let mut referenced_table: Option<&TableName> = None;
+        for object in objects {
+            let Some(field_validator) = object.0.get(&field) else {
+                anyhow::bail!(invalid_reference(
+                    table_name,
+                    &field,
+                    "the field must be in every object of the document schema"
+                ));
+            };
+            let (table, nullable) = match field_validator.validator() {
+                Validator::Id(table) => (table, false),
+                Validator::Union(members) => {
+                    let mut tables = members.iter().filter_map(|member| match member {
+                        Validator::Id(table) => Some(table),
+                        _ => None,
+                    });
+                    let only_ids_and_null = members
+                        .iter()
+                        .all(|member| matches!(member, Validator::Id(_) | Validator::Null));
+                    match (tables.next(), tables.next(), only_ids_and_null) {
+                        (Some(table), None, true) => (table, members.contains(&Validator::Null)),
+                        _ => anyhow::bail!(invalid_reference(
+                            table_name,
+                            &field,
+                            "the field must be a `v.id(...)`, optionally unioned with `v.null()`"
+                        )),
+                    }
+                },
+                _ => anyhow::bail!(invalid_reference(
+                    table_name,
+                    &field,
+                    "the field must be a `v.id(...)`, optionally unioned with `v.null()`"
+                )),
+            };
+            if *referenced_table.get_or_insert(table) != table {
+                anyhow::bail!(invalid_reference(
+                    table_name,
+                    &field,
+                    "the field must reference the same table in every object"
+                ));
+            }
+            if on_delete == OnDelete::SetNull && !nullable {
+                anyhow::bail!(invalid_reference(
+                    table_name,
+                    &field,
+                    "`onDelete: \"setNull\"` requires the field to allow `v.null()`"
+                ));
+            }
+        }
+        let referenced_table = referenced_table
+            .expect("document schema has at least one object")
+            .clone();
+        if referenced_table.is_system() {
+            anyhow::bail!(invalid_reference(
+                table_name,
+                &field,
+                "references to system tables aren't supported"
+            ));
+        }
+        Ok(Self {
+            field,
+            referenced_table,
+            on_delete,
+        })
+    }
 }
 
 impl Display for IndexSchema {
@@ -771,6 +981,18 @@ impl DocumentSchema {
         Ok(())
     }
 
+    /// Fills in the `default`s of fields missing from a new document. Only
+    /// document types with a single object have their defaults applied, since
+    /// which member of a union a document is meant to match is ambiguous.
+    pub fn apply_defaults(&self, value: ConvexObject) -> anyhow::Result<ConvexObject> {
+        match self {
+            DocumentSchema::Union(objects) if objects.len() == 1 => {
+                objects[0].apply_defaults(value)
+            },
+            _ => Ok(value),
+        }
+    }
+
     /// Returns `true` when it is sometimes possible to have a field with the
     /// given path on the document if this table definition is enforced, or
     /// `false` when it is never possible.
@@ -861,6 +1083,19 @@ fn invalid_top_level_type_in_schema(validator: &Validator) -> ErrorMetadata {
     )
 }
 
+pub fn invalid_reference(
+    table_name: &TableName,
+    field: &IdentifierFieldName,
+    reason: &str,
+) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidReference",
+        format!(
+            "In table \"{table_name}\" the reference on field \"{field}\" is invalid: {reason}."
+        ),
+    )
+}
+
 pub fn missing_schema_export_error() -> ErrorMetadata {
     ErrorMetadata::bad_request(
         "MissingSchemaExportError",
@@ -0,0 +1,285 @@
+//! Suggest a [`DatabaseSchema`] for existing data from the shapes inferred
+//! for each table.
+
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+
+use shape_inference::{
+    Shape,
+    ShapeConfig,
+    ShapeCounter,
+    ShapeEnum,
+};
+use value::{
+    id_v6::DeveloperDocumentId,
+    utils::all_tables_number_to_name,
+    NamespacedTableMapping,
+    TableName,
+    TableNumber,
+    VirtualTableMapping,
+};
+
+use super::{
+    validator::{
+        FieldValidator,
+        LiteralValidator,
+        ObjectValidator,
+        Validator,
+    },
+    DatabaseSchema,
+    DocumentSchema,
+    TableDefinition,
+};
+
+/// Options controlling how [`DatabaseSchema::suggest_from_shapes`] turns
+/// inferred shapes into validators.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SuggestSchemaOptions {
+    /// Keep the string literals seen in a field as a union of `v.literal`s.
+    /// Otherwise literals are widened to `v.string()`.
+    pub literal_unions: bool,
+    /// Merge a union of objects into a single object, marking fields that
+    /// aren't present in every member as optional. Otherwise each object shape
+    /// is kept as its own union member.
+    pub optional_fields: bool,
+    /// Suggest `v.id(table)` for strings that are IDs of an existing table.
+    /// Otherwise IDs are suggested as `v.string()`.
+    pub detect_ids: bool,
+}
+
+impl Default for SuggestSchemaOptions {
+    fn default() -> Self {
+        Self {
+            literal_unions: true,
+            optional_fields: true,
+            detect_ids: true,
+        }
+    }
+}
+
+struct SchemaSuggester<'a, F> {
+    options: &'a SuggestSchemaOptions,
+    table_number_to_name: F,
+}
+
+impl<'a, F: Fn(TableNumber) -> anyhow::Result<TableName>> SchemaSuggester<'a, F> {
+    fn id_validator(&self, table_number: TableNumber) -> Option<Validator> {
+        if !self.options.detect_ids {
+            return None;
+        }
+        (self.table_number_to_name)(table_number)
+            .ok()
+            .map(Validator::Id)
+    }
+
+    fn validator<C: ShapeConfig, S: ShapeCounter>(&self, shape: &Shape<C, S>) -> Validator {
+        match shape.variant() {
+            // `Never` only shows up nested in otherwise empty containers (e.g. `[]`), where
+            // any element type is a better suggestion than one matching nothing.
+            ShapeEnum::Never => Validator::Any,
+            ShapeEnum::Null => Validator::Null,
+            ShapeEnum::Int64 => Validator::Int64,
+            ShapeEnum::Float64
+            | ShapeEnum::NegativeInf
+            | ShapeEnum::PositiveInf
+            | ShapeEnum::NegativeZero
+            | ShapeEnum::NaN
+            | ShapeEnum::NormalFloat64 => Validator::Float64,
+            ShapeEnum::Boolean => Validator::Boolean,
+            ShapeEnum::StringLiteral(s) => {
+                if let Ok(id) = DeveloperDocumentId::decode(&s.literal)
+                    && let Some(validator) = self.id_validator(*id.table())
+                {
+                    validator
+                } else if self.options.literal_unions {
+                    Validator::Literal(LiteralValidator::String(s.literal.clone()))
+                } else {
+                    Validator::String
+                }
+            },
+            ShapeEnum::Id(table_number) => self
+                .id_validator(*table_number)
+                .unwrap_or(Validator::String),
+            ShapeEnum::FieldName | ShapeEnum::String => Validator::String,
+            ShapeEnum::Bytes => Validator::Bytes,
+            ShapeEnum::Array(array_type) => {
+                Validator::Array(Box::new(self.validator(array_type.element())))
+            },
+            ShapeEnum::Set(set_type) => {
+                Validator::Set(Box::new(self.validator(set_type.element())))
+            },
+            ShapeEnum::Map(map_type) => Validator::Map(
+                Box::new(self.validator(map_type.key())),
+                Box::new(self.validator(map_type.value())),
+            ),
+            ShapeEnum::Object(object_type) => {
+                let fields = object_type
+                    .iter()
+                    .map(|(field_name, field)| {
+                        (
+                            field_name.clone(),
+                            FieldValidator {
+                                validator: self.validator(&field.value_shape),
+                                optional: field.optional,
+                            },
+                        )
+                    })
+                    .collect();
+                Validator::Object(ObjectValidator(fields))
+            },
+            ShapeEnum::Record(record_type) => {
+                // Record keys can only be IDs or strings.
+                let key = match self.validator(record_type.field()) {
+                    key @ Validator::Id(_) => key,
+                    _ => Validator::String,
+                };
+                Validator::Record(Box::new(key), Box::new(self.validator(record_type.value())))
+            },
+            ShapeEnum::Union(union_type) => self.union(
+                union_type
+                    .iter()
+                    .map(|shape| self.validator(shape))
+                    .collect(),
+            ),
+            ShapeEnum::Unknown => Validator::Any,
+        }
+    }
+
+    /// Simplify a union of validators: flatten nested unions, drop literals
+    /// covered by `v.string()`, merge objects if requested and collapse
+    /// single-member unions.
+    fn union(&self, members: Vec<Validator>) -> Validator {
+        let mut flattened = BTreeSet::new();
+        let mut stack = members;
+        while let Some(member) = stack.pop() {
+            match member {
+                Validator::Union(nested) => stack.extend(nested),
+                Validator::Any => return Validator::Any,
+                member => {
+                    flattened.insert(member);
+                },
+            }
+        }
+        if flattened.contains(&Validator::String) {
+            flattened.retain(|v| !matches!(v, Validator::Literal(LiteralValidator::String(_))));
+        }
+        let mut members: Vec<_> = if self.options.optional_fields {
+            let (objects, mut others): (Vec<_>, Vec<_>) = flattened
+                .into_iter()
+                .partition(|v| matches!(v, Validator::Object(_)));
+            let objects: Vec<_> = objects
+                .into_iter()
+                .filter_map(|v| match v {
+                    Validator::Object(object) => Some(object),
+                    _ => None,
+                })
+                .collect();
+            if !objects.is_empty() {
+                others.push(Validator::Object(self.merge_objects(objects)));
+            }
+            others.sort();
+            others
+        } else {
+            flattened.into_iter().collect()
+        };
+        if members.len() == 1 {
+            return members.pop().unwrap();
+        }
+        Validator::Union(members)
+    }
+
+    /// Merge objects into one whose fields are the union of their fields. A
+    /// field is required only if it's required in every object.
+    fn merge_objects(&self, objects: Vec<ObjectValidator>) -> ObjectValidator {
+        let num_objects = objects.len();
+        let mut fields: BTreeMap<_, (Vec<Validator>, bool, usize)> = BTreeMap::new();
+        for ObjectValidator(object_fields) in objects {
+            for (field_name, field) in object_fields {
+                let (validators, optional, count) =
+                    fields.entry(field_name).or_insert((vec![], false, 0));
+                validators.push(field.validator);
+                *optional |= field.optional;
+                *count += 1;
+            }
+        }
+        let fields = fields
+            .into_iter()
+            .map(|(field_name, (validators, optional, count))| {
+                let field = FieldValidator {
+                    validator: self.union(validators),
+                    optional: optional || count < num_objects,
+                };
+                (field_name, field)
+            })
+            .collect();
+        ObjectValidator(fields)
+    }
+
+    fn document_schema<C: ShapeConfig, S: ShapeCounter>(
+        &self,
+        shape: &Shape<C, S>,
+    ) -> DocumentSchema {
+        if matches!(shape.variant(), ShapeEnum::Never) {
+            // An empty table gives us nothing to go on.
+            return DocumentSchema::Any;
+        }
+        let members = match self.validator(shape) {
+            Validator::Object(object) => vec![object],
+            Validator::Union(members) => {
+                let mut objects = vec![];
+                for member in members {
+                    let Validator::Object(object) = member else {
+                        return DocumentSchema::Any;
+                    };
+                    objects.push(object);
+                }
+                objects
+            },
+            _ => return DocumentSchema::Any,
+        };
+        let mut objects: Vec<_> = members
+            .into_iter()
+            .map(ObjectValidator::filter_system_fields)
+            .collect();
+        objects.sort();
+        objects.dedup();
+        DocumentSchema::Union(objects)
+    }
+}
+
+impl DatabaseSchema {
+    /// Suggest a schema with a document type for each table, derived from the
+    /// shape inferred for its documents. The suggestion has no indexes.
+    pub fn suggest_from_shapes<C: ShapeConfig, S: ShapeCounter>(
+        shapes: BTreeMap<TableName, Shape<C, S>>,
+        table_mapping: &NamespacedTableMapping,
+        virtual_table_mapping: &VirtualTableMapping,
+        options: &SuggestSchemaOptions,
+    ) -> Self {
+        let suggester = SchemaSuggester {
+            options,
+            table_number_to_name: all_tables_number_to_name(table_mapping, virtual_table_mapping),
+        };
+        let tables = shapes
+            .into_iter()
+            .map(|(table_name, shape)| {
+                let definition = TableDefinition {
+                    table_name: table_name.clone(),
+                    indexes: BTreeMap::new(),
+                    search_indexes: BTreeMap::new(),
+                    vector_indexes: BTreeMap::new(),
+                    aggregate_indexes: BTreeMap::new(),
+                    references: BTreeMap::new(),
+                    document_type: Some(suggester.document_schema(&shape)),
+                };
+                (table_name, definition)
+            })
+            .collect();
+        DatabaseSchema {
+            tables,
+            schema_validation: true,
+        }
+    }
+}
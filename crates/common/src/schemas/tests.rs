@@ -1,4 +1,5 @@
 use cmd_util::env::env_config;
+use errors::ErrorMetadataAnyhowExt;
 use proptest::prelude::*;
 use serde_json::{
     json,
@@ -15,6 +16,7 @@ use value::{
 };
 
 use crate::{
+    db_schema,
     db_schema_with_vector_indexes,
     object_validator,
     schemas::{
@@ -25,6 +27,7 @@ use crate::{
         },
         DatabaseSchema,
         DocumentSchema,
+        OnDelete,
         Validator,
     },
     testing::assert_roundtrips,
@@ -636,6 +639,77 @@ fn test_json_backwards_compatibility() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_references() -> anyhow::Result<()> {
+    let document_type = DocumentSchema::Union(vec![object_validator!(
+        "authorId" => FieldValidator::required_field_type(Validator::Id("users".parse()?)),
+        "editorId" => FieldValidator::optional_field_type(Validator::Union(vec![
+            Validator::Id("users".parse()?),
+            Validator::Null,
+        ])),
+        "title" => FieldValidator::required_field_type(Validator::String),
+    )]);
+    let schema_json = |indexes: JsonValue, references: JsonValue| -> anyhow::Result<JsonValue> {
+        let mut schema_json = JsonValue::try_from(db_schema!("posts" => document_type.clone()))?;
+        schema_json["tables"][0]["indexes"] = indexes;
+        schema_json["tables"][0]["references"] = references;
+        Ok(schema_json)
+    };
+    let indexes = json!([
+        {"indexDescriptor": "by_author", "fields": ["authorId", "title"]},
+        {"indexDescriptor": "by_editor", "fields": ["editorId"]},
+    ]);
+
+    let schema = DatabaseSchema::try_from(schema_json(
+        indexes.clone(),
+        json!([
+            {"fieldName": "authorId", "onDelete": "cascade"},
+            {"fieldName": "editorId", "onDelete": "setNull"},
+        ]),
+    )?)?;
+    let posts = &schema.tables[&"posts".parse()?];
+    let author = &posts.references[&"authorId".parse()?];
+    assert_eq!(author.referenced_table, "users".parse()?);
+    assert_eq!(author.on_delete, OnDelete::Cascade);
+    assert_eq!(
+        posts
+            .reference_index(&"authorId".parse()?)
+            .map(|index| index.index_descriptor.to_string()),
+        Some("by_author".to_string())
+    );
+    assert_eq!(
+        DatabaseSchema::try_from(JsonValue::try_from(schema.clone())?)?,
+        schema
+    );
+
+    // `setNull` needs a nullable field.
+    let error = DatabaseSchema::try_from(schema_json(
+        indexes.clone(),
+        json!([{"fieldName": "authorId", "onDelete": "setNull"}]),
+    )?)
+    .unwrap_err();
+    assert_eq!(error.short_msg(), "InvalidReference");
+    assert!(error.to_string().contains("v.null()"), "{error}");
+
+    // Only `v.id(...)` fields can be references.
+    let error = DatabaseSchema::try_from(schema_json(
+        indexes,
+        json!([{"fieldName": "title", "onDelete": "restrict"}]),
+    )?)
+    .unwrap_err();
+    assert_eq!(error.short_msg(), "InvalidReference");
+
+    // References need an index to find the referencing documents.
+    let error = DatabaseSchema::try_from(schema_json(
+        json!([{"indexDescriptor": "by_title", "fields": ["title", "authorId"]}]),
+        json!([{"fieldName": "authorId", "onDelete": "restrict"}]),
+    )?)
+    .unwrap_err();
+    assert_eq!(error.short_msg(), "InvalidReference");
+    assert!(error.to_string().contains("add an index"), "{error}");
+    Ok(())
+}
+
 fn empty_table_mapping() -> NamespacedTableMapping {
     TableMapping::new().namespace(TableNamespace::test_user())
 }
@@ -1588,3 +1662,152 @@ mod can_contain_field {
         Ok(())
     }
 }
+
+mod suggest_schema {
+    use std::collections::BTreeMap;
+
+    use shape_inference::{
+        testing::TestConfig,
+        CountedShape,
+    };
+    use value::{
+        assert_val,
+        ResolvedDocumentId,
+        TableName,
+        TableNamespace,
+        VirtualTableMapping,
+    };
+
+    use crate::{
+        object_validator,
+        schemas::{
+            suggest::SuggestSchemaOptions,
+            tests::empty_table_mapping,
+            validator::{
+                FieldValidator,
+                LiteralValidator,
+                Validator,
+            },
+            DatabaseSchema,
+            DocumentSchema,
+        },
+        testing::TestIdGenerator,
+    };
+
+    fn suggest(
+        shape: CountedShape<TestConfig>,
+        options: SuggestSchemaOptions,
+    ) -> anyhow::Result<Option<DocumentSchema>> {
+        let table_name: TableName = "table".parse()?;
+        let mut schema = DatabaseSchema::suggest_from_shapes(
+            BTreeMap::from([(table_name.clone(), shape)]),
+            &empty_table_mapping(),
+            &VirtualTableMapping::new(),
+            &options,
+        );
+        Ok(schema.tables.remove(&table_name).unwrap().document_type)
+    }
+
+    #[test]
+    fn test_literal_unions() -> anyhow::Result<()> {
+        let shape = CountedShape::<TestConfig>::empty()
+            .insert_value(&assert_val!({"kind" => "cat"}))
+            .insert_value(&assert_val!({"kind" => "dog"}));
+
+        let literals = Validator::Union(vec![
+            Validator::Literal(LiteralValidator::String("cat".try_into()?)),
+            Validator::Literal(LiteralValidator::String("dog".try_into()?)),
+        ]);
+        assert_eq!(
+            suggest(shape.clone(), SuggestSchemaOptions::default())?,
+            Some(DocumentSchema::Union(vec![
+                object_validator!("kind" => FieldValidator::required_field_type(literals))
+            ]))
+        );
+
+        let options = SuggestSchemaOptions {
+            literal_unions: false,
+            ..SuggestSchemaOptions::default()
+        };
+        assert_eq!(
+            suggest(shape, options)?,
+            Some(DocumentSchema::Union(vec![
+                object_validator!("kind" => FieldValidator::required_field_type(Validator::String))
+            ]))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_optional_fields() -> anyhow::Result<()> {
+        let shape = CountedShape::<TestConfig>::empty()
+            .insert_value(&assert_val!({"count" => 1, "name" => "x1"}))
+            .insert_value(&assert_val!({"count" => 2.5}))
+            .insert_value(&assert_val!({"count" => 3, "tags" => [true]}));
+
+        assert_eq!(
+            suggest(shape, SuggestSchemaOptions::default())?,
+            Some(DocumentSchema::Union(vec![object_validator!(
+                "count" => FieldValidator::required_field_type(Validator::Union(vec![
+                    Validator::Float64,
+                    Validator::Int64,
+                ])),
+                "name" => FieldValidator::optional_field_type(Validator::String),
+                "tags" => FieldValidator::optional_field_type(Validator::Array(Box::new(
+                    Validator::Boolean
+                ))),
+            )]))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_ids() -> anyhow::Result<()> {
+        let mut id_generator = TestIdGenerator::new();
+        let dog_id: ResolvedDocumentId = id_generator.user_generate(&"dogs".parse()?);
+        let shape =
+            CountedShape::<TestConfig>::empty().insert_value(&assert_val!({"owner" => dog_id}));
+        let table_name: TableName = "table".parse()?;
+        let table_mapping = id_generator.namespace(TableNamespace::test_user());
+
+        let suggest_with = |options: SuggestSchemaOptions| {
+            DatabaseSchema::suggest_from_shapes(
+                BTreeMap::from([(table_name.clone(), shape.clone())]),
+                &table_mapping,
+                &VirtualTableMapping::new(),
+                &options,
+            )
+            .tables[&table_name]
+                .document_type
+                .clone()
+        };
+        assert_eq!(
+            suggest_with(SuggestSchemaOptions::default()),
+            Some(DocumentSchema::Union(vec![object_validator!(
+                "owner" => FieldValidator::required_field_type(Validator::Id("dogs".parse()?))
+            )]))
+        );
+        assert_eq!(
+            suggest_with(SuggestSchemaOptions {
+                detect_ids: false,
+                ..SuggestSchemaOptions::default()
+            }),
+            Some(DocumentSchema::Union(vec![object_validator!(
+                "owner" => FieldValidator::required_field_type(Validator::String)
+            )]))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_table() -> anyhow::Result<()> {
+        assert_eq!(
+            suggest(
+                CountedShape::<TestConfig>::empty(),
+                SuggestSchemaOptions::default()
+            )?,
+            Some(DocumentSchema::Any)
+        );
+        Ok(())
+    }
+}
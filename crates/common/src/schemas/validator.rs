@@ -44,7 +44,13 @@ use value::{
     VirtualTableMapping,
 };
 
-use super::DocumentSchema;
+use super::{
+    constraints::{
+        invalid_constraint,
+        ValueConstraints,
+    },
+    DocumentSchema,
+};
 use crate::{
     document::{
         CREATION_TIME_FIELD,
@@ -73,6 +79,9 @@ pub enum Validator {
     Object(ObjectValidator),
     Union(Vec<Validator>),
     Any,
+    /// A validator whose values must also satisfy some constraints. Build
+    /// these with [`Validator::constrained`].
+    Constrained(Box<Validator>, ValueConstraints),
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -146,11 +155,44 @@ impl Display for Validator {
                 display_sequence(f, ["v.union(", ")"], validators.iter())
             },
             Validator::Any => write!(f, "v.any()"),
+            Validator::Constrained(validator, constraints) => {
+                write!(f, "v.constrained({validator}, {constraints})")
+            },
         }
     }
 }
 
 impl Validator {
+    /// Wraps `validator` so its values must also satisfy `constraints`,
+    /// checking that the constraints apply to it and that the default, if
+    /// any, is valid.
+    pub fn constrained(
+        validator: Validator,
+        constraints: ValueConstraints,
+    ) -> anyhow::Result<Self> {
+        if constraints.is_empty() {
+            return Ok(validator);
+        }
+        constraints.check_applies_to(&validator)?;
+        let default = constraints.default.clone();
+        let constrained = Validator::Constrained(Box::new(validator), constraints);
+        if let Some(default) = default {
+            // Defaults are part of the schema, so there's no table mapping to check IDs
+            // against.
+            let no_tables = |_: TableNumber| -> anyhow::Result<TableName> {
+                anyhow::bail!("Defaults can't contain IDs")
+            };
+            if let Err(e) =
+                constrained.check_value_internal(&default, &no_tables, ValidationContext::new())
+            {
+                anyhow::bail!(invalid_constraint(format!(
+                    "`default` doesn't match its validator: {e}"
+                )));
+            }
+        }
+        Ok(constrained)
+    }
+
     pub fn check_value(
         &self,
         value: &ConvexValue,
@@ -316,6 +358,21 @@ impl Validator {
                 });
             },
             (Validator::Any, _) => return Ok(()),
+            (Validator::Constrained(validator, constraints), value) => {
+                validator.check_value_internal(
+                    value,
+                    all_tables_number_to_name,
+                    context.clone(),
+                )?;
+                if let Some(constraint) = constraints.violation(value) {
+                    return Err(ValidationError::ConstraintViolation {
+                        value: value.clone(),
+                        constraint,
+                        validator: self.clone(),
+                        context,
+                    });
+                }
+            },
             (..) => {
                 return Err(ValidationError::NoMatch {
                     value: value.clone(),
@@ -485,6 +542,9 @@ impl Validator {
                 }
             },
 
+            // Constraints only narrow the values a validator accepts.
+            (Validator::Constrained(validator, _), _) => validator.is_subset(superset),
+
             _ => false,
         }
     }
@@ -516,6 +576,9 @@ impl Validator {
             Validator::Union(unions) => unions
                 .iter()
                 .any(|v| v.is_string_subtype_with_string_literal()),
+            Validator::Constrained(validator, _) => {
+                validator.is_string_subtype_with_string_literal()
+            },
         }
     }
 
@@ -544,6 +607,7 @@ impl Validator {
                         ._can_contain_field(&field_path_parts[1..])
                 })
                 .unwrap_or(false),
+            Validator::Constrained(validator, _) => validator._can_contain_field(field_path_parts),
             _ => false,
         }
     }
@@ -566,6 +630,7 @@ impl Validator {
             },
             Validator::Any => true,
             Validator::Union(validators) => validators.iter().any(Self::is_valid_vector_validator),
+            Validator::Constrained(validator, _) => Self::is_valid_vector_validator(validator),
             _ => false,
         };
     }
@@ -588,6 +653,9 @@ impl Validator {
                         ._overlaps_with_array_float64(&field_path_parts[1..])
                 })
                 .unwrap_or(true),
+            Validator::Constrained(validator, _) => {
+                validator._overlaps_with_array_float64(field_path_parts)
+            },
             _ => false,
         }
     }
@@ -606,7 +674,7 @@ impl Validator {
             // Values that map to `any`
             | Validator::Record(_, _)
             | Validator::Any => Ok(()),
-            Validator::Array(element_validator) => {
+            Validator::Array(element_validator) | Validator::Constrained(element_validator, _) => {
                 element_validator.ensure_supported_for_streaming_export()
             },
             Validator::Set(element_validator) => {
@@ -680,6 +748,11 @@ impl Validator {
                 json_schemas::union(options)
             },
             Validator::Any => json_schemas::any(),
+            Validator::Constrained(validator, constraints) => {
+                let mut json_schema = validator.to_json_schema(value_format);
+                constraints.add_to_json_schema(validator, value_format, &mut json_schema);
+                json_schema
+            },
         };
         json_schema
     }
@@ -694,7 +767,7 @@ impl Validator {
                         yield table_name;
                     }
                 },
-                Self::Array(item) | Self::Set(item) => {
+                Self::Array(item) | Self::Set(item) | Self::Constrained(item, _) => {
                     for table_name in item.foreign_keys() {
                         yield table_name;
                     }
@@ -724,6 +797,29 @@ impl Validator {
         ))
     }
 
+    /// Whether this validator or a nested object field has a `default`, i.e.
+    /// whether [`Validator::apply_defaults`] can change a value.
+    pub fn has_defaults(&self) -> bool {
+        match self {
+            Self::Object(o) => o.has_defaults(),
+            Self::Constrained(v, constraints) => constraints.default.is_some() || v.has_defaults(),
+            _ => false,
+        }
+    }
+
+    /// Fills in defaults for the missing fields of `value` if it's an object,
+    /// recursing into fields that are objects themselves. Defaults inside
+    /// unions, arrays and other containers aren't applied.
+    pub fn apply_defaults(&self, value: ConvexValue) -> anyhow::Result<ConvexValue> {
+        match (self, value) {
+            (Self::Object(o), ConvexValue::Object(object)) => {
+                Ok(ConvexValue::Object(o.apply_defaults(object)?))
+            },
+            (Self::Constrained(v, _), value) => v.apply_defaults(value),
+            (_, value) => Ok(value),
+        }
+    }
+
     pub fn has_map_or_set(&self) -> bool {
         match self {
             Self::Id(_)
@@ -740,6 +836,7 @@ impl Validator {
             Self::Record(k, v) => k.has_map_or_set() || v.has_map_or_set(),
             Self::Object(o) => o.has_map_or_set(),
             Self::Union(u) => u.iter().any(|o| o.has_map_or_set()),
+            Self::Constrained(v, _) => v.has_map_or_set(),
         }
     }
 
@@ -760,6 +857,10 @@ impl Validator {
             | Validator::Map(..)
             | Validator::Any => self,
             Validator::Object(o) => Validator::Object(o.filter_system_fields()),
+            Validator::Constrained(validator, constraints) => Validator::Constrained(
+                Box::new(validator.filter_top_level_system_fields()),
+                constraints,
+            ),
             Validator::Union(validators) => Validator::Union(
                 validators
                     .into_iter()
@@ -937,6 +1038,36 @@ impl ObjectValidator {
         fields.values().any(|f| f.has_map_or_set())
     }
 
+    pub fn has_defaults(&self) -> bool {
+        self.0.values().any(|f| f.validator.has_defaults())
+    }
+
+    /// Sets each missing field that has a `default` to it. See
+    /// [`Validator::apply_defaults`].
+    pub fn apply_defaults(&self, object: ConvexObject) -> anyhow::Result<ConvexObject> {
+        if !self.has_defaults() {
+            return Ok(object);
+        }
+        let mut fields = BTreeMap::from(object);
+        for (field_name, field_validator) in &self.0 {
+            let field_name = FieldName::from(field_name.clone());
+            let value = match fields.remove(&field_name) {
+                Some(value) => field_validator.validator.apply_defaults(value)?,
+                None => {
+                    let Validator::Constrained(_, constraints) = &field_validator.validator else {
+                        continue;
+                    };
+                    let Some(default) = &constraints.default else {
+                        continue;
+                    };
+                    default.clone()
+                },
+            };
+            fields.insert(field_name, value);
+        }
+        fields.try_into()
+    }
+
     pub fn to_json_schema(
         &self,
         add_top_level_fields: AddTopLevelFields,
@@ -1096,6 +1227,15 @@ Validator: {validator}")]
         validator: Validator,
         context: ValidationContext,
     },
+    #[display(
+        fmt = "`{value}` does not satisfy `{constraint}` in validator `{validator}`.{context}"
+    )]
+    ConstraintViolation {
+        value: ConvexValue,
+        constraint: String,
+        validator: Validator,
+        context: ValidationContext,
+    },
 }
 
 #[cfg(test)]
@@ -1709,6 +1849,131 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_constraints() -> anyhow::Result<()> {
+        let validator = Validator::try_from(json!({
+            "type": "object",
+            "value": {
+                "name": {
+                    "fieldType": {
+                        "type": "string",
+                        "constraints": {"minLength": 1, "maxLength": 5, "pattern": "^[a-z]+$"},
+                    },
+                    "optional": false,
+                },
+                "age": {
+                    "fieldType": {"type": "number", "constraints": {"minimum": 0, "maximum": 150}},
+                    "optional": false,
+                },
+                "tags": {
+                    "fieldType": {
+                        "type": "array",
+                        "value": {"type": "string"},
+                        "constraints": {"maxLength": 2, "default": []},
+                    },
+                    "optional": true,
+                },
+            },
+        }))?;
+        assert_eq!(
+            Validator::try_from(serde_json::Value::try_from(validator.clone())?)?,
+            validator
+        );
+
+        let check = |value: ConvexObject| {
+            validator.check_value(
+                &ConvexValue::Object(value),
+                &empty_table_mapping(),
+                &VirtualTableMapping::new(),
+            )
+        };
+        check(assert_obj!("name" => "ada", "age" => 36.0))?;
+        let too_many_tags = ConvexValue::Array(array![
+            assert_val!("a"),
+            assert_val!("b"),
+            assert_val!("c")
+        ]?);
+        for (value, expected_constraint) in [
+            (assert_obj!("name" => "", "age" => 36.0), "minLength: 1"),
+            (
+                assert_obj!("name" => "lovelace", "age" => 36.0),
+                "maxLength: 5",
+            ),
+            (
+                assert_obj!("name" => "Ada", "age" => 36.0),
+                "pattern: \"^[a-z]+$\"",
+            ),
+            (assert_obj!("name" => "ada", "age" => -1.0), "minimum"),
+            (assert_obj!("name" => "ada", "age" => f64::NAN), "minimum"),
+            (
+                assert_obj!(
+                    "name" => "ada",
+                    "age" => 36.0,
+                    "tags" => too_many_tags,
+                ),
+                "maxLength: 2",
+            ),
+        ] {
+            must_let::must_let!(
+                let Err(ValidationError::ConstraintViolation { constraint, context, .. }) =
+                    check(value)
+            );
+            assert!(constraint.starts_with(expected_constraint), "{constraint}");
+            assert!(context.to_string().starts_with("Path: ."), "{context}");
+        }
+
+        // Missing fields are set to their default.
+        must_let::must_let!(let Validator::Object(object_validator) = &validator);
+        assert_eq!(
+            object_validator.apply_defaults(assert_obj!("name" => "ada", "age" => 36.0))?,
+            assert_obj!("name" => "ada", "age" => 36.0, "tags" => ConvexValue::Array(array![])),
+        );
+
+        // Constraints narrow the values a validator accepts.
+        let name_validator =
+            object_validator.0[&"name".parse::<value::IdentifierFieldName>()?].validator();
+        assert!(name_validator.is_subset(&Validator::String));
+        assert!(!Validator::String.is_subset(name_validator));
+
+        for invalid in [
+            json!({"type": "number", "constraints": {"pattern": "a"}}),
+            json!({"type": "string", "constraints": {"pattern": "("}}),
+            json!({"type": "string", "constraints": {"minLength": 2, "maxLength": 1}}),
+            json!({"type": "bigint", "constraints": {"minimum": 1}}),
+            json!({"type": "string", "constraints": {"minLength": 2, "default": "a"}}),
+        ] {
+            let err = Validator::try_from(invalid).unwrap_err();
+            assert_eq!(err.short_msg(), "InvalidValidatorConstraint");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_system_fields_of_constrained_object() -> anyhow::Result<()> {
+        let validator = Validator::try_from(json!({
+            "type": "object",
+            "value": {
+                "_id": {"fieldType": {"type": "string"}, "optional": true},
+                "name": {"fieldType": {"type": "string"}, "optional": false},
+            },
+            "constraints": {"default": {"name": "ada"}},
+        }))?;
+        must_let::must_let!(
+            let Validator::Constrained(object_validator, _) =
+                validator.filter_top_level_system_fields()
+        );
+        must_let::must_let!(let Validator::Object(object_validator) = *object_validator);
+        assert_eq!(
+            object_validator
+                .0
+                .keys()
+                .map(|f| f.to_string())
+                .collect::<Vec<_>>(),
+            vec!["name"]
+        );
+        Ok(())
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig { cases: 64 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1), failure_persistence: None, .. ProptestConfig::default() })]
 
@@ -141,6 +141,8 @@ pub enum FunctionCaller {
     Action {
         parent_scheduled_job: Option<DeveloperDocumentId>,
     },
+    // A batch of a migration's transform, run by the migration worker.
+    Migration,
 }
 
 impl FunctionCaller {
@@ -152,7 +154,8 @@ impl FunctionCaller {
             FunctionCaller::HttpEndpoint
             | FunctionCaller::Cron
             | FunctionCaller::Scheduler { .. }
-            | FunctionCaller::Action { .. } => None,
+            | FunctionCaller::Action { .. }
+            | FunctionCaller::Migration => None,
         }
         .cloned()
     }
@@ -163,7 +166,8 @@ impl FunctionCaller {
             | FunctionCaller::HttpApi(_)
             | FunctionCaller::Tester(_)
             | FunctionCaller::HttpEndpoint
-            | FunctionCaller::Cron => None,
+            | FunctionCaller::Cron
+            | FunctionCaller::Migration => None,
             FunctionCaller::Scheduler { job_id } => Some(*job_id),
             FunctionCaller::Action {
                 parent_scheduled_job,
@@ -178,7 +182,8 @@ impl FunctionCaller {
             | FunctionCaller::Tester(_)
             | FunctionCaller::HttpEndpoint
             | FunctionCaller::Cron
-            | FunctionCaller::Scheduler { .. } => true,
+            | FunctionCaller::Scheduler { .. }
+            | FunctionCaller::Migration => true,
             FunctionCaller::Action { .. } => false,
         }
     }
@@ -194,7 +199,8 @@ impl FunctionCaller {
             | FunctionCaller::Tester(_) => true,
             FunctionCaller::Cron
             | FunctionCaller::Scheduler { .. }
-            | FunctionCaller::Action { .. } => false,
+            | FunctionCaller::Action { .. }
+            | FunctionCaller::Migration => false,
         }
     }
 
@@ -210,7 +216,8 @@ impl FunctionCaller {
             FunctionCaller::Tester(_)
             | FunctionCaller::Cron
             | FunctionCaller::Scheduler { .. }
-            | FunctionCaller::Action { .. } => AllowedVisibility::All,
+            | FunctionCaller::Action { .. }
+            | FunctionCaller::Migration => AllowedVisibility::All,
         }
     }
 }
@@ -225,6 +232,7 @@ impl fmt::Display for FunctionCaller {
             FunctionCaller::Cron => "Cron",
             FunctionCaller::Scheduler { .. } => "Scheduler",
             FunctionCaller::Action { .. } => "Action",
+            FunctionCaller::Migration => "Migration",
         };
         write!(f, "{s}")
     }
@@ -258,6 +266,7 @@ impl From<FunctionCaller> for pb::common::FunctionCaller {
                 };
                 pb::common::function_caller::Caller::Action(caller)
             },
+            FunctionCaller::Migration => pb::common::function_caller::Caller::Migration(()),
         };
         Self {
             caller: Some(caller),
@@ -299,6 +308,7 @@ impl TryFrom<pb::common::FunctionCaller> for FunctionCaller {
                     parent_scheduled_job,
                 }
             },
+            Some(pb::common::function_caller::Caller::Migration(())) => FunctionCaller::Migration,
             None => anyhow::bail!("Missing `caller` field"),
         };
         Ok(caller)
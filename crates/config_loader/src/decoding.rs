@@ -1,6 +1,10 @@
 //! Decoding configuration files into Rust types
-use std::marker::PhantomData;
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData,
+};
 
+use anyhow::Context;
 use prost_reflect::{
     prost::Message,
     DynamicMessage,
@@ -67,3 +71,33 @@ impl<T: ProstMessage> ConfigDecoder for TextProtoDecoder<T> {
         Ok(message.transcode_to()?)
     }
 }
+
+/// Decodes a file of `NAME=value` lines, skipping blank lines and lines
+/// starting with `#`.
+#[derive(Copy, Clone)]
+pub struct KeyValueDecoder;
+
+impl ConfigDecoder for KeyValueDecoder {
+    type Output = BTreeMap<String, String>;
+
+    fn decode(&self, contents: Vec<u8>) -> anyhow::Result<Self::Output> {
+        let contents = String::from_utf8(contents)?;
+        let mut out = BTreeMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line
+                .split_once('=')
+                .with_context(|| format!("Line {} is not of the form NAME=value", i + 1))?;
+            let name = name.trim();
+            anyhow::ensure!(
+                out.insert(name.to_owned(), value.trim().to_owned())
+                    .is_none(),
+                "{name} is set more than once"
+            );
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,143 @@
+//! Overriding live knobs from a config file.
+
+use std::path::PathBuf;
+
+use common::{
+    errors::report_error,
+    live_knobs::{
+        apply_overrides,
+        DynamicKnob,
+    },
+    runtime::{
+        Runtime,
+        SpawnHandle,
+    },
+};
+use futures::StreamExt;
+use tokio::signal::unix::SignalKind;
+
+use crate::{
+    decoding::KeyValueDecoder,
+    ConfigLoader,
+};
+
+/// Overrides the knobs in a registry (usually
+/// [`common::knobs::LIVE_KNOBS`]) with the `NAME=value` lines of a config
+/// file, and applies the file again whenever a signal is received. A reload
+/// with an unknown knob or an invalid value is logged and leaves every knob
+/// unchanged. Knobs removed from the file go back to their initial value.
+pub struct KnobOverrides<RT: Runtime> {
+    loader: ConfigLoader<RT, KeyValueDecoder>,
+    handle: RT::Handle,
+}
+
+impl<RT: Runtime> KnobOverrides<RT> {
+    /// Fails if the config file can't be read or its initial contents are
+    /// invalid.
+    pub async fn new(
+        rt: RT,
+        signal_kind: SignalKind,
+        config_path: PathBuf,
+        registry: &'static [&'static dyn DynamicKnob],
+    ) -> anyhow::Result<Self> {
+        let loader =
+            ConfigLoader::new(rt.clone(), signal_kind, config_path, KeyValueDecoder).await?;
+        let mut updates = loader.subscribe();
+        apply_overrides(registry, &loader.get_config())?;
+        let handle = rt.spawn("knob_overrides", async move {
+            while let Some(overrides) = updates.next().await {
+                match apply_overrides(registry, &overrides) {
+                    Ok(()) => tracing::info!("Applied knob overrides {overrides:?}"),
+                    Err(mut e) => report_error(&mut e),
+                }
+            }
+        });
+        Ok(Self { loader, handle })
+    }
+
+    /// Manually trigger a reload of the config file on disk.
+    pub fn reload(&self) {
+        self.loader.reload();
+    }
+}
+
+impl<RT: Runtime> Drop for KnobOverrides<RT> {
+    fn drop(&mut self) {
+        self.handle.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{
+            Seek,
+            Write,
+        },
+        time::Duration,
+    };
+
+    use common::{
+        live_knobs::{
+            DynamicKnob,
+            KnobSource,
+            LiveKnob,
+        },
+        runtime::Runtime,
+    };
+    use runtime::prod::ProdRuntime;
+    use tokio::signal::unix::SignalKind;
+
+    use crate::knobs::KnobOverrides;
+
+    static TEST_LIMIT: LiveKnob<usize> = LiveKnob::new("KNOB_OVERRIDES_TEST_LIMIT", 10);
+    static REGISTRY: &[&dyn DynamicKnob] = &[&TEST_LIMIT];
+
+    fn rewrite(file: &mut tempfile::NamedTempFile, contents: &str) -> anyhow::Result<()> {
+        file.as_file().set_len(0)?;
+        file.seek(std::io::SeekFrom::Start(0))?;
+        file.write_all(contents.as_bytes())?;
+        Ok(())
+    }
+
+    async fn wait_for_limit(rt: &ProdRuntime, expected: usize) {
+        for _ in 0..100 {
+            if TEST_LIMIT.get() == expected {
+                return;
+            }
+            rt.wait(Duration::from_millis(50)).await;
+        }
+        panic!("TEST_LIMIT never became {expected}");
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_knob_overrides(rt: ProdRuntime) -> anyhow::Result<()> {
+        let mut file = tempfile::NamedTempFile::new()?;
+        rewrite(&mut file, "# Overrides\nKNOB_OVERRIDES_TEST_LIMIT = 20\n")?;
+        let overrides = KnobOverrides::new(
+            rt.clone(),
+            SignalKind::user_defined2(),
+            file.path().to_owned(),
+            REGISTRY,
+        )
+        .await?;
+        assert_eq!(TEST_LIMIT.get(), 20);
+        assert_eq!(TEST_LIMIT.value().source, KnobSource::Override);
+
+        rewrite(&mut file, "KNOB_OVERRIDES_TEST_LIMIT=30\n")?;
+        overrides.reload();
+        wait_for_limit(&rt, 30).await;
+
+        // An invalid file keeps the previous overrides.
+        rewrite(&mut file, "KNOB_OVERRIDES_TEST_LIMIT=lots\n")?;
+        overrides.reload();
+        rt.wait(Duration::from_millis(200)).await;
+        assert_eq!(TEST_LIMIT.get(), 30);
+
+        rewrite(&mut file, "")?;
+        overrides.reload();
+        wait_for_limit(&rt, 10).await;
+        assert_eq!(TEST_LIMIT.value().source, KnobSource::Default);
+        Ok(())
+    }
+}
@@ -31,6 +31,7 @@ use tokio_stream::wrappers::{
 };
 
 pub mod decoding;
+pub mod knobs;
 
 /// This struct loads a file from disk upon creation and then sets up a signal
 /// handler to load it again whenever a user-defined signal is received.
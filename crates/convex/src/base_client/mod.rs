@@ -20,6 +20,7 @@ use convex_sync_types::{
     QueryId,
     QuerySetModification,
     QuerySetVersion,
+    SerializedQueryJournal,
     SessionRequestSeqNumber,
     StateModification,
     StateVersion,
@@ -51,6 +52,15 @@ pub use query_result::{
     FunctionResult,
     QueryResults,
 };
+mod optimistic_updates;
+pub use optimistic_updates::{
+    OptimisticLocalStore,
+    OptimisticUpdate,
+};
+use optimistic_updates::{
+    OptimisticQueryResults,
+    Query,
+};
 
 use self::request_manager::RequestType;
 
@@ -63,13 +73,7 @@ struct LocalQuery {
     canonicalized_udf_path: CanonicalizedUdfPath,
     args: BTreeMap<String, Value>,
     num_subscribers: usize, // TODO: remove
-}
-
-#[derive(Clone, Debug)]
-struct Query {
-    result: FunctionResult,
-    _udf_path: CanonicalizedUdfPath,
-    _args: BTreeMap<String, Value>,
+    journal: SerializedQueryJournal,
 }
 
 /// An identifier for a single subscriber to a query.
@@ -146,6 +150,7 @@ impl LocalSyncState {
             canonicalized_udf_path,
             args,
             num_subscribers: 1,
+            journal: None,
         };
 
         self.query_set.insert(query_token.clone(), query);
@@ -214,6 +219,16 @@ impl LocalSyncState {
         )
     }
 
+    fn save_query_journal(&mut self, query_id: QueryId, journal: SerializedQueryJournal) {
+        let Some(query_token) = self.query_token(query_id) else {
+            // We've already unsubscribed from this query.
+            return;
+        };
+        if let Some(local_query) = self.query_set.get_mut(&query_token) {
+            local_query.journal = journal;
+        }
+    }
+
     fn set_auth(&mut self, token: AuthenticationToken) -> ClientMessage {
         self.auth_token = token.clone();
         let base_version = self.identity_version;
@@ -231,7 +246,9 @@ impl LocalSyncState {
                 query_id: local_query.id,
                 udf_path: local_query.canonicalized_udf_path.clone().into(),
                 args: vec![Value::Object(local_query.args.clone()).into()],
-                journal: None,
+                // Resend the journal so paginated queries resume with the same page
+                // boundaries.
+                journal: Some(local_query.journal.clone()),
             });
             modifications.push(add)
         }
@@ -331,39 +348,6 @@ impl RemoteQuerySet {
     }
 }
 
-#[derive(Default, Debug)]
-struct OptimisticQueryResults {
-    query_results: BTreeMap<QueryId, Query>,
-}
-
-impl OptimisticQueryResults {
-    fn ingest_query_results_from_server(
-        &mut self,
-        server_query_results: BTreeMap<QueryId, Query>,
-        _optimistic_updates_to_drop: BTreeSet<RequestId>,
-    ) -> BTreeMap<QueryId, FunctionResult> {
-        // TODO: use optimistic_updates_to_drop
-        let old_query_results = self.query_results.clone();
-        self.query_results = server_query_results;
-        let mut changed_queries = BTreeMap::new();
-        for (query_id, query) in self.query_results.iter() {
-            let old_query = old_query_results.get(query_id);
-            if match old_query {
-                Some(old_query) => old_query.result != query.result,
-                None => true,
-            } {
-                let result = query.result.clone();
-                changed_queries.insert(*query_id, result);
-            }
-        }
-        changed_queries
-    }
-
-    fn query_result(&self, query_id: QueryId) -> Option<FunctionResult> {
-        self.query_results.get(&query_id).map(|q| q.result.clone())
-    }
-}
-
 /// The synchronous state machine for the `ConvexClient`. It's recommended to
 /// use the higher level `ConvexClient` unless you are building a framework.
 ///
@@ -389,6 +373,16 @@ impl OptimisticQueryResults {
 /// used to synchronously request the current value, as opposed to a stream of
 /// values in [`subscribe`](crate::ConvexClient::subscribe()).
 ///
+/// ## Optimistic updates
+/// [`mutation_with_optimistic_update`](Self::mutation_with_optimistic_update())
+/// takes an [`OptimisticUpdate`] that patches the local query results through
+/// an [`OptimisticLocalStore`] while the mutation is in flight. Optimistic
+/// results are layered over the server's results and are rolled back once the
+/// mutation is reflected in a transition or fails. The values returned by
+/// [`get_query`](Self::get_query()) and
+/// [`latest_results`](Self::latest_results()) include any pending optimistic
+/// updates.
+///
 /// **Note: these methods have the side effect of
 /// adding messages to be sent to the server, so you would need to flush all
 /// outgoing messages by looping on
@@ -432,6 +426,7 @@ impl OptimisticQueryResults {
 /// - [`subscribe`](Self::unsubscribe())
 /// - [`unsubscribe`](Self::unsubscribe())
 /// - [`mutation`](Self::unsubscribe())
+/// - [`mutation_with_optimistic_update`](Self::mutation_with_optimistic_update())
 pub struct BaseConvexClient {
     state: LocalSyncState,
     remote_query_set: RemoteQuerySet,
@@ -504,6 +499,36 @@ impl BaseConvexClient {
         &mut self,
         udf_path: UdfPath,
         args: BTreeMap<String, Value>,
+    ) -> oneshot::Receiver<FunctionResult> {
+        self.mutation_inner(udf_path, args, None)
+    }
+
+    /// Track mutation, apply `optimistic_update` to the local query results
+    /// and add mutation request to the outgoing message queue.
+    ///
+    /// The optimistic update is replayed on top of each new set of results
+    /// from the server until the mutation's effects are visible in a
+    /// transition or the mutation fails, at which point it is rolled back.
+    /// Call [`latest_results`](Self::latest_results()) afterwards to observe
+    /// the optimistic results.
+    ///
+    /// After calling this, it is highly recommended to loop on
+    /// [`pop_next_message`](Self::pop_next_message()) to flush websocket
+    /// messages to the server.
+    pub fn mutation_with_optimistic_update(
+        &mut self,
+        udf_path: UdfPath,
+        args: BTreeMap<String, Value>,
+        optimistic_update: OptimisticUpdate,
+    ) -> oneshot::Receiver<FunctionResult> {
+        self.mutation_inner(udf_path, args, Some(optimistic_update))
+    }
+
+    fn mutation_inner(
+        &mut self,
+        udf_path: UdfPath,
+        args: BTreeMap<String, Value>,
+        optimistic_update: Option<OptimisticUpdate>,
     ) -> oneshot::Receiver<FunctionResult> {
         let request_id = self.next_request_id;
         self.next_request_id = request_id + 1;
@@ -519,6 +544,14 @@ impl BaseConvexClient {
             RequestId::new(request_id),
             RequestType::Mutation,
         );
+        if let Some(optimistic_update) = optimistic_update {
+            let changed_queries = self.optimistic_query_results.apply_optimistic_update(
+                &self.state.query_set,
+                optimistic_update,
+                RequestId::new(request_id),
+            );
+            self.update_latest_results(changed_queries);
+        }
         self.outgoing_message_queue.push_back(message);
         result_receiver
     }
@@ -585,16 +618,29 @@ impl BaseConvexClient {
         message: ServerMessage,
     ) -> Result<Option<QueryResults>, ReconnectProtocolReason> {
         match message {
-            ServerMessage::Transition { end_version, .. } => {
+            ServerMessage::Transition {
+                end_version,
+                ref modifications,
+                ..
+            } => {
+                for modification in modifications {
+                    match modification {
+                        StateModification::QueryUpdated {
+                            query_id, journal, ..
+                        }
+                        | StateModification::QueryFailed {
+                            query_id, journal, ..
+                        } => self.state.save_query_journal(*query_id, journal.clone()),
+                        StateModification::QueryRemoved { .. } => {},
+                    }
+                }
                 self.observe_timestamp(end_version.ts);
                 self.remote_query_set.transition(message)?;
                 let completed_requests = self
                     .request_manager
                     .remove_and_notify_completed(end_version.ts);
-                let changed_query_ids = self.on_query_result_changes(completed_requests)?;
-                for (id, result) in changed_query_ids {
-                    self.state.latest_results.results.insert(id, result);
-                }
+                let changed_queries = self.on_query_result_changes(completed_requests)?;
+                self.update_latest_results(changed_queries);
                 return Ok(Some(self.state.latest_results.clone()));
             },
             ServerMessage::MutationResponse {
@@ -611,12 +657,22 @@ impl BaseConvexClient {
                     self.observe_timestamp(ts);
                 }
                 let request_id = RequestId::new(request_id);
-                self.request_manager.update_request(
+                let completed_requests = self.request_manager.update_request(
                     &request_id,
                     RequestType::Mutation,
                     result.into(),
                     ts,
                 )?;
+                // Failed mutations complete immediately, so roll back their optimistic
+                // updates without waiting for a transition.
+                if self
+                    .optimistic_query_results
+                    .has_optimistic_update(&completed_requests)
+                {
+                    let changed_queries = self.on_query_result_changes(completed_requests)?;
+                    self.update_latest_results(changed_queries);
+                    return Ok(Some(self.state.latest_results.clone()));
+                }
             },
             ServerMessage::AuthError {
                 error_message,
@@ -662,6 +718,26 @@ impl BaseConvexClient {
         &self.state.latest_results
     }
 
+    /// Returns the number of mutations that have been sent but haven't yet
+    /// completed.
+    pub fn num_inflight_mutations(&self) -> usize {
+        self.request_manager
+            .num_ongoing_requests(RequestType::Mutation)
+    }
+
+    /// Returns the number of actions that have been sent but haven't yet
+    /// completed.
+    pub fn num_inflight_actions(&self) -> usize {
+        self.request_manager
+            .num_ongoing_requests(RequestType::Action)
+    }
+
+    /// Returns whether the mutation or action with `request_id` is still in
+    /// flight.
+    pub fn is_request_inflight(&self, request_id: SessionRequestSeqNumber) -> bool {
+        self.request_manager.is_ongoing(&RequestId::new(request_id))
+    }
+
     /// Resend all subscribed queries and ongoing mutations. Should be used once
     /// the websocket closes and reconnects.
     pub fn resend_ongoing_queries_mutations(&mut self) {
@@ -679,7 +755,7 @@ impl BaseConvexClient {
     fn on_query_result_changes(
         &mut self,
         completed_requests: BTreeSet<RequestId>,
-    ) -> Result<BTreeMap<QueryId, FunctionResult>, ReconnectProtocolReason> {
+    ) -> Result<BTreeMap<QueryId, Option<FunctionResult>>, ReconnectProtocolReason> {
         let remote_query_results = &self.remote_query_set.remote_query_set;
         let mut query_id_to_value = BTreeMap::new();
         for (query_id, result) in remote_query_results.iter() {
@@ -703,7 +779,27 @@ impl BaseConvexClient {
         }
         Ok(self
             .optimistic_query_results
-            .ingest_query_results_from_server(query_id_to_value, completed_requests))
+            .ingest_query_results_from_server(
+                &self.state.query_set,
+                query_id_to_value,
+                completed_requests,
+            ))
+    }
+
+    fn update_latest_results(
+        &mut self,
+        changed_queries: BTreeMap<QueryId, Option<FunctionResult>>,
+    ) {
+        for (id, result) in changed_queries {
+            match result {
+                Some(result) => {
+                    self.state.latest_results.results.insert(id, result);
+                },
+                None => {
+                    self.state.latest_results.results.remove(&id);
+                },
+            }
+        }
     }
 
     fn local_query_result(&self, query_id: QueryId) -> Option<FunctionResult> {
@@ -0,0 +1,229 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+
+use convex_sync_types::{
+    CanonicalizedUdfPath,
+    QueryId,
+    UdfPath,
+};
+
+use super::{
+    request_manager::RequestId,
+    serialize_path_and_args,
+    LocalQuery,
+    QueryToken,
+};
+use crate::{
+    FunctionResult,
+    Value,
+};
+
+/// A temporary, local update to query results within the client.
+///
+/// Optimistic updates are attached to a mutation with
+/// [`mutation_with_optimistic_update`](super::BaseConvexClient::mutation_with_optimistic_update)
+/// and run immediately, before the mutation is sent to the server. They are
+/// re-run on top of every new set of server results until the mutation's
+/// effects are reflected in a transition from the server (or the mutation
+/// fails), at which point the update is rolled back.
+///
+/// Because the update may be replayed many times, it should be a pure
+/// function of the [`OptimisticLocalStore`] it is given.
+pub type OptimisticUpdate = Box<dyn FnMut(&mut OptimisticLocalStore<'_>) + Send>;
+
+#[derive(Clone, Debug)]
+pub(super) struct Query {
+    pub(super) result: FunctionResult,
+    pub(super) _udf_path: CanonicalizedUdfPath,
+    pub(super) _args: BTreeMap<String, Value>,
+}
+
+/// A view of the client's local query results, passed to an
+/// [`OptimisticUpdate`].
+///
+/// Only queries that the client is currently subscribed to can be read or
+/// modified.
+pub struct OptimisticLocalStore<'a> {
+    query_set: &'a BTreeMap<QueryToken, LocalQuery>,
+    query_results: &'a mut BTreeMap<QueryId, Query>,
+    modified_queries: BTreeSet<QueryId>,
+}
+
+impl<'a> OptimisticLocalStore<'a> {
+    fn new(
+        query_set: &'a BTreeMap<QueryToken, LocalQuery>,
+        query_results: &'a mut BTreeMap<QueryId, Query>,
+    ) -> Self {
+        Self {
+            query_set,
+            query_results,
+            modified_queries: BTreeSet::new(),
+        }
+    }
+
+    /// Retrieve the current value of the query `udf_path` called with `args`.
+    ///
+    /// Returns `None` if the client isn't subscribed to the query, if it's
+    /// still loading, or if it currently holds an error.
+    pub fn get_query(&self, udf_path: UdfPath, args: BTreeMap<String, Value>) -> Option<Value> {
+        let local_query = self
+            .query_set
+            .get(&serialize_path_and_args(udf_path, args))?;
+        Self::query_value(self.query_results.get(&local_query.id))
+    }
+
+    /// Retrieve the args and current values of every subscribed query for the
+    /// function `udf_path`.
+    ///
+    /// The value is `None` for queries that are loading or that hold an
+    /// error.
+    pub fn get_all_queries(
+        &self,
+        udf_path: UdfPath,
+    ) -> Vec<(BTreeMap<String, Value>, Option<Value>)> {
+        let udf_path = udf_path.canonicalize();
+        self.query_set
+            .values()
+            .filter(|local_query| local_query.canonicalized_udf_path == udf_path)
+            .map(|local_query| {
+                (
+                    local_query.args.clone(),
+                    Self::query_value(self.query_results.get(&local_query.id)),
+                )
+            })
+            .collect()
+    }
+
+    /// Optimistically set the value of the query `udf_path` called with
+    /// `args`. Passing `None` marks the query as loading.
+    ///
+    /// This is a no-op if the client isn't subscribed to the query.
+    pub fn set_query(
+        &mut self,
+        udf_path: UdfPath,
+        args: BTreeMap<String, Value>,
+        value: Option<Value>,
+    ) {
+        let Some(local_query) = self.query_set.get(&serialize_path_and_args(udf_path, args)) else {
+            return;
+        };
+        match value {
+            Some(value) => {
+                self.query_results.insert(
+                    local_query.id,
+                    Query {
+                        result: FunctionResult::Value(value),
+                        _udf_path: local_query.canonicalized_udf_path.clone(),
+                        _args: local_query.args.clone(),
+                    },
+                );
+            },
+            None => {
+                self.query_results.remove(&local_query.id);
+            },
+        }
+        self.modified_queries.insert(local_query.id);
+    }
+
+    fn query_value(query: Option<&Query>) -> Option<Value> {
+        match query?.result {
+            FunctionResult::Value(ref value) => Some(value.clone()),
+            FunctionResult::ErrorMessage(_) | FunctionResult::ConvexError(_) => None,
+        }
+    }
+}
+
+/// Query results from the server with the pending optimistic updates layered
+/// on top.
+#[derive(Default)]
+pub(super) struct OptimisticQueryResults {
+    query_results: BTreeMap<QueryId, Query>,
+    optimistic_updates: Vec<(RequestId, OptimisticUpdate)>,
+}
+
+impl std::fmt::Debug for OptimisticQueryResults {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OptimisticQueryResults")
+            .field("query_results", &self.query_results)
+            .field(
+                "optimistic_updates",
+                &self
+                    .optimistic_updates
+                    .iter()
+                    .map(|(request_id, _)| request_id)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl OptimisticQueryResults {
+    /// Replace the results with `server_query_results`, dropping the
+    /// optimistic updates for completed requests and replaying the rest.
+    ///
+    /// Returns the new result for every query whose result changed, with
+    /// `None` for queries that no longer have a result.
+    pub(super) fn ingest_query_results_from_server(
+        &mut self,
+        query_set: &BTreeMap<QueryToken, LocalQuery>,
+        server_query_results: BTreeMap<QueryId, Query>,
+        optimistic_updates_to_drop: BTreeSet<RequestId>,
+    ) -> BTreeMap<QueryId, Option<FunctionResult>> {
+        self.optimistic_updates
+            .retain(|(request_id, _)| !optimistic_updates_to_drop.contains(request_id));
+        let old_query_results = std::mem::replace(&mut self.query_results, server_query_results);
+        let mut local_store = OptimisticLocalStore::new(query_set, &mut self.query_results);
+        for (_, update) in self.optimistic_updates.iter_mut() {
+            update(&mut local_store);
+        }
+
+        let mut changed_queries = BTreeMap::new();
+        for (query_id, query) in self.query_results.iter() {
+            let old_query = old_query_results.get(query_id);
+            if match old_query {
+                Some(old_query) => old_query.result != query.result,
+                None => true,
+            } {
+                changed_queries.insert(*query_id, Some(query.result.clone()));
+            }
+        }
+        for query_id in old_query_results.keys() {
+            if !self.query_results.contains_key(query_id) {
+                changed_queries.insert(*query_id, None);
+            }
+        }
+        changed_queries
+    }
+
+    /// Apply `update` to the current results and keep it around to be replayed
+    /// on future server results until `request_id` completes.
+    ///
+    /// Returns the new result for every query the update modified.
+    pub(super) fn apply_optimistic_update(
+        &mut self,
+        query_set: &BTreeMap<QueryToken, LocalQuery>,
+        mut update: OptimisticUpdate,
+        request_id: RequestId,
+    ) -> BTreeMap<QueryId, Option<FunctionResult>> {
+        let mut local_store = OptimisticLocalStore::new(query_set, &mut self.query_results);
+        update(&mut local_store);
+        let modified_queries = local_store.modified_queries;
+        self.optimistic_updates.push((request_id, update));
+        modified_queries
+            .into_iter()
+            .map(|query_id| (query_id, self.query_result(query_id)))
+            .collect()
+    }
+
+    pub(super) fn has_optimistic_update(&self, request_ids: &BTreeSet<RequestId>) -> bool {
+        self.optimistic_updates
+            .iter()
+            .any(|(request_id, _)| request_ids.contains(request_id))
+    }
+
+    pub(super) fn query_result(&self, query_id: QueryId) -> Option<FunctionResult> {
+        self.query_results.get(&query_id).map(|q| q.result.clone())
+    }
+}
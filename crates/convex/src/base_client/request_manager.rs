@@ -83,7 +83,7 @@ impl RequestManager {
         request_type: RequestType,
         value: FunctionResult,
         ts: Option<Timestamp>,
-    ) -> Result<(), ReconnectProtocolReason> {
+    ) -> Result<BTreeSet<RequestId>, ReconnectProtocolReason> {
         let Some((request, _)) = self.ongoing_requests.get_mut(request_id) else {
             return Err("Invalid request id from server".to_string());
         };
@@ -96,10 +96,12 @@ impl RequestManager {
         request.status = RequestStatus::Completed;
 
         // Actions and errored mutations are ok to complete immediately
+        let mut completed_requests = BTreeSet::new();
         if request_type == RequestType::Action || errored {
             self._remove_and_notify_completed(request_id);
+            completed_requests.insert(*request_id);
         }
-        Ok(())
+        Ok(completed_requests)
     }
 
     pub fn remove_and_notify_completed(&mut self, ts: Timestamp) -> BTreeSet<RequestId> {
@@ -153,6 +155,17 @@ impl RequestManager {
         rx
     }
 
+    pub fn num_ongoing_requests(&self, request_type: RequestType) -> usize {
+        self.ongoing_requests
+            .values()
+            .filter(|(request, _)| request.typ == request_type)
+            .count()
+    }
+
+    pub fn is_ongoing(&self, request_id: &RequestId) -> bool {
+        self.ongoing_requests.contains_key(request_id)
+    }
+
     pub fn restart(&self) -> VecDeque<ClientMessage> {
         // Sort ongoing requests by timestamp
         let mut ordered_requests = Vec::from_iter(self.ongoing_requests.values());
@@ -0,0 +1,88 @@
+use std::pin::Pin;
+
+use futures::{
+    task,
+    Stream,
+    StreamExt,
+};
+use tokio_stream::wrappers::WatchStream;
+
+#[cfg(doc)]
+use crate::ConvexClient;
+
+/// The state of the WebSocket connection to the Convex deployment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum WebSocketState {
+    /// The client is establishing its first connection.
+    Connecting,
+    /// The client is connected.
+    Connected,
+    /// The connection was lost and the client is reconnecting with backoff.
+    Reconnecting {
+        /// The number of consecutive failed connection attempts.
+        attempt: u32,
+        /// Why the last connection was closed.
+        last_close_reason: String,
+    },
+}
+
+/// A snapshot of the [`ConvexClient`]'s connection to the deployment and its
+/// outstanding requests.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConnectionState {
+    /// The state of the underlying WebSocket.
+    pub web_socket: WebSocketState,
+    /// The number of mutations that haven't completed yet, including those
+    /// waiting to be resent after a reconnect.
+    pub inflight_mutations: usize,
+    /// The number of actions that haven't completed yet.
+    pub inflight_actions: usize,
+}
+
+impl ConnectionState {
+    pub(super) fn new() -> Self {
+        Self {
+            web_socket: WebSocketState::Connecting,
+            inflight_mutations: 0,
+            inflight_actions: 0,
+        }
+    }
+
+    /// Is the WebSocket currently connected?
+    pub fn is_connected(&self) -> bool {
+        self.web_socket == WebSocketState::Connected
+    }
+
+    /// Returns the number of mutations and actions that haven't completed
+    /// yet.
+    pub fn num_pending_requests(&self) -> usize {
+        self.inflight_mutations + self.inflight_actions
+    }
+}
+
+/// A stream of [`ConnectionState`]s, returned by
+/// [`ConvexClient::watch_connection_state`].
+///
+/// The current state is yielded immediately, followed by a new item each time
+/// the state changes. Intermediate states may be skipped if the stream isn't
+/// polled promptly.
+pub struct ConnectionStateSubscription {
+    watch: WatchStream<ConnectionState>,
+}
+
+impl ConnectionStateSubscription {
+    pub(super) fn new(watch: WatchStream<ConnectionState>) -> Self {
+        Self { watch }
+    }
+}
+
+impl Stream for ConnectionStateSubscription {
+    type Item = ConnectionState;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        self.watch.poll_next_unpin(cx)
+    }
+}
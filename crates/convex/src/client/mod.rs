@@ -1,6 +1,7 @@
 use std::{
     collections::BTreeMap,
     convert::Infallible,
+    path::PathBuf,
     sync::Arc,
 };
 
@@ -20,21 +21,32 @@ use futures::{
     StreamExt,
 };
 use tokio::{
-    sync::broadcast,
+    sync::{
+        broadcast,
+        watch,
+    },
     task::JoinHandle,
 };
-use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::{
+    BroadcastStream,
+    WatchStream,
+};
 use url::Url;
 
 use self::worker::AuthenticateRequest;
-#[cfg(doc)]
-use crate::SubscriberId;
 use crate::{
     base_client::{
         BaseConvexClient,
+        OptimisticLocalStore,
         QueryResults,
     },
     client::{
+        connection_state::{
+            ConnectionState,
+            ConnectionStateSubscription,
+        },
+        mutation_queue::MutationQueue,
+        paginated_query::PaginatedQuerySubscription,
         subscription::{
             QuerySetSubscription,
             QuerySubscription,
@@ -54,7 +66,15 @@ use crate::{
     value::Value,
     FunctionResult,
 };
+#[cfg(doc)]
+use crate::{
+    PaginationStatus,
+    SubscriberId,
+};
 
+pub mod connection_state;
+mod mutation_queue;
+pub mod paginated_query;
 pub mod subscription;
 mod worker;
 
@@ -90,6 +110,7 @@ pub struct ConvexClient {
     listen_handle: Option<Arc<JoinHandle<Infallible>>>,
     request_sender: mpsc::UnboundedSender<ClientRequest>,
     watch_receiver: broadcast::Receiver<QueryResults>,
+    connection_state_receiver: watch::Receiver<ConnectionState>,
 }
 
 /// Clone the [`ConvexClient`], sharing the connection and outstanding
@@ -100,6 +121,7 @@ impl Clone for ConvexClient {
             listen_handle: self.listen_handle.clone(),
             request_sender: self.request_sender.clone(),
             watch_receiver: self.watch_receiver.resubscribe(),
+            connection_state_receiver: self.connection_state_receiver.clone(),
         }
     }
 }
@@ -130,32 +152,7 @@ impl ConvexClient {
     /// # }
     /// ```
     pub async fn new(deployment_url: &str) -> anyhow::Result<Self> {
-        let ws_url = deployment_to_ws_url(deployment_url.try_into()?)?;
-
-        // Channels for the `listen` background thread
-        let (response_sender, response_receiver) = mpsc::channel(1);
-        let (request_sender, request_receiver) = mpsc::unbounded();
-
-        // Listener for when each transaction completes
-        let (watch_sender, watch_receiver) = broadcast::channel(1);
-
-        let base_client = BaseConvexClient::new();
-
-        let protocol = WebSocketManager::open(ws_url, response_sender).await?;
-
-        let listen_handle = tokio::spawn(worker(
-            response_receiver,
-            request_receiver,
-            watch_sender,
-            base_client,
-            protocol,
-        ));
-        let client = ConvexClient {
-            listen_handle: Some(Arc::new(listen_handle)),
-            request_sender,
-            watch_receiver,
-        };
-        Ok(client)
+        ConvexClientBuilder::new(deployment_url).build().await
     }
 
     /// Subscribe to the results of query `name` called with `args`.
@@ -200,6 +197,46 @@ impl ConvexClient {
         Ok(res)
     }
 
+    /// Subscribe to the paginated query `name` with `args`, starting with a
+    /// first page of `initial_num_items` items.
+    ///
+    /// The query must take a `paginationOpts` argument, which is added to
+    /// `args`, and return a `PaginationResult`. Returns a
+    /// [`PaginatedQuerySubscription`] which yields the merged items of every
+    /// loaded page along with a [`PaginationStatus`]. Call
+    /// [`PaginatedQuerySubscription::load_more`] to load further pages.
+    ///
+    /// Like [`usePaginatedQuery`](https://docs.convex.dev/database/pagination)
+    /// in the JS client, pages that grow too large are transparently split in
+    /// two, and pagination restarts from the first page if the query's cursors
+    /// become invalid.
+    ///
+    /// ```no_run
+    /// # use convex::{ConvexClient, PaginationStatus};
+    /// # use futures::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// let mut client = ConvexClient::new("https://cool-music-123.convex.cloud").await?;
+    /// let mut sub = client
+    ///     .subscribe_paginated("listMessages", maplit::btreemap!{}, 10)
+    ///     .await?;
+    /// while let Some(Ok(page)) = sub.next().await {
+    ///     println!("{:?}", page.results);
+    ///     if page.status == PaginationStatus::CanLoadMore {
+    ///         sub.load_more(10);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    pub async fn subscribe_paginated(
+        &mut self,
+        name: &str,
+        args: BTreeMap<String, Value>,
+        initial_num_items: usize,
+    ) -> anyhow::Result<PaginatedQuerySubscription> {
+        PaginatedQuerySubscription::new(self.clone(), name, args, initial_num_items).await
+    }
+
     /// Make a oneshot request to a query `name` with `args`.
     ///
     /// Returns a [`FunctionResult`] representing the result of the query.
@@ -256,7 +293,72 @@ impl ConvexClient {
         let (tx, rx) = oneshot::channel();
 
         let udf_path: UdfPath = name.parse()?;
-        let request = MutationRequest { udf_path, args };
+        let request = MutationRequest {
+            udf_path,
+            args,
+            optimistic_update: None,
+        };
+
+        self.request_sender
+            .send(ClientRequest::Mutation(request, tx))
+            .await?;
+
+        let res = rx.await?;
+        Ok(res.await?)
+    }
+
+    /// Perform a mutation `name` with `args`, optimistically updating local
+    /// query results with `optimistic_update` while it is in flight, and
+    /// return a future containing the return value of the mutation once it
+    /// completes.
+    ///
+    /// Subscriptions observe the optimistic results immediately. The update
+    /// is rolled back once the mutation's effects arrive from the server or
+    /// the mutation fails.
+    ///
+    /// ```no_run
+    /// # use convex::ConvexClient;
+    /// # use futures::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// let mut client = ConvexClient::new("https://cool-music-123.convex.cloud").await?;
+    /// let list_messages: convex_sync_types::UdfPath = "listMessages".parse()?;
+    /// let result = client.mutation_with_optimistic_update(
+    ///     "sendMessage",
+    ///     maplit::btreemap!{
+    ///         "body".into() => "Let it be.".into(),
+    ///         "author".into() => "The Beatles".into(),
+    ///     },
+    ///     move |store| {
+    ///         if let Some(convex::Value::Array(mut messages)) =
+    ///             store.get_query(list_messages.clone(), maplit::btreemap!{})
+    ///         {
+    ///             messages.push("Let it be.".into());
+    ///             store.set_query(
+    ///                 list_messages.clone(),
+    ///                 maplit::btreemap!{},
+    ///                 Some(convex::Value::Array(messages)),
+    ///             );
+    ///         }
+    ///     },
+    /// ).await?;
+    /// println!("{result:?}");
+    /// # Ok(())
+    /// # }
+    pub async fn mutation_with_optimistic_update(
+        &mut self,
+        name: &str,
+        args: BTreeMap<String, Value>,
+        optimistic_update: impl FnMut(&mut OptimisticLocalStore<'_>) + Send + 'static,
+    ) -> anyhow::Result<FunctionResult> {
+        let (tx, rx) = oneshot::channel();
+
+        let udf_path: UdfPath = name.parse()?;
+        let request = MutationRequest {
+            udf_path,
+            args,
+            optimistic_update: Some(Box::new(optimistic_update)),
+        };
 
         self.request_sender
             .send(ClientRequest::Mutation(request, tx))
@@ -334,6 +436,45 @@ impl ConvexClient {
         QuerySetSubscription::new(BroadcastStream::new(self.watch_receiver.resubscribe()))
     }
 
+    /// Get the current state of the client's connection to the deployment,
+    /// including the number of requests that haven't completed yet.
+    ///
+    /// ```no_run
+    /// # use convex::ConvexClient;
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// let client = ConvexClient::new("https://cool-music-123.convex.cloud").await?;
+    /// let state = client.connection_state();
+    /// println!("connected: {}, pending: {}", state.is_connected(), state.num_pending_requests());
+    /// # Ok(())
+    /// # }
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection_state_receiver.borrow().clone()
+    }
+
+    /// Watch the state of the client's connection to the deployment.
+    ///
+    /// Returns a [`ConnectionStateSubscription`] which implements
+    /// [`Stream`]<[`ConnectionState`]>. It yields the current state
+    /// immediately and then a new state each time the WebSocket connects,
+    /// disconnects, or the number of in-flight requests changes.
+    ///
+    /// ```no_run
+    /// # use convex::ConvexClient;
+    /// # use futures::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// let client = ConvexClient::new("https://cool-music-123.convex.cloud").await?;
+    /// let mut states = client.watch_connection_state();
+    /// while let Some(state) = states.next().await {
+    ///     println!("{:?}", state.web_socket);
+    /// }
+    /// # Ok(())
+    /// # }
+    pub fn watch_connection_state(&self) -> ConnectionStateSubscription {
+        ConnectionStateSubscription::new(WatchStream::new(self.connection_state_receiver.clone()))
+    }
+
     /// Set auth for use when calling Convex functions.
     ///
     /// Set it with a token that you get from your auth provider via their login
@@ -374,6 +515,86 @@ impl ConvexClient {
     }
 }
 
+/// A builder for a [`ConvexClient`] with non-default options.
+///
+/// ```no_run
+/// # use convex::ConvexClientBuilder;
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// let client = ConvexClientBuilder::new("https://cool-music-123.convex.cloud")
+///     .with_mutation_queue("/var/lib/my-app/convex-mutations.json")
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ConvexClientBuilder {
+    deployment_url: String,
+    mutation_queue_path: Option<PathBuf>,
+}
+
+impl ConvexClientBuilder {
+    /// Create a builder for a client communicating with `deployment_url`.
+    pub fn new(deployment_url: &str) -> Self {
+        Self {
+            deployment_url: deployment_url.to_string(),
+            mutation_queue_path: None,
+        }
+    }
+
+    /// Persist mutations that haven't completed to a file at `path`.
+    ///
+    /// Mutations are written to the file before they're sent and removed once
+    /// they complete. When a client is built with a queue left behind by a
+    /// previous process (e.g. after a crash), the remaining mutations are
+    /// resent in the order they were originally issued. Replayed mutations
+    /// may execute more than once if the previous process exited after the
+    /// server ran them but before their completion was recorded.
+    pub fn with_mutation_queue(mut self, path: impl Into<PathBuf>) -> Self {
+        self.mutation_queue_path = Some(path.into());
+        self
+    }
+
+    /// Construct the [`ConvexClient`].
+    pub async fn build(self) -> anyhow::Result<ConvexClient> {
+        let ws_url = deployment_to_ws_url(self.deployment_url.as_str().try_into()?)?;
+        let mutation_queue = match self.mutation_queue_path {
+            Some(path) => Some(MutationQueue::open(path).await?),
+            None => None,
+        };
+
+        // Channels for the `listen` background thread
+        let (response_sender, response_receiver) = mpsc::channel(1);
+        let (request_sender, request_receiver) = mpsc::unbounded();
+
+        // Listener for when each transaction completes
+        let (watch_sender, watch_receiver) = broadcast::channel(1);
+        let (connection_state_sender, connection_state_receiver) =
+            watch::channel(ConnectionState::new());
+
+        let base_client = BaseConvexClient::new();
+
+        let protocol = WebSocketManager::open(ws_url, response_sender).await?;
+
+        let listen_handle = tokio::spawn(worker(
+            response_receiver,
+            request_receiver,
+            watch_sender,
+            connection_state_sender,
+            mutation_queue,
+            base_client,
+            protocol,
+        ));
+        let client = ConvexClient {
+            listen_handle: Some(Arc::new(listen_handle)),
+            request_sender,
+            watch_receiver,
+            connection_state_receiver,
+        };
+        Ok(client)
+    }
+}
+
 fn deployment_to_ws_url(mut deployment_url: Url) -> anyhow::Result<Url> {
     let ws_scheme = match deployment_url.scheme() {
         "http" | "ws" => "ws",
@@ -415,13 +636,24 @@ pub mod tests {
     use maplit::btreemap;
     use pretty_assertions::assert_eq;
     use serde_json::json;
-    use tokio::sync::broadcast;
+    use tokio::sync::{
+        broadcast,
+        watch,
+    };
 
     use super::ConvexClient;
     use crate::{
-        base_client::FunctionResult,
+        base_client::{
+            FunctionResult,
+            OptimisticLocalStore,
+        },
         client::{
+            connection_state::{
+                ConnectionState,
+                WebSocketState,
+            },
             deployment_to_ws_url,
+            mutation_queue::MutationQueue,
             worker::worker,
             BaseConvexClient,
         },
@@ -435,6 +667,12 @@ pub mod tests {
 
     impl ConvexClient {
         pub async fn with_test_protocol() -> anyhow::Result<(Self, TestProtocolManager)> {
+            Self::with_test_protocol_and_mutation_queue(None).await
+        }
+
+        pub async fn with_test_protocol_and_mutation_queue(
+            mutation_queue: Option<MutationQueue>,
+        ) -> anyhow::Result<(Self, TestProtocolManager)> {
             let _ = tracing_subscriber::fmt()
                 .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
                 .try_init();
@@ -445,6 +683,8 @@ pub mod tests {
 
             // Listener for when each transaction completes
             let (watch_sender, watch_receiver) = broadcast::channel(1);
+            let (connection_state_sender, connection_state_receiver) =
+                watch::channel(ConnectionState::new());
 
             let test_protocol =
                 TestProtocolManager::open("ws://test.com".parse()?, response_sender).await?;
@@ -454,6 +694,8 @@ pub mod tests {
                 response_receiver,
                 request_receiver,
                 watch_sender,
+                connection_state_sender,
+                mutation_queue,
                 base_client,
                 test_protocol.clone(),
             ));
@@ -462,6 +704,7 @@ pub mod tests {
                 listen_handle: Some(Arc::new(listen_handle)),
                 request_sender,
                 watch_receiver,
+                connection_state_receiver,
             };
             Ok((client, test_protocol))
         }
@@ -898,6 +1141,353 @@ pub mod tests {
         Ok(())
     }
 
+    fn increment_value(udf_path: &'static str) -> impl FnMut(&mut OptimisticLocalStore<'_>) {
+        move |store| {
+            let udf_path: UdfPath = udf_path.parse().unwrap();
+            if let Some(Value::Int64(value)) = store.get_query(udf_path.clone(), btreemap! {}) {
+                store.set_query(udf_path, btreemap! {}, Some((value + 1).into()));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_optimistic_update() -> anyhow::Result<()> {
+        let (mut client, mut test_protocol) = ConvexClient::with_test_protocol().await?;
+        let mut subscription = client.subscribe("getValue", btreemap! {}).await?;
+        let query_id = subscription.query_id();
+        test_protocol.take_sent().await;
+
+        let (transition, version) =
+            fake_transition(StateVersion::initial(), vec![(query_id, 1.into())]);
+        test_protocol.fake_server_response(transition).await?;
+        assert_eq!(
+            subscription.next().await,
+            Some(FunctionResult::Value(1.into()))
+        );
+
+        let mut mutation_client = client.clone();
+        let mut res = tokio::spawn(async move {
+            mutation_client
+                .mutation_with_optimistic_update(
+                    "incrementValue",
+                    btreemap! {},
+                    increment_value("getValue"),
+                )
+                .await
+        });
+        test_protocol.wait_until_n_messages_sent(1).await;
+        assert_eq!(
+            test_protocol.take_sent().await,
+            vec![ClientMessage::Mutation {
+                request_id: 0,
+                udf_path: UdfPath::from_str("incrementValue")?,
+                args: vec![json!({})],
+            }]
+        );
+        // The optimistic result is visible before the server responds.
+        assert_eq!(
+            subscription.next().await,
+            Some(FunctionResult::Value(2.into()))
+        );
+
+        // The update is replayed on top of new server results while the
+        // mutation is in flight.
+        let (transition, version) = fake_transition(version, vec![(query_id, 5.into())]);
+        test_protocol.fake_server_response(transition).await?;
+        assert_eq!(
+            subscription.next().await,
+            Some(FunctionResult::Value(6.into()))
+        );
+
+        // Once the transition containing the mutation arrives, the update is
+        // dropped in favor of the server's result.
+        let (transition, mutation_version) = fake_transition(version, vec![(query_id, 6.into())]);
+        test_protocol
+            .fake_server_response(ServerMessage::MutationResponse {
+                request_id: 0,
+                result: Ok(Value::Null),
+                ts: Some(mutation_version.ts),
+                log_lines: LogLinesMessage(vec![]),
+            })
+            .await?;
+        tokio::time::timeout(Duration::from_millis(50), &mut res)
+            .await
+            .unwrap_err();
+        test_protocol.fake_server_response(transition).await?;
+        assert_eq!(res.await??, FunctionResult::Value(Value::Null));
+        assert_eq!(
+            subscription.next().await,
+            Some(FunctionResult::Value(6.into()))
+        );
+
+        let (transition, _version) = fake_transition(mutation_version, vec![(query_id, 7.into())]);
+        test_protocol.fake_server_response(transition).await?;
+        assert_eq!(
+            subscription.next().await,
+            Some(FunctionResult::Value(7.into()))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_optimistic_update_rolled_back_on_error() -> anyhow::Result<()> {
+        let (mut client, mut test_protocol) = ConvexClient::with_test_protocol().await?;
+        let mut subscription = client.subscribe("getValue", btreemap! {}).await?;
+        let query_id = subscription.query_id();
+        test_protocol.take_sent().await;
+
+        let (transition, _version) =
+            fake_transition(StateVersion::initial(), vec![(query_id, 1.into())]);
+        test_protocol.fake_server_response(transition).await?;
+        assert_eq!(
+            subscription.next().await,
+            Some(FunctionResult::Value(1.into()))
+        );
+
+        let mut mutation_client = client.clone();
+        let res = tokio::spawn(async move {
+            mutation_client
+                .mutation_with_optimistic_update(
+                    "incrementValue",
+                    btreemap! {},
+                    increment_value("getValue"),
+                )
+                .await
+        });
+        test_protocol.wait_until_n_messages_sent(1).await;
+        test_protocol.take_sent().await;
+        assert_eq!(
+            subscription.next().await,
+            Some(FunctionResult::Value(2.into()))
+        );
+
+        let mutation_result = FunctionResult::ErrorMessage("JEEPERS".into());
+        let (mut_resp, _transition) = fake_mutation_response(mutation_result.clone());
+        test_protocol.fake_server_response(mut_resp).await?;
+        assert_eq!(res.await??, mutation_result);
+        // The failed mutation's update is rolled back without a transition.
+        assert_eq!(
+            subscription.next().await,
+            Some(FunctionResult::Value(1.into()))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_optimistic_update_loading_query() -> anyhow::Result<()> {
+        let (mut client, test_protocol) = ConvexClient::with_test_protocol().await?;
+        let mut subscription = client.subscribe("getValue", btreemap! {}).await?;
+        test_protocol.take_sent().await;
+
+        let mut mutation_client = client.clone();
+        let _res = tokio::spawn(async move {
+            mutation_client
+                .mutation_with_optimistic_update("setValue", btreemap! {}, |store| {
+                    let udf_path: UdfPath = "getValue".parse().unwrap();
+                    assert_eq!(store.get_query(udf_path.clone(), btreemap! {}), None);
+                    assert_eq!(
+                        store.get_all_queries(udf_path.clone()),
+                        vec![(btreemap! {}, None)]
+                    );
+                    // Queries that aren't subscribed to are ignored.
+                    store.set_query("otherValue".parse().unwrap(), btreemap! {}, Some(1.into()));
+                    store.set_query(udf_path, btreemap! {}, Some(3.into()));
+                })
+                .await
+        });
+        test_protocol.wait_until_n_messages_sent(1).await;
+        assert_eq!(
+            subscription.next().await,
+            Some(FunctionResult::Value(3.into()))
+        );
+        Ok(())
+    }
+
+    async fn wait_for_connection_state(
+        client: &ConvexClient,
+        mut f: impl FnMut(&ConnectionState) -> bool,
+    ) -> ConnectionState {
+        let mut states = client.watch_connection_state();
+        tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                let state = states.next().await.expect("Client dropped");
+                if f(&state) {
+                    return state;
+                }
+            }
+        })
+        .await
+        .expect("Test timed out waiting for connection state")
+    }
+
+    #[tokio::test]
+    async fn test_connection_state() -> anyhow::Result<()> {
+        let (client, mut test_protocol) = ConvexClient::with_test_protocol().await?;
+        wait_for_connection_state(&client, |state| state.is_connected()).await;
+        test_protocol.take_sent().await;
+
+        let mut mutation_client = client.clone();
+        let res = tokio::spawn(async move {
+            mutation_client
+                .mutation("incrementCounter", btreemap! {})
+                .await
+        });
+        let state =
+            wait_for_connection_state(&client, |state| state.num_pending_requests() > 0).await;
+        assert_eq!(
+            state,
+            ConnectionState {
+                web_socket: WebSocketState::Connected,
+                inflight_mutations: 1,
+                inflight_actions: 0,
+            }
+        );
+
+        // Losing the connection resends the in-flight mutation.
+        test_protocol.fake_failure("ServerClosed").await?;
+        let state = wait_for_connection_state(&client, |state| !state.is_connected()).await;
+        assert_eq!(
+            state,
+            ConnectionState {
+                web_socket: WebSocketState::Reconnecting {
+                    attempt: 1,
+                    last_close_reason: "ServerClosed".into(),
+                },
+                inflight_mutations: 1,
+                inflight_actions: 0,
+            }
+        );
+        test_protocol.wait_until_n_messages_sent(4).await;
+        assert_eq!(
+            test_protocol.take_sent().await,
+            vec![
+                ClientMessage::Mutation {
+                    request_id: 0,
+                    udf_path: UdfPath::from_str("incrementCounter")?,
+                    args: vec![json!({})],
+                },
+                ClientMessage::Connect {
+                    session_id: SessionId::nil(),
+                    connection_count: 1,
+                    last_close_reason: "ServerClosed".to_string(),
+                    max_observed_timestamp: None,
+                },
+                ClientMessage::ModifyQuerySet {
+                    base_version: 0,
+                    new_version: 1,
+                    modifications: vec![],
+                },
+                ClientMessage::Mutation {
+                    request_id: 0,
+                    udf_path: UdfPath::from_str("incrementCounter")?,
+                    args: vec![json!({})],
+                },
+            ]
+        );
+
+        test_protocol.fake_failure("InactiveServer").await?;
+        let state = wait_for_connection_state(&client, |state| {
+            matches!(
+                state.web_socket,
+                WebSocketState::Reconnecting { attempt: 2, .. }
+            )
+        })
+        .await;
+        assert_eq!(
+            state.web_socket,
+            WebSocketState::Reconnecting {
+                attempt: 2,
+                last_close_reason: "InactiveServer".into(),
+            }
+        );
+
+        test_protocol.fake_connected().await?;
+        wait_for_connection_state(&client, |state| state.is_connected()).await;
+
+        let (mut_resp, transition) = fake_mutation_response(FunctionResult::Value(Value::Null));
+        test_protocol.fake_server_response(mut_resp).await?;
+        test_protocol.fake_server_response(transition).await?;
+        res.await??;
+        let state =
+            wait_for_connection_state(&client, |state| state.num_pending_requests() == 0).await;
+        assert_eq!(
+            state,
+            ConnectionState {
+                web_socket: WebSocketState::Connected,
+                inflight_mutations: 0,
+                inflight_actions: 0,
+            }
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mutation_queue_replay() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("convex-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await?;
+        let path = dir.join("mutations.json");
+
+        // Mutations left behind by a previous process.
+        let mut queue = MutationQueue::open(path.clone()).await?;
+        for (request_id, udf_path) in [(7, "second"), (3, "first")] {
+            queue
+                .track(&ClientMessage::Mutation {
+                    request_id,
+                    udf_path: udf_path.parse()?,
+                    args: vec![json!({})],
+                })
+                .await?;
+        }
+
+        let queue = MutationQueue::open(path.clone()).await?;
+        let (_client, mut test_protocol) =
+            ConvexClient::with_test_protocol_and_mutation_queue(Some(queue)).await?;
+        test_protocol.wait_until_n_messages_sent(3).await;
+        // Replayed in their original order under new request ids.
+        assert_eq!(
+            test_protocol.take_sent().await[1..],
+            vec![
+                ClientMessage::Mutation {
+                    request_id: 0,
+                    udf_path: "first".parse()?,
+                    args: vec![json!({})],
+                },
+                ClientMessage::Mutation {
+                    request_id: 1,
+                    udf_path: "second".parse()?,
+                    args: vec![json!({})],
+                },
+            ]
+        );
+
+        let (transition, version) = fake_transition(StateVersion::initial(), vec![]);
+        for request_id in [0, 1] {
+            test_protocol
+                .fake_server_response(ServerMessage::MutationResponse {
+                    request_id,
+                    result: Ok(Value::Null),
+                    ts: Some(version.ts),
+                    log_lines: LogLinesMessage(vec![]),
+                })
+                .await?;
+        }
+        test_protocol.fake_server_response(transition).await?;
+
+        // Completed mutations are removed from the queue.
+        tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                let queue = MutationQueue::open(path.clone()).await?;
+                if queue.pending().is_empty() {
+                    return anyhow::Ok(());
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await??;
+        tokio::fs::remove_dir_all(dir).await?;
+        Ok(())
+    }
+
     #[test]
     fn test_deployment_url() -> anyhow::Result<()> {
         assert_eq!(
@@ -0,0 +1,290 @@
+//! A durable on-disk record of mutations that have been sent but haven't
+//! completed, so they can be replayed after the process restarts.
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use convex_sync_types::{
+    ClientMessage,
+    SessionRequestSeqNumber,
+    UdfPath,
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+
+use crate::Value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueuedMutation {
+    pub udf_path: UdfPath,
+    pub args: BTreeMap<String, Value>,
+}
+
+impl QueuedMutation {
+    fn to_json(&self, request_id: SessionRequestSeqNumber) -> JsonValue {
+        json!({
+            "requestId": request_id,
+            "udfPath": String::from(self.udf_path.clone()),
+            "args": JsonValue::from(Value::Object(self.args.clone())),
+        })
+    }
+
+    fn from_json(json: JsonValue) -> anyhow::Result<(SessionRequestSeqNumber, Self)> {
+        let request_id = json
+            .get("requestId")
+            .and_then(JsonValue::as_u64)
+            .context("Missing requestId")?
+            .try_into()?;
+        let udf_path = json
+            .get("udfPath")
+            .and_then(JsonValue::as_str)
+            .context("Missing udfPath")?
+            .parse()?;
+        let Value::Object(args) =
+            Value::try_from(json.get("args").cloned().context("Missing args")?)?
+        else {
+            anyhow::bail!("Mutation args must be an object");
+        };
+        Ok((request_id, Self { udf_path, args }))
+    }
+}
+
+/// Mutations are keyed by the [`SessionRequestSeqNumber`] they were sent with,
+/// so they can be replayed in their original order.
+pub struct MutationQueue {
+    path: PathBuf,
+    mutations: BTreeMap<SessionRequestSeqNumber, QueuedMutation>,
+}
+
+impl MutationQueue {
+    /// Open the queue stored at `path`, loading any mutations left over from a
+    /// previous process.
+    pub async fn open(path: PathBuf) -> anyhow::Result<Self> {
+        let mut mutations = BTreeMap::new();
+        match tokio::fs::read(&path).await {
+            Ok(contents) => {
+                let json: JsonValue = serde_json::from_slice(&contents)
+                    .with_context(|| format!("Corrupt mutation queue at {}", path.display()))?;
+                let JsonValue::Array(entries) = json else {
+                    anyhow::bail!("Corrupt mutation queue at {}", path.display());
+                };
+                for entry in entries {
+                    let (request_id, mutation) = QueuedMutation::from_json(entry)?;
+                    mutations.insert(request_id, mutation);
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+            Err(e) => return Err(e).context("Failed to read mutation queue"),
+        }
+        Ok(Self { path, mutations })
+    }
+
+    /// The queued mutations, in the order they were originally sent.
+    pub fn pending(&self) -> Vec<(SessionRequestSeqNumber, QueuedMutation)> {
+        self.mutations
+            .iter()
+            .map(|(request_id, mutation)| (*request_id, mutation.clone()))
+            .collect()
+    }
+
+    /// Record a mutation that's about to be sent to the server. Other messages
+    /// are ignored.
+    pub async fn track(&mut self, message: &ClientMessage) -> anyhow::Result<()> {
+        let Some((request_id, mutation)) = Self::parse(message)? else {
+            return Ok(());
+        };
+        if self.mutations.contains_key(&request_id) {
+            return Ok(());
+        }
+        self.mutations.insert(request_id, mutation);
+        self.persist().await
+    }
+
+    /// Replace every queued mutation with the mutations in `messages`, writing
+    /// the result to disk once. Used when replaying the queue under new
+    /// request ids, so a crash partway through never loses the old entries.
+    pub async fn replace(&mut self, messages: &[ClientMessage]) -> anyhow::Result<()> {
+        let mut mutations = BTreeMap::new();
+        for message in messages {
+            if let Some((request_id, mutation)) = Self::parse(message)? {
+                mutations.insert(request_id, mutation);
+            }
+        }
+        self.mutations = mutations;
+        self.persist().await
+    }
+
+    fn parse(
+        message: &ClientMessage,
+    ) -> anyhow::Result<Option<(SessionRequestSeqNumber, QueuedMutation)>> {
+        let ClientMessage::Mutation {
+            request_id,
+            udf_path,
+            args,
+        } = message
+        else {
+            return Ok(None);
+        };
+        let Some(Value::Object(args)) = args.first().cloned().map(Value::try_from).transpose()?
+        else {
+            anyhow::bail!("Mutation args must be an object");
+        };
+        Ok(Some((
+            *request_id,
+            QueuedMutation {
+                udf_path: udf_path.clone(),
+                args,
+            },
+        )))
+    }
+
+    /// Drop the mutations for which `f` returns false, returning whether any
+    /// were dropped. Call [`MutationQueue::persist`] to write the change to
+    /// disk.
+    pub fn retain(&mut self, mut f: impl FnMut(SessionRequestSeqNumber) -> bool) -> bool {
+        let num_mutations = self.mutations.len();
+        self.mutations.retain(|request_id, _| f(*request_id));
+        self.mutations.len() != num_mutations
+    }
+
+    /// Atomically replace the file on disk with the current queue.
+    pub async fn persist(&self) -> anyhow::Result<()> {
+        let entries: Vec<_> = self
+            .mutations
+            .iter()
+            .map(|(request_id, mutation)| mutation.to_json(*request_id))
+            .collect();
+        let tmp_path = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, JsonValue::Array(entries).to_string())
+            .await
+            .context("Failed to write mutation queue")?;
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .context("Failed to write mutation queue")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use convex_sync_types::ClientMessage;
+    use maplit::btreemap;
+    use serde_json::json;
+
+    use super::{
+        MutationQueue,
+        QueuedMutation,
+    };
+
+    #[tokio::test]
+    async fn test_mutation_queue_roundtrip() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("convex-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await?;
+        let path = dir.join("mutations.json");
+
+        let mut queue = MutationQueue::open(path.clone()).await?;
+        assert!(queue.pending().is_empty());
+        for request_id in [3, 1, 2] {
+            queue
+                .track(&ClientMessage::Mutation {
+                    request_id,
+                    udf_path: "messages:send".parse()?,
+                    args: vec![json!({ "n": request_id })],
+                })
+                .await?;
+        }
+        assert!(queue.retain(|request_id| request_id != 2));
+        queue.persist().await?;
+
+        let queue = MutationQueue::open(path).await?;
+        assert_eq!(
+            queue.pending(),
+            vec![
+                (
+                    1,
+                    QueuedMutation {
+                        udf_path: "messages:send".parse()?,
+                        args: btreemap! { "n".into() => 1.0.into() },
+                    }
+                ),
+                (
+                    3,
+                    QueuedMutation {
+                        udf_path: "messages:send".parse()?,
+                        args: btreemap! { "n".into() => 3.0.into() },
+                    }
+                ),
+            ]
+        );
+        tokio::fs::remove_dir_all(dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mutation_queue_replace() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("convex-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await?;
+        let path = dir.join("mutations.json");
+
+        let mut queue = MutationQueue::open(path.clone()).await?;
+        for request_id in [4, 5] {
+            queue
+                .track(&ClientMessage::Mutation {
+                    request_id,
+                    udf_path: "messages:send".parse()?,
+                    args: vec![json!({ "n": request_id })],
+                })
+                .await?;
+        }
+
+        // Reading the pending mutations leaves them on disk, so a crash before
+        // they're replaced doesn't lose any.
+        let queue = MutationQueue::open(path.clone()).await?;
+        let pending = queue.pending();
+        assert_eq!(pending.len(), 2);
+        let mut queue = MutationQueue::open(path.clone()).await?;
+        assert_eq!(queue.pending(), pending);
+
+        // Replaying under new request ids swaps the old entries out in one write,
+        // even when the new ids overlap the old ones.
+        let replayed: Vec<_> = pending
+            .iter()
+            .zip([0, 4])
+            .map(|((_, mutation), request_id)| ClientMessage::Mutation {
+                request_id,
+                udf_path: mutation.udf_path.clone(),
+                args: vec![serde_json::Value::from(crate::Value::Object(
+                    mutation.args.clone(),
+                ))],
+            })
+            .collect();
+        queue.replace(&replayed).await?;
+
+        let queue = MutationQueue::open(path).await?;
+        assert_eq!(
+            queue.pending(),
+            vec![
+                (
+                    0,
+                    QueuedMutation {
+                        udf_path: "messages:send".parse()?,
+                        args: btreemap! { "n".into() => 4.0.into() },
+                    }
+                ),
+                (
+                    4,
+                    QueuedMutation {
+                        udf_path: "messages:send".parse()?,
+                        args: btreemap! { "n".into() => 5.0.into() },
+                    }
+                ),
+            ]
+        );
+        tokio::fs::remove_dir_all(dir).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,837 @@
+use std::{
+    collections::BTreeMap,
+    pin::Pin,
+    sync::atomic::{
+        AtomicU64,
+        Ordering,
+    },
+};
+
+use futures::{
+    channel::mpsc,
+    select_biased,
+    task,
+    FutureExt,
+    Stream,
+    StreamExt,
+};
+use tokio::task::JoinHandle;
+
+use crate::{
+    client::subscription::{
+        QuerySetSubscription,
+        QuerySubscription,
+    },
+    ConvexClient,
+    FunctionResult,
+    QueryResults,
+    Value,
+};
+
+/// Distinguishes the pages of separate paginated subscriptions to the same
+/// query, so they never share a query (and its journal) on the server.
+static NEXT_PAGINATION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The status of a [`PaginatedQuerySubscription`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PaginationStatus {
+    /// The first page is still loading.
+    LoadingFirstPage,
+    /// More items can be loaded with
+    /// [`load_more`](PaginatedQuerySubscription::load_more).
+    CanLoadMore,
+    /// A page requested by
+    /// [`load_more`](PaginatedQuerySubscription::load_more) is loading.
+    LoadingMore,
+    /// Every item of the query has been loaded.
+    Exhausted,
+}
+
+/// The merged results of every loaded page of a paginated query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaginatedQueryResults {
+    /// The items of every loaded page, in order.
+    pub results: Vec<Value>,
+    /// Whether more pages are loading or can be loaded.
+    pub status: PaginationStatus,
+}
+
+/// A reactive subscription to a paginated query, returned by
+/// [`ConvexClient::subscribe_paginated`].
+///
+/// The query must take a `paginationOpts` argument and return a
+/// `PaginationResult`, e.g. by returning the result of `.paginate()` on a
+/// database query.
+///
+/// [`PaginatedQuerySubscription`] implements
+/// [`Stream`]<`Result<`[`PaginatedQueryResults`]`, `[`FunctionResult`]`>`>.
+/// Every loaded page stays subscribed, and a new item appears on the stream
+/// each time the merged results change. Errors from the query are yielded as
+/// the [`FunctionResult`] of the failing page. If the subscription itself
+/// fails, e.g. because the client stopped running, the error is yielded as a
+/// [`FunctionResult::ErrorMessage`] and the stream ends.
+///
+/// The pages are unsubscribed when this subscription is dropped.
+pub struct PaginatedQuerySubscription {
+    load_more_sender: mpsc::UnboundedSender<usize>,
+    results: mpsc::UnboundedReceiver<Result<PaginatedQueryResults, FunctionResult>>,
+    handle: JoinHandle<()>,
+}
+
+impl PaginatedQuerySubscription {
+    pub(super) async fn new(
+        client: ConvexClient,
+        name: &str,
+        args: BTreeMap<String, Value>,
+        initial_num_items: usize,
+    ) -> anyhow::Result<Self> {
+        let (load_more_sender, load_more_receiver) = mpsc::unbounded();
+        let (results_sender, results) = mpsc::unbounded();
+        // Watch the query set before subscribing to the first page, so we don't
+        // miss its result.
+        let watch = client.watch_all();
+        let mut paginator = Paginator {
+            client,
+            name: name.to_string(),
+            args,
+            initial_num_items,
+            id: 0,
+            next_page_key: 0,
+            page_keys: vec![],
+            pages: BTreeMap::new(),
+            ongoing_splits: BTreeMap::new(),
+            continue_cursor: None,
+        };
+        paginator.reset().await?;
+        let handle = tokio::spawn(paginator.run(watch, load_more_receiver, results_sender));
+        Ok(Self {
+            load_more_sender,
+            results,
+            handle,
+        })
+    }
+
+    /// Load `num_items` more items after the currently loaded pages.
+    ///
+    /// This is a no-op unless the latest status is
+    /// [`PaginationStatus::CanLoadMore`].
+    pub fn load_more(&self, num_items: usize) {
+        let _ = self.load_more_sender.unbounded_send(num_items);
+    }
+}
+
+impl Drop for PaginatedQuerySubscription {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl Stream for PaginatedQuerySubscription {
+    type Item = Result<PaginatedQueryResults, FunctionResult>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        self.results.poll_next_unpin(cx)
+    }
+}
+
+/// An index into [`Paginator::pages`]. Keys are never reused.
+type PageKey = usize;
+
+struct Page {
+    subscription: QuerySubscription,
+    cursor: Option<String>,
+    result: Option<FunctionResult>,
+}
+
+/// The fields of a `PaginationResult` returned by a page.
+struct PaginationResult {
+    page: Vec<Value>,
+    is_done: bool,
+    continue_cursor: String,
+    split_cursor: Option<String>,
+    page_status: Option<String>,
+}
+
+impl TryFrom<&FunctionResult> for PaginationResult {
+    type Error = FunctionResult;
+
+    fn try_from(result: &FunctionResult) -> Result<Self, FunctionResult> {
+        let invalid = || {
+            FunctionResult::ErrorMessage(
+                "Paginated query did not return a PaginationResult".to_string(),
+            )
+        };
+        let mut fields = match result {
+            FunctionResult::Value(Value::Object(fields)) => fields.clone(),
+            FunctionResult::Value(_) => return Err(invalid()),
+            FunctionResult::ErrorMessage(_) | FunctionResult::ConvexError(_) => {
+                return Err(result.clone())
+            },
+        };
+        let optional_string = |value: Option<Value>| match value {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s)),
+            Some(_) => Err(invalid()),
+        };
+        let Some(Value::Array(page)) = fields.remove("page") else {
+            return Err(invalid());
+        };
+        let Some(Value::Boolean(is_done)) = fields.remove("isDone") else {
+            return Err(invalid());
+        };
+        let Some(Value::String(continue_cursor)) = fields.remove("continueCursor") else {
+            return Err(invalid());
+        };
+        Ok(Self {
+            page,
+            is_done,
+            continue_cursor,
+            split_cursor: optional_string(fields.remove("splitCursor"))?,
+            page_status: optional_string(fields.remove("pageStatus"))?,
+        })
+    }
+}
+
+/// Manages the page subscriptions of a paginated query, mirroring
+/// `usePaginatedQuery` in the JS client.
+struct Paginator {
+    client: ConvexClient,
+    name: String,
+    args: BTreeMap<String, Value>,
+    initial_num_items: usize,
+    id: u64,
+    next_page_key: PageKey,
+    /// The pages that make up the results, in order.
+    page_keys: Vec<PageKey>,
+    pages: BTreeMap<PageKey, Page>,
+    /// Pages that are being replaced by two smaller pages once both have
+    /// loaded.
+    ongoing_splits: BTreeMap<PageKey, (PageKey, PageKey)>,
+    /// Where the next page starts, if more items can be loaded.
+    continue_cursor: Option<String>,
+}
+
+impl Paginator {
+    async fn run(
+        mut self,
+        watch: QuerySetSubscription,
+        load_more_receiver: mpsc::UnboundedReceiver<usize>,
+        results_sender: mpsc::UnboundedSender<Result<PaginatedQueryResults, FunctionResult>>,
+    ) {
+        if let Err(e) = self
+            .send_results(watch, load_more_receiver, &results_sender)
+            .await
+        {
+            tracing::error!("Failed to update paginated query {}: {e:?}", self.name);
+            let _ = results_sender.unbounded_send(Err(FunctionResult::ErrorMessage(format!(
+                "Failed to update paginated query {}: {e}",
+                self.name
+            ))));
+        }
+    }
+
+    /// Send the merged results each time they change, until the subscription
+    /// is dropped.
+    async fn send_results(
+        &mut self,
+        mut watch: QuerySetSubscription,
+        mut load_more_receiver: mpsc::UnboundedReceiver<usize>,
+        results_sender: &mpsc::UnboundedSender<Result<PaginatedQueryResults, FunctionResult>>,
+    ) -> anyhow::Result<()> {
+        let mut last_results = None;
+        loop {
+            let results = self.results().await?;
+            if last_results.as_ref() != Some(&results) {
+                if results_sender.unbounded_send(results.clone()).is_err() {
+                    return Ok(());
+                }
+                last_results = Some(results);
+            }
+
+            select_biased! {
+                num_items = load_more_receiver.next().fuse() => match num_items {
+                    Some(num_items) => self.load_more(num_items).await?,
+                    None => return Ok(()),
+                },
+                query_results = watch.next().fuse() => match query_results {
+                    Some(query_results) => self.ingest(&query_results),
+                    None => anyhow::bail!("The client stopped receiving query results"),
+                },
+            }
+        }
+    }
+
+    /// Drop every page and start over from the first page.
+    async fn reset(&mut self) -> anyhow::Result<()> {
+        self.id = NEXT_PAGINATION_ID.fetch_add(1, Ordering::Relaxed);
+        self.next_page_key = 0;
+        self.page_keys = vec![];
+        self.pages = BTreeMap::new();
+        self.ongoing_splits = BTreeMap::new();
+        self.continue_cursor = None;
+        let key = self.add_page(self.initial_num_items, None, None).await?;
+        self.page_keys.push(key);
+        Ok(())
+    }
+
+    async fn add_page(
+        &mut self,
+        num_items: usize,
+        cursor: Option<String>,
+        end_cursor: Option<String>,
+    ) -> anyhow::Result<PageKey> {
+        let mut pagination_opts = BTreeMap::from([
+            ("numItems".to_string(), Value::Float64(num_items as f64)),
+            ("cursor".to_string(), cursor.clone().into()),
+            ("id".to_string(), Value::Float64(self.id as f64)),
+        ]);
+        if let Some(end_cursor) = end_cursor {
+            pagination_opts.insert("endCursor".to_string(), end_cursor.into());
+        }
+        let mut args = self.args.clone();
+        args.insert("paginationOpts".to_string(), Value::Object(pagination_opts));
+        let mut subscription = self.client.subscribe(&self.name, args).await?;
+        let key = self.next_page_key;
+        self.next_page_key += 1;
+        self.pages.insert(
+            key,
+            Page {
+                result: subscription.initial.take(),
+                subscription,
+                cursor,
+            },
+        );
+        Ok(key)
+    }
+
+    async fn load_more(&mut self, num_items: usize) -> anyhow::Result<()> {
+        // Only load one page at a time, after the last page has loaded.
+        let Some(cursor) = self.continue_cursor.take() else {
+            return Ok(());
+        };
+        let key = self.add_page(num_items, Some(cursor), None).await?;
+        self.page_keys.push(key);
+        Ok(())
+    }
+
+    fn ingest(&mut self, query_results: &QueryResults) {
+        for page in self.pages.values_mut() {
+            if let Some(result) = query_results.get(page.subscription.id()) {
+                page.result = Some(result.clone());
+            }
+        }
+    }
+
+    /// Start replacing the page at `key` with the two pages on either side of
+    /// `split_cursor`.
+    async fn split_page(
+        &mut self,
+        key: PageKey,
+        split_cursor: String,
+        continue_cursor: String,
+    ) -> anyhow::Result<()> {
+        let cursor = self.pages[&key].cursor.clone();
+        let first = self
+            .add_page(self.initial_num_items, cursor, Some(split_cursor.clone()))
+            .await?;
+        let second = self
+            .add_page(
+                self.initial_num_items,
+                Some(split_cursor),
+                Some(continue_cursor),
+            )
+            .await?;
+        self.ongoing_splits.insert(key, (first, second));
+        Ok(())
+    }
+
+    /// Swap in the halves of split pages once both have loaded.
+    fn complete_splits(&mut self) {
+        let completed: Vec<_> = self
+            .ongoing_splits
+            .iter()
+            .filter(|(_, (first, second))| {
+                self.pages[first].result.is_some() && self.pages[second].result.is_some()
+            })
+            .map(|(key, halves)| (*key, *halves))
+            .collect();
+        for (key, (first, second)) in completed {
+            self.ongoing_splits.remove(&key);
+            self.pages.remove(&key);
+            if let Some(index) = self.page_keys.iter().position(|k| *k == key) {
+                self.page_keys.splice(index..=index, [first, second]);
+            }
+        }
+    }
+
+    /// Merge the loaded pages, splitting pages that have grown too large and
+    /// starting over if a cursor has become invalid.
+    async fn results(&mut self) -> anyhow::Result<Result<PaginatedQueryResults, FunctionResult>> {
+        self.complete_splits();
+        let mut results = vec![];
+        let mut last_page = None;
+        let mut splits = vec![];
+        let mut split_required = false;
+        for key in self.page_keys.iter() {
+            let Some(ref result) = self.pages[key].result else {
+                last_page = None;
+                break;
+            };
+            let page = match PaginationResult::try_from(result) {
+                Ok(page) => page,
+                Err(FunctionResult::ErrorMessage(message)) if message.contains("InvalidCursor") => {
+                    // The paginated database query was probably data-dependent and
+                    // changed underneath us, so our cursors no longer match it.
+                    tracing::warn!(
+                        "Paginated query hit error, resetting pagination state: {message}"
+                    );
+                    self.reset().await?;
+                    return Ok(Ok(PaginatedQueryResults {
+                        results: vec![],
+                        status: PaginationStatus::LoadingFirstPage,
+                    }));
+                },
+                Err(e) => return Ok(Err(e)),
+            };
+            if !self.ongoing_splits.contains_key(key) {
+                if let Some(ref split_cursor) = page.split_cursor {
+                    if matches!(
+                        page.page_status.as_deref(),
+                        Some("SplitRecommended" | "SplitRequired")
+                    ) || page.page.len() > self.initial_num_items * 2
+                    {
+                        splits.push((*key, split_cursor.clone(), page.continue_cursor.clone()));
+                    }
+                }
+            }
+            if page.page_status.as_deref() == Some("SplitRequired") {
+                // The server couldn't fetch the full page, so stop before it while
+                // the page is splitting.
+                split_required = true;
+                break;
+            }
+            results.extend(page.page.iter().cloned());
+            last_page = Some(page);
+        }
+        for (key, split_cursor, continue_cursor) in splits {
+            self.split_page(key, split_cursor, continue_cursor).await?;
+        }
+
+        self.continue_cursor = None;
+        let status = match last_page {
+            _ if split_required => PaginationStatus::LoadingMore,
+            None if self.page_keys.len() == 1 && self.ongoing_splits.is_empty() => {
+                PaginationStatus::LoadingFirstPage
+            },
+            None => PaginationStatus::LoadingMore,
+            Some(page) if page.is_done => PaginationStatus::Exhausted,
+            Some(page) => {
+                self.continue_cursor = Some(page.continue_cursor);
+                PaginationStatus::CanLoadMore
+            },
+        };
+        Ok(Ok(PaginatedQueryResults { results, status }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use convex_sync_types::{
+        ClientMessage,
+        LogLinesMessage,
+        Query,
+        QueryId,
+        QuerySetModification,
+        SerializedQueryJournal,
+        StateModification,
+        StateVersion,
+    };
+    use futures::StreamExt;
+    use maplit::btreemap;
+    use pretty_assertions::assert_eq;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use super::{
+        PaginatedQueryResults,
+        PaginatedQuerySubscription,
+        PaginationStatus,
+    };
+    use crate::{
+        sync::{
+            testing::TestProtocolManager,
+            ServerMessage,
+        },
+        ConvexClient,
+        FunctionResult,
+        Value,
+    };
+
+    /// Wait for the client to add `n` queries, returning each query's id,
+    /// `paginationOpts` and journal.
+    async fn added_queries(
+        test_protocol: &TestProtocolManager,
+        n: usize,
+    ) -> Vec<(QueryId, JsonValue, Option<SerializedQueryJournal>)> {
+        let mut added = vec![];
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while added.len() < n {
+                for message in test_protocol.take_sent().await {
+                    let ClientMessage::ModifyQuerySet { modifications, .. } = message else {
+                        continue;
+                    };
+                    for modification in modifications {
+                        if let QuerySetModification::Add(Query {
+                            query_id,
+                            args,
+                            journal,
+                            ..
+                        }) = modification
+                        {
+                            added.push((query_id, args[0]["paginationOpts"].clone(), journal));
+                        }
+                    }
+                }
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("Test timed out waiting for queries to be added");
+        assert_eq!(added.len(), n);
+        added
+    }
+
+    fn page(items: Vec<Value>, is_done: bool, continue_cursor: &str) -> Value {
+        Value::Object(btreemap! {
+            "page".into() => Value::Array(items),
+            "isDone".into() => is_done.into(),
+            "continueCursor".into() => continue_cursor.into(),
+        })
+    }
+
+    fn split_page(items: Vec<Value>, continue_cursor: &str, split_cursor: &str) -> Value {
+        let Value::Object(mut fields) = page(items, false, continue_cursor) else {
+            unreachable!()
+        };
+        fields.insert("splitCursor".into(), split_cursor.into());
+        fields.insert("pageStatus".into(), "SplitRecommended".into());
+        Value::Object(fields)
+    }
+
+    async fn fake_transition(
+        test_protocol: &mut TestProtocolManager,
+        version: &mut StateVersion,
+        modifications: Vec<StateModification<Value>>,
+    ) -> anyhow::Result<()> {
+        let start_version = *version;
+        version.ts = version.ts.succ()?;
+        test_protocol
+            .fake_server_response(ServerMessage::Transition {
+                start_version,
+                end_version: *version,
+                modifications,
+            })
+            .await
+    }
+
+    fn updated(query_id: QueryId, value: Value, journal: &str) -> StateModification<Value> {
+        StateModification::QueryUpdated {
+            query_id,
+            value,
+            log_lines: LogLinesMessage(vec![]),
+            journal: Some(journal.to_string()),
+        }
+    }
+
+    async fn next(
+        subscription: &mut PaginatedQuerySubscription,
+    ) -> Result<PaginatedQueryResults, FunctionResult> {
+        tokio::time::timeout(Duration::from_secs(2), subscription.next())
+            .await
+            .expect("Test timed out waiting for paginated results")
+            .expect("Paginated subscription closed")
+    }
+
+    #[tokio::test]
+    async fn test_paginated_query() -> anyhow::Result<()> {
+        let (mut client, mut test_protocol) = ConvexClient::with_test_protocol().await?;
+        test_protocol.take_sent().await;
+        let mut version = StateVersion::initial();
+
+        let mut subscription = client
+            .subscribe_paginated("listMessages", btreemap! {}, 2)
+            .await?;
+        let [(first_id, pagination_opts, None)] = &added_queries(&test_protocol, 1).await[..]
+        else {
+            panic!("Expected a single page without a journal");
+        };
+        let id = pagination_opts["id"].clone();
+        assert_eq!(
+            *pagination_opts,
+            json!({ "numItems": 2.0, "cursor": null, "id": id })
+        );
+        assert_eq!(
+            next(&mut subscription).await,
+            Ok(PaginatedQueryResults {
+                results: vec![],
+                status: PaginationStatus::LoadingFirstPage,
+            })
+        );
+
+        fake_transition(
+            &mut test_protocol,
+            &mut version,
+            vec![updated(
+                *first_id,
+                page(vec![1.into(), 2.into()], false, "c1"),
+                "j1",
+            )],
+        )
+        .await?;
+        assert_eq!(
+            next(&mut subscription).await,
+            Ok(PaginatedQueryResults {
+                results: vec![1.into(), 2.into()],
+                status: PaginationStatus::CanLoadMore,
+            })
+        );
+
+        subscription.load_more(3);
+        let [(second_id, pagination_opts, None)] = &added_queries(&test_protocol, 1).await[..]
+        else {
+            panic!("Expected a single page without a journal");
+        };
+        assert_eq!(
+            *pagination_opts,
+            json!({ "numItems": 3.0, "cursor": "c1", "id": id })
+        );
+        assert_eq!(
+            next(&mut subscription).await,
+            Ok(PaginatedQueryResults {
+                results: vec![1.into(), 2.into()],
+                status: PaginationStatus::LoadingMore,
+            })
+        );
+
+        fake_transition(
+            &mut test_protocol,
+            &mut version,
+            vec![updated(*second_id, page(vec![3.into()], true, "c2"), "j2")],
+        )
+        .await?;
+        assert_eq!(
+            next(&mut subscription).await,
+            Ok(PaginatedQueryResults {
+                results: vec![1.into(), 2.into(), 3.into()],
+                status: PaginationStatus::Exhausted,
+            })
+        );
+
+        // The pages resume from their journals after a reconnect.
+        test_protocol.fake_failure("ConnectionLost").await?;
+        let mut resent: Vec<_> = added_queries(&test_protocol, 2)
+            .await
+            .into_iter()
+            .map(|(query_id, _, journal)| (query_id, journal))
+            .collect();
+        resent.sort();
+        assert_eq!(
+            resent,
+            vec![
+                (*first_id, Some(Some("j1".to_string()))),
+                (*second_id, Some(Some("j2".to_string()))),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_paginated_query_split() -> anyhow::Result<()> {
+        let (mut client, mut test_protocol) = ConvexClient::with_test_protocol().await?;
+        test_protocol.take_sent().await;
+        let mut version = StateVersion::initial();
+
+        let mut subscription = client
+            .subscribe_paginated("listMessages", btreemap! {}, 2)
+            .await?;
+        let [(first_id, pagination_opts, _)] = &added_queries(&test_protocol, 1).await[..] else {
+            panic!("Expected a single page");
+        };
+        let id = pagination_opts["id"].clone();
+        next(&mut subscription).await.unwrap();
+
+        // The page has grown, so the server recommends splitting it.
+        let items: Vec<Value> = (1..=5).map(Value::from).collect();
+        fake_transition(
+            &mut test_protocol,
+            &mut version,
+            vec![updated(
+                *first_id,
+                split_page(items.clone(), "c1", "s1"),
+                "j1",
+            )],
+        )
+        .await?;
+        assert_eq!(
+            next(&mut subscription).await,
+            Ok(PaginatedQueryResults {
+                results: items.clone(),
+                status: PaginationStatus::CanLoadMore,
+            })
+        );
+        let [(left_id, left_opts, _), (right_id, right_opts, _)] =
+            &added_queries(&test_protocol, 2).await[..]
+        else {
+            panic!("Expected two halves");
+        };
+        assert_eq!(
+            *left_opts,
+            json!({ "numItems": 2.0, "cursor": null, "endCursor": "s1", "id": id })
+        );
+        assert_eq!(
+            *right_opts,
+            json!({ "numItems": 2.0, "cursor": "s1", "endCursor": "c1", "id": id })
+        );
+
+        // The original page is replaced once both halves have loaded.
+        fake_transition(
+            &mut test_protocol,
+            &mut version,
+            vec![
+                updated(*left_id, page(items[..3].to_vec(), false, "s1"), "j2"),
+                updated(*right_id, page(items[3..].to_vec(), false, "c1"), "j3"),
+            ],
+        )
+        .await?;
+        tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                let removed = test_protocol.take_sent().await.into_iter().any(|message| {
+                    matches!(
+                        message,
+                        ClientMessage::ModifyQuerySet { ref modifications, .. }
+                            if modifications.contains(
+                                &QuerySetModification::Remove { query_id: *first_id }
+                            )
+                    )
+                });
+                if removed {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+        })
+        .await?;
+
+        // Subsequent results come from the halves.
+        fake_transition(
+            &mut test_protocol,
+            &mut version,
+            vec![updated(*right_id, page(vec![4.into()], false, "c1"), "j3")],
+        )
+        .await?;
+        assert_eq!(
+            next(&mut subscription).await,
+            Ok(PaginatedQueryResults {
+                results: vec![1.into(), 2.into(), 3.into(), 4.into()],
+                status: PaginationStatus::CanLoadMore,
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_paginated_query_invalid_cursor() -> anyhow::Result<()> {
+        let (mut client, mut test_protocol) = ConvexClient::with_test_protocol().await?;
+        test_protocol.take_sent().await;
+        let mut version = StateVersion::initial();
+
+        let mut subscription = client
+            .subscribe_paginated("listMessages", btreemap! {}, 2)
+            .await?;
+        let [(first_id, pagination_opts, _)] = &added_queries(&test_protocol, 1).await[..] else {
+            panic!("Expected a single page");
+        };
+        let id = pagination_opts["id"].clone();
+        next(&mut subscription).await.unwrap();
+
+        fake_transition(
+            &mut test_protocol,
+            &mut version,
+            vec![StateModification::QueryFailed {
+                query_id: *first_id,
+                error_message: "InvalidCursor: Tried to run a query starting from a cursor, but \
+                                it looks like this cursor is from a different query."
+                    .to_string(),
+                log_lines: LogLinesMessage(vec![]),
+                journal: None,
+                error_data: None,
+            }],
+        )
+        .await?;
+
+        // Pagination restarts from the first page with a new id.
+        let [(_, pagination_opts, _)] = &added_queries(&test_protocol, 1).await[..] else {
+            panic!("Expected a single page");
+        };
+        assert_eq!(pagination_opts["cursor"], JsonValue::Null);
+        assert_ne!(pagination_opts["id"], id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_paginated_query_error() -> anyhow::Result<()> {
+        let (mut client, mut test_protocol) = ConvexClient::with_test_protocol().await?;
+        test_protocol.take_sent().await;
+        let mut version = StateVersion::initial();
+
+        let mut subscription = client
+            .subscribe_paginated("listMessages", btreemap! {}, 2)
+            .await?;
+        let [(first_id, ..)] = &added_queries(&test_protocol, 1).await[..] else {
+            panic!("Expected a single page");
+        };
+        next(&mut subscription).await.unwrap();
+
+        fake_transition(
+            &mut test_protocol,
+            &mut version,
+            vec![updated(*first_id, 1.into(), "j1")],
+        )
+        .await?;
+        assert_eq!(
+            next(&mut subscription).await,
+            Err(FunctionResult::ErrorMessage(
+                "Paginated query did not return a PaginationResult".to_string()
+            ))
+        );
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_paginated_query_client_failure() -> anyhow::Result<()> {
+        let (mut client, test_protocol) = ConvexClient::with_test_protocol().await?;
+        test_protocol.take_sent().await;
+
+        let mut subscription = client
+            .subscribe_paginated("listMessages", btreemap! {}, 2)
+            .await?;
+        added_queries(&test_protocol, 1).await;
+        next(&mut subscription).await.unwrap();
+
+        // Stop the client's worker out from under the subscription.
+        client.listen_handle.as_ref().unwrap().abort();
+        let Err(FunctionResult::ErrorMessage(message)) = next(&mut subscription).await else {
+            panic!("Expected the failure to be yielded");
+        };
+        assert!(message.contains("listMessages"), "{message}");
+        let end = tokio::time::timeout(Duration::from_secs(2), subscription.next()).await?;
+        assert!(end.is_none());
+        Ok(())
+    }
+}
@@ -18,15 +18,27 @@ use futures::{
     FutureExt,
     StreamExt,
 };
-use tokio::sync::broadcast;
+use tokio::sync::{
+    broadcast,
+    watch,
+};
 use tokio_stream::wrappers::BroadcastStream;
 
 use crate::{
     base_client::{
         BaseConvexClient,
+        OptimisticUpdate,
         SubscriberId,
     },
     client::{
+        connection_state::{
+            ConnectionState,
+            WebSocketState,
+        },
+        mutation_queue::{
+            MutationQueue,
+            QueuedMutation,
+        },
         QueryResults,
         QuerySubscription,
     },
@@ -64,6 +76,7 @@ pub enum ClientRequest {
 pub struct MutationRequest {
     pub udf_path: UdfPath,
     pub args: BTreeMap<String, Value>,
+    pub optimistic_update: Option<OptimisticUpdate>,
 }
 
 pub struct ActionRequest {
@@ -90,21 +103,28 @@ pub async fn worker<T: SyncProtocol>(
 
     mut client_request_receiver: mpsc::UnboundedReceiver<ClientRequest>,
     mut watch_sender: broadcast::Sender<QueryResults>,
+    connection_state_sender: watch::Sender<ConnectionState>,
+    mut mutation_queue: Option<MutationQueue>,
     mut base_client: BaseConvexClient,
     mut protocol_manager: T,
 ) -> Infallible {
     let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+    replay_mutation_queue(&mut mutation_queue, &mut base_client, &mut protocol_manager).await;
+    update_inflight_requests(&connection_state_sender, &base_client);
     loop {
         let e = loop {
-            match _worker_once(
+            let result = _worker_once(
                 &mut protocol_response_receiver,
                 &mut client_request_receiver,
                 &mut watch_sender,
+                &connection_state_sender,
+                &mut mutation_queue,
                 &mut base_client,
                 &mut protocol_manager,
             )
-            .await
-            {
+            .await;
+            update_inflight_requests(&connection_state_sender, &base_client);
+            match result {
                 Ok(()) => backoff.reset(),
                 Err(e) => break e,
             }
@@ -114,6 +134,16 @@ pub async fn worker<T: SyncProtocol>(
         tracing::error!(
             "Convex Client Worker failed: {e:?}. Backing off for {delay:?} and retrying."
         );
+        connection_state_sender.send_modify(|state| {
+            let attempt = match state.web_socket {
+                WebSocketState::Reconnecting { attempt, .. } => attempt + 1,
+                WebSocketState::Connecting | WebSocketState::Connected => 1,
+            };
+            state.web_socket = WebSocketState::Reconnecting {
+                attempt,
+                last_close_reason: e.clone(),
+            };
+        });
         // Tell the sync protocol to reconnect followed by an immediate resend of
         // ongoing queries/mutations. It's important these happen together to
         // ensure mutation ordering.
@@ -124,16 +154,69 @@ pub async fn worker<T: SyncProtocol>(
             })
             .await;
         base_client.resend_ongoing_queries_mutations();
-        flush_messages(&mut base_client, &mut protocol_manager).await;
+        flush_messages(&mut base_client, &mut protocol_manager, &mut mutation_queue).await;
         tokio::time::sleep(delay).await;
     }
 }
 
+/// Resend the mutations left in the durable queue by a previous process, in
+/// their original order. They're tracked under new request ids, and the queue
+/// is rewritten once after every mutation has been re-tracked, so the old
+/// entries are only dropped once the new ones have been persisted.
+async fn replay_mutation_queue<P: SyncProtocol>(
+    mutation_queue: &mut Option<MutationQueue>,
+    base_client: &mut BaseConvexClient,
+    protocol: &mut P,
+) {
+    let Some(queue) = mutation_queue else {
+        return;
+    };
+    let pending = queue.pending();
+    if pending.is_empty() {
+        return;
+    }
+    tracing::info!("Replaying {} queued mutations", pending.len());
+    for (_, QueuedMutation { udf_path, args }) in pending {
+        // Nobody is waiting on the results of mutations from a previous process.
+        drop(base_client.mutation(udf_path, args));
+    }
+    let mut messages = vec![];
+    while let Some(message) = base_client.pop_next_message() {
+        messages.push(message);
+    }
+    if let Err(e) = queue.replace(&messages).await {
+        tracing::error!("Failed to persist replayed mutations to queue: {e:?}");
+    }
+    for message in messages {
+        let _ = protocol.send(message).await;
+    }
+}
+
+fn update_inflight_requests(
+    connection_state_sender: &watch::Sender<ConnectionState>,
+    base_client: &BaseConvexClient,
+) {
+    let inflight_mutations = base_client.num_inflight_mutations();
+    let inflight_actions = base_client.num_inflight_actions();
+    connection_state_sender.send_if_modified(|state| {
+        if state.inflight_mutations == inflight_mutations
+            && state.inflight_actions == inflight_actions
+        {
+            return false;
+        }
+        state.inflight_mutations = inflight_mutations;
+        state.inflight_actions = inflight_actions;
+        true
+    });
+}
+
 async fn _worker_once<T: SyncProtocol>(
     protocol_response_receiver: &mut mpsc::Receiver<ProtocolResponse>,
 
     client_request_receiver: &mut mpsc::UnboundedReceiver<ClientRequest>,
     watch_sender: &mut broadcast::Sender<QueryResults>,
+    connection_state_sender: &watch::Sender<ConnectionState>,
+    mutation_queue: &mut Option<MutationQueue>,
     base_client: &mut BaseConvexClient,
     protocol_manager: &mut T,
 ) -> Result<(), ReconnectProtocolReason> {
@@ -145,9 +228,23 @@ async fn _worker_once<T: SyncProtocol>(
                         // Notify watchers of the new consistent query results at new timestamp
                         let _ = watch_sender.send(subscriber_id_to_latest_value);
                     }
+                    if let Some(queue) = mutation_queue {
+                        if queue.retain(|request_id| base_client.is_request_inflight(request_id)) {
+                            if let Err(e) = queue.persist().await {
+                                tracing::error!("Failed to update mutation queue: {e:?}");
+                            }
+                        }
+                    }
+                },
+                Some(ProtocolResponse::Connected) => {
+                    connection_state_sender.send_if_modified(|state| {
+                        let modified = state.web_socket != WebSocketState::Connected;
+                        state.web_socket = WebSocketState::Connected;
+                        modified
+                    });
                 },
-                Some(ProtocolResponse::Failure) => {
-                    return Err("ProtocolFailure".into());
+                Some(ProtocolResponse::Failure(reason)) => {
+                    return Err(reason);
                 },
                 None => {},
             }
@@ -161,7 +258,7 @@ async fn _worker_once<T: SyncProtocol>(
                         args,
                     } =  query;
                     let subscriber_id = base_client.subscribe(udf_path, args);
-                    flush_messages(base_client, protocol_manager).await;
+                    flush_messages(base_client, protocol_manager, mutation_queue).await;
 
                     let watch = BroadcastStream::new(watch);
                     let subscription = QuerySubscription {
@@ -176,10 +273,19 @@ async fn _worker_once<T: SyncProtocol>(
                     let MutationRequest {
                         udf_path,
                         args,
+                        optimistic_update,
                     } = mutation;
-                    let result_receiver = base_client
-                        .mutation(udf_path, args);
-                    flush_messages(base_client, protocol_manager).await;
+                    let result_receiver = match optimistic_update {
+                        Some(optimistic_update) => {
+                            let result_receiver = base_client
+                                .mutation_with_optimistic_update(udf_path, args, optimistic_update);
+                            // Notify watchers of the optimistic query results
+                            let _ = watch_sender.send(base_client.latest_results().clone());
+                            result_receiver
+                        },
+                        None => base_client.mutation(udf_path, args),
+                    };
+                    flush_messages(base_client, protocol_manager, mutation_queue).await;
                     let _ = tx.send(result_receiver);
                 },
                 ClientRequest::Action(action, tx) => {
@@ -189,17 +295,17 @@ async fn _worker_once<T: SyncProtocol>(
                     } = action;
                     let result_receiver = base_client
                         .action(udf_path, args);
-                    flush_messages(base_client, protocol_manager).await;
+                    flush_messages(base_client, protocol_manager, mutation_queue).await;
                     let _ = tx.send(result_receiver);
                 },
                 ClientRequest::Unsubscribe(unsubscribe) => {
                     let UnsubscribeRequest {subscriber_id} = unsubscribe;
                     base_client.unsubscribe(subscriber_id);
-                    flush_messages(base_client, protocol_manager).await;
+                    flush_messages(base_client, protocol_manager, mutation_queue).await;
                 },
                 ClientRequest::Authenticate(authenticate) => {
                     base_client.set_auth(authenticate.token);
-                    flush_messages(base_client, protocol_manager).await;
+                    flush_messages(base_client, protocol_manager, mutation_queue).await;
                 },
             }
         }
@@ -207,9 +313,19 @@ async fn _worker_once<T: SyncProtocol>(
     Ok(())
 }
 
-/// Flush all messages to the protocol
-async fn flush_messages<P: SyncProtocol>(base_client: &mut BaseConvexClient, protocol: &mut P) {
+/// Flush all messages to the protocol, persisting mutations to the durable
+/// queue (if any) before they're sent.
+async fn flush_messages<P: SyncProtocol>(
+    base_client: &mut BaseConvexClient,
+    protocol: &mut P,
+    mutation_queue: &mut Option<MutationQueue>,
+) {
     while let Some(modification) = base_client.pop_next_message() {
+        if let Some(queue) = mutation_queue {
+            if let Err(e) = queue.track(&modification).await {
+                tracing::error!("Failed to persist mutation to queue: {e:?}");
+            }
+        }
         let _ = protocol.send(modification).await;
     }
 }
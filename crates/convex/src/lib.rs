@@ -51,11 +51,22 @@ pub use value::{
 
 mod client;
 pub use client::{
+    connection_state::{
+        ConnectionState,
+        ConnectionStateSubscription,
+        WebSocketState,
+    },
+    paginated_query::{
+        PaginatedQueryResults,
+        PaginatedQuerySubscription,
+        PaginationStatus,
+    },
     subscription::{
         QuerySetSubscription,
         QuerySubscription,
     },
     ConvexClient,
+    ConvexClientBuilder,
 };
 
 pub mod base_client;
@@ -27,7 +27,10 @@ pub type ServerMessage = convex_sync_types::ServerMessage<Value>;
 #[derive(Debug)]
 pub enum ProtocolResponse {
     ServerMessage(ServerMessage),
-    Failure,
+    /// The protocol (re)established its connection to the server.
+    Connected,
+    /// The connection failed, with the reason it was closed.
+    Failure(ReconnectProtocolReason),
 }
 
 #[async_trait]
@@ -27,6 +27,7 @@ use crate::sync::{
 #[derive(Debug)]
 struct TestProtocolInner {
     closed: bool,
+    connection_count: u32,
     sent_messages: Vec<ClientMessage>,
 }
 #[derive(Debug, Clone)]
@@ -43,6 +44,20 @@ impl TestProtocolManager {
         Ok(())
     }
 
+    pub async fn fake_connected(&mut self) -> anyhow::Result<()> {
+        self.response_sender
+            .send(ProtocolResponse::Connected)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn fake_failure(&mut self, reason: &str) -> anyhow::Result<()> {
+        self.response_sender
+            .send(ProtocolResponse::Failure(reason.to_string()))
+            .await?;
+        Ok(())
+    }
+
     pub async fn wait_until_n_messages_sent(&self, n: usize) {
         tokio::time::timeout(Duration::from_secs(2), async {
             while self.inner.lock().sent_messages.len() < n {
@@ -67,6 +82,7 @@ impl SyncProtocol for TestProtocolManager {
         let mut test_protocol = TestProtocolManager {
             inner: Arc::new(Mutex::new(TestProtocolInner {
                 closed: false,
+                connection_count: 0,
                 sent_messages: vec![],
             })),
             response_sender,
@@ -83,6 +99,7 @@ impl SyncProtocol for TestProtocolManager {
                 max_observed_timestamp: None,
             })
             .await?;
+        test_protocol.fake_connected().await?;
 
         Ok(test_protocol)
     }
@@ -96,7 +113,22 @@ impl SyncProtocol for TestProtocolManager {
         Ok(())
     }
 
+    /// Simulates a reconnect by sending a fresh `Connect` message. Tests are
+    /// responsible for acknowledging the new connection with
+    /// [`TestProtocolManager::fake_connected`].
     async fn reconnect(&mut self, request: ReconnectRequest) {
-        panic!("Test reconnected {request:?}");
+        let connection_count = {
+            let mut inner = self.inner.lock();
+            inner.connection_count += 1;
+            inner.connection_count
+        };
+        self.send(ClientMessage::Connect {
+            session_id: SessionId::new(Uuid::nil()),
+            connection_count,
+            last_close_reason: request.reason,
+            max_observed_timestamp: request.max_observed_timestamp,
+        })
+        .await
+        .expect("Test protocol closed");
     }
 }
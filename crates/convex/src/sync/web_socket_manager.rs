@@ -165,7 +165,10 @@ impl WebSocketWorker {
             // The worker will send a Reconnect message and the new query set all together.
             // Drain the input request queue until we get that reconnect message - which
             // will be followed by the refreshed query set.
-            let _ = worker.on_response.send(ProtocolResponse::Failure).await;
+            let _ = worker
+                .on_response
+                .send(ProtocolResponse::Failure(last_close_reason.clone()))
+                .await;
             tracing::debug!("Waiting for base client to acknowledge reconnect");
             loop {
                 let request = worker.internal_receiver.next().await;
@@ -205,6 +208,7 @@ impl WebSocketWorker {
         )
         .await?;
         tracing::debug!("completed websocket {verb} to {}", self.ws_url);
+        let _ = self.on_response.send(ProtocolResponse::Connected).await;
 
         loop {
             select_biased! {
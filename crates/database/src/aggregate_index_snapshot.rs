@@ -0,0 +1,468 @@
+//! Checkpoints of the in-memory deltas of the aggregate indexes, and the
+//! background flush that writes them to `_aggregate_index_counts`. Without a
+//! checkpoint every startup would have to scan each table with an aggregate
+//! index to recompute its deltas.
+use std::{
+    collections::BTreeSet,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::index::{
+        database_index::DatabaseIndexState,
+        IndexConfig,
+    },
+    document::ResolvedDocument,
+    interval::{
+        End,
+        Interval,
+        Start,
+    },
+    knobs::AGGREGATE_INDEX_FLUSH_CHUNK_SIZE,
+    persistence::{
+        Persistence,
+        PersistenceGlobalKey,
+        PersistenceReader,
+        PersistenceSnapshot,
+        RepeatablePersistence,
+        RetentionValidator,
+        TimestampRange,
+    },
+    query::Order,
+    runtime::Runtime,
+    types::{
+        IndexId,
+        RepeatableTimestamp,
+        TabletIndexName,
+        Timestamp,
+    },
+    value::JsonInteger,
+};
+use futures::TryStreamExt;
+use indexing::{
+    aggregate_index::{
+        add_delta,
+        AggregateCount,
+        AggregateDeltas,
+        AggregateIndexes,
+    },
+    index_registry::IndexRegistry,
+};
+use serde::Deserialize;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use value::{
+    base64,
+    TableMapping,
+    TableNamespace,
+};
+
+use crate::{
+    bootstrap_model::{
+        aggregate_index_counts::{
+            nodes_of_index_interval,
+            AggregateNodeContents,
+            AGGREGATE_INDEX_COUNTS_BY_INDEX_ID,
+        },
+        defaults::BootstrapTableIds,
+    },
+    persistence_helpers::stream_transactions,
+    AggregateIndexCountsModel,
+    AggregateIndexNode,
+    Database,
+    AGGREGATE_INDEX_COUNTS_TABLE,
+};
+
+#[derive(Clone, Debug)]
+pub struct AggregateIndexSnapshot {
+    pub indexes: AggregateIndexes,
+    pub ts: Timestamp,
+}
+
+impl AggregateIndexSnapshot {
+    pub async fn load(reader: &dyn PersistenceReader) -> anyhow::Result<Option<Self>> {
+        let Some(value) = reader
+            .get_persistence_global(PersistenceGlobalKey::AggregateIndexes)
+            .await?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(Self::try_from(value)?))
+    }
+}
+
+impl From<&AggregateIndexSnapshot> for JsonValue {
+    fn from(snapshot: &AggregateIndexSnapshot) -> Self {
+        let indexes: serde_json::Map<String, JsonValue> = snapshot
+            .indexes
+            .iter()
+            .map(|(index_id, deltas)| {
+                let deltas: Vec<JsonValue> = deltas
+                    .iter()
+                    .map(|(key, delta)| {
+                        // Encode the bits so NaN and infinities round trip.
+                        let value = delta
+                            .value
+                            .map(|value| JsonInteger::encode(value.to_bits() as i64));
+                        json!([
+                            base64::encode_urlsafe(key),
+                            value,
+                            JsonInteger::encode(delta.count),
+                        ])
+                    })
+                    .collect();
+                (index_id.to_string(), JsonValue::Array(deltas))
+            })
+            .collect();
+        json!({
+            "indexes": indexes,
+            "ts": JsonInteger::encode(snapshot.ts.into()),
+        })
+    }
+}
+
+impl TryFrom<JsonValue> for AggregateIndexSnapshot {
+    type Error = anyhow::Error;
+
+    fn try_from(json_value: JsonValue) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct AggregateIndexSnapshotJson {
+            indexes: serde_json::Map<String, JsonValue>,
+            ts: String,
+        }
+        let snapshot: AggregateIndexSnapshotJson = serde_json::from_value(json_value)?;
+        let mut indexes = AggregateIndexes::default();
+        for (index_id, deltas_json) in snapshot.indexes {
+            let index_id: IndexId = index_id.parse()?;
+            let deltas_json: Vec<(String, Option<String>, String)> =
+                serde_json::from_value(deltas_json)?;
+            let mut deltas = AggregateDeltas::new();
+            for (key, value, count) in deltas_json {
+                let value = value
+                    .map(|value| anyhow::Ok(f64::from_bits(JsonInteger::decode(value)? as u64)))
+                    .transpose()?;
+                deltas.insert(
+                    base64::decode_urlsafe(&key)?,
+                    AggregateCount {
+                        value,
+                        count: JsonInteger::decode(count)?,
+                    },
+                );
+            }
+            indexes.insert_index(index_id, deltas);
+        }
+        Ok(Self {
+            indexes,
+            ts: JsonInteger::decode(snapshot.ts)?.try_into()?,
+        })
+    }
+}
+
+pub async fn write_snapshot(
+    persistence: &dyn Persistence,
+    snapshot: &AggregateIndexSnapshot,
+) -> anyhow::Result<()> {
+    persistence
+        .write_persistence_global(
+            PersistenceGlobalKey::AggregateIndexes,
+            JsonValue::from(snapshot),
+        )
+        .await
+}
+
+/// Applies a revision to `indexes`. Besides the changes to the aggregate
+/// keys of documents, this takes writes to `_aggregate_index_counts` out of
+/// the deltas, so persisted counts plus deltas stay up to date no matter
+/// which transaction flushed them.
+pub fn update_aggregate_indexes(
+    indexes: &mut AggregateIndexes,
+    // NB: We assume that `index_registry` has already received this update.
+    index_registry: &IndexRegistry,
+    table_mapping: &TableMapping,
+    deletion: Option<&ResolvedDocument>,
+    insertion: Option<&ResolvedDocument>,
+) -> anyhow::Result<()> {
+    indexes.update(index_registry, deletion, insertion)?;
+    for (document, sign) in [(deletion, -1), (insertion, 1)] {
+        let Some(document) = document else {
+            continue;
+        };
+        // Internal nodes only summarize their children, so the leaves hold
+        // every persisted count.
+        if let Some(AggregateIndexNode {
+            index_id,
+            contents: AggregateNodeContents::Leaf(counts),
+        }) = AggregateIndexNode::from_document(table_mapping, document)?
+        {
+            for count in counts {
+                indexes.flushed(index_id, count.key, count.value, sign * count.count);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Applies every commit in `(from_ts, target_ts]` to `indexes`, returning the
+/// number of revisions read.
+pub async fn catch_up(
+    indexes: &mut AggregateIndexes,
+    persistence: &RepeatablePersistence,
+    bootstrap_tables: BootstrapTableIds,
+    index_registry: &IndexRegistry,
+    table_mapping: &TableMapping,
+    from_ts: Timestamp,
+    target_ts: RepeatableTimestamp,
+) -> anyhow::Result<usize> {
+    if from_ts >= *target_ts {
+        return Ok(0);
+    }
+    let range = TimestampRange::new(from_ts.succ()?..=*target_ts)?;
+    let transaction_stream = stream_transactions(bootstrap_tables, persistence, range, Order::Asc);
+    futures::pin_mut!(transaction_stream);
+    let mut num_revisions = 0;
+    while let Some(transaction) = transaction_stream.try_next().await? {
+        for revision_pair in transaction.revision_pairs {
+            update_aggregate_indexes(
+                indexes,
+                index_registry,
+                table_mapping,
+                revision_pair.prev_document(),
+                revision_pair.document(),
+            )
+            .with_context(|| format!("Failed to apply revision at {}", transaction.ts))?;
+            num_revisions += 1;
+        }
+    }
+    Ok(num_revisions)
+}
+
+/// Computes the aggregate index deltas at `persistence_snapshot` by catching
+/// up the last checkpoint on the log written since. Indexes missing from the
+/// checkpoint, or all of them if it can't be used, are computed from scratch.
+///
+/// Returns the aggregate indexes and the number of log entries processed.
+pub async fn bootstrap(
+    persistence: Arc<dyn PersistenceReader>,
+    retention_validator: Arc<dyn RetentionValidator>,
+    bootstrap_tables: BootstrapTableIds,
+    index_registry: &IndexRegistry,
+    table_mapping: &TableMapping,
+    persistence_snapshot: &PersistenceSnapshot,
+) -> anyhow::Result<(AggregateIndexes, usize)> {
+    let target_ts = persistence_snapshot.timestamp();
+    let mut indexes = AggregateIndexes::default();
+    let mut num_revisions = 0;
+    if let Some(stored) = AggregateIndexSnapshot::load(persistence.as_ref()).await?
+        && stored.ts <= *target_ts
+    {
+        let repeatable_persistence =
+            RepeatablePersistence::new(persistence.clone(), target_ts, retention_validator);
+        let mut caught_up = stored.indexes;
+        match catch_up(
+            &mut caught_up,
+            &repeatable_persistence,
+            bootstrap_tables,
+            index_registry,
+            table_mapping,
+            stored.ts,
+            target_ts,
+        )
+        .await
+        {
+            Ok(n) => {
+                indexes = caught_up;
+                num_revisions = n;
+            },
+            Err(e) => tracing::warn!(
+                "Couldn't catch up aggregate index checkpoint at {}, recomputing: {e:?}",
+                stored.ts
+            ),
+        }
+    }
+    // Drop indexes that were deleted since the checkpoint.
+    let live_indexes: Vec<_> = index_registry
+        .all_aggregate_indexes()
+        .into_iter()
+        .map(|index| index.id().internal_id())
+        .collect();
+    indexes.retain(|index_id| live_indexes.contains(index_id));
+    load_missing(
+        &mut indexes,
+        index_registry,
+        table_mapping,
+        persistence_snapshot,
+    )
+    .await?;
+    Ok((indexes, num_revisions))
+}
+
+/// Computes the deltas of every backfilled aggregate index that isn't loaded
+/// yet as the difference between scanning its table at `snapshot` and its
+/// persisted counts. This is only a fallback for when there's no usable
+/// checkpoint.
+async fn load_missing(
+    indexes: &mut AggregateIndexes,
+    index_registry: &IndexRegistry,
+    table_mapping: &TableMapping,
+    snapshot: &PersistenceSnapshot,
+) -> anyhow::Result<()> {
+    for index in index_registry.all_aggregate_indexes() {
+        if let IndexConfig::Aggregate {
+            on_disk_state: DatabaseIndexState::Backfilling(_),
+            ..
+        } = index.config
+        {
+            continue;
+        }
+        let index_id = index.id().internal_id();
+        if indexes.contains(&index_id) {
+            continue;
+        }
+        let tablet_id = *index.name.table();
+        let by_id = index_registry.must_get_by_id(tablet_id)?.id();
+        let mut deltas = AggregateDeltas::new();
+        let mut num_documents = 0;
+        let mut stream =
+            snapshot.index_scan(by_id, tablet_id, &Interval::all(), Order::Asc, usize::MAX);
+        while let Some((_, _, document)) = stream.try_next().await? {
+            num_documents += 1;
+            for (id, key, value) in AggregateIndexes::document_keys(index_registry, &document) {
+                if id == index_id {
+                    add_delta(&mut deltas, key, value, 1);
+                }
+            }
+        }
+        if let Some(counts_tablet_id) = table_mapping
+            .namespace(TableNamespace::Global)
+            .id_if_exists(&AGGREGATE_INDEX_COUNTS_TABLE)
+            && let Some(counts_index) = index_registry.get_enabled(&TabletIndexName::new(
+                counts_tablet_id,
+                AGGREGATE_INDEX_COUNTS_BY_INDEX_ID.descriptor().clone(),
+            )?)
+        {
+            let mut stream = snapshot.index_scan(
+                counts_index.id(),
+                counts_tablet_id,
+                &nodes_of_index_interval(index_id)?,
+                Order::Asc,
+                usize::MAX,
+            );
+            while let Some((_, _, document)) = stream.try_next().await? {
+                let node = AggregateIndexNode::try_from(document.into_value().0)?;
+                if let AggregateNodeContents::Leaf(counts) = node.contents {
+                    for count in counts {
+                        add_delta(&mut deltas, count.key, count.value, -count.count);
+                    }
+                }
+            }
+        }
+        indexes.insert_index(index_id, deltas);
+        tracing::info!(
+            "Loaded aggregate index {} from {num_documents} documents",
+            index.name
+        );
+    }
+    Ok(())
+}
+
+/// Writes the in-memory deltas of every loaded aggregate index to
+/// `_aggregate_index_counts`, in transactions of up to
+/// `AGGREGATE_INDEX_FLUSH_CHUNK_SIZE` keys, and deletes the nodes of dropped
+/// indexes. Returns the number of counts written.
+pub async fn flush<RT: Runtime>(database: &Database<RT>) -> anyhow::Result<usize> {
+    let chunk_size = *AGGREGATE_INDEX_FLUSH_CHUNK_SIZE;
+    let mut num_flushed = 0;
+    let index_ids: Vec<IndexId> = database
+        .aggregate_index_snapshot()
+        .indexes
+        .iter()
+        .map(|(index_id, _)| *index_id)
+        .collect();
+    for index_id in index_ids {
+        let mut start = vec![];
+        loop {
+            let mut tx = database.begin_system().await?;
+            if !AggregateIndexCountsModel::new(&mut tx).is_ready() {
+                return Ok(num_flushed);
+            }
+            // Backfills compute deltas assuming nothing is persisted for the
+            // index yet, so leave backfilling indexes alone.
+            let is_backfilled = tx
+                .index
+                .index_registry()
+                .all_aggregate_indexes()
+                .into_iter()
+                .any(|index| {
+                    index.id().internal_id() == index_id
+                        && !matches!(
+                            index.config,
+                            IndexConfig::Aggregate {
+                                on_disk_state: DatabaseIndexState::Backfilling(_),
+                                ..
+                            }
+                        )
+                });
+            if !is_backfilled {
+                break;
+            }
+            // Re-read the deltas at the transaction's snapshot so the counts
+            // written are exactly what the committer takes out of them.
+            let interval = Interval {
+                start: Start::Included(start.clone().into()),
+                end: End::Unbounded,
+            };
+            let Some(deltas) = tx
+                .count_snapshot
+                .aggregate_deltas(index_id, &interval)
+                .await?
+            else {
+                break;
+            };
+            let chunk: Vec<_> = deltas.into_iter().take(chunk_size).collect();
+            let Some(last) = chunk.last() else {
+                break;
+            };
+            // The smallest key after `last`.
+            start = last.key.clone();
+            start.push(0);
+            let num_in_chunk = chunk.len();
+            let mut model = AggregateIndexCountsModel::new(&mut tx);
+            for delta in chunk {
+                model.flush(index_id, delta).await?;
+            }
+            database
+                .commit_with_write_source(tx, "aggregate_index_flush")
+                .await?;
+            num_flushed += num_in_chunk;
+            if num_in_chunk < chunk_size {
+                break;
+            }
+        }
+    }
+    loop {
+        let mut tx = database.begin_system().await?;
+        let live_indexes: BTreeSet<_> = tx
+            .index
+            .index_registry()
+            .all_aggregate_indexes()
+            .into_iter()
+            .map(|index| index.id().internal_id())
+            .collect();
+        let num_deleted = AggregateIndexCountsModel::new(&mut tx)
+            .delete_dropped(&live_indexes, chunk_size)
+            .await?;
+        if num_deleted == 0 {
+            break;
+        }
+        database
+            .commit_with_write_source(tx, "aggregate_index_flush")
+            .await?;
+        if num_deleted < chunk_size {
+            break;
+        }
+    }
+    Ok(num_flushed)
+}
@@ -0,0 +1,933 @@
+//! Persisted counts of the documents with each key of an aggregate index. See
+//! [`indexing::aggregate_index::AggregateIndexes`] for how they're combined
+//! with the in-memory deltas of the commits since they were written.
+//!
+//! The counts of each index are kept in a B-tree, whose root document has the
+//! index's id. Internal nodes hold the aggregate of each child's subtree, so
+//! aggregating a range only reads the nodes along its two ends.
+use std::{
+    cmp::Ordering,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    str::FromStr,
+    sync::LazyLock,
+};
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::index::database_index::IndexedFields,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    index::IndexKey,
+    interval::{
+        End,
+        Interval,
+        Start,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        IndexId,
+        IndexName,
+        TabletIndexName,
+    },
+};
+use indexing::{
+    aggregate_index::{
+        cmp_values,
+        AggregateCount,
+        AggregateDelta,
+        RangeAggregate,
+    },
+    backend_in_memory_indexes::RangeRequest,
+};
+use value::{
+    obj,
+    ConvexObject,
+    ConvexValue,
+    FieldPath,
+    InternalId,
+    ResolvedDocumentId,
+    TableMapping,
+    TableName,
+    TableNamespace,
+    TabletIdAndTableNumber,
+};
+
+use crate::{
+    defaults::{
+        system_index,
+        SystemIndex,
+        SystemTable,
+    },
+    query::IndexRangeResponse,
+    reads::TransactionReadSet,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+
+pub static AGGREGATE_INDEX_COUNTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_aggregate_index_counts"
+        .parse()
+        .expect("_aggregate_index_counts is an invalid table name")
+});
+
+static INDEX_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "indexId".parse().expect("Invalid built-in field"));
+
+pub static AGGREGATE_INDEX_COUNTS_BY_INDEX_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&AGGREGATE_INDEX_COUNTS_TABLE, "by_index_id"));
+
+static BY_INDEX_ID_FIELDS: LazyLock<IndexedFields> = LazyLock::new(|| {
+    vec![INDEX_ID_FIELD.clone()]
+        .try_into()
+        .expect("Invalid built-in index fields")
+});
+
+/// Most counts a leaf, or children an internal node, holds before it's split
+/// in two.
+const MAX_NODE_SIZE: usize = 32;
+
+/// Fewest counts or children a node holds before it's merged into a
+/// neighbour.
+const MIN_NODE_SIZE: usize = MAX_NODE_SIZE / 4;
+
+pub struct AggregateIndexCountsTable;
+impl SystemTable for AggregateIndexCountsTable {
+    fn table_name(&self) -> &'static TableName {
+        &AGGREGATE_INDEX_COUNTS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: AGGREGATE_INDEX_COUNTS_BY_INDEX_ID.clone(),
+            fields: BY_INDEX_ID_FIELDS.clone(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<AggregateIndexNode>::try_from(document).map(|_| ())
+    }
+}
+
+/// The number of documents with `key` in an aggregate index as of the last
+/// time it was flushed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AggregateIndexCount {
+    pub key: Vec<u8>,
+    pub value: Option<f64>,
+    pub count: i64,
+}
+
+/// A child of an internal node, holding the keys from `start` up to the next
+/// child's `start`. The first child also holds the keys of its parent below
+/// its `start`.
+#[derive(Clone, Debug)]
+pub struct AggregateNodeChild {
+    pub id: InternalId,
+    pub start: Vec<u8>,
+    pub aggregate: RangeAggregate,
+}
+
+#[derive(Clone, Debug)]
+pub enum AggregateNodeContents {
+    /// Counts of distinct keys, in key order.
+    Leaf(Vec<AggregateIndexCount>),
+    /// Children in key order.
+    Internal(Vec<AggregateNodeChild>),
+}
+
+/// A node of the B-tree holding the counts of the aggregate index `index_id`.
+#[derive(Clone, Debug)]
+pub struct AggregateIndexNode {
+    pub index_id: IndexId,
+    pub contents: AggregateNodeContents,
+}
+
+impl AggregateIndexNode {
+    /// Parses `document` if it's in `_aggregate_index_counts`.
+    pub fn from_document(
+        table_mapping: &TableMapping,
+        document: &ResolvedDocument,
+    ) -> anyhow::Result<Option<Self>> {
+        let tablet_id = document.id().tablet_id;
+        if !table_mapping.is_system_tablet(tablet_id)
+            || table_mapping.tablet_name(tablet_id)? != *AGGREGATE_INDEX_COUNTS_TABLE
+        {
+            return Ok(None);
+        }
+        Ok(Some(document.value().0.clone().try_into()?))
+    }
+}
+
+impl AggregateNodeContents {
+    fn len(&self) -> usize {
+        match self {
+            AggregateNodeContents::Leaf(counts) => counts.len(),
+            AggregateNodeContents::Internal(children) => children.len(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The aggregate of every count in the node's subtree.
+    fn aggregate(&self) -> anyhow::Result<RangeAggregate> {
+        let mut aggregate = RangeAggregate::default();
+        match self {
+            AggregateNodeContents::Leaf(counts) => {
+                for count in counts {
+                    aggregate.add(count.value, u64::try_from(count.count)?);
+                }
+            },
+            AggregateNodeContents::Internal(children) => {
+                for child in children {
+                    aggregate.merge(&child.aggregate);
+                }
+            },
+        }
+        Ok(aggregate)
+    }
+
+    /// Moves the second half of the node into a new node, returning it with
+    /// the smallest key it holds.
+    fn split_off(&mut self) -> (Vec<u8>, Self) {
+        match self {
+            AggregateNodeContents::Leaf(counts) => {
+                let right = counts.split_off(counts.len() / 2);
+                (right[0].key.clone(), AggregateNodeContents::Leaf(right))
+            },
+            AggregateNodeContents::Internal(children) => {
+                let right = children.split_off(children.len() / 2);
+                (
+                    right[0].start.clone(),
+                    AggregateNodeContents::Internal(right),
+                )
+            },
+        }
+    }
+
+    /// Moves the contents of `right`, the node after this one holding the
+    /// keys from `right_start`, to the end of this node.
+    fn append(&mut self, right: Self, right_start: Vec<u8>) -> anyhow::Result<()> {
+        match (self, right) {
+            (AggregateNodeContents::Leaf(left), AggregateNodeContents::Leaf(mut right)) => {
+                left.append(&mut right);
+            },
+            (AggregateNodeContents::Internal(left), AggregateNodeContents::Internal(mut right)) => {
+                // The first child of `right` held the keys from its parent's
+                // start, which is now `right_start`.
+                if let Some(first) = right.first_mut() {
+                    first.start = right_start;
+                }
+                left.append(&mut right);
+            },
+            _ => anyhow::bail!("Aggregate index nodes at the same depth must be the same kind"),
+        }
+        Ok(())
+    }
+
+    /// Adds the parts of the node's subtree in `interval`, given the keys
+    /// `range` the node holds, to `pieces`, and the children that are only
+    /// partly in `interval` to `partial`.
+    fn split_interval(
+        self,
+        range: &Interval,
+        interval: &Interval,
+        pieces: &mut Vec<Piece>,
+        partial: &mut Vec<(InternalId, Interval)>,
+    ) {
+        match self {
+            AggregateNodeContents::Leaf(counts) => pieces.extend(
+                counts
+                    .into_iter()
+                    .filter(|count| interval.contains(&count.key))
+                    .map(Piece::Count),
+            ),
+            AggregateNodeContents::Internal(children) => {
+                let ranges: Vec<_> = (0..children.len())
+                    .map(|i| child_range(&children, i, range))
+                    .collect();
+                for (child, child_range) in children.into_iter().zip(ranges) {
+                    if interval.is_disjoint(&child_range) {
+                        continue;
+                    }
+                    if interval.is_superset(&child_range) {
+                        pieces.push(Piece::Subtree {
+                            id: child.id,
+                            range: child_range,
+                            aggregate: child.aggregate,
+                        });
+                    } else {
+                        partial.push((child.id, child_range));
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// The keys held by the `i`th child of a node holding `range`.
+fn child_range(children: &[AggregateNodeChild], i: usize, range: &Interval) -> Interval {
+    let start = if i == 0 {
+        range.start.clone()
+    } else {
+        Start::Included(children[i].start.clone().into())
+    };
+    let end = match children.get(i + 1) {
+        Some(next) => End::Excluded(next.start.clone().into()),
+        None => range.end.clone(),
+    };
+    Interval { start, end }
+}
+
+/// Part of the counts in a range: either a subtree that's entirely in it or
+/// the count of a single key.
+#[derive(Clone, Debug)]
+enum Piece {
+    Subtree {
+        id: InternalId,
+        range: Interval,
+        aggregate: RangeAggregate,
+    },
+    Count(AggregateIndexCount),
+}
+
+impl Piece {
+    /// The minimum value in the piece for `Ordering::Less`, or the maximum
+    /// for `Ordering::Greater`.
+    fn bound(&self, order: Ordering) -> Option<f64> {
+        match self {
+            Piece::Subtree { aggregate, .. } if order == Ordering::Less => aggregate.min(),
+            Piece::Subtree { aggregate, .. } => aggregate.max(),
+            Piece::Count(count) => count.value,
+        }
+    }
+}
+
+impl TryFrom<AggregateIndexNode> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(value: AggregateIndexNode) -> Result<Self, Self::Error> {
+        let index_id = ConvexValue::String(value.index_id.to_string().try_into()?);
+        match value.contents {
+            AggregateNodeContents::Leaf(counts) => {
+                let counts = counts
+                    .into_iter()
+                    .map(|count| {
+                        ConvexValue::try_from(obj!(
+                            "key" => ConvexValue::try_from(count.key)?,
+                            "value" => count.value.map_or(ConvexValue::Null, ConvexValue::from),
+                            "count" => count.count,
+                        )?)
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                obj!("indexId" => index_id, "counts" => counts)
+            },
+            AggregateNodeContents::Internal(children) => {
+                let children = children
+                    .into_iter()
+                    .map(|child| {
+                        ConvexValue::try_from(obj!(
+                            "id" => child.id.to_string(),
+                            "start" => ConvexValue::try_from(child.start)?,
+                            "aggregate" => ConvexObject::try_from(child.aggregate)?,
+                        )?)
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                obj!("indexId" => index_id, "children" => children)
+            },
+        }
+    }
+}
+
+impl TryFrom<ConvexObject> for AggregateIndexNode {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ConvexObject) -> Result<Self, Self::Error> {
+        let mut fields: BTreeMap<_, _> = value.into();
+        let index_id = match fields.remove("indexId") {
+            Some(ConvexValue::String(index_id)) => {
+                IndexId::from_str(index_id.to_string().as_str())?
+            },
+            _ => anyhow::bail!("Missing or invalid `indexId` field for AggregateIndexNode"),
+        };
+        let contents = match (fields.remove("counts"), fields.remove("children")) {
+            (Some(ConvexValue::Array(counts)), None) => AggregateNodeContents::Leaf(
+                counts
+                    .into_iter()
+                    .map(parse_count)
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            (None, Some(ConvexValue::Array(children))) => AggregateNodeContents::Internal(
+                children
+                    .into_iter()
+                    .map(parse_child)
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            _ => anyhow::bail!(
+                "AggregateIndexNode must have exactly one of `counts` and `children` arrays"
+            ),
+        };
+        Ok(Self { index_id, contents })
+    }
+}
+
+fn parse_count(value: ConvexValue) -> anyhow::Result<AggregateIndexCount> {
+    let ConvexValue::Object(object) = value else {
+        anyhow::bail!("Invalid count for AggregateIndexNode");
+    };
+    let mut fields: BTreeMap<_, _> = object.into();
+    let key = match fields.remove("key") {
+        Some(ConvexValue::Bytes(key)) => key.into(),
+        _ => anyhow::bail!("Missing or invalid `key` field for AggregateIndexCount"),
+    };
+    let value = match fields.remove("value") {
+        Some(ConvexValue::Float64(value)) => Some(value),
+        Some(ConvexValue::Null) => None,
+        _ => anyhow::bail!("Missing or invalid `value` field for AggregateIndexCount"),
+    };
+    let count = match fields.remove("count") {
+        Some(ConvexValue::Int64(count)) => count,
+        _ => anyhow::bail!("Missing or invalid `count` field for AggregateIndexCount"),
+    };
+    Ok(AggregateIndexCount { key, value, count })
+}
+
+fn parse_child(value: ConvexValue) -> anyhow::Result<AggregateNodeChild> {
+    let ConvexValue::Object(object) = value else {
+        anyhow::bail!("Invalid child for AggregateIndexNode");
+    };
+    let mut fields: BTreeMap<_, _> = object.into();
+    let id = match fields.remove("id") {
+        Some(ConvexValue::String(id)) => InternalId::from_str(id.to_string().as_str())?,
+        _ => anyhow::bail!("Missing or invalid `id` field for AggregateNodeChild"),
+    };
+    let start = match fields.remove("start") {
+        Some(ConvexValue::Bytes(start)) => start.into(),
+        _ => anyhow::bail!("Missing or invalid `start` field for AggregateNodeChild"),
+    };
+    let aggregate = match fields.remove("aggregate") {
+        Some(ConvexValue::Object(aggregate)) => aggregate.try_into()?,
+        _ => anyhow::bail!("Missing or invalid `aggregate` field for AggregateNodeChild"),
+    };
+    Ok(AggregateNodeChild {
+        id,
+        start,
+        aggregate,
+    })
+}
+
+pub struct AggregateIndexCountsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> AggregateIndexCountsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Returns the aggregate of the documents of `index_id` in `interval`,
+    /// given the `deltas` of the keys in `interval` that haven't been flushed
+    /// yet.
+    ///
+    /// The nodes read aren't added to the read set: flushing only moves
+    /// counts from the deltas into the nodes, so it doesn't change the
+    /// result. The caller records the interval of the index instead, which
+    /// the writes that do change it touch.
+    pub async fn aggregate(
+        &mut self,
+        index_id: IndexId,
+        interval: &Interval,
+        deltas: &BTreeMap<Vec<u8>, AggregateCount>,
+    ) -> anyhow::Result<RangeAggregate> {
+        let pieces = self.pieces(index_id, interval).await?;
+        let mut aggregate = RangeAggregate::default();
+        for piece in &pieces {
+            match piece {
+                Piece::Subtree {
+                    aggregate: subtree, ..
+                } => aggregate.merge(subtree),
+                Piece::Count(count) => aggregate.add(count.value, u64::try_from(count.count)?),
+            }
+        }
+        for delta in deltas.values() {
+            match u64::try_from(delta.count) {
+                Ok(count) => aggregate.add(delta.value, count),
+                Err(_) => aggregate.remove(delta.value, delta.count.unsigned_abs())?,
+            }
+        }
+        // The deltas may have removed the persisted minimum or maximum.
+        let min = self.bound(pieces.clone(), deltas, Ordering::Less).await?;
+        let max = self.bound(pieces, deltas, Ordering::Greater).await?;
+        aggregate.set_bounds(min, max);
+        Ok(aggregate)
+    }
+
+    /// Splits the counts of `index_id` in `interval` into the subtrees that
+    /// are entirely in it and the counts of the leaves that are only partly
+    /// in it, reading the nodes along the two ends of `interval`.
+    async fn pieces(
+        &mut self,
+        index_id: IndexId,
+        interval: &Interval,
+    ) -> anyhow::Result<Vec<Piece>> {
+        let mut pieces = vec![];
+        let mut partial = vec![(index_id, Interval::all())];
+        while let Some((id, range)) = partial.pop() {
+            let Some(node) = self.node_untracked(id).await? else {
+                // The root doesn't exist until the first count is flushed.
+                anyhow::ensure!(id == index_id, "Aggregate index node {id} is missing");
+                continue;
+            };
+            node.contents
+                .split_interval(&range, interval, &mut pieces, &mut partial);
+        }
+        Ok(pieces)
+    }
+
+    /// Returns the minimum value for `Ordering::Less`, or the maximum for
+    /// `Ordering::Greater`, of `pieces` combined with `deltas`. Only the
+    /// subtrees that `deltas` removes documents from are read, and only until
+    /// the most extreme value left is found.
+    async fn bound(
+        &mut self,
+        mut pieces: Vec<Piece>,
+        deltas: &BTreeMap<Vec<u8>, AggregateCount>,
+        order: Ordering,
+    ) -> anyhow::Result<Option<f64>> {
+        let is_better = |value: f64, than: Option<f64>| {
+            than.map_or(true, |than| cmp_values(value, than) == order)
+        };
+        let mut bound = None;
+        for delta in deltas.values() {
+            if delta.count > 0
+                && let Some(value) = delta.value
+                && is_better(value, bound)
+            {
+                bound = Some(value);
+            }
+        }
+        loop {
+            let mut best: Option<(usize, f64)> = None;
+            for (i, piece) in pieces.iter().enumerate() {
+                if let Some(value) = piece.bound(order)
+                    && is_better(value, best.map(|(_, best)| best))
+                {
+                    best = Some((i, value));
+                }
+            }
+            let Some((i, value)) = best else {
+                break;
+            };
+            if !is_better(value, bound) {
+                break;
+            }
+            match pieces.swap_remove(i) {
+                Piece::Count(count) => {
+                    let delta = deltas.get(&count.key).map_or(0, |delta| delta.count);
+                    if count.count + delta > 0 {
+                        bound = Some(value);
+                        break;
+                    }
+                },
+                Piece::Subtree { id, range, .. } => {
+                    if !deltas
+                        .range::<[u8], _>(&range)
+                        .any(|(_, delta)| delta.count < 0)
+                    {
+                        bound = Some(value);
+                        break;
+                    }
+                    let node = self
+                        .node_untracked(id)
+                        .await?
+                        .with_context(|| format!("Aggregate index node {id} is missing"))?;
+                    node.contents
+                        .split_interval(&range, &range, &mut pieces, &mut vec![]);
+                },
+            }
+        }
+        Ok(bound)
+    }
+
+    /// Adds `delta` to the persisted count of its key in `index_id`,
+    /// splitting the nodes that grow too large and merging the ones that
+    /// shrink too small.
+    pub async fn flush(&mut self, index_id: IndexId, delta: AggregateDelta) -> anyhow::Result<()> {
+        // Walk down to the leaf holding the key, remembering the child taken
+        // at each internal node.
+        let mut path = vec![];
+        let mut id = index_id;
+        let mut root_exists = true;
+        let mut counts = loop {
+            let Some(node) = self.node(id).await? else {
+                anyhow::ensure!(id == index_id, "Aggregate index node {id} is missing");
+                root_exists = false;
+                break vec![];
+            };
+            match node.contents {
+                AggregateNodeContents::Leaf(counts) => break counts,
+                AggregateNodeContents::Internal(children) => {
+                    let i = children
+                        .partition_point(|child| child.start <= delta.key)
+                        .saturating_sub(1);
+                    let child_id = children[i].id;
+                    path.push((children, i));
+                    id = child_id;
+                },
+            }
+        };
+        match counts.binary_search_by(|count| count.key.cmp(&delta.key)) {
+            Ok(i) => {
+                counts[i].count += delta.count;
+                if counts[i].count == 0 {
+                    counts.remove(i);
+                }
+            },
+            Err(i) => counts.insert(
+                i,
+                AggregateIndexCount {
+                    key: delta.key,
+                    value: delta.value,
+                    count: delta.count,
+                },
+            ),
+        }
+        anyhow::ensure!(
+            counts.iter().all(|count| count.count > 0),
+            "Flushing aggregate index {index_id} would make a count negative"
+        );
+
+        // Write the nodes back up to the root, updating the aggregates of
+        // their children along the way.
+        let mut contents = AggregateNodeContents::Leaf(counts);
+        while let Some((mut children, i)) = path.pop() {
+            self.write_child(index_id, &mut children, i, contents)
+                .await?;
+            contents = AggregateNodeContents::Internal(children);
+        }
+        self.write_root(index_id, contents, root_exists).await
+    }
+
+    /// Writes `contents` as the `i`th of `children`, merging it into a
+    /// neighbour once it's small enough so deletes don't leave the tree
+    /// sparse, and splitting it once it's too large.
+    async fn write_child(
+        &mut self,
+        index_id: IndexId,
+        children: &mut Vec<AggregateNodeChild>,
+        mut i: usize,
+        mut contents: AggregateNodeContents,
+    ) -> anyhow::Result<()> {
+        if contents.len() < MIN_NODE_SIZE && children.len() > 1 {
+            let j = if i > 0 { i - 1 } else { i + 1 };
+            let sibling_id = children[j].id;
+            let sibling = self
+                .node(sibling_id)
+                .await?
+                .with_context(|| format!("Aggregate index node {sibling_id} is missing"))?;
+            // Keep the left node and delete the right one.
+            let (left, right) = (i.min(j), i.max(j));
+            let right_start = children[right].start.clone();
+            contents = if j < i {
+                let mut merged = sibling.contents;
+                merged.append(contents, right_start)?;
+                merged
+            } else {
+                contents.append(sibling.contents, right_start)?;
+                contents
+            };
+            self.delete_node(children[right].id).await?;
+            children.remove(right);
+            i = left;
+        }
+        let id = children[i].id;
+        if contents.is_empty() {
+            self.delete_node(id).await?;
+            children.remove(i);
+            return Ok(());
+        }
+        let split = (contents.len() > MAX_NODE_SIZE).then(|| contents.split_off());
+        children[i].aggregate = contents.aggregate()?;
+        self.replace_node(index_id, id, contents).await?;
+        if let Some((start, right)) = split {
+            let aggregate = right.aggregate()?;
+            let right_id = self.insert_node(index_id, right).await?;
+            children.insert(
+                i + 1,
+                AggregateNodeChild {
+                    id: right_id,
+                    start,
+                    aggregate,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Writes the root of `index_id`, which keeps the index's id: a root with
+    /// a single child is replaced by the child, and a root that's too large
+    /// moves its contents into two new children.
+    async fn write_root(
+        &mut self,
+        index_id: IndexId,
+        mut contents: AggregateNodeContents,
+        exists: bool,
+    ) -> anyhow::Result<()> {
+        loop {
+            let AggregateNodeContents::Internal(ref children) = contents else {
+                break;
+            };
+            if children.len() != 1 {
+                break;
+            }
+            let child_id = children[0].id;
+            let child = self
+                .node(child_id)
+                .await?
+                .with_context(|| format!("Aggregate index node {child_id} is missing"))?;
+            self.delete_node(child_id).await?;
+            contents = child.contents;
+        }
+        if contents.len() > MAX_NODE_SIZE {
+            let (start, right) = contents.split_off();
+            let left_aggregate = contents.aggregate()?;
+            let right_aggregate = right.aggregate()?;
+            let left_id = self.insert_node(index_id, contents).await?;
+            let right_id = self.insert_node(index_id, right).await?;
+            contents = AggregateNodeContents::Internal(vec![
+                AggregateNodeChild {
+                    id: left_id,
+                    start: vec![],
+                    aggregate: left_aggregate,
+                },
+                AggregateNodeChild {
+                    id: right_id,
+                    start,
+                    aggregate: right_aggregate,
+                },
+            ]);
+        }
+        match (exists, contents.is_empty()) {
+            (true, true) => self.delete_node(index_id).await?,
+            (true, false) => self.replace_node(index_id, index_id, contents).await?,
+            (false, true) => {},
+            (false, false) => {
+                let node = AggregateIndexNode { index_id, contents };
+                SystemMetadataModel::new_global(self.tx)
+                    .insert_with_internal_id(
+                        &AGGREGATE_INDEX_COUNTS_TABLE,
+                        index_id,
+                        node.try_into()?,
+                    )
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Deletes up to `limit` nodes of aggregate indexes that aren't in
+    /// `live_indexes`, returning how many were deleted.
+    pub async fn delete_dropped(
+        &mut self,
+        live_indexes: &BTreeSet<IndexId>,
+        limit: usize,
+    ) -> anyhow::Result<usize> {
+        if !self.is_ready() {
+            return Ok(0);
+        }
+        let mut num_deleted = 0;
+        let mut last_index_id: Option<IndexId> = None;
+        // Skip from one index's nodes to the next, only reading the nodes of
+        // dropped indexes in full.
+        while num_deleted < limit {
+            let range = match last_index_id {
+                Some(index_id) => vec![IndexRangeExpression::Gt(
+                    INDEX_ID_FIELD.clone(),
+                    ConvexValue::String(index_id.to_string().try_into()?),
+                )],
+                None => vec![],
+            };
+            let Some(first) = self.query_one(range).await? else {
+                break;
+            };
+            let index_id = first.index_id;
+            last_index_id = Some(index_id);
+            if live_indexes.contains(&index_id) {
+                continue;
+            }
+            let range = vec![IndexRangeExpression::Eq(
+                INDEX_ID_FIELD.clone(),
+                ConvexValue::String(index_id.to_string().try_into()?).into(),
+            )];
+            let mut query = ResolvedQuery::new(
+                self.tx,
+                TableNamespace::Global,
+                Query::index_range(IndexRange {
+                    index_name: AGGREGATE_INDEX_COUNTS_BY_INDEX_ID.clone(),
+                    range,
+                    order: Order::Asc,
+                }),
+            )?;
+            let mut ids = vec![];
+            while ids.len() < limit - num_deleted
+                && let Some(document) = query.next(self.tx, None).await?
+            {
+                ids.push(document.id());
+            }
+            for id in ids {
+                SystemMetadataModel::new_global(self.tx).delete(id).await?;
+                num_deleted += 1;
+            }
+        }
+        Ok(num_deleted)
+    }
+
+    async fn query_one(
+        &mut self,
+        range: Vec<IndexRangeExpression>,
+    ) -> anyhow::Result<Option<ParsedDocument<AggregateIndexNode>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: AGGREGATE_INDEX_COUNTS_BY_INDEX_ID.clone(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .next(self.tx, None)
+            .await?
+            .map(ParsedDocument::<AggregateIndexNode>::try_from)
+            .transpose()
+    }
+
+    /// Reads the node `id`, or `None` if it doesn't exist or
+    /// `_aggregate_index_counts` hasn't been created yet.
+    async fn node(&mut self, id: InternalId) -> anyhow::Result<Option<AggregateIndexNode>> {
+        let Some(table_id) = self.table_id() else {
+            return Ok(None);
+        };
+        self.tx
+            .get(ResolvedDocumentId::new(table_id, id))
+            .await?
+            .map(|document| AggregateIndexNode::try_from(document.into_value().0))
+            .transpose()
+    }
+
+    /// Reads the node `id` like [`Self::node`], but without adding it to the
+    /// read set. The node is read at the transaction's snapshot, which is
+    /// also where the deltas it's combined with come from.
+    async fn node_untracked(
+        &mut self,
+        id: InternalId,
+    ) -> anyhow::Result<Option<AggregateIndexNode>> {
+        let Some(table_id) = self.table_id() else {
+            return Ok(None);
+        };
+        let index_key = IndexKey::new(vec![], ResolvedDocumentId::new(table_id, id).into());
+        let IndexRangeResponse { page, .. } = self
+            .tx
+            .index
+            .range(
+                &mut TransactionReadSet::new(),
+                RangeRequest {
+                    index_name: TabletIndexName::by_id(table_id.tablet_id),
+                    printable_index_name: IndexName::by_id(AGGREGATE_INDEX_COUNTS_TABLE.clone()),
+                    interval: Interval::prefix(index_key.into_bytes().into()),
+                    order: Order::Asc,
+                    max_size: 1,
+                },
+            )
+            .await?;
+        page.into_iter()
+            .next()
+            .map(|(_, document, _)| AggregateIndexNode::try_from(document.into_value().0))
+            .transpose()
+    }
+
+    async fn insert_node(
+        &mut self,
+        index_id: IndexId,
+        contents: AggregateNodeContents,
+    ) -> anyhow::Result<InternalId> {
+        let node = AggregateIndexNode { index_id, contents };
+        let id = SystemMetadataModel::new_global(self.tx)
+            .insert(&AGGREGATE_INDEX_COUNTS_TABLE, node.try_into()?)
+            .await?;
+        Ok(id.internal_id())
+    }
+
+    async fn replace_node(
+        &mut self,
+        index_id: IndexId,
+        id: InternalId,
+        contents: AggregateNodeContents,
+    ) -> anyhow::Result<()> {
+        let table_id = self.table_id().context("Missing _aggregate_index_counts")?;
+        let node = AggregateIndexNode { index_id, contents };
+        SystemMetadataModel::new_global(self.tx)
+            .replace(ResolvedDocumentId::new(table_id, id), node.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_node(&mut self, id: InternalId) -> anyhow::Result<()> {
+        let table_id = self.table_id().context("Missing _aggregate_index_counts")?;
+        SystemMetadataModel::new_global(self.tx)
+            .delete(ResolvedDocumentId::new(table_id, id))
+            .await?;
+        Ok(())
+    }
+
+    fn table_id(&mut self) -> Option<TabletIdAndTableNumber> {
+        self.tx
+            .table_mapping()
+            .namespace(TableNamespace::Global)
+            .id_and_number_if_exists(&AGGREGATE_INDEX_COUNTS_TABLE)
+    }
+
+    /// Whether counts can be flushed yet, which needs
+    /// `_aggregate_index_counts` to be created and its `by_index_id` index to
+    /// have finished backfilling.
+    pub fn is_ready(&mut self) -> bool {
+        let Some(table_id) = self.table_id() else {
+            return false;
+        };
+        let Ok(index_name) = TabletIndexName::new(
+            table_id.tablet_id,
+            AGGREGATE_INDEX_COUNTS_BY_INDEX_ID.descriptor().clone(),
+        ) else {
+            return false;
+        };
+        self.tx
+            .index
+            .index_registry()
+            .get_enabled(&index_name)
+            .is_some()
+    }
+}
+
+/// Returns the interval of the `by_index_id` index of
+/// `_aggregate_index_counts` holding the nodes of `index_id`.
+pub fn nodes_of_index_interval(index_id: IndexId) -> anyhow::Result<Interval> {
+    IndexRange {
+        index_name: AGGREGATE_INDEX_COUNTS_BY_INDEX_ID.clone(),
+        range: vec![IndexRangeExpression::Eq(
+            INDEX_ID_FIELD.clone(),
+            ConvexValue::String(index_id.to_string().try_into()?).into(),
+        )],
+        order: Order::Asc,
+    }
+    .compile(BY_INDEX_ID_FIELDS.clone(), None, None)
+}
@@ -115,6 +115,9 @@ impl<'a, RT: Runtime> ImportFacingModel<'a, RT> {
                 &table_mapping_for_schema.namespace(TableNamespace::by_component_TODO()),
             )
             .await?;
+        self.tx
+            .check_unique_indexes(table_name, None, &document)
+            .await?;
         self.tx.apply_validated_write(id, None, Some(document))?;
 
         Ok(id.into())
@@ -7,10 +7,10 @@ use std::collections::{
 use anyhow::Context;
 use common::{
     bootstrap_model::index::{
+        aggregate_index::DeveloperAggregateIndexConfig,
         database_index::{
             DatabaseIndexState,
             DeveloperDatabaseIndexConfig,
-            IndexedFields,
         },
         index_validation_error,
         text_index::{
@@ -31,9 +31,11 @@ use common::{
     document::{
         ParsedDocument,
         ResolvedDocument,
+        ID_FIELD_PATH,
     },
     query::{
         IndexRange,
+        IndexRangeExpression,
         Order,
         Query,
     },
@@ -55,6 +57,12 @@ use common::{
 };
 use errors::ErrorMetadata;
 use indexing::{
+    aggregate_index::{
+        aggregate_range_on_id_error,
+        index_not_an_aggregate_index_error,
+        AggregateCount,
+        RangeAggregate,
+    },
     backend_in_memory_indexes::index_not_a_database_index_error,
     index_registry::{
         index_not_found_error,
@@ -77,6 +85,7 @@ use crate::{
     reads::TransactionReadSet,
     transaction_index::TransactionIndex,
     unauthorized_error,
+    AggregateIndexCountsModel,
     ResolvedQuery,
     SystemMetadataModel,
     TableModel,
@@ -204,10 +213,16 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
                     backfilled_index.name
                 )
             })?;
+        // Unique indexes were checked for duplicates before they finished
+        // backfilling, and writes have been checked against them since.
         match doc.config {
             IndexConfig::Database {
                 ref mut on_disk_state,
                 ..
+            }
+            | IndexConfig::Aggregate {
+                ref mut on_disk_state,
+                ..
             } => match on_disk_state {
                 DatabaseIndexState::Backfilling(_) | DatabaseIndexState::Enabled => {
                     anyhow::bail!(
@@ -371,10 +386,14 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
             // Collect the database indexes.
             for (index_descriptor, index_schema) in &table_schema.indexes {
                 let index_name = IndexName::new(table_name.clone(), index_descriptor.clone())?;
-                indexes_in_schema.push(IndexMetadata::new_backfilling(
+                indexes_in_schema.push(IndexMetadata::new_backfilling_database_index(
                     *self.tx.begin_timestamp(),
                     index_name.clone(),
-                    index_schema.fields.clone(),
+                    DeveloperDatabaseIndexConfig {
+                        fields: index_schema.fields.clone(),
+                        unique: index_schema.unique,
+                        filter: index_schema.filter.clone(),
+                    },
                 ))
             }
 
@@ -396,6 +415,17 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
                     index_schema.filter_fields.clone(),
                 ));
             }
+            for (index_descriptor, index_schema) in &table_schema.aggregate_indexes {
+                let index_name = IndexName::new(table_name.clone(), index_descriptor.clone())?;
+                indexes_in_schema.push(IndexMetadata::new_backfilling_aggregate_index(
+                    *self.tx.begin_timestamp(),
+                    index_name.clone(),
+                    DeveloperAggregateIndexConfig {
+                        fields: index_schema.fields.clone(),
+                        value_field: index_schema.value_field.clone(),
+                    },
+                ));
+            }
         }
 
         let mut diff = IndexDiff::default();
@@ -536,11 +566,11 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
         Ok(diff)
     }
 
-    pub fn indexed_fields(
+    pub fn database_index_config(
         &mut self,
         stable_index_name: &StableIndexName,
         printable_index_name: &IndexName,
-    ) -> anyhow::Result<IndexedFields> {
+    ) -> anyhow::Result<DeveloperDatabaseIndexConfig> {
         let resolved_index_name = stable_index_name
             .tablet_index_name()
             .with_context(|| index_not_found_error(printable_index_name))?;
@@ -548,13 +578,102 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
             self.require_enabled_index_metadata(printable_index_name, resolved_index_name)?;
         match metadata.config.clone() {
             IndexConfig::Database {
-                developer_config: DeveloperDatabaseIndexConfig { fields },
-                ..
-            } => Ok(fields),
+                developer_config, ..
+            } => Ok(developer_config),
             _ => anyhow::bail!(index_not_a_database_index_error(printable_index_name)),
         }
     }
 
+    /// Returns the count and sum/min/max over documents in `range` of the
+    /// aggregate index `index_name`, up-to-date with the current transaction.
+    ///
+    /// The range of the index is added to the read set, so the result is
+    /// invalidated by writes to documents in that range. Flushing their counts
+    /// to `_aggregate_index_counts` doesn't change it, so the nodes read
+    /// aren't.
+    pub async fn aggregate(
+        &mut self,
+        namespace: TableNamespace,
+        index_name: &IndexName,
+        range: Vec<IndexRangeExpression>,
+    ) -> anyhow::Result<RangeAggregate> {
+        let stable_index_name = self.stable_index_name(
+            namespace,
+            index_name,
+            TableFilter::ExcludePrivateSystemTables,
+        )?;
+        let resolved_index_name = stable_index_name
+            .tablet_index_name()
+            .with_context(|| index_not_found_error(index_name))?
+            .clone();
+        let metadata = self.require_enabled_index_metadata(index_name, &resolved_index_name)?;
+        let IndexConfig::Aggregate {
+            ref developer_config,
+            ..
+        } = metadata.config
+        else {
+            anyhow::bail!(index_not_an_aggregate_index_error(index_name));
+        };
+        // Aggregate keys end with the summarized value instead of `_id`.
+        if range.iter().any(|expression| {
+            let (IndexRangeExpression::Eq(field, _)
+            | IndexRangeExpression::Gt(field, _)
+            | IndexRangeExpression::Gte(field, _)
+            | IndexRangeExpression::Lt(field, _)
+            | IndexRangeExpression::Lte(field, _)) = expression;
+            *field == *ID_FIELD_PATH
+        }) {
+            anyhow::bail!(aggregate_range_on_id_error(index_name));
+        }
+        let interval = IndexRange {
+            index_name: index_name.clone(),
+            range,
+            order: Order::Asc,
+        }
+        .compile(developer_config.fields.clone(), None, None)?;
+        let index_id = metadata.id().internal_id();
+
+        // Combine the deltas committed since the counts were last flushed, as
+        // of the beginning of the transaction, with the transaction's own
+        // writes, then apply them to the persisted counts.
+        let mut deltas = BTreeMap::new();
+        let committed = self
+            .tx
+            .count_snapshot
+            .aggregate_deltas(index_id, &interval)
+            .await?
+            .with_context(|| format!("Aggregate index {index_name} isn't loaded"))?;
+        for delta in committed {
+            deltas.insert(
+                delta.key,
+                AggregateCount {
+                    value: delta.value,
+                    count: delta.count,
+                },
+            );
+        }
+        if let Some(tx_deltas) = self.tx.aggregate_index_deltas.get(&index_id) {
+            for (key, delta) in tx_deltas.range::<_, [u8]>(&interval) {
+                let entry = deltas.entry(key.clone()).or_insert(AggregateCount {
+                    value: delta.value,
+                    count: 0,
+                });
+                entry.count += delta.count;
+            }
+        }
+        deltas.retain(|_, delta| delta.count != 0);
+        let aggregate = AggregateIndexCountsModel::new(self.tx)
+            .aggregate(index_id, &interval, &deltas)
+            .await?;
+
+        self.tx.reads.record_indexed_directly(
+            resolved_index_name,
+            developer_config.fields.clone(),
+            interval,
+        )?;
+        Ok(aggregate)
+    }
+
     /// Returns the index metadata for the given name if it's enabled or fails
     /// with a descriptive error if the index is either missing or not
     /// enabled.
@@ -885,9 +1004,12 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
             let index_name = TabletIndexName::new(target_table, index.name.descriptor().clone())?;
             let metadata = match index.into_value().config {
                 IndexConfig::Database {
-                    developer_config: DeveloperDatabaseIndexConfig { fields },
-                    ..
-                } => IndexMetadata::new_backfilling(*self.tx.begin_timestamp(), index_name, fields),
+                    developer_config, ..
+                } => IndexMetadata::new_backfilling_database_index(
+                    *self.tx.begin_timestamp(),
+                    index_name,
+                    developer_config,
+                ),
                 IndexConfig::Search {
                     developer_config:
                         DeveloperSearchIndexConfig {
@@ -914,6 +1036,13 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
                     dimensions,
                     filter_fields,
                 ),
+                IndexConfig::Aggregate {
+                    developer_config, ..
+                } => IndexMetadata::new_backfilling_aggregate_index(
+                    *self.tx.begin_timestamp(),
+                    index_name,
+                    developer_config,
+                ),
             };
             SystemMetadataModel::new_global(self.tx)
                 .insert_metadata(&INDEX_TABLE, metadata.try_into()?)
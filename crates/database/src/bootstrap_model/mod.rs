@@ -2,6 +2,7 @@
 //! low level database to start up properly.
 //!
 //! Higher level tables belong in the model crate, layered above the database.
+pub mod aggregate_index_counts;
 pub mod components;
 pub mod defaults;
 pub mod import_facing;
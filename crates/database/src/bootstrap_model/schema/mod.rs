@@ -1,3 +1,4 @@
+mod references;
 #[cfg(test)]
 mod tests;
 pub mod types;
@@ -34,6 +35,7 @@ use common::{
 use errors::ErrorMetadata;
 use value::{
     val,
+    ConvexObject,
     FieldPath,
     NamespacedTableMapping,
     ResolvedDocumentId,
@@ -165,6 +167,29 @@ impl<'a, RT: Runtime> SchemaModel<'a, RT> {
         Ok(())
     }
 
+    /// Fills in the `default`s the active schema declares for fields missing
+    /// from `value`, a new document for `table_name`.
+    pub async fn apply_defaults(
+        &mut self,
+        table_name: &TableName,
+        value: ConvexObject,
+    ) -> anyhow::Result<ConvexObject> {
+        let Some((_id, active_schema)) = self.get_by_state(SchemaState::Active).await? else {
+            return Ok(value);
+        };
+        if !active_schema.schema_validation {
+            return Ok(value);
+        }
+        match active_schema
+            .tables
+            .get(table_name)
+            .and_then(|table| table.document_type.as_ref())
+        {
+            Some(document_type) => document_type.apply_defaults(value),
+            None => Ok(value),
+        }
+    }
+
     /// You probably want to use `enforce`.
     /// enforce_with_table_mapping allows schema validation to use a custom
     /// TableMapping for validating foreign references, which is useful for
@@ -411,6 +436,10 @@ impl<'a, RT: Runtime> SchemaModel<'a, RT> {
                     SchemaValidationError::ReferencedTableCannotBeDeleted {
                         table_name, ..
                     } => table_name,
+                    SchemaValidationError::UniqueIndexHasDuplicates { table_name, .. } => {
+                        table_name
+                    },
+                    SchemaValidationError::DanglingReference { table_name, .. } => table_name,
                 };
                 SystemMetadataModel::new(self.tx, self.namespace)
                     .patch(
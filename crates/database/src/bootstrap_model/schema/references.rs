@@ -0,0 +1,331 @@
+//! Referential integrity for the `v.id(...)` fields a schema declares as
+//! references.
+
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+
+use common::{
+    bootstrap_model::schema::SchemaState,
+    document::ResolvedDocument,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    schemas::{
+        DatabaseSchema,
+        OnDelete,
+        ReferenceSchema,
+        SchemaValidationError,
+        TableDefinition,
+    },
+    types::{
+        IndexName,
+        MaybeValue,
+    },
+};
+use errors::ErrorMetadata;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+    FieldName,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+};
+
+use super::SchemaModel;
+use crate::{
+    IndexModel,
+    PatchValue,
+    ResolvedQuery,
+};
+
+impl<'a, RT: Runtime> SchemaModel<'a, RT> {
+    /// Fails if `document` references a document that doesn't exist through a
+    /// field the active schema declares as a reference. A pending or
+    /// validated schema the document would violate is marked as failed.
+    pub async fn enforce_references(
+        &mut self,
+        table_name: &TableName,
+        document: &ResolvedDocument,
+    ) -> anyhow::Result<()> {
+        if let Some(active_schema) = self.enforced_schema().await? {
+            if let Some(error) = self
+                .find_dangling_reference(&active_schema, table_name, document)
+                .await?
+            {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "DanglingReference",
+                    error.to_string()
+                ));
+            }
+        }
+        if let Some((id, in_progress_schema)) = self.in_progress_schema().await? {
+            if let Some(error) = self
+                .find_dangling_reference(&in_progress_schema, table_name, document)
+                .await?
+            {
+                self.mark_failed(id, error).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the active schema's `onDelete` behavior to the documents
+    /// referencing `document`, which has just been deleted from `table_name`.
+    /// Returns the referencing documents to delete for
+    /// [`OnDelete::Cascade`], which the caller deletes in turn.
+    pub async fn enforce_delete(
+        &mut self,
+        table_name: &TableName,
+        document: &ResolvedDocument,
+    ) -> anyhow::Result<Vec<ResolvedDocumentId>> {
+        let id = document.developer_id();
+        let mut to_delete = vec![];
+        let active_schema = self.enforced_schema().await?;
+        if let Some(active_schema) = &active_schema {
+            for (definition, reference) in references_to(active_schema, table_name) {
+                if !self.reference_index_enabled(definition, reference)? {
+                    continue;
+                }
+                let limit = (reference.on_delete == OnDelete::Restrict).then_some(1);
+                let referencing = self
+                    .referencing_documents(definition, reference, id, limit)
+                    .await?;
+                match reference.on_delete {
+                    OnDelete::Restrict => {
+                        if let Some(referencing) = referencing.first() {
+                            anyhow::bail!(ErrorMetadata::bad_request(
+                                "ReferencedDocumentCannotBeDeleted",
+                                format!(
+                                    "Failed to delete document with ID \"{}\" from table \
+                                     \"{table_name}\" because document \"{}\" in table \"{}\" \
+                                     references it in field \"{}\", which has `onDelete: \
+                                     \"restrict\"`",
+                                    id.encode(),
+                                    referencing.developer_id().encode(),
+                                    definition.table_name,
+                                    reference.field,
+                                ),
+                            ));
+                        }
+                    },
+                    OnDelete::Cascade => {
+                        to_delete.extend(referencing.iter().map(|document| document.id()));
+                    },
+                    OnDelete::SetNull => {
+                        for referencing in referencing {
+                            let patch = PatchValue::from(BTreeMap::from([(
+                                FieldName::from(reference.field.clone()),
+                                MaybeValue(Some(ConvexValue::Null)),
+                            )]));
+                            self.tx.patch_inner(referencing.id(), patch).await?;
+                        }
+                    },
+                }
+            }
+        }
+
+        if let Some((schema_id, in_progress_schema)) = self.in_progress_schema().await? {
+            for (definition, reference) in references_to(&in_progress_schema, table_name) {
+                // References the active schema also has were just enforced.
+                let enforced = active_schema.as_ref().is_some_and(|active_schema| {
+                    active_schema
+                        .tables
+                        .get(&definition.table_name)
+                        .and_then(|active| active.references.get(&reference.field))
+                        .is_some_and(|active| active.referenced_table == *table_name)
+                });
+                // Documents that reference `id` through an index that's still
+                // backfilling are checked by the schema worker instead.
+                if enforced || !self.reference_index_enabled(definition, reference)? {
+                    continue;
+                }
+                let referencing = self
+                    .referencing_documents(definition, reference, id, Some(1))
+                    .await?;
+                if let Some(referencing) = referencing.first() {
+                    let error = SchemaValidationError::DanglingReference {
+                        table_name: definition.table_name.clone(),
+                        id: referencing.developer_id(),
+                        field: reference.field.clone(),
+                        referenced_table: table_name.clone(),
+                        referenced_id: id,
+                    };
+                    self.mark_failed(schema_id, error).await?;
+                    break;
+                }
+            }
+        }
+        Ok(to_delete)
+    }
+
+    /// The tables with a field the enforced or in-progress schema declares
+    /// as a reference to `table_name`, which deleting from `table_name` would
+    /// apply `onDelete` to.
+    pub async fn tables_referencing(
+        &mut self,
+        table_name: &TableName,
+    ) -> anyhow::Result<BTreeSet<TableName>> {
+        let mut tables = BTreeSet::new();
+        let active_schema = self.enforced_schema().await?;
+        let in_progress_schema = self.in_progress_schema().await?;
+        for schema in active_schema
+            .iter()
+            .chain(in_progress_schema.iter().map(|(_id, schema)| schema))
+        {
+            tables.extend(
+                references_to(schema, table_name)
+                    .map(|(definition, _reference)| definition.table_name.clone()),
+            );
+        }
+        Ok(tables)
+    }
+
+    /// The active schema, if it's enforced.
+    async fn enforced_schema(&mut self) -> anyhow::Result<Option<DatabaseSchema>> {
+        let schema = self
+            .get_by_state(SchemaState::Active)
+            .await?
+            .map(|(_id, schema)| schema)
+            .filter(|schema| schema.schema_validation);
+        Ok(schema)
+    }
+
+    /// The pending or validated schema, if there is one with validation
+    /// enabled.
+    async fn in_progress_schema(
+        &mut self,
+    ) -> anyhow::Result<Option<(ResolvedDocumentId, DatabaseSchema)>> {
+        let pending_schema = self.get_by_state(SchemaState::Pending).await?;
+        let validated_schema = self.get_by_state(SchemaState::Validated).await?;
+        let schema = match (pending_schema, validated_schema) {
+            (None, None) => None,
+            (Some(schema), None) | (None, Some(schema)) => Some(schema),
+            (Some(_), Some(_)) => {
+                anyhow::bail!("Invalid schema state: both pending and validated schemas exist")
+            },
+        };
+        Ok(schema.filter(|(_id, schema)| schema.schema_validation))
+    }
+
+    async fn find_dangling_reference(
+        &mut self,
+        schema: &DatabaseSchema,
+        table_name: &TableName,
+        document: &ResolvedDocument,
+    ) -> anyhow::Result<Option<SchemaValidationError>> {
+        let Some(definition) = schema.tables.get(table_name) else {
+            return Ok(None);
+        };
+        for reference in definition.references.values() {
+            // Missing and `null` fields don't reference anything.
+            let Some(ConvexValue::String(value)) = document.value().get(&*reference.field) else {
+                continue;
+            };
+            let Ok(referenced_id) = DeveloperDocumentId::decode(value) else {
+                continue;
+            };
+            if !self
+                .reference_exists(&reference.referenced_table, referenced_id)
+                .await?
+            {
+                return Ok(Some(SchemaValidationError::DanglingReference {
+                    table_name: table_name.clone(),
+                    id: document.developer_id(),
+                    field: reference.field.clone(),
+                    referenced_table: reference.referenced_table.clone(),
+                    referenced_id,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn reference_exists(
+        &mut self,
+        referenced_table: &TableName,
+        referenced_id: DeveloperDocumentId,
+    ) -> anyhow::Result<bool> {
+        let table_mapping = self.tx.table_mapping().namespace(self.namespace);
+        let Ok(id) = referenced_id.to_resolved(&table_mapping.inject_table_id()) else {
+            return Ok(false);
+        };
+        if table_mapping.tablet_name(id.table().tablet_id)? != *referenced_table {
+            return Ok(false);
+        }
+        Ok(self.tx.get(id).await?.is_some())
+    }
+
+    /// Whether the index backing `reference` can be queried. It's still
+    /// backfilling while the schema declaring it is being pushed.
+    fn reference_index_enabled(
+        &mut self,
+        definition: &TableDefinition,
+        reference: &ReferenceSchema,
+    ) -> anyhow::Result<bool> {
+        let Some(index) = definition.reference_index(&reference.field) else {
+            return Ok(false);
+        };
+        let index_name = IndexName::new(
+            definition.table_name.clone(),
+            index.index_descriptor.clone(),
+        )?;
+        Ok(IndexModel::new(self.tx)
+            .enabled_index_metadata(self.namespace, &index_name)?
+            .is_some())
+    }
+
+    /// The documents in `definition`'s table whose `reference` field is `id`,
+    /// found through the reference's index.
+    async fn referencing_documents(
+        &mut self,
+        definition: &TableDefinition,
+        reference: &ReferenceSchema,
+        id: DeveloperDocumentId,
+        limit: Option<usize>,
+    ) -> anyhow::Result<Vec<ResolvedDocument>> {
+        let index = definition
+            .reference_index(&reference.field)
+            .ok_or_else(|| anyhow::anyhow!("No index for reference {}", reference.field))?;
+        let index_range = IndexRange {
+            index_name: IndexName::new(
+                definition.table_name.clone(),
+                index.index_descriptor.clone(),
+            )?,
+            range: vec![IndexRangeExpression::Eq(
+                FieldPath::new(vec![reference.field.clone()])?,
+                ConvexValue::String(id.encode().try_into()?).into(),
+            )],
+            order: Order::Asc,
+        };
+        let mut query_stream =
+            ResolvedQuery::new(self.tx, self.namespace, Query::index_range(index_range))?;
+        let mut documents = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            documents.push(document);
+            if limit.is_some_and(|limit| documents.len() >= limit) {
+                break;
+            }
+        }
+        Ok(documents)
+    }
+}
+
+fn references_to<'s>(
+    schema: &'s DatabaseSchema,
+    table_name: &'s TableName,
+) -> impl Iterator<Item = (&'s TableDefinition, &'s ReferenceSchema)> {
+    schema.tables.values().flat_map(move |definition| {
+        definition
+            .references
+            .values()
+            .filter(move |reference| reference.referenced_table == *table_name)
+            .map(move |reference| (definition, reference))
+    })
+}
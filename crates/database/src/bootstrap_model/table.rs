@@ -1,6 +1,7 @@
 use std::{
     collections::BTreeSet,
     sync::LazyLock,
+    time::Duration,
 };
 
 use anyhow::Context;
@@ -26,6 +27,10 @@ use common::{
         ResolvedDocument,
     },
     interval::Interval,
+    knobs::{
+        MAX_TABLE_RETENTION,
+        MIN_TABLE_RETENTION,
+    },
     query::{
         Order,
         Query,
@@ -218,6 +223,7 @@ impl<'a, RT: Runtime> TableModel<'a, RT> {
             number: table_metadata.number,
             state: TableState::Deleting,
             namespace: table_metadata.namespace,
+            document_retention: table_metadata.document_retention,
         };
         SystemMetadataModel::new_global(self.tx)
             .replace(table_doc_id, updated_table_metadata.try_into()?)
@@ -225,6 +231,70 @@ impl<'a, RT: Runtime> TableModel<'a, RT> {
         Ok(())
     }
 
+    /// How long the table keeps superseded revisions of its documents, or
+    /// `None` if it uses the deployment's `DOCUMENT_RETENTION_DELAY`.
+    pub async fn document_retention(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+    ) -> anyhow::Result<Option<Duration>> {
+        let tablet_id = self.user_tablet_id(namespace, table_name)?;
+        Ok(self.get_table_metadata(tablet_id).await?.document_retention)
+    }
+
+    /// Overrides how long the table keeps superseded revisions of its
+    /// documents, or resets it to the deployment default with `None`. The
+    /// override must be between `MIN_TABLE_RETENTION` and
+    /// `MAX_TABLE_RETENTION`, and can be longer than the default.
+    pub async fn set_document_retention(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+        document_retention: Option<Duration>,
+    ) -> anyhow::Result<()> {
+        let tablet_id = self.user_tablet_id(namespace, table_name)?;
+        if let Some(retention) = document_retention
+            && (retention < *MIN_TABLE_RETENTION || retention > *MAX_TABLE_RETENTION)
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidTableRetention",
+                format!(
+                    "Retention for table \"{table_name}\" must be between {}s and {}s, got {}s",
+                    MIN_TABLE_RETENTION.as_secs(),
+                    MAX_TABLE_RETENTION.as_secs(),
+                    retention.as_secs()
+                ),
+            ));
+        }
+        let table_metadata = self.get_table_metadata(tablet_id).await?;
+        let table_doc_id = table_metadata.id();
+        let mut table_metadata = table_metadata.into_value();
+        table_metadata.document_retention = document_retention;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(table_doc_id, table_metadata.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    fn user_tablet_id(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+    ) -> anyhow::Result<TabletId> {
+        match self
+            .tx
+            .table_mapping()
+            .namespace(namespace)
+            .id_if_exists(table_name)
+        {
+            Some(tablet_id) if !table_name.is_system() => Ok(tablet_id),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "TableNotFound",
+                format!("Table \"{table_name}\" doesn't exist"),
+            )),
+        }
+    }
+
     async fn get_table_metadata(
         &mut self,
         tablet_id: TabletId,
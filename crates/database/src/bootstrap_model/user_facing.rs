@@ -22,6 +22,10 @@ use indexing::backend_in_memory_indexes::{
     BatchKey,
     RangeRequest,
 };
+use keybroker::{
+    AdminPermission,
+    Identity,
+};
 use maplit::btreemap;
 use value::{
     check_user_size,
@@ -48,6 +52,7 @@ use crate::{
     unauthorized_error,
     virtual_tables::VirtualTable,
     PatchValue,
+    SchemaModel,
     TableModel,
     Transaction,
 };
@@ -162,6 +167,7 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
                             .inject_table_id(),
                     )?;
                     let table_name = self.tx.table_mapping().tablet_name(id_.table().tablet_id)?;
+                    check_admin_key_table(&self.tx.identity, &table_name)?;
                     ids_to_fetch.insert(batch_key, (id_, table_name));
                 }
             };
@@ -221,7 +227,11 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
                 format!("{table} is a read-only table"),
             ));
         }
+        check_admin_key_can_write(&self.tx.identity, &table)?;
 
+        let value = SchemaModel::new(self.tx, self.namespace)
+            .apply_defaults(&table, value)
+            .await?;
         check_user_size(value.size())?;
         self.tx.retention_validator.fail_if_falling_behind()?;
         let id = self.tx.id_generator.generate(&table);
@@ -279,6 +289,7 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         {
             anyhow::bail!(unauthorized_error("patch"))
         }
+        self.check_admin_key_can_write_id(id)?;
         self.tx.retention_validator.fail_if_falling_behind()?;
 
         let id_ = id.map_table(
@@ -312,6 +323,7 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         {
             anyhow::bail!(unauthorized_error("replace"))
         }
+        self.check_admin_key_can_write_id(id)?;
         if !self.tx.is_system(*id.table()) {
             check_user_size(value.size())?;
         }
@@ -338,6 +350,7 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         {
             anyhow::bail!(unauthorized_error("delete"))
         }
+        self.check_admin_key_can_write_id(id)?;
         self.tx.retention_validator.fail_if_falling_behind()?;
 
         let id_ = id.map_table(
@@ -351,6 +364,21 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         Ok(document.to_developer())
     }
 
+    fn check_admin_key_can_write_id(&self, id: DeveloperDocumentId) -> anyhow::Result<()> {
+        if self.tx.identity.admin_scope().is_none() {
+            return Ok(());
+        }
+        let table_name = if self.tx.virtual_table_mapping().number_exists(id.table()) {
+            self.tx.virtual_table_mapping().name(*id.table())?
+        } else {
+            self.tx
+                .table_mapping()
+                .namespace(self.namespace)
+                .number_to_name()(*id.table())?
+        };
+        check_admin_key_can_write(&self.tx.identity, &table_name)
+    }
+
     pub fn record_read_document(
         &mut self,
         document: &DeveloperDocument,
@@ -369,6 +397,23 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
     }
 }
 
+/// Admin keys may be scoped to a subset of tables, which applies to everything
+/// read or written through the user facing model.
+pub(crate) fn check_admin_key_table(
+    identity: &Identity,
+    table_name: &TableName,
+) -> anyhow::Result<()> {
+    match identity.admin_scope() {
+        Some(scope) => scope.check_table(table_name),
+        None => Ok(()),
+    }
+}
+
+fn check_admin_key_can_write(identity: &Identity, table_name: &TableName) -> anyhow::Result<()> {
+    identity.check_admin_permission(AdminPermission::WriteData)?;
+    check_admin_key_table(identity, table_name)
+}
+
 fn start_index_range<RT: Runtime>(
     tx: &mut Transaction<RT>,
     request: IndexRangeRequest,
@@ -387,6 +432,7 @@ fn start_index_range<RT: Runtime>(
             let index_name = tablet_index_name
                 .clone()
                 .map_table(&tx.table_mapping().tablet_to_name())?;
+            check_admin_key_table(&tx.identity, index_name.table())?;
             Ok(Err(RangeRequest {
                 index_name: tablet_index_name.clone(),
                 printable_index_name: index_name,
@@ -397,6 +443,7 @@ fn start_index_range<RT: Runtime>(
         },
         StableIndexName::Virtual(index_name, tablet_index_name) => {
             log_virtual_table_query();
+            check_admin_key_table(&tx.identity, index_name.table())?;
             Ok(Err(RangeRequest {
                 index_name: tablet_index_name.clone(),
                 printable_index_name: index_name.clone(),
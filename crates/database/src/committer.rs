@@ -57,6 +57,7 @@ use common::{
     types::{
         DatabaseIndexUpdate,
         DatabaseIndexValue,
+        IndexId,
         RepeatableTimestamp,
         Timestamp,
         WriteTimestamp,
@@ -79,7 +80,13 @@ use futures::{
     StreamExt,
     TryStreamExt,
 };
-use indexing::index_registry::IndexRegistry;
+use indexing::{
+    aggregate_index::{
+        AggregateDeltas,
+        AggregateIndexes,
+    },
+    index_registry::IndexRegistry,
+};
 use minitrace::prelude::*;
 use parking_lot::Mutex;
 use prometheus::VMHistogram;
@@ -98,6 +105,7 @@ use value::{
 };
 
 use crate::{
+    aggregate_index_snapshot,
     bootstrap_model::defaults::BootstrapTableIds,
     database::{
         ConflictingReadWithWriteSource,
@@ -304,6 +312,13 @@ impl<RT: Runtime> Committer<RT> {
                             let response = self.load_indexes_into_memory(tables).await;
                             let _ = result.send(response);
                         }
+                        Some(CommitterMessage::LoadAggregateIndex {
+                            index_id, deltas, ts, result
+                        }) => {
+                            let response =
+                                self.load_aggregate_index(index_id, deltas, ts).await;
+                            let _ = result.send(response);
+                        }
                     }
                 },
             }
@@ -447,6 +462,56 @@ impl<RT: Runtime> Committer<RT> {
         Ok(())
     }
 
+    // The index worker computes `deltas` at `ts` without blocking commits,
+    // so this only blocks the committer while it replays the commits since
+    // then. From then on every commit keeps the deltas up to date.
+    async fn load_aggregate_index(
+        &mut self,
+        index_id: IndexId,
+        deltas: AggregateDeltas,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        let (last_snapshot, latest_ts) = {
+            let snapshot_manager = self.snapshot_manager.read();
+            (
+                snapshot_manager.latest_snapshot(),
+                snapshot_manager.latest_ts(),
+            )
+        };
+
+        let repeatable_persistence = RepeatablePersistence::new(
+            self.persistence.reader(),
+            latest_ts,
+            self.retention_validator.clone(),
+        );
+        let mut loaded = AggregateIndexes::default();
+        loaded.insert_index(index_id, deltas);
+        let num_revisions = aggregate_index_snapshot::catch_up(
+            &mut loaded,
+            &repeatable_persistence,
+            BootstrapTableIds::new(last_snapshot.table_mapping()),
+            &last_snapshot.index_registry,
+            last_snapshot.table_mapping(),
+            ts,
+            latest_ts,
+        )
+        .await?;
+        let mut aggregate_indexes = last_snapshot.aggregate_indexes.clone();
+        for (index_id, deltas) in loaded.iter() {
+            aggregate_indexes.insert_index(*index_id, deltas.clone());
+        }
+
+        let mut snapshot_manager = self.snapshot_manager.write();
+        if latest_ts != snapshot_manager.latest_ts() {
+            panic!("Snapshots were changed concurrently during commit?");
+        }
+        snapshot_manager.overwrite_last_snapshot_aggregate_indexes(aggregate_indexes);
+        tracing::info!(
+            "Loaded aggregate index {index_id} after replaying {num_revisions} revisions"
+        );
+        Ok(())
+    }
+
     fn bump_max_repeatable_ts(&mut self, result: oneshot::Sender<Timestamp>) {
         let timer = metrics::bump_repeatable_ts_timer();
         // next_max_repeatable_ts bumps the last_assigned_ts, so all future commits on
@@ -883,6 +948,29 @@ impl<RT: Runtime> CommitterClient<RT> {
         rx.await.map_err(|_| metrics::shutdown_error())?
     }
 
+    // Tell the committer to start maintaining an aggregate index computed at
+    // `ts`, catching it up to the latest snapshot.
+    pub async fn load_aggregate_index(
+        &self,
+        index_id: IndexId,
+        deltas: AggregateDeltas,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        let (tx, rx) = oneshot::channel();
+        let message = CommitterMessage::LoadAggregateIndex {
+            index_id,
+            deltas,
+            ts,
+            result: tx,
+        };
+        self.sender.try_send(message).map_err(|e| match e {
+            TrySendError::Full(..) => metrics::committer_full_error().into(),
+            TrySendError::Closed(..) => metrics::shutdown_error(),
+        })?;
+        // The only reason we might fail here if the committer is shutting down.
+        rx.await.map_err(|_| metrics::shutdown_error())?
+    }
+
     pub fn commit(
         &self,
         transaction: Transaction<RT>,
@@ -1014,6 +1102,12 @@ enum CommitterMessage<RT: Runtime> {
         tables: BTreeSet<TableName>,
         result: oneshot::Sender<anyhow::Result<()>>,
     },
+    LoadAggregateIndex {
+        index_id: IndexId,
+        deltas: AggregateDeltas,
+        ts: Timestamp,
+        result: oneshot::Sender<anyhow::Result<()>>,
+    },
     FinishSearchAndVectorBootstrap {
         bootstrapped_indexes: BootstrappedSearchAndVectorIndexes<RT>,
         bootstrap_ts: RepeatableTimestamp,
@@ -115,6 +115,7 @@ use futures::{
 };
 use imbl::OrdMap;
 use indexing::{
+    aggregate_index::AggregateDeltas,
     backend_in_memory_indexes::{
         BackendInMemoryIndexes,
         DatabaseIndexSnapshot,
@@ -140,6 +141,7 @@ use usage_tracking::{
 use value::{
     heap_size::HeapSize,
     id_v6::DeveloperDocumentId,
+    InternalDocumentId,
     Size,
     TableNamespace,
     TableNumber,
@@ -151,6 +153,10 @@ use vector::{
 };
 
 use crate::{
+    aggregate_index_snapshot::{
+        self,
+        AggregateIndexSnapshot,
+    },
     bootstrap_model::{
         table::{
             NUM_RESERVED_LEGACY_TABLE_NUMBERS,
@@ -167,6 +173,7 @@ use crate::{
     },
     defaults::{
         bootstrap_system_tables,
+        BootstrapTableIds,
         SystemIndex,
         DEFAULT_BOOTSTRAP_TABLE_NUMBERS,
     },
@@ -180,6 +187,7 @@ use crate::{
     retention::LeaderRetentionManager,
     search_and_vector_bootstrap::SearchAndVectorIndexBootstrapWorker,
     snapshot_manager::{
+        CountSnapshot,
         Snapshot,
         SnapshotManager,
         TableSummaries,
@@ -254,10 +262,10 @@ const MAX_VECTOR_ATTEMPTS: u32 = 5;
 pub struct Database<RT: Runtime> {
     committer: CommitterClient<RT>,
     subscriptions: SubscriptionsClient<RT>,
-    log: LogReader,
+    pub(crate) log: LogReader,
     snapshot_manager: Reader<SnapshotManager<RT>>,
     pub(crate) runtime: RT,
-    reader: Arc<dyn PersistenceReader>,
+    pub(crate) reader: Arc<dyn PersistenceReader>,
     write_commits_since_load: Arc<AtomicUsize>,
     retention_manager: LeaderRetentionManager<RT>,
     pub searcher: Arc<dyn Searcher>,
@@ -594,6 +602,18 @@ impl<RT: Runtime> DatabaseSnapshot<RT> {
         };
         drop(load_indexes_into_memory_timer);
 
+        tracing::info!("Bootstrapping aggregate indexes...");
+        let (aggregate_indexes, aggregates_num_rows) = aggregate_index_snapshot::bootstrap(
+            persistence.clone(),
+            retention_validator.clone(),
+            BootstrapTableIds::new(&table_mapping),
+            &index_registry,
+            &table_mapping,
+            &persistence_snapshot,
+        )
+        .await?;
+        tracing::info!("Bootstrapped aggregate indexes (read {aggregates_num_rows} rows)");
+
         let search = SearchIndexManager::new(
             rt.clone(),
             SearchIndexManagerState::Bootstrapping,
@@ -634,6 +654,7 @@ impl<RT: Runtime> DatabaseSnapshot<RT> {
                 table_summaries,
                 index_registry,
                 in_memory_indexes,
+                aggregate_indexes,
                 search_indexes: search,
                 vector_indexes: vector,
             },
@@ -952,6 +973,33 @@ impl<RT: Runtime> Database<RT> {
             .boxed()
     }
 
+    /// Load the set of documents and tombstones in all tables within the given
+    /// timestamp range.
+    ///
+    /// rate_limiter must be based on rows per second.
+    pub fn load_documents<'a>(
+        &'a self,
+        timestamp_range: TimestampRange,
+        order: Order,
+        rate_limiter: &'a RateLimiter<RT>,
+    ) -> DocumentStream<'a> {
+        self.reader
+            .load_documents(
+                timestamp_range,
+                order,
+                *DEFAULT_DOCUMENTS_PAGE_SIZE,
+                self.retention_validator(),
+            )
+            .then(|val| async {
+                while let Err(not_until) = rate_limiter.check() {
+                    let delay = not_until.wait_time_from(self.runtime.monotonic_now().as_nanos());
+                    self.runtime.wait(delay).await;
+                }
+                val
+            })
+            .boxed()
+    }
+
     /// Allows iterating over tables at any repeatable timestamp,
     /// even if it's outside of retention.
     /// TableIterator will have to walk all documents between snapshot_ts
@@ -975,8 +1023,40 @@ impl<RT: Runtime> Database<RT> {
         )
     }
 
+    /// Returns which of `ids` exist at `ts`. Unlike reads in a transaction,
+    /// this doesn't record the reads, so it can check any number of documents
+    /// a batch at a time.
+    pub async fn documents_exist_at(
+        &self,
+        ts: RepeatableTimestamp,
+        ids: impl IntoIterator<Item = InternalDocumentId>,
+    ) -> anyhow::Result<BTreeSet<InternalDocumentId>> {
+        Ok(self.documents_at(ts, ids).await?.into_keys().collect())
+    }
+
+    /// Returns the documents with the given ids at `ts`, leaving out the ones
+    /// that don't exist. Like `documents_exist_at`, this doesn't record the
+    /// reads.
+    pub async fn documents_at(
+        &self,
+        ts: RepeatableTimestamp,
+        ids: impl IntoIterator<Item = InternalDocumentId>,
+    ) -> anyhow::Result<BTreeMap<InternalDocumentId, ResolvedDocument>> {
+        let persistence =
+            RepeatablePersistence::new(self.reader.clone(), ts, self.retention_validator());
+        let ids = ids
+            .into_iter()
+            .map(|id| Ok((id, ts.succ()?)))
+            .collect::<anyhow::Result<_>>()?;
+        let revisions = persistence.previous_revisions(ids).await?;
+        Ok(revisions
+            .into_iter()
+            .filter_map(|((id, _), (_, document))| document.map(|document| (id, document)))
+            .collect())
+    }
+
     #[minitrace::trace]
-    async fn snapshot_table_mapping(
+    pub(crate) async fn snapshot_table_mapping(
         &self,
         ts: RepeatableTimestamp,
     ) -> anyhow::Result<Arc<TableMapping>> {
@@ -1020,7 +1100,7 @@ impl<RT: Runtime> Database<RT> {
     }
 
     #[minitrace::trace]
-    async fn snapshot_by_id_indexes(
+    pub(crate) async fn snapshot_by_id_indexes(
         &self,
         ts: RepeatableTimestamp,
     ) -> anyhow::Result<Arc<BTreeMap<TabletId, IndexId>>> {
@@ -1455,7 +1535,10 @@ impl<RT: Runtime> Database<RT> {
                 self.search_storage.clone(),
             )),
         );
-        let count_snapshot = Arc::new(snapshot.table_summaries);
+        let count_snapshot = Arc::new(CountSnapshot::new(
+            snapshot.table_summaries,
+            snapshot.aggregate_indexes,
+        ));
         let tx = Transaction::new(
             identity,
             id_generator,
@@ -1480,6 +1563,15 @@ impl<RT: Runtime> Database<RT> {
         Ok(snapshot)
     }
 
+    /// The aggregate index deltas at the latest snapshot, for checkpointing.
+    pub fn aggregate_index_snapshot(&self) -> AggregateIndexSnapshot {
+        let snapshot_manager = self.snapshot_manager.lock();
+        AggregateIndexSnapshot {
+            indexes: snapshot_manager.latest_snapshot().aggregate_indexes,
+            ts: *snapshot_manager.latest_ts(),
+        }
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub async fn commit(&self, transaction: Transaction<RT>) -> anyhow::Result<Timestamp> {
         self.commit_with_write_source(transaction, WriteSource::unknown())
@@ -1510,6 +1602,17 @@ impl<RT: Runtime> Database<RT> {
         self.committer.load_indexes_into_memory(tables).await
     }
 
+    pub async fn load_aggregate_index(
+        &self,
+        index_id: IndexId,
+        deltas: AggregateDeltas,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        self.committer
+            .load_aggregate_index(index_id, deltas, ts)
+            .await
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub async fn bump_max_repeatable_ts(&self) -> anyhow::Result<Timestamp> {
         self.committer.bump_max_repeatable_ts().await
@@ -5,6 +5,7 @@ use std::{
     },
     fmt::Display,
     num::NonZeroU32,
+    ops::Bound,
     sync::{
         Arc,
         LazyLock,
@@ -14,21 +15,26 @@ use std::{
 
 use common::{
     backoff::Backoff,
-    bootstrap_model::index::{
-        database_index::{
-            DatabaseIndexState,
-            IndexedFields,
+    bootstrap_model::{
+        index::{
+            database_index::{
+                DatabaseIndexState,
+                DeveloperDatabaseIndexConfig,
+                IndexedFields,
+            },
+            IndexConfig,
+            IndexMetadata,
+            TabletIndexMetadata,
+            INDEX_TABLE,
         },
-        IndexConfig,
-        IndexMetadata,
-        TabletIndexMetadata,
-        INDEX_TABLE,
+        schema::SchemaState,
     },
     document::{
         ParsedDocument,
         ResolvedDocument,
     },
     errors::report_error,
+    index::IndexKey,
     knobs::{
         ENABLE_INDEX_BACKFILL,
         INDEX_BACKFILL_CHUNK_RATE,
@@ -59,6 +65,7 @@ use common::{
         RuntimeInstant,
         SpawnHandle,
     },
+    schemas::SchemaValidationError,
     types::{
         DatabaseIndexUpdate,
         IndexId,
@@ -86,7 +93,14 @@ use futures::{
     TryStreamExt,
 };
 use governor::Quota;
-use indexing::index_registry::IndexRegistry;
+use indexing::{
+    aggregate_index::{
+        add_delta,
+        AggregateDeltas,
+        AggregateIndexes,
+    },
+    index_registry::IndexRegistry,
+};
 use keybroker::Identity;
 use maplit::{
     btreemap,
@@ -94,11 +108,13 @@ use maplit::{
 };
 use tracing::log;
 use value::{
+    DeveloperDocumentId,
     InternalDocumentId,
     TableNamespace,
 };
 
 use crate::{
+    aggregate_index_snapshot,
     metrics::{
         log_index_backfilled,
         log_num_indexes_to_backfill,
@@ -106,12 +122,25 @@ use crate::{
     },
     retention::LeaderRetentionManager,
     Database,
+    IndexModel,
     ResolvedQuery,
+    SchemaModel,
     SystemMetadataModel,
     TableIterator,
+    Transaction,
 };
 
 const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_UNIQUE_INDEX_DUPLICATES_REPORTED: usize = 10;
+
+/// The result of checking a unique index for duplicates before it finishes
+/// backfilling.
+enum UniqueIndexCheck {
+    /// There were no duplicates at this timestamp.
+    Unique(Timestamp),
+    /// Groups of documents with the same indexed values.
+    Duplicates(Vec<Vec<DeveloperDocumentId>>),
+}
 
 static ENTRIES_PER_SECOND: LazyLock<NonZeroU32> = LazyLock::new(|| {
     NonZeroU32::new(
@@ -294,16 +323,25 @@ impl<RT: Runtime> IndexWorker<RT> {
                 }
             }
             let mut to_backfill = vec![];
+            let mut aggregates_to_backfill = vec![];
             for (id, doc) in &index_documents {
                 let index_metadata: ParsedDocument<IndexMetadata<TabletId>> =
                     doc.clone().try_into()?;
-                if let IndexConfig::Database { on_disk_state, .. } = &index_metadata.config {
-                    if matches!(*on_disk_state, DatabaseIndexState::Backfilling(_)) {
-                        to_backfill.push(id.internal_id());
-                    }
+                match &index_metadata.config {
+                    IndexConfig::Database { on_disk_state, .. } => {
+                        if matches!(*on_disk_state, DatabaseIndexState::Backfilling(_)) {
+                            to_backfill.push(id.internal_id());
+                        }
+                    },
+                    IndexConfig::Aggregate { on_disk_state, .. } => {
+                        if matches!(*on_disk_state, DatabaseIndexState::Backfilling(_)) {
+                            aggregates_to_backfill.push(id.internal_id());
+                        }
+                    },
+                    IndexConfig::Search { .. } | IndexConfig::Vector { .. } => {},
                 }
             }
-            let num_to_backfill = to_backfill.len();
+            let num_to_backfill = to_backfill.len() + aggregates_to_backfill.len();
             log::info!(
                 "{num_to_backfill} database indexes to backfill @ {}",
                 tx.begin_timestamp()
@@ -313,6 +351,9 @@ impl<RT: Runtime> IndexWorker<RT> {
                 self.backfill_one(index_id, tx.table_mapping(), index_documents.clone())
                     .await?;
             }
+            for index_id in aggregates_to_backfill {
+                self.backfill_aggregate(index_id).await?;
+            }
             if num_to_backfill > 0 {
                 // We backfilled at least one index during this loop iteration.
                 // There's no point in subscribing, as we'd immediately be woken by our own
@@ -378,6 +419,59 @@ impl<RT: Runtime> IndexWorker<RT> {
         Ok(())
     }
 
+    /// Aggregate indexes have no persisted entries, so backfilling one walks
+    /// the table at a snapshot in rate limited chunks and hands the counts to
+    /// the committer as deltas, which it catches up to the latest commit.
+    /// Commits keep them up to date from then on, so it's immediately safe
+    /// to mark as backfilled, after which they get flushed to persistence.
+    /// The deltas are checkpointed afterwards so restarts don't need to walk
+    /// the table again.
+    async fn backfill_aggregate(&mut self, index_id: IndexId) -> anyhow::Result<()> {
+        log::info!("Starting backfill of aggregate index {index_id}");
+        let ts = self.database.now_ts_for_reads();
+        let index_registry = self.database.snapshot(ts)?.index_registry;
+        let Some(index) = index_registry
+            .all_aggregate_indexes()
+            .into_iter()
+            .find(|index| index.id().internal_id() == index_id)
+        else {
+            log::warn!("Aggregate index {index_id} no longer exists, skipping backfill");
+            return Ok(());
+        };
+        let tablet_id = *index.name.table();
+        let by_id = index_registry.must_get_by_id(tablet_id)?.id();
+
+        let mut deltas = AggregateDeltas::new();
+        let stream = self
+            .database
+            .table_iterator(ts, *INDEX_BACKFILL_CHUNK_SIZE, None)
+            .stream_documents_in_table(tablet_id, by_id, None);
+        pin_mut!(stream);
+        let mut num_documents = 0;
+        while let Some((document, _)) = stream.try_next().await? {
+            if num_documents % *INDEX_BACKFILL_CHUNK_SIZE == 0 {
+                self.index_writer.wait_for_rate_limit().await;
+            }
+            for (id, key, value) in AggregateIndexes::document_keys(&index_registry, &document) {
+                if id == index_id {
+                    add_delta(&mut deltas, key, value, 1);
+                }
+            }
+            num_documents += 1;
+        }
+        log::info!("Computed aggregate index {index_id} from {num_documents} documents at {ts}");
+
+        self.database
+            .load_aggregate_index(index_id, deltas, *ts)
+            .await?;
+        self.finish_backfill(index_id).await?;
+        aggregate_index_snapshot::write_snapshot(
+            self.index_writer.persistence.as_ref(),
+            &self.database.aggregate_index_snapshot(),
+        )
+        .await
+    }
+
     async fn begin_backfill(
         &mut self,
         index_id: IndexId,
@@ -471,6 +565,7 @@ impl<RT: Runtime> IndexWorker<RT> {
     }
 
     async fn finish_backfill(&mut self, index_id: IndexId) -> anyhow::Result<()> {
+        let unique_index_check = self.check_unique_index(index_id).await?;
         // Now that we're done, write that we've finished backfilling the index, sanity
         // checking that it wasn't written concurrently with our backfill.
         let mut tx = self.database.begin(Identity::system()).await?;
@@ -481,6 +576,17 @@ impl<RT: Runtime> IndexWorker<RT> {
             .await?
             .ok_or_else(|| anyhow::anyhow!("Index {index_id:?} no longer exists"))?;
         let mut index_metadata = TabletIndexMetadata::from_document(index_doc)?;
+        let duplicates = match unique_index_check {
+            None => vec![],
+            Some(UniqueIndexCheck::Duplicates(duplicates)) => duplicates,
+            Some(UniqueIndexCheck::Unique(checked_ts)) => {
+                self.find_duplicates_since(&mut tx, &index_metadata.name, checked_ts)
+                    .await?
+            },
+        };
+        if !duplicates.is_empty() {
+            return self.fail_unique_index(tx, index_metadata, duplicates).await;
+        }
         let is_system_index_on_user_table = index_metadata.name.descriptor().is_reserved();
         let is_index_on_system_table = tx
             .table_mapping()
@@ -489,6 +595,10 @@ impl<RT: Runtime> IndexWorker<RT> {
             IndexConfig::Database {
                 ref mut on_disk_state,
                 ..
+            }
+            | IndexConfig::Aggregate {
+                ref mut on_disk_state,
+                ..
             } => {
                 anyhow::ensure!(
                     matches!(*on_disk_state, DatabaseIndexState::Backfilling(_)),
@@ -520,6 +630,189 @@ impl<RT: Runtime> IndexWorker<RT> {
         log_index_backfilled();
         Ok(())
     }
+
+    /// Writes to backfilled and enabled unique indexes are checked in the
+    /// transaction, but documents written before the index finishes
+    /// backfilling are only checked here, in pages at a snapshot, before it's
+    /// marked as backfilled. Returns `None` if the index isn't unique.
+    async fn check_unique_index(
+        &mut self,
+        index_id: IndexId,
+    ) -> anyhow::Result<Option<UniqueIndexCheck>> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let index_table_id = tx.bootstrap_tables().index_id;
+        let index_doc = tx
+            .get(ResolvedDocumentId::new(index_table_id, index_id))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Index {index_id:?} no longer exists"))?;
+        let index_metadata = TabletIndexMetadata::from_document(index_doc)?;
+        let IndexConfig::Database {
+            ref developer_config,
+            ..
+        } = index_metadata.config
+        else {
+            return Ok(None);
+        };
+        if !developer_config.unique {
+            return Ok(None);
+        }
+        let ts = self.database.now_ts_for_reads();
+        let duplicates = self
+            .find_duplicates(ts, *index_metadata.name.table(), index_id, developer_config)
+            .await?;
+        if duplicates.is_empty() {
+            return Ok(Some(UniqueIndexCheck::Unique(*ts)));
+        }
+        log::info!(
+            "Unique index {} has {} groups of duplicate documents",
+            index_metadata.name,
+            duplicates.len()
+        );
+        Ok(Some(UniqueIndexCheck::Duplicates(duplicates)))
+    }
+
+    /// Checks the documents written to the index's table after `checked_ts`
+    /// against the index in `tx`. `tx` depends on the whole index, so a write
+    /// made before it commits makes it retry rather than go unchecked.
+    async fn find_duplicates_since(
+        &self,
+        tx: &mut Transaction<RT>,
+        index_name: &TabletIndexName,
+        checked_ts: Timestamp,
+    ) -> anyhow::Result<Vec<Vec<DeveloperDocumentId>>> {
+        tx.record_pending_index_read(index_name)?;
+        let snapshot_ts = *tx.begin_timestamp();
+        if snapshot_ts <= checked_ts {
+            return Ok(vec![]);
+        }
+        let rate_limiter =
+            new_rate_limiter(self.runtime.clone(), Quota::per_second(*ENTRIES_PER_SECOND));
+        let mut latest = BTreeMap::new();
+        let mut documents = self.database.load_documents_in_table(
+            *index_name.table(),
+            TimestampRange::new((Bound::Excluded(checked_ts), Bound::Included(snapshot_ts)))?,
+            Order::Asc,
+            &rate_limiter,
+        );
+        while let Some((_, id, document)) = documents.try_next().await? {
+            latest.insert(id, document);
+        }
+        let mut duplicates = vec![];
+        for document in latest.into_values().flatten() {
+            if let Some(existing_id) = tx.pending_index_duplicate(index_name, &document).await? {
+                duplicates.push(vec![existing_id, document.developer_id()]);
+                if duplicates.len() >= MAX_UNIQUE_INDEX_DUPLICATES_REPORTED {
+                    break;
+                }
+            }
+        }
+        Ok(duplicates)
+    }
+
+    /// Fails the pending schema that added a unique index with duplicates so
+    /// the push reports them, and drops the index since it can never be
+    /// enabled.
+    async fn fail_unique_index(
+        &mut self,
+        mut tx: Transaction<RT>,
+        index_metadata: ParsedDocument<TabletIndexMetadata>,
+        duplicates: Vec<Vec<DeveloperDocumentId>>,
+    ) -> anyhow::Result<()> {
+        let tablet_id = *index_metadata.name.table();
+        let table_name = tx.table_mapping().tablet_name(tablet_id)?;
+        let namespace = tx.table_mapping().tablet_namespace(tablet_id)?;
+        let index_descriptor = index_metadata.name.descriptor().clone();
+        let mut schema_model = SchemaModel::new(&mut tx, namespace);
+        for state in [SchemaState::Pending, SchemaState::Validated] {
+            let Some((schema_id, schema)) = schema_model.get_by_state(state).await? else {
+                continue;
+            };
+            let adds_unique_index = schema
+                .tables
+                .get(&table_name)
+                .and_then(|table| table.indexes.get(&index_descriptor))
+                .is_some_and(|index| index.unique);
+            if adds_unique_index {
+                schema_model
+                    .mark_failed(
+                        schema_id,
+                        SchemaValidationError::UniqueIndexHasDuplicates {
+                            table_name: table_name.clone(),
+                            index_descriptor: index_descriptor.clone(),
+                            duplicates: duplicates.clone(),
+                        },
+                    )
+                    .await?;
+            }
+        }
+        IndexModel::new(&mut tx)
+            .drop_index(index_metadata.id())
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "index_worker_unique_index_duplicates")
+            .await?;
+        log::info!(
+            "Dropped unique index {} with duplicates",
+            index_metadata.name
+        );
+        Ok(())
+    }
+
+    /// Walks the index in order and returns up to
+    /// `MAX_UNIQUE_INDEX_DUPLICATES_REPORTED` groups of documents with equal
+    /// indexed values. Documents missing an indexed field or excluded by the
+    /// index's filter are ignored.
+    async fn find_duplicates(
+        &self,
+        ts: RepeatableTimestamp,
+        tablet_id: TabletId,
+        index_id: IndexId,
+        developer_config: &DeveloperDatabaseIndexConfig,
+    ) -> anyhow::Result<Vec<Vec<DeveloperDocumentId>>> {
+        let fields = &developer_config.fields;
+        let table_iterator = self
+            .database
+            .table_iterator(ts, *INDEX_BACKFILL_CHUNK_SIZE, None);
+        let stream = table_iterator.stream_documents_in_table_by_index(
+            tablet_id,
+            index_id,
+            fields.clone(),
+            None,
+        );
+        pin_mut!(stream);
+        let mut duplicates = vec![];
+        let mut previous: Option<(IndexKey, Vec<DeveloperDocumentId>)> = None;
+        while let Some((_, _, document)) = stream.try_next().await? {
+            if !developer_config.includes(&document) {
+                continue;
+            }
+            let index_key = document.index_key(&fields[..], self.persistence_version);
+            if index_key.indexed_values().iter().any(Option::is_none) {
+                continue;
+            }
+            if let Some((previous_key, ids)) = &mut previous
+                && previous_key.indexed_values() == index_key.indexed_values()
+            {
+                ids.push(document.developer_id());
+                continue;
+            }
+            if let Some((_, ids)) = previous.take()
+                && ids.len() > 1
+            {
+                duplicates.push(ids);
+                if duplicates.len() >= MAX_UNIQUE_INDEX_DUPLICATES_REPORTED {
+                    return Ok(duplicates);
+                }
+            }
+            previous = Some((index_key, vec![document.developer_id()]));
+        }
+        if let Some((_, ids)) = previous
+            && ids.len() > 1
+        {
+            duplicates.push(ids);
+        }
+        Ok(duplicates)
+    }
 }
 
 impl<RT: Runtime> IndexWriter<RT> {
@@ -541,6 +834,18 @@ impl<RT: Runtime> IndexWriter<RT> {
         }
     }
 
+    /// Waits until the rate limiter allows another chunk of
+    /// `INDEX_BACKFILL_CHUNK_SIZE` entries.
+    async fn wait_for_rate_limit(&self) {
+        while let Err(not_until) = self.rate_limiter.check() {
+            // NB: We can't use `RateLimiter`'s async API since it internally relies on
+            // `futures-timer`. These timers will never get satisfied under our test
+            // runtime.
+            let delay = not_until.wait_time_from(self.runtime.monotonic_now().as_nanos());
+            self.runtime.wait(delay).await;
+        }
+    }
+
     /// Backfill in two steps: first a snapshot at the current time, and then
     /// walking the log. After the current snapshot is backfilled, index
     /// snapshot reads at >=ts are valid. The subsequent walking of the log
@@ -879,13 +1184,7 @@ impl<RT: Runtime> IndexWriter<RT> {
             // There are potentially more document revisions, so start a new chunk. First,
             // check with the rate limiter upfront to ensure we're allowed to
             // continue.
-            while let Err(not_until) = self.rate_limiter.check() {
-                // NB: We can't use `RateLimiter`'s async API since it internally relies on
-                // `futures-timer`. These timers will never get satisfied under our test
-                // runtime.
-                let delay = not_until.wait_time_from(self.runtime.monotonic_now().as_nanos());
-                self.runtime.wait(delay).await;
-            }
+            self.wait_for_rate_limit().await;
 
             // Try to fill up a full chunk until we exhaust the stream or fill the chunk.
             let mut chunk = BTreeSet::new();
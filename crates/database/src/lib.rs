@@ -17,6 +17,7 @@
 #![feature(cow_is_borrowed)]
 #![feature(try_find)]
 
+pub mod aggregate_index_snapshot;
 mod bootstrap_model;
 mod committer;
 mod database;
@@ -26,6 +27,7 @@ mod index_workers;
 mod metrics;
 pub mod patch;
 pub mod persistence_helpers;
+mod point_in_time;
 mod preloaded;
 pub mod query;
 pub mod reads;
@@ -95,6 +97,13 @@ pub use writes::{
 
 pub use self::{
     bootstrap_model::{
+        aggregate_index_counts::{
+            AggregateIndexCount,
+            AggregateIndexCountsModel,
+            AggregateIndexCountsTable,
+            AggregateIndexNode,
+            AGGREGATE_INDEX_COUNTS_TABLE,
+        },
         components::{
             definition::{
                 ComponentDefinitionsTable,
@@ -159,6 +168,13 @@ pub use self::{
         IndexSelector,
         IndexWriter,
     },
+    point_in_time::{
+        DocumentHistoryPage,
+        DocumentRevision,
+        HistoricalQueryPage,
+        RestoreTableBatch,
+        RestoreTableCounts,
+    },
     query::{
         soft_data_limit,
         DeveloperQuery,
@@ -171,6 +187,7 @@ pub use self::{
         RetentionType,
     },
     snapshot_manager::{
+        CountSnapshot,
         Snapshot,
         TableSummaries,
     },
@@ -0,0 +1,687 @@
+//! Reading and querying user tables as they were at a past timestamp,
+//! restoring tables to that state, and listing a document's past revisions.
+//! These only work within the retention window: older revisions may already
+//! have been deleted by retention.
+
+use std::{
+    cmp::Ordering,
+    collections::BTreeSet,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::index::{
+        database_index::{
+            DatabaseIndexState,
+            DeveloperDatabaseIndexConfig,
+            IndexedFields,
+        },
+        IndexConfig,
+        TabletIndexMetadata,
+    },
+    document::ResolvedDocument,
+    index::{
+        IndexKey,
+        IndexKeyBytes,
+    },
+    persistence::{
+        RepeatablePersistence,
+        RetentionValidator,
+        TableRetentionValidator,
+    },
+    query::{
+        Expression,
+        FullTableScan,
+        IndexRange,
+        Query,
+        QueryOperator,
+        QuerySource,
+    },
+    runtime::Runtime,
+    types::{
+        IndexId,
+        IndexName,
+        RepeatableTimestamp,
+        TableName,
+        Timestamp,
+    },
+};
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
+use futures::{
+    pin_mut,
+    TryStreamExt,
+};
+use keybroker::Identity;
+use value::{
+    DeveloperDocumentId,
+    ResolvedDocumentId,
+    TableMapping,
+    TableNamespace,
+    TabletId,
+    TabletIdAndTableNumber,
+};
+
+use crate::{
+    bootstrap_model::user_facing::check_admin_key_table,
+    database::unauthorized_error,
+    Database,
+    ImportFacingModel,
+    IndexModel,
+    SchemaModel,
+    SnapshotPage,
+    TableIterator,
+    Transaction,
+    WriteSource,
+};
+
+/// Number of documents restored per transaction.
+const RESTORE_BATCH_SIZE: usize = 100;
+
+/// Most documents a restore batch walks past, so that a batch of a mostly
+/// unchanged table doesn't read the whole table.
+const RESTORE_MAX_WALKED: usize = 1000;
+
+/// Most index entries a page of a historical query scans, so that a selective
+/// filter can't make a single page read the whole table.
+const HISTORICAL_QUERY_MAX_SCANNED: usize = 10_000;
+
+/// A page of the documents matching a query at a past timestamp.
+#[derive(Debug)]
+pub struct HistoricalQueryPage {
+    /// Each document with the timestamp of its revision, in index order.
+    pub documents: Vec<(Timestamp, ResolvedDocument)>,
+    /// The last index key scanned. Pass as `cursor` to continue after it.
+    pub cursor: Option<IndexKeyBytes>,
+    /// False once the whole range has been scanned.
+    pub has_more: bool,
+}
+
+/// How many documents a batch of a restore changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RestoreTableCounts {
+    /// Documents that had been deleted since the timestamp.
+    pub inserted: u64,
+    /// Documents that had been modified since the timestamp.
+    pub replaced: u64,
+    /// Documents that had been inserted since the timestamp.
+    pub deleted: u64,
+}
+
+#[derive(Debug)]
+pub struct RestoreTableBatch {
+    pub counts: RestoreTableCounts,
+    /// The last document the batch walked. Pass as `cursor` to restore the
+    /// next batch.
+    pub cursor: Option<DeveloperDocumentId>,
+    /// True once the whole table has been restored.
+    pub done: bool,
+}
+
+/// A revision of a document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentRevision {
+    pub ts: Timestamp,
+    /// `None` if the revision deleted the document.
+    pub document: Option<ResolvedDocument>,
+    /// What wrote the revision, if it's recent enough to still be in the
+    /// write log.
+    pub write_source: Option<WriteSource>,
+}
+
+#[derive(Debug)]
+pub struct DocumentHistoryPage {
+    /// Newest first.
+    pub revisions: Vec<DocumentRevision>,
+    /// Pass as `cursor` to continue with older revisions.
+    pub cursor: Option<Timestamp>,
+    /// False once the oldest retained revision has been returned.
+    pub has_more: bool,
+}
+
+impl<RT: Runtime> Database<RT> {
+    /// Lists the documents in `table_name` as of `ts`, in `_id` order and
+    /// starting after `cursor`. Each document is returned with the timestamp
+    /// of its revision.
+    pub async fn list_historical_documents(
+        &self,
+        identity: Identity,
+        ts: Timestamp,
+        table_name: TableName,
+        cursor: Option<DeveloperDocumentId>,
+        limit: usize,
+    ) -> anyhow::Result<SnapshotPage> {
+        anyhow::ensure!(
+            identity.is_system() || identity.is_admin(),
+            unauthorized_error("list_historical_documents")
+        );
+        let snapshot = self.historical_snapshot(ts)?;
+        let table_mapping = self.snapshot_table_mapping(snapshot).await?;
+        let tablet_id = user_table_at(&table_mapping, &table_name, ts)?.tablet_id;
+        check_admin_key_table(&identity, &table_name)?;
+        let page = self
+            .list_snapshot(identity, Some(ts), cursor, Some(table_name), limit, limit)
+            .await
+            .map_err(|e| out_of_retention_error(e, ts))?;
+        self.validate_historical_snapshot(tablet_id, ts).await?;
+        Ok(page)
+    }
+
+    /// Runs `query` against the documents as of `ts`, returning up to `limit`
+    /// matches from the part of its range after `cursor`. Index ranges must
+    /// use an index that was enabled at `ts`, and a full table scan reads in
+    /// `_creationTime` order. Filters are applied to each scanned document,
+    /// while limits aren't supported since the results are paginated.
+    pub async fn query_historical_documents(
+        &self,
+        identity: Identity,
+        ts: Timestamp,
+        query: Query,
+        cursor: Option<IndexKeyBytes>,
+        limit: usize,
+    ) -> anyhow::Result<HistoricalQueryPage> {
+        anyhow::ensure!(
+            identity.is_system() || identity.is_admin(),
+            unauthorized_error("query_historical_documents")
+        );
+        let index_range = match query.source {
+            QuerySource::FullTableScan(FullTableScan { table_name, order }) => IndexRange {
+                index_name: IndexName::by_creation_time(table_name),
+                range: vec![],
+                order,
+            },
+            QuerySource::IndexRange(index_range) => index_range,
+            QuerySource::Search(_) => anyhow::bail!(invalid_historical_query_error(
+                "Search queries can't be run at a past timestamp"
+            )),
+        };
+        let filters = query
+            .operators
+            .into_iter()
+            .map(|operator| match operator {
+                QueryOperator::Filter(expression) => Ok(expression),
+                QueryOperator::Limit(_) => anyhow::bail!(invalid_historical_query_error(
+                    "Historical queries are paginated, so they can't have a limit"
+                )),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let order = index_range.order;
+
+        let snapshot = self.historical_snapshot(ts)?;
+        let table_mapping = self.snapshot_table_mapping(snapshot).await?;
+        let table_name = index_range.index_name.table().clone();
+        let tablet_id = user_table_at(&table_mapping, &table_name, ts)?.tablet_id;
+        check_admin_key_table(&identity, &table_name)?;
+        let (index_id, indexed_fields, index_filter) = self
+            .historical_index(snapshot, tablet_id, &index_range.index_name)
+            .await
+            .map_err(|e| out_of_retention_error(e, ts))?;
+        let interval = index_range.compile(indexed_fields, index_filter.as_ref(), None)?;
+        let interval = match cursor {
+            Some(cursor) if interval.contains(&cursor.0) => interval.split_after(cursor, order).1,
+            Some(_) => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidCursor",
+                "Cursor is outside of the query's range"
+            )),
+            None => interval,
+        };
+
+        let persistence = RepeatablePersistence::new(
+            self.reader.clone(),
+            snapshot,
+            self.table_retention_validator(tablet_id),
+        );
+        let persistence_snapshot = persistence
+            .read_snapshot(snapshot)
+            .map_err(|e| out_of_retention_error(e, ts))?;
+        let stream = persistence_snapshot.index_scan(index_id, tablet_id, &interval, order, limit);
+        pin_mut!(stream);
+        let mut documents = vec![];
+        let mut cursor = None;
+        let mut scanned = 0;
+        let mut has_more = true;
+        while documents.len() < limit && scanned < HISTORICAL_QUERY_MAX_SCANNED {
+            let Some((key, revision_ts, document)) = stream
+                .try_next()
+                .await
+                .map_err(|e| out_of_retention_error(e, ts))?
+            else {
+                has_more = false;
+                break;
+            };
+            scanned += 1;
+            cursor = Some(key);
+            let mut matches = true;
+            for filter in &filters {
+                if !filter.eval(&document.value().0)?.into_boolean()? {
+                    matches = false;
+                    break;
+                }
+            }
+            if matches {
+                documents.push((revision_ts, document));
+            }
+        }
+        self.validate_historical_snapshot(tablet_id, ts).await?;
+        Ok(HistoricalQueryPage {
+            documents,
+            cursor,
+            has_more,
+        })
+    }
+
+    /// Gets the revision of the document `id` as of `ts`, with the timestamp
+    /// it was written at, or `None` if it didn't exist then.
+    pub async fn get_historical_document(
+        &self,
+        identity: Identity,
+        ts: Timestamp,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<(Timestamp, ResolvedDocument)>> {
+        anyhow::ensure!(
+            identity.is_system() || identity.is_admin(),
+            unauthorized_error("get_historical_document")
+        );
+        let snapshot = self.historical_snapshot(ts)?;
+        let table_mapping = self.snapshot_table_mapping(snapshot).await?;
+        let Some(table_name) = table_mapping
+            .namespace(TableNamespace::by_component_TODO())
+            .name_by_number_if_exists(*id.table())
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        let tablet_id = user_table_at(&table_mapping, &table_name, ts)?.tablet_id;
+        check_admin_key_table(&identity, &table_name)?;
+        let by_id_indexes = self.snapshot_by_id_indexes(snapshot).await?;
+        let by_id = *by_id_indexes
+            .get(&tablet_id)
+            .with_context(|| format!("by_id index for {tablet_id:?} missing"))?;
+        let persistence = RepeatablePersistence::new(
+            self.reader.clone(),
+            snapshot,
+            self.table_retention_validator(tablet_id),
+        );
+        let document = persistence
+            .read_snapshot(snapshot)
+            .map_err(|e| out_of_retention_error(e, ts))?
+            .index_get(by_id, tablet_id, IndexKey::new(vec![], id))
+            .await
+            .map_err(|e| out_of_retention_error(e, ts))?;
+        self.validate_historical_snapshot(tablet_id, ts).await?;
+        Ok(document)
+    }
+
+    /// Lists up to `limit` revisions of the document `id`, newest first,
+    /// starting with the last revision before `cursor` or with the latest
+    /// one. The history ends at the table's retention window, since older
+    /// revisions may have been deleted.
+    pub async fn document_history(
+        &self,
+        identity: Identity,
+        id: DeveloperDocumentId,
+        cursor: Option<Timestamp>,
+        limit: usize,
+    ) -> anyhow::Result<DocumentHistoryPage> {
+        anyhow::ensure!(
+            identity.is_system() || identity.is_admin(),
+            unauthorized_error("document_history")
+        );
+        let snapshot = self.now_ts_for_reads();
+        let table_mapping = self.snapshot_table_mapping(snapshot).await?;
+        let table_name = table_mapping
+            .namespace(TableNamespace::by_component_TODO())
+            .name_by_number_if_exists(*id.table())
+            .cloned()
+            .context(ErrorMetadata::bad_request(
+                "TableNotFound",
+                format!("Table for document {} doesn't exist", id.encode()),
+            ))?;
+        let tablet_id = user_table_at(&table_mapping, &table_name, *snapshot)?.tablet_id;
+        check_admin_key_table(&identity, &table_name)?;
+        let mut before = match cursor {
+            Some(cursor) if cursor > snapshot.succ()? => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidCursor",
+                format!("Invalid cursor {cursor}")
+            )),
+            Some(cursor) => cursor,
+            None => snapshot.succ()?,
+        };
+        let retention_validator = self.table_retention_validator(tablet_id);
+        let persistence =
+            RepeatablePersistence::new(self.reader.clone(), snapshot, retention_validator.clone());
+        let document_id = tablet_id.id(id.internal_id());
+        // Walk back one revision past `limit` to find out if there are more.
+        let mut revisions = vec![];
+        while revisions.len() <= limit {
+            let previous: anyhow::Result<_> = try {
+                let previous = persistence
+                    .previous_revisions(BTreeSet::from([(document_id, before)]))
+                    .await?;
+                retention_validator
+                    .validate_table_document_snapshot(tablet_id, before)
+                    .await?;
+                previous
+            };
+            let mut previous = match previous {
+                Ok(previous) => previous,
+                Err(e) if e.is_out_of_retention() => break,
+                Err(e) => return Err(e),
+            };
+            let Some((ts, document)) = previous.remove(&(document_id, before)) else {
+                break;
+            };
+            revisions.push(DocumentRevision {
+                ts,
+                document,
+                write_source: self.log.write_source(ts),
+            });
+            before = ts;
+        }
+        let has_more = revisions.len() > limit;
+        revisions.truncate(limit);
+        Ok(DocumentHistoryPage {
+            cursor: revisions.last().map(|revision| revision.ts),
+            revisions,
+            has_more,
+        })
+    }
+
+    /// Checks that each of `table_names` can be restored to its state as of
+    /// `ts`, before starting a restore with [`Self::restore_table_batch`].
+    pub async fn check_restore_tables(
+        &self,
+        identity: Identity,
+        ts: Timestamp,
+        table_names: &BTreeSet<TableName>,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            identity.is_system() || identity.is_admin(),
+            unauthorized_error("restore_tables")
+        );
+        let snapshot = self.historical_snapshot(ts)?;
+        let historical_mapping = self.snapshot_table_mapping(snapshot).await?;
+        let mut tx = self.begin(identity.clone()).await?;
+        for table_name in table_names {
+            let table_id = user_table_at(&historical_mapping, table_name, ts)?;
+            check_admin_key_table(&identity, table_name)?;
+            self.validate_historical_snapshot(table_id.tablet_id, ts)
+                .await?;
+            check_restorable(&mut tx, ts, table_name, table_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Restores the next batch of `table_name` after `cursor` to its state as
+    /// of `ts`, writing the documents that changed since then to `tx` as new
+    /// revisions: documents deleted since are inserted again with the same
+    /// `_id` and `_creationTime`, modified documents are replaced with their
+    /// old value and documents inserted since are deleted.
+    ///
+    /// The table is walked as of `ts` and as of `tx`'s snapshot, both in `_id`
+    /// order, so a batch only writes the documents that differ. Batches aren't
+    /// atomic with each other, so concurrent writes to the table may be
+    /// overwritten. `ts` is checked against the retention window after the
+    /// batch is written, and the caller must commit `tx` right away, so a
+    /// restore never writes documents read from a snapshot retention has
+    /// started deleting.
+    ///
+    /// Deleting documents would apply the `onDelete` behavior of the schema's
+    /// references, so tables that other tables reference can't be restored.
+    pub async fn restore_table_batch(
+        &self,
+        tx: &mut Transaction<RT>,
+        ts: Timestamp,
+        table_name: &TableName,
+        cursor: Option<DeveloperDocumentId>,
+    ) -> anyhow::Result<RestoreTableBatch> {
+        let snapshot = self.historical_snapshot(ts)?;
+        let historical_mapping = self.snapshot_table_mapping(snapshot).await?;
+        let table_id = user_table_at(&historical_mapping, table_name, ts)?;
+        check_restorable(tx, ts, table_name, table_id).await?;
+        let cursor = match cursor {
+            Some(cursor) if *cursor.table() == table_id.table_number => {
+                Some(ResolvedDocumentId::new(table_id, cursor.internal_id()))
+            },
+            Some(cursor) => anyhow::bail!(
+                "Restore cursor {} isn't in table \"{table_name}\"",
+                cursor.encode()
+            ),
+            None => None,
+        };
+
+        let historical_by_id = *self
+            .snapshot_by_id_indexes(snapshot)
+            .await?
+            .get(&table_id.tablet_id)
+            .with_context(|| format!("by_id index for {table_id:?} missing at {ts}"))?;
+        let current_by_id = IndexModel::new(tx)
+            .by_id_index_metadata(table_id.tablet_id)
+            .await?
+            .id()
+            .internal_id();
+        let historical = TableIterator::new(
+            self.runtime.clone(),
+            snapshot,
+            self.reader.clone(),
+            self.table_retention_validator(table_id.tablet_id),
+            RESTORE_BATCH_SIZE,
+            None,
+        )
+        .stream_documents_in_table(table_id.tablet_id, historical_by_id, cursor);
+        let current = self
+            .table_iterator(tx.begin_timestamp(), RESTORE_BATCH_SIZE, None)
+            .stream_documents_in_table(table_id.tablet_id, current_by_id, cursor);
+        pin_mut!(historical);
+        pin_mut!(current);
+
+        // Collect the documents to write, where `None` means the document
+        // didn't exist at `ts`, up to a batch of them or until enough unchanged
+        // documents have been walked past.
+        let mut batch = vec![];
+        let mut walked = 0;
+        let mut cursor = cursor;
+        let mut historical_doc = historical
+            .try_next()
+            .await
+            .map_err(|e| out_of_retention_error(e, ts))?;
+        let mut current_doc = current.try_next().await?;
+        let done = loop {
+            if batch.len() >= RESTORE_BATCH_SIZE || walked >= RESTORE_MAX_WALKED {
+                break false;
+            }
+            let order = match (&historical_doc, &current_doc) {
+                (None, None) => break true,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((historical, _)), Some((current, _))) => historical.id().cmp(&current.id()),
+            };
+            walked += 1;
+            match order {
+                Ordering::Less => {
+                    let (historical, _) = historical_doc.take().context("missing document")?;
+                    cursor = Some(historical.id());
+                    batch.push((historical.id(), Some(historical)));
+                },
+                Ordering::Greater => {
+                    let (current, _) = current_doc.take().context("missing document")?;
+                    cursor = Some(current.id());
+                    batch.push((current.id(), None));
+                },
+                Ordering::Equal => {
+                    let (historical, _) = historical_doc.take().context("missing document")?;
+                    let (current, _) = current_doc.take().context("missing document")?;
+                    cursor = Some(historical.id());
+                    if historical.value() != current.value() {
+                        batch.push((historical.id(), Some(historical)));
+                    }
+                },
+            }
+            if historical_doc.is_none() {
+                historical_doc = historical
+                    .try_next()
+                    .await
+                    .map_err(|e| out_of_retention_error(e, ts))?;
+            }
+            if current_doc.is_none() {
+                current_doc = current.try_next().await?;
+            }
+        };
+
+        let mut counts = RestoreTableCounts::default();
+        for (id, historical) in batch {
+            // Read the latest revision, which may have changed since the table
+            // was walked.
+            let current = tx.get(id).await?;
+            match (current, historical) {
+                (None, None) => {},
+                (Some(_), None) => {
+                    tx.delete_inner(id).await?;
+                    counts.deleted += 1;
+                },
+                (Some(current), Some(historical)) => {
+                    if current.value() != historical.value() {
+                        tx.replace_inner(id, historical.into_value().0).await?;
+                        counts.replaced += 1;
+                    }
+                },
+                (None, Some(historical)) => {
+                    let table_mapping = tx.table_mapping().clone();
+                    ImportFacingModel::new(tx)
+                        .insert(
+                            table_id,
+                            table_name,
+                            historical.into_value().0,
+                            &table_mapping,
+                        )
+                        .await?;
+                    counts.inserted += 1;
+                },
+            }
+        }
+        // The documents were read as of `ts`, so only write them if `ts` is
+        // still within the table's retention.
+        self.validate_historical_snapshot(table_id.tablet_id, ts)
+            .await?;
+        Ok(RestoreTableBatch {
+            counts,
+            cursor: cursor.map(DeveloperDocumentId::from),
+            done,
+        })
+    }
+
+    /// Checks that `ts` isn't in the future and may be within retention,
+    /// before reading at it.
+    fn historical_snapshot(&self, ts: Timestamp) -> anyhow::Result<RepeatableTimestamp> {
+        let snapshot = self
+            .now_ts_for_reads()
+            .prior_ts(ts)
+            .context(ErrorMetadata::bad_request(
+                "SnapshotTooNew",
+                format!("Timestamp {ts} is in the future."),
+            ))?;
+        self.retention_validator()
+            .optimistic_validate_snapshot(ts)
+            .map_err(|e| out_of_retention_error(e, ts))?;
+        Ok(snapshot)
+    }
+
+    /// Validates reads of `tablet_id` against the table's own retention,
+    /// which may delete its history sooner or later than the default.
+    fn table_retention_validator(&self, tablet_id: TabletId) -> Arc<dyn RetentionValidator> {
+        Arc::new(TableRetentionValidator::new(
+            self.retention_validator(),
+            tablet_id,
+        ))
+    }
+
+    /// Checks that `ts` was still within the retention of `tablet_id` after
+    /// reading it at `ts`.
+    async fn validate_historical_snapshot(
+        &self,
+        tablet_id: TabletId,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        self.retention_validator()
+            .validate_table_snapshot(tablet_id, ts)
+            .await
+            .map_err(|e| out_of_retention_error(e, ts))
+    }
+}
+
+/// Checks that `table_name` is still the table it was at `ts`, and that no
+/// table references it, since restoring it deletes documents.
+async fn check_restorable<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    ts: Timestamp,
+    table_name: &TableName,
+    table_id: TabletIdAndTableNumber,
+) -> anyhow::Result<()> {
+    let namespace = TableNamespace::by_component_TODO();
+    let current_tablet_id = tx
+        .table_mapping()
+        .namespace(namespace)
+        .id_if_exists(table_name);
+    if current_tablet_id != Some(table_id.tablet_id) {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "RestoreTableReplaced",
+            format!(
+                "Table \"{table_name}\" has been deleted or replaced since {ts}, so it can't be \
+                 restored"
+            ),
+        ));
+    }
+    let referencing = SchemaModel::new(tx, namespace)
+        .tables_referencing(table_name)
+        .await?;
+    if let Some(referencing) = referencing.first() {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "RestoreTableReferenced",
+            format!(
+                "Table \"{table_name}\" can't be restored because the schema declares a reference \
+                 to it from table \"{referencing}\", and restoring would apply the reference's \
+                 `onDelete` to the documents it deletes. Remove the reference from the schema to \
+                 restore the table."
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Looks up the user table `table_name` in the table mapping as of `ts`.
+fn user_table_at(
+    table_mapping: &TableMapping,
+    table_name: &TableName,
+    ts: Timestamp,
+) -> anyhow::Result<TabletIdAndTableNumber> {
+    let table_id = table_mapping
+        .namespace(TableNamespace::by_component_TODO())
+        .id_and_number_if_exists(table_name);
+    match table_id {
+        Some(table_id) if !table_name.is_system() => Ok(table_id),
+        _ => anyhow::bail!(ErrorMetadata::bad_request(
+            "TableNotFoundAtTimestamp",
+            format!("Table \"{table_name}\" didn't exist at {ts}"),
+        )),
+    }
+}
+
+fn invalid_historical_query_error(msg: &'static str) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidHistoricalQuery", msg)
+}
+
+/// Turns retention's internal error into one that tells the caller to pick a
+/// more recent timestamp.
+fn out_of_retention_error(e: anyhow::Error, ts: Timestamp) -> anyhow::Error {
+    if e.is_out_of_retention() {
+        e.context(ErrorMetadata::bad_request(
+            "TimestampOutOfRetention",
+            format!("Timestamp {ts} is older than the retained history"),
+        ))
+    } else {
+        e
+    }
+}
@@ -131,13 +131,13 @@ impl IndexRange {
             maximum_bytes_read,
             soft_maximum_rows_read: soft_data_limit(
                 maximum_rows_read
-                    .unwrap_or(*TRANSACTION_MAX_READ_SIZE_ROWS)
-                    .min(*TRANSACTION_MAX_READ_SIZE_ROWS),
+                    .unwrap_or(usize::MAX)
+                    .min(TRANSACTION_MAX_READ_SIZE_ROWS.get()),
             ),
             soft_maximum_bytes_read: soft_data_limit(
                 maximum_bytes_read
-                    .unwrap_or(*TRANSACTION_MAX_READ_SIZE_BYTES)
-                    .min(*TRANSACTION_MAX_READ_SIZE_BYTES),
+                    .unwrap_or(usize::MAX)
+                    .min(TRANSACTION_MAX_READ_SIZE_BYTES.get()),
             ),
             version,
         }
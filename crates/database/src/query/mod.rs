@@ -8,7 +8,10 @@ use anyhow::Context;
 use async_trait::async_trait;
 use common::{
     bootstrap_model::index::{
-        database_index::IndexedFields,
+        database_index::{
+            DeveloperDatabaseIndexConfig,
+            IndexedFields,
+        },
         INDEX_TABLE,
     },
     document::{
@@ -315,10 +318,12 @@ impl<RT: Runtime> DeveloperQuery<RT> {
         };
         let stable_index_name =
             IndexModel::new(tx).stable_index_name(namespace, &index_name, table_filter)?;
-        let indexed_fields = match query.source {
-            QuerySource::FullTableScan(_) => IndexedFields::creation_time(),
+        let (indexed_fields, index_filter) = match query.source {
+            QuerySource::FullTableScan(_) => (IndexedFields::creation_time(), None),
             QuerySource::IndexRange(_) => {
-                IndexModel::new(tx).indexed_fields(&stable_index_name, &index_name)?
+                let DeveloperDatabaseIndexConfig { fields, filter, .. } =
+                    IndexModel::new(tx).database_index_config(&stable_index_name, &index_name)?;
+                (fields, filter)
             },
             QuerySource::Search(_) => {
                 // Hack! Search indexes don't have any concept of indexed fields.
@@ -326,7 +331,7 @@ impl<RT: Runtime> DeveloperQuery<RT> {
                 // because the order of the fields changes the query result.
                 // Search query results don't depend on the index used so we
                 // can just an empty list of fields.
-                IndexedFields::try_from(Vec::new())?
+                (IndexedFields::try_from(Vec::new())?, None)
             },
         };
         let fingerprint = query.fingerprint(&indexed_fields)?;
@@ -366,7 +371,11 @@ impl<RT: Runtime> DeveloperQuery<RT> {
                 let virtual_table_mapping = tx.virtual_table_mapping().clone();
                 let virtual_table_number_map = stable_index_name
                     .virtual_table_number_map(tx.table_mapping(), &virtual_table_mapping)?;
-                let interval = index_range.compile(indexed_fields, virtual_table_number_map)?;
+                let interval = index_range.compile(
+                    indexed_fields,
+                    index_filter.as_ref(),
+                    virtual_table_number_map,
+                )?;
                 QueryNode::IndexRange(IndexRange::new(
                     stable_index_name,
                     index_name,
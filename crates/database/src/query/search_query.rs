@@ -202,7 +202,7 @@ impl SearchResultIterator {
 
     fn is_approaching_data_limit(&self) -> bool {
         let soft_maximum_rows_read = soft_data_limit(MAX_CANDIDATE_REVISIONS);
-        let soft_maximum_bytes_read = soft_data_limit(*TRANSACTION_MAX_READ_SIZE_BYTES);
+        let soft_maximum_bytes_read = soft_data_limit(TRANSACTION_MAX_READ_SIZE_BYTES.get());
         self.next_index > soft_maximum_rows_read || self.bytes_read > soft_maximum_bytes_read
     }
 
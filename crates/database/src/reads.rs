@@ -4,6 +4,7 @@ use std::{
     sync::LazyLock,
 };
 
+use anyhow::Context;
 use cmd_util::env::env_config;
 use common::{
     bootstrap_model::index::database_index::IndexedFields,
@@ -362,25 +363,27 @@ impl TransactionReadSet {
         tx_size.total_document_size += document_size;
 
         if !is_system_table {
+            let max_rows = TRANSACTION_MAX_READ_SIZE_ROWS.get();
+            let max_bytes = TRANSACTION_MAX_READ_SIZE_BYTES.get();
             anyhow::ensure!(
-                tx_size.total_document_count <= *TRANSACTION_MAX_READ_SIZE_ROWS,
+                tx_size.total_document_count <= max_rows,
                 ErrorMetadata::pagination_limit(
                     "TooManyDocumentsRead",
                     format!(
                         "Too many documents read in a single function execution (limit: {}). \
                          {OVER_LIMIT_HELP}",
-                        *TRANSACTION_MAX_READ_SIZE_ROWS,
+                        max_rows,
                     )
                 ),
             );
             anyhow::ensure!(
-                tx_size.total_document_size <= *TRANSACTION_MAX_READ_SIZE_BYTES,
+                tx_size.total_document_size <= max_bytes,
                 ErrorMetadata::pagination_limit(
                     "TooManyBytesRead",
                     format!(
                         "Too many bytes read in a single function execution (limit: {} bytes). \
                          {OVER_LIMIT_HELP}",
-                        *TRANSACTION_MAX_READ_SIZE_BYTES,
+                        max_bytes,
                     )
                 ),
             );
@@ -402,12 +405,12 @@ impl TransactionReadSet {
         self.num_intervals = self.num_intervals.saturating_sub(num_intervals_before);
         self.num_intervals += num_intervals_after;
         anyhow::ensure!(
-            self.num_intervals <= *TRANSACTION_MAX_READ_SET_INTERVALS,
+            self.num_intervals <= TRANSACTION_MAX_READ_SET_INTERVALS.get(),
             ErrorMetadata::pagination_limit(
                 "TooManyReads",
                 format!(
                     "Too many reads in a single function execution (limit: {}). {OVER_LIMIT_HELP}",
-                    *TRANSACTION_MAX_READ_SET_INTERVALS
+                    TRANSACTION_MAX_READ_SET_INTERVALS.get()
                 ),
             )
         );
@@ -435,6 +438,92 @@ impl TransactionReadSet {
     }
 }
 
+impl TryFrom<ReadSet> for pb::function_runner::ReadSet {
+    type Error = anyhow::Error;
+
+    fn try_from(read_set: ReadSet) -> anyhow::Result<Self> {
+        let (indexed, search) = read_set.consume();
+        let indexed: Vec<_> = indexed
+            .map(|(index_name, reads)| {
+                anyhow::Ok(pb::function_runner::IndexReads {
+                    index_name: Some(index_name.to_string()),
+                    fields: reads.fields.into(),
+                    intervals: reads.intervals.into(),
+                })
+            })
+            .try_collect()?;
+        let search = search
+            .map(|(index_name, reads)| pb::function_runner::SearchReads {
+                index_name: Some(index_name.to_string()),
+                reads: Some(reads.into()),
+            })
+            .collect();
+        Ok(Self { indexed, search })
+    }
+}
+
+impl TryFrom<pb::function_runner::ReadSet> for ReadSet {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        pb::function_runner::ReadSet { indexed, search }: pb::function_runner::ReadSet,
+    ) -> anyhow::Result<Self> {
+        let indexed: BTreeMap<_, _> = indexed
+            .into_iter()
+            .map(|reads| {
+                let index_name: TabletIndexName = reads
+                    .index_name
+                    .context("Missing `index_name` field")?
+                    .parse()?;
+                let reads = IndexReads {
+                    fields: reads.fields.try_into()?,
+                    intervals: reads.intervals.try_into()?,
+                    stack_traces: None,
+                };
+                anyhow::Ok((index_name, reads))
+            })
+            .try_collect()?;
+        let search: BTreeMap<_, _> = search
+            .into_iter()
+            .map(|reads| {
+                let index_name: TabletIndexName = reads
+                    .index_name
+                    .context("Missing `index_name` field")?
+                    .parse()?;
+                let reads = reads.reads.context("Missing `reads` field")?.try_into()?;
+                anyhow::Ok((index_name, reads))
+            })
+            .try_collect()?;
+        Ok(Self::new(indexed, search))
+    }
+}
+
+impl From<TransactionReadSize> for pb::function_runner::TransactionReadSize {
+    fn from(size: TransactionReadSize) -> Self {
+        Self {
+            total_document_size: Some(size.total_document_size as u64),
+            total_document_count: Some(size.total_document_count as u64),
+        }
+    }
+}
+
+impl TryFrom<pb::function_runner::TransactionReadSize> for TransactionReadSize {
+    type Error = anyhow::Error;
+
+    fn try_from(size: pb::function_runner::TransactionReadSize) -> anyhow::Result<Self> {
+        Ok(Self {
+            total_document_size: size
+                .total_document_size
+                .context("Missing `total_document_size` field")?
+                .try_into()?,
+            total_document_count: size
+                .total_document_count
+                .context("Missing `total_document_count` field")?
+                .try_into()?,
+        })
+    }
+}
+
 #[cfg(any(test, feature = "testing"))]
 impl proptest::arbitrary::Arbitrary for ReadSet {
     type Parameters = ();
@@ -6,11 +6,13 @@ use std::{
     collections::{
         hash_map::DefaultHasher,
         BTreeMap,
+        BTreeSet,
     },
     hash::{
         Hash,
         Hasher,
     },
+    iter,
     sync::Arc,
     time::Duration,
 };
@@ -19,13 +21,19 @@ use anyhow::Context;
 use async_trait::async_trait;
 use common::{
     backoff::Backoff,
-    bootstrap_model::index::{
-        database_index::{
-            DatabaseIndexState,
-            IndexedFields,
+    bootstrap_model::{
+        index::{
+            database_index::{
+                DatabaseIndexState,
+                IndexedFields,
+            },
+            IndexConfig,
+            IndexMetadata,
+        },
+        tables::{
+            TableMetadata,
+            TABLES_TABLE,
         },
-        IndexConfig,
-        IndexMetadata,
     },
     document::{
         ParsedDocument,
@@ -45,6 +53,7 @@ use common::{
         DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS,
         INDEX_RETENTION_DELAY,
         MAX_RETENTION_DELAY_SECONDS,
+        MIN_TABLE_RETENTION,
         RESET_DOCUMENT_RETENTION,
         RETENTION_DELETES_ENABLED,
         RETENTION_DELETE_BATCH,
@@ -97,21 +106,29 @@ use common::{
 };
 use errors::ErrorMetadata;
 use futures::{
-    future::try_join_all,
+    future::{
+        self,
+        try_join_all,
+    },
     pin_mut,
     StreamExt,
     TryStreamExt,
 };
 use futures_async_stream::try_stream;
 use governor::Quota;
+use indexing::backend_in_memory_indexes::InMemoryIndexes;
 use parking_lot::Mutex;
 use rand::Rng;
+use serde_json::Value as JsonValue;
 use tokio::sync::watch::{
     self,
     Receiver,
     Sender,
 };
-use value::InternalDocumentId;
+use value::{
+    InternalDocumentId,
+    TableNamespace,
+};
 
 use crate::{
     metrics::{
@@ -153,6 +170,16 @@ pub struct SnapshotBounds {
     /// min_document_snapshot_ts is the earliest snapshot at which we are
     /// guaranteed to not have deleted views of data in the write-ahead log.
     min_document_snapshot_ts: Timestamp,
+
+    /// min_snapshot_ts for tables that keep their history for less than
+    /// INDEX_RETENTION_DELAY. The global deletion pass skips these tables.
+    min_table_snapshot_ts: BTreeMap<TabletId, Timestamp>,
+
+    /// min_document_snapshot_ts for tables that override
+    /// DOCUMENT_RETENTION_DELAY, ahead of min_document_snapshot_ts for
+    /// shorter overrides and behind it for longer ones. The global deletion
+    /// pass skips these tables.
+    min_table_document_snapshot_ts: BTreeMap<TabletId, Timestamp>,
 }
 
 impl SnapshotBounds {
@@ -163,16 +190,131 @@ impl SnapshotBounds {
     fn advance_min_document_snapshot_ts(&mut self, candidate: Timestamp) {
         self.min_document_snapshot_ts = cmp::max(self.min_document_snapshot_ts, candidate);
     }
+
+    fn min_table_snapshot_ts(&self, tablet_id: TabletId) -> Timestamp {
+        self.min_table_snapshot_ts
+            .get(&tablet_id)
+            .copied()
+            .unwrap_or(self.min_snapshot_ts)
+    }
+
+    fn min_table_document_snapshot_ts(&self, tablet_id: TabletId) -> Timestamp {
+        self.min_table_document_snapshot_ts
+            .get(&tablet_id)
+            .copied()
+            .unwrap_or(self.min_document_snapshot_ts)
+    }
 }
 
 pub struct Checkpoint {
     checkpoint: Option<Timestamp>,
+    /// Checkpoints of the tables deleted on their own schedule.
+    table_checkpoints: BTreeMap<TabletId, Timestamp>,
 }
 
 impl Checkpoint {
     fn advance_checkpoint(&mut self, candidate: Timestamp) {
         self.checkpoint = Some(cmp::max(self.checkpoint.unwrap_or_default(), candidate));
     }
+
+    fn table_checkpoint(&self, tablet_id: TabletId) -> Timestamp {
+        self.table_checkpoints
+            .get(&tablet_id)
+            .copied()
+            .or(self.checkpoint)
+            .unwrap_or(Timestamp::MIN)
+    }
+}
+
+/// The part of the log each table's deletion has left to process, as a
+/// `(cursor, min_snapshot_ts)` pair: log entries at or after the cursor and
+/// before min_snapshot_ts. Tables with their own retention have their own
+/// range, all other tables share the global one.
+#[derive(Clone, Debug, PartialEq)]
+struct RetentionRanges {
+    global: (Timestamp, Timestamp),
+    tables: BTreeMap<TabletId, (Timestamp, Timestamp)>,
+}
+
+impl RetentionRanges {
+    fn global(cursor: Timestamp, min_snapshot_ts: Timestamp) -> Self {
+        Self {
+            global: (cursor, min_snapshot_ts),
+            tables: BTreeMap::new(),
+        }
+    }
+
+    /// Tables in `table_bounds` start from their persisted cursor, or from the
+    /// global cursor if they just got their own retention. Tables that lost
+    /// their own retention while behind the global cursor keep their cursor,
+    /// up to the global bound, until they catch up.
+    fn new(
+        cursor: Timestamp,
+        min_snapshot_ts: Timestamp,
+        table_bounds: &BTreeMap<TabletId, Timestamp>,
+        table_cursors: &BTreeMap<TabletId, Timestamp>,
+    ) -> Self {
+        let mut tables: BTreeMap<_, _> = table_bounds
+            .iter()
+            .map(|(tablet_id, bound)| {
+                let table_cursor = table_cursors.get(tablet_id).copied().unwrap_or(cursor);
+                (*tablet_id, (table_cursor, *bound))
+            })
+            .collect();
+        for (tablet_id, table_cursor) in table_cursors {
+            if !table_bounds.contains_key(tablet_id) && *table_cursor < cursor {
+                tables.insert(*tablet_id, (*table_cursor, min_snapshot_ts));
+            }
+        }
+        Self {
+            global: (cursor, min_snapshot_ts),
+            tables,
+        }
+    }
+
+    fn contains(&self, tablet_id: TabletId, ts: Timestamp) -> bool {
+        let (cursor, min_snapshot_ts) = self.tables.get(&tablet_id).copied().unwrap_or(self.global);
+        cursor <= ts && ts < min_snapshot_ts
+    }
+
+    /// Merges the ranges into disjoint windows of the log, in order, so each
+    /// log entry is scanned once however many tables' ranges include it.
+    fn windows(&self) -> Vec<(Timestamp, Timestamp)> {
+        let mut ranges: Vec<_> = iter::once(self.global)
+            .chain(self.tables.values().copied())
+            .filter(|(cursor, min_snapshot_ts)| cursor < min_snapshot_ts)
+            .collect();
+        ranges.sort();
+        let mut windows: Vec<(Timestamp, Timestamp)> = vec![];
+        for (start, end) in ranges {
+            match windows.last_mut() {
+                Some((_, last_end)) if start <= *last_end => {
+                    *last_end = cmp::max(*last_end, end);
+                },
+                _ => windows.push((start, end)),
+            }
+        }
+        windows
+    }
+
+    /// Moves every cursor up to `ts`, once the log has been processed up to
+    /// and including `ts`, without passing the range's end.
+    fn advance(&mut self, ts: Timestamp) {
+        for (cursor, min_snapshot_ts) in
+            iter::once(&mut self.global).chain(self.tables.values_mut())
+        {
+            if let Ok(last) = min_snapshot_ts.pred() {
+                *cursor = cmp::max(*cursor, cmp::min(ts, last));
+            }
+        }
+    }
+
+    fn table_cursors(&self) -> BTreeMap<TabletId, Timestamp> {
+        self.tables
+            .iter()
+            .map(|(tablet_id, (cursor, _))| (*tablet_id, *cursor))
+            .collect()
+    }
 }
 
 pub struct LeaderRetentionManager<RT: Runtime> {
@@ -222,6 +364,60 @@ pub async fn latest_retention_min_snapshot_ts(
     Ok(min_snapshot_ts)
 }
 
+pub async fn latest_table_retention_min_snapshot_ts(
+    persistence: &dyn PersistenceReader,
+    retention_type: RetentionType,
+) -> anyhow::Result<BTreeMap<TabletId, Timestamp>> {
+    let key = match retention_type {
+        RetentionType::Document => {
+            PersistenceGlobalKey::TableDocumentRetentionMinSnapshotTimestamps
+        },
+        RetentionType::Index => PersistenceGlobalKey::TableRetentionMinSnapshotTimestamps,
+    };
+    get_table_timestamps(persistence, key).await
+}
+
+async fn get_table_timestamps(
+    persistence: &dyn PersistenceReader,
+    key: PersistenceGlobalKey,
+) -> anyhow::Result<BTreeMap<TabletId, Timestamp>> {
+    let Some(value) = persistence.get_persistence_global(key).await? else {
+        return Ok(BTreeMap::new());
+    };
+    let JsonValue::Object(tables) = value else {
+        anyhow::bail!("invalid table retention timestamps {value:?}");
+    };
+    tables
+        .into_iter()
+        .map(|(tablet_id, ts)| {
+            let ts = match ConvexValue::try_from(ts)? {
+                ConvexValue::Int64(ts) => Timestamp::try_from(ts)?,
+                value => anyhow::bail!("invalid table retention timestamp {value:?}"),
+            };
+            Ok((tablet_id.parse()?, ts))
+        })
+        .collect()
+}
+
+async fn write_table_timestamps(
+    persistence: &dyn Persistence,
+    key: PersistenceGlobalKey,
+    timestamps: &BTreeMap<TabletId, Timestamp>,
+) -> anyhow::Result<()> {
+    let value: serde_json::Map<String, JsonValue> = timestamps
+        .iter()
+        .map(|(tablet_id, ts)| {
+            (
+                tablet_id.to_string(),
+                ConvexValue::from(i64::from(*ts)).into(),
+            )
+        })
+        .collect();
+    persistence
+        .write_persistence_global(key, JsonValue::Object(value))
+        .await
+}
+
 const INITIAL_BACKOFF: Duration = Duration::from_millis(50);
 
 impl<RT: Runtime> LeaderRetentionManager<RT> {
@@ -245,13 +441,26 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
             latest_retention_min_snapshot_ts(reader.as_ref(), RetentionType::Index).await?;
         let min_document_snapshot_ts =
             latest_retention_min_snapshot_ts(reader.as_ref(), RetentionType::Document).await?;
+        let min_table_snapshot_ts =
+            latest_table_retention_min_snapshot_ts(reader.as_ref(), RetentionType::Index).await?;
+        let min_table_document_snapshot_ts =
+            latest_table_retention_min_snapshot_ts(reader.as_ref(), RetentionType::Document)
+                .await?;
         let bounds = SnapshotBounds {
             min_snapshot_ts,
             min_document_snapshot_ts,
+            min_table_snapshot_ts,
+            min_table_document_snapshot_ts,
         };
         let (bounds_reader, bounds_writer) = new_split_rw_lock(bounds);
-        let checkpoint = Checkpoint { checkpoint: None };
-        let document_checkpoint = Checkpoint { checkpoint: None };
+        let checkpoint = Checkpoint {
+            checkpoint: None,
+            table_checkpoints: BTreeMap::new(),
+        };
+        let document_checkpoint = Checkpoint {
+            checkpoint: None,
+            table_checkpoints: BTreeMap::new(),
+        };
         let (checkpoint_reader, checkpoint_writer) = new_split_rw_lock(checkpoint);
         let (document_checkpoint_reader, document_checkpoint_writer) =
             new_split_rw_lock(document_checkpoint);
@@ -382,8 +591,8 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         retention_type: RetentionType,
     ) -> anyhow::Result<Timestamp> {
         let delay = match retention_type {
-            RetentionType::Document => *DOCUMENT_RETENTION_DELAY,
-            RetentionType::Index => *INDEX_RETENTION_DELAY,
+            RetentionType::Document => DOCUMENT_RETENTION_DELAY.get(),
+            RetentionType::Index => INDEX_RETENTION_DELAY.get(),
         };
         let mut candidate = snapshot_reader
             .lock()
@@ -478,6 +687,119 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         }
     }
 
+    /// Returns the tables that set their own `document_retention`, as of the
+    /// latest snapshot.
+    async fn table_retention_overrides(
+        snapshot_reader: &Reader<SnapshotManager<RT>>,
+    ) -> anyhow::Result<BTreeMap<TabletId, Duration>> {
+        let snapshot = snapshot_reader.lock().latest_snapshot();
+        let tables_tablet_id = snapshot
+            .table_registry
+            .table_mapping()
+            .namespace(TableNamespace::Global)
+            .id(&TABLES_TABLE)?
+            .tablet_id;
+        let tables_by_id = snapshot
+            .index_registry
+            .enabled_index_metadata(&TabletIndexName::by_id(tables_tablet_id))
+            .context("_tables.by_id must exist")?
+            .id()
+            .internal_id();
+        let tables = snapshot
+            .in_memory_indexes
+            .range(
+                tables_by_id,
+                &Interval::all(),
+                Order::Asc,
+                tables_tablet_id,
+                TABLES_TABLE.clone(),
+            )
+            .await?
+            .context("_tables.by_id must be in memory")?;
+        let mut overrides = BTreeMap::new();
+        for (_, _, doc) in tables {
+            let table: ParsedDocument<TableMetadata> = doc.try_into()?;
+            if let Some(retention) = table.document_retention
+                && table.is_active()
+            {
+                overrides.insert(TabletId(table.id().internal_id()), retention);
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// Advances the retention bound of each table with its own retention:
+    /// the document bound of every table that overrides the default, and the
+    /// index bound of those that keep their history for less than
+    /// INDEX_RETENTION_DELAY. A new bound starts at the global bound, since
+    /// earlier data may already be deleted, and like the global bounds a
+    /// document bound never passes the table's index retention checkpoint.
+    ///
+    /// A table's bound stops advancing when its override is removed, and is
+    /// dropped once the global bound catches up with it.
+    async fn advance_table_timestamps(
+        bounds_writer: &Writer<SnapshotBounds>,
+        persistence: &dyn Persistence,
+        snapshot_reader: &Reader<SnapshotManager<RT>>,
+        checkpoint_reader: &Reader<Checkpoint>,
+        retention_type: RetentionType,
+    ) -> anyhow::Result<()> {
+        let overrides = Self::table_retention_overrides(snapshot_reader).await?;
+        let latest_ts = snapshot_reader.lock().latest_ts();
+        let (min_snapshot_ts, current) = {
+            let bounds = bounds_writer.read();
+            match retention_type {
+                RetentionType::Document => (
+                    bounds.min_document_snapshot_ts,
+                    bounds.min_table_document_snapshot_ts.clone(),
+                ),
+                RetentionType::Index => {
+                    (bounds.min_snapshot_ts, bounds.min_table_snapshot_ts.clone())
+                },
+            }
+        };
+        let mut new_bounds = current.clone();
+        let mut active = BTreeSet::new();
+        for (tablet_id, retention) in overrides {
+            let mut candidate = latest_ts.sub(retention).unwrap_or(Timestamp::MIN);
+            match retention_type {
+                RetentionType::Document => {
+                    let index_confirmed_deleted =
+                        checkpoint_reader.lock().table_checkpoint(tablet_id);
+                    candidate = cmp::min(candidate, index_confirmed_deleted);
+                },
+                RetentionType::Index => {
+                    if retention >= INDEX_RETENTION_DELAY.get() {
+                        continue;
+                    }
+                },
+            }
+            let bound = new_bounds.entry(tablet_id).or_insert(min_snapshot_ts);
+            *bound = cmp::max(*bound, candidate);
+            active.insert(tablet_id);
+        }
+        new_bounds.retain(|tablet_id, ts| active.contains(tablet_id) || *ts > min_snapshot_ts);
+        if new_bounds == current {
+            return Ok(());
+        }
+        let key = match retention_type {
+            RetentionType::Document => {
+                PersistenceGlobalKey::TableDocumentRetentionMinSnapshotTimestamps
+            },
+            RetentionType::Index => PersistenceGlobalKey::TableRetentionMinSnapshotTimestamps,
+        };
+        // Same as `advance_timestamp`, write to persistence before memory.
+        write_table_timestamps(persistence, key, &new_bounds).await?;
+        tracing::debug!("Advance table {retention_type:?} min snapshots to {new_bounds:?}");
+        match retention_type {
+            RetentionType::Document => {
+                bounds_writer.write().min_table_document_snapshot_ts = new_bounds
+            },
+            RetentionType::Index => bounds_writer.write().min_table_snapshot_ts = new_bounds,
+        }
+        Ok(())
+    }
+
     async fn go_advance_min_snapshot(
         bounds_writer: Writer<SnapshotBounds>,
         checkpoint_reader: Reader<Checkpoint>,
@@ -510,6 +832,20 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                 )
                 .await;
                 Self::emit_timestamp(&min_document_snapshot_sender, document_ts).await;
+
+                for retention_type in [RetentionType::Index, RetentionType::Document] {
+                    if let Err(mut err) = Self::advance_table_timestamps(
+                        &bounds_writer,
+                        persistence.as_ref(),
+                        &snapshot_reader,
+                        &checkpoint_reader,
+                        retention_type,
+                    )
+                    .await
+                    {
+                        report_error(&mut err);
+                    }
+                }
             }
             // We jitter every loop to avoid synchronization of polling the database
             // across different instances
@@ -518,12 +854,15 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
     }
 
     /// Finds expired index entries in the index table and returns a tuple of
-    /// the form (scanned_index_ts, expired_index_entry)
+    /// the form (scanned_index_ts, expired_index_entry). Scans the log from
+    /// `cursor` to `min_snapshot_ts`, skipping entries outside of their
+    /// table's range in `ranges`.
     #[try_stream(ok = (Timestamp, IndexEntry), error = anyhow::Error)]
     async fn expired_index_entries(
         reader: RepeatablePersistence,
         cursor: Timestamp,
         min_snapshot_ts: Timestamp,
+        ranges: &RetentionRanges,
         all_indexes: &BTreeMap<IndexId, (GenericIndexName<TabletId>, IndexedFields)>,
         persistence_version: PersistenceVersion,
     ) {
@@ -534,6 +873,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         let reader_ = &reader;
         let mut index_entry_chunks = reader
             .load_documents(TimestampRange::new(cursor..min_snapshot_ts)?, Order::Asc)
+            .try_filter(move |(ts, id, _)| future::ready(ranges.contains(*id.table(), *ts)))
             .try_chunks2(*RETENTION_READ_CHUNK)
             .map(move |chunk| async move {
                 let chunk = chunk?.to_vec();
@@ -622,78 +962,86 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         }
     }
 
-    /// Deletes some index entries based on `bounds` which identify what may be
-    /// deleted. Returns a pair of the new cursor and the total expired index
-    /// entries processed. The cursor is a timestamp which has been
-    /// fully deleted, along with all prior timestamps. The total expired index
-    /// entries is the number of index entries we found were expired, not
-    /// necessarily the total we deleted or wanted to delete, though they're
-    /// correlated.
+    /// Deletes some index entries in `ranges`, which identify what may be
+    /// deleted. Returns a pair of the ranges with their cursors advanced and
+    /// the total expired index entries processed. Each cursor is a timestamp
+    /// which has been fully deleted in its range, along with all prior
+    /// timestamps. The total expired index entries is the number of index
+    /// entries we found were expired, not necessarily the total we deleted or
+    /// wanted to delete, though they're correlated.
     async fn delete(
-        min_snapshot_ts: Timestamp,
+        mut ranges: RetentionRanges,
         persistence: Arc<dyn Persistence>,
         rt: &RT,
-        cursor: Timestamp,
         all_indexes: &BTreeMap<IndexId, (GenericIndexName<TabletId>, IndexedFields)>,
         retention_validator: Arc<dyn RetentionValidator>,
-    ) -> anyhow::Result<(Timestamp, usize)> {
-        if !*RETENTION_DELETES_ENABLED || min_snapshot_ts == Timestamp::MIN {
-            return Ok((cursor, 0));
+    ) -> anyhow::Result<(RetentionRanges, usize)> {
+        let windows = ranges.windows();
+        let Some((_, max_snapshot_ts)) = windows.last().copied() else {
+            return Ok((ranges, 0));
+        };
+        if !RETENTION_DELETES_ENABLED.get() {
+            return Ok((ranges, 0));
         }
         // The number of rows we delete in persistence.
         let mut total_deleted_rows: usize = 0;
         // The number of expired entries we read from chunks.
         let mut total_expired_entries = 0;
-        let mut new_cursor = cursor;
 
         let reader = persistence.reader();
         let persistence_version = reader.version();
-        let snapshot_ts = new_static_repeatable_ts(min_snapshot_ts, reader.as_ref(), rt).await?;
+        let snapshot_ts = new_static_repeatable_ts(max_snapshot_ts, reader.as_ref(), rt).await?;
         let reader = RepeatablePersistence::new(reader, snapshot_ts, retention_validator.clone());
 
-        tracing::trace!("delete: about to grab chunks");
-        let expired_chunks = Self::expired_index_entries(
-            reader,
-            cursor,
-            min_snapshot_ts,
-            all_indexes,
-            persistence_version,
-        )
-        .try_chunks2(*RETENTION_DELETE_CHUNK);
-        pin_mut!(expired_chunks);
-        while let Some(delete_chunk) = expired_chunks.try_next().await? {
-            tracing::trace!(
-                "delete: got a chunk and finished waiting {:?}",
-                delete_chunk.len()
-            );
-            total_expired_entries += delete_chunk.len();
-            let results = try_join_all(Self::partition_chunk(delete_chunk).into_iter().map(
-                |delete_chunk| Self::delete_chunk(delete_chunk, persistence.clone(), new_cursor),
-            ))
-            .await?;
-            let (chunk_new_cursors, deleted_rows): (Vec<_>, Vec<_>) = results.into_iter().unzip();
-            // We have successfully deleted all of delete_chunk, so update
-            // total_deleted_rows and new_cursor to reflect the deletions.
-            total_deleted_rows += deleted_rows.into_iter().sum::<usize>();
-            if let Some(max_new_cursor) = chunk_new_cursors.into_iter().max() {
-                new_cursor = max_new_cursor;
-            }
-            if new_cursor > cursor && total_expired_entries > *RETENTION_DELETE_BATCH {
-                tracing::debug!(
-                    "delete: returning early with {new_cursor:?}, total expired index entries \
-                     read: {total_expired_entries:?}, total rows deleted: {total_deleted_rows:?}"
+        for (cursor, min_snapshot_ts) in windows {
+            let mut new_cursor = cursor;
+            let window_ranges = ranges.clone();
+            tracing::trace!("delete: about to grab chunks");
+            let expired_chunks = Self::expired_index_entries(
+                reader.clone(),
+                cursor,
+                min_snapshot_ts,
+                &window_ranges,
+                all_indexes,
+                persistence_version,
+            )
+            .try_chunks2(*RETENTION_DELETE_CHUNK);
+            pin_mut!(expired_chunks);
+            while let Some(delete_chunk) = expired_chunks.try_next().await? {
+                tracing::trace!(
+                    "delete: got a chunk and finished waiting {:?}",
+                    delete_chunk.len()
                 );
-                // we're not done deleting everything.
-                return Ok((new_cursor, total_expired_entries));
+                total_expired_entries += delete_chunk.len();
+                let results = try_join_all(Self::partition_chunk(delete_chunk).into_iter().map(
+                    |delete_chunk| {
+                        Self::delete_chunk(delete_chunk, persistence.clone(), new_cursor)
+                    },
+                ))
+                .await?;
+                let (chunk_new_cursors, deleted_rows): (Vec<_>, Vec<_>) =
+                    results.into_iter().unzip();
+                // We have successfully deleted all of delete_chunk, so update
+                // total_deleted_rows and the cursors to reflect the deletions.
+                total_deleted_rows += deleted_rows.into_iter().sum::<usize>();
+                if let Some(max_new_cursor) = chunk_new_cursors.into_iter().max() {
+                    new_cursor = max_new_cursor;
+                }
+                ranges.advance(new_cursor);
+                if new_cursor > cursor && total_expired_entries > RETENTION_DELETE_BATCH.get() {
+                    tracing::debug!(
+                        "delete: returning early with {ranges:?}, total expired index entries \
+                         read: {total_expired_entries:?}, total rows deleted: \
+                         {total_deleted_rows:?}"
+                    );
+                    // we're not done deleting everything.
+                    return Ok((ranges, total_expired_entries));
+                }
             }
+            ranges.advance(min_snapshot_ts.pred()?);
         }
-        tracing::debug!(
-            "delete: finished loop, returning {:?}",
-            min_snapshot_ts.pred()
-        );
-        min_snapshot_ts
-            .pred()
-            .map(|timestamp| (timestamp, total_expired_entries))
+        tracing::debug!("delete: finished loop, returning {ranges:?}");
+        Ok((ranges, total_expired_entries))
     }
 
     pub async fn delete_all_no_checkpoint(
@@ -705,15 +1053,15 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         retention_validator: Arc<dyn RetentionValidator>,
     ) -> anyhow::Result<()> {
         while cursor_ts.succ()? < min_snapshot_ts {
-            let (new_cursor_ts, _) = Self::delete(
-                min_snapshot_ts,
+            let (ranges, _) = Self::delete(
+                RetentionRanges::global(cursor_ts, min_snapshot_ts),
                 persistence.clone(),
                 rt,
-                cursor_ts,
                 all_indexes,
                 retention_validator.clone(),
             )
             .await?;
+            let (new_cursor_ts, _) = ranges.global;
             tracing::info!(
                 "custom index retention completed between ts {cursor_ts} and {new_cursor_ts}"
             );
@@ -724,18 +1072,30 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
 
     /// Finds expired documents in the documents log and returns a tuple of the
     /// form (scanned_document_ts, (expired_document_ts,
-    /// internal_document_ts))
+    /// internal_document_ts)). Scans the log from `cursor` to
+    /// `min_document_snapshot_ts`, skipping documents outside of their
+    /// table's range in `ranges`.
     #[try_stream(ok = (Timestamp, Option<(Timestamp, InternalDocumentId)>), error = anyhow::Error)]
     async fn expired_documents(
         rt: &RT,
         reader: RepeatablePersistence,
         cursor: Timestamp,
         min_document_snapshot_ts: Timestamp,
+        ranges: &RetentionRanges,
     ) {
         tracing::trace!(
             "expired_documents: reading expired documents from {cursor:?} to {:?}",
             min_document_snapshot_ts,
         );
+        // Tables with their own retention can't keep their documents for less
+        // than MIN_TABLE_RETENTION.
+        let retention_delay = move |id: InternalDocumentId| {
+            if ranges.tables.contains_key(id.table()) {
+                *MIN_TABLE_RETENTION
+            } else {
+                DOCUMENT_RETENTION_DELAY.get()
+            }
+        };
         let reader_ = &reader;
         let mut document_chunks = reader
             .load_documents_with_retention_validator(
@@ -743,6 +1103,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                 Order::Asc,
                 Arc::new(NoopRetentionValidator),
             )
+            .try_filter(move |(ts, id, _)| future::ready(ranges.contains(*id.table(), *ts)))
             .try_chunks2(*RETENTION_READ_CHUNK)
             .map(move |chunk| async move {
                 let chunk = chunk?.to_vec();
@@ -772,7 +1133,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                                 ts <= Timestamp::try_from(
                                     rt.clone().unix_timestamp().as_system_time()
                                 )?
-                                .sub(*DOCUMENT_RETENTION_DELAY)?,
+                                .sub(retention_delay(id))?,
                                 "Tried to delete document (id: {id}, ts: {ts}), which was out of \
                                  the retention window"
                             );
@@ -797,7 +1158,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                     anyhow::ensure!(
                         *prev_rev_ts
                             <= Timestamp::try_from(rt.unix_timestamp().as_system_time())?
-                                .sub(*DOCUMENT_RETENTION_DELAY)?,
+                                .sub(retention_delay(id))?,
                         "Tried to delete document (id: {id}, ts: {prev_rev_ts}), which was out of \
                          the retention window"
                     );
@@ -821,97 +1182,100 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         }
     }
 
-    /// Deletes some documents based on `bounds` which identify what may be
-    /// deleted. Returns a pair of the new cursor and the total number of
-    /// documents processed. The cursor is a timestamp which has been
-    /// fully deleted, along with all prior timestamps. The total expired
-    /// document count is the number of documents we found were expired, not
-    /// necessarily the total we deleted or wanted to delete, though they're
-    /// correlated.
+    /// Deletes some documents in `ranges`, which identify what may be
+    /// deleted. Returns a pair of the ranges with their cursors advanced and
+    /// the total number of documents processed. Each cursor is a timestamp
+    /// which has been fully deleted in its range, along with all prior
+    /// timestamps. The total expired document count is the number of
+    /// documents we found were expired, not necessarily the total we deleted
+    /// or wanted to delete, though they're correlated.
     async fn delete_documents(
-        min_snapshot_ts: Timestamp,
+        ranges: RetentionRanges,
         persistence: Arc<dyn Persistence>,
         rt: &RT,
-        cursor: Timestamp,
         retention_validator: Arc<dyn RetentionValidator>,
-    ) -> anyhow::Result<(Timestamp, usize)> {
-        if !*RETENTION_DOCUMENT_DELETES_ENABLED || min_snapshot_ts == Timestamp::MIN {
-            return Ok((cursor, 0));
+    ) -> anyhow::Result<(RetentionRanges, usize)> {
+        let windows = ranges.windows();
+        let Some((_, max_snapshot_ts)) = windows.last().copied() else {
+            return Ok((ranges, 0));
+        };
+        if !RETENTION_DOCUMENT_DELETES_ENABLED.get() {
+            return Ok((ranges, 0));
         }
         // The number of rows we delete in persistence.
         let mut total_deleted_rows: usize = 0;
         // The number of expired entries we read from chunks.
         let mut total_expired_entries = 0;
-        let mut new_cursor = cursor;
         // The number of scanned documents
         let mut scanned_documents = 0;
+        let mut new_ranges = ranges.clone();
 
         let reader = persistence.reader();
-        let snapshot_ts = new_static_repeatable_ts(min_snapshot_ts, reader.as_ref(), rt).await?;
+        let snapshot_ts = new_static_repeatable_ts(max_snapshot_ts, reader.as_ref(), rt).await?;
         let reader = RepeatablePersistence::new(reader, snapshot_ts, retention_validator.clone());
 
-        tracing::trace!("delete_documents: about to grab chunks");
-        let expired_chunks = Self::expired_documents(rt, reader, cursor, min_snapshot_ts)
-            .try_chunks2(*RETENTION_DELETE_CHUNK);
-        pin_mut!(expired_chunks);
-        while let Some(scanned_chunk) = expired_chunks.try_next().await? {
-            tracing::trace!(
-                "delete_documents: got a chunk and finished waiting {:?}",
-                scanned_chunk.len()
-            );
-            // Converts scanned documents to the actual documents we want to delete
-            scanned_documents += scanned_chunk.len();
-            let delete_chunk: Vec<(Timestamp, (Timestamp, InternalDocumentId))> = scanned_chunk
-                .into_iter()
-                .filter_map(
-                    |doc: (Timestamp, Option<(Timestamp, InternalDocumentId)>)| {
-                        if doc.1.is_some() {
-                            Some((doc.0, doc.1.unwrap()))
-                        } else {
-                            None
-                        }
-                    },
-                )
-                .collect();
-            total_expired_entries += delete_chunk.len();
-            let results = try_join_all(
-                Self::partition_document_chunk(delete_chunk)
-                    .into_iter()
-                    .map(|delete_chunk| {
-                        Self::delete_document_chunk(delete_chunk, persistence.clone(), new_cursor)
-                    }),
-            )
-            .await?;
-            let (chunk_new_cursors, deleted_rows): (Vec<_>, Vec<_>) = results.into_iter().unzip();
-            // We have successfully deleted all of delete_chunk, so update
-            // total_deleted_rows and new_cursor to reflect the deletions.
-            total_deleted_rows += deleted_rows.into_iter().sum::<usize>();
-            if let Some(max_new_cursor) = chunk_new_cursors.into_iter().max() {
-                new_cursor = max_new_cursor;
-            }
-            if new_cursor > cursor && scanned_documents >= *DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS
-            {
-                tracing::debug!(
-                    "delete_documents: returning early with {new_cursor:?}, total expired \
-                     documents read: {total_expired_entries:?}, total rows deleted: \
-                     {total_deleted_rows:?}"
+        for (cursor, min_snapshot_ts) in windows {
+            let mut new_cursor = cursor;
+            tracing::trace!("delete_documents: about to grab chunks");
+            let expired_chunks =
+                Self::expired_documents(rt, reader.clone(), cursor, min_snapshot_ts, &ranges)
+                    .try_chunks2(*RETENTION_DELETE_CHUNK);
+            pin_mut!(expired_chunks);
+            while let Some(scanned_chunk) = expired_chunks.try_next().await? {
+                tracing::trace!(
+                    "delete_documents: got a chunk and finished waiting {:?}",
+                    scanned_chunk.len()
                 );
-                // we're not done deleting everything.
-                return Ok((new_cursor, total_expired_entries));
+                // Converts scanned documents to the actual documents we want to delete
+                scanned_documents += scanned_chunk.len();
+                let delete_chunk: Vec<(Timestamp, (Timestamp, InternalDocumentId))> = scanned_chunk
+                    .into_iter()
+                    .filter_map(|(ts, expired)| expired.map(|expired| (ts, expired)))
+                    .collect();
+                total_expired_entries += delete_chunk.len();
+                let results = try_join_all(
+                    Self::partition_document_chunk(delete_chunk)
+                        .into_iter()
+                        .map(|delete_chunk| {
+                            Self::delete_document_chunk(
+                                delete_chunk,
+                                persistence.clone(),
+                                new_cursor,
+                            )
+                        }),
+                )
+                .await?;
+                let (chunk_new_cursors, deleted_rows): (Vec<_>, Vec<_>) =
+                    results.into_iter().unzip();
+                // We have successfully deleted all of delete_chunk, so update
+                // total_deleted_rows and the cursors to reflect the deletions.
+                total_deleted_rows += deleted_rows.into_iter().sum::<usize>();
+                if let Some(max_new_cursor) = chunk_new_cursors.into_iter().max() {
+                    new_cursor = max_new_cursor;
+                }
+                new_ranges.advance(new_cursor);
+                if new_cursor > cursor
+                    && scanned_documents >= DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS.get()
+                {
+                    tracing::debug!(
+                        "delete_documents: returning early with {new_ranges:?}, total expired \
+                         documents read: {total_expired_entries:?}, total rows deleted: \
+                         {total_deleted_rows:?}"
+                    );
+                    // we're not done deleting everything.
+                    return Ok((new_ranges, total_expired_entries));
+                }
             }
+            new_ranges.advance(min_snapshot_ts.pred()?);
         }
-        // Don't advance the retention confirmed deleted timestamp if dry run is enabled
-        if *DOCUMENT_RETENTION_DRY_RUN {
+        // Don't advance the retention confirmed deleted timestamps if dry run is
+        // enabled
+        if DOCUMENT_RETENTION_DRY_RUN.get() {
             tracing::info!("DRY RUN: Would have deleted {total_expired_entries} documents");
-            return Ok((cursor, total_expired_entries));
+            return Ok((ranges, total_expired_entries));
         }
-        tracing::debug!(
-            "delete: finished loop, returning {:?}",
-            min_snapshot_ts.pred()
-        );
-        min_snapshot_ts
-            .pred()
-            .map(|timestamp| (timestamp, total_expired_entries))
+        tracing::debug!("delete_documents: finished loop, returning {new_ranges:?}");
+        Ok((new_ranges, total_expired_entries))
     }
 
     /// Partitions IndexEntry into RETENTION_DELETE_PARALLEL parts where each
@@ -993,7 +1357,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
         persistence: Arc<dyn Persistence>,
         mut new_cursor: Timestamp,
     ) -> anyhow::Result<(Timestamp, usize)> {
-        if *DOCUMENT_RETENTION_DRY_RUN {
+        if DOCUMENT_RETENTION_DRY_RUN.get() {
             tracing::info!("Would have deleted {} documents", delete_chunk.len());
             return Ok((new_cursor, delete_chunk.len()));
         }
@@ -1082,7 +1446,12 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                     RetentionType::Index,
                 )
                 .await?;
-                tracing::trace!("go_delete: loaded checkpoint: {cursor:?}");
+                let table_cursors =
+                    Self::get_table_checkpoints(reader.as_ref(), RetentionType::Index).await?;
+                tracing::trace!("go_delete: loaded checkpoint: {cursor:?}, {table_cursors:?}");
+                let table_bounds = bounds_reader.lock().min_table_snapshot_ts.clone();
+                let ranges =
+                    RetentionRanges::new(cursor, min_snapshot_ts, &table_bounds, &table_cursors);
                 let latest_ts = snapshot_reader.lock().latest_ts();
                 Self::accumulate_indexes(
                     persistence.as_ref(),
@@ -1095,11 +1464,10 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                 .await?;
                 tracing::trace!("go_delete: Loaded initial indexes");
                 let index_count_before = all_indexes.len();
-                let (new_cursor, expired_index_entries_processed) = Self::delete(
-                    min_snapshot_ts,
+                let (ranges, expired_index_entries_processed) = Self::delete(
+                    ranges,
                     persistence.clone(),
                     &rt,
-                    cursor,
                     &all_indexes,
                     retention_validator.clone(),
                 )
@@ -1117,12 +1485,12 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                 .await?;
                 tracing::trace!("go_delete: loaded second round of indexes");
                 if all_indexes.len() == index_count_before {
-                    tracing::debug!("go_delete: Checkpointing at: {new_cursor:?}");
+                    tracing::debug!("go_delete: Checkpointing at: {ranges:?}");
                     // No indexes were added while we were doing the delete.
-                    // So the `delete` covered all index rows up to new_cursor.
+                    // So the `delete` covered all index rows up to the cursors.
                     Self::checkpoint(
                         persistence.as_ref(),
-                        new_cursor,
+                        &ranges,
                         &checkpoint_writer,
                         RetentionType::Index,
                     )
@@ -1137,7 +1505,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
 
                 // If we deleted >= the delete batch size, we probably returned
                 // early and have more work to do, so run again immediately.
-                is_working = expired_index_entries_processed >= *RETENTION_DELETE_BATCH;
+                is_working = expired_index_entries_processed >= RETENTION_DELETE_BATCH.get();
                 if is_working {
                     tracing::trace!(
                         "go_delete: processed {expired_index_entries_processed:?} rows, more to go"
@@ -1214,20 +1582,30 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                     RetentionType::Document,
                 )
                 .await?;
-                tracing::trace!("go_delete_documents: loaded checkpoint: {cursor:?}");
-                let (new_cursor, expired_documents_processed) = Self::delete_documents(
+                let table_cursors =
+                    Self::get_table_checkpoints(reader.as_ref(), RetentionType::Document).await?;
+                tracing::trace!(
+                    "go_delete_documents: loaded checkpoint: {cursor:?}, {table_cursors:?}"
+                );
+                let table_bounds = bounds_reader.lock().min_table_document_snapshot_ts.clone();
+                let ranges = RetentionRanges::new(
+                    cursor,
                     min_document_snapshot_ts,
+                    &table_bounds,
+                    &table_cursors,
+                );
+                let (ranges, expired_documents_processed) = Self::delete_documents(
+                    ranges,
                     persistence.clone(),
                     &rt,
-                    cursor,
                     retention_validator.clone(),
                 )
                 .await?;
-                tracing::debug!("go_delete_documents: Checkpointing at: {new_cursor:?}");
+                tracing::debug!("go_delete_documents: Checkpointing at: {ranges:?}");
 
                 Self::checkpoint(
                     persistence.as_ref(),
-                    new_cursor,
+                    &ranges,
                     &checkpoint_writer,
                     RetentionType::Document,
                 )
@@ -1236,7 +1614,7 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
                 // If we deleted >= the delete batch size, we probably returned
                 // early and have more work to do, so run again immediately.
                 is_working =
-                    expired_documents_processed >= *DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS;
+                    expired_documents_processed >= DOCUMENT_RETENTION_MAX_SCANNED_DOCUMENTS.get();
                 if is_working {
                     tracing::trace!(
                         "go_delete_documents: processed {expired_documents_processed:?} rows, \
@@ -1257,23 +1635,48 @@ impl<RT: Runtime> LeaderRetentionManager<RT> {
 
     async fn checkpoint(
         persistence: &dyn Persistence,
-        cursor: Timestamp,
+        ranges: &RetentionRanges,
         checkpoint_writer: &Writer<Checkpoint>,
         retention_type: RetentionType,
     ) -> anyhow::Result<()> {
-        let key = match retention_type {
-            RetentionType::Document => {
-                PersistenceGlobalKey::DocumentRetentionConfirmedDeletedTimestamp
-            },
-            RetentionType::Index => PersistenceGlobalKey::RetentionConfirmedDeletedTimestamp,
+        let (key, table_key) = match retention_type {
+            RetentionType::Document => (
+                PersistenceGlobalKey::DocumentRetentionConfirmedDeletedTimestamp,
+                PersistenceGlobalKey::TableDocumentRetentionConfirmedDeletedTimestamps,
+            ),
+            RetentionType::Index => (
+                PersistenceGlobalKey::RetentionConfirmedDeletedTimestamp,
+                PersistenceGlobalKey::TableRetentionConfirmedDeletedTimestamps,
+            ),
         };
+        let (cursor, _) = ranges.global;
+        let table_cursors = ranges.table_cursors();
+        // Write the table cursors first: a table that just got its own
+        // retention starts from the global cursor if it has none, which would
+        // skip what it hasn't deleted yet if the global cursor were ahead.
+        write_table_timestamps(persistence, table_key, &table_cursors).await?;
         persistence
             .write_persistence_global(key, ConvexValue::from(i64::from(cursor)).into())
             .await?;
-        checkpoint_writer.write().advance_checkpoint(cursor);
+        let mut checkpoint = checkpoint_writer.write();
+        checkpoint.advance_checkpoint(cursor);
+        checkpoint.table_checkpoints = table_cursors;
         Ok(())
     }
 
+    async fn get_table_checkpoints(
+        persistence: &dyn PersistenceReader,
+        retention_type: RetentionType,
+    ) -> anyhow::Result<BTreeMap<TabletId, Timestamp>> {
+        let key = match retention_type {
+            RetentionType::Document => {
+                PersistenceGlobalKey::TableDocumentRetentionConfirmedDeletedTimestamps
+            },
+            RetentionType::Index => PersistenceGlobalKey::TableRetentionConfirmedDeletedTimestamps,
+        };
+        get_table_timestamps(persistence, key).await
+    }
+
     pub async fn get_checkpoint_no_logging(
         persistence: &dyn PersistenceReader,
         retention_type: RetentionType,
@@ -1429,6 +1832,42 @@ impl<RT: Runtime> RetentionValidator for LeaderRetentionManager<RT> {
         Ok(())
     }
 
+    async fn validate_table_snapshot(
+        &self,
+        tablet_id: TabletId,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        let min_snapshot_ts = self.bounds_reader.lock().min_table_snapshot_ts(tablet_id);
+        log_snapshot_verification_age(&self.rt, ts, min_snapshot_ts, false, true);
+        if ts < min_snapshot_ts {
+            anyhow::bail!(snapshot_invalid_error(
+                ts,
+                min_snapshot_ts,
+                RetentionType::Index
+            ));
+        }
+        Ok(())
+    }
+
+    async fn validate_table_document_snapshot(
+        &self,
+        tablet_id: TabletId,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        let min_snapshot_ts = self
+            .bounds_reader
+            .lock()
+            .min_table_document_snapshot_ts(tablet_id);
+        if ts < min_snapshot_ts {
+            anyhow::bail!(snapshot_invalid_error(
+                ts,
+                min_snapshot_ts,
+                RetentionType::Document
+            ));
+        }
+        Ok(())
+    }
+
     fn optimistic_validate_snapshot(&self, ts: Timestamp) -> anyhow::Result<()> {
         let min_snapshot_ts = self.bounds_reader.lock().min_snapshot_ts;
         log_snapshot_verification_age(&self.rt, ts, min_snapshot_ts, true, true);
@@ -1455,7 +1894,7 @@ impl<RT: Runtime> RetentionValidator for LeaderRetentionManager<RT> {
         let checkpoint = self.checkpoint_reader.lock().checkpoint;
         if let Some(checkpoint) = checkpoint {
             let age = Timestamp::try_from(self.rt.system_time())?.secs_since_f64(checkpoint);
-            let retention_delay_seconds = (*INDEX_RETENTION_DELAY).as_secs();
+            let retention_delay_seconds = INDEX_RETENTION_DELAY.get().as_secs();
 
             let min_failure_duration = Duration::from_secs(
                 retention_delay_seconds * *RETENTION_FAIL_START_MULTIPLIER as u64,
@@ -1505,9 +1944,17 @@ impl<RT: Runtime> FollowerRetentionManager<RT> {
             latest_retention_min_snapshot_ts(persistence.as_ref(), RetentionType::Index).await?;
         let min_document_snapshot_ts =
             latest_retention_min_snapshot_ts(persistence.as_ref(), RetentionType::Document).await?;
+        let min_table_snapshot_ts =
+            latest_table_retention_min_snapshot_ts(persistence.as_ref(), RetentionType::Index)
+                .await?;
+        let min_table_document_snapshot_ts =
+            latest_table_retention_min_snapshot_ts(persistence.as_ref(), RetentionType::Document)
+                .await?;
         let snapshot_bounds = Arc::new(Mutex::new(SnapshotBounds {
             min_snapshot_ts,
             min_document_snapshot_ts,
+            min_table_snapshot_ts,
+            min_table_document_snapshot_ts,
         }));
         Ok(Self {
             rt,
@@ -1544,6 +1991,57 @@ impl<RT: Runtime> RetentionValidator for FollowerRetentionManager<RT> {
         Ok(())
     }
 
+    async fn validate_table_snapshot(
+        &self,
+        tablet_id: TabletId,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        self.min_snapshot_ts().await?;
+        let latest =
+            latest_table_retention_min_snapshot_ts(self.persistence.as_ref(), RetentionType::Index)
+                .await?;
+        let min_snapshot_ts = {
+            let mut snapshot_bounds = self.snapshot_bounds.lock();
+            snapshot_bounds.min_table_snapshot_ts = latest;
+            snapshot_bounds.min_table_snapshot_ts(tablet_id)
+        };
+        log_snapshot_verification_age(&self.rt, ts, min_snapshot_ts, false, false);
+        if ts < min_snapshot_ts {
+            anyhow::bail!(snapshot_invalid_error(
+                ts,
+                min_snapshot_ts,
+                RetentionType::Index
+            ));
+        }
+        Ok(())
+    }
+
+    async fn validate_table_document_snapshot(
+        &self,
+        tablet_id: TabletId,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        self.min_document_snapshot_ts().await?;
+        let latest = latest_table_retention_min_snapshot_ts(
+            self.persistence.as_ref(),
+            RetentionType::Document,
+        )
+        .await?;
+        let min_snapshot_ts = {
+            let mut snapshot_bounds = self.snapshot_bounds.lock();
+            snapshot_bounds.min_table_document_snapshot_ts = latest;
+            snapshot_bounds.min_table_document_snapshot_ts(tablet_id)
+        };
+        if ts < min_snapshot_ts {
+            anyhow::bail!(snapshot_invalid_error(
+                ts,
+                min_snapshot_ts,
+                RetentionType::Document
+            ));
+        }
+        Ok(())
+    }
+
     fn optimistic_validate_snapshot(&self, ts: Timestamp) -> anyhow::Result<()> {
         let min_snapshot_ts = self.snapshot_bounds.lock().min_snapshot_ts;
         log_snapshot_verification_age(&self.rt, ts, min_snapshot_ts, true, false);
@@ -1607,6 +2105,7 @@ mod tests {
             ConflictStrategy,
             NoopRetentionValidator,
             Persistence,
+            PersistenceGlobalKey,
             RepeatablePersistence,
         },
         query::Order,
@@ -1626,6 +2125,7 @@ mod tests {
         },
         value::{
             ConvexValue,
+            InternalDocumentId,
             ResolvedDocumentId,
             TableName,
         },
@@ -1645,6 +2145,7 @@ mod tests {
     use super::LeaderRetentionManager;
     use crate::retention::{
         snapshot_invalid_error,
+        RetentionRanges,
         RetentionType,
     };
 
@@ -1786,6 +2287,7 @@ mod tests {
             reader,
             Timestamp::MIN,
             min_snapshot_ts,
+            &RetentionRanges::global(Timestamp::MIN, min_snapshot_ts),
             &all_indexes,
             persistence_version,
         );
@@ -1858,7 +2360,7 @@ mod tests {
         // The max repeatable ts needs to be ahead of Timestamp::MIN by at least the
         // retention delay, so the anyhow::ensure before we delete doesn't fail
         let repeatable_ts =
-            unchecked_repeatable_ts(min_snapshot_ts.add(*DOCUMENT_RETENTION_DELAY)?);
+            unchecked_repeatable_ts(min_snapshot_ts.add(DOCUMENT_RETENTION_DELAY.get())?);
 
         let reader = p.reader();
         let retention_validator = Arc::new(NoopRetentionValidator);
@@ -1869,6 +2371,7 @@ mod tests {
             reader,
             Timestamp::MIN,
             min_snapshot_ts,
+            &RetentionRanges::global(Timestamp::MIN, min_snapshot_ts),
         );
         let scanned: Vec<_> = scanned_stream.try_collect().await?;
         let expired: Vec<_> = scanned
@@ -1942,7 +2445,7 @@ mod tests {
         // The max repeatable ts needs to be ahead of Timestamp::MIN by at least the
         // retention delay, so the anyhow::ensure before we delete doesn't fail
         let repeatable_ts =
-            unchecked_repeatable_ts(min_snapshot_ts.add(*DOCUMENT_RETENTION_DELAY)?);
+            unchecked_repeatable_ts(min_snapshot_ts.add(DOCUMENT_RETENTION_DELAY.get())?);
 
         let retention_validator = Arc::new(NoopRetentionValidator);
         let reader = p.reader();
@@ -1954,6 +2457,7 @@ mod tests {
             reader.clone(),
             Timestamp::MIN,
             min_snapshot_ts,
+            &RetentionRanges::global(Timestamp::MIN, min_snapshot_ts),
         );
         let scanned: Vec<_> = scanned_stream.try_collect().await?;
         let expired: Vec<_> = scanned
@@ -2002,4 +2506,122 @@ mod tests {
 
         Ok(())
     }
+
+    #[convex_macro::test_runtime]
+    async fn test_delete_documents_with_table_retention(rt: TestRuntime) -> anyhow::Result<()> {
+        env::set_var("DOCUMENT_RETENTION_DRY_RUN", "false");
+        env::set_var("RETENTION_DOCUMENT_DELETES_ENABLED", "true");
+        let p = Arc::new(TestPersistence::new());
+        let mut id_generator = TestIdGenerator::new();
+        let shorter: TableName = str::parse("shorter")?;
+        let default: TableName = str::parse("default")?;
+        let longer: TableName = str::parse("longer")?;
+        let shorter_id = id_generator.user_generate(&shorter);
+        let default_id = id_generator.user_generate(&default);
+        let longer_id = id_generator.user_generate(&longer);
+
+        let mut documents = vec![];
+        for ts in 1..=6 {
+            for id in [shorter_id, default_id, longer_id] {
+                documents.push(doc(id, ts, Some(ts.into()))?);
+            }
+        }
+        p.write(documents, BTreeSet::new(), ConflictStrategy::Error)
+            .await?;
+        p.write_persistence_global(
+            PersistenceGlobalKey::MaxRepeatableTimestamp,
+            Timestamp::must(10).into(),
+        )
+        .await?;
+
+        // "default" uses the global range, "shorter" is ahead of it and
+        // "longer" behind it.
+        let ranges = RetentionRanges::new(
+            Timestamp::MIN,
+            Timestamp::must(4),
+            &btreemap! {
+                shorter_id.tablet_id => Timestamp::must(6),
+                longer_id.tablet_id => Timestamp::must(2),
+            },
+            &btreemap! {},
+        );
+        assert_eq!(ranges.windows(), vec![(Timestamp::MIN, Timestamp::must(6))]);
+        let (ranges, _) = LeaderRetentionManager::<TestRuntime>::delete_documents(
+            ranges,
+            p.clone(),
+            &rt,
+            Arc::new(NoopRetentionValidator),
+        )
+        .await?;
+        assert_eq!(ranges.global, (Timestamp::must(3), Timestamp::must(4)));
+        assert_eq!(
+            ranges.table_cursors(),
+            btreemap! {
+                shorter_id.tablet_id => Timestamp::must(5),
+                longer_id.tablet_id => Timestamp::must(1),
+            }
+        );
+
+        // Each table keeps the revisions visible at its own bound and later.
+        let remaining: Vec<_> = p
+            .reader()
+            .load_all_documents()
+            .try_collect::<Vec<_>>()
+            .await?;
+        let revisions = |id: ResolvedDocumentId| -> Vec<i32> {
+            remaining
+                .iter()
+                .filter(|(_, doc_id, _)| *doc_id == InternalDocumentId::from(id))
+                .map(|(ts, ..)| i64::from(*ts) as i32)
+                .collect()
+        };
+        assert_eq!(revisions(shorter_id), vec![5, 6]);
+        assert_eq!(revisions(default_id), vec![3, 4, 5, 6]);
+        assert_eq!(revisions(longer_id), vec![1, 2, 3, 4, 5, 6]);
+
+        // Once "longer" loses its own retention, it keeps its cursor until it
+        // catches up with the global one, and is then deleted by the global
+        // pass.
+        let ranges = RetentionRanges::new(
+            Timestamp::must(3),
+            Timestamp::must(4),
+            &btreemap! {},
+            &ranges.table_cursors(),
+        );
+        assert_eq!(
+            ranges.tables,
+            btreemap! { longer_id.tablet_id => (Timestamp::must(1), Timestamp::must(4)) }
+        );
+        let (ranges, _) = LeaderRetentionManager::<TestRuntime>::delete_documents(
+            ranges,
+            p.clone(),
+            &rt,
+            Arc::new(NoopRetentionValidator),
+        )
+        .await?;
+        let ranges = RetentionRanges::new(
+            ranges.global.0,
+            Timestamp::must(4),
+            &btreemap! {},
+            &ranges.table_cursors(),
+        );
+        assert_eq!(
+            ranges,
+            RetentionRanges::global(Timestamp::must(3), Timestamp::must(4))
+        );
+        let remaining: Vec<_> = p
+            .reader()
+            .load_all_documents()
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(
+            remaining
+                .iter()
+                .filter(|(_, doc_id, _)| *doc_id == InternalDocumentId::from(longer_id))
+                .count(),
+            4
+        );
+
+        Ok(())
+    }
 }
@@ -11,9 +11,11 @@ use common::{
         DocumentUpdate,
         ResolvedDocument,
     },
+    interval::Interval,
     runtime::Runtime,
     types::{
         DatabaseIndexUpdate,
+        IndexId,
         RepeatableReason,
         RepeatableTimestamp,
         Timestamp,
@@ -23,6 +25,10 @@ use common::{
 use errors::ErrorMetadata;
 use imbl::OrdMap;
 use indexing::{
+    aggregate_index::{
+        AggregateDelta,
+        AggregateIndexes,
+    },
     backend_in_memory_indexes::BackendInMemoryIndexes,
     index_registry::IndexRegistry,
 };
@@ -38,6 +44,7 @@ use value::{
 use vector::VectorIndexManager;
 
 use crate::{
+    aggregate_index_snapshot,
     table_registry::{
         TableUpdate,
         TableUpdateMode,
@@ -70,15 +77,39 @@ pub struct TableSummaries {
     pub user_size: usize,
 }
 
+/// The parts of a [`Snapshot`] that answer [`TableCountSnapshot`] queries.
+pub struct CountSnapshot {
+    table_summaries: TableSummaries,
+    aggregate_indexes: AggregateIndexes,
+}
+
+impl CountSnapshot {
+    pub fn new(table_summaries: TableSummaries, aggregate_indexes: AggregateIndexes) -> Self {
+        Self {
+            table_summaries,
+            aggregate_indexes,
+        }
+    }
+}
+
 #[async_trait]
-impl TableCountSnapshot for TableSummaries {
+impl TableCountSnapshot for CountSnapshot {
     async fn count(&self, table: TabletId) -> anyhow::Result<u64> {
         let count = self
+            .table_summaries
             .tables
             .get(&table)
             .map_or(0, |summary| summary.num_values() as u64);
         Ok(count)
     }
+
+    async fn aggregate_deltas(
+        &self,
+        index_id: IndexId,
+        interval: &Interval,
+    ) -> anyhow::Result<Option<Vec<AggregateDelta>>> {
+        Ok(self.aggregate_indexes.deltas(&index_id, interval))
+    }
 }
 
 impl TableSummaries {
@@ -181,6 +212,7 @@ pub struct Snapshot<RT: Runtime> {
     pub table_summaries: TableSummaries,
     pub index_registry: IndexRegistry,
     pub in_memory_indexes: BackendInMemoryIndexes,
+    pub aggregate_indexes: AggregateIndexes,
     pub search_indexes: SearchIndexManager<RT>,
     pub vector_indexes: VectorIndexManager,
 }
@@ -216,6 +248,14 @@ impl<RT: Runtime> Snapshot<RT> {
         self.index_registry
             .update(removal, insertion)
             .context("Index update failed")?;
+        aggregate_index_snapshot::update_aggregate_indexes(
+            &mut self.aggregate_indexes,
+            &self.index_registry,
+            self.table_registry.table_mapping(),
+            removal,
+            insertion,
+        )
+        .context("Aggregate index update failed")?;
         let in_memory_index_updates = self.in_memory_indexes.update(
             &self.index_registry,
             commit_ts,
@@ -389,6 +429,14 @@ impl<RT: Runtime> SnapshotManager<RT> {
         snapshot.in_memory_indexes = in_memory_indexes;
     }
 
+    pub fn overwrite_last_snapshot_aggregate_indexes(
+        &mut self,
+        aggregate_indexes: AggregateIndexes,
+    ) {
+        let (_ts, ref mut snapshot) = self.versions.back_mut().expect("snapshot versions empty");
+        snapshot.aggregate_indexes = aggregate_indexes;
+    }
+
     pub fn push(&mut self, ts: Timestamp, snapshot: Snapshot<RT>) {
         assert!(*self.latest_ts() < ts);
         while self.versions.len() > 1 && (ts - self.earliest_ts()) > MAX_TRANSACTION_WINDOW {
@@ -68,7 +68,8 @@ pub fn assert_backfilling(
     let index_metadata: common::bootstrap_model::index::IndexMetadata<value::TabletId> =
         get_recent_index_metadata(&mut tx, table_name, index_name)?;
     match index_metadata.config {
-        IndexConfig::Database { on_disk_state, .. } => {
+        IndexConfig::Database { on_disk_state, .. }
+        | IndexConfig::Aggregate { on_disk_state, .. } => {
             assert_matches!(on_disk_state, DatabaseIndexState::Backfilling(_))
         },
         IndexConfig::Search { on_disk_state, .. } => {
@@ -90,7 +91,8 @@ pub async fn assert_backfilled(
     let index_metadata: common::bootstrap_model::index::IndexMetadata<value::TabletId> =
         get_recent_index_metadata(&mut tx, table_name, index_name)?;
     match index_metadata.config {
-        IndexConfig::Database { on_disk_state, .. } => {
+        IndexConfig::Database { on_disk_state, .. }
+        | IndexConfig::Aggregate { on_disk_state, .. } => {
             assert_matches!(on_disk_state, DatabaseIndexState::Backfilled { .. })
         },
         IndexConfig::Search { on_disk_state, .. } => {
@@ -112,7 +114,8 @@ pub async fn assert_enabled(
     let index_metadata: common::bootstrap_model::index::IndexMetadata<value::TabletId> =
         get_recent_index_metadata(&mut tx, table_name, index_name)?;
     match index_metadata.config {
-        IndexConfig::Database { on_disk_state, .. } => {
+        IndexConfig::Database { on_disk_state, .. }
+        | IndexConfig::Aggregate { on_disk_state, .. } => {
             assert_eq!(on_disk_state, DatabaseIndexState::Enabled)
         },
         IndexConfig::Search { on_disk_state, .. } => {
@@ -195,5 +198,12 @@ pub fn get_index_fields<T: TableIdentifier>(index_metadata: IndexMetadata<T>) ->
             .iter()
             .map(|field| field.to_string())
             .collect(),
+        IndexConfig::Aggregate {
+            developer_config, ..
+        } => developer_config
+            .fields
+            .iter()
+            .flat_map(|field_path| field_path.fields().iter().map(|field| field.to_string()))
+            .collect(),
     }
 }
@@ -10,21 +10,33 @@ use std::{
 };
 
 use ::usage_tracking::FunctionUsageTracker;
+use anyhow::Context;
 use common::{
     assert_obj,
-    bootstrap_model::index::{
-        database_index::{
-            DeveloperDatabaseIndexConfig,
-            IndexedFields,
+    bootstrap_model::{
+        index::{
+            aggregate_index::DeveloperAggregateIndexConfig,
+            database_index::{
+                DeveloperDatabaseIndexConfig,
+                IndexedFields,
+            },
+            IndexConfig,
+            IndexMetadata,
         },
-        IndexConfig,
-        IndexMetadata,
+        schema::SchemaState,
     },
     db_schema,
     document::{
         CreationTime,
         ResolvedDocument,
     },
+    interval::Interval,
+    knobs::{
+        DOCUMENT_RETENTION_DELAY,
+        INDEX_BACKFILL_CHUNK_SIZE,
+        MAX_TABLE_RETENTION,
+        MIN_TABLE_RETENTION,
+    },
     maybe_val,
     object_validator,
     pause::PauseClient,
@@ -41,6 +53,7 @@ use common::{
     },
     runtime::Runtime,
     schemas::{
+        constraints::ValueConstraints,
         validator::{
             FieldValidator,
             Validator,
@@ -48,6 +61,8 @@ use common::{
         DatabaseSchema,
         DocumentSchema,
         IndexSchema,
+        OnDelete,
+        ReferenceSchema,
         TableDefinition,
         MAX_INDEXES_PER_TABLE,
     },
@@ -55,8 +70,10 @@ use common::{
         unchecked_repeatable_ts,
         IndexDescriptor,
         IndexName,
+        MaybeValue,
         RepeatableTimestamp,
         TableName,
+        TabletIndexName,
     },
     value::{
         ConvexObject,
@@ -67,6 +84,7 @@ use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
 };
+use futures::TryStreamExt;
 use imbl::OrdSet;
 use keybroker::Identity;
 use must_let::must_let;
@@ -87,6 +105,11 @@ use value::{
 };
 
 use crate::{
+    aggregate_index_snapshot::{
+        flush,
+        AggregateIndexSnapshot,
+    },
+    defaults::SystemTable,
     index_worker::{
         IndexSelector,
         IndexWriter,
@@ -106,19 +129,23 @@ use crate::{
         DbFixturesArgs,
     },
     write_log::WriteSource,
+    AggregateIndexCountsTable,
     Database,
     DatabaseSnapshot,
     ImportFacingModel,
     IndexModel,
     IndexWorker,
+    PatchValue,
     SchemaModel,
     SystemMetadataModel,
     TableModel,
     TestFacingModel,
     Transaction,
     UserFacingModel,
+    AGGREGATE_INDEX_COUNTS_TABLE,
 };
 
+mod point_in_time_tests;
 mod randomized_search_tests;
 mod streaming_export_tests;
 mod usage_tracking;
@@ -244,6 +271,8 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
         IndexSchema {
             index_descriptor: index_name1.descriptor().clone(),
             fields: vec![str::parse("a")?, str::parse("b")?].try_into()?,
+            unique: false,
+            filter: None,
         },
     );
     indexes.insert(
@@ -251,6 +280,8 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
         IndexSchema {
             index_descriptor: index_name2.descriptor().clone(),
             fields: vec![str::parse("c")?, str::parse("d")?].try_into()?,
+            unique: false,
+            filter: None,
         },
     );
 
@@ -262,6 +293,8 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
             indexes,
             search_indexes: BTreeMap::new(),
             vector_indexes: BTreeMap::new(),
+            aggregate_indexes: BTreeMap::new(),
+            references: BTreeMap::new(),
             document_type: None,
         },
     );
@@ -300,6 +333,8 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
         IndexSchema {
             index_descriptor: index_name2.descriptor().clone(),
             fields: vec![str::parse("c")?].try_into()?,
+            unique: false,
+            filter: None,
         },
     );
     indexes.insert(
@@ -307,6 +342,8 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
         IndexSchema {
             index_descriptor: index_name3.descriptor().clone(),
             fields: vec![str::parse("e")?, str::parse("f")?].try_into()?,
+            unique: false,
+            filter: None,
         },
     );
 
@@ -318,6 +355,8 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
             indexes,
             search_indexes: BTreeMap::new(),
             vector_indexes: BTreeMap::new(),
+            aggregate_indexes: BTreeMap::new(),
+            references: BTreeMap::new(),
             document_type: None,
         },
     );
@@ -374,7 +413,7 @@ fn get_pending_index_fields(
         .pending_index_metadata(namespace, index_name)?
         .expect("index should exist");
     must_let!(let IndexConfig::Database { developer_config, .. } = &index_c_d.config);
-    must_let!(let DeveloperDatabaseIndexConfig { fields } = developer_config);
+    must_let!(let DeveloperDatabaseIndexConfig { fields, .. } = developer_config);
     Ok(fields.clone())
 }
 
@@ -1562,6 +1601,66 @@ async fn test_interrupted_import_then_delete_table(rt: TestRuntime) -> anyhow::R
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_set_table_document_retention(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let table_name: TableName = "table".parse()?;
+    let namespace = TableNamespace::test_user();
+
+    let mut tx = database.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!())
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let mut table_model = TableModel::new(&mut tx);
+    assert_eq!(
+        table_model
+            .document_retention(namespace, &table_name)
+            .await?,
+        None
+    );
+    for invalid in [
+        MIN_TABLE_RETENTION.saturating_sub(Duration::from_secs(1)),
+        *MAX_TABLE_RETENTION + Duration::from_secs(1),
+    ] {
+        assert!(table_model
+            .set_document_retention(namespace, &table_name, Some(invalid))
+            .await
+            .is_err());
+    }
+    assert!(table_model
+        .set_document_retention(namespace, &"missing".parse()?, Some(*MIN_TABLE_RETENTION))
+        .await
+        .is_err());
+    // Tables can keep their history for longer than the default too.
+    let longer = DOCUMENT_RETENTION_DELAY.get() + Duration::from_secs(1);
+    table_model
+        .set_document_retention(namespace, &table_name, Some(longer))
+        .await?;
+    database.commit(tx).await?;
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let mut table_model = TableModel::new(&mut tx);
+    assert_eq!(
+        table_model
+            .document_retention(namespace, &table_name)
+            .await?,
+        Some(longer)
+    );
+    table_model
+        .set_document_retention(namespace, &table_name, None)
+        .await?;
+    assert_eq!(
+        table_model
+            .document_retention(namespace, &table_name)
+            .await?,
+        None
+    );
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn add_indexes_at_limit_with_backfilling_index_adds_index(
     rt: TestRuntime,
@@ -1825,6 +1924,1153 @@ async fn test_index_backfill(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_unique_index(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = str::parse("users")?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), "by_email".parse()?)?;
+
+    let mut tx = db.begin_system().await?;
+    let begin_ts = tx.begin_timestamp();
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_backfilling_database_index(
+                *begin_ts,
+                index_name.clone(),
+                DeveloperDatabaseIndexConfig {
+                    fields: vec![str::parse("email")?].try_into()?,
+                    unique: true,
+                    filter: None,
+                },
+            ),
+        )
+        .await?;
+    db.commit(tx).await?;
+    IndexWorker::new_terminating(rt, tp, Arc::new(NoopRetentionValidator), db.clone()).await?;
+    let mut tx = db.begin_system().await?;
+    IndexModel::new(&mut tx)
+        .enable_index_for_testing(namespace, &index_name)
+        .await?;
+    db.commit(tx).await?;
+
+    let mut tx = db.begin_system().await?;
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("email" => "a@example.com"))
+        .await?;
+    // Documents without the indexed field never conflict.
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!())
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!())
+        .await?;
+    db.commit(tx).await?;
+
+    // Inserting a duplicate fails.
+    let mut tx = db.begin_system().await?;
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("email" => "a@example.com"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "UniqueIndexViolation");
+
+    // Updating a document without changing its indexed values succeeds.
+    let mut tx = db.begin_system().await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(id, assert_obj!("email" => "a@example.com", "name" => "a"))
+        .await?;
+    // And so does moving the value to a new document after the old one changes.
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(id, assert_obj!("email" => "b@example.com"))
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("email" => "a@example.com"))
+        .await?;
+    db.commit(tx).await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_unique_index_checked_once_backfilled(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = str::parse("users")?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), "by_email".parse()?)?;
+
+    let mut tx = db.begin_system().await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("email" => "a@example.com"))
+        .await?;
+    let begin_ts = tx.begin_timestamp();
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_backfilling_database_index(
+                *begin_ts,
+                index_name.clone(),
+                DeveloperDatabaseIndexConfig {
+                    fields: vec![str::parse("email")?].try_into()?,
+                    unique: true,
+                    filter: None,
+                },
+            ),
+        )
+        .await?;
+    db.commit(tx).await?;
+    IndexWorker::new_terminating(rt, tp, Arc::new(NoopRetentionValidator), db.clone()).await?;
+
+    // Writes are checked against the index as soon as it's backfilled, before
+    // it's enabled.
+    let mut tx = db.begin_system().await?;
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("email" => "a@example.com"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "UniqueIndexViolation");
+
+    // Including writes in the same transaction.
+    let mut tx = db.begin_system().await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("email" => "b@example.com"))
+        .await?;
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("email" => "b@example.com"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "UniqueIndexViolation");
+
+    let mut tx = db.begin_system().await?;
+    IndexModel::new(&mut tx)
+        .enable_index_for_testing(namespace, &index_name)
+        .await?;
+    db.commit(tx).await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_unique_index_with_duplicates_dropped(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = str::parse("users")?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), "by_email".parse()?)?;
+
+    let mut tx = db.begin_system().await?;
+    for _ in 0..2 {
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table_name.clone(), assert_obj!("email" => "a@example.com"))
+            .await?;
+    }
+    let begin_ts = tx.begin_timestamp();
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_backfilling_database_index(
+                *begin_ts,
+                index_name.clone(),
+                DeveloperDatabaseIndexConfig {
+                    fields: vec![str::parse("email")?].try_into()?,
+                    unique: true,
+                    filter: None,
+                },
+            ),
+        )
+        .await?;
+    db.commit(tx).await?;
+    IndexWorker::new_terminating(rt, tp, Arc::new(NoopRetentionValidator), db.clone()).await?;
+
+    // The index can never be enabled, so the worker drops it.
+    let mut tx = db.begin_system().await?;
+    assert!(IndexModel::new(&mut tx)
+        .pending_index_metadata(namespace, &index_name)?
+        .is_none());
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_partial_index(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = str::parse("tasks")?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), "open_by_priority".parse()?)?;
+
+    // Documents written before the index exists are backfilled.
+    let mut tx = db.begin_system().await?;
+    let mut ids = vec![];
+    for (status, priority) in [("open", 1), ("closed", 2), ("open", 3)] {
+        let id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(
+                table_name.clone(),
+                assert_obj!("status" => status, "priority" => priority),
+            )
+            .await?;
+        ids.push(id);
+    }
+    let (closed_later, backfilled) = (ids[0], ids[2]);
+    let begin_ts = tx.begin_timestamp();
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_backfilling_database_index(
+                *begin_ts,
+                index_name.clone(),
+                DeveloperDatabaseIndexConfig {
+                    fields: vec![str::parse("status")?, str::parse("priority")?].try_into()?,
+                    unique: false,
+                    filter: Some(Expression::field_eq_literal(
+                        "status".parse()?,
+                        val!("open"),
+                    )),
+                },
+            ),
+        )
+        .await?;
+    db.commit(tx).await?;
+    IndexWorker::new_terminating(rt, tp.clone(), Arc::new(NoopRetentionValidator), db.clone())
+        .await?;
+    let mut tx = db.begin_system().await?;
+    IndexModel::new(&mut tx)
+        .enable_index_for_testing(namespace, &index_name)
+        .await?;
+    db.commit(tx).await?;
+
+    // Writes add and remove entries as documents start and stop matching.
+    let mut tx = db.begin_system().await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            table_name.clone(),
+            assert_obj!("status" => "closed", "priority" => 4),
+        )
+        .await?;
+    let inserted = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            table_name.clone(),
+            assert_obj!("status" => "open", "priority" => 5),
+        )
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(
+            closed_later,
+            assert_obj!("status" => "closed", "priority" => 1),
+        )
+        .await?;
+    let ts = db.commit(tx).await?;
+
+    // Only the open documents have index entries.
+    let mut tx = db.begin_system().await?;
+    let tablet_id = tx
+        .table_mapping()
+        .namespace(namespace)
+        .id(&table_name)?
+        .tablet_id;
+    let index_id = tx
+        .index
+        .index_registry()
+        .get_enabled(&TabletIndexName::new(
+            tablet_id,
+            index_name.descriptor().clone(),
+        )?)
+        .context("Missing index")?
+        .id();
+    let indexed: Vec<_> = tp
+        .reader()
+        .index_scan(
+            index_id,
+            tablet_id,
+            ts,
+            &Interval::all(),
+            Order::Asc,
+            100,
+            Arc::new(NoopRetentionValidator),
+        )
+        .map_ok(|(_, _, document)| document.developer_id())
+        .try_collect()
+        .await?;
+    assert_eq!(indexed, vec![backfilled, inserted]);
+
+    // And a query using the index returns them in index order.
+    let query = Query {
+        source: QuerySource::IndexRange(IndexRange {
+            index_name: index_name.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                "status".parse()?,
+                maybe_val!("open"),
+            )],
+            order: Order::Desc,
+        }),
+        operators: vec![],
+    };
+    let actual: Vec<_> = run_query(db.clone(), namespace, query)
+        .await?
+        .iter()
+        .map(ResolvedDocument::developer_id)
+        .collect();
+    assert_eq!(actual, vec![inserted, backfilled]);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_references(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let users: TableName = str::parse("users")?;
+    let posts: TableName = str::parse("posts")?;
+    let comments: TableName = str::parse("comments")?;
+    let namespace = TableNamespace::test_user();
+    let reference_indexes = [
+        (posts.clone(), "by_author", "authorId", OnDelete::Cascade),
+        (posts.clone(), "by_editor", "editorId", OnDelete::SetNull),
+        (comments.clone(), "by_post", "postId", OnDelete::Restrict),
+    ];
+
+    let mut tx = db.begin_system().await?;
+    let begin_ts = tx.begin_timestamp();
+    for (table_name, descriptor, field, _) in &reference_indexes {
+        IndexModel::new(&mut tx)
+            .add_application_index(
+                namespace,
+                IndexMetadata::new_backfilling_database_index(
+                    *begin_ts,
+                    IndexName::new(table_name.clone(), descriptor.parse()?)?,
+                    DeveloperDatabaseIndexConfig {
+                        fields: vec![field.parse()?].try_into()?,
+                        unique: false,
+                        filter: None,
+                    },
+                ),
+            )
+            .await?;
+    }
+    db.commit(tx).await?;
+    IndexWorker::new_terminating(rt, tp, Arc::new(NoopRetentionValidator), db.clone()).await?;
+    let mut tx = db.begin_system().await?;
+    for (table_name, descriptor, ..) in &reference_indexes {
+        IndexModel::new(&mut tx)
+            .enable_index_for_testing(
+                namespace,
+                &IndexName::new(table_name.clone(), descriptor.parse()?)?,
+            )
+            .await?;
+    }
+    db.commit(tx).await?;
+
+    let mut db_schema = db_schema!(
+        users.clone() => DocumentSchema::Union(vec![object_validator!()]),
+        posts.clone() => DocumentSchema::Union(vec![object_validator!(
+            "authorId" => FieldValidator::required_field_type(Validator::Id(users.clone())),
+            "editorId" => FieldValidator::required_field_type(Validator::Union(vec![
+                Validator::Id(users.clone()),
+                Validator::Null,
+            ])),
+        )]),
+        comments.clone() => DocumentSchema::Union(vec![object_validator!(
+            "postId" => FieldValidator::required_field_type(Validator::Id(posts.clone())),
+        )])
+    );
+    for (table_name, descriptor, field, on_delete) in reference_indexes {
+        let definition = db_schema.tables.get_mut(&table_name).unwrap();
+        let reference = ReferenceSchema::new(
+            &table_name,
+            field.parse()?,
+            on_delete,
+            definition.document_type.as_ref(),
+        )?;
+        definition.indexes.insert(
+            descriptor.parse()?,
+            IndexSchema {
+                index_descriptor: descriptor.parse()?,
+                fields: vec![field.parse()?].try_into()?,
+                unique: false,
+                filter: None,
+            },
+        );
+        definition.references.insert(field.parse()?, reference);
+    }
+    let mut tx = db.begin_system().await?;
+    let mut schema_model = SchemaModel::new_root_for_test(&mut tx);
+    let (schema_id, _) = schema_model.submit_pending(db_schema).await?;
+    schema_model.mark_validated(schema_id).await?;
+    schema_model.mark_active(schema_id).await?;
+    db.commit(tx).await?;
+
+    let mut tx = db.begin_system().await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    let author = model.insert(users.clone(), assert_obj!()).await?;
+    let editor = model.insert(users.clone(), assert_obj!()).await?;
+    let deleted = model.insert(users.clone(), assert_obj!()).await?;
+    model.delete(deleted).await?;
+    let post = model
+        .insert(
+            posts.clone(),
+            assert_obj!("authorId" => author, "editorId" => editor),
+        )
+        .await?;
+    let comment = model
+        .insert(comments.clone(), assert_obj!("postId" => post))
+        .await?;
+    db.commit(tx).await?;
+
+    // Referencing a document that doesn't exist fails.
+    let mut tx = db.begin_system().await?;
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            posts.clone(),
+            assert_obj!("authorId" => deleted, "editorId" => ConvexValue::Null),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "DanglingReference");
+
+    // `restrict` keeps the post from being deleted while a comment references
+    // it.
+    let mut tx = db.begin_system().await?;
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .delete(post)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "ReferencedDocumentCannotBeDeleted");
+
+    // `setNull` clears the editor when they're deleted.
+    let mut tx = db.begin_system().await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    model.delete(editor).await?;
+    let post_document = model.get(post, None).await?.unwrap();
+    assert_eq!(
+        post_document.value().get("editorId"),
+        Some(&ConvexValue::Null)
+    );
+    db.commit(tx).await?;
+
+    // `cascade` deletes the author's posts along with them.
+    let mut tx = db.begin_system().await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    model.delete(comment).await?;
+    model.delete(author).await?;
+    assert!(model.get(post, None).await?.is_none());
+    db.commit(tx).await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_schema_defaults_and_constraints(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = new_test_database(rt).await;
+    let table_name: TableName = "tasks".parse()?;
+    let name_validator = Validator::constrained(
+        Validator::String,
+        ValueConstraints {
+            min_length: Some(1),
+            ..Default::default()
+        },
+    )?;
+    let status_validator = Validator::constrained(
+        Validator::String,
+        ValueConstraints {
+            default: Some(assert_val!("todo")),
+            ..Default::default()
+        },
+    )?;
+    let db_schema = db_schema!(table_name.clone() => DocumentSchema::Union(vec![
+        object_validator!(
+            "name" => FieldValidator::required_field_type(name_validator),
+            "status" => FieldValidator::optional_field_type(status_validator),
+        )
+    ]));
+    let mut tx = database.begin(Identity::system()).await?;
+    let mut schema_model = SchemaModel::new_root_for_test(&mut tx);
+    let (schema_id, _) = schema_model.submit_pending(db_schema).await?;
+    schema_model.mark_validated(schema_id).await?;
+    schema_model.mark_active(schema_id).await?;
+    database.commit(tx).await?;
+
+    // Missing fields are set to their default on insert.
+    let mut tx = database.begin(Identity::system()).await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    let id = model
+        .insert(table_name.clone(), assert_obj!("name" => "write tests"))
+        .await?;
+    let document = model.get(id, None).await?.unwrap();
+    assert_eq!(document.value().get("status"), Some(&assert_val!("todo")));
+    let id = model
+        .insert(
+            table_name.clone(),
+            assert_obj!("name" => "ship", "status" => "done"),
+        )
+        .await?;
+    let document = model.get(id, None).await?.unwrap();
+    assert_eq!(document.value().get("status"), Some(&assert_val!("done")));
+
+    // Inserts and patches that violate a constraint fail.
+    let err = model
+        .insert(table_name.clone(), assert_obj!("name" => ""))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "SchemaEnforcementError");
+    let err = model
+        .patch(
+            id,
+            PatchValue::from(BTreeMap::from([(
+                "name".parse()?,
+                MaybeValue(Some(assert_val!(""))),
+            )])),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "SchemaEnforcementError");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_references_with_backfilling_index(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let posts: TableName = str::parse("posts")?;
+    let comments: TableName = str::parse("comments")?;
+    let namespace = TableNamespace::test_user();
+
+    // The index for the reference is added but never backfilled, as when the
+    // schema declaring it is still being pushed.
+    let mut tx = db.begin_system().await?;
+    let begin_ts = tx.begin_timestamp();
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_backfilling_database_index(
+                *begin_ts,
+                IndexName::new(comments.clone(), "by_post".parse()?)?,
+                DeveloperDatabaseIndexConfig {
+                    fields: vec!["postId".parse()?].try_into()?,
+                    unique: false,
+                    filter: None,
+                },
+            ),
+        )
+        .await?;
+    db.commit(tx).await?;
+
+    let mut db_schema = db_schema!(
+        posts.clone() => DocumentSchema::Union(vec![object_validator!()]),
+        comments.clone() => DocumentSchema::Union(vec![object_validator!(
+            "postId" => FieldValidator::required_field_type(Validator::Id(posts.clone())),
+        )])
+    );
+    let definition = db_schema.tables.get_mut(&comments).unwrap();
+    let reference = ReferenceSchema::new(
+        &comments,
+        "postId".parse()?,
+        OnDelete::Restrict,
+        definition.document_type.as_ref(),
+    )?;
+    definition.indexes.insert(
+        "by_post".parse()?,
+        IndexSchema {
+            index_descriptor: "by_post".parse()?,
+            fields: vec!["postId".parse()?].try_into()?,
+            unique: false,
+            filter: None,
+        },
+    );
+    definition.references.insert("postId".parse()?, reference);
+    let mut tx = db.begin_system().await?;
+    let (schema_id, _) = SchemaModel::new_root_for_test(&mut tx)
+        .submit_pending(db_schema)
+        .await?;
+    db.commit(tx).await?;
+
+    let mut tx = db.begin_system().await?;
+    let mut model = UserFacingModel::new_root_for_test(&mut tx);
+    let post = model.insert(posts.clone(), assert_obj!()).await?;
+    model
+        .insert(comments.clone(), assert_obj!("postId" => post))
+        .await?;
+    db.commit(tx).await?;
+
+    // Deleting the referenced post doesn't query the backfilling index, and
+    // leaves checking the pending schema to the schema worker.
+    let mut tx = db.begin_system().await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(post)
+        .await?;
+    db.commit(tx).await?;
+    let mut tx = db.begin_system().await?;
+    let (pending_id, _) = SchemaModel::new_root_for_test(&mut tx)
+        .get_by_state(SchemaState::Pending)
+        .await?
+        .unwrap();
+    assert_eq!(pending_id, schema_id);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_aggregate_index(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = str::parse("orders")?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), "by_customer".parse()?)?;
+
+    // Documents written before the index exists are picked up by backfill.
+    let mut tx = db.begin_system().await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            table_name.clone(),
+            assert_obj!("customer" => "a", "total" => 10.0),
+        )
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            table_name.clone(),
+            assert_obj!("customer" => "b", "total" => 5.0),
+        )
+        .await?;
+    db.commit(tx).await?;
+
+    let mut tx = db.begin_system().await?;
+    let begin_ts = tx.begin_timestamp();
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_backfilling_aggregate_index(
+                *begin_ts,
+                index_name.clone(),
+                DeveloperAggregateIndexConfig {
+                    fields: vec![str::parse("customer")?].try_into()?,
+                    value_field: Some(str::parse("total")?),
+                },
+            ),
+        )
+        .await?;
+    db.commit(tx).await?;
+    IndexWorker::new_terminating(rt, tp, Arc::new(NoopRetentionValidator), db.clone()).await?;
+    let mut tx = db.begin_system().await?;
+    IndexModel::new(&mut tx)
+        .enable_index_for_testing(namespace, &index_name)
+        .await?;
+    db.commit(tx).await?;
+
+    let mut tx = db.begin_system().await?;
+    let all = tx.aggregate(namespace, &index_name, vec![]).await?;
+    assert_eq!(all.count(), 2);
+    assert_eq!(all.sum(), 15.0);
+    assert_eq!(all.min(), Some(5.0));
+    assert_eq!(all.max(), Some(10.0));
+
+    // The transaction's own writes are reflected before commit.
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            table_name.clone(),
+            assert_obj!("customer" => "a", "total" => 20.0),
+        )
+        .await?;
+    // Documents without a numeric value are counted but not summed.
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("customer" => "a"))
+        .await?;
+    let a = tx
+        .aggregate(namespace, &index_name, customer_is("a")?)
+        .await?;
+    assert_eq!(a.count(), 3);
+    assert_eq!(a.sum(), 30.0);
+    assert_eq!(a.max(), Some(20.0));
+    db.commit(tx).await?;
+
+    let mut tx = db.begin_system().await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(id, assert_obj!("customer" => "b", "total" => 1.0))
+        .await?;
+    db.commit(tx).await?;
+
+    let mut tx = db.begin_system().await?;
+    let a = tx
+        .aggregate(namespace, &index_name, customer_is("a")?)
+        .await?;
+    assert_eq!(a.count(), 2);
+    assert_eq!(a.sum(), 10.0);
+    assert_eq!(a.max(), Some(10.0));
+    let b = tx
+        .aggregate(namespace, &index_name, customer_is("b")?)
+        .await?;
+    assert_eq!(b.count(), 2);
+    assert_eq!(b.min(), Some(1.0));
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(id)
+        .await?;
+    db.commit(tx).await?;
+
+    let mut tx = db.begin_system().await?;
+    let b = tx
+        .aggregate(namespace, &index_name, customer_is("b")?)
+        .await?;
+    assert_eq!(b.count(), 1);
+    assert_eq!(b.min(), Some(5.0));
+    let missing = tx
+        .aggregate(namespace, &index_name, customer_is("c")?)
+        .await?;
+    assert_eq!(missing.count(), 0);
+    assert_eq!(missing.min(), None);
+
+    let b_to_c = tx
+        .aggregate(
+            namespace,
+            &index_name,
+            vec![
+                IndexRangeExpression::Gt("customer".parse()?, val!("a")),
+                IndexRangeExpression::Lte("customer".parse()?, val!("c")),
+            ],
+        )
+        .await?;
+    assert_eq!(b_to_c.count(), 1);
+
+    let err = tx
+        .aggregate(
+            namespace,
+            &index_name,
+            vec![
+                IndexRangeExpression::Eq("customer".parse()?, maybe_val!("a")),
+                IndexRangeExpression::Gt("_id".parse()?, val!("")),
+            ],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "AggregateRangeOnId");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_aggregate_index_checkpoint(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = str::parse("orders")?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), "by_customer".parse()?)?;
+
+    // More documents than a single backfill chunk.
+    let num_documents = *INDEX_BACKFILL_CHUNK_SIZE + 1;
+    let mut tx = db.begin_system().await?;
+    for _ in 0..num_documents {
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(
+                table_name.clone(),
+                assert_obj!("customer" => "a", "total" => 0.25),
+            )
+            .await?;
+    }
+    db.commit(tx).await?;
+
+    let mut tx = db.begin_system().await?;
+    let begin_ts = tx.begin_timestamp();
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_backfilling_aggregate_index(
+                *begin_ts,
+                index_name.clone(),
+                DeveloperAggregateIndexConfig {
+                    fields: vec![str::parse("customer")?].try_into()?,
+                    value_field: Some(str::parse("total")?),
+                },
+            ),
+        )
+        .await?;
+    db.commit(tx).await?;
+    IndexWorker::new_terminating(
+        rt.clone(),
+        tp.clone(),
+        Arc::new(NoopRetentionValidator),
+        db.clone(),
+    )
+    .await?;
+    let mut tx = db.begin_system().await?;
+    IndexModel::new(&mut tx)
+        .enable_index_for_testing(namespace, &index_name)
+        .await?;
+    db.commit(tx).await?;
+
+    // The backfill checkpoints the aggregate.
+    let checkpoint = AggregateIndexSnapshot::load(tp.reader().as_ref())
+        .await?
+        .context("Missing aggregate index checkpoint")?;
+    assert_eq!(checkpoint.indexes.iter().count(), 1);
+
+    // Writes after the checkpoint are replayed from the log on restart.
+    let mut tx = db.begin_system().await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            table_name.clone(),
+            assert_obj!("customer" => "b", "total" => 5.0),
+        )
+        .await?;
+    db.commit(tx).await?;
+
+    let DbFixtures { db, .. } = DbFixtures::new_with_args(
+        &rt,
+        DbFixturesArgs {
+            tp: Some(tp),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let mut tx = db.begin_system().await?;
+    let a = tx
+        .aggregate(namespace, &index_name, customer_is("a")?)
+        .await?;
+    assert_eq!(a.count(), num_documents as u64);
+    assert_eq!(a.sum(), 0.25 * num_documents as f64);
+    let all = tx.aggregate(namespace, &index_name, vec![]).await?;
+    assert_eq!(all.count(), num_documents as u64 + 1);
+    assert_eq!(all.max(), Some(5.0));
+    Ok(())
+}
+
+fn customer_is(customer: &str) -> anyhow::Result<Vec<IndexRangeExpression>> {
+    Ok(vec![IndexRangeExpression::Eq(
+        "customer".parse()?,
+        ConvexValue::try_from(customer)?.into(),
+    )])
+}
+
+/// Checks aggregates over a few ranges of customers against `orders`.
+async fn check_aggregate_ranges(
+    tx: &mut Transaction<TestRuntime>,
+    namespace: TableNamespace,
+    index_name: &IndexName,
+    orders: &BTreeMap<DeveloperDocumentId, (String, f64)>,
+) -> anyhow::Result<()> {
+    for (start, end) in [("c000", "c400"), ("c010", "c011"), ("c031", "c317")] {
+        let range = vec![
+            IndexRangeExpression::Gte("customer".parse()?, ConvexValue::try_from(start)?),
+            IndexRangeExpression::Lt("customer".parse()?, ConvexValue::try_from(end)?),
+        ];
+        let totals: Vec<f64> = orders
+            .values()
+            .filter(|(customer, _)| start <= customer.as_str() && customer.as_str() < end)
+            .map(|(_, total)| *total)
+            .collect();
+        let aggregate = tx.aggregate(namespace, index_name, range).await?;
+        assert_eq!(aggregate.count(), totals.len() as u64);
+        assert_eq!(aggregate.sum(), totals.iter().sum::<f64>());
+        assert_eq!(aggregate.min(), totals.iter().copied().reduce(f64::min));
+        assert_eq!(aggregate.max(), totals.iter().copied().reduce(f64::max));
+    }
+    Ok(())
+}
+
+async fn create_aggregate_index_counts_table(db: &Database<TestRuntime>) -> anyhow::Result<()> {
+    let mut tx = db.begin_system().await?;
+    let table = AggregateIndexCountsTable;
+    tx.create_system_table_testing(TableNamespace::Global, table.table_name(), None)
+        .await?;
+    let begin_ts = tx.begin_timestamp();
+    for index in table.indexes() {
+        IndexModel::new(&mut tx)
+            .add_system_index(
+                TableNamespace::Global,
+                IndexMetadata::new_backfilling(*begin_ts, index.name, index.fields),
+            )
+            .await?;
+    }
+    db.commit(tx).await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_aggregate_index_flush(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = str::parse("orders")?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), "by_customer".parse()?)?;
+    create_aggregate_index_counts_table(&db).await?;
+
+    let mut tx = db.begin_system().await?;
+    let mut ids = vec![];
+    for (customer, total) in [("a", 1.0), ("a", 1.0), ("a", 2.0), ("b", 3.0)] {
+        let id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(
+                table_name.clone(),
+                assert_obj!("customer" => customer, "total" => total),
+            )
+            .await?;
+        ids.push(id);
+    }
+    db.commit(tx).await?;
+
+    let mut tx = db.begin_system().await?;
+    let begin_ts = tx.begin_timestamp();
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_backfilling_aggregate_index(
+                *begin_ts,
+                index_name.clone(),
+                DeveloperAggregateIndexConfig {
+                    fields: vec![str::parse("customer")?].try_into()?,
+                    value_field: Some(str::parse("total")?),
+                },
+            ),
+        )
+        .await?;
+    db.commit(tx).await?;
+    IndexWorker::new_terminating(
+        rt.clone(),
+        tp.clone(),
+        Arc::new(NoopRetentionValidator),
+        db.clone(),
+    )
+    .await?;
+    let mut tx = db.begin_system().await?;
+    IndexModel::new(&mut tx)
+        .enable_index_for_testing(namespace, &index_name)
+        .await?;
+    db.commit(tx).await?;
+
+    // One count per distinct customer and total.
+    assert_eq!(flush(&db).await?, 3);
+    assert!(db
+        .aggregate_index_snapshot()
+        .indexes
+        .iter()
+        .all(|(_, deltas)| deltas.is_empty()));
+    let mut tx = db.begin_system().await?;
+    // The three counts fit in the root.
+    let persisted = TableModel::new(&mut tx)
+        .count(TableNamespace::Global, &AGGREGATE_INDEX_COUNTS_TABLE)
+        .await?;
+    assert_eq!(persisted, 1);
+    let a = tx
+        .aggregate(namespace, &index_name, customer_is("a")?)
+        .await?;
+    assert_eq!(a.count(), 3);
+    assert_eq!(a.sum(), 4.0);
+
+    // Writes after a flush are combined with the persisted counts, and
+    // flushing again deletes the counts that drop to zero.
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(ids[3])
+        .await?;
+    db.commit(tx).await?;
+    let mut tx = db.begin_system().await?;
+    let all = tx.aggregate(namespace, &index_name, vec![]).await?;
+    assert_eq!(all.count(), 3);
+    assert_eq!(all.max(), Some(2.0));
+    assert_eq!(flush(&db).await?, 1);
+    assert_eq!(flush(&db).await?, 0);
+
+    // The persisted counts and remaining deltas agree after a restart.
+    let DbFixtures { db, .. } = DbFixtures::new_with_args(
+        &rt,
+        DbFixturesArgs {
+            tp: Some(tp),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let mut tx = db.begin_system().await?;
+    let all = tx.aggregate(namespace, &index_name, vec![]).await?;
+    assert_eq!(all.count(), 3);
+    assert_eq!(all.sum(), 4.0);
+    let b = tx
+        .aggregate(namespace, &index_name, customer_is("b")?)
+        .await?;
+    assert_eq!(b.count(), 0);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_aggregate_index_flush_keeps_subscriptions(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = str::parse("orders")?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), "by_customer".parse()?)?;
+    create_aggregate_index_counts_table(&db).await?;
+
+    let mut tx = db.begin_system().await?;
+    let begin_ts = tx.begin_timestamp();
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_backfilling_aggregate_index(
+                *begin_ts,
+                index_name.clone(),
+                DeveloperAggregateIndexConfig {
+                    fields: vec![str::parse("customer")?].try_into()?,
+                    value_field: Some(str::parse("total")?),
+                },
+            ),
+        )
+        .await?;
+    db.commit(tx).await?;
+    IndexWorker::new_terminating(
+        rt.clone(),
+        tp.clone(),
+        Arc::new(NoopRetentionValidator),
+        db.clone(),
+    )
+    .await?;
+    let mut tx = db.begin_system().await?;
+    IndexModel::new(&mut tx)
+        .enable_index_for_testing(namespace, &index_name)
+        .await?;
+    for (customer, total) in [("a", 1.0), ("b", 2.0)] {
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(
+                table_name.clone(),
+                assert_obj!("customer" => customer, "total" => total),
+            )
+            .await?;
+    }
+    db.commit(tx).await?;
+    assert_eq!(flush(&db).await?, 2);
+
+    let mut tx = db.begin_system().await?;
+    let a = tx
+        .aggregate(namespace, &index_name, customer_is("a")?)
+        .await?;
+    assert_eq!(a.count(), 1);
+    let token = tx.into_token()?;
+
+    // Flushing a write to another customer rewrites the root holding the
+    // count of "a" too, but doesn't change its aggregate.
+    let mut tx = db.begin_system().await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            table_name.clone(),
+            assert_obj!("customer" => "b", "total" => 3.0),
+        )
+        .await?;
+    db.commit(tx).await?;
+    assert_eq!(flush(&db).await?, 1);
+    let token = db
+        .refresh_token(token, *db.now_ts_for_reads())
+        .await?
+        .context("Flushing another customer's count invalidated the aggregate")?;
+
+    // Writes to "a" still invalidate it.
+    let mut tx = db.begin_system().await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            table_name.clone(),
+            assert_obj!("customer" => "a", "total" => 4.0),
+        )
+        .await?;
+    db.commit(tx).await?;
+    assert!(db
+        .refresh_token(token, *db.now_ts_for_reads())
+        .await?
+        .is_none());
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_aggregate_index_tree(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = str::parse("orders")?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), "by_customer".parse()?)?;
+    create_aggregate_index_counts_table(&db).await?;
+
+    let mut tx = db.begin_system().await?;
+    let begin_ts = tx.begin_timestamp();
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            namespace,
+            IndexMetadata::new_backfilling_aggregate_index(
+                *begin_ts,
+                index_name.clone(),
+                DeveloperAggregateIndexConfig {
+                    fields: vec![str::parse("customer")?].try_into()?,
+                    value_field: Some(str::parse("total")?),
+                },
+            ),
+        )
+        .await?;
+    db.commit(tx).await?;
+    IndexWorker::new_terminating(
+        rt.clone(),
+        tp.clone(),
+        Arc::new(NoopRetentionValidator),
+        db.clone(),
+    )
+    .await?;
+    let mut tx = db.begin_system().await?;
+    IndexModel::new(&mut tx)
+        .enable_index_for_testing(namespace, &index_name)
+        .await?;
+
+    // Enough distinct keys to split the root.
+    let mut orders = BTreeMap::new();
+    for i in 0..1000 {
+        let customer = format!("c{:03}", i % 400);
+        let total = (i * 7 % 101) as f64;
+        let id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(
+                table_name.clone(),
+                assert_obj!("customer" => customer.clone(), "total" => total),
+            )
+            .await?;
+        orders.insert(id, (customer, total));
+    }
+    db.commit(tx).await?;
+    assert_eq!(flush(&db).await?, 1000);
+    let mut tx = db.begin_system().await?;
+    let num_nodes = TableModel::new(&mut tx)
+        .count(TableNamespace::Global, &AGGREGATE_INDEX_COUNTS_TABLE)
+        .await?;
+    assert!(num_nodes > 32);
+
+    check_aggregate_ranges(&mut tx, namespace, &index_name, &orders).await?;
+
+    // Deleting the smallest and largest totals without flushing makes the
+    // bounds come from the rest of the tree.
+    for total in [0.0, 100.0] {
+        let ids: Vec<_> = orders
+            .iter()
+            .filter(|(_, (_, t))| *t == total)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in ids {
+            UserFacingModel::new_root_for_test(&mut tx)
+                .delete(id)
+                .await?;
+            orders.remove(&id);
+        }
+    }
+    check_aggregate_ranges(&mut tx, namespace, &index_name, &orders).await?;
+    db.commit(tx).await?;
+    let mut tx = db.begin_system().await?;
+    check_aggregate_ranges(&mut tx, namespace, &index_name, &orders).await?;
+
+    // Flushing the deletes, and deleting almost everything after, merges the
+    // nodes left nearly empty.
+    flush(&db).await?;
+    let mut tx = db.begin_system().await?;
+    check_aggregate_ranges(&mut tx, namespace, &index_name, &orders).await?;
+    let ids: Vec<_> = orders.keys().skip(3).copied().collect();
+    for id in ids {
+        UserFacingModel::new_root_for_test(&mut tx)
+            .delete(id)
+            .await?;
+        orders.remove(&id);
+    }
+    check_aggregate_ranges(&mut tx, namespace, &index_name, &orders).await?;
+    db.commit(tx).await?;
+    flush(&db).await?;
+    let mut tx = db.begin_system().await?;
+    check_aggregate_ranges(&mut tx, namespace, &index_name, &orders).await?;
+    let persisted = TableModel::new(&mut tx)
+        .count(TableNamespace::Global, &AGGREGATE_INDEX_COUNTS_TABLE)
+        .await?;
+    assert!(persisted < num_nodes / 4);
+
+    // The persisted tree and remaining deltas agree after a restart.
+    let DbFixtures { db, .. } = DbFixtures::new_with_args(
+        &rt,
+        DbFixturesArgs {
+            tp: Some(tp),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let mut tx = db.begin_system().await?;
+    check_aggregate_ranges(&mut tx, namespace, &index_name, &orders).await?;
+    Ok(())
+}
+
 // Same as test_index_backfill but writing the index with IndexWriter directly.
 #[convex_macro::test_runtime]
 async fn test_index_write(rt: TestRuntime) -> anyhow::Result<()> {
@@ -0,0 +1,536 @@
+use std::collections::BTreeSet;
+
+use common::{
+    assert_obj,
+    bootstrap_model::index::IndexMetadata,
+    db_schema,
+    knobs::MIN_TABLE_RETENTION,
+    maybe_val,
+    object_validator,
+    persistence::PersistenceGlobalKey,
+    query::{
+        Expression,
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    schemas::{
+        validator::{
+            FieldValidator,
+            Validator,
+        },
+        DocumentSchema,
+        IndexSchema,
+        OnDelete,
+        ReferenceSchema,
+    },
+    types::{
+        IndexName,
+        TableName,
+    },
+};
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use pretty_assertions::assert_eq;
+use runtime::testing::TestRuntime;
+use sync_types::Timestamp;
+use value::{
+    assert_val,
+    ConvexValue,
+    DeveloperDocumentId,
+    TableNamespace,
+};
+
+use crate::{
+    test_helpers::{
+        DbFixtures,
+        DbFixturesArgs,
+    },
+    Database,
+    IndexModel,
+    RestoreTableCounts,
+    SchemaModel,
+    TableModel,
+    TestFacingModel,
+    UserFacingModel,
+    WriteSource,
+};
+
+/// Restores `table_name` batch by batch after `cursor`, committing each batch
+/// like the restore worker does, and returns the total counts.
+async fn restore_table(
+    db: &Database<TestRuntime>,
+    ts: Timestamp,
+    table_name: &TableName,
+    mut cursor: Option<DeveloperDocumentId>,
+) -> anyhow::Result<RestoreTableCounts> {
+    let mut counts = RestoreTableCounts::default();
+    loop {
+        let mut tx = db.begin(Identity::system()).await?;
+        let batch = db
+            .restore_table_batch(&mut tx, ts, table_name, cursor)
+            .await?;
+        db.commit(tx).await?;
+        counts.inserted += batch.counts.inserted;
+        counts.replaced += batch.counts.replaced;
+        counts.deleted += batch.counts.deleted;
+        if batch.done {
+            return Ok(counts);
+        }
+        cursor = batch.cursor;
+    }
+}
+
+#[convex_macro::test_runtime]
+async fn test_historical_reads_and_restore(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = "messages".parse()?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let edited = TestFacingModel::new(&mut tx)
+        .insert_and_get(table_name.clone(), assert_obj!("body" => "hello"))
+        .await?;
+    let deleted = TestFacingModel::new(&mut tx)
+        .insert_and_get(table_name.clone(), assert_obj!("body" => "bye"))
+        .await?;
+    let ts = db.commit(tx).await?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(edited.developer_id(), assert_obj!("body" => "edited"))
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(deleted.developer_id())
+        .await?;
+    let inserted = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("body" => "new"))
+        .await?;
+    db.commit(tx).await?;
+
+    let page = db
+        .list_historical_documents(Identity::system(), ts, table_name.clone(), None, 10)
+        .await?;
+    let mut expected = vec![edited.clone(), deleted.clone()];
+    expected.sort_by_key(|doc| doc.id());
+    assert_eq!(
+        page.documents
+            .into_iter()
+            .map(|(_, _, doc)| doc)
+            .collect::<Vec<_>>(),
+        expected
+    );
+    assert!(!page.has_more);
+    let (_, document) = db
+        .get_historical_document(Identity::system(), ts, edited.developer_id())
+        .await?
+        .unwrap();
+    assert_eq!(document, edited);
+    assert!(db
+        .get_historical_document(Identity::system(), ts, inserted)
+        .await?
+        .is_none());
+
+    assert!(db
+        .list_historical_documents(
+            Identity::system(),
+            Timestamp::MAX,
+            table_name.clone(),
+            None,
+            10
+        )
+        .await
+        .is_err());
+    assert!(db
+        .check_restore_tables(
+            Identity::system(),
+            ts,
+            &BTreeSet::from(["missing".parse()?])
+        )
+        .await
+        .is_err());
+    assert!(db
+        .check_restore_tables(Identity::Unknown, ts, &BTreeSet::from([table_name.clone()]))
+        .await
+        .is_err());
+    db.check_restore_tables(
+        Identity::system(),
+        ts,
+        &BTreeSet::from([table_name.clone()]),
+    )
+    .await?;
+
+    let counts = restore_table(&db, ts, &table_name, None).await?;
+    assert_eq!(
+        counts,
+        RestoreTableCounts {
+            inserted: 1,
+            replaced: 1,
+            deleted: 1,
+        }
+    );
+    let mut tx = db.begin(Identity::system()).await?;
+    for doc in [edited, deleted] {
+        let restored = tx.get(doc.id()).await?.unwrap();
+        assert_eq!(restored.into_value(), doc.into_value());
+    }
+    let inserted = inserted.map_table(
+        tx.table_mapping()
+            .namespace(TableNamespace::test_user())
+            .inject_table_id(),
+    )?;
+    assert!(tx.get(inserted).await?.is_none());
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_document_history(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = "messages".parse()?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("body" => "hello"))
+        .await?;
+    let inserted_ts = db.commit_with_write_source(tx, "insert").await?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(id, assert_obj!("body" => "edited"))
+        .await?;
+    let replaced_ts = db.commit_with_write_source(tx, "replace").await?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(id)
+        .await?;
+    let deleted_ts = db.commit(tx).await?;
+
+    assert!(db
+        .document_history(Identity::Unknown, id, None, 10)
+        .await
+        .is_err());
+
+    let page = db.document_history(Identity::system(), id, None, 2).await?;
+    assert!(page.has_more);
+    assert_eq!(page.cursor, Some(replaced_ts));
+    let [deleted, replaced] = &page.revisions[..] else {
+        panic!("Expected two revisions: {:?}", page.revisions);
+    };
+    assert_eq!(deleted.ts, deleted_ts);
+    assert!(deleted.document.is_none());
+    assert_eq!(replaced.ts, replaced_ts);
+    let document = replaced.document.as_ref().unwrap();
+    assert_eq!(document.developer_id(), id);
+    assert_eq!(document.value().get("body"), Some(&assert_val!("edited")));
+    assert_eq!(
+        replaced.write_source.as_ref().and_then(WriteSource::as_str),
+        Some("replace")
+    );
+
+    let page = db
+        .document_history(Identity::system(), id, page.cursor, 2)
+        .await?;
+    assert!(!page.has_more);
+    let [inserted] = &page.revisions[..] else {
+        panic!("Expected one revision: {:?}", page.revisions);
+    };
+    assert_eq!(inserted.ts, inserted_ts);
+    assert_eq!(
+        inserted.write_source.as_ref().and_then(WriteSource::as_str),
+        Some("insert")
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_query_historical_documents(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = "messages".parse()?;
+    let index_name = IndexName::new(table_name.clone(), "by_channel".parse()?)?;
+    let mut tx = db.begin(Identity::system()).await?;
+    IndexModel::new(&mut tx)
+        .add_application_index(
+            TableNamespace::test_user(),
+            IndexMetadata::new_enabled(index_name.clone(), vec!["channel".parse()?].try_into()?),
+        )
+        .await?;
+    db.commit(tx).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let first = TestFacingModel::new(&mut tx)
+        .insert_and_get(table_name.clone(), assert_obj!("channel" => "a", "n" => 1))
+        .await?;
+    let second = TestFacingModel::new(&mut tx)
+        .insert_and_get(table_name.clone(), assert_obj!("channel" => "a", "n" => 2))
+        .await?;
+    TestFacingModel::new(&mut tx)
+        .insert(&table_name, assert_obj!("channel" => "b", "n" => 3))
+        .await?;
+    let ts = db.commit(tx).await?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(
+            first.developer_id(),
+            assert_obj!("channel" => "b", "n" => 1),
+        )
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(second.developer_id())
+        .await?;
+    db.commit(tx).await?;
+
+    let channel_a = Query::index_range(IndexRange {
+        index_name: index_name.clone(),
+        range: vec![IndexRangeExpression::Eq(
+            "channel".parse()?,
+            maybe_val!("a"),
+        )],
+        order: Order::Asc,
+    });
+    let page = db
+        .query_historical_documents(Identity::system(), ts, channel_a.clone(), None, 1)
+        .await?;
+    assert!(page.has_more);
+    let mut documents = page.documents;
+    let page = db
+        .query_historical_documents(Identity::system(), ts, channel_a.clone(), page.cursor, 10)
+        .await?;
+    assert!(!page.has_more);
+    documents.extend(page.documents);
+    // Documents with the same channel are in `_id` order.
+    let mut expected = vec![first, second.clone()];
+    expected.sort_by_key(|doc| doc.id());
+    assert_eq!(
+        documents
+            .into_iter()
+            .map(|(_, doc)| doc)
+            .collect::<Vec<_>>(),
+        expected
+    );
+
+    let query = Query::full_table_scan(table_name.clone(), Order::Asc).filter(Expression::Eq(
+        Box::new(Expression::Field("n".parse()?)),
+        Box::new(Expression::Literal(maybe_val!(2))),
+    ));
+    let page = db
+        .query_historical_documents(Identity::system(), ts, query, None, 10)
+        .await?;
+    assert_eq!(
+        page.documents
+            .into_iter()
+            .map(|(_, doc)| doc)
+            .collect::<Vec<_>>(),
+        vec![second]
+    );
+
+    let err = db
+        .query_historical_documents(Identity::system(), ts, channel_a.limit(1), None, 10)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidHistoricalQuery");
+    let missing_index = Query::index_range(IndexRange {
+        index_name: IndexName::new(table_name.clone(), "by_n".parse()?)?,
+        range: vec![],
+        order: Order::Asc,
+    });
+    let err = db
+        .query_historical_documents(Identity::system(), ts, missing_index, None, 10)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "IndexNotFoundAtTimestamp");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_restore_resumes_from_cursor(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = "messages".parse()?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let mut ids = vec![];
+    for i in 0..4i64 {
+        let id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table_name.clone(), assert_obj!("n" => i))
+            .await?;
+        ids.push(id);
+    }
+    ids.sort();
+    let ts = db.commit(tx).await?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    for id in &ids {
+        UserFacingModel::new_root_for_test(&mut tx)
+            .delete(*id)
+            .await?;
+    }
+    db.commit(tx).await?;
+
+    // Resuming after the second document, as if a restore had committed a
+    // batch up to it, only restores the documents after it.
+    let counts = restore_table(&db, ts, &table_name, Some(ids[1])).await?;
+    assert_eq!(
+        counts,
+        RestoreTableCounts {
+            inserted: 2,
+            replaced: 0,
+            deleted: 0,
+        }
+    );
+    let mut tx = db.begin(Identity::system()).await?;
+    for (i, id) in ids.into_iter().enumerate() {
+        let document = UserFacingModel::new_root_for_test(&mut tx)
+            .get(id, None)
+            .await?;
+        assert_eq!(document.is_some(), i >= 2);
+    }
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_restore_referenced_table(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let posts: TableName = "posts".parse()?;
+    let comments: TableName = "comments".parse()?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let post = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(posts.clone(), assert_obj!())
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(comments.clone(), assert_obj!("postId" => post))
+        .await?;
+    let ts = db.commit(tx).await?;
+
+    let mut db_schema = db_schema!(
+        posts.clone() => DocumentSchema::Union(vec![object_validator!()]),
+        comments.clone() => DocumentSchema::Union(vec![object_validator!(
+            "postId" => FieldValidator::required_field_type(Validator::Id(posts.clone())),
+        )])
+    );
+    let definition = db_schema.tables.get_mut(&comments).unwrap();
+    let reference = ReferenceSchema::new(
+        &comments,
+        "postId".parse()?,
+        OnDelete::Cascade,
+        definition.document_type.as_ref(),
+    )?;
+    definition.indexes.insert(
+        "by_post".parse()?,
+        IndexSchema {
+            index_descriptor: "by_post".parse()?,
+            fields: vec!["postId".parse()?].try_into()?,
+            unique: false,
+            filter: None,
+        },
+    );
+    definition.references.insert("postId".parse()?, reference);
+    let mut tx = db.begin(Identity::system()).await?;
+    SchemaModel::new_root_for_test(&mut tx)
+        .submit_pending(db_schema)
+        .await?;
+    db.commit(tx).await?;
+
+    // Restoring posts deletes the ones inserted since `ts`, which would cascade
+    // to their comments.
+    let err = db
+        .check_restore_tables(Identity::system(), ts, &BTreeSet::from([posts.clone()]))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "RestoreTableReferenced");
+    let mut tx = db.begin(Identity::system()).await?;
+    let err = db
+        .restore_table_batch(&mut tx, ts, &posts, None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "RestoreTableReferenced");
+    db.check_restore_tables(Identity::system(), ts, &BTreeSet::from([comments]))
+        .await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_restore_short_retention_table(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = "messages".parse()?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name.clone(), assert_obj!("body" => "hello"))
+        .await?;
+    TableModel::new(&mut tx)
+        .set_document_retention(
+            TableNamespace::test_user(),
+            &table_name,
+            Some(*MIN_TABLE_RETENTION),
+        )
+        .await?;
+    let ts = db.commit(tx).await?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(id, assert_obj!("body" => "edited"))
+        .await?;
+    let tablet_id = tx
+        .table_mapping()
+        .namespace(TableNamespace::test_user())
+        .id_if_exists(&table_name)
+        .unwrap();
+    let edited_ts = db.commit(tx).await?;
+
+    // Retention has deleted the table's history before `edited_ts`, while the
+    // default retention still covers `ts`.
+    tp.write_persistence_global(
+        PersistenceGlobalKey::TableRetentionMinSnapshotTimestamps,
+        serde_json::Value::Object(
+            [(
+                tablet_id.to_string(),
+                ConvexValue::from(i64::from(edited_ts)).into(),
+            )]
+            .into_iter()
+            .collect(),
+        ),
+    )
+    .await?;
+    let DbFixtures { db, .. } = DbFixtures::new_with_args(
+        &rt,
+        DbFixturesArgs {
+            tp: Some(tp),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let tables = BTreeSet::from([table_name.clone()]);
+    let err = db
+        .check_restore_tables(Identity::system(), ts, &tables)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TimestampOutOfRetention");
+    let err = restore_table(&db, ts, &table_name, None).await.unwrap_err();
+    assert_eq!(err.short_msg(), "TimestampOutOfRetention");
+    let err = db
+        .get_historical_document(Identity::system(), ts, id)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TimestampOutOfRetention");
+    let err = db
+        .list_historical_documents(Identity::system(), ts, table_name.clone(), None, 10)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TimestampOutOfRetention");
+    let query = Query::full_table_scan(table_name.clone(), Order::Asc);
+    let err = db
+        .query_historical_documents(Identity::system(), ts, query, None, 10)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "TimestampOutOfRetention");
+
+    // The failed restore didn't touch the table, which can still be restored
+    // to a timestamp within its retention.
+    db.check_restore_tables(Identity::system(), edited_ts, &tables)
+        .await?;
+    restore_table(&db, edited_ts, &table_name, None).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let document = UserFacingModel::new_root_for_test(&mut tx)
+        .get(id, None)
+        .await?
+        .unwrap();
+    assert_eq!(document.value().get("body"), Some(&assert_val!("edited")));
+    Ok(())
+}
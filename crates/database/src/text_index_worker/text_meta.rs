@@ -241,7 +241,9 @@ impl SearchIndex for TextSearchIndex {
         metadata: ParsedDocument<TabletIndexMetadata>,
     ) -> anyhow::Result<(Self::DeveloperConfig, SearchOnDiskState<Self>)> {
         let (on_disk_state, developer_config) = match metadata.into_value().config {
-            IndexConfig::Database { .. } | IndexConfig::Vector { .. } => {
+            IndexConfig::Database { .. }
+            | IndexConfig::Vector { .. }
+            | IndexConfig::Aggregate { .. } => {
                 anyhow::bail!("Index type changed!")
             },
             IndexConfig::Search {
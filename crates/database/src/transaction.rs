@@ -17,6 +17,7 @@ use common::{
     bootstrap_model::{
         index::{
             database_index::IndexedFields,
+            IndexConfig,
             IndexMetadata,
             INDEX_TABLE,
         },
@@ -44,6 +45,7 @@ use common::{
     persistence::RetentionValidator,
     query::{
         CursorPosition,
+        IndexRangeExpression,
         Order,
         Search,
         SearchVersion,
@@ -73,9 +75,18 @@ use common::{
     version::Version,
 };
 use errors::ErrorMetadata;
-use indexing::backend_in_memory_indexes::{
-    BatchKey,
-    RangeRequest,
+use indexing::{
+    aggregate_index::{
+        add_delta,
+        AggregateDelta,
+        AggregateDeltas,
+        AggregateIndexes,
+        RangeAggregate,
+    },
+    backend_in_memory_indexes::{
+        BatchKey,
+        RangeRequest,
+    },
 };
 use keybroker::{
     Identity,
@@ -159,6 +170,9 @@ pub struct Transaction<RT: Runtime> {
     /// this transaction. If there is no entry for a table, assume deltas
     /// are zero.
     pub(crate) table_count_deltas: BTreeMap<TabletId, i64>,
+    /// The changes this transaction made to the counts of the keys of each
+    /// aggregate index.
+    pub(crate) aggregate_index_deltas: BTreeMap<IndexId, AggregateDeltas>,
 
     pub(crate) stats: BTreeMap<TabletId, TableStats>,
 
@@ -185,6 +199,15 @@ pub trait TableCountSnapshot: Send + Sync + 'static {
     /// Returns the number of documents in the table at the timestamp of the
     /// snapshot.
     async fn count(&self, table: TabletId) -> anyhow::Result<u64>;
+
+    /// Returns the changes to the persisted counts of the keys of the
+    /// aggregate index `index_id` in `interval` at the timestamp of the
+    /// snapshot, or `None` if the index isn't loaded.
+    async fn aggregate_deltas(
+        &self,
+        index_id: IndexId,
+        interval: &Interval,
+    ) -> anyhow::Result<Option<Vec<AggregateDelta>>>;
 }
 
 impl<RT: Runtime> Transaction<RT> {
@@ -211,6 +234,7 @@ impl<RT: Runtime> Transaction<RT> {
             metadata,
             count_snapshot: count,
             table_count_deltas: BTreeMap::new(),
+            aggregate_index_deltas: BTreeMap::new(),
             stats: BTreeMap::new(),
             runtime,
             retention_validator,
@@ -510,6 +534,10 @@ impl<RT: Runtime> Transaction<RT> {
         SchemaModel::new(self, namespace)
             .enforce(&new_document)
             .await?;
+        self.enforce_references(namespace, &table_name, &new_document)
+            .await?;
+        self.check_unique_indexes(&table_name, Some(&old_document), &new_document)
+            .await?;
 
         self.apply_validated_write(id, Some(old_document), Some(new_document.clone()))?;
         Ok(new_document)
@@ -539,7 +567,7 @@ impl<RT: Runtime> Transaction<RT> {
             .table_mapping()
             .tablet_namespace(id.table().tablet_id)?;
         let (old_document, _) =
-            self.get_inner(id, table_name)
+            self.get_inner(id, table_name.clone())
                 .await?
                 .context(ErrorMetadata::bad_request(
                     "NonexistentDocument",
@@ -552,6 +580,10 @@ impl<RT: Runtime> Transaction<RT> {
         SchemaModel::new(self, namespace)
             .enforce(&new_document)
             .await?;
+        self.enforce_references(namespace, &table_name, &new_document)
+            .await?;
+        self.check_unique_indexes(&table_name, Some(&old_document), &new_document)
+            .await?;
 
         self.apply_validated_write(
             new_document.id(),
@@ -568,7 +600,7 @@ impl<RT: Runtime> Transaction<RT> {
     ) -> anyhow::Result<ResolvedDocument> {
         let table_name = self.table_mapping().tablet_name(id.table().tablet_id)?;
         let (document, _) =
-            self.get_inner(id, table_name)
+            self.get_inner(id, table_name.clone())
                 .await?
                 .context(ErrorMetadata::bad_request(
                     "NonexistentDocument",
@@ -576,9 +608,57 @@ impl<RT: Runtime> Transaction<RT> {
                 ))?;
 
         self.apply_validated_write(document.id(), Some(document.clone()), None)?;
+
+        // Cascading deletes can reach documents that were already deleted, e.g.
+        // through a cycle of references, so skip those.
+        let mut to_delete = self.enforce_delete(&table_name, &document).await?;
+        while let Some(id) = to_delete.pop() {
+            let table_name = self.table_mapping().tablet_name(id.table().tablet_id)?;
+            let Some((document, _)) = self.get_inner(id, table_name.clone()).await? else {
+                continue;
+            };
+            self.apply_validated_write(id, Some(document.clone()), None)?;
+            to_delete.extend(self.enforce_delete(&table_name, &document).await?);
+        }
         Ok(document)
     }
 
+    /// Checks the references on a user document being written, per
+    /// [`SchemaModel::enforce_references`].
+    async fn enforce_references(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+        document: &ResolvedDocument,
+    ) -> anyhow::Result<()> {
+        if self
+            .table_mapping()
+            .is_system_tablet(document.id().table().tablet_id)
+        {
+            return Ok(());
+        }
+        SchemaModel::new(self, namespace)
+            .enforce_references(table_name, document)
+            .await
+    }
+
+    /// Applies the `onDelete` behavior of references to a deleted user
+    /// document, returning the referencing documents to delete in turn.
+    async fn enforce_delete(
+        &mut self,
+        table_name: &TableName,
+        document: &ResolvedDocument,
+    ) -> anyhow::Result<Vec<ResolvedDocumentId>> {
+        let tablet_id = document.id().table().tablet_id;
+        if self.table_mapping().is_system_tablet(tablet_id) {
+            return Ok(vec![]);
+        }
+        let namespace = self.table_mapping().tablet_namespace(tablet_id)?;
+        SchemaModel::new(self, namespace)
+            .enforce_delete(table_name, document)
+            .await
+    }
+
     #[minitrace::trace]
     #[convex_macro::instrument_future]
     pub async fn count(
@@ -595,6 +675,19 @@ impl<RT: Runtime> Transaction<RT> {
         TableModel::new(self).count(namespace, system_table).await
     }
 
+    #[minitrace::trace]
+    #[convex_macro::instrument_future]
+    pub async fn aggregate(
+        &mut self,
+        namespace: TableNamespace,
+        index_name: &IndexName,
+        range: Vec<IndexRangeExpression>,
+    ) -> anyhow::Result<RangeAggregate> {
+        IndexModel::new(self)
+            .aggregate(namespace, index_name, range)
+            .await
+    }
+
     pub fn into_token(self) -> anyhow::Result<Token> {
         if !self.is_readonly() {
             anyhow::bail!("Transaction isn't readonly");
@@ -881,6 +974,17 @@ impl<RT: Runtime> Transaction<RT> {
             old_document.as_ref().map(|d| d.value().deref()),
             new_document.as_ref().map(|d| d.value().deref()),
         )?;
+        let mut aggregate_deltas = vec![];
+        for (document, sign) in [(&old_document, -1), (&new_document, 1)] {
+            let Some(document) = document else {
+                continue;
+            };
+            for (index_id, key, value) in
+                AggregateIndexes::document_keys(index_update.registry(), document)
+            {
+                aggregate_deltas.push((index_id, key, value, sign));
+            }
+        }
         let stats = self.stats.entry(id.table().tablet_id).or_default();
         let mut delta = 0;
         match (old_document.as_ref(), new_document.as_ref()) {
@@ -924,6 +1028,14 @@ impl<RT: Runtime> Transaction<RT> {
             .table_count_deltas
             .entry(id.table().tablet_id)
             .or_default() += delta;
+        for (index_id, key, value, sign) in aggregate_deltas {
+            add_delta(
+                self.aggregate_index_deltas.entry(index_id).or_default(),
+                key,
+                value,
+                sign,
+            );
+        }
         Ok(())
     }
 
@@ -936,10 +1048,95 @@ impl<RT: Runtime> Transaction<RT> {
             .table_mapping()
             .tablet_namespace(document_id.table().tablet_id)?;
         SchemaModel::new(self, namespace).enforce(&document).await?;
+        let table_name = self
+            .table_mapping()
+            .tablet_name(document_id.table().tablet_id)?;
+        self.enforce_references(namespace, &table_name, &document)
+            .await?;
+        self.check_unique_indexes(&table_name, None, &document)
+            .await?;
         self.apply_validated_write(document_id, None, Some(document))?;
         Ok(document_id)
     }
 
+    /// Fails if writing `new_document` would violate a unique index on its
+    /// table.
+    pub(crate) async fn check_unique_indexes(
+        &mut self,
+        table_name: &TableName,
+        old_document: Option<&ResolvedDocument>,
+        new_document: &ResolvedDocument,
+    ) -> anyhow::Result<()> {
+        self.index
+            .check_unique_indexes(&mut self.reads, table_name, old_document, new_document)
+            .await
+    }
+
+    /// Records a read of the whole pending database index `index_name`, so any
+    /// write to its table makes this transaction fail to commit.
+    pub(crate) fn record_pending_index_read(
+        &mut self,
+        index_name: &TabletIndexName,
+    ) -> anyhow::Result<()> {
+        let index = self
+            .index
+            .index_registry()
+            .get_pending(index_name)
+            .with_context(|| format!("Missing pending index {index_name}"))?;
+        let IndexConfig::Database {
+            ref developer_config,
+            ..
+        } = index.metadata.config
+        else {
+            anyhow::bail!("{index_name} isn't a database index");
+        };
+        self.reads.record_indexed_directly(
+            index_name.clone(),
+            developer_config.fields.clone(),
+            Interval::all(),
+        )
+    }
+
+    /// Returns a document other than `document` with the same indexed values
+    /// in the pending database index `index_name`, recording the range read.
+    /// The index worker uses this to check documents written while it was
+    /// checking a unique index for duplicates.
+    pub(crate) async fn pending_index_duplicate(
+        &mut self,
+        index_name: &TabletIndexName,
+        document: &ResolvedDocument,
+    ) -> anyhow::Result<Option<DeveloperDocumentId>> {
+        let index = self
+            .index
+            .index_registry()
+            .get_pending(index_name)
+            .with_context(|| format!("Missing pending index {index_name}"))?
+            .clone();
+        let IndexConfig::Database {
+            ref developer_config,
+            ..
+        } = index.metadata.config
+        else {
+            anyhow::bail!("{index_name} isn't a database index");
+        };
+        if !developer_config.includes(document) {
+            return Ok(None);
+        }
+        let fields = developer_config.fields.clone();
+        let persistence_version = self.index.index_registry().persistence_version();
+        if document
+            .index_key(&fields[..], persistence_version)
+            .indexed_values()
+            .iter()
+            .any(Option::is_none)
+        {
+            return Ok(None);
+        }
+        self.index
+            .pending_index_duplicate(&mut self.reads, &index, &fields, document)
+            .await
+    }
+
     pub async fn search(
         &mut self,
         stable_index_name: &StableIndexName,
@@ -26,7 +26,10 @@ use common::{
         IndexKey,
         IndexKeyBytes,
     },
-    interval::Interval,
+    interval::{
+        BinaryKey,
+        Interval,
+    },
     knobs::TRANSACTION_MAX_READ_SIZE_BYTES,
     query::{
         CursorPosition,
@@ -44,6 +47,8 @@ use common::{
         WriteTimestamp,
     },
 };
+use errors::ErrorMetadata;
+use futures::TryStreamExt;
 use indexing::{
     backend_in_memory_indexes::{
         index_not_a_database_index_error,
@@ -66,8 +71,10 @@ use search::{
 };
 use storage::Storage;
 use value::{
+    values_to_bytes,
     DeveloperDocumentId,
     FieldPath,
+    TableName,
 };
 
 use crate::{
@@ -304,7 +311,7 @@ impl TransactionIndex {
                     match self.require_enabled(reads, index_name, printable_index_name) {
                         Ok(index) => match index.metadata().config.clone() {
                             IndexConfig::Database {
-                                developer_config: DeveloperDatabaseIndexConfig { fields },
+                                developer_config: DeveloperDatabaseIndexConfig { fields, .. },
                                 ..
                             } => fields,
                             _ => Err(index_not_a_database_index_error(printable_index_name))?,
@@ -352,7 +359,7 @@ impl TransactionIndex {
                     .into_iter()
                     .take(max_size)
                     .take_while(|(_, document, _)| {
-                        within_bytes_limit = total_bytes < *TRANSACTION_MAX_READ_SIZE_BYTES;
+                        within_bytes_limit = total_bytes < TRANSACTION_MAX_READ_SIZE_BYTES.get();
                         // Allow the query to exceed the limit by one document so the query
                         // is guaranteed to make progress and probably fail.
                         // Note system document limits are different, so a single document
@@ -464,6 +471,146 @@ impl TransactionIndex {
         ))
     }
 
+    /// Check that replacing `old_document` with `new_document` doesn't give
+    /// two documents the same indexed values in any backfilled or enabled
+    /// unique index on the table. The ranges checked are recorded in `reads`,
+    /// so a concurrent transaction writing a conflicting document will fail
+    /// to commit.
+    pub async fn check_unique_indexes(
+        &mut self,
+        reads: &mut TransactionReadSet,
+        table_name: &TableName,
+        old_document: Option<&ResolvedDocument>,
+        new_document: &ResolvedDocument,
+    ) -> anyhow::Result<()> {
+        let persistence_version = self.index_registry.persistence_version();
+        let mut range_requests = vec![];
+        let mut pending_indexes = vec![];
+        for index in self
+            .index_registry
+            .unique_indexes_by_table(new_document.id().table().tablet_id)
+        {
+            let IndexConfig::Database {
+                ref developer_config,
+                ..
+            } = index.metadata.config
+            else {
+                continue;
+            };
+            // Documents excluded from a partial index can't conflict.
+            if !developer_config.includes(new_document) {
+                continue;
+            }
+            let fields = &developer_config.fields;
+            let index_key = new_document.index_key(&fields[..], persistence_version);
+            // Like NULLs in SQL, documents missing an indexed field never conflict.
+            if index_key.indexed_values().iter().any(Option::is_none) {
+                continue;
+            }
+            if let Some(old_document) = old_document
+                && developer_config.includes(old_document)
+                && old_document
+                    .index_key(&fields[..], persistence_version)
+                    .indexed_values()
+                    == index_key.indexed_values()
+            {
+                // The indexed values haven't changed, so they're still unique.
+                continue;
+            }
+            if !index.metadata.config.is_enabled() {
+                pending_indexes.push((index.clone(), fields.clone()));
+                continue;
+            }
+            range_requests.push(RangeRequest {
+                index_name: index.name(),
+                printable_index_name: IndexName::new(
+                    table_name.clone(),
+                    index.name().descriptor().clone(),
+                )?,
+                interval: Interval::prefix(BinaryKey::from(values_to_bytes(
+                    index_key.indexed_values(),
+                ))),
+                order: Order::Asc,
+                // The document itself may already be in the index.
+                max_size: 2,
+            });
+        }
+        for range_request in range_requests {
+            let printable_index_name = range_request.printable_index_name.clone();
+            let IndexRangeResponse { page, .. } = self.range(reads, range_request).await?;
+            if let Some((_, existing_document, _)) = page
+                .iter()
+                .find(|(_, document, _)| document.id() != new_document.id())
+            {
+                anyhow::bail!(unique_index_violation_error(
+                    &printable_index_name,
+                    new_document.developer_id(),
+                    existing_document.developer_id(),
+                ));
+            }
+        }
+        for (index, fields) in pending_indexes {
+            if let Some(existing_id) = self
+                .pending_index_duplicate(reads, &index, &fields, new_document)
+                .await?
+            {
+                let printable_index_name =
+                    IndexName::new(table_name.clone(), index.name().descriptor().clone())?;
+                anyhow::bail!(unique_index_violation_error(
+                    &printable_index_name,
+                    new_document.developer_id(),
+                    existing_id,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a document other than `document` with the same values in the
+    /// pending database index `index`, if any. Pending indexes aren't
+    /// readable through [`Self::range`], so this reads the index directly from
+    /// persistence and overlays this transaction's writes. The range read is
+    /// recorded in `reads`.
+    pub async fn pending_index_duplicate(
+        &mut self,
+        reads: &mut TransactionReadSet,
+        index: &Index,
+        fields: &IndexedFields,
+        document: &ResolvedDocument,
+    ) -> anyhow::Result<Option<DeveloperDocumentId>> {
+        let persistence_version = self.index_registry.persistence_version();
+        let index_key = document.index_key(&fields[..], persistence_version);
+        let interval =
+            Interval::prefix(BinaryKey::from(values_to_bytes(index_key.indexed_values())));
+        reads.record_indexed_directly(index.name(), fields.clone(), interval.clone())?;
+        let pending = self.database_index_updates.get(&index.id());
+        if let Some(pending) = pending
+            && let Some((_, existing_document)) = pending
+                .range(&interval)
+                .filter_map(|(key, document)| Some((key, document?)))
+                .find(|(_, existing_document)| existing_document.id() != document.id())
+        {
+            return Ok(Some(existing_document.developer_id()));
+        }
+        let mut stream = self.database_index_snapshot.scan_backfilled_index(
+            index.id(),
+            *index.name().table(),
+            &interval,
+            Order::Asc,
+            2,
+        );
+        while let Some((key, _, existing_document)) = stream.try_next().await? {
+            // This transaction's writes replace the committed entries.
+            if pending.is_some_and(|pending| pending.contains_key(&key)) {
+                continue;
+            }
+            if existing_document.id() != document.id() {
+                return Ok(Some(existing_document.developer_id()));
+            }
+        }
+        Ok(None)
+    }
+
     // TODO: Add precise error types to facilitate detecting which indexing errors
     // are the developer's fault or not.
     pub fn begin_update(
@@ -619,6 +766,22 @@ impl TransactionIndex {
     }
 }
 
+fn unique_index_violation_error(
+    index_name: &IndexName,
+    id: DeveloperDocumentId,
+    existing_id: DeveloperDocumentId,
+) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "UniqueIndexViolation",
+        format!(
+            "Document with ID \"{}\" has the same indexed values as the existing document with ID \
+             \"{}\" in the unique index \"{index_name}\"",
+            id.encode(),
+            existing_id.encode(),
+        ),
+    )
+}
+
 #[derive(Debug)]
 pub struct TransactionIndexMap {
     /// Unlike IndexMap we can simply use BTreeMap since the TransactionIndexMap
@@ -644,6 +807,10 @@ impl TransactionIndexMap {
             .map(|(k, v)| (IndexKeyBytes(k.clone()), v.as_ref().map(|v| v.unpack())))
     }
 
+    pub fn contains_key(&self, k: &IndexKeyBytes) -> bool {
+        self.inner.contains_key(&k.0)
+    }
+
     pub fn insert(&mut self, k: IndexKey, v: Option<ResolvedDocument>) {
         self.inner
             .insert(k.into_bytes().0, v.map(PackedDocument::pack));
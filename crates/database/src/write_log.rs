@@ -90,6 +90,10 @@ impl WriteSource {
     pub fn new(source: impl Into<Cow<'static, str>>) -> Self {
         Self(Some(source.into()))
     }
+
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
 }
 
 impl From<Option<String>> for WriteSource {
@@ -248,6 +252,11 @@ impl WriteLog {
             .map(|(t, w, source)| (t, w.iter(), source)))
     }
 
+    fn write_source(&self, ts: Timestamp) -> Option<&WriteSource> {
+        let i = self.by_ts.binary_search_by_key(&ts, |&(ts, ..)| ts).ok()?;
+        self.by_ts.get(i).map(|(_, _, source)| source)
+    }
+
     fn is_stale(
         &self,
         reads: &ReadSet,
@@ -360,6 +369,11 @@ impl LogReader {
         let max_ts = inner.max_ts();
         inner.refresh_token(token, max_ts)
     }
+
+    /// Returns the source of the commit at `ts`, if it's still in the log.
+    pub fn write_source(&self, ts: Timestamp) -> Option<WriteSource> {
+        self.inner.read().write_source(ts).cloned()
+    }
 }
 
 impl HeapSize for LogReader {
@@ -145,22 +145,22 @@ impl Writes {
         } else {
             let tx_size = &self.user_tx_size;
             anyhow::ensure!(
-                tx_size.num_writes <= *TRANSACTION_MAX_NUM_USER_WRITES,
+                tx_size.num_writes <= TRANSACTION_MAX_NUM_USER_WRITES.get(),
                 ErrorMetadata::pagination_limit(
                     "TooManyWrites",
                     format!(
                         "Too many writes in a single function execution (limit: {})",
-                        *TRANSACTION_MAX_NUM_USER_WRITES,
+                        TRANSACTION_MAX_NUM_USER_WRITES.get(),
                     )
                 ),
             );
             anyhow::ensure!(
-                tx_size.size <= *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
+                tx_size.size <= TRANSACTION_MAX_USER_WRITE_SIZE_BYTES.get(),
                 ErrorMetadata::pagination_limit(
                     "TooManyBytesWritten",
                     format!(
                         "Too many bytes written in a single function execution (limit: {} bytes)",
-                        *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
+                        TRANSACTION_MAX_USER_WRITE_SIZE_BYTES.get(),
                     )
                 ),
             );
@@ -0,0 +1,524 @@
+//! gRPC service that runs queries and mutations for a backend in a separate
+//! process. See [`crate::remote::RemoteFunctionRunner`] for the client.
+//!
+//! The function runner reads documents directly from persistence, but table
+//! counts and aggregate index deltas only live in the backend's memory, so it
+//! asks the backend for them over the same bidirectional stream the request
+//! came in on.
+//!
+//! Every request must carry a system key for the instance in its
+//! [`AUTHORIZATION_METADATA_KEY`] metadata, since the function runner trusts
+//! the identity and writes the backend sends along with it.
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{
+            AtomicBool,
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+    },
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use common::{
+    document::DocumentUpdate,
+    interval::Interval,
+    persistence::PersistenceReader,
+    query::{
+        InternalSearch,
+        SearchVersion,
+    },
+    runtime::Runtime,
+    types::{
+        ConvexOrigin,
+        IndexId,
+        UdfType,
+    },
+};
+use database::{
+    DatabaseSnapshot,
+    TableCountSnapshot,
+    TransactionSearchSnapshot,
+};
+use errors::ErrorMetadata;
+use futures::{
+    channel::{
+        mpsc,
+        oneshot,
+    },
+    pin_mut,
+    select_biased,
+    stream::BoxStream,
+    FutureExt,
+    StreamExt,
+};
+use indexing::{
+    aggregate_index::AggregateDelta,
+    index_registry::Index,
+};
+use isolate::ValidatedPathAndArgs;
+use keybroker::{
+    Identity,
+    InstanceSecret,
+    KeyBroker,
+};
+use model::environment_variables::types::{
+    EnvVarName,
+    EnvVarValue,
+};
+use parking_lot::Mutex;
+use pb::{
+    common::UdfType as UdfTypeProto,
+    error_metadata::ErrorMetadataStatusExt,
+    function_runner::{
+        callback_request::Request as CallbackRequestKind,
+        callback_response::Response as CallbackResponseKind,
+        function_runner_service_server::{
+            FunctionRunnerService,
+            FunctionRunnerServiceServer,
+        },
+        run_function_client_message::Message as ClientMessage,
+        run_function_server_message::Message as ServerMessage,
+        AggregateRequest,
+        CallbackRequest,
+        CallbackResponse,
+        IndexLastModified,
+        RunFunctionClientMessage,
+        RunFunctionRequest,
+        RunFunctionResponse,
+        RunFunctionServerMessage,
+        TableCountRequest,
+    },
+};
+use search::QueryResults;
+use sync_types::Timestamp;
+use tonic::{
+    metadata::MetadataMap,
+    Request,
+    Response,
+    Status,
+    Streaming,
+};
+use value::TabletId;
+
+use crate::server::{
+    FunctionRunnerCore,
+    InstanceStorage,
+};
+
+type ServerMessageSender = mpsc::UnboundedSender<Result<RunFunctionServerMessage, Status>>;
+
+/// gRPC metadata key holding the backend's system key.
+pub const AUTHORIZATION_METADATA_KEY: &str = "authorization";
+
+/// Short message of the error returned when a function uses text or vector
+/// search, which the backend handles by running the function in process.
+pub const SEARCH_UNSUPPORTED: &str = "SearchUnsupported";
+
+pub struct FunctionRunnerGrpcService<RT: Runtime> {
+    rt: RT,
+    server: FunctionRunnerCore<RT, InstanceStorage>,
+    persistence_reader: Arc<dyn PersistenceReader>,
+
+    // Static information about the backend this function runner serves.
+    instance_name: String,
+    instance_secret: InstanceSecret,
+    key_broker: KeyBroker,
+    convex_origin: ConvexOrigin,
+}
+
+impl<RT: Runtime> Clone for FunctionRunnerGrpcService<RT> {
+    fn clone(&self) -> Self {
+        Self {
+            rt: self.rt.clone(),
+            server: self.server.clone(),
+            persistence_reader: self.persistence_reader.clone(),
+            instance_name: self.instance_name.clone(),
+            instance_secret: self.instance_secret,
+            key_broker: self.key_broker.clone(),
+            convex_origin: self.convex_origin.clone(),
+        }
+    }
+}
+
+impl<RT: Runtime> FunctionRunnerGrpcService<RT> {
+    pub async fn new(
+        rt: RT,
+        persistence_reader: Arc<dyn PersistenceReader>,
+        storage: InstanceStorage,
+        instance_name: String,
+        instance_secret: InstanceSecret,
+        convex_origin: ConvexOrigin,
+    ) -> anyhow::Result<Self> {
+        // Like `InProcessFunctionRunner`, we only serve a single backend.
+        let max_percent_per_client = 100;
+        let server = FunctionRunnerCore::new(rt.clone(), storage, max_percent_per_client).await?;
+        let key_broker = KeyBroker::new(&instance_name, instance_secret)?;
+        Ok(Self {
+            rt,
+            server,
+            persistence_reader,
+            instance_name,
+            instance_secret,
+            key_broker,
+            convex_origin,
+        })
+    }
+
+    pub fn into_server(self) -> FunctionRunnerServiceServer<Self> {
+        FunctionRunnerServiceServer::new(self)
+    }
+
+    pub async fn shutdown(&self) -> anyhow::Result<()> {
+        self.server.shutdown().await
+    }
+
+    async fn handle_request(
+        &self,
+        request: RunFunctionRequest,
+        mut inbound: Streaming<RunFunctionClientMessage>,
+        outbound: ServerMessageSender,
+    ) -> anyhow::Result<RunFunctionResponse> {
+        let RunFunctionRequest {
+            instance_name,
+            path_and_args,
+            udf_type,
+            identity,
+            ts,
+            existing_writes,
+            journal,
+            system_env_vars,
+            in_memory_index_last_modified,
+            context,
+        } = request;
+        let instance_name = instance_name.context("Missing `instance_name` field")?;
+        anyhow::ensure!(
+            instance_name == self.instance_name,
+            ErrorMetadata::bad_request(
+                "WrongInstance",
+                format!(
+                    "Function runner serves {}, not {instance_name}",
+                    self.instance_name
+                ),
+            )
+        );
+        let udf_type: UdfType =
+            UdfTypeProto::try_from(udf_type.context("Missing `udf_type` field")?)?.into();
+        anyhow::ensure!(
+            matches!(udf_type, UdfType::Query | UdfType::Mutation),
+            "The remote function runner only runs queries and mutations, not {udf_type}"
+        );
+        let path_and_args = ValidatedPathAndArgs::from_proto(
+            path_and_args.context("Missing `path_and_args` field")?,
+        )?;
+        // The caller authenticated with the instance's system key in `run_function`, so
+        // we can trust the identity it already checked.
+        let identity =
+            Identity::from_proto_unchecked(identity.context("Missing `identity` field")?)?;
+        let ts = ts.context("Missing `ts` field")?.try_into()?;
+        let existing_writes = existing_writes
+            .context("Missing `existing_writes` field")?
+            .try_into()?;
+        let journal = journal.context("Missing `journal` field")?.try_into()?;
+        let system_env_vars: BTreeMap<_, _> = system_env_vars
+            .into_iter()
+            .map(|(name, value)| {
+                anyhow::Ok((name.parse::<EnvVarName>()?, value.parse::<EnvVarValue>()?))
+            })
+            .try_collect()?;
+        let in_memory_index_last_modified: BTreeMap<_, _> = in_memory_index_last_modified
+            .into_iter()
+            .map(|IndexLastModified { index_id, ts }| {
+                let index_id = IndexId::try_from(index_id.context("Missing `index_id` field")?)?;
+                let ts = Timestamp::try_from(ts.context("Missing `ts` field")?)?;
+                anyhow::Ok((index_id, ts))
+            })
+            .try_collect()?;
+        let context = context.context("Missing `context` field")?.try_into()?;
+
+        let pending = Arc::new(Mutex::new(BTreeMap::new()));
+        let table_count_snapshot = Arc::new(RemoteCountSnapshot {
+            next_id: AtomicU64::new(0),
+            pending: pending.clone(),
+            outbound,
+        });
+        let search_snapshot = Arc::new(UnsupportedSearchSnapshot {
+            attempted: AtomicBool::new(false),
+        });
+        let bootstrap_metadata =
+            DatabaseSnapshot::<RT>::get_meta_ids(self.persistence_reader.as_ref()).await?;
+
+        // NOTE: The backend checks retention once it has the result, so we don't
+        // have to here.
+        let run = self
+            .server
+            .run_function_no_retention_check(
+                self.instance_name.clone(),
+                self.instance_secret,
+                self.persistence_reader.clone(),
+                self.convex_origin.clone(),
+                bootstrap_metadata,
+                table_count_snapshot,
+                search_snapshot.clone(),
+                None,
+                None,
+                None,
+                path_and_args,
+                udf_type,
+                identity,
+                ts,
+                existing_writes,
+                journal,
+                system_env_vars,
+                in_memory_index_last_modified,
+                context,
+            )
+            .fuse();
+        let route_callbacks = async {
+            while let Some(message) = inbound.message().await? {
+                let Some(ClientMessage::CallbackResponse(response)) = message.message else {
+                    anyhow::bail!("Expected only callback responses after `RunFunctionRequest`");
+                };
+                let id = response.id.context("Missing `id` field")?;
+                let sender = pending
+                    .lock()
+                    .remove(&id)
+                    .with_context(|| format!("Response to unknown callback {id}"))?;
+                // The function may have already failed and stopped waiting.
+                _ = sender.send(response);
+            }
+            anyhow::bail!("Backend closed the stream before the function finished")
+        }
+        .fuse();
+        pin_mut!(run, route_callbacks);
+        let (transaction, outcome, usage_stats) = select_biased! {
+            result = run => result?,
+            result = route_callbacks => return result,
+        };
+        // The function may have caught the search error, so check whether it tried
+        // rather than relying on the outcome.
+        anyhow::ensure!(
+            !search_snapshot.attempted.load(Ordering::SeqCst),
+            ErrorMetadata::bad_request(
+                SEARCH_UNSUPPORTED,
+                "Text and vector search aren't supported by the remote function runner",
+            )
+        );
+        let transaction = transaction.context("Missing transaction for query or mutation")?;
+        Ok(RunFunctionResponse {
+            transaction: Some(transaction.try_into()?),
+            outcome: Some(outcome.try_into()?),
+            usage_stats: Some(usage_stats.into()),
+        })
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> FunctionRunnerService for FunctionRunnerGrpcService<RT> {
+    type RunFunctionStream = BoxStream<'static, Result<RunFunctionServerMessage, Status>>;
+
+    async fn run_function(
+        &self,
+        request: Request<Streaming<RunFunctionClientMessage>>,
+    ) -> Result<Response<Self::RunFunctionStream>, Status> {
+        if let Err(e) = authenticate(&self.key_broker, request.metadata()) {
+            return Err(Status::from_anyhow(e));
+        }
+        let mut inbound = request.into_inner();
+        let Some(ClientMessage::Request(request)) =
+            inbound.message().await?.and_then(|message| message.message)
+        else {
+            return Err(Status::invalid_argument(
+                "Expected `RunFunctionRequest` as the first message",
+            ));
+        };
+        let (outbound_tx, outbound_rx) = mpsc::unbounded();
+        let service = self.clone();
+        // Callback requests are streamed back while the function runs, so run it in
+        // the background and return the stream right away.
+        self.rt.spawn("function_runner_grpc_request", async move {
+            let message = match service
+                .handle_request(request, inbound, outbound_tx.clone())
+                .await
+            {
+                Ok(response) => Ok(RunFunctionServerMessage {
+                    message: Some(ServerMessage::Response(response)),
+                }),
+                Err(e) => Err(Status::from_anyhow(e)),
+            };
+            _ = outbound_tx.unbounded_send(message);
+        });
+        Ok(Response::new(outbound_rx.boxed()))
+    }
+}
+
+/// Answers table counts and aggregate index deltas by asking the backend over
+/// the request stream.
+struct RemoteCountSnapshot {
+    next_id: AtomicU64,
+    pending: Arc<Mutex<BTreeMap<u64, oneshot::Sender<CallbackResponse>>>>,
+    outbound: ServerMessageSender,
+}
+
+impl RemoteCountSnapshot {
+    async fn callback(&self, request: CallbackRequestKind) -> anyhow::Result<CallbackResponseKind> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().insert(id, tx);
+        let message = RunFunctionServerMessage {
+            message: Some(ServerMessage::CallbackRequest(CallbackRequest {
+                id: Some(id),
+                request: Some(request),
+            })),
+        };
+        self.outbound
+            .unbounded_send(Ok(message))
+            .map_err(|_| anyhow::anyhow!("Backend disconnected"))?;
+        let response = rx
+            .await
+            .context("Backend disconnected before answering callback")?;
+        match response.response.context("Missing `response` field")? {
+            CallbackResponseKind::Error(e) => anyhow::bail!("Callback failed in backend: {e}"),
+            response => Ok(response),
+        }
+    }
+}
+
+#[async_trait]
+impl TableCountSnapshot for RemoteCountSnapshot {
+    async fn count(&self, table: TabletId) -> anyhow::Result<u64> {
+        let request = CallbackRequestKind::TableCount(TableCountRequest {
+            tablet_id: Some(table.0.into()),
+        });
+        match self.callback(request).await? {
+            CallbackResponseKind::TableCount(count) => Ok(count),
+            response => anyhow::bail!("Unexpected response to table count: {response:?}"),
+        }
+    }
+
+    async fn aggregate_deltas(
+        &self,
+        index_id: IndexId,
+        interval: &Interval,
+    ) -> anyhow::Result<Option<Vec<AggregateDelta>>> {
+        let request = CallbackRequestKind::Aggregate(AggregateRequest {
+            index_id: Some(index_id.into()),
+            interval: Some(interval.clone().into()),
+        });
+        match self.callback(request).await? {
+            CallbackResponseKind::Aggregate(response) => {
+                if !response.loaded.context("Missing `loaded` field")? {
+                    return Ok(None);
+                }
+                let deltas = response
+                    .deltas
+                    .into_iter()
+                    .map(AggregateDelta::try_from)
+                    .try_collect()?;
+                Ok(Some(deltas))
+            },
+            response => anyhow::bail!("Unexpected response to aggregate: {response:?}"),
+        }
+    }
+}
+
+/// Text and vector indexes live in the backend's memory and aren't served to
+/// the remote function runner, so functions that search are failed with
+/// [`SEARCH_UNSUPPORTED`] and run in the backend instead.
+struct UnsupportedSearchSnapshot {
+    attempted: AtomicBool,
+}
+
+#[async_trait]
+impl TransactionSearchSnapshot for UnsupportedSearchSnapshot {
+    async fn search(
+        &self,
+        _index: &Index,
+        _search: &InternalSearch,
+        _version: SearchVersion,
+        _pending_updates: &Vec<DocumentUpdate>,
+    ) -> anyhow::Result<QueryResults> {
+        self.attempted.store(true, Ordering::SeqCst);
+        anyhow::bail!(ErrorMetadata::bad_request(
+            SEARCH_UNSUPPORTED,
+            "Text and vector search aren't supported by the remote function runner",
+        ))
+    }
+}
+
+/// Only the backend, which holds the instance secret, may run functions.
+fn authenticate(key_broker: &KeyBroker, metadata: &MetadataMap) -> anyhow::Result<()> {
+    let key = metadata
+        .get(AUTHORIZATION_METADATA_KEY)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Convex "))
+        .ok_or_else(|| {
+            ErrorMetadata::unauthenticated(
+                "MissingSystemKey",
+                "Function runner requests must be authorized with a system key",
+            )
+        })?;
+    let identity = key_broker.check_admin_key(key).map_err(|e| {
+        e.context(ErrorMetadata::unauthenticated(
+            "BadSystemKey",
+            "The provided system key was invalid for this instance",
+        ))
+    })?;
+    anyhow::ensure!(
+        identity.is_system(),
+        ErrorMetadata::unauthenticated(
+            "BadSystemKey",
+            "Function runner requests must be authorized with a system key, not an admin key",
+        )
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use common::types::MemberId;
+    use errors::ErrorMetadataAnyhowExt;
+    use keybroker::{
+        InstanceSecret,
+        KeyBroker,
+        DEV_INSTANCE_NAME,
+    };
+    use tonic::metadata::MetadataMap;
+
+    use super::{
+        authenticate,
+        AUTHORIZATION_METADATA_KEY,
+    };
+
+    fn metadata(key: &str) -> anyhow::Result<MetadataMap> {
+        let mut metadata = MetadataMap::new();
+        metadata.insert(AUTHORIZATION_METADATA_KEY, format!("Convex {key}").parse()?);
+        Ok(metadata)
+    }
+
+    #[test]
+    fn test_authenticate() -> anyhow::Result<()> {
+        let key_broker = KeyBroker::dev();
+        authenticate(
+            &key_broker,
+            &metadata(&key_broker.issue_system_key().to_string())?,
+        )?;
+
+        let err = authenticate(&key_broker, &MetadataMap::new()).unwrap_err();
+        assert_eq!(err.short_msg(), "MissingSystemKey");
+
+        // Admin keys are for the dashboard and CLI, not the backend.
+        let admin_key = key_broker.issue_admin_key(MemberId(0));
+        let err = authenticate(&key_broker, &metadata(&admin_key.to_string())?).unwrap_err();
+        assert_eq!(err.short_msg(), "BadSystemKey");
+
+        let other_broker = KeyBroker::new(DEV_INSTANCE_NAME, InstanceSecret::random())?;
+        let other_key = other_broker.issue_system_key();
+        let err = authenticate(&key_broker, &metadata(&other_key.to_string())?).unwrap_err();
+        assert_eq!(err.short_msg(), "BadSystemKey");
+        Ok(())
+    }
+}
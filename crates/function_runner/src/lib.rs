@@ -10,6 +10,7 @@ use std::{
     sync::Arc,
 };
 
+use anyhow::Context;
 use async_trait::async_trait;
 use common::{
     document::DocumentUpdate,
@@ -41,6 +42,15 @@ use model::environment_variables::types::{
     EnvVarName,
     EnvVarValue,
 };
+use pb::{
+    common::DocumentUpdate as DocumentUpdateProto,
+    function_runner::{
+        FunctionFinalTransaction as FunctionFinalTransactionProto,
+        FunctionReads as FunctionReadsProto,
+        FunctionWrites as FunctionWritesProto,
+        TabletRowsRead,
+    },
+};
 #[cfg(any(test, feature = "testing"))]
 use proptest::strategy::Strategy;
 use sync_types::Timestamp;
@@ -50,10 +60,12 @@ use value::{
     TabletId,
 };
 
+pub mod grpc;
 mod in_memory_indexes;
 mod isolate_worker;
 mod metrics;
 mod module_cache;
+pub mod remote;
 pub mod server;
 
 #[async_trait]
@@ -112,6 +124,67 @@ impl<RT: Runtime> From<Transaction<RT>> for FunctionFinalTransaction {
     }
 }
 
+impl TryFrom<FunctionFinalTransaction> for FunctionFinalTransactionProto {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionFinalTransaction {
+            begin_timestamp,
+            reads,
+            writes,
+            rows_read_by_tablet,
+        }: FunctionFinalTransaction,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            begin_timestamp: Some(begin_timestamp.into()),
+            reads: Some(reads.try_into()?),
+            writes: Some(writes.try_into()?),
+            rows_read_by_tablet: rows_read_by_tablet
+                .into_iter()
+                .map(|(tablet_id, rows_read)| TabletRowsRead {
+                    tablet_id: Some(tablet_id.0.into()),
+                    rows_read: Some(rows_read),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl TryFrom<FunctionFinalTransactionProto> for FunctionFinalTransaction {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionFinalTransactionProto {
+            begin_timestamp,
+            reads,
+            writes,
+            rows_read_by_tablet,
+        }: FunctionFinalTransactionProto,
+    ) -> anyhow::Result<Self> {
+        let rows_read_by_tablet: BTreeMap<_, _> = rows_read_by_tablet
+            .into_iter()
+            .map(
+                |TabletRowsRead {
+                     tablet_id,
+                     rows_read,
+                 }| {
+                    let tablet_id =
+                        TabletId(tablet_id.context("Missing `tablet_id` field")?.try_into()?);
+                    anyhow::Ok((tablet_id, rows_read.context("Missing `rows_read` field")?))
+                },
+            )
+            .try_collect()?;
+        Ok(Self {
+            begin_timestamp: begin_timestamp
+                .context("Missing `begin_timestamp` field")?
+                .try_into()?,
+            reads: reads.context("Missing `reads` field")?.try_into()?,
+            writes: writes.context("Missing `writes` field")?.try_into()?,
+            rows_read_by_tablet,
+        })
+    }
+}
+
 #[cfg_attr(
     any(test, feature = "testing"),
     derive(Clone, Debug, PartialEq, proptest_derive::Arbitrary)
@@ -138,6 +211,52 @@ impl From<TransactionReadSet> for FunctionReads {
     }
 }
 
+impl TryFrom<FunctionReads> for FunctionReadsProto {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionReads {
+            reads,
+            num_intervals,
+            user_tx_size,
+            system_tx_size,
+        }: FunctionReads,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            reads: Some(reads.try_into()?),
+            num_intervals: Some(num_intervals as u64),
+            user_tx_size: Some(user_tx_size.into()),
+            system_tx_size: Some(system_tx_size.into()),
+        })
+    }
+}
+
+impl TryFrom<FunctionReadsProto> for FunctionReads {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionReadsProto {
+            reads,
+            num_intervals,
+            user_tx_size,
+            system_tx_size,
+        }: FunctionReadsProto,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            reads: reads.context("Missing `reads` field")?.try_into()?,
+            num_intervals: num_intervals
+                .context("Missing `num_intervals` field")?
+                .try_into()?,
+            user_tx_size: user_tx_size
+                .context("Missing `user_tx_size` field")?
+                .try_into()?,
+            system_tx_size: system_tx_size
+                .context("Missing `system_tx_size` field")?
+                .try_into()?,
+        })
+    }
+}
+
 /// Subset of [`Writes`] that is returned by [FunctionRunner] after a function
 /// has executed.
 #[cfg_attr(any(test, feature = "testing"), derive(Debug, PartialEq))]
@@ -177,3 +296,73 @@ impl From<Writes> for FunctionWrites {
         }
     }
 }
+
+impl TryFrom<FunctionWrites> for FunctionWritesProto {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionWrites {
+            updates,
+            generated_ids,
+        }: FunctionWrites,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            updates: updates
+                .into_values()
+                .map(DocumentUpdateProto::try_from)
+                .try_collect()?,
+            generated_ids: generated_ids.into_iter().map(|id| id.into()).collect(),
+        })
+    }
+}
+
+impl TryFrom<FunctionWritesProto> for FunctionWrites {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionWritesProto {
+            updates,
+            generated_ids,
+        }: FunctionWritesProto,
+    ) -> anyhow::Result<Self> {
+        let updates: BTreeMap<_, _> = updates
+            .into_iter()
+            .map(|update| {
+                let update = DocumentUpdate::try_from(update)?;
+                anyhow::Ok((update.id, update))
+            })
+            .try_collect()?;
+        let generated_ids: BTreeSet<_> = generated_ids
+            .into_iter()
+            .map(ResolvedDocumentId::try_from)
+            .try_collect()?;
+        Ok(Self {
+            updates,
+            generated_ids,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use value::testing::assert_roundtrips;
+
+    use super::{
+        FunctionFinalTransaction,
+        FunctionFinalTransactionProto,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn test_final_transaction_roundtrips(transaction in any::<FunctionFinalTransaction>()) {
+            assert_roundtrips::<FunctionFinalTransaction, FunctionFinalTransactionProto>(
+                transaction,
+            );
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Runs queries and mutations for a local backend started with
+//! `--function-runner-url`, reading from the same SQLite database and local
+//! storage directory.
+use std::{
+    net::Ipv4Addr,
+    sync::Arc,
+};
+
+use clap::Parser;
+use cmd_util::env::config_service;
+use common::{
+    errors::MainError,
+    grpc::ConvexGrpcService,
+    persistence::Persistence,
+    types::ConvexOrigin,
+};
+use function_runner::{
+    grpc::FunctionRunnerGrpcService,
+    server::InstanceStorage,
+};
+use keybroker::{
+    InstanceSecret,
+    DEV_INSTANCE_NAME,
+    DEV_SECRET,
+};
+use runtime::prod::ProdRuntime;
+use sqlite::SqlitePersistence;
+use storage::{
+    LocalDirStorage,
+    StorageUseCase,
+};
+use tokio::signal;
+
+#[derive(Parser)]
+struct Config {
+    /// File path for SQLite, shared with the backend
+    #[clap(default_value = "convex_local_backend.sqlite3")]
+    db_spec: String,
+
+    /// Which directory the backend's local storage uses
+    #[clap(long, default_value = "convex_local_storage")]
+    local_storage: String,
+
+    /// Host interface to bind to. Only the backend should talk to the function
+    /// runner, so this defaults to loopback.
+    #[clap(short, long, default_value = "127.0.0.1")]
+    interface: Ipv4Addr,
+
+    /// Host port to serve gRPC on
+    #[clap(short, long, default_value = "3212")]
+    port: u16,
+
+    /// Origin of the Convex server
+    #[clap(long, default_value = "http://127.0.0.1:3210")]
+    convex_origin: ConvexOrigin,
+
+    #[clap(long, requires = "instance_secret")]
+    instance_name: Option<String>,
+
+    #[clap(long, requires = "instance_name")]
+    instance_secret: Option<String>,
+}
+
+fn main() -> Result<(), MainError> {
+    let _guard = config_service();
+    let config = Config::parse();
+    tracing::info!("Starting function runner with config {:?}", config.db_spec);
+
+    let tokio = ProdRuntime::init_tokio()?;
+    let runtime = ProdRuntime::new(&tokio);
+    let runtime_ = runtime.clone();
+    runtime.block_on("main", async move {
+        run_server(runtime_, config).await?;
+        Ok(())
+    })
+}
+
+async fn run_server(runtime: ProdRuntime, config: Config) -> anyhow::Result<()> {
+    let instance_name = config.instance_name.unwrap_or(DEV_INSTANCE_NAME.to_owned());
+    let instance_secret = InstanceSecret::try_from(
+        config
+            .instance_secret
+            .unwrap_or(DEV_SECRET.to_owned())
+            .as_str(),
+    )?;
+    // The backend owns the database, we only ever read from it.
+    let persistence = SqlitePersistence::new(&config.db_spec, true)?;
+    let storage = InstanceStorage {
+        files_storage: Arc::new(LocalDirStorage::for_use_case(
+            runtime.clone(),
+            &config.local_storage,
+            StorageUseCase::Files,
+        )?),
+        modules_storage: Arc::new(LocalDirStorage::for_use_case(
+            runtime.clone(),
+            &config.local_storage,
+            StorageUseCase::Modules,
+        )?),
+    };
+    let service = FunctionRunnerGrpcService::new(
+        runtime,
+        persistence.reader(),
+        storage,
+        instance_name,
+        instance_secret,
+        config.convex_origin,
+    )
+    .await?;
+    ConvexGrpcService::new()
+        .add_service(service.clone().into_server())
+        .serve((config.interface, config.port).into(), async {
+            if let Err(e) = signal::ctrl_c().await {
+                tracing::error!("Failed to listen for Ctrl-C: {e:?}");
+            }
+            tracing::info!("Received Ctrl-C signal!");
+        })
+        .await?;
+    service.shutdown().await?;
+    Ok(())
+}
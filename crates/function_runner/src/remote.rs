@@ -0,0 +1,344 @@
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    sync::Arc,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use common::{
+    components::CanonicalizedComponentFunctionPath,
+    execution_context::ExecutionContext,
+    interval::Interval,
+    log_lines::LogLine,
+    query_journal::QueryJournal,
+    runtime::Runtime,
+    types::{
+        IndexId,
+        RepeatableTimestamp,
+        UdfType,
+    },
+};
+use database::{
+    CountSnapshot,
+    Database,
+    TableCountSnapshot,
+};
+use errors::ErrorMetadataAnyhowExt;
+use futures::channel::mpsc;
+use isolate::{
+    ActionCallbacks,
+    FunctionOutcome,
+    ValidatedPathAndArgs,
+};
+use keybroker::{
+    Identity,
+    KeyBroker,
+};
+use model::environment_variables::types::{
+    EnvVarName,
+    EnvVarValue,
+};
+use parking_lot::Mutex;
+use pb::{
+    common::UdfType as UdfTypeProto,
+    error_metadata::ErrorMetadataStatusExt,
+    function_runner::{
+        callback_request::Request as CallbackRequestKind,
+        callback_response::Response as CallbackResponseKind,
+        function_runner_service_client::FunctionRunnerServiceClient,
+        run_function_client_message::Message as ClientMessage,
+        run_function_server_message::Message as ServerMessage,
+        AggregateDelta as AggregateDeltaProto,
+        AggregateRequest,
+        AggregateResponse,
+        CallbackRequest,
+        CallbackResponse,
+        IndexLastModified,
+        RunFunctionClientMessage,
+        RunFunctionRequest,
+        RunFunctionResponse,
+        TableCountRequest,
+    },
+};
+use sync_types::Timestamp;
+use tonic::{
+    metadata::{
+        Ascii,
+        MetadataValue,
+    },
+    transport::{
+        Channel,
+        Endpoint,
+    },
+};
+use url::Url;
+use usage_tracking::FunctionUsageStats;
+use value::TabletId;
+
+use crate::{
+    grpc::{
+        AUTHORIZATION_METADATA_KEY,
+        SEARCH_UNSUPPORTED,
+    },
+    server::validate_run_function_result,
+    FunctionFinalTransaction,
+    FunctionRunner,
+    FunctionWrites,
+};
+
+/// Runs queries and mutations in a function runner process serving
+/// [`crate::grpc::FunctionRunnerGrpcService`], answering its table count and
+/// aggregate index delta callbacks from this backend's in-memory state.
+/// Actions, HTTP actions, functions in components and functions that use text
+/// or vector search still run in process with `in_process_runner`.
+pub struct RemoteFunctionRunner<RT: Runtime> {
+    client: FunctionRunnerServiceClient<Channel>,
+    instance_name: String,
+    // "Convex <system key>", proving to the function runner that requests come
+    // from this backend.
+    authorization: MetadataValue<Ascii>,
+    database: Database<RT>,
+    in_process_runner: Arc<dyn FunctionRunner<RT>>,
+    // Functions the function runner refused to run because they searched.
+    search_paths: Mutex<BTreeSet<CanonicalizedComponentFunctionPath>>,
+}
+
+impl<RT: Runtime> RemoteFunctionRunner<RT> {
+    pub fn new(
+        url: &Url,
+        instance_name: String,
+        key_broker: &KeyBroker,
+        database: Database<RT>,
+        in_process_runner: Arc<dyn FunctionRunner<RT>>,
+    ) -> anyhow::Result<Self> {
+        // Connect lazily so the backend can start before the function runner.
+        let channel = Endpoint::from_shared(url.to_string())?.connect_lazy();
+        let authorization = format!("Convex {}", key_broker.issue_system_key()).parse()?;
+        Ok(Self {
+            client: FunctionRunnerServiceClient::new(channel),
+            instance_name,
+            authorization,
+            database,
+            in_process_runner,
+            search_paths: Mutex::new(BTreeSet::new()),
+        })
+    }
+
+    async fn run_remote(
+        &self,
+        request: RunFunctionRequest,
+        count_snapshot: &CountSnapshot,
+    ) -> anyhow::Result<RunFunctionResponse> {
+        let (outbound_tx, outbound_rx) = mpsc::unbounded();
+        outbound_tx.unbounded_send(RunFunctionClientMessage {
+            message: Some(ClientMessage::Request(request)),
+        })?;
+        let mut outbound = tonic::Request::new(outbound_rx);
+        outbound
+            .metadata_mut()
+            .insert(AUTHORIZATION_METADATA_KEY, self.authorization.clone());
+        let mut inbound = self
+            .client
+            .clone()
+            .run_function(outbound)
+            .await
+            .map_err(|status| status.into_anyhow())?
+            .into_inner();
+        while let Some(message) = inbound
+            .message()
+            .await
+            .map_err(|status| status.into_anyhow())?
+        {
+            match message.message.context("Missing `message` field")? {
+                ServerMessage::CallbackRequest(CallbackRequest { id, request }) => {
+                    let response = match answer_callback(
+                        count_snapshot,
+                        request.context("Missing `request` field")?,
+                    )
+                    .await
+                    {
+                        Ok(response) => response,
+                        Err(e) => CallbackResponseKind::Error(format!("{e:#}")),
+                    };
+                    outbound_tx.unbounded_send(RunFunctionClientMessage {
+                        message: Some(ClientMessage::CallbackResponse(CallbackResponse {
+                            id,
+                            response: Some(response),
+                        })),
+                    })?;
+                },
+                ServerMessage::Response(response) => return Ok(response),
+            }
+        }
+        anyhow::bail!("Function runner closed the stream without a response")
+    }
+
+    async fn run_remote_function(
+        &self,
+        path_and_args: ValidatedPathAndArgs,
+        udf_type: UdfType,
+        identity: Identity,
+        ts: RepeatableTimestamp,
+        existing_writes: FunctionWrites,
+        journal: QueryJournal,
+        system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        in_memory_index_last_modified: BTreeMap<IndexId, Timestamp>,
+        context: ExecutionContext,
+    ) -> anyhow::Result<(
+        Option<FunctionFinalTransaction>,
+        FunctionOutcome,
+        FunctionUsageStats,
+    )> {
+        let snapshot = self.database.snapshot(ts)?;
+        let count_snapshot =
+            CountSnapshot::new(snapshot.table_summaries, snapshot.aggregate_indexes);
+        let request = RunFunctionRequest {
+            instance_name: Some(self.instance_name.clone()),
+            path_and_args: Some(path_and_args.clone().try_into()?),
+            udf_type: Some(UdfTypeProto::from(udf_type).into()),
+            identity: Some(identity.clone().into()),
+            ts: Some(ts.into()),
+            existing_writes: Some(existing_writes.try_into()?),
+            journal: Some(journal.into()),
+            system_env_vars: system_env_vars
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            in_memory_index_last_modified: in_memory_index_last_modified
+                .into_iter()
+                .map(|(index_id, ts)| IndexLastModified {
+                    index_id: Some(index_id.into()),
+                    ts: Some(ts.into()),
+                })
+                .collect(),
+            context: Some(context.into()),
+        };
+
+        // NOTE: Like `InProcessFunctionRunner`, we must not surface any errors or
+        // results until after we call `validate_run_function_result` below.
+        let result: anyhow::Result<_> = try {
+            let RunFunctionResponse {
+                transaction,
+                outcome,
+                usage_stats,
+            } = self.run_remote(request, &count_snapshot).await?;
+            let transaction = FunctionFinalTransaction::try_from(
+                transaction.context("Missing `transaction` field")?,
+            )?;
+            let outcome = FunctionOutcome::from_proto(
+                outcome.context("Missing `outcome` field")?,
+                path_and_args,
+                identity.into(),
+            )?;
+            let usage_stats = usage_stats
+                .context("Missing `usage_stats` field")?
+                .try_into()?;
+            (Some(transaction), outcome, usage_stats)
+        };
+        validate_run_function_result(udf_type, *ts, self.database.retention_validator()).await?;
+        result
+    }
+}
+
+async fn answer_callback(
+    count_snapshot: &CountSnapshot,
+    request: CallbackRequestKind,
+) -> anyhow::Result<CallbackResponseKind> {
+    let response = match request {
+        CallbackRequestKind::TableCount(TableCountRequest { tablet_id }) => {
+            let tablet_id = TabletId(tablet_id.context("Missing `tablet_id` field")?.try_into()?);
+            CallbackResponseKind::TableCount(count_snapshot.count(tablet_id).await?)
+        },
+        CallbackRequestKind::Aggregate(AggregateRequest { index_id, interval }) => {
+            let index_id = IndexId::try_from(index_id.context("Missing `index_id` field")?)?;
+            let interval = Interval::try_from(interval.context("Missing `interval` field")?)?;
+            let deltas = count_snapshot.aggregate_deltas(index_id, &interval).await?;
+            CallbackResponseKind::Aggregate(AggregateResponse {
+                loaded: Some(deltas.is_some()),
+                deltas: deltas
+                    .into_iter()
+                    .flatten()
+                    .map(AggregateDeltaProto::from)
+                    .collect(),
+            })
+        },
+    };
+    Ok(response)
+}
+
+#[async_trait]
+impl<RT: Runtime> FunctionRunner<RT> for RemoteFunctionRunner<RT> {
+    #[minitrace::trace]
+    async fn run_function(
+        &self,
+        path_and_args: ValidatedPathAndArgs,
+        udf_type: UdfType,
+        identity: Identity,
+        ts: RepeatableTimestamp,
+        existing_writes: FunctionWrites,
+        journal: QueryJournal,
+        log_line_sender: Option<mpsc::UnboundedSender<LogLine>>,
+        system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        in_memory_index_last_modified: BTreeMap<IndexId, Timestamp>,
+        context: ExecutionContext,
+    ) -> anyhow::Result<(
+        Option<FunctionFinalTransaction>,
+        FunctionOutcome,
+        FunctionUsageStats,
+    )> {
+        // The protocol can't address functions in components yet, so run those in
+        // process too.
+        let run_in_process = matches!(udf_type, UdfType::Action | UdfType::HttpAction)
+            || !path_and_args.path().component.is_root()
+            || self.search_paths.lock().contains(path_and_args.path());
+        if !run_in_process {
+            let result = self
+                .run_remote_function(
+                    path_and_args.clone(),
+                    udf_type,
+                    identity.clone(),
+                    ts,
+                    existing_writes.clone(),
+                    journal.clone(),
+                    system_env_vars.clone(),
+                    in_memory_index_last_modified.clone(),
+                    context.clone(),
+                )
+                .await;
+            match result {
+                Err(e) if e.short_msg() == SEARCH_UNSUPPORTED => {
+                    tracing::info!(
+                        "{:?} uses search, running it in process from now on",
+                        path_and_args.path()
+                    );
+                    self.search_paths
+                        .lock()
+                        .insert(path_and_args.path().clone());
+                },
+                result => return result,
+            }
+        }
+        self.in_process_runner
+            .run_function(
+                path_and_args,
+                udf_type,
+                identity,
+                ts,
+                existing_writes,
+                journal,
+                log_line_sender,
+                system_env_vars,
+                in_memory_index_last_modified,
+                context,
+            )
+            .await
+    }
+
+    fn set_action_callbacks(&self, action_callbacks: Arc<dyn ActionCallbacks>) {
+        self.in_process_runner
+            .set_action_callbacks(action_callbacks);
+    }
+}
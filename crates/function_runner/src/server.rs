@@ -44,6 +44,7 @@ use common::{
 use database::{
     shutdown_error,
     BootstrapMetadata,
+    CountSnapshot,
     Database,
     FollowerRetentionManager,
     SearchIndexManagerSnapshot,
@@ -344,8 +345,8 @@ impl<RT: Runtime, S: StorageForInstance<RT>> FunctionRunnerCore<RT, S> {
         bootstrap_metadata: BootstrapMetadata,
         table_count_snapshot: Arc<dyn TableCountSnapshot>,
         search_index_snapshot: Arc<dyn TransactionSearchSnapshot>,
-        action_callbacks: Arc<dyn ActionCallbacks>,
-        fetch_client: Arc<dyn FetchClient>,
+        action_callbacks: Option<Arc<dyn ActionCallbacks>>,
+        fetch_client: Option<Arc<dyn FetchClient>>,
         log_line_sender: Option<mpsc::UnboundedSender<LogLine>>,
         path_and_args: ValidatedPathAndArgs,
         udf_type: UdfType,
@@ -438,6 +439,9 @@ impl<RT: Runtime, S: StorageForInstance<RT>> FunctionRunnerCore<RT, S> {
             },
             UdfType::Action => {
                 let (tx, rx) = oneshot::channel();
+                let action_callbacks =
+                    action_callbacks.context("Missing action callbacks for action")?;
+                let fetch_client = fetch_client.context("Missing fetch client for action")?;
                 let log_line_sender =
                     log_line_sender.context("Missing log line sender for action")?;
                 let request = IsolateRequest::new(
@@ -574,7 +578,10 @@ impl<RT: Runtime> FunctionRunner<RT> for InProcessFunctionRunner<RT> {
         FunctionUsageStats,
     )> {
         let snapshot = self.database.snapshot(ts)?;
-        let table_count_snapshot = Arc::new(snapshot.table_summaries);
+        let table_count_snapshot = Arc::new(CountSnapshot::new(
+            snapshot.table_summaries,
+            snapshot.aggregate_indexes,
+        ));
         let search_index_snapshot = Arc::new(SearchIndexManagerSnapshot::new(
             snapshot.index_registry,
             snapshot.search_indexes,
@@ -602,8 +609,8 @@ impl<RT: Runtime> FunctionRunner<RT> for InProcessFunctionRunner<RT> {
                 self.database.bootstrap_metadata.clone(),
                 table_count_snapshot,
                 search_index_snapshot,
-                action_callbacks,
-                self.fetch_client.clone(),
+                Some(action_callbacks),
+                Some(self.fetch_client.clone()),
                 log_line_sender,
                 path_and_args,
                 udf_type,
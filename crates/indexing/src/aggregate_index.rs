@@ -0,0 +1,510 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+};
+
+use anyhow::Context;
+use common::{
+    document::ResolvedDocument,
+    index::IndexKey,
+    interval::Interval,
+    types::{
+        IndexId,
+        IndexName,
+    },
+};
+use errors::ErrorMetadata;
+use imbl::OrdMap;
+use pb::function_runner::AggregateDelta as AggregateDeltaProto;
+use value::{
+    obj,
+    values_to_bytes,
+    ConvexObject,
+    ConvexValue,
+};
+
+use crate::index_registry::IndexRegistry;
+
+const LIMB_BITS: u32 = 32;
+const LIMB_MASK: i128 = (1 << LIMB_BITS) - 1;
+// Normalize once a limb grows past this, well before it could overflow.
+const MAX_LIMB: i128 = 1 << 100;
+// Binary exponent of the smallest subnormal f64, which is bit 0 of the sum.
+const MIN_EXPONENT: i32 = -1074;
+
+/// An exact sum of f64s, stored as a fixed-point integer split into 32-bit
+/// limbs keyed by their position. Its value only depends on which values
+/// have been added, not their order, so large values that cancel out can't
+/// swallow the small ones in between like they would in an f64 accumulator.
+#[derive(Clone, Debug, Default)]
+struct ExactSum {
+    limbs: BTreeMap<i32, i128>,
+    num_nan: i64,
+    num_infinity: i64,
+    num_neg_infinity: i64,
+}
+
+impl ExactSum {
+    fn add(&mut self, value: f64, multiplicity: u64) {
+        self.update(value, i128::from(multiplicity));
+    }
+
+    fn update(&mut self, value: f64, multiplicity: i128) {
+        let counter = if value.is_nan() {
+            &mut self.num_nan
+        } else if value == f64::INFINITY {
+            &mut self.num_infinity
+        } else if value == f64::NEG_INFINITY {
+            &mut self.num_neg_infinity
+        } else {
+            let bits = value.to_bits();
+            let biased_exponent = ((bits >> 52) & 0x7ff) as i32;
+            let fraction = bits & ((1 << 52) - 1);
+            // `value` is `mantissa * 2^(shift + MIN_EXPONENT)`.
+            let (mantissa, shift) = if biased_exponent == 0 {
+                (fraction, 0)
+            } else {
+                (fraction | (1 << 52), biased_exponent - 1)
+            };
+            let sign = if bits >> 63 == 1 { -1 } else { 1 };
+            let shifted = u128::from(mantissa) << (shift as u32 % LIMB_BITS);
+            let position = shift / LIMB_BITS as i32;
+            let mut normalize = false;
+            for i in 0..3 {
+                let piece = ((shifted >> (LIMB_BITS * i)) as i128) & LIMB_MASK;
+                if piece != 0 {
+                    let limb = self.limbs.entry(position + i as i32).or_insert(0);
+                    *limb += sign * multiplicity * piece;
+                    normalize |= limb.abs() > MAX_LIMB;
+                }
+            }
+            if normalize {
+                self.normalize();
+            }
+            return;
+        };
+        *counter += multiplicity as i64;
+    }
+
+    /// Adds every value added to `other`.
+    fn merge(&mut self, other: &ExactSum) {
+        let mut normalize = false;
+        for (position, limb) in &other.limbs {
+            let sum = self.limbs.entry(*position).or_insert(0);
+            *sum += limb;
+            normalize |= sum.abs() > MAX_LIMB;
+        }
+        if normalize {
+            self.normalize();
+        }
+        self.num_nan += other.num_nan;
+        self.num_infinity += other.num_infinity;
+        self.num_neg_infinity += other.num_neg_infinity;
+    }
+
+    /// Propagate carries so every limb but the last is in `[0, 2^32)`.
+    fn normalize(&mut self) {
+        let mut limbs = std::mem::take(&mut self.limbs).into_iter().peekable();
+        let Some(&(mut position, _)) = limbs.peek() else {
+            return;
+        };
+        let mut carry = 0;
+        loop {
+            if let Some((_, limb)) = limbs.next_if(|(p, _)| *p == position) {
+                carry += limb;
+            }
+            if limbs.peek().is_none() && carry.abs() <= LIMB_MASK {
+                if carry != 0 {
+                    self.limbs.insert(position, carry);
+                }
+                break;
+            }
+            let low = carry & LIMB_MASK;
+            if low != 0 {
+                self.limbs.insert(position, low);
+            }
+            carry >>= LIMB_BITS;
+            position = match limbs.peek() {
+                Some((next, _)) if carry == 0 => *next,
+                _ => position + 1,
+            };
+        }
+    }
+
+    fn to_f64(&self) -> f64 {
+        if self.num_nan > 0 || (self.num_infinity > 0 && self.num_neg_infinity > 0) {
+            return f64::NAN;
+        }
+        if self.num_infinity > 0 {
+            return f64::INFINITY;
+        }
+        if self.num_neg_infinity > 0 {
+            return f64::NEG_INFINITY;
+        }
+        let mut normalized = self.clone();
+        normalized.normalize();
+        // Adding the most significant limbs first keeps the rounding error
+        // within a few ulps.
+        normalized
+            .limbs
+            .iter()
+            .rev()
+            .map(|(position, limb)| {
+                (*limb as f64) * exp2(*position * LIMB_BITS as i32 + MIN_EXPONENT)
+            })
+            .sum()
+    }
+}
+
+fn exp2(exponent: i32) -> f64 {
+    if exponent > 1023 {
+        f64::INFINITY
+    } else if exponent >= -1022 {
+        f64::from_bits(((exponent + 1023) as u64) << 52)
+    } else {
+        f64::from_bits(1 << (exponent - MIN_EXPONENT))
+    }
+}
+
+/// Count and sum/min/max of the documents in a range of an aggregate index.
+#[derive(Clone, Debug, Default)]
+pub struct RangeAggregate {
+    count: u64,
+    sum: ExactSum,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl RangeAggregate {
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum.to_f64()
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.max
+    }
+
+    /// Adds `count` documents with summarized value `value`.
+    pub fn add(&mut self, value: Option<f64>, count: u64) {
+        if count == 0 {
+            return;
+        }
+        self.count += count;
+        if let Some(value) = value {
+            self.sum.add(value, count);
+            self.update_bounds(value);
+        }
+    }
+
+    /// Removes `count` documents with summarized value `value`. Their value
+    /// may have been the minimum or maximum, so the caller has to find the
+    /// new ones and set them with [`Self::set_bounds`].
+    pub fn remove(&mut self, value: Option<f64>, count: u64) -> anyhow::Result<()> {
+        self.count = self
+            .count
+            .checked_sub(count)
+            .context("Removed more documents than the range has")?;
+        if let Some(value) = value {
+            self.sum.update(value, -i128::from(count));
+        }
+        Ok(())
+    }
+
+    /// Adds the documents of `other`, a range disjoint from this one.
+    pub fn merge(&mut self, other: &RangeAggregate) {
+        self.count += other.count;
+        self.sum.merge(&other.sum);
+        for value in [other.min, other.max].into_iter().flatten() {
+            self.update_bounds(value);
+        }
+    }
+
+    pub fn set_bounds(&mut self, min: Option<f64>, max: Option<f64>) {
+        self.min = min;
+        self.max = max;
+    }
+
+    fn update_bounds(&mut self, value: f64) {
+        if self
+            .min
+            .map_or(true, |min| cmp_values(value, min) == Ordering::Less)
+        {
+            self.min = Some(value);
+        }
+        if self
+            .max
+            .map_or(true, |max| cmp_values(value, max) == Ordering::Greater)
+        {
+            self.max = Some(value);
+        }
+    }
+}
+
+impl TryFrom<RangeAggregate> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(value: RangeAggregate) -> Result<Self, Self::Error> {
+        let mut sum = value.sum;
+        // Every limb fits in an i64 once normalized.
+        sum.normalize();
+        let limbs = sum
+            .limbs
+            .into_iter()
+            .map(|(position, limb)| {
+                ConvexValue::try_from(vec![
+                    ConvexValue::from(i64::from(position)),
+                    ConvexValue::from(i64::try_from(limb)?),
+                ])
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        obj!(
+            "count" => i64::try_from(value.count)?,
+            "sumLimbs" => limbs,
+            "numNaN" => sum.num_nan,
+            "numInfinity" => sum.num_infinity,
+            "numNegInfinity" => sum.num_neg_infinity,
+            "min" => value.min.map_or(ConvexValue::Null, ConvexValue::from),
+            "max" => value.max.map_or(ConvexValue::Null, ConvexValue::from),
+        )
+    }
+}
+
+impl TryFrom<ConvexObject> for RangeAggregate {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ConvexObject) -> Result<Self, Self::Error> {
+        let mut fields: BTreeMap<_, _> = value.into();
+        let mut int_field = |name: &str| match fields.remove(name) {
+            Some(ConvexValue::Int64(value)) => Ok(value),
+            _ => anyhow::bail!("Missing or invalid `{name}` field for RangeAggregate"),
+        };
+        let count = u64::try_from(int_field("count")?)?;
+        let num_nan = int_field("numNaN")?;
+        let num_infinity = int_field("numInfinity")?;
+        let num_neg_infinity = int_field("numNegInfinity")?;
+        let mut limbs = BTreeMap::new();
+        let Some(ConvexValue::Array(limbs_array)) = fields.remove("sumLimbs") else {
+            anyhow::bail!("Missing or invalid `sumLimbs` field for RangeAggregate");
+        };
+        for limb in limbs_array {
+            let ConvexValue::Array(limb) = limb else {
+                anyhow::bail!("Invalid `sumLimbs` entry for RangeAggregate");
+            };
+            let [ConvexValue::Int64(position), ConvexValue::Int64(limb)] = limb[..] else {
+                anyhow::bail!("Invalid `sumLimbs` entry for RangeAggregate");
+            };
+            limbs.insert(i32::try_from(position)?, i128::from(limb));
+        }
+        let mut float_field = |name: &str| match fields.remove(name) {
+            Some(ConvexValue::Float64(value)) => Ok(Some(value)),
+            Some(ConvexValue::Null) => Ok(None),
+            _ => anyhow::bail!("Missing or invalid `{name}` field for RangeAggregate"),
+        };
+        let min = float_field("min")?;
+        let max = float_field("max")?;
+        Ok(Self {
+            count,
+            sum: ExactSum {
+                limbs,
+                num_nan,
+                num_infinity,
+                num_neg_infinity,
+            },
+            min,
+            max,
+        })
+    }
+}
+
+/// Orders summarized values the way they sort in indexes.
+pub fn cmp_values(a: f64, b: f64) -> Ordering {
+    ConvexValue::from(a).cmp(&ConvexValue::from(b))
+}
+
+/// Returns the key of a document in the per-key counts of an aggregate
+/// index: its index key followed by its summarized value, so every distinct
+/// summarized value within an index key gets its own count.
+pub fn aggregate_key(index_key: &IndexKey, value: Option<f64>) -> Vec<u8> {
+    let mut values = index_key.indexed_values().to_vec();
+    values.push(value.map(ConvexValue::from));
+    values_to_bytes(&values)
+}
+
+/// A number of documents sharing an aggregate key, and the summarized value
+/// they have in common.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AggregateCount {
+    pub value: Option<f64>,
+    pub count: i64,
+}
+
+/// Changes to the counts of one aggregate index since they were last written
+/// to persistence, keyed by [`aggregate_key`]. Keys whose changes cancel out
+/// are removed.
+pub type AggregateDeltas = OrdMap<Vec<u8>, AggregateCount>;
+
+/// Adds `count` to the delta for `key` in `deltas`.
+pub fn add_delta(deltas: &mut AggregateDeltas, key: Vec<u8>, value: Option<f64>, count: i64) {
+    if count == 0 {
+        return;
+    }
+    let mut delta = deltas
+        .get(&key)
+        .copied()
+        .unwrap_or(AggregateCount { value, count: 0 });
+    delta.count += count;
+    if delta.count == 0 {
+        deltas.remove(&key);
+    } else {
+        deltas.insert(key, delta);
+    }
+}
+
+/// The delta for one key of an aggregate index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AggregateDelta {
+    pub key: Vec<u8>,
+    pub value: Option<f64>,
+    pub count: i64,
+}
+
+impl From<AggregateDelta> for AggregateDeltaProto {
+    fn from(delta: AggregateDelta) -> Self {
+        Self {
+            key: Some(delta.key),
+            value: delta.value,
+            count: Some(delta.count),
+        }
+    }
+}
+
+impl TryFrom<AggregateDeltaProto> for AggregateDelta {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: AggregateDeltaProto) -> anyhow::Result<Self> {
+        Ok(Self {
+            key: proto.key.context("Missing `key` field")?,
+            value: proto.value,
+            count: proto.count.context("Missing `count` field")?,
+        })
+    }
+}
+
+/// In-memory state of the aggregate indexes that have been loaded.
+///
+/// Aggregate indexes keep a count of the documents with each
+/// [`aggregate_key`] in persistence, which is only written in the background.
+/// Every commit adds its changes to the in-memory deltas via
+/// [`AggregateIndexes::update`], and writing a count to persistence takes
+/// what was written out of the deltas via [`AggregateIndexes::flushed`], so a
+/// key's persisted count plus its delta is always up to date.
+#[derive(Clone, Debug, Default)]
+pub struct AggregateIndexes {
+    indexes: OrdMap<IndexId, AggregateDeltas>,
+}
+
+impl AggregateIndexes {
+    /// Starts maintaining `index_id` from `deltas`, which must be up to date
+    /// with the snapshot this is applied to.
+    pub fn insert_index(&mut self, index_id: IndexId, deltas: AggregateDeltas) {
+        self.indexes.insert(index_id, deltas);
+    }
+
+    pub fn contains(&self, index_id: &IndexId) -> bool {
+        self.indexes.contains_key(index_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&IndexId, &AggregateDeltas)> {
+        self.indexes.iter()
+    }
+
+    /// Returns the deltas for the keys of `index_id` in `interval`, or `None`
+    /// if the index isn't loaded.
+    pub fn deltas(&self, index_id: &IndexId, interval: &Interval) -> Option<Vec<AggregateDelta>> {
+        let deltas = self.indexes.get(index_id)?;
+        Some(
+            deltas
+                .range::<_, [u8]>(interval)
+                .map(|(key, delta)| AggregateDelta {
+                    key: key.clone(),
+                    value: delta.value,
+                    count: delta.count,
+                })
+                .collect(),
+        )
+    }
+
+    /// Stops maintaining the indexes for which `f` returns false.
+    pub fn retain(&mut self, mut f: impl FnMut(&IndexId) -> bool) {
+        let removed: Vec<_> = self.indexes.keys().filter(|id| !f(id)).copied().collect();
+        for index_id in removed {
+            self.indexes.remove(&index_id);
+        }
+    }
+
+    /// Records that the persisted count of `key` in `index_id` changed by
+    /// `count`, removing the change from its delta.
+    pub fn flushed(&mut self, index_id: IndexId, key: Vec<u8>, value: Option<f64>, count: i64) {
+        if let Some(deltas) = self.indexes.get_mut(&index_id) {
+            add_delta(deltas, key, value, -count);
+        }
+    }
+
+    /// Returns the aggregate keys of `document` in every aggregate index on
+    /// its table.
+    pub fn document_keys<'a>(
+        index_registry: &'a IndexRegistry,
+        document: &'a ResolvedDocument,
+    ) -> impl Iterator<Item = (IndexId, Vec<u8>, Option<f64>)> + 'a {
+        index_registry
+            .aggregate_keys(document)
+            .map(|(index, key, value)| (index.id(), aggregate_key(&key, value), value))
+    }
+
+    pub fn update(
+        &mut self,
+        // NB: We assume that `index_registry` has already received this update.
+        index_registry: &IndexRegistry,
+        deletion: Option<&ResolvedDocument>,
+        insertion: Option<&ResolvedDocument>,
+    ) -> anyhow::Result<()> {
+        if let (Some(old_document), None) = (deletion, insertion) {
+            if old_document.table() == index_registry.index_table() {
+                self.indexes.remove(&old_document.id().internal_id());
+            }
+        }
+        for (document, sign) in [(deletion, -1), (insertion, 1)] {
+            let Some(document) = document else {
+                continue;
+            };
+            for (index_id, key, value) in Self::document_keys(index_registry, document) {
+                if let Some(deltas) = self.indexes.get_mut(&index_id) {
+                    add_delta(deltas, key, value, sign);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn index_not_an_aggregate_index_error(name: &IndexName) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "IndexNotAnAggregateIndex",
+        format!("Index {name} is not an aggregate index"),
+    )
+}
+
+pub fn aggregate_range_on_id_error(name: &IndexName) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "AggregateRangeOnId",
+        format!("Aggregate index {name} can't be queried with a range on `_id`"),
+    )
+}
@@ -27,7 +27,10 @@ use common::{
         Interval,
         IntervalSet,
     },
-    persistence::PersistenceSnapshot,
+    persistence::{
+        IndexStream,
+        PersistenceSnapshot,
+    },
     query::{
         CursorPosition,
         Order,
@@ -147,8 +150,10 @@ impl BackendInMemoryIndexes {
                             index_metadata.name
                         )
                     },
-                    IndexConfig::Search { .. } | IndexConfig::Vector { .. } => {
-                        // We do not load search or vector indexes into memory.
+                    IndexConfig::Search { .. }
+                    | IndexConfig::Vector { .. }
+                    | IndexConfig::Aggregate { .. } => {
+                        // We do not load search, vector or aggregate indexes into memory here.
                         continue;
                     },
                 }
@@ -551,6 +556,21 @@ impl DatabaseIndexSnapshot {
         Ok((results, cache_miss_results, CursorPosition::End))
     }
 
+    /// Scan an index directly from persistence at the snapshot, even if it
+    /// isn't enabled. Used to validate backfilled indexes before they're
+    /// enabled, so it bypasses the cache and the in-memory indexes.
+    pub fn scan_backfilled_index(
+        &self,
+        index_id: IndexId,
+        tablet_id: TabletId,
+        interval: &Interval,
+        order: Order,
+        size_hint: usize,
+    ) -> IndexStream<'_> {
+        self.persistence
+            .index_scan(index_id, tablet_id, interval, order, size_hint)
+    }
+
     pub fn timestamp(&self) -> RepeatableTimestamp {
         self.persistence.timestamp()
     }
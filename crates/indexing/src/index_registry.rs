@@ -167,10 +167,15 @@ impl IndexRegistry {
                 for index in self.indexes_by_table(document.table().tablet_id) {
                     // Only yield fields from database indexes.
                     if let IndexConfig::Database {
-                        developer_config: DeveloperDatabaseIndexConfig { fields },
+                        developer_config,
                         on_disk_state: _,
                     } = &index.metadata.config
                     {
+                        // Partial indexes skip documents that don't match their filter.
+                        if !developer_config.includes(document) {
+                            continue;
+                        }
+                        let fields = &developer_config.fields;
                         yield (
                             index,
                             document.index_key(&fields[..], self.persistence_version()),
@@ -181,6 +186,35 @@ impl IndexRegistry {
         )
     }
 
+    /// Yields the aggregate indexes `document` contributes to, along with its
+    /// key in each index and the value it contributes to sum/min/max.
+    pub fn aggregate_keys<'a>(
+        &'a self,
+        document: &'a ResolvedDocument,
+    ) -> impl Iterator<Item = (&'a Index, IndexKey, Option<f64>)> + 'a {
+        iter::from_coroutine(
+            #[coroutine]
+            move || {
+                for index in self.indexes_by_table(document.table().tablet_id) {
+                    if let IndexConfig::Aggregate {
+                        developer_config,
+                        on_disk_state: _,
+                    } = &index.metadata.config
+                    {
+                        yield (
+                            index,
+                            document.index_key(
+                                &developer_config.fields[..],
+                                self.persistence_version(),
+                            ),
+                            developer_config.summarized_value(document),
+                        );
+                    }
+                }
+            },
+        )
+    }
+
     pub fn index_updates<'a>(
         &'a self,
         deletion: Option<&'a ResolvedDocument>,
@@ -392,6 +426,13 @@ impl IndexRegistry {
             .collect()
     }
 
+    pub fn all_aggregate_indexes(&self) -> Vec<ParsedDocument<TabletIndexMetadata>> {
+        self.all_indexes()
+            .filter(|index| index.is_aggregate_index())
+            .cloned()
+            .collect()
+    }
+
     pub fn all_search_and_vector_indexes(&self) -> Vec<ParsedDocument<TabletIndexMetadata>> {
         self.all_indexes()
             .filter(|index| index.is_search_index() || index.is_vector_index())
@@ -423,7 +464,9 @@ impl IndexRegistry {
                     IndexConfig::Database {
                         developer_config, ..
                     } => Some((index_id, (index_name, developer_config.fields.clone()))),
-                    IndexConfig::Search { .. } | IndexConfig::Vector { .. } => None,
+                    IndexConfig::Search { .. }
+                    | IndexConfig::Vector { .. }
+                    | IndexConfig::Aggregate { .. } => None,
                 }
             })
             .collect()
@@ -470,6 +513,25 @@ impl IndexRegistry {
             .filter(|index| index.metadata.is_vector_index())
     }
 
+    /// Returns the backfilled and enabled database indexes on the given table
+    /// that require their indexed values to be unique. Backfilled indexes
+    /// were checked for duplicates before they finished backfilling, so
+    /// writes are checked against them from then on.
+    pub fn unique_indexes_by_table(
+        &self,
+        tablet_id: TabletId,
+    ) -> impl Iterator<Item = &'_ Index> + '_ {
+        self.indexes_by_table(tablet_id).filter(|index| {
+            matches!(
+                index.metadata.config,
+                IndexConfig::Database {
+                    developer_config: DeveloperDatabaseIndexConfig { unique: true, .. },
+                    on_disk_state: DatabaseIndexState::Backfilled | DatabaseIndexState::Enabled,
+                }
+            )
+        })
+    }
+
     /// Returns both enabled and pending indexes for the given table.
     ///
     /// Multiple Indexes with a given name will be returned if an index is
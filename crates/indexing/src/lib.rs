@@ -7,6 +7,7 @@
 #![feature(lazy_cell)]
 #![feature(try_blocks)]
 
+pub mod aggregate_index;
 pub mod backend_in_memory_indexes;
 pub mod index_registry;
 pub mod interval;
@@ -21,6 +21,7 @@ use common::{
         ResolvedDocument,
     },
     index::IndexKey,
+    interval::Interval,
     persistence::{
         ConflictStrategy,
         NoopRetentionValidator,
@@ -36,6 +37,7 @@ use common::{
         DatabaseIndexUpdate,
         DatabaseIndexValue,
         GenericIndexName,
+        IndexId,
         PersistenceVersion,
         TableName,
         Timestamp,
@@ -69,6 +71,13 @@ impl IdGenerator for ConstantId {
 }
 
 use crate::{
+    aggregate_index::{
+        add_delta,
+        AggregateCount,
+        AggregateDeltas,
+        AggregateIndexes,
+        RangeAggregate,
+    },
     backend_in_memory_indexes::BackendInMemoryIndexes,
     index_registry::IndexRegistry,
 };
@@ -264,7 +273,7 @@ fn test_metadata_change_index() -> anyhow::Result<()> {
         .contains("Can't modify developer index config for existing indexes"));
     let current_metadata = index_registry.enabled_index_metadata(&by_name).unwrap();
     must_let!(let IndexConfig::Database { developer_config, .. } = &current_metadata.config);
-    must_let!(let DeveloperDatabaseIndexConfig { fields } = developer_config);
+    must_let!(let DeveloperDatabaseIndexConfig { fields, .. } = developer_config);
     assert_eq!(*fields, vec!["name".parse()?].try_into()?,);
 
     // Changing which table the index is indexing is not allowed.
@@ -302,7 +311,7 @@ fn test_metadata_change_index() -> anyhow::Result<()> {
     let current_metadata = index_registry.enabled_index_metadata(&by_name).unwrap();
     must_let!(
         let IndexConfig::Database {
-            developer_config: DeveloperDatabaseIndexConfig { fields },
+            developer_config: DeveloperDatabaseIndexConfig { fields, .. },
             ..
         } = &current_metadata.config
     );
@@ -355,7 +364,7 @@ fn test_second_pending_index_for_name_fails() -> anyhow::Result<()> {
     );
     let current_index = index_registry.get_pending(&by_name).unwrap();
     must_let!(let IndexConfig::Database { developer_config, .. } = &current_index.metadata.config);
-    must_let!(let DeveloperDatabaseIndexConfig { fields } = developer_config);
+    must_let!(let DeveloperDatabaseIndexConfig { fields, .. } = developer_config);
     assert_eq!(*fields, vec!["name".parse()?].try_into()?,);
 
     Ok(())
@@ -887,3 +896,75 @@ pub fn same_indexes_same_docs_different_update_order_are_same() -> anyhow::Resul
     assert!(second.same_indexes(&first));
     Ok(())
 }
+
+#[test]
+fn test_aggregate_sum_is_exact() -> anyhow::Result<()> {
+    let mut aggregate = RangeAggregate::default();
+    aggregate.add(Some(0.1), 100_000);
+    aggregate.add(Some(7.0), 1);
+    assert_eq!(aggregate.sum(), 100_000.0 * 0.1 + 7.0);
+    assert_eq!(aggregate.count(), 100_001);
+
+    // Values that cancel out don't lose the small ones in between.
+    let mut aggregate = RangeAggregate::default();
+    aggregate.add(Some(1e16), 1);
+    aggregate.add(Some(1.0), 1);
+    aggregate.add(Some(-1e16), 1);
+    assert_eq!(aggregate.sum(), 1.0);
+    assert_eq!(aggregate.min(), Some(-1e16));
+    assert_eq!(aggregate.max(), Some(1e16));
+
+    // Extremes of the f64 range.
+    let mut aggregate = RangeAggregate::default();
+    aggregate.add(Some(f64::MAX), 1);
+    aggregate.add(Some(f64::MIN_POSITIVE / 4.0), 1);
+    aggregate.add(Some(-f64::MAX), 1);
+    assert_eq!(aggregate.sum(), f64::MIN_POSITIVE / 4.0);
+    Ok(())
+}
+
+#[test]
+fn test_aggregate_sum_non_finite() -> anyhow::Result<()> {
+    let mut aggregate = RangeAggregate::default();
+    aggregate.add(Some(1.0), 1);
+    aggregate.add(Some(f64::INFINITY), 1);
+    assert_eq!(aggregate.sum(), f64::INFINITY);
+    aggregate.add(Some(f64::NEG_INFINITY), 1);
+    assert!(aggregate.sum().is_nan());
+
+    let mut aggregate = RangeAggregate::default();
+    aggregate.add(Some(f64::NAN), 1);
+    assert!(aggregate.sum().is_nan());
+    // Documents without a value are counted but not summed.
+    let mut aggregate = RangeAggregate::default();
+    aggregate.add(None, 2);
+    assert_eq!(aggregate.count(), 2);
+    assert_eq!(aggregate.sum(), 0.0);
+    assert_eq!(aggregate.min(), None);
+    Ok(())
+}
+
+#[test]
+fn test_aggregate_deltas() -> anyhow::Result<()> {
+    let mut deltas = AggregateDeltas::new();
+    add_delta(&mut deltas, vec![1], Some(1.0), 2);
+    add_delta(&mut deltas, vec![2], None, 1);
+    add_delta(&mut deltas, vec![1], Some(1.0), -1);
+    assert_eq!(
+        deltas.get(&vec![1]),
+        Some(&AggregateCount {
+            value: Some(1.0),
+            count: 1
+        })
+    );
+    // Keys whose changes cancel out are dropped.
+    add_delta(&mut deltas, vec![2], None, -1);
+    assert_eq!(deltas.len(), 1);
+
+    let index_id = IndexId::MIN;
+    let mut indexes = AggregateIndexes::default();
+    indexes.insert_index(index_id, deltas);
+    indexes.flushed(index_id, vec![1], Some(1.0), 1);
+    assert_eq!(indexes.deltas(&index_id, &Interval::all()), Some(vec![]));
+    Ok(())
+}
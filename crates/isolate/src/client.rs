@@ -752,6 +752,9 @@ impl<RT: Runtime> IsolateClient<RT> {
             anyhow::bail!("Requested an action from an Isolate client that does not allow actions")
         }
         let timer = metrics::execute_timer(&UdfType::HttpAction, router_path.npm_version());
+        let fetch_client = fetch_client
+            .for_function(router_path.path())
+            .unwrap_or(fetch_client);
         let (tx, rx) = oneshot::channel();
         let key_broker = KeyBroker::new(&self.instance_name, self.instance_secret)?;
         let request = RequestType::HttpAction {
@@ -808,6 +811,9 @@ impl<RT: Runtime> IsolateClient<RT> {
             anyhow::bail!("Requested an action from an Isolate client that does not allow actions")
         }
         let timer = metrics::execute_timer(&UdfType::Action, path_and_args.npm_version());
+        let fetch_client = fetch_client
+            .for_function(path_and_args.path())
+            .unwrap_or(fetch_client);
         let (tx, rx) = oneshot::channel();
         let key_broker = KeyBroker::new(&self.instance_name, self.instance_secret)?;
         let request = RequestType::Action {
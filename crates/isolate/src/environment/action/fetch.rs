@@ -40,14 +40,18 @@ impl<RT: Runtime> TaskExecutor<RT> {
             Ok(parts) => parts,
             Err(e) => {
                 // All fetch errors are treated as developer errors since we have little
-                // control of what they request.
+                // control of what they request. Errors like egress policy violations
+                // already say what went wrong.
+                let e = if e.downcast_ref::<ErrorMetadata>().is_some() {
+                    e
+                } else {
+                    ErrorMetadata::bad_request("FetchFailed", e.to_string()).into()
+                };
                 _ = self
                     .task_retval_sender
                     .unbounded_send(TaskResponse::TaskDone {
                         task_id,
-                        variant: Err(
-                            ErrorMetadata::bad_request("FetchFailed", e.to_string()).into()
-                        ),
+                        variant: Err(e),
                     });
                 Self::log_fetch_request(t, origin, Err(()), initial_response_time);
                 return;
@@ -147,6 +147,7 @@ use crate::{
         HttpActionResponseHead,
         HttpActionResponsePart,
         HttpActionResponseStreamer,
+        WebSocketMessage,
     },
     isolate::{
         Isolate,
@@ -467,9 +468,22 @@ impl<RT: Runtime> ActionEnvironment<RT> {
             },
             None => None,
         };
-        let args_str =
-            serde_json::to_value(HttpRequestV8::from_request(http_request.head, stream_id)?)?
-                .to_string();
+        let web_socket_stream_id = match http_request.web_socket {
+            Some(messages) => {
+                let stream_id = scope.state_mut()?.create_stream()?;
+                scope.state_mut()?.environment.send_stream(
+                    stream_id,
+                    Some(messages.map_ok(|message| message.to_frame()).boxed()),
+                );
+                Some(stream_id)
+            },
+            None => None,
+        };
+        let request_v8 = HttpRequestV8 {
+            web_socket_stream_id,
+            ..HttpRequestV8::from_request(http_request.head, stream_id)?
+        };
+        let args_str = serde_json::to_value(request_v8)?.to_string();
         metrics::log_argument_length(&args_str);
         let args_v8_str = v8::String::new(&mut scope, &args_str)
             .ok_or_else(|| anyhow!("Failed to create argument string"))?;
@@ -501,7 +515,12 @@ impl<RT: Runtime> ActionEnvironment<RT> {
     > {
         let json_value: JsonValue = serde_json::from_str(&result_str)?;
         let v8_response: HttpResponseV8 = serde_json::from_value(json_value)?;
+        let is_web_socket = v8_response.is_web_socket();
         let (raw_response, stream_id) = v8_response.into_response()?;
+        anyhow::ensure!(
+            !is_web_socket || raw_response.status == StatusCode::SWITCHING_PROTOCOLS,
+            "WebSocket responses must have status 101"
+        );
         let (body_sender, body_receiver) = mpsc::unbounded();
         match stream_id {
             Some(stream_id) => {
@@ -516,7 +535,18 @@ impl<RT: Runtime> ActionEnvironment<RT> {
             })))
         });
 
-        Ok(head.chain(body_receiver.map_ok(|b| Ok(HttpActionResponsePart::BodyChunk(b)))))
+        // The stream of an accepted WebSocket carries outgoing messages, and
+        // ends when the action closes the socket.
+        let body = body_receiver.map(move |chunk| {
+            let chunk = chunk?;
+            let part = if is_web_socket {
+                HttpActionResponsePart::WebSocketMessage(WebSocketMessage::from_frame(chunk)?)
+            } else {
+                HttpActionResponsePart::BodyChunk(chunk)
+            };
+            Ok(Ok(part))
+        });
+        Ok(head.chain(body))
     }
 
     fn handle_http_streamed_part(
@@ -552,6 +582,9 @@ impl<RT: Runtime> ActionEnvironment<RT> {
                     streamer.send_part(HttpActionResponsePart::BodyChunk(b))?;
                 }
             },
+            Ok(HttpActionResponsePart::WebSocketMessage(m)) => {
+                streamer.send_part(HttpActionResponsePart::WebSocketMessage(m))?;
+            },
             Err(e) => environment.trace_system(SystemWarning {
                 level: LogLevel::Error,
                 messages: vec![e.to_string()],
@@ -1118,7 +1151,7 @@ impl<RT: Runtime> ActionEnvironment<RT> {
     ) -> anyhow::Result<()> {
         if let Some(warning) = approaching_limit_warning(
             arguments.size(),
-            *FUNCTION_MAX_ARGS_SIZE,
+            FUNCTION_MAX_ARGS_SIZE.get(),
             "FunctionArgumentsTooLarge",
             || "Large size of the action arguments".to_string(),
             None,
@@ -1132,7 +1165,7 @@ impl<RT: Runtime> ActionEnvironment<RT> {
         if let Some(result) = result {
             if let Some(warning) = approaching_limit_warning(
                 result.size(),
-                *FUNCTION_MAX_RESULT_SIZE,
+                FUNCTION_MAX_RESULT_SIZE.get(),
                 "TooLargeFunctionResult",
                 || "Large size of the action return value".to_string(),
                 None,
@@ -198,7 +198,7 @@ impl HttpActionOutcome {
             syscall_trace: syscall_trace.unwrap_or_default(),
             udf_server_version,
 
-            memory_in_mb: (*ISOLATE_MAX_USER_HEAP_SIZE / (1 << 20))
+            memory_in_mb: (ISOLATE_MAX_USER_HEAP_SIZE.get() / (1 << 20))
                 .try_into()
                 .unwrap(),
         }
@@ -33,11 +33,11 @@ impl<RT: Runtime> TaskExecutor<RT> {
                             .task_retval_sender
                             .unbounded_send(TaskResponse::StreamExtend {
                                 stream_id,
-                                chunk: Err(ErrorMetadata::bad_request(
-                                    "StreamFailed",
-                                    e.to_string(),
-                                )
-                                .into()),
+                                chunk: Err(if e.downcast_ref::<ErrorMetadata>().is_some() {
+                                    e
+                                } else {
+                                    ErrorMetadata::bad_request("StreamFailed", e.to_string()).into()
+                                }),
                             });
                         return Err(());
                     },
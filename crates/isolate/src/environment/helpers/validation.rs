@@ -423,7 +423,9 @@ impl ValidatedPathAndArgs {
             .namespace(TableNamespace::by_component_TODO());
         let virtual_table_mapping = &tx.virtual_table_mapping().clone();
 
-        // If the UDF has an args validator, check that these args match.
+        // If the UDF has an args validator, fill in its defaults and check that these
+        // args match.
+        let args = analyzed_function.args.apply_defaults(args)?;
         let args_validation_error =
             analyzed_function
                 .args
@@ -20,6 +20,7 @@ use common::{
     query::{
         Cursor,
         CursorPosition,
+        IndexRangeExpression,
         Query,
     },
     query_journal::QueryJournal,
@@ -30,6 +31,7 @@ use common::{
     },
     types::{
         AllowedVisibility,
+        IndexName,
         PersistenceVersion,
         UdfType,
     },
@@ -75,6 +77,7 @@ use serde_json::{
 use value::{
     heap_size::HeapSize,
     id_v6::DeveloperDocumentId,
+    obj,
     ConvexArray,
     ConvexObject,
     TableName,
@@ -160,7 +163,7 @@ impl AsyncSyscallBatch {
     }
 
     pub fn can_push(&self, name: &str, _args: &JsonValue) -> bool {
-        if self.len() >= *MAX_SYSCALL_BATCH_SIZE {
+        if self.len() >= MAX_SYSCALL_BATCH_SIZE.get() {
             return false;
         }
         match (self, name) {
@@ -562,6 +565,7 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                 let result = match &name[..] {
                     // Database
                     "1.0/count" => Box::pin(Self::count(provider, args)).await,
+                    "1.0/aggregate" => Box::pin(Self::aggregate(provider, args)).await,
                     "1.0/insert" => Box::pin(Self::insert(provider, args)).await,
                     "1.0/shallowMerge" => Box::pin(Self::shallow_merge(provider, args)).await,
                     "1.0/replace" => Box::pin(Self::replace(provider, args)).await,
@@ -639,6 +643,45 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(ConvexValue::from(result).into())
     }
 
+    #[convex_macro::instrument_future]
+    async fn aggregate(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct AggregateArgs {
+            table: String,
+            index: String,
+            range: Vec<JsonValue>,
+        }
+        let (index_name, range) = with_argument_error("db.aggregate", || {
+            let args: AggregateArgs = serde_json::from_value(args)?;
+            let table: TableName = args.table.parse().context(ArgName("table"))?;
+            let index_name = IndexName::new(table, args.index.parse().context(ArgName("index"))?)
+                .context(ArgName("index"))?;
+            let range = args
+                .range
+                .into_iter()
+                .map(IndexRangeExpression::try_from)
+                .collect::<anyhow::Result<Vec<_>>>()
+                .context(ArgName("range"))?;
+            Ok((index_name, range))
+        })?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let aggregate = tx.aggregate(component.into(), &index_name, range).await?;
+
+        // Return numbers as f64, which converts to number type in Javascript.
+        let count = f64::from(u32::try_from(aggregate.count())?);
+        let min = aggregate.min().map_or(ConvexValue::Null, ConvexValue::from);
+        let max = aggregate.max().map_or(ConvexValue::Null, ConvexValue::from);
+        let result = obj!(
+            "count" => count,
+            "sum" => aggregate.sum(),
+            "min" => min,
+            "max" => max,
+        )?;
+        Ok(ConvexValue::Object(result).into())
+    }
+
     #[convex_macro::instrument_future]
     async fn get_user_identity(provider: &mut P, _args: JsonValue) -> anyhow::Result<JsonValue> {
         // TODO: Somehow make the Transaction aware of the dependency on the user.
@@ -810,7 +810,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         };
         if let Some(warning) = approaching_limit_warning(
             arguments.size(),
-            *FUNCTION_MAX_ARGS_SIZE,
+            FUNCTION_MAX_ARGS_SIZE.get(),
             "TooLargeFunctionArguments",
             || "Large size of the function arguments".to_string(),
             None,
@@ -821,7 +821,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         }
         if let Some(warning) = approaching_limit_warning(
             execution_size.read_size.total_document_count,
-            *TRANSACTION_MAX_READ_SIZE_ROWS,
+            TRANSACTION_MAX_READ_SIZE_ROWS.get(),
             "TooManyDocumentsRead",
             || "Many documents read in a single function execution".to_string(),
             Some(OVER_LIMIT_HELP),
@@ -832,7 +832,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         }
         if let Some(warning) = approaching_limit_warning(
             execution_size.num_intervals,
-            *TRANSACTION_MAX_READ_SET_INTERVALS,
+            TRANSACTION_MAX_READ_SET_INTERVALS.get(),
             "TooManyReads",
             || "Many reads in a single function execution".to_string(),
             Some(OVER_LIMIT_HELP),
@@ -843,7 +843,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         }
         if let Some(warning) = approaching_limit_warning(
             execution_size.read_size.total_document_size,
-            *TRANSACTION_MAX_READ_SIZE_BYTES,
+            TRANSACTION_MAX_READ_SIZE_BYTES.get(),
             "TooManyBytesRead",
             || "Many bytes read in a single function execution".to_string(),
             Some(OVER_LIMIT_HELP),
@@ -854,7 +854,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         }
         if let Some(warning) = approaching_limit_warning(
             execution_size.write_size.num_writes,
-            *TRANSACTION_MAX_NUM_USER_WRITES,
+            TRANSACTION_MAX_NUM_USER_WRITES.get(),
             "TooManyWrites",
             || "Many writes in a single function execution".to_string(),
             None,
@@ -865,7 +865,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         }
         if let Some(warning) = approaching_limit_warning(
             execution_size.write_size.size,
-            *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
+            TRANSACTION_MAX_USER_WRITE_SIZE_BYTES.get(),
             "TooManyBytesWritten",
             || "Many bytes written in a single function execution".to_string(),
             None,
@@ -876,7 +876,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         }
         if let Some(warning) = approaching_limit_warning(
             execution_size.scheduled_size.num_writes,
-            *TRANSACTION_MAX_NUM_SCHEDULED,
+            TRANSACTION_MAX_NUM_SCHEDULED.get(),
             "TooManyFunctionsScheduled",
             || "Many functions scheduled by this mutation".to_string(),
             None,
@@ -887,7 +887,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         }
         if let Some(warning) = approaching_limit_warning(
             execution_size.scheduled_size.size,
-            *TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES,
+            TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES.get(),
             "ScheduledFunctionsArgumentsTooLarge",
             || {
                 "Large total size of the arguments of scheduled functions from this mutation"
@@ -930,7 +930,7 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         if let Some(result) = result {
             if let Some(warning) = approaching_limit_warning(
                 result.size(),
-                *FUNCTION_MAX_RESULT_SIZE,
+                FUNCTION_MAX_RESULT_SIZE.get(),
                 "TooLargeFunctionResult",
                 || "Large size of the function return value".to_string(),
                 None,
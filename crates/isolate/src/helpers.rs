@@ -126,12 +126,12 @@ pub fn validate_udf_args_size(
     path: &CanonicalizedComponentFunctionPath,
     args: &ConvexArray,
 ) -> Result<(), JsError> {
-    if args.size() > *FUNCTION_MAX_ARGS_SIZE {
+    if args.size() > FUNCTION_MAX_ARGS_SIZE.get() {
         return Err(JsError::from_message(format!(
             "Arguments for {} are too large (actual: {}, limit: {})",
             path.udf_path.clone(),
             args.size().format_size(BINARY),
-            (*FUNCTION_MAX_ARGS_SIZE).format_size(BINARY),
+            FUNCTION_MAX_ARGS_SIZE.get().format_size(BINARY),
         )));
     }
 
@@ -154,12 +154,12 @@ pub fn deserialize_udf_result(
     })?;
     let result = match ConvexValue::try_from(result_v) {
         Ok(value) => {
-            if value.size() > *FUNCTION_MAX_RESULT_SIZE {
+            if value.size() > FUNCTION_MAX_RESULT_SIZE.get() {
                 Err(JsError::from_message(format!(
                     "Function {} return value is too large (actual: {}, limit: {})",
                     path.debug_str(),
                     value.size().format_size(BINARY),
-                    (*FUNCTION_MAX_RESULT_SIZE).format_size(BINARY),
+                    FUNCTION_MAX_RESULT_SIZE.get().format_size(BINARY),
                 )))
             } else {
                 Ok(value)
@@ -36,6 +36,10 @@ pub struct HttpRequestV8 {
     pub url: String,
     pub method: String,
     pub stream_id: Option<uuid::Uuid>,
+    /// Stream of framed incoming messages, for HTTP action requests that ask
+    /// to upgrade to a WebSocket.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_socket_stream_id: Option<uuid::Uuid>,
 }
 
 impl HttpRequestV8 {
@@ -83,6 +87,7 @@ impl HttpRequestV8 {
             url: request.url.to_string(),
             method: request.method.to_string(),
             stream_id,
+            web_socket_stream_id: None,
         })
     }
 }
@@ -95,9 +100,17 @@ pub struct HttpResponseV8 {
     status_text: Option<String>,
     header_pairs: Vec<(String, String)>,
     url: Option<String>,
+    /// Set by HTTP actions accepting a WebSocket, in which case the stream
+    /// carries framed outgoing messages instead of a body.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    web_socket: bool,
 }
 
 impl HttpResponseV8 {
+    pub fn is_web_socket(&self) -> bool {
+        self.web_socket
+    }
+
     pub fn into_response(self) -> anyhow::Result<(HttpResponse, Option<uuid::Uuid>)> {
         let status_code = StatusCode::try_from(self.status)?;
 
@@ -150,6 +163,7 @@ impl HttpResponseV8 {
                 status_text,
                 header_pairs,
                 url: response.url.map(|u| u.to_string()),
+                web_socket: false,
             },
         ))
     }
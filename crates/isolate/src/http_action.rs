@@ -1,6 +1,12 @@
 use core::fmt;
 
-use bytes::Bytes;
+use anyhow::Context;
+use bytes::{
+    Buf,
+    BufMut,
+    Bytes,
+    BytesMut,
+};
 use common::types::{
     HttpActionRoute,
     RoutableMethod,
@@ -26,12 +32,16 @@ pub const HTTP_ACTION_BODY_LIMIT: usize = 20 << 20;
 pub struct HttpActionRequest {
     pub head: HttpActionRequestHead,
     pub body: Option<BoxStream<'static, anyhow::Result<bytes::Bytes>>>,
+    /// Messages from the client, if it asked to upgrade to a WebSocket. The
+    /// stream ends when the client disconnects.
+    pub web_socket: Option<BoxStream<'static, anyhow::Result<WebSocketMessage>>>,
 }
 
 impl fmt::Debug for HttpActionRequest {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("HttpActionRequest")
             .field("head", &self.head)
+            .field("web_socket", &self.web_socket.is_some())
             .finish()
     }
 }
@@ -95,7 +105,8 @@ impl proptest::arbitrary::Arbitrary for HttpActionRequest {
                         method,
                         url,
                     },
-                    body: body.map(|body| stream::once(async move { Ok(body.into())}).boxed())
+                    body: body.map(|body| stream::once(async move { Ok(body.into())}).boxed()),
+                    web_socket: None,
 
                 })
             }
@@ -154,6 +165,64 @@ impl proptest::arbitrary::Arbitrary for HttpActionResponse {
 pub enum HttpActionResponsePart {
     Head(HttpActionResponseHead),
     BodyChunk(Bytes),
+    /// A message to the client, after a `101 Switching Protocols` head.
+    WebSocketMessage(WebSocketMessage),
+}
+
+/// A message on a WebSocket accepted by an HTTP action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WebSocketMessage {
+    Text(String),
+    Binary(Bytes),
+    Close { code: Option<u16>, reason: String },
+}
+
+const TEXT_FRAME: u8 = 0;
+const BINARY_FRAME: u8 = 1;
+const CLOSE_FRAME: u8 = 2;
+
+impl WebSocketMessage {
+    /// Encodes the message as a single chunk of a stream between Rust and
+    /// JS. The first byte is the message type, and close frames carry the
+    /// code as two bytes, with zero for no code.
+    pub fn to_frame(&self) -> Bytes {
+        let mut frame = BytesMut::new();
+        match self {
+            WebSocketMessage::Text(text) => {
+                frame.put_u8(TEXT_FRAME);
+                frame.put_slice(text.as_bytes());
+            },
+            WebSocketMessage::Binary(bytes) => {
+                frame.put_u8(BINARY_FRAME);
+                frame.put_slice(bytes);
+            },
+            WebSocketMessage::Close { code, reason } => {
+                frame.put_u8(CLOSE_FRAME);
+                frame.put_u16(code.unwrap_or(0));
+                frame.put_slice(reason.as_bytes());
+            },
+        }
+        frame.freeze()
+    }
+
+    pub fn from_frame(mut frame: Bytes) -> anyhow::Result<Self> {
+        anyhow::ensure!(frame.has_remaining(), "Empty WebSocket frame");
+        let message = match frame.get_u8() {
+            TEXT_FRAME => WebSocketMessage::Text(String::from_utf8(frame.to_vec())?),
+            BINARY_FRAME => WebSocketMessage::Binary(frame),
+            CLOSE_FRAME => {
+                anyhow::ensure!(frame.remaining() >= 2, "Truncated WebSocket close frame");
+                let code = frame.get_u16();
+                WebSocketMessage::Close {
+                    code: (code != 0).then_some(code),
+                    reason: String::from_utf8(frame.to_vec())
+                        .context("Invalid WebSocket close reason")?,
+                }
+            },
+            t => anyhow::bail!("Unknown WebSocket frame type {t}"),
+        };
+        Ok(message)
+    }
 }
 
 impl HttpActionResponsePart {
@@ -235,10 +304,23 @@ impl HttpActionResponseStreamer {
         Ok(())
     }
 
+    fn send_web_socket_message(&mut self, message: WebSocketMessage) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.head
+                .as_ref()
+                .is_some_and(|head| head.status == StatusCode::SWITCHING_PROTOCOLS),
+            "Sending WebSocket message before accepting the upgrade"
+        );
+        self.sender
+            .unbounded_send(HttpActionResponsePart::WebSocketMessage(message))?;
+        Ok(())
+    }
+
     pub fn send_part(&mut self, part: HttpActionResponsePart) -> anyhow::Result<()> {
         match part {
             HttpActionResponsePart::Head(h) => self.send_head(h)?,
             HttpActionResponsePart::BodyChunk(b) => self.send_body(b)?,
+            HttpActionResponsePart::WebSocketMessage(m) => self.send_web_socket_message(m)?,
         }
         Ok(())
     }
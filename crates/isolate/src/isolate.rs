@@ -152,7 +152,7 @@ impl<RT: Runtime> Isolate<RT> {
         let _timer = create_isolate_timer();
         let create_params = v8::CreateParams::default().heap_limits(
             INITIAL_HEAP_SIZE,
-            *ISOLATE_MAX_USER_HEAP_SIZE + *ISOLATE_MAX_HEAP_EXTRA_SIZE,
+            ISOLATE_MAX_USER_HEAP_SIZE.get() + ISOLATE_MAX_HEAP_EXTRA_SIZE.get(),
         );
 
         let mut v8_isolate = v8::Isolate::new(create_params);
@@ -255,7 +255,7 @@ impl<RT: Runtime> Isolate<RT> {
         let mut stats = v8::HeapStatistics::default();
         self.v8_isolate.get_heap_statistics(&mut stats);
         log_heap_statistics(&stats);
-        if stats.total_available_size() < *ISOLATE_MAX_USER_HEAP_SIZE {
+        if stats.total_available_size() < ISOLATE_MAX_USER_HEAP_SIZE.get() {
             self.handle.terminate(TerminationReason::OutOfMemory);
             return Err(IsolateNotClean::TooMuchMemoryCarryOver(
                 stats.total_available_size().format_size(BINARY),
@@ -305,6 +305,7 @@ impl<RT: Runtime> Isolate<RT> {
             stream_listeners: WithHeapSize::default(),
             console_timers: WithHeapSize::default(),
             text_decoders: BTreeMap::new(),
+            compression_streams: BTreeMap::new(),
         };
         Ok((self.handle.clone(), state))
     }
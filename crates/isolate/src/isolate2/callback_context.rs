@@ -458,7 +458,10 @@ mod op_provider {
     use crate::{
         environment::AsyncOpRequest,
         isolate2::client::PendingAsyncOp,
-        ops::OpProvider,
+        ops::{
+            CompressionResource,
+            OpProvider,
+        },
         request_scope::{
             StreamListener,
             TextDecoderResource,
@@ -587,5 +590,26 @@ mod op_provider {
         fn remove_text_decoder(&mut self, uuid: &Uuid) -> anyhow::Result<TextDecoderResource> {
             self.context_state()?.remove_text_decoder(uuid)
         }
+
+        fn create_compression_stream(
+            &mut self,
+            resource: CompressionResource,
+        ) -> anyhow::Result<Uuid> {
+            self.context_state()?.create_compression_stream(resource)
+        }
+
+        fn get_compression_stream(
+            &mut self,
+            uuid: &Uuid,
+        ) -> anyhow::Result<&mut CompressionResource> {
+            self.context_state()?.get_compression_stream(uuid)
+        }
+
+        fn remove_compression_stream(
+            &mut self,
+            uuid: &Uuid,
+        ) -> anyhow::Result<CompressionResource> {
+            self.context_state()?.remove_compression_stream(uuid)
+        }
     }
 }
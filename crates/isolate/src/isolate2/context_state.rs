@@ -30,7 +30,11 @@ use super::{
     PromiseId,
 };
 use crate::{
-    ops::CryptoOps,
+    ops::{
+        check_open_compression_streams,
+        CompressionResource,
+        CryptoOps,
+    },
     request_scope::{
         ReadableStream,
         StreamListener,
@@ -60,6 +64,10 @@ pub struct ContextState {
     // Additionally, `TextDecoderResource` should have a fairly small heap size.
     pub text_decoders: BTreeMap<uuid::Uuid, TextDecoderResource>,
 
+    // Like `text_decoders`, this isn't wrapped in `WithHeapSize`. Each
+    // resource's output buffer is drained on every write.
+    pub compression_streams: BTreeMap<uuid::Uuid, CompressionResource>,
+
     pub environment: Box<dyn Environment>,
 
     pub failure: Option<ContextFailure>,
@@ -87,6 +95,8 @@ impl ContextState {
 
             text_decoders: BTreeMap::new(),
 
+            compression_streams: BTreeMap::new(),
+
             environment,
 
             failure: None,
@@ -199,6 +209,34 @@ impl ContextState {
             .ok_or_else(|| anyhow::anyhow!("Text decoder resource not found"))?;
         Ok(decoder)
     }
+
+    pub fn create_compression_stream(
+        &mut self,
+        resource: CompressionResource,
+    ) -> anyhow::Result<Uuid> {
+        check_open_compression_streams(self.compression_streams.len())?;
+        let id = CryptoOps::random_uuid(self.environment.rng()?)?;
+        self.compression_streams.insert(id, resource);
+        Ok(id)
+    }
+
+    pub fn get_compression_stream(
+        &mut self,
+        id: &uuid::Uuid,
+    ) -> anyhow::Result<&mut CompressionResource> {
+        self.compression_streams
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Compression stream resource not found"))
+    }
+
+    pub fn remove_compression_stream(
+        &mut self,
+        id: &uuid::Uuid,
+    ) -> anyhow::Result<CompressionResource> {
+        self.compression_streams
+            .remove(id)
+            .ok_or_else(|| anyhow::anyhow!("Compression stream resource not found"))
+    }
 }
 
 pub enum ContextFailure {
@@ -19,7 +19,7 @@ impl Thread {
     pub fn new() -> Self {
         let create_params = v8::CreateParams::default().heap_limits(
             INITIAL_HEAP_SIZE,
-            *ISOLATE_MAX_USER_HEAP_SIZE + *ISOLATE_MAX_HEAP_EXTRA_SIZE,
+            ISOLATE_MAX_USER_HEAP_SIZE.get() + ISOLATE_MAX_HEAP_EXTRA_SIZE.get(),
         );
         let mut isolate = v8::Isolate::new(create_params);
 
@@ -91,6 +91,7 @@ pub use self::{
         HttpActionResponseHead,
         HttpActionResponsePart,
         HttpActionResponseStreamer,
+        WebSocketMessage,
         HTTP_ACTION_BODY_LIMIT,
     },
     isolate::IsolateHeapStats,
@@ -0,0 +1,187 @@
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+// https://github.com/denoland/deno/blob/main/ext/web/compression.rs
+
+use std::io::Write;
+
+use common::knobs::{
+    ISOLATE_MAX_OPEN_COMPRESSION_STREAMS,
+    ISOLATE_MAX_USER_HEAP_SIZE,
+};
+use deno_core::ToJsBuffer;
+use errors::ErrorMetadata;
+use flate2::{
+    write::{
+        DeflateDecoder,
+        DeflateEncoder,
+        GzDecoder,
+        GzEncoder,
+        ZlibDecoder,
+        ZlibEncoder,
+    },
+    Compression,
+};
+use serde_bytes::ByteBuf;
+use uuid::Uuid;
+
+use super::OpProvider;
+
+/// Input is fed to the (de)compressor in chunks of this size so a small,
+/// highly compressed input can't expand past the memory limit before we get a
+/// chance to check. Deflate expands by at most ~1000x, so a single chunk can't
+/// overshoot the limit by more than a few megabytes.
+const INPUT_CHUNK_SIZE: usize = 4096;
+
+pub enum CompressionResource {
+    DeflateDecoder(ZlibDecoder<Vec<u8>>),
+    DeflateEncoder(ZlibEncoder<Vec<u8>>),
+    DeflateRawDecoder(DeflateDecoder<Vec<u8>>),
+    DeflateRawEncoder(DeflateEncoder<Vec<u8>>),
+    GzDecoder(GzDecoder<Vec<u8>>),
+    GzEncoder(GzEncoder<Vec<u8>>),
+}
+
+impl CompressionResource {
+    fn new(format: &str, is_decoder: bool) -> anyhow::Result<Self> {
+        let w = Vec::new();
+        let resource = match (format, is_decoder) {
+            ("deflate", true) => Self::DeflateDecoder(ZlibDecoder::new(w)),
+            ("deflate", false) => Self::DeflateEncoder(ZlibEncoder::new(w, Compression::default())),
+            ("deflate-raw", true) => Self::DeflateRawDecoder(DeflateDecoder::new(w)),
+            ("deflate-raw", false) => {
+                Self::DeflateRawEncoder(DeflateEncoder::new(w, Compression::default()))
+            },
+            ("gzip", true) => Self::GzDecoder(GzDecoder::new(w)),
+            ("gzip", false) => Self::GzEncoder(GzEncoder::new(w, Compression::default())),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "UnsupportedCompressionFormat",
+                format!("Unsupported compression format: '{format}'"),
+            )),
+        };
+        Ok(resource)
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Self::DeflateDecoder(d) => d,
+            Self::DeflateEncoder(d) => d,
+            Self::DeflateRawDecoder(d) => d,
+            Self::DeflateRawEncoder(d) => d,
+            Self::GzDecoder(d) => d,
+            Self::GzEncoder(d) => d,
+        }
+    }
+
+    fn output(&mut self) -> &mut Vec<u8> {
+        match self {
+            Self::DeflateDecoder(d) => d.get_mut(),
+            Self::DeflateEncoder(d) => d.get_mut(),
+            Self::DeflateRawDecoder(d) => d.get_mut(),
+            Self::DeflateRawEncoder(d) => d.get_mut(),
+            Self::GzDecoder(d) => d.get_mut(),
+            Self::GzEncoder(d) => d.get_mut(),
+        }
+    }
+
+    fn write(&mut self, input: &[u8]) -> anyhow::Result<Vec<u8>> {
+        for chunk in input.chunks(INPUT_CHUNK_SIZE) {
+            self.writer().write_all(chunk).map_err(corrupt_data)?;
+            check_output_size(self.output().len())?;
+        }
+        self.writer().flush().map_err(corrupt_data)?;
+        check_output_size(self.output().len())?;
+        Ok(std::mem::take(self.output()))
+    }
+
+    fn finish(self) -> anyhow::Result<Vec<u8>> {
+        let output = match self {
+            Self::DeflateDecoder(d) => d.finish(),
+            Self::DeflateEncoder(d) => d.finish(),
+            Self::DeflateRawDecoder(d) => d.finish(),
+            Self::DeflateRawEncoder(d) => d.finish(),
+            Self::GzDecoder(d) => d.finish(),
+            Self::GzEncoder(d) => d.finish(),
+        };
+        let output = output.map_err(corrupt_data)?;
+        check_output_size(output.len())?;
+        Ok(output)
+    }
+}
+
+fn corrupt_data(e: std::io::Error) -> anyhow::Error {
+    anyhow::Error::from(e).context(ErrorMetadata::bad_request(
+        "CompressionError",
+        format!("Failed to process compressed data: {e}"),
+    ))
+}
+
+fn check_output_size(len: usize) -> anyhow::Result<()> {
+    if len > ISOLATE_MAX_USER_HEAP_SIZE.get() {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "CompressionOutputTooLarge",
+            format!(
+                "Compression stream produced more than {} bytes from a single chunk",
+                ISOLATE_MAX_USER_HEAP_SIZE.get()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Called before opening a stream, with the number of streams the request
+/// already has open.
+pub fn check_open_compression_streams(open: usize) -> anyhow::Result<()> {
+    if open >= *ISOLATE_MAX_OPEN_COMPRESSION_STREAMS {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "TooManyCompressionStreams",
+            format!(
+                "Too many open compression streams (limit: {}). Read each stream to \
+                 the end or cancel it before opening more.",
+                *ISOLATE_MAX_OPEN_COMPRESSION_STREAMS
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[convex_macro::v8_op]
+pub fn op_compression_new<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    format: String,
+    is_decoder: bool,
+) -> anyhow::Result<Uuid> {
+    let resource = CompressionResource::new(&format, is_decoder)?;
+    provider.create_compression_stream(resource)
+}
+
+#[convex_macro::v8_op]
+pub fn op_compression_write<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    id: Uuid,
+    input: ByteBuf,
+) -> anyhow::Result<ToJsBuffer> {
+    let resource = provider.get_compression_stream(&id)?;
+    let output = match resource.write(&input) {
+        Ok(output) => output,
+        Err(e) => {
+            // The stream errors out, so the JS side won't call `finish`.
+            provider.remove_compression_stream(&id)?;
+            return Err(e);
+        },
+    };
+    Ok(output.into())
+}
+
+#[convex_macro::v8_op]
+pub fn op_compression_finish<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    id: Uuid,
+    report_errors: bool,
+) -> anyhow::Result<ToJsBuffer> {
+    let resource = provider.remove_compression_stream(&id)?;
+    let output = match resource.finish() {
+        Ok(output) => output,
+        Err(e) if report_errors => return Err(e),
+        Err(_) => vec![],
+    };
+    Ok(output.into())
+}
@@ -0,0 +1,245 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+// https://github.com/denoland/deno/blob/main/ext/crypto/decrypt.rs
+
+use aes::cipher::{
+    block_padding::Pkcs7,
+    BlockDecryptMut,
+    KeyIvInit,
+};
+use aes_gcm::{
+    aead::{
+        generic_array::{
+            typenum::{
+                U12,
+                U16,
+            },
+            ArrayLength,
+            Unsigned,
+        },
+        AeadInPlace,
+        KeyInit,
+    },
+    AesGcm,
+    Nonce,
+};
+use rsa::{
+    pkcs1::DecodeRsaPrivateKey,
+    RsaPrivateKey,
+};
+use serde::Deserialize;
+
+use super::{
+    encrypt::{
+        apply_aes_ctr,
+        rsa_oaep_padding,
+    },
+    shared::{
+        operation_error,
+        type_error,
+        AnyError,
+        ShaHash,
+        V8RawKeyData,
+    },
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecryptOptions {
+    key: V8RawKeyData,
+    #[serde(flatten)]
+    algorithm: DecryptAlgorithm,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", tag = "algorithm")]
+pub enum DecryptAlgorithm {
+    #[serde(rename = "RSA-OAEP")]
+    RsaOaep {
+        hash: ShaHash,
+        #[serde(with = "serde_bytes")]
+        label: Vec<u8>,
+    },
+    #[serde(rename = "AES-CBC", rename_all = "camelCase")]
+    AesCbc {
+        #[serde(with = "serde_bytes")]
+        iv: Vec<u8>,
+        length: usize,
+    },
+    #[serde(rename = "AES-GCM", rename_all = "camelCase")]
+    AesGcm {
+        #[serde(with = "serde_bytes")]
+        iv: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        additional_data: Option<Vec<u8>>,
+        length: usize,
+        tag_length: usize,
+    },
+    #[serde(rename = "AES-CTR", rename_all = "camelCase")]
+    AesCtr {
+        #[serde(with = "serde_bytes")]
+        counter: Vec<u8>,
+        ctr_length: usize,
+        key_length: usize,
+    },
+}
+
+pub fn decrypt(opts: DecryptOptions, data: &[u8]) -> Result<Vec<u8>, AnyError> {
+    let key = opts.key;
+    match opts.algorithm {
+        DecryptAlgorithm::RsaOaep { hash, label } => decrypt_rsa_oaep(key, hash, label, data),
+        DecryptAlgorithm::AesCbc { iv, length } => decrypt_aes_cbc(key, length, iv, data),
+        DecryptAlgorithm::AesGcm {
+            iv,
+            additional_data,
+            length,
+            tag_length,
+        } => decrypt_aes_gcm(key, length, tag_length, iv, additional_data, data),
+        DecryptAlgorithm::AesCtr {
+            counter,
+            ctr_length,
+            key_length,
+        } => apply_aes_ctr(key, key_length, &counter, ctr_length, data),
+    }
+}
+
+fn decrypt_rsa_oaep(
+    key: V8RawKeyData,
+    hash: ShaHash,
+    label: Vec<u8>,
+    data: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+    let private_key = RsaPrivateKey::from_pkcs1_der(key.as_rsa_private_key()?)
+        .map_err(|_| operation_error("failed to decode private key"))?;
+    private_key
+        .decrypt(rsa_oaep_padding(hash, label), data)
+        .map_err(|_| operation_error("Decryption failed"))
+}
+
+fn decrypt_aes_cbc(
+    key: V8RawKeyData,
+    length: usize,
+    iv: Vec<u8>,
+    data: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+    let key = key.as_secret_key()?;
+    let plaintext = match length {
+        128 => cbc::Decryptor::<aes::Aes128>::new_from_slices(key, &iv)
+            .map_err(|_| operation_error("Invalid key or iv"))?
+            .decrypt_padded_vec_mut::<Pkcs7>(data),
+        192 => cbc::Decryptor::<aes::Aes192>::new_from_slices(key, &iv)
+            .map_err(|_| operation_error("Invalid key or iv"))?
+            .decrypt_padded_vec_mut::<Pkcs7>(data),
+        256 => cbc::Decryptor::<aes::Aes256>::new_from_slices(key, &iv)
+            .map_err(|_| operation_error("Invalid key or iv"))?
+            .decrypt_padded_vec_mut::<Pkcs7>(data),
+        _ => return Err(type_error("invalid length")),
+    };
+    plaintext.map_err(|_| operation_error("Decryption failed. Invalid padding"))
+}
+
+fn decrypt_aes_gcm_general<N: ArrayLength<u8>>(
+    key: &[u8],
+    iv: &[u8],
+    length: usize,
+    additional_data: &[u8],
+    plaintext: &mut [u8],
+    tag: &[u8],
+) -> Result<(), AnyError> {
+    let nonce = Nonce::<N>::from_slice(iv);
+    let result = match length {
+        128 => decrypt_aes_gcm_truncated(
+            &AesGcm::<aes::Aes128, N>::new_from_slice(key)
+                .map_err(|_| operation_error("Decryption failed"))?,
+            nonce,
+            additional_data,
+            plaintext,
+            tag,
+        ),
+        192 => decrypt_aes_gcm_truncated(
+            &AesGcm::<aes::Aes192, N>::new_from_slice(key)
+                .map_err(|_| operation_error("Decryption failed"))?,
+            nonce,
+            additional_data,
+            plaintext,
+            tag,
+        ),
+        256 => decrypt_aes_gcm_truncated(
+            &AesGcm::<aes::Aes256, N>::new_from_slice(key)
+                .map_err(|_| operation_error("Decryption failed"))?,
+            nonce,
+            additional_data,
+            plaintext,
+            tag,
+        ),
+        _ => return Err(type_error("invalid length")),
+    };
+    result.map_err(|_| operation_error("Decryption failed"))
+}
+
+/// The `aes_gcm` crate only checks full 128-bit tags, so for shorter tags
+/// this decrypts without checking, then encrypts the plaintext again to
+/// compute the full tag and compares its prefix.
+fn decrypt_aes_gcm_truncated<C: AeadInPlace>(
+    cipher: &C,
+    nonce: &aes_gcm::aead::Nonce<C>,
+    additional_data: &[u8],
+    buffer: &mut [u8],
+    tag: &[u8],
+) -> Result<(), aes_gcm::Error> {
+    if tag.len() == C::TagSize::USIZE {
+        return cipher.decrypt_in_place_detached(
+            nonce,
+            additional_data,
+            buffer,
+            aes_gcm::aead::Tag::<C>::from_slice(tag),
+        );
+    }
+    // GCM encrypts and decrypts with the same keystream, so encrypting the
+    // ciphertext decrypts it.
+    cipher.encrypt_in_place_detached(nonce, additional_data, buffer)?;
+    let mut reencrypted = buffer.to_vec();
+    let full_tag = cipher.encrypt_in_place_detached(nonce, additional_data, &mut reencrypted)?;
+    // Compare in constant time.
+    let difference = full_tag[..tag.len()]
+        .iter()
+        .zip(tag)
+        .fold(0, |acc, (a, b)| acc | (a ^ b));
+    if difference != 0 {
+        return Err(aes_gcm::Error);
+    }
+    Ok(())
+}
+
+fn decrypt_aes_gcm(
+    key: V8RawKeyData,
+    length: usize,
+    tag_length: usize,
+    iv: Vec<u8>,
+    additional_data: Option<Vec<u8>>,
+    data: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+    let key = key.as_secret_key()?;
+    let additional_data = additional_data.unwrap_or_default();
+
+    // Same tag lengths as `encrypt_aes_gcm` accepts.
+    if ![32, 64, 96, 104, 112, 120, 128].contains(&tag_length) {
+        return Err(type_error("invalid tag length"));
+    }
+    let tag_length = tag_length / 8;
+    if data.len() < tag_length {
+        return Err(operation_error("Decryption failed"));
+    }
+    let (ciphertext, tag) = data.split_at(data.len() - tag_length);
+    let mut plaintext = ciphertext.to_vec();
+
+    match iv.len() {
+        12 => {
+            decrypt_aes_gcm_general::<U12>(key, &iv, length, &additional_data, &mut plaintext, tag)?
+        },
+        16 => {
+            decrypt_aes_gcm_general::<U16>(key, &iv, length, &additional_data, &mut plaintext, tag)?
+        },
+        _ => return Err(type_error("iv length not equal to 12 or 16")),
+    };
+    Ok(plaintext)
+}
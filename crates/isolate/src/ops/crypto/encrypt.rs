@@ -0,0 +1,253 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+// https://github.com/denoland/deno/blob/main/ext/crypto/encrypt.rs
+
+use aes::cipher::{
+    block_padding::Pkcs7,
+    BlockEncryptMut,
+    KeyIvInit,
+    StreamCipher,
+};
+use aes_gcm::{
+    aead::{
+        generic_array::{
+            typenum::{
+                U12,
+                U16,
+            },
+            ArrayLength,
+        },
+        AeadInPlace,
+        KeyInit,
+    },
+    AesGcm,
+    Nonce,
+};
+use ctr::{
+    Ctr128BE,
+    Ctr32BE,
+    Ctr64BE,
+};
+use rand::{
+    CryptoRng,
+    RngCore,
+};
+use rsa::{
+    pkcs1::DecodeRsaPublicKey,
+    Oaep,
+    RsaPublicKey,
+};
+use serde::Deserialize;
+use sha1::Sha1;
+use sha2::{
+    Sha256,
+    Sha384,
+    Sha512,
+};
+
+use super::shared::{
+    operation_error,
+    type_error,
+    AnyError,
+    ShaHash,
+    V8RawKeyData,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptOptions {
+    key: V8RawKeyData,
+    #[serde(flatten)]
+    algorithm: EncryptAlgorithm,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", tag = "algorithm")]
+pub enum EncryptAlgorithm {
+    #[serde(rename = "RSA-OAEP")]
+    RsaOaep {
+        hash: ShaHash,
+        #[serde(with = "serde_bytes")]
+        label: Vec<u8>,
+    },
+    #[serde(rename = "AES-CBC", rename_all = "camelCase")]
+    AesCbc {
+        #[serde(with = "serde_bytes")]
+        iv: Vec<u8>,
+        length: usize,
+    },
+    #[serde(rename = "AES-GCM", rename_all = "camelCase")]
+    AesGcm {
+        #[serde(with = "serde_bytes")]
+        iv: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        additional_data: Option<Vec<u8>>,
+        length: usize,
+        tag_length: usize,
+    },
+    #[serde(rename = "AES-CTR", rename_all = "camelCase")]
+    AesCtr {
+        #[serde(with = "serde_bytes")]
+        counter: Vec<u8>,
+        ctr_length: usize,
+        key_length: usize,
+    },
+}
+
+pub fn encrypt(
+    opts: EncryptOptions,
+    data: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>, AnyError> {
+    let key = opts.key;
+    match opts.algorithm {
+        EncryptAlgorithm::RsaOaep { hash, label } => encrypt_rsa_oaep(key, hash, label, data, rng),
+        EncryptAlgorithm::AesCbc { iv, length } => encrypt_aes_cbc(key, length, iv, data),
+        EncryptAlgorithm::AesGcm {
+            iv,
+            additional_data,
+            length,
+            tag_length,
+        } => encrypt_aes_gcm(key, length, tag_length, iv, additional_data, data),
+        EncryptAlgorithm::AesCtr {
+            counter,
+            ctr_length,
+            key_length,
+        } => apply_aes_ctr(key, key_length, &counter, ctr_length, data),
+    }
+}
+
+pub(super) fn rsa_oaep_padding(hash: ShaHash, label: Vec<u8>) -> Oaep {
+    let label = String::from_utf8_lossy(&label).into_owned();
+    match hash {
+        ShaHash::Sha1 => Oaep::new_with_label::<Sha1, _>(label),
+        ShaHash::Sha256 => Oaep::new_with_label::<Sha256, _>(label),
+        ShaHash::Sha384 => Oaep::new_with_label::<Sha384, _>(label),
+        ShaHash::Sha512 => Oaep::new_with_label::<Sha512, _>(label),
+    }
+}
+
+fn encrypt_rsa_oaep(
+    key: V8RawKeyData,
+    hash: ShaHash,
+    label: Vec<u8>,
+    data: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>, AnyError> {
+    let public_key = key.as_rsa_public_key()?;
+    let public_key = RsaPublicKey::from_pkcs1_der(&public_key)
+        .map_err(|_| operation_error("failed to decode public key"))?;
+    public_key
+        .encrypt(rng, rsa_oaep_padding(hash, label), data)
+        .map_err(|e| operation_error(format!("Encryption failed: {e}")))
+}
+
+fn encrypt_aes_cbc(
+    key: V8RawKeyData,
+    length: usize,
+    iv: Vec<u8>,
+    data: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+    let key = key.as_secret_key()?;
+    let ciphertext = match length {
+        128 => cbc::Encryptor::<aes::Aes128>::new_from_slices(key, &iv)
+            .map_err(|_| operation_error("Invalid key or iv"))?
+            .encrypt_padded_vec_mut::<Pkcs7>(data),
+        192 => cbc::Encryptor::<aes::Aes192>::new_from_slices(key, &iv)
+            .map_err(|_| operation_error("Invalid key or iv"))?
+            .encrypt_padded_vec_mut::<Pkcs7>(data),
+        256 => cbc::Encryptor::<aes::Aes256>::new_from_slices(key, &iv)
+            .map_err(|_| operation_error("Invalid key or iv"))?
+            .encrypt_padded_vec_mut::<Pkcs7>(data),
+        _ => return Err(type_error("invalid length")),
+    };
+    Ok(ciphertext)
+}
+
+fn encrypt_aes_gcm_general<N: ArrayLength<u8>>(
+    key: &[u8],
+    iv: &[u8],
+    length: usize,
+    ciphertext: &mut [u8],
+    additional_data: &[u8],
+) -> Result<aes_gcm::Tag, AnyError> {
+    let nonce = Nonce::<N>::from_slice(iv);
+    let tag = match length {
+        128 => AesGcm::<aes::Aes128, N>::new_from_slice(key)
+            .map_err(|_| operation_error("Encryption failed"))?
+            .encrypt_in_place_detached(nonce, additional_data, ciphertext),
+        192 => AesGcm::<aes::Aes192, N>::new_from_slice(key)
+            .map_err(|_| operation_error("Encryption failed"))?
+            .encrypt_in_place_detached(nonce, additional_data, ciphertext),
+        256 => AesGcm::<aes::Aes256, N>::new_from_slice(key)
+            .map_err(|_| operation_error("Encryption failed"))?
+            .encrypt_in_place_detached(nonce, additional_data, ciphertext),
+        _ => return Err(type_error("invalid length")),
+    };
+    tag.map_err(|_| operation_error("Encryption failed"))
+}
+
+fn encrypt_aes_gcm(
+    key: V8RawKeyData,
+    length: usize,
+    tag_length: usize,
+    iv: Vec<u8>,
+    additional_data: Option<Vec<u8>>,
+    data: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+    let key = key.as_secret_key()?;
+    let additional_data = additional_data.unwrap_or_default();
+    let mut ciphertext = data.to_vec();
+    // Fixed 96-bit or 128-bit nonce.
+    let tag = match iv.len() {
+        12 => encrypt_aes_gcm_general::<U12>(key, &iv, length, &mut ciphertext, &additional_data)?,
+        16 => encrypt_aes_gcm_general::<U16>(key, &iv, length, &mut ciphertext, &additional_data)?,
+        _ => return Err(type_error("iv length not equal to 12 or 16")),
+    };
+    // The spec truncates the tag to the requested length rather than failing.
+    let tag_length = tag_length / 8;
+    if tag_length > tag.len() {
+        return Err(operation_error("Invalid tag length"));
+    }
+    ciphertext.extend_from_slice(&tag[..tag_length]);
+    Ok(ciphertext)
+}
+
+fn apply_aes_ctr_general<B: KeyIvInit + StreamCipher>(
+    key: &[u8],
+    counter: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+    let mut cipher =
+        B::new_from_slices(key, counter).map_err(|_| operation_error("Invalid key or counter"))?;
+    let mut output = data.to_vec();
+    cipher
+        .try_apply_keystream(&mut output)
+        .map_err(|_| operation_error("The counter would wrap around"))?;
+    Ok(output)
+}
+
+/// AES-CTR is symmetric, so this both encrypts and decrypts.
+pub(super) fn apply_aes_ctr(
+    key: V8RawKeyData,
+    key_length: usize,
+    counter: &[u8],
+    ctr_length: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, AnyError> {
+    let key = key.as_secret_key()?;
+    match (ctr_length, key_length) {
+        (32, 128) => apply_aes_ctr_general::<Ctr32BE<aes::Aes128>>(key, counter, data),
+        (32, 192) => apply_aes_ctr_general::<Ctr32BE<aes::Aes192>>(key, counter, data),
+        (32, 256) => apply_aes_ctr_general::<Ctr32BE<aes::Aes256>>(key, counter, data),
+        (64, 128) => apply_aes_ctr_general::<Ctr64BE<aes::Aes128>>(key, counter, data),
+        (64, 192) => apply_aes_ctr_general::<Ctr64BE<aes::Aes192>>(key, counter, data),
+        (64, 256) => apply_aes_ctr_general::<Ctr64BE<aes::Aes256>>(key, counter, data),
+        (128, 128) => apply_aes_ctr_general::<Ctr128BE<aes::Aes128>>(key, counter, data),
+        (128, 192) => apply_aes_ctr_general::<Ctr128BE<aes::Aes192>>(key, counter, data),
+        (128, 256) => apply_aes_ctr_general::<Ctr128BE<aes::Aes256>>(key, counter, data),
+        (32 | 64 | 128, _) => Err(type_error("invalid length")),
+        _ => Err(type_error(
+            "invalid counter length. Currently supported 32/64/128 bits",
+        )),
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+// https://github.com/denoland/deno/blob/main/ext/crypto/generate_key.rs
+
+use p256::pkcs8::EncodePrivateKey;
+use rand::{
+    CryptoRng,
+    RngCore,
+};
+use rsa::{
+    pkcs1::EncodeRsaPrivateKey,
+    BigUint,
+    RsaPrivateKey,
+};
+use serde::Deserialize;
+
+use super::{
+    shared::{
+        operation_error,
+        AnyError,
+        EcNamedCurve,
+    },
+    CryptoHash,
+};
+
+// Generating larger keys takes long enough to hold up the isolate for most of
+// a function's time budget.
+const MAX_RSA_MODULUS_LENGTH: u32 = 4096;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", tag = "algorithm")]
+pub enum GenerateKeyOptions {
+    #[serde(rename = "RSA", rename_all = "camelCase")]
+    Rsa {
+        modulus_length: u32,
+        #[serde(with = "serde_bytes")]
+        public_exponent: Vec<u8>,
+    },
+    #[serde(rename = "EC", rename_all = "camelCase")]
+    Ec { named_curve: EcNamedCurve },
+    #[serde(rename = "AES", rename_all = "camelCase")]
+    Aes { length: usize },
+    #[serde(rename = "HMAC", rename_all = "camelCase")]
+    Hmac {
+        hash: CryptoHash,
+        length: Option<usize>,
+    },
+}
+
+/// Returns the secret key bytes for AES and HMAC, the PKCS#1 private key for
+/// RSA and the PKCS#8 private key for EC, matching how imported keys are
+/// stored.
+pub fn generate_key(
+    opts: GenerateKeyOptions,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>, AnyError> {
+    match opts {
+        GenerateKeyOptions::Rsa {
+            modulus_length,
+            public_exponent,
+        } => generate_key_rsa(modulus_length, &public_exponent, rng),
+        GenerateKeyOptions::Ec { named_curve } => generate_key_ec(named_curve, rng),
+        GenerateKeyOptions::Aes { length } => generate_key_aes(length, rng),
+        GenerateKeyOptions::Hmac { hash, length } => generate_key_hmac(hash, length, rng),
+    }
+}
+
+fn generate_key_rsa(
+    modulus_length: u32,
+    public_exponent: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>, AnyError> {
+    let exponent = BigUint::from_bytes_be(public_exponent);
+    if exponent != BigUint::from(3u32) && exponent != BigUint::from(65537u32) {
+        return Err(operation_error("Bad public exponent"));
+    }
+    if modulus_length > MAX_RSA_MODULUS_LENGTH {
+        return Err(operation_error(format!(
+            "modulusLength must be at most {MAX_RSA_MODULUS_LENGTH}"
+        )));
+    }
+    let private_key = RsaPrivateKey::new_with_exp(rng, modulus_length as usize, &exponent)
+        .map_err(|_| operation_error("Failed to generate RSA key"))?;
+    let private_key = private_key
+        .to_pkcs1_der()
+        .map_err(|_| operation_error("Failed to serialize RSA key"))?;
+    Ok(private_key.as_bytes().to_vec())
+}
+
+fn generate_key_ec(
+    named_curve: EcNamedCurve,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>, AnyError> {
+    let private_key = match named_curve {
+        EcNamedCurve::P256 => p256::SecretKey::random(rng).to_pkcs8_der(),
+        EcNamedCurve::P384 => p384::SecretKey::random(rng).to_pkcs8_der(),
+        EcNamedCurve::P521 => return Err(operation_error("Unsupported named curve")),
+    }
+    .map_err(|_| operation_error("Failed to serialize EC key"))?;
+    Ok(private_key.as_bytes().to_vec())
+}
+
+fn generate_key_aes(
+    length: usize,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>, AnyError> {
+    if length % 8 != 0 || length > 256 {
+        return Err(operation_error("Invalid AES key length"));
+    }
+    let mut key = vec![0u8; length / 8];
+    rng.fill_bytes(&mut key);
+    Ok(key)
+}
+
+fn generate_key_hmac(
+    hash: CryptoHash,
+    length: Option<usize>,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>, AnyError> {
+    let algorithm: &ring::digest::Algorithm = hash.into();
+    let length = match length {
+        Some(length) => {
+            if length % 8 != 0 {
+                return Err(operation_error("hmac block length must be byte aligned"));
+            }
+            let length = length / 8;
+            if length > ring::digest::MAX_BLOCK_LEN {
+                return Err(operation_error("hmac block length is too large"));
+            }
+            length
+        },
+        None => algorithm.block_len(),
+    };
+    let mut key = vec![0u8; length];
+    rng.fill_bytes(&mut key);
+    Ok(key)
+}
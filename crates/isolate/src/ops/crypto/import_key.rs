@@ -2,12 +2,17 @@
 // https://github.com/denoland/deno/blob/main/ext/crypto/import_key.rs
 
 use deno_core::ToJsBuffer;
-use elliptic_curve::pkcs8::{
-    der::Decode as _,
-    PrivateKeyInfo,
+use elliptic_curve::{
+    pkcs8::{
+        der::Decode as _,
+        PrivateKeyInfo,
+    },
+    sec1::ToEncodedPoint,
+};
+use p256::pkcs8::{
+    DecodePrivateKey,
+    EncodePrivateKey,
 };
-use p256::pkcs8::EncodePrivateKey;
-use ring::signature::EcdsaKeyPair;
 use rsa::{
     pkcs1::UintRef,
     pkcs8::der::Decode as RsaDecode,
@@ -35,10 +40,8 @@ use super::{
         ID_SECP521R1_OID,
         RSA_ENCRYPTION_OID,
     },
-    CryptoNamedCurve,
     CryptoOps,
 };
-use crate::ops::crypto::shared::secure_rng_unavailable;
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -517,37 +520,33 @@ fn import_key_ec_jwk(
             })
         },
         KeyData::JwkPrivateEc { d, x, y } => {
-            jwt_b64_int_or_err!(private_d, &d, "invalid JWK private key");
+            jwt_b64_int_or_err!(_private_d, &d, "invalid JWK private key");
             let point_bytes = import_key_ec_jwk_to_point(x, y, named_curve)?;
-            let pkcs8_der = match named_curve {
+            // The public key must match the private key.
+            let (pkcs8_der, expected_point) = match named_curve {
                 EcNamedCurve::P256 => {
                     let d = decode_b64url_to_field_bytes::<p256::NistP256>(&d)?;
                     let pk = p256::SecretKey::from_be_bytes(&d[..])?;
 
-                    pk.to_pkcs8_der().map_err(|e| anyhow::anyhow!(e))?
+                    (
+                        pk.to_pkcs8_der().map_err(|e| anyhow::anyhow!(e))?,
+                        pk.public_key().to_encoded_point(false).to_bytes(),
+                    )
                 },
                 EcNamedCurve::P384 => {
                     let d = decode_b64url_to_field_bytes::<p384::NistP384>(&d)?;
                     let pk = p384::SecretKey::from_be_bytes(&d[..])?;
 
-                    pk.to_pkcs8_der().map_err(|e| anyhow::anyhow!(e))?
+                    (
+                        pk.to_pkcs8_der().map_err(|e| anyhow::anyhow!(e))?,
+                        pk.public_key().to_encoded_point(false).to_bytes(),
+                    )
                 },
                 EcNamedCurve::P521 => return Err(data_error("Unsupported named curve")),
             };
-
-            // Import using ring, to validate key
-            let key_alg = match named_curve {
-                EcNamedCurve::P256 => CryptoNamedCurve::P256.into(),
-                EcNamedCurve::P384 => CryptoNamedCurve::P256.into(),
-                EcNamedCurve::P521 => return Err(data_error("Unsupported named curve")),
-            };
-
-            let _key_pair = EcdsaKeyPair::from_private_key_and_public_key(
-                key_alg,
-                private_d.as_bytes(),
-                point_bytes.as_ref(),
-                secure_rng_unavailable()?,
-            );
+            if expected_point[..] != point_bytes[..] {
+                return Err(data_error("invalid JWK private key"));
+            }
 
             Ok(ImportKeyResult::Ec {
                 raw_data: RustRawKeyData::Private(pkcs8_der.as_bytes().to_vec().into()),
@@ -633,17 +632,19 @@ fn import_key_ec(
 
             // 10.
             if let Some(pk_named_curve) = pk_named_curve {
-                let signing_alg = match pk_named_curve {
-                    EcNamedCurve::P256 => CryptoNamedCurve::P256.into(),
-                    EcNamedCurve::P384 => CryptoNamedCurve::P384.into(),
+                // Deserialize the PKCS#8 key to validate it.
+                match pk_named_curve {
+                    EcNamedCurve::P256 => {
+                        p256::SecretKey::from_pkcs8_der(&data)
+                            .map_err(|_| data_error("invalid P-256 private key"))?;
+                    },
+                    EcNamedCurve::P384 => {
+                        p384::SecretKey::from_pkcs8_der(&data)
+                            .map_err(|_| data_error("invalid P-384 private key"))?;
+                    },
                     EcNamedCurve::P521 => return Err(data_error("Unsupported named curve")),
                 };
 
-                // deserialize pkcs8 using ring crate, to VALIDATE public key
-                let _private_key =
-                    EcdsaKeyPair::from_pkcs8(signing_alg, &data, secure_rng_unavailable()?)
-                        .map_err(|e| anyhow::anyhow!(e))?;
-
                 // 11.
                 if named_curve != pk_named_curve {
                     return Err(data_error("curve mismatch"));
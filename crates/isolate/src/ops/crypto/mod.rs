@@ -1,20 +1,31 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 // https://github.com/denoland/deno/blob/main/ext/crypto/key.rs
 
+mod decrypt;
 mod ed25519;
+mod encrypt;
 mod export_key;
+mod generate_key;
 mod import_key;
 mod shared;
 mod x25519;
 
 use std::num::NonZeroU32;
 
+use aes_kw::{
+    KekAes128,
+    KekAes192,
+    KekAes256,
+};
 use anyhow::Context;
 use deno_core::ToJsBuffer;
+use elliptic_curve::sec1::FromEncodedPoint;
+use p256::pkcs8::DecodePrivateKey;
 use rand::Rng;
 use ring::{
     agreement::Algorithm as RingAlgorithm,
     digest,
+    hkdf,
     hmac::{
         Algorithm as HmacAlgorithm,
         Key as HmacKey,
@@ -55,16 +66,20 @@ use sha2::{
 use uuid::Uuid;
 
 use self::{
+    decrypt::DecryptOptions,
+    encrypt::EncryptOptions,
     export_key::{
         ExportKeyOptions,
         ExportKeyResult,
     },
+    generate_key::GenerateKeyOptions,
     import_key::{
         ImportKeyOptions,
         ImportKeyResult,
     },
     shared::{
         not_supported,
+        operation_error,
         secure_rng_unavailable,
         type_error,
         AnyError,
@@ -150,6 +165,52 @@ pub fn op_crypto_derive_bits<'b, P: OpProvider<'b>>(
     CryptoOps::derive_bits(arg, salt.map(|b| b.into_vec()))
 }
 
+#[convex_macro::v8_op]
+pub fn op_crypto_encrypt<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    opts: EncryptOptions,
+    data: ByteBuf,
+) -> anyhow::Result<ToJsBuffer> {
+    let rng = provider.rng()?;
+    Ok(encrypt::encrypt(opts, &data, rng)?.into())
+}
+
+#[convex_macro::v8_op]
+pub fn op_crypto_decrypt<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    opts: DecryptOptions,
+    data: ByteBuf,
+) -> anyhow::Result<ToJsBuffer> {
+    Ok(decrypt::decrypt(opts, &data)?.into())
+}
+
+#[convex_macro::v8_op]
+pub fn op_crypto_generate_key<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    opts: GenerateKeyOptions,
+) -> anyhow::Result<ToJsBuffer> {
+    let rng = provider.rng()?;
+    Ok(generate_key::generate_key(opts, rng)?.into())
+}
+
+#[convex_macro::v8_op]
+pub fn op_crypto_wrap_key<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    args: WrapUnwrapKeyArg,
+    data: ByteBuf,
+) -> anyhow::Result<ToJsBuffer> {
+    CryptoOps::wrap_key(args, &data)
+}
+
+#[convex_macro::v8_op]
+pub fn op_crypto_unwrap_key<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    args: WrapUnwrapKeyArg,
+    data: ByteBuf,
+) -> anyhow::Result<ToJsBuffer> {
+    CryptoOps::unwrap_key(args, &data)
+}
+
 #[convex_macro::v8_op]
 pub fn op_crypto_digest<'b, P: OpProvider<'b>>(
     provider: &mut P,
@@ -377,13 +438,22 @@ pub struct DeriveKeyArg {
     key: KeyData,
     algorithm: Algorithm,
     hash: Option<CryptoHash>,
+    // ECDH derives the whole shared secret, and JS truncates it.
+    #[serde(default)]
     length: usize,
     iterations: Option<u32>,
     // ECDH
-    // public_key: Option<KeyData>,
-    // named_curve: Option<CryptoNamedCurve>,
+    public_key: Option<KeyData>,
+    named_curve: Option<CryptoNamedCurve>,
     // HKDF
-    // info: Option<ByteBuf>,
+    info: Option<ByteBuf>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WrapUnwrapKeyArg {
+    key: V8RawKeyData,
+    algorithm: Algorithm,
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy)]
@@ -663,11 +733,106 @@ impl CryptoOps {
                 pbkdf2::derive(algorithm, iterations, &salt, &secret, &mut out);
                 Ok(out.into())
             },
-            Algorithm::Ecdh | Algorithm::Hkdf => anyhow::bail!("Signing algorithm not implemented"),
+            Algorithm::Ecdh => {
+                let named_curve = args
+                    .named_curve
+                    .ok_or_else(|| type_error("Missing argument namedCurve"))?;
+                let public_key = args
+                    .public_key
+                    .ok_or_else(|| type_error("Missing argument publicKey"))?;
+                let shared_secret = match named_curve {
+                    CryptoNamedCurve::P256 => {
+                        let secret_key = p256::SecretKey::from_pkcs8_der(&args.key.data)
+                            .map_err(|_| type_error("Unexpected error decoding private key"))?;
+                        let public_key = read_ec_public_key_p256(public_key)?;
+                        p256::ecdh::diffie_hellman(
+                            secret_key.to_nonzero_scalar(),
+                            public_key.as_affine(),
+                        )
+                        .raw_secret_bytes()
+                        .to_vec()
+                    },
+                    CryptoNamedCurve::P384 => {
+                        let secret_key = p384::SecretKey::from_pkcs8_der(&args.key.data)
+                            .map_err(|_| type_error("Unexpected error decoding private key"))?;
+                        let public_key = read_ec_public_key_p384(public_key)?;
+                        p384::ecdh::diffie_hellman(
+                            secret_key.to_nonzero_scalar(),
+                            public_key.as_affine(),
+                        )
+                        .raw_secret_bytes()
+                        .to_vec()
+                    },
+                };
+                // The raw serialized x-coordinate of the computed point.
+                Ok(shared_secret.into())
+            },
+            Algorithm::Hkdf => {
+                let salt = salt.ok_or_else(not_supported)?;
+                let algorithm = match args.hash.ok_or_else(not_supported)? {
+                    CryptoHash::Sha1 => hkdf::HKDF_SHA1_FOR_LEGACY_USE_ONLY,
+                    CryptoHash::Sha256 => hkdf::HKDF_SHA256,
+                    CryptoHash::Sha384 => hkdf::HKDF_SHA384,
+                    CryptoHash::Sha512 => hkdf::HKDF_SHA512,
+                };
+                let info = args.info.ok_or_else(not_supported)?;
+                // The caller must validate this.
+                assert!(args.length % 8 == 0);
+                let length = args.length / 8;
+
+                let prk = hkdf::Salt::new(algorithm, &salt).extract(&args.key.data);
+                let info = &[&*info];
+                let okm = prk
+                    .expand(info, HkdfOutput(length))
+                    .map_err(|_| operation_error("The length provided for HKDF is too large"))?;
+                let mut out = vec![0u8; length];
+                okm.fill(&mut out)?;
+                Ok(out.into())
+            },
             _ => Err(anyhow::anyhow!("Unsupported algorithm".to_string())),
         }
     }
 
+    pub fn wrap_key(args: WrapUnwrapKeyArg, data: &[u8]) -> anyhow::Result<ToJsBuffer> {
+        match args.algorithm {
+            Algorithm::AesKw => {
+                let key = args.key.as_secret_key()?;
+                if data.len() % 8 != 0 {
+                    return Err(type_error("Data must be multiple of 8 bytes"));
+                }
+                let wrapped_key = match key.len() {
+                    16 => KekAes128::new(key.into()).wrap_vec(data),
+                    24 => KekAes192::new(key.into()).wrap_vec(data),
+                    32 => KekAes256::new(key.into()).wrap_vec(data),
+                    _ => return Err(type_error("Invalid key length")),
+                }
+                .map_err(|_| operation_error("encryption error"))?;
+                Ok(wrapped_key.into())
+            },
+            _ => Err(type_error("Unsupported algorithm")),
+        }
+    }
+
+    pub fn unwrap_key(args: WrapUnwrapKeyArg, data: &[u8]) -> anyhow::Result<ToJsBuffer> {
+        match args.algorithm {
+            Algorithm::AesKw => {
+                let key = args.key.as_secret_key()?;
+                if data.len() % 8 != 0 {
+                    return Err(type_error("Data must be multiple of 8 bytes"));
+                }
+                let unwrapped_key = match key.len() {
+                    16 => KekAes128::new(key.into()).unwrap_vec(data),
+                    24 => KekAes192::new(key.into()).unwrap_vec(data),
+                    32 => KekAes256::new(key.into()).unwrap_vec(data),
+                    _ => return Err(type_error("Invalid key length")),
+                }
+                .map_err(|_| operation_error("decryption error - integrity check failed"))?;
+                Ok(unwrapped_key.into())
+            },
+            _ => Err(type_error("Unsupported algorithm")),
+        }
+    }
+
     pub fn subtle_digest(algorithm: CryptoHash, data: Vec<u8>) -> anyhow::Result<ToJsBuffer> {
         // TODO: Maybe this should be using `spawn_blocking`?
         let output = digest::digest(algorithm.into(), &data)
@@ -679,6 +844,46 @@ impl CryptoOps {
     }
 }
 
+struct HkdfOutput<T>(T);
+
+impl hkdf::KeyType for HkdfOutput<usize> {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+// A generated key pair shares the PKCS#8 private key between both halves, so
+// the public key may be either that or a SEC1-encoded point.
+fn read_ec_public_key_p256(key_data: KeyData) -> anyhow::Result<p256::PublicKey> {
+    match key_data.r#type {
+        KeyType::Private => Ok(p256::SecretKey::from_pkcs8_der(&key_data.data)
+            .map_err(|_| type_error("Unexpected error decoding private key"))?
+            .public_key()),
+        KeyType::Public => {
+            let point = p256::EncodedPoint::from_bytes(&*key_data.data)
+                .map_err(|_| type_error("Unexpected error decoding public key"))?;
+            Option::from(p256::PublicKey::from_encoded_point(&point))
+                .ok_or_else(|| type_error("Unexpected error decoding public key"))
+        },
+        KeyType::Secret => Err(type_error("Unexpected secret key")),
+    }
+}
+
+fn read_ec_public_key_p384(key_data: KeyData) -> anyhow::Result<p384::PublicKey> {
+    match key_data.r#type {
+        KeyType::Private => Ok(p384::SecretKey::from_pkcs8_der(&key_data.data)
+            .map_err(|_| type_error("Unexpected error decoding private key"))?
+            .public_key()),
+        KeyType::Public => {
+            let point = p384::EncodedPoint::from_bytes(&*key_data.data)
+                .map_err(|_| type_error("Unexpected error decoding public key"))?;
+            Option::from(p384::PublicKey::from_encoded_point(&point))
+                .ok_or_else(|| type_error("Unexpected error decoding public key"))
+        },
+        KeyType::Secret => Err(type_error("Unexpected secret key")),
+    }
+}
+
 fn read_rsa_public_key(key_data: KeyData) -> Result<RsaPublicKey, AnyError> {
     let public_key = match key_data.r#type {
         KeyType::Private => RsaPrivateKey::from_pkcs1_der(&key_data.data)?.to_public_key(),
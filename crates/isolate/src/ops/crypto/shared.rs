@@ -158,6 +158,10 @@ pub fn data_error(msg: impl Into<Cow<'static, str>>) -> AnyError {
     custom_error("DOMExceptionDataError", msg)
 }
 
+pub fn operation_error(msg: impl Into<Cow<'static, str>>) -> AnyError {
+    custom_error("DOMExceptionOperationError", msg)
+}
+
 pub fn not_supported_error(msg: impl Into<Cow<'static, str>>) -> AnyError {
     custom_error("DOMExceptionNotSupportedError", msg)
 }
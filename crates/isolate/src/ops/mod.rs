@@ -4,6 +4,7 @@
 //! functionality, causing a runtime error.
 
 mod blob;
+mod compression;
 mod console;
 mod crypto;
 mod database;
@@ -16,6 +17,7 @@ mod stream;
 mod text;
 mod time;
 mod validate_args;
+mod websocket;
 
 use std::{
     collections::BTreeMap,
@@ -56,6 +58,11 @@ use self::{
         op_blob_read_part,
         op_blob_slice_part,
     },
+    compression::{
+        op_compression_finish,
+        op_compression_new,
+        op_compression_write,
+    },
     console::{
         op_console_message,
         op_console_time_end,
@@ -66,13 +73,16 @@ use self::{
     crypto::{
         op_crypto_base64_url_decode,
         op_crypto_base64_url_encode,
+        op_crypto_decrypt,
         op_crypto_derive_bits,
         op_crypto_digest,
+        op_crypto_encrypt,
         op_crypto_export_key,
         op_crypto_export_pkcs8_ed25519,
         op_crypto_export_pkcs8_x25519,
         op_crypto_export_spki_ed25519,
         op_crypto_export_spki_x25519,
+        op_crypto_generate_key,
         op_crypto_get_random_values,
         op_crypto_import_key,
         op_crypto_import_pkcs8_ed25519,
@@ -83,8 +93,10 @@ use self::{
         op_crypto_random_uuid,
         op_crypto_sign,
         op_crypto_sign_ed25519,
+        op_crypto_unwrap_key,
         op_crypto_verify,
         op_crypto_verify_ed25519,
+        op_crypto_wrap_key,
     },
     database::op_get_table_mapping_without_system_tables,
     environment_variables::op_environment_variables_get,
@@ -127,8 +139,16 @@ use self::{
         op_now,
     },
     validate_args::op_validate_args,
+    websocket::{
+        op_web_socket_decode_message,
+        op_web_socket_encode_message,
+    },
 };
 pub use self::{
+    compression::{
+        check_open_compression_streams,
+        CompressionResource,
+    },
     crypto::CryptoOps,
     random::op_random,
 };
@@ -187,6 +207,10 @@ pub trait OpProvider<'b> {
     fn get_text_decoder(&mut self, uuid: &Uuid) -> anyhow::Result<&mut TextDecoderResource>;
     fn remove_text_decoder(&mut self, uuid: &Uuid) -> anyhow::Result<TextDecoderResource>;
 
+    fn create_compression_stream(&mut self, resource: CompressionResource) -> anyhow::Result<Uuid>;
+    fn get_compression_stream(&mut self, uuid: &Uuid) -> anyhow::Result<&mut CompressionResource>;
+    fn remove_compression_stream(&mut self, uuid: &Uuid) -> anyhow::Result<CompressionResource>;
+
     fn get_environment_variable(&mut self, name: EnvVarName)
         -> anyhow::Result<Option<EnvVarValue>>;
 
@@ -319,6 +343,18 @@ impl<'a, 'b: 'a, RT: Runtime, E: IsolateEnvironment<RT>> OpProvider<'b>
         self.state_mut()?.remove_text_decoder(uuid)
     }
 
+    fn create_compression_stream(&mut self, resource: CompressionResource) -> anyhow::Result<Uuid> {
+        self.state_mut()?.create_compression_stream(resource)
+    }
+
+    fn get_compression_stream(&mut self, uuid: &Uuid) -> anyhow::Result<&mut CompressionResource> {
+        self.state_mut()?.get_compression_stream(uuid)
+    }
+
+    fn remove_compression_stream(&mut self, uuid: &Uuid) -> anyhow::Result<CompressionResource> {
+        self.state_mut()?.remove_compression_stream(uuid)
+    }
+
     fn get_environment_variable(
         &mut self,
         name: EnvVarName,
@@ -383,6 +419,9 @@ pub fn run_op<'b, P: OpProvider<'b>>(
         "textEncoder/newDecoder" => op_text_encoder_new_decoder(provider, args, rv)?,
         "textEncoder/cleanup" => op_text_encoder_cleanup(provider, args, rv)?,
         "textEncoder/normalizeLabel" => op_text_encoder_normalize_label(provider, args, rv)?,
+        "compression/new" => op_compression_new(provider, args, rv)?,
+        "compression/write" => op_compression_write(provider, args, rv)?,
+        "compression/finish" => op_compression_finish(provider, args, rv)?,
         "atob" => op_atob(provider, args, rv)?,
         "btoa" => op_btoa(provider, args, rv)?,
         "environmentVariables/get" => op_environment_variables_get(provider, args, rv)?,
@@ -390,6 +429,8 @@ pub fn run_op<'b, P: OpProvider<'b>>(
             op_get_table_mapping_without_system_tables(provider, args, rv)?
         },
         "validateArgs" => op_validate_args(provider, args, rv)?,
+        "webSocket/encodeMessage" => op_web_socket_encode_message(provider, args, rv)?,
+        "webSocket/decodeMessage" => op_web_socket_decode_message(provider, args, rv)?,
 
         "crypto/randomUUID" => op_crypto_random_uuid(provider, args, rv)?,
         "crypto/getRandomValues" => op_crypto_get_random_values(provider, args, rv)?,
@@ -399,6 +440,11 @@ pub fn run_op<'b, P: OpProvider<'b>>(
         "crypto/verifyEd25519" => op_crypto_verify_ed25519(provider, args, rv)?,
         "crypto/deriveBits" => op_crypto_derive_bits(provider, args, rv)?,
         "crypto/digest" => op_crypto_digest(provider, args, rv)?,
+        "crypto/encrypt" => op_crypto_encrypt(provider, args, rv)?,
+        "crypto/decrypt" => op_crypto_decrypt(provider, args, rv)?,
+        "crypto/generateKey" => op_crypto_generate_key(provider, args, rv)?,
+        "crypto/wrapKey" => op_crypto_wrap_key(provider, args, rv)?,
+        "crypto/unwrapKey" => op_crypto_unwrap_key(provider, args, rv)?,
         "crypto/importKey" => op_crypto_import_key(provider, args, rv)?,
         "crypto/importSpkiEd25519" => op_crypto_import_spki_ed25519(provider, args, rv)?,
         "crypto/importPkcs8Ed25519" => op_crypto_import_pkcs8_ed25519(provider, args, rv)?,
@@ -0,0 +1,54 @@
+use bytes::Bytes;
+use deno_core::ToJsBuffer;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_bytes::ByteBuf;
+
+use super::OpProvider;
+use crate::http_action::WebSocketMessage;
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum OutgoingMessageV8 {
+    Text { data: String },
+    Binary { data: ByteBuf },
+    Close { code: Option<u16>, reason: String },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum IncomingMessageV8 {
+    Text { data: String },
+    Binary { data: ToJsBuffer },
+    Close { code: Option<u16>, reason: String },
+}
+
+#[convex_macro::v8_op]
+pub fn op_web_socket_encode_message<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    message: OutgoingMessageV8,
+) -> anyhow::Result<ToJsBuffer> {
+    let message = match message {
+        OutgoingMessageV8::Text { data } => WebSocketMessage::Text(data),
+        OutgoingMessageV8::Binary { data } => WebSocketMessage::Binary(data.into_vec().into()),
+        OutgoingMessageV8::Close { code, reason } => WebSocketMessage::Close { code, reason },
+    };
+    Ok(message.to_frame().to_vec().into())
+}
+
+#[convex_macro::v8_op]
+pub fn op_web_socket_decode_message<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    frame: ByteBuf,
+) -> anyhow::Result<IncomingMessageV8> {
+    let message = match WebSocketMessage::from_frame(Bytes::from(frame.into_vec()))? {
+        WebSocketMessage::Text(data) => IncomingMessageV8::Text { data },
+        WebSocketMessage::Binary(data) => IncomingMessageV8::Binary {
+            data: data.to_vec().into(),
+        },
+        WebSocketMessage::Close { code, reason } => IncomingMessageV8::Close { code, reason },
+    };
+    Ok(message)
+}
@@ -54,8 +54,10 @@ use crate::{
     },
     module_map::ModuleMap,
     ops::{
+        check_open_compression_streams,
         run_op,
         start_async_op,
+        CompressionResource,
         CryptoOps,
     },
     strings,
@@ -99,6 +101,9 @@ pub struct RequestState<RT: Runtime, E: IsolateEnvironment<RT>> {
     // This is not wrapped in `WithHeapSize` so we can return `&mut TextDecoderStream`.
     // Additionally, `TextDecoderResource` should have a fairly small heap size.
     pub text_decoders: BTreeMap<uuid::Uuid, TextDecoderResource>,
+    // Like `text_decoders`, this isn't wrapped in `WithHeapSize`. Each
+    // resource's output buffer is drained on every write.
+    pub compression_streams: BTreeMap<uuid::Uuid, CompressionResource>,
 }
 
 pub struct TextDecoderResource {
@@ -180,6 +185,35 @@ impl<RT: Runtime, E: IsolateEnvironment<RT>> RequestState<RT, E> {
         Ok(decoder)
     }
 
+    pub fn create_compression_stream(
+        &mut self,
+        resource: CompressionResource,
+    ) -> anyhow::Result<uuid::Uuid> {
+        check_open_compression_streams(self.compression_streams.len())?;
+        let rng = self.environment.rng()?;
+        let uuid = CryptoOps::random_uuid(rng)?;
+        self.compression_streams.insert(uuid, resource);
+        Ok(uuid)
+    }
+
+    pub fn get_compression_stream(
+        &mut self,
+        id: &uuid::Uuid,
+    ) -> anyhow::Result<&mut CompressionResource> {
+        self.compression_streams
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Compression stream resource not found"))
+    }
+
+    pub fn remove_compression_stream(
+        &mut self,
+        id: &uuid::Uuid,
+    ) -> anyhow::Result<CompressionResource> {
+        self.compression_streams
+            .remove(id)
+            .ok_or_else(|| anyhow::anyhow!("Compression stream resource not found"))
+    }
+
     #[allow(unused)]
     pub fn read_part(&self, id: uuid::Uuid) -> anyhow::Result<bytes::Bytes> {
         self.blob_parts
@@ -23,7 +23,10 @@ use common::{
     },
     errors::JsError,
     execution_context::ExecutionContext,
-    http::fetch::ProxiedFetchClient,
+    http::fetch::{
+        FetchClient,
+        ProxiedFetchClient,
+    },
     log_lines::{
         LogLine,
         LogLines,
@@ -252,6 +255,7 @@ pub struct UdfTest<RT: Runtime, P: Persistence + Clone> {
     pub module_loader: Arc<dyn ModuleLoader<RT>>,
     search_storage: Arc<dyn Storage>,
     file_storage: TransactionalFileStorage<RT>,
+    fetch_client: Arc<dyn FetchClient>,
 
     isolate_v2_enabled: bool,
 }
@@ -368,6 +372,7 @@ impl<RT: Runtime, P: Persistence + Clone> UdfTest<RT, P> {
             search_storage,
             module_loader,
             file_storage,
+            fetch_client: Arc::new(ProxiedFetchClient::new(None, DEV_INSTANCE_NAME.to_owned())),
             isolate_v2_enabled: false,
         }))
     }
@@ -394,6 +399,13 @@ impl<RT: Runtime, P: Persistence + Clone> UdfTest<RT, P> {
         self.isolate_v2_enabled = true;
     }
 
+    /// Use `fetch_client` instead of the network for `fetch` in actions and
+    /// HTTP actions, e.g. a
+    /// [`CassetteFetchClient`](common::http::fetch::cassette::CassetteFetchClient).
+    pub fn set_fetch_client(&mut self, fetch_client: Arc<dyn FetchClient>) {
+        self.fetch_client = fetch_client;
+    }
+
     pub async fn create_index(&self, name: &str, field: &str) -> anyhow::Result<()> {
         let mut tx = self.database.begin(Identity::system()).await?;
         let index_name = name.parse()?;
@@ -904,6 +916,9 @@ impl<RT: Runtime, P: Persistence + Clone> UdfTest<RT, P> {
             match part {
                 HttpActionResponsePart::BodyChunk(bytes) => body.extend(bytes),
                 HttpActionResponsePart::Head(head) => response_head = Some(head),
+                HttpActionResponsePart::WebSocketMessage(_) => {
+                    anyhow::bail!("Use raw_http_action for HTTP actions accepting WebSockets")
+                },
             }
         }
         let response = match outcome.result {
@@ -931,7 +946,7 @@ impl<RT: Runtime, P: Persistence + Clone> UdfTest<RT, P> {
         let mut tx = self.database.begin(identity.clone()).await?;
         let path: UdfPath = udf_path.parse()?;
 
-        let fetch_client = Arc::new(ProxiedFetchClient::new(None, DEV_INSTANCE_NAME.to_owned()));
+        let fetch_client = self.fetch_client.clone();
         let (log_line_sender, log_line_receiver) = mpsc::unbounded();
         let outcome = self
             .isolate
@@ -1064,7 +1079,7 @@ impl<RT: Runtime, P: Persistence + Clone> UdfTest<RT, P> {
             },
             Ok(path_and_args) => path_and_args,
         };
-        let fetch_client = Arc::new(ProxiedFetchClient::new(None, DEV_INSTANCE_NAME.to_owned()));
+        let fetch_client = self.fetch_client.clone();
         let (log_line_sender, log_line_receiver) = mpsc::unbounded();
 
         // TODO(presley): Make this also be able to use local executor.
@@ -150,3 +150,40 @@ async fn test_record(rt: TestRuntime) -> anyhow::Result<()> {
     })
     .await
 }
+
+#[convex_macro::test_runtime]
+async fn test_arg_defaults_and_constraints(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        // Missing args are set to their default before the function runs.
+        let result = t
+            .query(
+                "args_validation:constrainedArgs",
+                assert_obj!("count" => 1.),
+            )
+            .await?;
+        assert_eq!(
+            result,
+            ConvexValue::Object(assert_obj!("name" => "anonymous", "count" => 1.))
+        );
+        let result = t
+            .query(
+                "args_validation:constrainedArgs",
+                assert_obj!("name" => "ada", "count" => 1.),
+            )
+            .await?;
+        assert_eq!(
+            result,
+            ConvexValue::Object(assert_obj!("name" => "ada", "count" => 1.))
+        );
+
+        let e = t
+            .query_js_error(
+                "args_validation:constrainedArgs",
+                assert_obj!("count" => 11.),
+            )
+            .await?;
+        assert_contains(&e, "ArgumentValidationError");
+        Ok(())
+    })
+    .await
+}
@@ -47,9 +47,11 @@ use crate::{
     },
     tests::assert_contains,
     HttpActionRequestHead,
+    HttpActionResponsePart,
     HttpActionResponseStreamer,
     HttpActionResult,
     IsolateConfig,
+    WebSocketMessage,
 };
 
 pub fn http_request(path: &str) -> HttpActionRequest {
@@ -60,6 +62,7 @@ pub fn http_request(path: &str) -> HttpActionRequest {
             method: Method::GET,
         },
         body: None,
+        web_socket: None,
     }
 }
 
@@ -71,6 +74,7 @@ pub fn http_post_request(path: &str, body: Vec<u8>) -> HttpActionRequest {
             method: Method::POST,
         },
         body: Some(stream::once(async move { Ok(body.into()) }).boxed()),
+        web_socket: None,
     }
 }
 
@@ -225,6 +229,60 @@ async fn test_http_scheduler(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_http_web_socket(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = http_action_udf_test(rt).await?;
+
+    let mut request = http_request("webSocket");
+    request.web_socket = Some(
+        stream::iter([
+            Ok(WebSocketMessage::Text("hello".to_string())),
+            Ok(WebSocketMessage::Binary(vec![1, 2, 3].into())),
+            Ok(WebSocketMessage::Text("bye".to_string())),
+        ])
+        .boxed(),
+    );
+    let (http_response_sender, http_response_receiver) = mpsc::unbounded();
+    let (outcome, _log_lines) = t
+        .raw_http_action(
+            "http_action",
+            request,
+            Identity::system(),
+            HttpActionResponseStreamer::new(http_response_sender),
+        )
+        .await?;
+    assert_matches!(outcome.result, HttpActionResult::Streamed);
+
+    let parts: Vec<HttpActionResponsePart> = http_response_receiver.collect().await;
+    must_let!(let HttpActionResponsePart::Head(head) = &parts[0]);
+    assert_eq!(head.status, StatusCode::SWITCHING_PROTOCOLS);
+    let messages: Vec<WebSocketMessage> = parts[1..]
+        .iter()
+        .map(|part| {
+            must_let!(let HttpActionResponsePart::WebSocketMessage(message) = part);
+            message.clone()
+        })
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            WebSocketMessage::Text("Echo: hello".to_string()),
+            WebSocketMessage::Binary(vec![3, 2, 1].into()),
+            WebSocketMessage::Close {
+                code: Some(1000),
+                reason: "Goodbye".to_string(),
+            },
+        ]
+    );
+
+    // Requests that didn't ask to upgrade can't accept a WebSocket.
+    let err = t
+        .http_action_js_error("http_action", http_request("webSocket"), Identity::system())
+        .await?;
+    assert_contains(&err, "didn't ask to upgrade to a WebSocket");
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_http_error_in_run(rt: TestRuntime) -> anyhow::Result<()> {
     let t = http_action_udf_test(rt).await?;
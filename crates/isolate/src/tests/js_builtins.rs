@@ -69,10 +69,13 @@ async fn test_crypto(rt: TestRuntime) -> anyhow::Result<()> {
         must_let!(let ConvexValue::String(r) = t.query("js_builtins/crypto:test", assert_obj!()).await?);
         assert_eq!(String::from(r), "success".to_string());
 
+        must_let!(let ConvexValue::String(r) = t.query("js_builtins/crypto:rsa", assert_obj!()).await?);
+        assert_eq!(String::from(r), "success".to_string());
+
         assert_contains(
             &t.query_js_error("js_builtins/crypto:methodNotImplemented", assert_obj!())
                 .await?,
-            "Not implemented: generateKey for SubtleCrypto",
+            "Not implemented: deriveBits with algorithm X25519 for SubtleCrypto",
         );
         Ok(())
     }).await
@@ -147,6 +150,15 @@ async fn test_response(rt: TestRuntime) -> anyhow::Result<()> {
     }).await
 }
 
+#[convex_macro::test_runtime]
+async fn test_compression(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        must_let!(let ConvexValue::String(r) = t.query("js_builtins/compression", assert_obj!()).await?);
+        assert_eq!(String::from(r), "success".to_string());
+        Ok(())
+    }).await
+}
+
 #[convex_macro::test_runtime]
 async fn test_text_encoder(rt: TestRuntime) -> anyhow::Result<()> {
     UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
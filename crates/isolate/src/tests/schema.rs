@@ -243,6 +243,8 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                 indexes: btreemap!(),
                 search_indexes: btreemap!(),
                 vector_indexes: btreemap!(),
+                aggregate_indexes: btreemap!(),
+                references: btreemap!(),
                 document_type: Some(DocumentSchema::Union(vec![
                   object_validator!(
                     "ref" => FieldValidator::required_field_type(Validator::Id("twoIndexTable".parse()?)),
@@ -271,14 +273,20 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                     by_email.clone() => IndexSchema {
                         index_descriptor: by_email,
                         fields: vec!["email".parse()?].try_into()?,
+                        unique: false,
+                        filter: None,
                     },
                     by_creation_deleted.clone() => IndexSchema {
                         index_descriptor: by_creation_deleted,
                         fields: vec!["creation".parse()?, "deleted".parse()?].try_into()?,
+                        unique: false,
+                        filter: None,
                     },
                 ),
                 search_indexes: btreemap!(),
                 vector_indexes: btreemap!(),
+                aggregate_indexes: btreemap!(),
+                references: btreemap!(),
                 document_type: None,
             },
             name3.clone() => TableDefinition {
@@ -292,6 +300,8 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                 )?
                },
                vector_indexes: btreemap!(),
+               aggregate_indexes: btreemap!(),
+               references: btreemap!(),
                document_type: None,
           }
         ),
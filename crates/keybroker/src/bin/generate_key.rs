@@ -1,12 +1,20 @@
-use std::env;
+use std::{
+    env,
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
 
 use common::types::MemberId;
 use keybroker::{
+    AdminKeyScope,
     InstanceSecret,
     KeyBroker,
 };
 
-const USAGE: &str = "USAGE: ./generate_key <instance_name> <instance_secret> [member_id]";
+const USAGE: &str = "USAGE: ./generate_key <instance_name> <instance_secret> [member_id] \
+                     [full|read-only|export-only|deploy-only] [ttl_secs]";
 
 fn main() -> anyhow::Result<()> {
     let instance_name = env::args().nth(1).ok_or_else(|| anyhow::anyhow!(USAGE))?;
@@ -16,10 +24,30 @@ fn main() -> anyhow::Result<()> {
         .unwrap_or_else(|| "0".to_owned())
         .parse::<u64>()
         .map_err(|_| anyhow::anyhow!(USAGE))?;
+    let scope = match env::args().nth(4).as_deref() {
+        None | Some("full") => AdminKeyScope::full(),
+        Some("read-only") => AdminKeyScope::read_only(),
+        Some("export-only") => AdminKeyScope::export_only(),
+        Some("deploy-only") => AdminKeyScope::deploy_only(),
+        Some(_) => anyhow::bail!(USAGE),
+    };
+    let expires_at = env::args()
+        .nth(5)
+        .map(|ttl_secs| {
+            let ttl_secs = ttl_secs
+                .parse::<u64>()
+                .map_err(|_| anyhow::anyhow!(USAGE))?;
+            anyhow::Ok(SystemTime::now() + Duration::from_secs(ttl_secs))
+        })
+        .transpose()?;
     let instance_secret = InstanceSecret::try_from(&instance_secret_s[..])?;
 
     let broker = KeyBroker::new(&instance_name[..], instance_secret)?;
-    let admin_key = broker.issue_admin_key(MemberId(member_id));
+    let admin_key = if scope.is_full() && expires_at.is_none() {
+        broker.issue_admin_key(MemberId(member_id))
+    } else {
+        broker.issue_scoped_admin_key(MemberId(member_id), scope, expires_at)
+    };
     println!("{}", admin_key);
     let system_key = broker.issue_system_key();
     println!("{}", system_key);
@@ -90,6 +90,10 @@ use crate::{
         log_actions_token_expired,
         log_store_file_auth_expired,
     },
+    scope::{
+        AdminKeyScope,
+        AdminPermission,
+    },
     secret::InstanceSecret,
 };
 
@@ -291,6 +295,82 @@ impl Identity {
         None
     }
 
+    /// Returns the scope of the admin key backing this identity, if it is an
+    /// admin or an admin acting as a user.
+    pub fn admin_scope(&self) -> Option<&AdminKeyScope> {
+        match self {
+            Identity::InstanceAdmin(identity) | Identity::ActingUser(identity, _) => {
+                Some(&identity.scope)
+            },
+            Identity::System(_) | Identity::User(_) | Identity::Unknown => None,
+        }
+    }
+
+    /// Returns the id of the admin key backing this identity, which is used to
+    /// revoke it.
+    pub fn admin_key_id(&self) -> Option<&str> {
+        match self {
+            Identity::InstanceAdmin(identity) | Identity::ActingUser(identity, _) => {
+                identity.key_id()
+            },
+            Identity::System(_) | Identity::User(_) | Identity::Unknown => None,
+        }
+    }
+
+    /// Fails if this identity is backed by an admin key that lacks
+    /// `permission`. Non-admin identities are not restricted by admin key
+    /// scopes.
+    pub fn check_admin_permission(&self, permission: AdminPermission) -> anyhow::Result<()> {
+        match self.admin_scope() {
+            Some(scope) => scope.check_permission(permission),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails if this identity is backed by an admin key that may not access
+    /// the component at `component_path`.
+    pub fn check_admin_component(&self, component_path: &str) -> anyhow::Result<()> {
+        match self.admin_scope() {
+            Some(scope) => scope.check_component(component_path),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails if this identity is backed by an admin key that may not access
+    /// `table_name`.
+    pub fn check_admin_table(&self, table_name: &str) -> anyhow::Result<()> {
+        match self.admin_scope() {
+            Some(scope) => scope.check_table(table_name),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails if this identity is backed by an admin key limited to some tables
+    /// or components, for operations that touch all of them.
+    pub fn check_admin_all_tables(&self) -> anyhow::Result<()> {
+        match self.admin_scope() {
+            Some(scope) => scope.check_all_tables(),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails if this identity is backed by an admin key with a restricted
+    /// scope, so scoped keys can't manage keys with more access than their
+    /// own.
+    pub fn check_admin_full_access(&self) -> anyhow::Result<()> {
+        match self.admin_scope() {
+            Some(scope) => scope.check_full(),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether this identity may see `table_name`, for listings that skip
+    /// tables instead of failing.
+    pub fn allows_admin_table(&self, table_name: &str) -> bool {
+        self.admin_scope()
+            .map_or(true, |scope| scope.allows_table(table_name))
+    }
+
     pub fn instance_admin_principal(&self) -> Option<AdminIdentityPrincipal> {
         if let Identity::InstanceAdmin(AdminIdentity { principal, .. }) = self {
             return Some(principal.clone());
@@ -481,6 +561,10 @@ pub struct AdminIdentity {
     instance_name: String,
     principal: AdminIdentityPrincipal,
     key: String,
+    // Identifies the admin key for revocation. Keys issued before key ids were
+    // introduced and access tokens don't have one.
+    key_id: Option<String>,
+    scope: AdminKeyScope,
 }
 
 impl From<AdminIdentity> for pb::convex_identity::AdminIdentity {
@@ -489,6 +573,8 @@ impl From<AdminIdentity> for pb::convex_identity::AdminIdentity {
             instance_name,
             principal,
             key,
+            key_id,
+            scope,
         }: AdminIdentity,
     ) -> Self {
         Self {
@@ -502,6 +588,8 @@ impl From<AdminIdentity> for pb::convex_identity::AdminIdentity {
                 ),
             },
             key: Some(key),
+            key_id,
+            scope: Some(scope.into()),
         }
     }
 }
@@ -521,10 +609,16 @@ impl AdminIdentity {
             None => anyhow::bail!("Missing principal"),
         };
         let key = msg.key.ok_or_else(|| anyhow::anyhow!("Missing key"))?;
+        let scope = match msg.scope {
+            Some(scope) => scope.try_into()?,
+            None => AdminKeyScope::full(),
+        };
         Ok(Self {
             instance_name,
             principal,
             key,
+            key_id: msg.key_id,
+            scope,
         })
     }
 
@@ -537,12 +631,22 @@ impl AdminIdentity {
             instance_name,
             principal,
             key: access_token,
+            key_id: None,
+            scope: AdminKeyScope::full(),
         }
     }
 
     pub fn principal(&self) -> &AdminIdentityPrincipal {
         &self.principal
     }
+
+    pub fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    pub fn scope(&self) -> &AdminKeyScope {
+        &self.scope
+    }
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -553,10 +657,18 @@ impl Arbitrary for AdminIdentity {
 
     fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
         use proptest::prelude::*;
-        any::<(AdminIdentityPrincipal, String)>().prop_map(|(principal, key)| AdminIdentity {
+        any::<(
+            AdminIdentityPrincipal,
+            String,
+            Option<String>,
+            AdminKeyScope,
+        )>()
+        .prop_map(|(principal, key, key_id, scope)| AdminIdentity {
             instance_name: "fake-instance-name".to_string(),
             principal,
             key,
+            key_id,
+            scope,
         })
     }
 }
@@ -568,6 +680,19 @@ impl AdminIdentity {
             instance_name,
             principal: AdminIdentityPrincipal::Member(member_id),
             key: "chocolate-charlies-cupcake".to_string(),
+            key_id: None,
+            scope: AdminKeyScope::full(),
+        }
+    }
+
+    pub fn new_scoped_for_test_only(
+        instance_name: String,
+        member_id: MemberId,
+        scope: AdminKeyScope,
+    ) -> AdminIdentity {
+        AdminIdentity {
+            scope,
+            ..Self::new_for_test_only(instance_name, member_id)
         }
     }
 
@@ -623,11 +748,23 @@ impl KeyBroker {
     }
 
     pub fn issue_admin_key(&self, member_id: MemberId) -> AdminKey {
-        AdminKey::new(self.issue_key(Some(member_id)))
+        AdminKey::new(self.issue_key(Some(member_id), None, None))
+    }
+
+    /// Issue an admin key restricted to `scope` that stops working after
+    /// `expires_at`, if provided. The key can be revoked by the id on the
+    /// identity returned from [`KeyBroker::check_admin_key`].
+    pub fn issue_scoped_admin_key(
+        &self,
+        member_id: MemberId,
+        scope: AdminKeyScope,
+        expires_at: Option<SystemTime>,
+    ) -> AdminKey {
+        AdminKey::new(self.issue_key(Some(member_id), Some(scope), expires_at))
     }
 
     pub fn issue_system_key(&self) -> SystemKey {
-        SystemKey::new(self.issue_key(None))
+        SystemKey::new(self.issue_key(None, None, None))
     }
 
     pub fn issue_store_file_authorization<RT: Runtime>(
@@ -652,20 +789,37 @@ impl KeyBroker {
     /// Private helper method to generate an admin key.
     /// If `member_id` is None, it generates a system key, otherwise
     /// an admin key for the given user.
-    fn issue_key(&self, member_id: Option<MemberId>) -> String {
+    fn issue_key(
+        &self,
+        member_id: Option<MemberId>,
+        scope: Option<AdminKeyScope>,
+        expires_at: Option<SystemTime>,
+    ) -> String {
         let now = SystemTime::now();
         let since_epoch = now
             .duration_since(SystemTime::UNIX_EPOCH)
             .expect("Failed to compute seconds since epoch?");
+        let expires_s = expires_at.map(|expires_at| {
+            expires_at
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .expect("Failed to compute seconds since epoch?")
+                .as_secs()
+        });
 
-        let identity = match member_id {
-            Some(member_id) => AdminIdentityProto::MemberId(member_id.0),
-            None => AdminIdentityProto::System(()),
+        let (identity, key_id) = match member_id {
+            Some(member_id) => (
+                AdminIdentityProto::MemberId(member_id.0),
+                Some(hex::encode(rand::random::<[u8; 16]>())),
+            ),
+            None => (AdminIdentityProto::System(()), None),
         };
         let proto = AdminKeyProto {
             instance_name: None,
             issued_s: since_epoch.as_secs(),
             identity: Some(identity),
+            key_id,
+            expires_s,
+            scope: scope.map(Into::into),
         };
         format_admin_key(
             &self.instance_name,
@@ -691,6 +845,9 @@ impl KeyBroker {
             instance_name: instance_name_from_encrypted_part,
             issued_s,
             identity,
+            key_id,
+            expires_s,
+            scope,
         } = self
             .encryptor
             .decode_proto(ADMIN_KEY_VERSION, encrypted_part)
@@ -706,12 +863,27 @@ impl KeyBroker {
         }
         anyhow::ensure!(issued_s != 0, "Proto missing issued_s");
         let identity = identity.context("Proto missing identity")?;
+        if let Some(expires_s) = expires_s {
+            let expiration = SystemTime::UNIX_EPOCH + Duration::from_secs(expires_s);
+            if SystemTime::now() >= expiration {
+                anyhow::bail!(ErrorMetadata::unauthenticated(
+                    "AdminKeyExpired",
+                    "The provided admin key has expired."
+                ));
+            }
+        }
+        let scope = match scope {
+            Some(scope) => scope.try_into()?,
+            None => AdminKeyScope::full(),
+        };
 
         Ok(match identity {
             AdminIdentityProto::MemberId(member_id) => Identity::InstanceAdmin(AdminIdentity {
                 instance_name: self.instance_name.clone(),
                 principal: AdminIdentityPrincipal::Member(MemberId(member_id)),
                 key: key.to_string(),
+                key_id,
+                scope,
             }),
             AdminIdentityProto::System(()) => Identity::system(),
         })
@@ -948,6 +1120,8 @@ mod tests {
     };
     use crate::{
         AdminIdentity,
+        AdminKeyScope,
+        AdminPermission,
         Identity,
     };
 
@@ -996,6 +1170,9 @@ mod tests {
             instance_name: Some(kb.instance_name.clone()),
             issued_s: since_epoch.as_secs(),
             identity: Some(identity),
+            key_id: None,
+            expires_s: None,
+            scope: None,
         };
         kb.encryptor.encode_proto(ADMIN_KEY_VERSION, proto)
     }
@@ -1004,7 +1181,53 @@ mod tests {
     fn test_old_admin_keys() -> anyhow::Result<()> {
         let kb = KeyBroker::dev();
         let key = AdminKey::new(old_issue_key(&kb, Some(MemberId(0))));
-        kb.check_admin_key(&key.to_string()).unwrap();
+        let admin = kb.check_admin_key(&key.to_string()).unwrap();
+        assert_eq!(admin.admin_scope(), Some(&AdminKeyScope::full()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scoped_admin_keys() -> anyhow::Result<()> {
+        let kb = KeyBroker::dev();
+        let scope = AdminKeyScope::read_only().restrict_tables(["messages".to_string()]);
+        let key = kb.issue_scoped_admin_key(MemberId(0), scope.clone(), None);
+        let admin = kb.check_admin_key(&key.to_string())?;
+        assert_eq!(admin.admin_scope(), Some(&scope));
+        admin.check_admin_permission(AdminPermission::ReadData)?;
+        assert!(admin
+            .check_admin_permission(AdminPermission::WriteData)
+            .is_err());
+
+        // Every admin key gets its own id.
+        let Identity::InstanceAdmin(admin) = admin else {
+            panic!("Expected an admin identity");
+        };
+        let Identity::InstanceAdmin(other) =
+            kb.check_admin_key(&kb.issue_admin_key(MemberId(0)).to_string())?
+        else {
+            panic!("Expected an admin identity");
+        };
+        assert!(admin.key_id().is_some());
+        assert_ne!(admin.key_id(), other.key_id());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_admin_keys() -> anyhow::Result<()> {
+        let kb = KeyBroker::dev();
+        let now = SystemTime::now();
+        let key = kb.issue_scoped_admin_key(
+            MemberId(0),
+            AdminKeyScope::full(),
+            Some(now + Duration::from_secs(3600)),
+        );
+        kb.check_admin_key(&key.to_string())?;
+        let key = kb.issue_scoped_admin_key(
+            MemberId(0),
+            AdminKeyScope::full(),
+            Some(now - Duration::from_secs(1)),
+        );
+        assert!(kb.check_admin_key(&key.to_string()).is_err());
         Ok(())
     }
 
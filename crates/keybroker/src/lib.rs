@@ -6,6 +6,7 @@
 mod broker;
 mod encryptor;
 mod metrics;
+mod scope;
 mod secret;
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
@@ -24,6 +25,10 @@ pub use self::{
         UserIdentity,
     },
     encryptor::Encryptor,
+    scope::{
+        AdminKeyScope,
+        AdminPermission,
+    },
     secret::{
         InstanceSecret,
         Secret,
@@ -0,0 +1,289 @@
+use std::{
+    collections::BTreeSet,
+    fmt,
+};
+
+use errors::ErrorMetadata;
+use pb::convex_keys::{
+    admin_key_scope::{
+        Names as NamesProto,
+        Permission as PermissionProto,
+    },
+    AdminKeyScope as AdminKeyScopeProto,
+};
+
+/// An operation an admin key may be allowed to perform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum AdminPermission {
+    /// Read documents, run queries and view deployment metadata.
+    ReadData,
+    /// Write documents and run mutations and actions.
+    WriteData,
+    /// Request and download snapshot exports.
+    Export,
+    /// Push code, schema, auth config and environment variables.
+    Deploy,
+}
+
+impl AdminPermission {
+    pub const ALL: [AdminPermission; 4] = [
+        AdminPermission::ReadData,
+        AdminPermission::WriteData,
+        AdminPermission::Export,
+        AdminPermission::Deploy,
+    ];
+}
+
+impl fmt::Display for AdminPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            AdminPermission::ReadData => "read data",
+            AdminPermission::WriteData => "write data",
+            AdminPermission::Export => "export",
+            AdminPermission::Deploy => "deploy",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl From<AdminPermission> for PermissionProto {
+    fn from(permission: AdminPermission) -> Self {
+        match permission {
+            AdminPermission::ReadData => PermissionProto::ReadData,
+            AdminPermission::WriteData => PermissionProto::WriteData,
+            AdminPermission::Export => PermissionProto::Export,
+            AdminPermission::Deploy => PermissionProto::Deploy,
+        }
+    }
+}
+
+impl TryFrom<PermissionProto> for AdminPermission {
+    type Error = anyhow::Error;
+
+    fn try_from(permission: PermissionProto) -> anyhow::Result<Self> {
+        Ok(match permission {
+            PermissionProto::Unspecified => anyhow::bail!("Unspecified admin key permission"),
+            PermissionProto::ReadData => AdminPermission::ReadData,
+            PermissionProto::WriteData => AdminPermission::WriteData,
+            PermissionProto::Export => AdminPermission::Export,
+            PermissionProto::Deploy => AdminPermission::Deploy,
+        })
+    }
+}
+
+/// What an admin key is allowed to do. Keys issued without a scope get
+/// [`AdminKeyScope::full`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct AdminKeyScope {
+    permissions: BTreeSet<AdminPermission>,
+    /// Component paths the key may access, or `None` for all components. The
+    /// root component is the empty string.
+    components: Option<BTreeSet<String>>,
+    /// Tables the key may access, or `None` for all tables.
+    tables: Option<BTreeSet<String>>,
+}
+
+impl AdminKeyScope {
+    pub fn full() -> Self {
+        Self::with_permissions(AdminPermission::ALL)
+    }
+
+    pub fn read_only() -> Self {
+        Self::with_permissions([AdminPermission::ReadData])
+    }
+
+    pub fn export_only() -> Self {
+        Self::with_permissions([AdminPermission::Export])
+    }
+
+    pub fn deploy_only() -> Self {
+        Self::with_permissions([AdminPermission::Deploy])
+    }
+
+    pub fn with_permissions(permissions: impl IntoIterator<Item = AdminPermission>) -> Self {
+        Self {
+            permissions: permissions.into_iter().collect(),
+            components: None,
+            tables: None,
+        }
+    }
+
+    /// Restrict the key to the given component paths.
+    pub fn restrict_components(mut self, components: impl IntoIterator<Item = String>) -> Self {
+        self.components = Some(components.into_iter().collect());
+        self
+    }
+
+    /// Restrict the key to the given tables.
+    pub fn restrict_tables(mut self, tables: impl IntoIterator<Item = String>) -> Self {
+        self.tables = Some(tables.into_iter().collect());
+        self
+    }
+
+    pub fn is_full(&self) -> bool {
+        *self == Self::full()
+    }
+
+    pub fn permissions(&self) -> &BTreeSet<AdminPermission> {
+        &self.permissions
+    }
+
+    pub fn allows(&self, permission: AdminPermission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    pub fn allows_component(&self, component_path: &str) -> bool {
+        self.components
+            .as_ref()
+            .map_or(true, |components| components.contains(component_path))
+    }
+
+    pub fn allows_table(&self, table_name: &str) -> bool {
+        self.tables
+            .as_ref()
+            .map_or(true, |tables| tables.contains(table_name))
+    }
+
+    pub fn check_permission(&self, permission: AdminPermission) -> anyhow::Result<()> {
+        if !self.allows(permission) {
+            anyhow::bail!(ErrorMetadata::forbidden(
+                "AdminKeyMissingPermission",
+                format!("The provided admin key does not have permission to {permission}."),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn check_component(&self, component_path: &str) -> anyhow::Result<()> {
+        if !self.allows_component(component_path) {
+            let component = if component_path.is_empty() {
+                "the root component".to_string()
+            } else {
+                format!("component '{component_path}'")
+            };
+            anyhow::bail!(ErrorMetadata::forbidden(
+                "AdminKeyComponentNotAllowed",
+                format!("The provided admin key does not have access to {component}."),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn check_table(&self, table_name: &str) -> anyhow::Result<()> {
+        if !self.allows_table(table_name) {
+            anyhow::bail!(ErrorMetadata::forbidden(
+                "AdminKeyTableNotAllowed",
+                format!("The provided admin key does not have access to table '{table_name}'."),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fails unless the key has every permission and access to every table,
+    /// for operations that manage other admin keys.
+    pub fn check_full(&self) -> anyhow::Result<()> {
+        if !self.is_full() {
+            anyhow::bail!(ErrorMetadata::forbidden(
+                "AdminKeyNotFullAccess",
+                "Only admin keys with full access can manage other admin keys.",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fails if the key is limited to some tables or components.
+    pub fn check_all_tables(&self) -> anyhow::Result<()> {
+        if self.components.is_some() || self.tables.is_some() {
+            anyhow::bail!(ErrorMetadata::forbidden(
+                "AdminKeyTablesRestricted",
+                "The provided admin key only has access to some tables, but this operation \
+                 accesses all of them.",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl From<AdminKeyScope> for AdminKeyScopeProto {
+    fn from(
+        AdminKeyScope {
+            permissions,
+            components,
+            tables,
+        }: AdminKeyScope,
+    ) -> Self {
+        Self {
+            permissions: permissions
+                .into_iter()
+                .map(|p| PermissionProto::from(p) as i32)
+                .collect(),
+            components: components.map(|names| NamesProto {
+                names: names.into_iter().collect(),
+            }),
+            tables: tables.map(|names| NamesProto {
+                names: names.into_iter().collect(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<AdminKeyScopeProto> for AdminKeyScope {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        AdminKeyScopeProto {
+            permissions,
+            components,
+            tables,
+        }: AdminKeyScopeProto,
+    ) -> anyhow::Result<Self> {
+        let permissions: BTreeSet<AdminPermission> = permissions
+            .into_iter()
+            .map(|p| PermissionProto::try_from(p)?.try_into())
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            permissions,
+            components: components.map(|names| names.names.into_iter().collect()),
+            tables: tables.map(|names| names.names.into_iter().collect()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pb::convex_keys::AdminKeyScope as AdminKeyScopeProto;
+    use proptest::prelude::*;
+    use sync_types::testing::assert_roundtrips;
+
+    use super::{
+        AdminKeyScope,
+        AdminPermission,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_scope_proto_roundtrips(scope in any::<AdminKeyScope>()) {
+            assert_roundtrips::<AdminKeyScope, AdminKeyScopeProto>(scope);
+        }
+    }
+
+    #[test]
+    fn test_restrictions() {
+        let scope = AdminKeyScope::read_only()
+            .restrict_components(["".to_string()])
+            .restrict_tables(["messages".to_string()]);
+        assert!(scope.allows(AdminPermission::ReadData));
+        assert!(!scope.allows(AdminPermission::WriteData));
+        assert!(scope.allows_component(""));
+        assert!(!scope.allows_component("waitlist"));
+        assert!(scope.allows_table("messages"));
+        assert!(!scope.allows_table("users"));
+        assert!(!scope.is_full());
+        assert!(AdminKeyScope::full().is_full());
+    }
+}
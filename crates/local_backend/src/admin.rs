@@ -1,12 +1,31 @@
 use anyhow::Context;
-use authentication::application_auth::ApplicationAuth;
-use common::types::MemberId;
+use application::Application;
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    runtime::Runtime,
+    types::MemberId,
+};
 use errors::ErrorMetadata;
+use http::StatusCode;
 use keybroker::{
     AdminIdentityPrincipal,
+    AdminPermission,
     Identity,
     KeyBroker,
 };
+use serde::Deserialize;
+
+use crate::{
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
 
 pub fn must_be_admin_from_keybroker(
     kb: &KeyBroker,
@@ -19,29 +38,41 @@ pub fn must_be_admin_from_keybroker(
     Ok(identity)
 }
 
-pub async fn must_be_admin_from_key(
-    app_auth: &ApplicationAuth,
+pub async fn must_be_admin_from_key<RT: Runtime>(
+    application: &Application<RT>,
     instance_name: String,
     admin_key_or_access_token: String,
+    permission: AdminPermission,
 ) -> anyhow::Result<Identity> {
-    let identity = app_auth
+    let identity = application
+        .app_auth()
         .check_key(admin_key_or_access_token, instance_name.clone())
         .await
         .context(bad_admin_key_error(Some(instance_name)))?;
+    application.check_admin_key_not_revoked(&identity).await?;
+    identity.check_admin_permission(permission)?;
     Ok(identity)
 }
 
-pub fn must_be_admin(identity: &Identity) -> anyhow::Result<AdminIdentityPrincipal> {
+pub fn must_be_admin(
+    identity: &Identity,
+    permission: AdminPermission,
+) -> anyhow::Result<AdminIdentityPrincipal> {
     if let Identity::InstanceAdmin(admin_identity) = identity {
+        admin_identity.scope().check_permission(permission)?;
         Ok(admin_identity.principal().clone())
     } else {
         Err(bad_admin_key_error(identity.instance_name()).into())
     }
 }
 
-pub fn must_be_admin_member(identity: &Identity) -> anyhow::Result<MemberId> {
+pub fn must_be_admin_member(
+    identity: &Identity,
+    permission: AdminPermission,
+) -> anyhow::Result<MemberId> {
     if let Identity::InstanceAdmin(admin_identity) = identity {
         if let AdminIdentityPrincipal::Member(member_id) = admin_identity.principal() {
+            admin_identity.scope().check_permission(permission)?;
             Ok(*member_id)
         } else {
             Err(bad_admin_key_error(identity.instance_name()).into())
@@ -64,3 +95,22 @@ pub fn bad_admin_key_error(instance_name: Option<String>) -> ErrorMetadata {
     };
     ErrorMetadata::forbidden("BadDeployKey", msg)
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeAdminKeyRequest {
+    key_id: String,
+}
+
+/// Revokes an admin key by its id. The revoked key is rejected by every
+/// endpoint from then on. Only keys with full access may revoke keys.
+pub async fn revoke_admin_key(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RevokeAdminKeyRequest { key_id }): Json<RevokeAdminKeyRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::Deploy)?;
+    identity.check_admin_full_access()?;
+    st.application.revoke_admin_key(identity, key_id).await?;
+    Ok(StatusCode::OK)
+}
@@ -3,10 +3,14 @@ use std::{
     path::PathBuf,
 };
 
+use anyhow::Context;
 use clap::Parser;
-use common::types::{
-    ConvexOrigin,
-    ConvexSite,
+use common::{
+    http::fetch::egress::EgressPolicy,
+    types::{
+        ConvexOrigin,
+        ConvexSite,
+    },
 };
 use keybroker::{
     InstanceSecret,
@@ -54,6 +58,21 @@ pub struct LocalConfig {
     /// Which directory should local storage use
     #[clap(long, default_value = "convex_local_storage")]
     local_storage: String,
+
+    /// gRPC URL of a `function-runner` process to run queries and mutations
+    /// in. Actions always run in process.
+    #[clap(long)]
+    pub function_runner_url: Option<Url>,
+
+    /// File of `NAME=value` lines overriding knobs that can change while the
+    /// backend is running. Reloaded on SIGHUP.
+    #[clap(long)]
+    pub knobs_config: Option<PathBuf>,
+
+    /// JSON file with the egress policy restricting outbound requests from
+    /// actions.
+    #[clap(long)]
+    pub egress_policy: Option<PathBuf>,
 }
 
 impl fmt::Debug for LocalConfig {
@@ -62,6 +81,9 @@ impl fmt::Debug for LocalConfig {
             .field("convex_origin", &self.convex_origin)
             .field("convex_site", &self.convex_site)
             .field("instance_name", &self.instance_name)
+            .field("function_runner_url", &self.function_runner_url)
+            .field("knobs_config", &self.knobs_config)
+            .field("egress_policy", &self.egress_policy)
             .finish()
     }
 }
@@ -108,14 +130,23 @@ impl LocalConfig {
         )
     }
 
+    pub fn egress_policy(&self) -> anyhow::Result<Option<EgressPolicy>> {
+        let Some(path) = &self.egress_policy else {
+            return Ok(None);
+        };
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read egress policy {}", path.display()))?;
+        let policy = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid egress policy {}", path.display()))?;
+        Ok(Some(policy))
+    }
+
     pub fn storage_dir(&self) -> PathBuf {
         self.local_storage.clone().into()
     }
 
     #[cfg(test)]
     pub fn new_for_test() -> anyhow::Result<Self> {
-        use anyhow::Context;
-
         let tempdir_handle = tempfile::tempdir()?;
         let db_path = tempdir_handle.path().join("convex_local_backend.sqlite3");
         // Easiest way to get a config object with defaults is to parse from cmd line
@@ -1,3 +1,8 @@
+use std::{
+    collections::BTreeMap,
+    time::Duration,
+};
+
 use application::valid_identifier::ValidIdentifier;
 use axum::{
     debug_handler,
@@ -12,6 +17,7 @@ use common::{
         },
         HttpResponseError,
     },
+    knobs::DOCUMENT_RETENTION_DELAY,
     shapes::{
         dashboard_shape_json,
         reduced::ReducedShape,
@@ -19,6 +25,7 @@ use common::{
 };
 use database::IndexModel;
 use http::StatusCode;
+use keybroker::AdminPermission;
 use serde::{
     Deserialize,
     Serialize,
@@ -48,7 +55,8 @@ pub async fn shapes2(
 ) -> Result<impl IntoResponse, HttpResponseError> {
     let mut out = serde_json::Map::new();
 
-    must_be_admin_member(&identity)?;
+    must_be_admin_member(&identity, AdminPermission::ReadData)?;
+    identity.check_admin_component("")?;
     let snapshot = st.application.latest_snapshot()?;
     let mapping = snapshot
         .table_mapping()
@@ -56,6 +64,9 @@ pub async fn shapes2(
     let virtual_mapping = snapshot.table_registry.virtual_table_mapping();
 
     for table_name in snapshot.table_registry.user_table_names() {
+        if !identity.allows_admin_table(table_name) {
+            continue;
+        }
         let table_summary = snapshot.table_summary(table_name);
         let shape = ReducedShape::from_type(
             table_summary.inferred_type(),
@@ -74,7 +85,7 @@ pub async fn delete_tables(
     ExtractIdentity(identity): ExtractIdentity,
     Json(DeleteTableArgs { table_names }): Json<DeleteTableArgs>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    must_be_admin_member(&identity)?;
+    must_be_admin_member(&identity, AdminPermission::WriteData)?;
     let table_names = table_names
         .into_iter()
         .map(|t| Ok(t.parse::<ValidIdentifier<TableName>>()?.0))
@@ -83,6 +94,64 @@ pub async fn delete_tables(
     Ok(StatusCode::OK)
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetTableRetentionResponse {
+    default_retention_seconds: u64,
+    /// Tables without an override map to `null`.
+    tables: BTreeMap<String, Option<u64>>,
+}
+
+#[debug_handler]
+pub async fn get_table_retention(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity, AdminPermission::ReadData)?;
+    let retention = st.application.table_document_retention(identity).await?;
+    Ok(Json(GetTableRetentionResponse {
+        default_retention_seconds: DOCUMENT_RETENTION_DELAY.get().as_secs(),
+        tables: retention
+            .into_iter()
+            .map(|(table_name, retention)| {
+                (
+                    String::from(table_name),
+                    retention.map(|retention| retention.as_secs()),
+                )
+            })
+            .collect(),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTableRetentionArgs {
+    table_name: String,
+    /// `null` resets the table to the default retention.
+    retention_seconds: Option<u64>,
+}
+
+#[debug_handler]
+pub async fn set_table_retention(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetTableRetentionArgs {
+        table_name,
+        retention_seconds,
+    }): Json<SetTableRetentionArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity, AdminPermission::WriteData)?;
+    let table_name = table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    st.application
+        .set_table_document_retention(
+            identity,
+            table_name,
+            retention_seconds.map(Duration::from_secs),
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct GetIndexesResponse {
@@ -94,7 +163,8 @@ pub async fn get_indexes(
     State(st): State<LocalAppState>,
     ExtractIdentity(identity): ExtractIdentity,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    must_be_admin_member(&identity)?;
+    must_be_admin_member(&identity, AdminPermission::ReadData)?;
+    identity.check_admin_component("")?;
     let mut tx = st.application.begin(identity.clone()).await?;
     let indexes = IndexModel::new(&mut tx)
         .get_application_indexes(TableNamespace::TODO())
@@ -102,7 +172,9 @@ pub async fn get_indexes(
     Ok(Json(GetIndexesResponse {
         indexes: indexes
             .into_iter()
-            .map(|idx| idx.into_value().try_into())
+            .map(|idx| idx.into_value())
+            .filter(|idx| identity.allows_admin_table(idx.name.table()))
+            .map(|idx| idx.try_into())
             .collect::<anyhow::Result<_>>()?,
     }))
 }
@@ -119,10 +191,136 @@ pub async fn get_source_code(
     ExtractIdentity(identity): ExtractIdentity,
     Query(GetSourceCodeArgs { path }): Query<GetSourceCodeArgs>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    must_be_admin_member(&identity)?;
+    must_be_admin_member(&identity, AdminPermission::ReadData)?;
     let source_code = st
         .application
         .get_source_code(identity, path.parse()?)
         .await?;
     Ok(Json(source_code))
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::headers::authorization::Credentials;
+    use database::UserFacingModel;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use keybroker::{
+        AdminKeyScope,
+        AdminPermission,
+        Identity,
+    };
+    use runtime::prod::ProdRuntime;
+    use serde_json::json;
+    use value::assert_obj;
+
+    use crate::test_helpers::{
+        setup_backend_for_test,
+        TestLocalBackend,
+    };
+
+    async fn insert_documents(backend: &TestLocalBackend) -> anyhow::Result<()> {
+        let mut tx = backend.st.application.begin(Identity::system()).await?;
+        for table in ["allowed", "hidden"] {
+            UserFacingModel::new_root_for_test(&mut tx)
+                .insert(table.parse()?, assert_obj!("a" => 1))
+                .await?;
+        }
+        backend.st.application.commit_test(tx).await?;
+        Ok(())
+    }
+
+    fn delete_tables_request(
+        auth_header: String,
+        table_names: &[&str],
+    ) -> anyhow::Result<Request<Body>> {
+        let body = json!({ "tableNames": table_names });
+        Ok(Request::builder()
+            .uri("/api/delete_tables")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", auth_header)
+            .body(Body::from(serde_json::to_vec(&body)?))?)
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_shapes_and_indexes_skip_disallowed_tables(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        insert_documents(&backend).await?;
+        let auth_header = backend
+            .scoped_admin_auth_header(
+                AdminKeyScope::read_only().restrict_tables(["allowed".to_string()]),
+            )?
+            .0
+            .encode();
+
+        let req = Request::builder()
+            .uri("/api/shapes2")
+            .method("GET")
+            .header("Authorization", auth_header.clone())
+            .body(Body::empty())?;
+        let shapes: serde_json::Map<String, serde_json::Value> =
+            backend.expect_success(req).await?;
+        assert_eq!(shapes.keys().collect::<Vec<_>>(), vec!["allowed"]);
+
+        let req = Request::builder()
+            .uri("/api/get_indexes")
+            .method("GET")
+            .header("Authorization", auth_header)
+            .body(Body::empty())?;
+        let response: serde_json::Value = backend.expect_success(req).await?;
+        let indexes = response["indexes"].as_array().unwrap();
+        assert!(indexes.iter().all(|index| index["table"] == "allowed"));
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_delete_tables_checks_scope(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        insert_documents(&backend).await?;
+
+        let read_only = backend
+            .scoped_admin_auth_header(AdminKeyScope::read_only())?
+            .0
+            .encode();
+        let req = delete_tables_request(read_only, &["allowed"])?;
+        backend
+            .expect_error(req, StatusCode::FORBIDDEN, "AdminKeyMissingPermission")
+            .await?;
+
+        let table_scoped = backend
+            .scoped_admin_auth_header(
+                AdminKeyScope::with_permissions([AdminPermission::WriteData])
+                    .restrict_tables(["allowed".to_string()]),
+            )?
+            .0
+            .encode();
+        let req = delete_tables_request(table_scoped.clone(), &["allowed", "hidden"])?;
+        backend
+            .expect_error(req, StatusCode::FORBIDDEN, "AdminKeyTableNotAllowed")
+            .await?;
+
+        let component_scoped = backend
+            .scoped_admin_auth_header(
+                AdminKeyScope::with_permissions([AdminPermission::WriteData])
+                    .restrict_components(["child".to_string()]),
+            )?
+            .0
+            .encode();
+        let req = delete_tables_request(component_scoped, &["allowed"])?;
+        backend
+            .expect_error(req, StatusCode::FORBIDDEN, "AdminKeyComponentNotAllowed")
+            .await?;
+
+        let req = delete_tables_request(table_scoped, &["allowed"])?;
+        backend.expect_success::<()>(req).await?;
+        let snapshot = backend.st.application.latest_snapshot()?;
+        let tables: Vec<_> = snapshot.table_registry.user_table_names().collect();
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].to_string(), "hidden");
+        Ok(())
+    }
+}
@@ -35,7 +35,10 @@ use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
 };
-use keybroker::Identity;
+use keybroker::{
+    AdminPermission,
+    Identity,
+};
 use model::{
     config::{
         types::{
@@ -316,9 +319,10 @@ pub async fn get_config(
     Json(req): Json<GetConfigRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     let identity = must_be_admin_from_key(
-        st.application.app_auth(),
+        &st.application,
         st.instance_name.clone(),
         req.admin_key,
+        AdminPermission::Deploy,
     )
     .await?;
 
@@ -346,9 +350,10 @@ pub async fn get_config_hashes(
     Json(req): Json<GetConfigRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     let identity = must_be_admin_from_key(
-        st.application.app_auth(),
+        &st.application,
         st.instance_name.clone(),
         req.admin_key,
+        AdminPermission::Deploy,
     )
     .await?;
 
@@ -46,7 +46,10 @@ use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
 };
-use keybroker::Identity;
+use keybroker::{
+    AdminPermission,
+    Identity,
+};
 use model::{
     auth::{
         types::AuthDiff,
@@ -360,9 +363,10 @@ pub async fn start_push_handler(
     request: StartPushRequest,
 ) -> anyhow::Result<StartPushResponse> {
     let _identity = must_be_admin_from_key(
-        st.application.app_auth(),
+        &st.application,
         st.instance_name.clone(),
         request.admin_key.clone(),
+        AdminPermission::Deploy,
     )
     .await?;
     let identity = Identity::system();
@@ -612,9 +616,10 @@ async fn wait_for_schema_handler(
     req: WaitForSchemaRequest,
 ) -> anyhow::Result<()> {
     let identity = must_be_admin_from_key(
-        st.application.app_auth(),
+        &st.application,
         st.instance_name.clone(),
         req.admin_key,
+        AdminPermission::Deploy,
     )
     .await?;
     let schema_change: SchemaChange = req.schema_change.try_into()?;
@@ -716,9 +721,10 @@ async fn finish_push_handler(
 ) -> anyhow::Result<FinishPushDiff> {
     let start_push = StartPushResponse::try_from(req.start_push)?;
     let _identity = must_be_admin_from_key(
-        st.application.app_auth(),
+        &st.application,
         st.instance_name.clone(),
         req.admin_key.clone(),
+        AdminPermission::Deploy,
     )
     .await?;
 
@@ -8,6 +8,7 @@ use common::http::{
     HttpResponseError,
 };
 use http::StatusCode;
+use keybroker::AdminPermission;
 use model::environment_variables::types::{
     EnvVarName,
     EnvVarValue,
@@ -56,7 +57,7 @@ pub async fn update_environment_variables(
     ExtractIdentity(identity): ExtractIdentity,
     Json(UpdateEnvVarsRequest { changes }): Json<UpdateEnvVarsRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    must_be_admin(&identity)?;
+    must_be_admin(&identity, AdminPermission::Deploy)?;
 
     let mut env_var_changes = vec![];
     for change in changes {
@@ -0,0 +1,367 @@
+use anyhow::Context;
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+    index::IndexKeyBytes,
+    query::Query as DatabaseQuery,
+    types::TableName,
+};
+use errors::ErrorMetadata;
+use keybroker::AdminPermission;
+use model::table_restores::types::{
+    TableRestore,
+    TableRestoreState,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use sync_types::Timestamp;
+use value::id_v6::DeveloperDocumentId;
+
+use crate::{
+    admin::must_be_admin,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+fn parse_ts(ts: &str) -> anyhow::Result<Timestamp> {
+    ts.parse().context(ErrorMetadata::bad_request(
+        "InvalidTimestamp",
+        format!("Invalid timestamp \"{ts}\""),
+    ))
+}
+
+fn parse_table_name(table: &str) -> anyhow::Result<TableName> {
+    table.parse().context(ErrorMetadata::bad_request(
+        "InvalidTableName",
+        format!("Invalid table name \"{table}\""),
+    ))
+}
+
+fn parse_document_id(id: &str) -> anyhow::Result<DeveloperDocumentId> {
+    DeveloperDocumentId::decode(id).context(ErrorMetadata::bad_request(
+        "InvalidDocumentId",
+        format!("Invalid document ID \"{id}\""),
+    ))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalDocumentJson {
+    /// Timestamp of the revision, which is at or before the requested one.
+    ts: String,
+    document: JsonValue,
+}
+
+impl HistoricalDocumentJson {
+    fn new(ts: Timestamp, document: ResolvedDocument) -> Self {
+        Self {
+            ts: ts.to_string(),
+            document: JsonValue::from(document.into_value().0),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListHistoricalDocumentsArgs {
+    ts: String,
+    table: String,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListHistoricalDocumentsResponse {
+    documents: Vec<HistoricalDocumentJson>,
+    cursor: Option<String>,
+    has_more: bool,
+}
+
+/// Lists a page of a table's documents as they were at `ts`, which must be
+/// within the retention window.
+pub async fn list_historical_documents(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListHistoricalDocumentsArgs { ts, table, cursor }): Query<ListHistoricalDocumentsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::ReadData)?;
+    let ts = parse_ts(&ts)?;
+    let table_name = parse_table_name(&table)?;
+    let cursor = cursor.as_deref().map(parse_document_id).transpose()?;
+    let page = st
+        .application
+        .list_historical_documents(identity, ts, table_name, cursor)
+        .await?;
+    Ok(Json(ListHistoricalDocumentsResponse {
+        documents: page
+            .documents
+            .into_iter()
+            .map(|(ts, _, document)| HistoricalDocumentJson::new(ts, document))
+            .collect(),
+        cursor: page.cursor.map(|cursor| cursor.encode()),
+        has_more: page.has_more,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryHistoricalDocumentsRequest {
+    ts: String,
+    /// A query in the same JSON format functions send to the database.
+    query: JsonValue,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryHistoricalDocumentsResponse {
+    documents: Vec<HistoricalDocumentJson>,
+    cursor: Option<String>,
+    has_more: bool,
+}
+
+/// Runs an index range or full table scan query, with optional filters,
+/// against a table as it was at `ts`, which must be within the retention
+/// window. Pages may be empty when the filters skip every scanned document.
+pub async fn query_historical_documents(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(QueryHistoricalDocumentsRequest { ts, query, cursor }): Json<
+        QueryHistoricalDocumentsRequest,
+    >,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::ReadData)?;
+    let ts = parse_ts(&ts)?;
+    let query = DatabaseQuery::try_from(query).context(ErrorMetadata::bad_request(
+        "InvalidQuery",
+        "Invalid query: expected an index range or full table scan in JSON query format",
+    ))?;
+    let cursor = cursor
+        .map(|cursor| {
+            base64::decode(&cursor)
+                .map(IndexKeyBytes)
+                .context(ErrorMetadata::bad_request(
+                    "InvalidCursor",
+                    format!("Invalid cursor \"{cursor}\""),
+                ))
+        })
+        .transpose()?;
+    let page = st
+        .application
+        .query_historical_documents(identity, ts, query, cursor)
+        .await?;
+    Ok(Json(QueryHistoricalDocumentsResponse {
+        documents: page
+            .documents
+            .into_iter()
+            .map(|(ts, document)| HistoricalDocumentJson::new(ts, document))
+            .collect(),
+        cursor: page.cursor.map(|cursor| base64::encode(cursor.0)),
+        has_more: page.has_more,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetHistoricalDocumentArgs {
+    ts: String,
+    id: String,
+}
+
+/// Gets a document as it was at `ts`, or `null` if it didn't exist then.
+pub async fn get_historical_document(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(GetHistoricalDocumentArgs { ts, id }): Query<GetHistoricalDocumentArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::ReadData)?;
+    let ts = parse_ts(&ts)?;
+    let id = parse_document_id(&id)?;
+    let document = st
+        .application
+        .get_historical_document(identity, ts, id)
+        .await?;
+    Ok(Json(document.map(|(ts, document)| {
+        HistoricalDocumentJson::new(ts, document)
+    })))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentHistoryArgs {
+    id: String,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentRevisionJson {
+    ts: String,
+    /// `null` if the revision deleted the document.
+    document: Option<JsonValue>,
+    /// `null` once the write is no longer in the write log.
+    write_source: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentHistoryResponse {
+    revisions: Vec<DocumentRevisionJson>,
+    cursor: Option<String>,
+    has_more: bool,
+}
+
+/// Lists a page of a document's revisions, newest first. The history stops at
+/// the retention window.
+pub async fn document_history(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(DocumentHistoryArgs { id, cursor }): Query<DocumentHistoryArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::ReadData)?;
+    let id = parse_document_id(&id)?;
+    let cursor = cursor.as_deref().map(parse_ts).transpose()?;
+    let page = st
+        .application
+        .document_history(identity, id, cursor)
+        .await?;
+    Ok(Json(DocumentHistoryResponse {
+        revisions: page
+            .revisions
+            .into_iter()
+            .map(|revision| DocumentRevisionJson {
+                ts: revision.ts.to_string(),
+                document: revision
+                    .document
+                    .map(|document| JsonValue::from(document.into_value().0)),
+                write_source: revision
+                    .write_source
+                    .as_ref()
+                    .and_then(|source| source.as_str())
+                    .map(String::from),
+            })
+            .collect(),
+        cursor: page.cursor.map(|cursor| cursor.to_string()),
+        has_more: page.has_more,
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreTablesRequest {
+    ts: String,
+    tables: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreTablesResponse {
+    restore_id: String,
+}
+
+/// Starts restoring tables to their state at `ts` by writing the documents
+/// that changed since then as new revisions. The restore runs in the
+/// background; its progress is reported by `list_table_restores`.
+pub async fn restore_tables(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RestoreTablesRequest { ts, tables }): Json<RestoreTablesRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::WriteData)?;
+    let ts = parse_ts(&ts)?;
+    let table_names = tables
+        .iter()
+        .map(|table| parse_table_name(table))
+        .collect::<anyhow::Result<_>>()?;
+    let restore_id = st
+        .application
+        .start_table_restore(identity, ts, table_names)
+        .await?;
+    Ok(Json(RestoreTablesResponse {
+        restore_id: restore_id.encode(),
+    }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRestoreProgressJson {
+    table: String,
+    done: bool,
+    inserted: u64,
+    replaced: u64,
+    deleted: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRestoreJson {
+    id: String,
+    creation_time: Option<f64>,
+    ts: String,
+    state: &'static str,
+    error: Option<String>,
+    tables: Vec<TableRestoreProgressJson>,
+}
+
+impl From<ParsedDocument<TableRestore>> for TableRestoreJson {
+    fn from(document: ParsedDocument<TableRestore>) -> Self {
+        let id = document.developer_id().encode();
+        let creation_time = document.creation_time().map(f64::from);
+        let TableRestore {
+            ts,
+            tables,
+            state,
+            cursor: _,
+        } = document.into_value();
+        let error = match &state {
+            TableRestoreState::Failed { error } => Some(error.clone()),
+            _ => None,
+        };
+        Self {
+            id,
+            creation_time,
+            ts: ts.to_string(),
+            state: state.as_str(),
+            error,
+            tables: tables
+                .into_iter()
+                .map(|table| TableRestoreProgressJson {
+                    table: table.table_name.to_string(),
+                    done: table.done,
+                    inserted: table.inserted,
+                    replaced: table.replaced,
+                    deleted: table.deleted,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Lists table restores with how many documents each has inserted, replaced
+/// and deleted in each table so far, most recently started first.
+pub async fn list_table_restores(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::ReadData)?;
+    let restores = st.application.list_table_restores(identity).await?;
+    let restores: Vec<TableRestoreJson> =
+        restores.into_iter().map(TableRestoreJson::from).collect();
+    Ok(Json(restores))
+}
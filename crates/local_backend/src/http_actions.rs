@@ -8,8 +8,14 @@ use axum::{
     },
     debug_handler,
     extract::{
+        ws::{
+            CloseFrame,
+            Message,
+            WebSocket,
+        },
         FromRequest,
         State,
+        WebSocketUpgrade,
     },
     response::{
         IntoResponse,
@@ -26,23 +32,35 @@ use common::{
         ComponentFunctionPath,
         ComponentPath,
     },
+    errors::report_error,
     http::{
         ExtractRequestId,
         HttpResponseError,
     },
+    knobs::{
+        HTTP_ACTION_WEB_SOCKET_BUFFER_SIZE,
+        HTTP_ACTION_WEB_SOCKET_MAX_MESSAGE_SIZE,
+    },
     runtime::Runtime,
     types::FunctionCaller,
     RequestId,
 };
 use futures::{
     channel::mpsc,
-    stream::BoxStream,
+    pin_mut,
+    select_biased,
+    stream::{
+        BoxStream,
+        SplitStream,
+    },
     FutureExt,
+    SinkExt,
     StreamExt,
     TryStreamExt,
 };
 use futures_async_stream::try_stream;
 use http::{
+    header::SEC_WEBSOCKET_PROTOCOL,
     HeaderMap,
     Method,
     StatusCode,
@@ -53,6 +71,7 @@ use isolate::{
     HttpActionRequestHead,
     HttpActionResponsePart,
     HttpActionResponseStreamer,
+    WebSocketMessage,
 };
 use keybroker::Identity;
 use url::Url;
@@ -91,6 +110,7 @@ impl FromRequest<LocalAppState, axum::body::Body> for ExtractHttpRequestMetadata
                     method,
                 },
                 body: None,
+                web_socket: None,
             }));
         }
 
@@ -103,6 +123,7 @@ impl FromRequest<LocalAppState, axum::body::Body> for ExtractHttpRequestMetadata
                 method,
             },
             body: Some(Box::pin(body.map_err(|e| e.into()))),
+            web_socket: None,
         }))
     }
 }
@@ -113,13 +134,20 @@ pub async fn http_any_method(
     State(st): State<LocalAppState>,
     TryExtractIdentity(identity_result): TryExtractIdentity,
     ExtractRequestId(request_id): ExtractRequestId,
-    ExtractHttpRequestMetadata(http_request_metadata): ExtractHttpRequestMetadata,
-) -> Result<impl IntoResponse, HttpResponseError> {
+    web_socket_upgrade: Option<WebSocketUpgrade>,
+    ExtractHttpRequestMetadata(mut http_request_metadata): ExtractHttpRequestMetadata,
+) -> Result<Response, HttpResponseError> {
     // All HTTP actions run the default export of the http.js path.
     let path = ComponentFunctionPath {
         component: ComponentPath::root(),
         udf_path: "http.js".parse()?,
     };
+    let mut incoming_sender = None;
+    if web_socket_upgrade.is_some() {
+        let (sender, receiver) = mpsc::channel(*HTTP_ACTION_WEB_SOCKET_BUFFER_SIZE);
+        http_request_metadata.web_socket = Some(receiver.map(Ok).boxed());
+        incoming_sender = Some(sender);
+    }
     let mut http_response_stream = stream_http_response(
         path,
         request_id,
@@ -131,6 +159,27 @@ pub async fn http_any_method(
     let Some(HttpActionResponsePart::Head(response_head)) = head else {
         return Err(anyhow::anyhow!("Did not receive HTTP response head first").into());
     };
+    if response_head.status == StatusCode::SWITCHING_PROTOCOLS {
+        let (Some(upgrade), Some(incoming_sender)) = (web_socket_upgrade, incoming_sender) else {
+            return Err(
+                anyhow::anyhow!("HTTP action accepted a WebSocket without an upgrade").into(),
+            );
+        };
+        let upgrade = match response_head
+            .headers
+            .get(SEC_WEBSOCKET_PROTOCOL)
+            .and_then(|protocol| protocol.to_str().ok())
+        {
+            Some(protocol) => upgrade.protocols([protocol.to_string()]),
+            None => upgrade,
+        };
+        return Ok(upgrade
+            .max_message_size(*HTTP_ACTION_WEB_SOCKET_MAX_MESSAGE_SIZE)
+            .max_frame_size(*HTTP_ACTION_WEB_SOCKET_MAX_MESSAGE_SIZE)
+            .on_upgrade(move |socket| {
+                relay_web_socket(socket, incoming_sender, http_response_stream)
+            }));
+    }
     let body = http_response_stream.map(|p| match p {
         Ok(HttpActionResponsePart::BodyChunk(bytes)) => Ok(bytes),
         Err(e) => Err(e),
@@ -143,7 +192,143 @@ pub async fn http_any_method(
         status: response_head.status,
         headers: response_head.headers,
         body: Box::pin(body),
-    })
+    }
+    .into_response())
+}
+
+/// Forwards messages between a client's WebSocket and the HTTP action that
+/// accepted it, until either side closes it. Each direction has its own
+/// buffer, so a full buffer of incoming messages doesn't hold up outgoing
+/// ones: the backend stops reading from the client while the action catches
+/// up, and drops the connection when the client falls too far behind.
+async fn relay_web_socket(
+    socket: WebSocket,
+    incoming_sender: mpsc::Sender<WebSocketMessage>,
+    mut http_response_stream: BoxStream<'static, anyhow::Result<HttpActionResponsePart>>,
+) {
+    let (mut socket_sender, socket_receiver) = socket.split();
+    let (outgoing_sender, mut outgoing_receiver) =
+        mpsc::channel(*HTTP_ACTION_WEB_SOCKET_BUFFER_SIZE);
+    let mut end = None;
+    {
+        let incoming = forward_incoming(socket_receiver, incoming_sender).fuse();
+        let outgoing = forward_outgoing(&mut http_response_stream, outgoing_sender).fuse();
+        let write = async {
+            while let Some(message) = outgoing_receiver.next().await {
+                if socket_sender.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }
+        .fuse();
+        pin_mut!(incoming, outgoing, write);
+        loop {
+            select_biased! {
+                // The writer finishes once the action is done and its
+                // messages are flushed, or the socket fails.
+                () = write => break,
+                outgoing_end = outgoing => {
+                    end = Some(outgoing_end);
+                    if outgoing_end == WebSocketEnd::ClientTooSlow {
+                        break;
+                    }
+                },
+                () = incoming => {
+                    end.get_or_insert(WebSocketEnd::ClientClosed);
+                    break;
+                },
+            }
+        }
+    }
+    match end {
+        // Writing a close frame would wait on the client too.
+        Some(WebSocketEnd::ClientTooSlow | WebSocketEnd::ActionClosed) => {},
+        Some(WebSocketEnd::ClientClosed | WebSocketEnd::ActionFinished) | None => {
+            _ = socket_sender.send(Message::Close(None)).await;
+        },
+    }
+    // Let the action see the socket close and finish, instead of cancelling it.
+    while http_response_stream.next().await.is_some() {}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WebSocketEnd {
+    /// The client closed the socket or the connection failed.
+    ClientClosed,
+    /// The action sent a close message.
+    ActionClosed,
+    /// The action finished or failed without closing the socket.
+    ActionFinished,
+    /// More than `HTTP_ACTION_WEB_SOCKET_BUFFER_SIZE` messages were waiting
+    /// on the client.
+    ClientTooSlow,
+}
+
+/// Passes messages from the client to the action until the client closes the
+/// socket, waiting while the action's buffer is full.
+async fn forward_incoming(
+    mut socket_receiver: SplitStream<WebSocket>,
+    mut incoming_sender: mpsc::Sender<WebSocketMessage>,
+) {
+    while let Some(Ok(message)) = socket_receiver.next().await {
+        let message = match message {
+            Message::Text(text) => WebSocketMessage::Text(text),
+            Message::Binary(bytes) => WebSocketMessage::Binary(bytes.into()),
+            Message::Close(frame) => WebSocketMessage::Close {
+                code: frame.as_ref().map(|frame| frame.code),
+                reason: frame
+                    .map(|frame| frame.reason.into_owned())
+                    .unwrap_or_default(),
+            },
+            Message::Ping(_) | Message::Pong(_) => continue,
+        };
+        _ = incoming_sender.send(message).await;
+    }
+}
+
+/// Passes messages from the action to `outgoing_sender` without waiting on
+/// the client, until the action closes the socket or the buffer fills up.
+async fn forward_outgoing(
+    http_response_stream: &mut BoxStream<'static, anyhow::Result<HttpActionResponsePart>>,
+    mut outgoing_sender: mpsc::Sender<Message>,
+) -> WebSocketEnd {
+    while let Some(part) = http_response_stream.next().await {
+        let message = match part {
+            Ok(HttpActionResponsePart::WebSocketMessage(message)) => message,
+            Ok(_) => {
+                report_error(&mut anyhow::anyhow!(
+                    "Unexpected element in HTTP action WebSocket stream"
+                ));
+                return WebSocketEnd::ActionFinished;
+            },
+            Err(mut e) => {
+                report_error(&mut e);
+                return WebSocketEnd::ActionFinished;
+            },
+        };
+        let (message, closed) = match message {
+            WebSocketMessage::Text(text) => (Message::Text(text), false),
+            WebSocketMessage::Binary(bytes) => (Message::Binary(bytes.to_vec()), false),
+            WebSocketMessage::Close { code, reason } => (
+                Message::Close(code.map(|code| CloseFrame {
+                    code,
+                    reason: reason.into(),
+                })),
+                true,
+            ),
+        };
+        if let Err(e) = outgoing_sender.try_send(message) {
+            return if e.is_full() {
+                WebSocketEnd::ClientTooSlow
+            } else {
+                WebSocketEnd::ClientClosed
+            };
+        }
+        if closed {
+            return WebSocketEnd::ActionClosed;
+        }
+    }
+    WebSocketEnd::ActionFinished
 }
 
 #[try_stream(ok=HttpActionResponsePart, error=anyhow::Error, boxed)]
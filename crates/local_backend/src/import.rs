@@ -27,6 +27,7 @@ use futures::{
     TryStreamExt,
 };
 use model::snapshot_imports::types::{
+    CsvImportOptions,
     ImportFormat,
     ImportMode,
 };
@@ -55,6 +56,8 @@ pub struct ImportQueryArgs {
     format: ImportFormatArg,
     #[serde(default)]
     mode: ImportMode,
+    /// JSON-encoded `CsvImportOptions`, only allowed for CSV imports.
+    csv_options: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -79,6 +82,7 @@ enum ImportFormatArg {
     Csv,
     JsonLines,
     JsonArray,
+    Parquet,
     Zip,
 }
 #[derive(Serialize)]
@@ -90,6 +94,7 @@ struct ImportResponse {
 fn parse_format_arg(
     table_name: Option<String>,
     format: ImportFormatArg,
+    csv_options: Option<String>,
 ) -> anyhow::Result<ImportFormat> {
     let table_name = table_name
         .map(|table_name| {
@@ -101,6 +106,15 @@ fn parse_format_arg(
             })
         })
         .transpose()?;
+    let csv_options = csv_options
+        .map(|csv_options| parse_csv_options(&csv_options))
+        .transpose()?;
+    if csv_options.is_some() && format != ImportFormatArg::Csv {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidCsvOptions",
+            "CSV options are only allowed for CSV imports",
+        ));
+    }
     let inner_format = match format {
         ImportFormatArg::Zip => {
             if table_name.is_some() {
@@ -111,19 +125,41 @@ fn parse_format_arg(
             }
             ImportFormat::Zip
         },
-        ImportFormatArg::Csv => ImportFormat::Csv(table_name.context(
-            ErrorMetadata::bad_request("InvalidName", "CSV import requires table name"),
-        )?),
+        ImportFormatArg::Csv => ImportFormat::Csv(
+            table_name.context(ErrorMetadata::bad_request(
+                "InvalidName",
+                "CSV import requires table name",
+            ))?,
+            csv_options.unwrap_or_default(),
+        ),
         ImportFormatArg::JsonArray => ImportFormat::JsonArray(table_name.context(
             ErrorMetadata::bad_request("InvalidName", "JSON import requires table name"),
         )?),
         ImportFormatArg::JsonLines => ImportFormat::JsonLines(table_name.context(
             ErrorMetadata::bad_request("InvalidName", "JSONL import requires table name"),
         )?),
+        ImportFormatArg::Parquet => ImportFormat::Parquet(table_name.context(
+            ErrorMetadata::bad_request("InvalidName", "Parquet import requires table name"),
+        )?),
     };
     Ok(inner_format)
 }
 
+fn parse_csv_options(csv_options: &str) -> anyhow::Result<CsvImportOptions> {
+    let options: CsvImportOptions = serde_json::from_str(csv_options).map_err(|e| {
+        ErrorMetadata::bad_request("InvalidCsvOptions", format!("invalid CSV options: {e}"))
+    })?;
+    for (name, c) in [("delimiter", options.delimiter), ("quote", options.quote)] {
+        if !c.is_ascii() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidCsvOptions",
+                format!("CSV {name} must be an ASCII character, not {c:?}"),
+            ));
+        }
+    }
+    Ok(options)
+}
+
 pub async fn import(
     State(st): State<LocalAppState>,
     ExtractIdentity(identity): ExtractIdentity,
@@ -131,10 +167,11 @@ pub async fn import(
         table_name,
         format,
         mode,
+        csv_options,
     }): Query<ImportQueryArgs>,
     stream: BodyStream,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    let format = parse_format_arg(table_name, format)?;
+    let format = parse_format_arg(table_name, format, csv_options)?;
     let body_stream = stream.map_err(anyhow::Error::from).boxed();
     let num_written = do_import(&st.application, identity, format, mode, body_stream).await?;
     Ok(Json(ImportResponse { num_written }))
@@ -198,12 +235,13 @@ pub async fn import_finish_upload(
                 table_name,
                 format,
                 mode,
+                csv_options,
             },
         upload_token,
         part_tokens,
     }): Json<ImportFinishUploadArgs>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    let format = parse_format_arg(table_name, format)?;
+    let format = parse_format_arg(table_name, format, csv_options)?;
     let import_id = st
         .application
         .import_finish_upload(
@@ -235,10 +273,11 @@ pub async fn prepare_import(
         table_name,
         format,
         mode,
+        csv_options,
     }): Query<ImportQueryArgs>,
     stream: BodyStream,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    let format = parse_format_arg(table_name, format)?;
+    let format = parse_format_arg(table_name, format, csv_options)?;
     let body_stream = stream.map_err(anyhow::Error::from).boxed();
     let import_id =
         upload_import_file(&st.application, identity, format, mode, body_stream).await?;
@@ -265,3 +304,63 @@ pub async fn perform_import(
     snapshot_import::perform_import(&st.application, identity, import_id).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use keybroker::{
+        AdminKeyScope,
+        AdminPermission,
+    };
+    use runtime::prod::ProdRuntime;
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_import_checks_scope(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let import = |auth_header: String, query: &str| {
+            Request::builder()
+                .uri(format!("/api/import?{query}"))
+                .method("POST")
+                .header("Authorization", auth_header)
+                .body(Body::from("{\"a\": 1}\n"))
+        };
+
+        let read_only = backend
+            .scoped_admin_auth_header(AdminKeyScope::read_only())?
+            .0
+            .encode();
+        let req = import(read_only, "tableName=allowed&format=jsonLines")?;
+        backend
+            .expect_error(req, StatusCode::FORBIDDEN, "AdminKeyMissingPermission")
+            .await?;
+
+        let table_scoped = backend
+            .scoped_admin_auth_header(
+                AdminKeyScope::with_permissions([AdminPermission::WriteData])
+                    .restrict_tables(["allowed".to_string()]),
+            )?
+            .0
+            .encode();
+        let req = import(table_scoped.clone(), "tableName=hidden&format=jsonLines")?;
+        backend
+            .expect_error(req, StatusCode::FORBIDDEN, "AdminKeyTableNotAllowed")
+            .await?;
+        // ZIP imports can write to any table.
+        let req = import(table_scoped.clone(), "format=zip")?;
+        backend
+            .expect_error(req, StatusCode::FORBIDDEN, "AdminKeyTablesRestricted")
+            .await?;
+
+        let req = import(table_scoped, "tableName=allowed&format=jsonLines")?;
+        let response: serde_json::Value = backend.expect_success(req).await?;
+        assert_eq!(response["numWritten"], 1);
+        Ok(())
+    }
+}
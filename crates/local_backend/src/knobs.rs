@@ -0,0 +1,83 @@
+use axum::{
+    debug_handler,
+    response::IntoResponse,
+};
+use common::{
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    knobs::LIVE_KNOBS,
+    live_knobs::{
+        knob_values,
+        KnobValue,
+    },
+};
+use keybroker::AdminPermission;
+use serde::Serialize;
+
+use crate::{
+    admin::must_be_admin,
+    authentication::ExtractIdentity,
+};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListKnobsResponse {
+    knobs: Vec<KnobValue>,
+}
+
+/// Lists the effective values of the knobs that can be changed without a
+/// restart through `--knobs-config`, and where each value comes from.
+#[debug_handler]
+pub async fn list_knobs(
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::ReadData)?;
+    Ok(Json(ListKnobsResponse {
+        knobs: knob_values(LIVE_KNOBS),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_list_knobs(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/api/list_knobs")
+            .method("GET")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(Body::empty())?;
+        let response: JsonValue = backend.expect_success(req).await?;
+        let knobs = response["knobs"].as_array().unwrap();
+        assert!(knobs.contains(&json!({
+            "name": "MAX_JOBS_CANCEL_BATCH",
+            "value": "1000",
+            "source": "default",
+        })));
+
+        let req = Request::builder()
+            .uri("/api/list_knobs")
+            .method("GET")
+            .body(Body::empty())?;
+        backend
+            .expect_error(req, StatusCode::FORBIDDEN, "BadDeployKey")
+            .await?;
+        Ok(())
+    }
+}
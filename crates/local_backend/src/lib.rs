@@ -28,7 +28,11 @@ use application::{
 };
 use common::{
     http::{
-        fetch::ProxiedFetchClient,
+        fetch::{
+            egress::EgressPolicyFetchClient,
+            FetchClient,
+            ProxiedFetchClient,
+        },
         RouteMapper,
     },
     knobs::ACTION_USER_TIMEOUT,
@@ -51,6 +55,7 @@ use file_storage::{
     TransactionalFileStorage,
 };
 use function_runner::{
+    remote::RemoteFunctionRunner,
     server::{
         InProcessFunctionRunner,
         InstanceStorage,
@@ -81,9 +86,12 @@ pub mod dashboard;
 pub mod deploy_config;
 pub mod deploy_config2;
 pub mod environment_variables;
+pub mod history;
 pub mod http_actions;
 pub mod import;
+pub mod knobs;
 pub mod logs;
+pub mod migrations;
 pub mod node_action_callbacks;
 pub mod parse;
 pub mod proxy;
@@ -211,7 +219,8 @@ pub async fn make_app(
 
     let node_process_timeout = *ACTION_USER_TIMEOUT + Duration::from_secs(5);
     let node_executor = Arc::new(LocalNodeExecutor::new(node_process_timeout)?);
-    let actions = Actions::new(
+    let egress_policy = config.egress_policy()?;
+    let mut actions = Actions::new(
         node_executor,
         config.convex_origin_url(),
         *ACTION_USER_TIMEOUT,
@@ -223,11 +232,23 @@ pub async fn make_app(
             "Running without a proxy in release mode -- UDF `fetch` requests are unrestricted!"
         );
     }
-    let fetch_client = Arc::new(ProxiedFetchClient::new(
-        config.convex_http_proxy.clone(),
-        config.name(),
-    ));
-    let function_runner: Arc<dyn FunctionRunner<ProdRuntime>> = Arc::new(
+    let fetch_client: Arc<dyn FetchClient> = match egress_policy {
+        Some(policy) => {
+            tracing::info!("Restricting outbound requests from actions with {policy:?}");
+            actions = actions.with_egress_policy(policy.clone());
+            Arc::new(EgressPolicyFetchClient::new(
+                runtime.clone(),
+                config.convex_http_proxy.clone(),
+                config.name(),
+                policy,
+            ))
+        },
+        None => Arc::new(ProxiedFetchClient::new(
+            config.convex_http_proxy.clone(),
+            config.name(),
+        )),
+    };
+    let mut function_runner: Arc<dyn FunctionRunner<ProdRuntime>> = Arc::new(
         InProcessFunctionRunner::new(
             config.name().clone(),
             config.secret()?,
@@ -243,6 +264,16 @@ pub async fn make_app(
         )
         .await?,
     );
+    if let Some(url) = &config.function_runner_url {
+        tracing::info!("Running queries and mutations in function runner at {url}");
+        function_runner = Arc::new(RemoteFunctionRunner::new(
+            url,
+            config.name(),
+            &config.key_broker()?,
+            database.clone(),
+            function_runner,
+        )?);
+    }
     let application = Application::new(
         runtime.clone(),
         database.clone(),
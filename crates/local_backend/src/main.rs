@@ -11,9 +11,11 @@ use cmd_util::env::config_service;
 use common::{
     errors::MainError,
     http::ConvexHttpService,
+    knobs::LIVE_KNOBS,
     runtime::Runtime,
     version::SERVER_VERSION_STR,
 };
+use config_loader::knobs::KnobOverrides;
 use database::ShutdownSignal;
 use futures::{
     future::{
@@ -34,6 +36,7 @@ use runtime::prod::ProdRuntime;
 use sqlite::SqlitePersistence;
 use tokio::signal::{
     self,
+    unix::SignalKind,
 };
 
 fn main() -> Result<(), MainError> {
@@ -75,6 +78,19 @@ async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::
     let (preempt_tx, mut preempt_rx) = async_broadcast::broadcast(1);
     // Use to signal to the http service to stop.
     let (shutdown_tx, shutdown_rx) = async_broadcast::broadcast(1);
+    // Keep the overrides alive for as long as we're serving.
+    let _knob_overrides = match &config.knobs_config {
+        Some(path) => Some(
+            KnobOverrides::new(
+                runtime.clone(),
+                SignalKind::hangup(),
+                path.clone(),
+                LIVE_KNOBS,
+            )
+            .await?,
+        ),
+        None => None,
+    };
     let persistence = SqlitePersistence::new(&config.db_spec, false)?;
     let st = make_app(
         runtime.clone(),
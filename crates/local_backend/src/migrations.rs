@@ -0,0 +1,169 @@
+use anyhow::Context;
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    document::ParsedDocument,
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    types::TableName,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use keybroker::AdminPermission;
+use model::migrations::types::{
+    Migration,
+    MigrationState,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::id_v6::DeveloperDocumentId;
+
+use crate::{
+    admin::must_be_admin,
+    authentication::ExtractIdentity,
+    parse::parse_udf_path,
+    LocalAppState,
+};
+
+const DEFAULT_BATCH_SIZE: u64 = 100;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartMigrationRequest {
+    table: String,
+    /// Path of the mutation transforming each batch, e.g.
+    /// `migrations:fixTypes`.
+    transform: String,
+    batch_size: Option<u64>,
+    dry_run: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartMigrationResponse {
+    migration_id: String,
+}
+
+/// Starts migrating a table. The migration runs in the background; its
+/// progress is reported by `list_migrations`.
+pub async fn start_migration(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(StartMigrationRequest {
+        table,
+        transform,
+        batch_size,
+        dry_run,
+    }): Json<StartMigrationRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::WriteData)?;
+    let table_name: TableName = table.parse().context(ErrorMetadata::bad_request(
+        "InvalidTableName",
+        format!("Invalid table name \"{table}\""),
+    ))?;
+    let transform = parse_udf_path(&transform)?.canonicalize();
+    let id = st
+        .application
+        .start_migration(
+            identity,
+            table_name,
+            transform,
+            batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+            dry_run.unwrap_or(false),
+        )
+        .await?;
+    Ok(Json(StartMigrationResponse {
+        migration_id: id.encode(),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelMigrationRequest {
+    id: String,
+}
+
+/// Stops a running migration. Batches it has already written are kept.
+pub async fn cancel_migration(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(CancelMigrationRequest { id }): Json<CancelMigrationRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::WriteData)?;
+    let id = DeveloperDocumentId::decode(&id).context(ErrorMetadata::bad_request(
+        "InvalidMigrationId",
+        format!("Invalid migration ID \"{id}\""),
+    ))?;
+    st.application.cancel_migration(identity, id).await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationJson {
+    id: String,
+    creation_time: Option<f64>,
+    table: String,
+    transform: String,
+    batch_size: u64,
+    dry_run: bool,
+    state: &'static str,
+    error: Option<String>,
+    documents_processed: u64,
+    documents_changed: u64,
+    documents_verified: u64,
+    documents_invalid: u64,
+    first_invalid: Option<String>,
+}
+
+impl From<ParsedDocument<Migration>> for MigrationJson {
+    fn from(document: ParsedDocument<Migration>) -> Self {
+        let id = document.developer_id().encode();
+        let creation_time = document.creation_time().map(f64::from);
+        let Migration {
+            table_name,
+            transform,
+            batch_size,
+            dry_run,
+            state,
+            cursor: _,
+            progress,
+        } = document.into_value();
+        let error = match &state {
+            MigrationState::Failed { error } => Some(error.clone()),
+            _ => None,
+        };
+        Self {
+            id,
+            creation_time,
+            table: table_name.to_string(),
+            transform: transform.to_string(),
+            batch_size,
+            dry_run,
+            state: state.as_str(),
+            error,
+            documents_processed: progress.documents_processed,
+            documents_changed: progress.documents_changed,
+            documents_verified: progress.documents_verified,
+            documents_invalid: progress.documents_invalid,
+            first_invalid: progress.first_invalid,
+        }
+    }
+}
+
+/// Lists all migrations with their progress, most recently started first.
+pub async fn list_migrations(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::ReadData)?;
+    let migrations = st.application.list_migrations(identity).await?;
+    let migrations: Vec<MigrationJson> = migrations.into_iter().map(MigrationJson::from).collect();
+    Ok(Json(migrations))
+}
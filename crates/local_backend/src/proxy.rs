@@ -17,7 +17,11 @@ use common::{
     },
     types::ConvexOrigin,
 };
-use http::Request;
+use http::{
+    header::UPGRADE,
+    Request,
+    StatusCode,
+};
 use hyper::Body;
 
 /// Routes HTTP actions to the main webserver
@@ -37,10 +41,32 @@ pub async fn dev_site_proxy(
     ) -> Result<impl IntoResponse, HttpResponseError> {
         let new_uri = format!("{}/http{}", st, request.uri());
         *request.uri_mut() = new_uri.parse().map_err(anyhow::Error::new)?;
-        let resp = hyper::Client::new()
+        // Connection upgrades (e.g. HTTP actions accepting WebSockets) are
+        // proxied by splicing the two upgraded connections together.
+        let client_upgrade = request
+            .headers()
+            .contains_key(UPGRADE)
+            .then(|| hyper::upgrade::on(&mut request));
+        let mut resp = hyper::Client::new()
             .request(request)
             .await
             .map_err(anyhow::Error::new)?;
+        if let Some(client_upgrade) = client_upgrade
+            && resp.status() == StatusCode::SWITCHING_PROTOCOLS
+        {
+            let backend_upgrade = hyper::upgrade::on(&mut resp);
+            tokio::spawn(async move {
+                let (mut client, mut backend) =
+                    match futures::try_join!(client_upgrade, backend_upgrade) {
+                        Ok(upgraded) => upgraded,
+                        Err(e) => {
+                            tracing::warn!("Failed to proxy connection upgrade: {e}");
+                            return;
+                        },
+                    };
+                _ = tokio::io::copy_bidirectional(&mut client, &mut backend).await;
+            });
+        }
         Ok(resp)
     }
 
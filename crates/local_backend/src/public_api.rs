@@ -466,7 +466,11 @@ mod tests {
         Value as JsonValue,
     };
 
-    use crate::test_helpers::setup_backend_for_test;
+    use crate::test_helpers::{
+        setup_backend_for_test,
+        setup_backend_with_function_runner_for_test,
+        TestLocalBackend,
+    };
 
     async fn http_format_tester(
         rt: ProdRuntime,
@@ -615,4 +619,47 @@ mod tests {
         )
         .await
     }
+
+    async fn run_json(
+        backend: &TestLocalBackend,
+        uri: &'static str,
+        udf: &'static str,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        let body = Body::from(serde_json::to_vec(&json!({
+            "path": udf,
+            "args": args,
+            "format": "json",
+        }))?);
+        let req = Request::builder()
+            .uri(uri)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Host", "localhost")
+            .body(body)?;
+        backend.expect_success(req).await
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_remote_function_runner(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_with_function_runner_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        // The mutation's count is answered by the backend over the stream.
+        for expected in [1.0, 2.0] {
+            let result = run_json(
+                &backend,
+                "/api/mutation",
+                "basic:insertAndCount",
+                json!({"foo": "bar"}),
+            )
+            .await?;
+            assert_eq!(result, json!({"status": "success", "value": expected}));
+        }
+        let result = run_json(&backend, "/api/query", "basic:count", json!({})).await?;
+        assert_eq!(result, json!({"status": "success", "value": 2.0}));
+        // Actions still run in process.
+        let result = run_json(&backend, "/api/action", "values:intAction", json!({})).await?;
+        assert_eq!(result, json!({"status": "success", "value": "1"}));
+        Ok(())
+    }
 }
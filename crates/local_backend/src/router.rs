@@ -45,10 +45,13 @@ use tower_http::{
 };
 
 use crate::{
+    admin::revoke_admin_key,
     dashboard::{
         delete_tables,
         get_indexes,
         get_source_code,
+        get_table_retention,
+        set_table_retention,
         shapes2,
     },
     deploy_config::{
@@ -58,6 +61,14 @@ use crate::{
     },
     deploy_config2,
     environment_variables::update_environment_variables,
+    history::{
+        document_history,
+        get_historical_document,
+        list_historical_documents,
+        list_table_restores,
+        query_historical_documents,
+        restore_tables,
+    },
     http_actions::http_action_handler,
     import::{
         import,
@@ -67,10 +78,16 @@ use crate::{
         perform_import,
         prepare_import,
     },
+    knobs::list_knobs,
     logs::{
         stream_function_logs,
         stream_udf_execution,
     },
+    migrations::{
+        cancel_migration,
+        list_migrations,
+        start_migration,
+    },
     node_action_callbacks::{
         action_callbacks_middleware,
         cancel_developer_job,
@@ -99,10 +116,14 @@ use crate::{
     schema::{
         prepare_schema,
         schema_state,
+        suggest_schema,
     },
     snapshot_export::{
+        create_export_schedule,
+        delete_export_schedule,
         get_export,
         get_zip_export,
+        list_export_schedules,
         request_export,
         request_zip_export,
     },
@@ -134,7 +155,11 @@ pub async fn router(st: LocalAppState) -> Router {
         .route("/shapes2", get(shapes2))
         .route("/get_indexes", get(get_indexes))
         .route("/delete_tables", post(delete_tables))
+        .route("/get_table_retention", get(get_table_retention))
+        .route("/set_table_retention", post(set_table_retention))
         .route("/get_source_code", get(get_source_code))
+        .route("/list_knobs", get(list_knobs))
+        .route("/revoke_admin_key", post(revoke_admin_key))
         // Metrics routes
         .route("/app_metrics/stream_udf_execution", get(stream_udf_execution))
         .route("/app_metrics/stream_function_logs", get(stream_function_logs))
@@ -160,6 +185,19 @@ pub async fn router(st: LocalAppState) -> Router {
         .route("/get_config", post(get_config))
         .route("/get_config_hashes", post(get_config_hashes))
         .route("/schema_state/:schema_id", get(schema_state))
+        .route("/suggest_schema", get(suggest_schema))
+        .route("/start_migration", post(start_migration))
+        .route("/cancel_migration", post(cancel_migration))
+        .route("/list_migrations", get(list_migrations))
+        .route("/list_historical_documents", get(list_historical_documents))
+        .route(
+            "/query_historical_documents",
+            post(query_historical_documents),
+        )
+        .route("/get_historical_document", get(get_historical_document))
+        .route("/document_history", get(document_history))
+        .route("/restore_tables", post(restore_tables))
+        .route("/list_table_restores", get(list_table_restores))
         .route("/stream_udf_execution", get(stream_udf_execution))
         .route("/stream_function_logs", get(stream_function_logs))
         .merge(import_routes())
@@ -169,7 +207,10 @@ pub async fn router(st: LocalAppState) -> Router {
         .route("/request", post(request_export))
         .route("/:snapshot_ts/:table_name", get(get_export))
         .route("/request/zip", post(request_zip_export))
-        .route("/zip/:snapshot_ts", get(get_zip_export));
+        .route("/zip/:snapshot_ts", get(get_zip_export))
+        .route("/schedules", get(list_export_schedules))
+        .route("/schedules/create", post(create_export_schedule))
+        .route("/schedules/delete", post(delete_export_schedule));
 
     let api_routes = Router::new()
         .merge(cli_routes)
@@ -16,6 +16,7 @@ use common::{
 };
 use errors::ErrorMetadata;
 use http::StatusCode;
+use keybroker::AdminPermission;
 use model::scheduled_jobs::{
     SchedulerModel,
     SCHEDULED_JOBS_TABLE,
@@ -48,6 +49,7 @@ pub async fn cancel_all_jobs(
     identity
         .member_id()
         .context(bad_admin_key_error(identity.instance_name()))?;
+    identity.check_admin_permission(AdminPermission::WriteData)?;
 
     let udf_path = udf_path
         .map(|p| p.parse())
@@ -80,6 +82,7 @@ pub async fn cancel_job(
     identity
         .member_id()
         .context(bad_admin_key_error(identity.instance_name()))?;
+    identity.check_admin_permission(AdminPermission::WriteData)?;
     st.application
         .execute_with_audit_log_events_and_occ_retries(identity.clone(), "cancel_job", |tx| {
             async {
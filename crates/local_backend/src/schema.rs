@@ -1,4 +1,5 @@
 use anyhow::Context;
+use application::SuggestedSchema;
 use axum::{
     debug_handler,
     extract::State,
@@ -7,6 +8,7 @@ use axum::{
 use common::{
     bootstrap_model::{
         index::{
+            aggregate_index::DeveloperAggregateIndexConfig,
             database_index::{
                 DatabaseIndexState,
                 DeveloperDatabaseIndexConfig,
@@ -33,9 +35,11 @@ use common::{
         extract::{
             Json,
             Path,
+            Query,
         },
         HttpResponseError,
     },
+    schemas::suggest::SuggestSchemaOptions,
 };
 use database::{
     IndexModel,
@@ -43,6 +47,7 @@ use database::{
     SchemaModel,
 };
 use errors::ErrorMetadata;
+use keybroker::AdminPermission;
 use serde::{
     Deserialize,
     Serialize,
@@ -106,8 +111,9 @@ struct BackfillResponse {
 pub struct IndexMetadataResponse {
     table: String,
     name: String,
-    // Either an array of fields (`string[]`) for a database index or an object of
-    // `{ searchField: string, filterFields: string }` for a search index.
+    // Either an array of fields (`string[]`) for a database index, an object of
+    // `{ searchField: string, filterFields: string }` for a search index, or
+    // `{ fields: string[], valueField: string | null }` for an aggregate index.
     fields: JsonValue,
     backfill: BackfillResponse,
 }
@@ -120,7 +126,7 @@ impl TryFrom<IndexMetadata<TableName>> for IndexMetadataResponse {
         let name = meta.name.descriptor().to_string();
         Ok(match meta.config {
             IndexConfig::Database {
-                developer_config: DeveloperDatabaseIndexConfig { fields },
+                developer_config: DeveloperDatabaseIndexConfig { fields, .. },
                 on_disk_state,
             } => {
                 let backfill_state = match on_disk_state {
@@ -202,6 +208,32 @@ impl TryFrom<IndexMetadata<TableName>> for IndexMetadataResponse {
                     },
                 }
             },
+            IndexConfig::Aggregate {
+                developer_config:
+                    DeveloperAggregateIndexConfig {
+                        fields,
+                        value_field,
+                    },
+                on_disk_state,
+            } => {
+                let backfill_state = match on_disk_state {
+                    DatabaseIndexState::Backfilling(_) => "in_progress".to_string(),
+                    DatabaseIndexState::Enabled | DatabaseIndexState::Backfilled => {
+                        "done".to_string()
+                    },
+                };
+                IndexMetadataResponse {
+                    table,
+                    name,
+                    fields: json!({
+                        "fields": JsonValue::from(ConvexValue::try_from(fields)?),
+                        "valueField": value_field.map(String::from),
+                    }),
+                    backfill: BackfillResponse {
+                        state: backfill_state,
+                    },
+                }
+            },
         })
     }
 }
@@ -257,9 +289,10 @@ pub async fn prepare_schema_handler(
 ) -> Result<(Json<PrepareSchemaResponse>, bool), HttpResponseError> {
     let bundle = req.bundle.try_into()?;
     let identity = must_be_admin_from_key(
-        st.application.app_auth(),
+        &st.application,
         st.instance_name.clone(),
         req.admin_key,
+        AdminPermission::Deploy,
     )
     .await?;
     let schema = match st.application.evaluate_schema(bundle).await {
@@ -357,7 +390,7 @@ pub async fn schema_state(
     Path(schema_id): Path<String>,
     ExtractIdentity(identity): ExtractIdentity,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    must_be_admin(&identity)?;
+    must_be_admin(&identity, AdminPermission::Deploy)?;
     let mut tx = st.application.begin(identity.clone()).await?;
     let indexes = IndexModel::new(&mut tx)
         .get_application_indexes(TableNamespace::TODO())
@@ -380,3 +413,47 @@ pub async fn schema_state(
         schema_state: state.into(),
     }))
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestSchemaArgs {
+    literal_unions: Option<bool>,
+    optional_fields: Option<bool>,
+    detect_ids: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SuggestSchemaResponse {
+    schema: JsonValue,
+    fully_validated: bool,
+}
+
+/// Suggests a schema matching the existing data, in the same JSON format as
+/// the schemas pushed by the CLI, and whether every document was checked
+/// against it.
+pub async fn suggest_schema(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(SuggestSchemaArgs {
+        literal_unions,
+        optional_fields,
+        detect_ids,
+    }): Query<SuggestSchemaArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::ReadData)?;
+    let defaults = SuggestSchemaOptions::default();
+    let options = SuggestSchemaOptions {
+        literal_unions: literal_unions.unwrap_or(defaults.literal_unions),
+        optional_fields: optional_fields.unwrap_or(defaults.optional_fields),
+        detect_ids: detect_ids.unwrap_or(defaults.detect_ids),
+    };
+    let SuggestedSchema {
+        schema,
+        fully_validated,
+    } = st.application.suggest_schema(identity, options).await?;
+    Ok(Json(SuggestSchemaResponse {
+        schema: JsonValue::try_from(schema)?,
+        fully_validated,
+    }))
+}
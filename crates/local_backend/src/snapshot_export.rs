@@ -12,18 +12,40 @@ use axum::{
     response::IntoResponse,
     TypedHeader,
 };
-use common::http::{
-    extract::{
-        Path,
-        Query,
+use common::{
+    document::ParsedDocument,
+    http::{
+        extract::{
+            Json,
+            Path,
+            Query,
+        },
+        HttpResponseError,
     },
-    HttpResponseError,
 };
 use errors::ErrorMetadata;
 use http::StatusCode;
-use serde::Deserialize;
+use keybroker::AdminPermission;
+use model::{
+    cron_jobs::types::CronSchedule,
+    exports::types::{
+        CsvExportOptions,
+        ExportFormat,
+        ExportSchedule,
+        ScheduledExport,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
 use storage::StorageGetStream;
 use sync_types::Timestamp;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexObject,
+};
 
 use crate::{
     admin::must_be_admin,
@@ -35,13 +57,54 @@ use crate::{
 // Export GETs are immutable. Browser can cache for a long time.
 const MAX_CACHE_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestExport {
+    #[serde(default)]
+    parquet: bool,
+    #[serde(default)]
+    csv: bool,
+    /// Separator for the headers of flattened nested fields in CSV exports.
+    path_separator: Option<char>,
+    /// How many levels of nested objects to flatten in CSV exports.
+    max_depth: Option<u32>,
+}
+
+impl RequestExport {
+    fn format(self) -> anyhow::Result<Option<ExportFormat>> {
+        if !self.csv && (self.path_separator.is_some() || self.max_depth.is_some()) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidExportOptions",
+                "pathSeparator and maxDepth are only allowed for CSV exports",
+            ));
+        }
+        let format = match (self.parquet, self.csv) {
+            (false, false) => None,
+            (true, false) => Some(ExportFormat::Parquet),
+            (false, true) => {
+                let defaults = CsvExportOptions::default();
+                Some(ExportFormat::Csv(CsvExportOptions {
+                    path_separator: self.path_separator.unwrap_or(defaults.path_separator),
+                    max_depth: self.max_depth.unwrap_or(defaults.max_depth),
+                }))
+            },
+            (true, true) => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidExportFormat",
+                "An export can't be both Parquet and CSV",
+            )),
+        };
+        Ok(format)
+    }
+}
+
 pub async fn request_export(
     State(st): State<LocalAppState>,
     ExtractIdentity(identity): ExtractIdentity,
+    Query(request): Query<RequestExport>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    must_be_admin(&identity)?;
+    must_be_admin(&identity, AdminPermission::Export)?;
     st.application
-        .request_export(identity, false, false)
+        .request_export(identity, request.format()?)
         .await?;
     Ok(StatusCode::OK)
 }
@@ -51,21 +114,196 @@ pub async fn request_export(
 pub struct RequestZipExport {
     #[serde(default)]
     include_storage: bool,
+    #[serde(default)]
+    parquet: bool,
+    /// Snapshot timestamp of an earlier export. If set, only the changes since
+    /// then are exported.
+    base_ts: Option<String>,
 }
 
 #[minitrace::trace]
 pub async fn request_zip_export(
     State(st): State<LocalAppState>,
     ExtractIdentity(identity): ExtractIdentity,
-    Query(RequestZipExport { include_storage }): Query<RequestZipExport>,
+    Query(RequestZipExport {
+        include_storage,
+        parquet,
+        base_ts,
+    }): Query<RequestZipExport>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    must_be_admin(&identity)?;
+    must_be_admin(&identity, AdminPermission::Export)?;
+    let base_ts = base_ts
+        .map(|base_ts| {
+            base_ts
+                .parse::<Timestamp>()
+                .context(ErrorMetadata::bad_request(
+                    "BadSnapshotTimestamp",
+                    "Base timestamp did not parse to a timestamp.",
+                ))
+        })
+        .transpose()?;
     st.application
-        .request_export(identity, true, include_storage)
+        .request_export(
+            identity,
+            Some(ExportFormat::Zip {
+                include_storage,
+                parquet,
+                base_ts,
+            }),
+        )
         .await?;
     Ok(StatusCode::OK)
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateExportScheduleRequest {
+    /// When to export, e.g. `0 3 * * *` for every day at 03:00 UTC.
+    cron: String,
+    #[serde(default)]
+    include_storage: bool,
+    #[serde(default)]
+    parquet: bool,
+    /// Export only the changes since the previous export, with a full export
+    /// every `retention_count` exports.
+    #[serde(default)]
+    incremental: bool,
+    retention_count: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateExportScheduleResponse {
+    schedule_id: String,
+}
+
+/// Schedules ZIP exports. They're listed with `list_export_schedules`.
+pub async fn create_export_schedule(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(CreateExportScheduleRequest {
+        cron,
+        include_storage,
+        parquet,
+        incremental,
+        retention_count,
+    }): Json<CreateExportScheduleRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::Export)?;
+    let id = st
+        .application
+        .create_export_schedule(
+            identity,
+            CronSchedule::Cron { cron_expr: cron },
+            include_storage,
+            parquet,
+            incremental,
+            retention_count,
+        )
+        .await?;
+    Ok(Json(CreateExportScheduleResponse {
+        schedule_id: id.encode(),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteExportScheduleRequest {
+    id: String,
+}
+
+/// Stops scheduling exports. The exports it already made are kept.
+pub async fn delete_export_schedule(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteExportScheduleRequest { id }): Json<DeleteExportScheduleRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::Export)?;
+    let id = DeveloperDocumentId::decode(&id).context(ErrorMetadata::bad_request(
+        "InvalidExportScheduleId",
+        format!("Invalid export schedule ID \"{id}\""),
+    ))?;
+    st.application.delete_export_schedule(identity, id).await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledExportJson {
+    export_id: String,
+    snapshot_ts: String,
+    /// Set for incremental exports.
+    base_ts: Option<String>,
+}
+
+impl From<ScheduledExport> for ScheduledExportJson {
+    fn from(export: ScheduledExport) -> Self {
+        Self {
+            export_id: export.export_id.encode(),
+            snapshot_ts: export.snapshot_ts.to_string(),
+            base_ts: export.base_ts.map(|ts| ts.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportScheduleJson {
+    id: String,
+    cron_schedule: JsonValue,
+    include_storage: bool,
+    parquet: bool,
+    incremental: bool,
+    retention_count: u64,
+    next_ts: String,
+    pending_export: Option<String>,
+    /// Retained exports, oldest first.
+    exports: Vec<ScheduledExportJson>,
+}
+
+impl TryFrom<ParsedDocument<ExportSchedule>> for ExportScheduleJson {
+    type Error = anyhow::Error;
+
+    fn try_from(document: ParsedDocument<ExportSchedule>) -> anyhow::Result<Self> {
+        let id = document.developer_id().encode();
+        let ExportSchedule {
+            cron_schedule,
+            include_storage,
+            parquet,
+            incremental,
+            retention_count,
+            next_ts,
+            pending_export,
+            exports,
+        } = document.into_value();
+        Ok(Self {
+            id,
+            cron_schedule: JsonValue::from(ConvexObject::try_from(cron_schedule)?),
+            include_storage,
+            parquet,
+            incremental,
+            retention_count,
+            next_ts: next_ts.to_string(),
+            pending_export: pending_export.map(|id| id.encode()),
+            exports: exports.into_iter().map(ScheduledExportJson::from).collect(),
+        })
+    }
+}
+
+/// Lists all export schedules, soonest first.
+pub async fn list_export_schedules(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity, AdminPermission::Export)?;
+    let schedules = st.application.list_export_schedules(identity).await?;
+    let schedules: Vec<ExportScheduleJson> = schedules
+        .into_iter()
+        .map(ExportScheduleJson::try_from)
+        .try_collect()?;
+    Ok(Json(schedules))
+}
+
 #[derive(Deserialize)]
 pub struct ExportRequest {
     // Timestamp the snapshot export started at
@@ -83,7 +321,7 @@ pub async fn get_export(
         table_name: file_name,
     }): Path<ExportRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    must_be_admin(&identity)?;
+    must_be_admin(&identity, AdminPermission::Export)?;
     let ts: Timestamp = snapshot_ts.parse().context(ErrorMetadata::bad_request(
         "BadSnapshotTimestamp",
         "Snapshot timestamp did not parse to a timestamp.",
@@ -92,12 +330,16 @@ pub async fn get_export(
         Some(".json")
     } else if file_name.ends_with(".jsonl") {
         Some(".jsonl")
+    } else if file_name.ends_with(".parquet") {
+        Some(".parquet")
+    } else if file_name.ends_with(".csv") {
+        Some(".csv")
     } else {
         None
     }
     .context(ErrorMetadata::bad_request(
         "BadSnapshotFilename",
-        "Snapshot filename must be {table}.json(l)",
+        "Snapshot filename must be {table}.json(l), {table}.parquet or {table}.csv",
     ))?;
     let table_name = file_name.strip_suffix(extension).unwrap();
     let StorageGetStream {
@@ -138,7 +380,7 @@ pub async fn get_zip_export(
     ExtractIdentity(identity): ExtractIdentity,
     Path(ZipExportRequest { snapshot_ts }): Path<ZipExportRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    must_be_admin(&identity)?;
+    must_be_admin(&identity, AdminPermission::Export)?;
     let ts: Timestamp = snapshot_ts.parse().context(ErrorMetadata::bad_request(
         "BadSnapshotTimestamp",
         "Snapshot timestamp did not parse to a timestamp.",
@@ -164,3 +406,39 @@ pub async fn get_zip_export(
         StreamBody::new(stream),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use keybroker::AdminKeyScope;
+    use runtime::prod::ProdRuntime;
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_request_export_checks_scope(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        for scope in [
+            AdminKeyScope::export_only().restrict_tables(["allowed".to_string()]),
+            AdminKeyScope::export_only().restrict_components(["".to_string()]),
+        ] {
+            let auth_header = backend.scoped_admin_auth_header(scope)?.0.encode();
+            for uri in ["/api/export/request", "/api/export/request/zip"] {
+                let req = Request::builder()
+                    .uri(uri)
+                    .method("POST")
+                    .header("Authorization", auth_header.clone())
+                    .body(Body::empty())?;
+                backend
+                    .expect_error(req, StatusCode::FORBIDDEN, "AdminKeyTablesRestricted")
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -6,22 +6,35 @@ use std::{
 use anyhow::Context;
 use axum::headers::Authorization;
 use common::{
+    grpc::ConvexGrpcService,
     http::{
         ConvexHttpService,
         HttpError,
         NoopRouteMapper,
     },
+    persistence::Persistence,
+    runtime::Runtime,
     testing::TestPersistence,
     types::MemberId,
 };
 use database::ShutdownSignal;
+use function_runner::{
+    grpc::FunctionRunnerGrpcService,
+    server::InstanceStorage,
+};
+use futures::future;
 use http::{
     Request,
     StatusCode,
 };
+use keybroker::AdminKeyScope;
 use metrics::SERVER_VERSION_STR;
 use runtime::prod::ProdRuntime;
 use serde::de::DeserializeOwned;
+use storage::{
+    LocalDirStorage,
+    StorageUseCase,
+};
 use sync_types::headers::ConvexAdminAuthorization;
 use tower::ServiceExt;
 
@@ -40,10 +53,60 @@ pub struct TestLocalBackend {
 }
 
 pub async fn setup_backend_for_test(runtime: ProdRuntime) -> anyhow::Result<TestLocalBackend> {
+    let config = LocalConfig::new_for_test()?;
+    setup_backend_with_config(runtime, config, TestPersistence::new()).await
+}
+
+/// Like [`setup_backend_for_test`], but runs queries and mutations in a
+/// function runner gRPC server started on a free local port.
+pub async fn setup_backend_with_function_runner_for_test(
+    runtime: ProdRuntime,
+) -> anyhow::Result<TestLocalBackend> {
+    let mut config = LocalConfig::new_for_test()?;
+    let persistence = TestPersistence::new();
+    let storage_dir = config.storage_dir();
+    let storage = InstanceStorage {
+        files_storage: Arc::new(LocalDirStorage::for_use_case(
+            runtime.clone(),
+            &storage_dir.to_string_lossy(),
+            StorageUseCase::Files,
+        )?),
+        modules_storage: Arc::new(LocalDirStorage::for_use_case(
+            runtime.clone(),
+            &storage_dir.to_string_lossy(),
+            StorageUseCase::Modules,
+        )?),
+    };
+    let service = FunctionRunnerGrpcService::new(
+        runtime.clone(),
+        persistence.reader(),
+        storage,
+        config.name(),
+        config.secret()?,
+        config.convex_origin_url(),
+    )
+    .await?;
+    let port = portpicker::pick_unused_port().context("No ports free")?;
+    runtime.spawn("function_runner_grpc", async move {
+        let result = ConvexGrpcService::new()
+            .add_service(service.into_server())
+            .serve(([127, 0, 0, 1], port).into(), future::pending())
+            .await;
+        if let Err(e) = result {
+            tracing::error!("Function runner gRPC server failed: {e:?}");
+        }
+    });
+    config.function_runner_url = Some(format!("http://127.0.0.1:{port}").parse()?);
+    setup_backend_with_config(runtime, config, persistence).await
+}
+
+async fn setup_backend_with_config(
+    runtime: ProdRuntime,
+    config: LocalConfig,
+    persistence: TestPersistence,
+) -> anyhow::Result<TestLocalBackend> {
     let (preempt_tx, _preempt_rx) = async_broadcast::broadcast(1);
     let (_shutdown_tx, shutdown_rx) = async_broadcast::broadcast(1);
-    let persistence = TestPersistence::new();
-    let config = LocalConfig::new_for_test()?;
     let st = make_app(
         runtime,
         config.clone(),
@@ -72,6 +135,18 @@ pub async fn setup_backend_for_test(runtime: ProdRuntime) -> anyhow::Result<Test
 }
 
 impl TestLocalBackend {
+    /// An `Authorization` header for an admin key restricted to `scope`.
+    pub fn scoped_admin_auth_header(
+        &self,
+        scope: AdminKeyScope,
+    ) -> anyhow::Result<Authorization<ConvexAdminAuthorization>> {
+        self.st
+            .application
+            .key_broker()
+            .issue_scoped_admin_key(MemberId(2), scope, None)
+            .as_header()
+    }
+
     pub async fn expect_success<T: DeserializeOwned>(
         &self,
         req: Request<hyper::Body>,
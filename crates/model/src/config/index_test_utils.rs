@@ -93,6 +93,8 @@ macro_rules! db_schema_with_indexes {
                             common::schemas::IndexSchema {
                                 index_descriptor: index_name.descriptor().clone(),
                                 fields: field_paths.try_into()?,
+                                unique: false,
+                                filter: None,
                             },
                         );
                     )*
@@ -101,6 +103,8 @@ macro_rules! db_schema_with_indexes {
                         indexes,
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
+                        aggregate_indexes: Default::default(),
+                        references: Default::default(),
                         document_type: None,
                     };
                     tables.insert(table_name, table_def);
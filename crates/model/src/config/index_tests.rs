@@ -90,6 +90,8 @@ macro_rules! db_schema_with_search_indexes {
                         indexes: BTreeMap::new(),
                         search_indexes,
                         vector_indexes: Default::default(),
+                        aggregate_indexes: Default::default(),
+                        references: Default::default(),
                         document_type: None,
                     };
                     tables.insert(table_name, table_def);
@@ -1132,6 +1134,19 @@ fn assert_index_data(actual: Vec<IndexConfig>, expected: Vec<TestIndexConfig>) {
                 };
                 TestIndexConfig(developer_config.vector_field.to_string(), vector_state)
             },
+            IndexConfig::Aggregate {
+                developer_config,
+                on_disk_state,
+            } => {
+                let aggregate_state = match on_disk_state {
+                    DatabaseIndexState::Backfilling(_) => TestIndexState::Backfilling,
+                    DatabaseIndexState::Backfilled => TestIndexState::Backfilled,
+                    DatabaseIndexState::Enabled => TestIndexState::Enabled,
+                };
+                assert_eq!(developer_config.fields.len(), 1);
+                let field_name = &developer_config.fields[0];
+                TestIndexConfig(field_name.to_string(), aggregate_state)
+            },
         })
         .collect();
 
@@ -18,7 +18,16 @@ pub fn compute_next_ts(
     prev_ts: Option<Timestamp>,
     now: Timestamp,
 ) -> anyhow::Result<Timestamp> {
-    let cron: Cron = match cron_spec.cron_schedule.clone() {
+    next_ts_for_schedule(&cron_spec.cron_schedule, prev_ts, now)
+}
+
+/// Like [`compute_next_ts`], for schedules that aren't attached to a cron job.
+pub fn next_ts_for_schedule(
+    cron_schedule: &CronSchedule,
+    prev_ts: Option<Timestamp>,
+    now: Timestamp,
+) -> anyhow::Result<Timestamp> {
+    let cron: Cron = match cron_schedule.clone() {
         CronSchedule::Interval { seconds } => {
             let next_ts = match prev_ts {
                 Some(prev_ts) => prev_ts.add(Duration::from_secs(seconds as u64))?,
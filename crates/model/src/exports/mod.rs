@@ -1,20 +1,45 @@
 use std::sync::LazyLock;
 
+use anyhow::Context;
 use common::{
     document::{
         ParsedDocument,
         ResolvedDocument,
     },
+    query::{
+        IndexRange,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
     types::IndexName,
 };
-use database::defaults::system_index;
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use sync_types::Timestamp;
 use value::{
+    id_v6::DeveloperDocumentId,
     FieldPath,
+    ResolvedDocumentId,
     TableName,
+    TableNamespace,
 };
 
-use self::types::Export;
+use self::types::{
+    Export,
+    ExportSchedule,
+    ScheduledExport,
+};
 use crate::{
+    cron_jobs::{
+        next_ts::next_ts_for_schedule,
+        types::CronSchedule,
+    },
     SystemIndex,
     SystemTable,
 };
@@ -62,3 +87,239 @@ impl SystemTable for ExportsTable {
         ParsedDocument::<Export>::try_from(document).map(|_| ())
     }
 }
+
+pub static EXPORT_SCHEDULES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_export_schedules"
+        .parse()
+        .expect("Invalid built-in export schedules table")
+});
+
+pub static EXPORT_SCHEDULES_BY_NEXT_TS_INDEX: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&EXPORT_SCHEDULES_TABLE, "by_next_ts"));
+
+static EXPORT_SCHEDULES_NEXT_TS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "nextTs".parse().expect("Invalid built-in field"));
+
+pub struct ExportSchedulesTable;
+impl SystemTable for ExportSchedulesTable {
+    fn table_name(&self) -> &'static TableName {
+        &EXPORT_SCHEDULES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: EXPORT_SCHEDULES_BY_NEXT_TS_INDEX.clone(),
+            fields: vec![EXPORT_SCHEDULES_NEXT_TS_FIELD.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<ExportSchedule>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct ExportScheduleModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ExportScheduleModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Schedule ZIP exports on `cron_schedule`, keeping the latest
+    /// `retention_count` of them.
+    pub async fn create(
+        &mut self,
+        cron_schedule: CronSchedule,
+        include_storage: bool,
+        parquet: bool,
+        incremental: bool,
+        retention_count: u64,
+        now: Timestamp,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        cron_schedule
+            .validate_format()
+            .map_err(|e| ErrorMetadata::bad_request("InvalidExportSchedule", format!("{e:#}")))?;
+        if retention_count == 0 {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidExportScheduleRetention",
+                "Export schedules must retain at least one export",
+            ));
+        }
+        if incremental && include_storage {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "IncrementalExportWithStorage",
+                "Incremental exports can't include file storage",
+            ));
+        }
+        let schedule = ExportSchedule {
+            next_ts: next_ts_for_schedule(&cron_schedule, None, now)?,
+            cron_schedule,
+            include_storage,
+            parquet,
+            incremental,
+            retention_count,
+            pending_export: None,
+            exports: vec![],
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&EXPORT_SCHEDULES_TABLE, schedule.try_into()?)
+            .await
+    }
+
+    pub async fn get(
+        &mut self,
+        id: ResolvedDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<ExportSchedule>>> {
+        anyhow::ensure!(self
+            .tx
+            .table_mapping()
+            .namespace(TableNamespace::Global)
+            .number_matches_name(id.table().table_number, &EXPORT_SCHEDULES_TABLE));
+        match self.tx.get(id).await? {
+            None => Ok(None),
+            Some(doc) => Ok(Some(doc.try_into()?)),
+        }
+    }
+
+    /// All export schedules, soonest first.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<ExportSchedule>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: EXPORT_SCHEDULES_BY_NEXT_TS_INDEX.clone(),
+            range: vec![],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut schedules = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            schedules.push(doc.try_into()?);
+        }
+        Ok(schedules)
+    }
+
+    /// The schedule that runs soonest, if there are any.
+    pub async fn next(&mut self) -> anyhow::Result<Option<ParsedDocument<ExportSchedule>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: EXPORT_SCHEDULES_BY_NEXT_TS_INDEX.clone(),
+            range: vec![],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .next(self.tx, None)
+            .await?
+            .map(|doc| doc.try_into())
+            .transpose()
+    }
+
+    /// Stop scheduling exports. Exports the schedule already made are kept.
+    pub async fn delete(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        self.get(id).await?.context(ErrorMetadata::not_found(
+            "ExportScheduleNotFound",
+            format!(
+                "Export schedule {} not found",
+                DeveloperDocumentId::from(id).encode()
+            ),
+        ))?;
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        Ok(())
+    }
+
+    /// Record that the schedule requested `export_id`, and find its next run
+    /// after `now`. Runs that were missed are skipped.
+    pub async fn start_run(
+        &mut self,
+        id: ResolvedDocumentId,
+        export_id: ResolvedDocumentId,
+        now: Timestamp,
+    ) -> anyhow::Result<()> {
+        let mut schedule = self
+            .get(id)
+            .await?
+            .context("export schedule not found")?
+            .into_value();
+        let mut next_ts =
+            next_ts_for_schedule(&schedule.cron_schedule, Some(schedule.next_ts), now)?;
+        while next_ts <= now {
+            next_ts = next_ts_for_schedule(&schedule.cron_schedule, Some(next_ts), now)?;
+        }
+        schedule.next_ts = next_ts;
+        schedule.pending_export = Some(export_id.into());
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, schedule.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Record that `export_id` completed at `snapshot_ts`, if a schedule
+    /// requested it. The schedule's exports that are no longer retained are
+    /// expired, so they can't be downloaded anymore.
+    pub async fn complete_run(
+        &mut self,
+        export_id: ResolvedDocumentId,
+        snapshot_ts: Timestamp,
+        base_ts: Option<Timestamp>,
+    ) -> anyhow::Result<()> {
+        let Some(schedule) = self.pending_for(export_id).await? else {
+            return Ok(());
+        };
+        let (id, mut schedule) = schedule.into_id_and_value();
+        schedule.pending_export = None;
+        let dropped = schedule.record_export(ScheduledExport {
+            export_id: export_id.into(),
+            snapshot_ts,
+            base_ts,
+        });
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, schedule.try_into()?)
+            .await?;
+        for dropped_id in dropped {
+            let dropped_id = dropped_id.to_resolved(
+                &self
+                    .tx
+                    .table_mapping()
+                    .namespace(TableNamespace::Global)
+                    .inject_table_id(),
+            )?;
+            let Some(doc) = self.tx.get(dropped_id).await? else {
+                continue;
+            };
+            let export: ParsedDocument<Export> = doc.try_into()?;
+            let now = u64::from(*self.tx.begin_timestamp());
+            let expired = export.into_value().expire(now);
+            SystemMetadataModel::new_global(self.tx)
+                .replace(dropped_id, expired.try_into()?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Record that `export_id` failed, if a schedule requested it. The
+    /// schedule tries again at its next run.
+    pub async fn fail_run(&mut self, export_id: ResolvedDocumentId) -> anyhow::Result<()> {
+        let Some(schedule) = self.pending_for(export_id).await? else {
+            return Ok(());
+        };
+        let (id, mut schedule) = schedule.into_id_and_value();
+        schedule.pending_export = None;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, schedule.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    async fn pending_for(
+        &mut self,
+        export_id: ResolvedDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<ExportSchedule>>> {
+        let export_id = DeveloperDocumentId::from(export_id);
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|schedule| schedule.pending_export == Some(export_id)))
+    }
+}
@@ -15,11 +15,14 @@ use common::{
 use maplit::btreemap;
 use sync_types::Timestamp;
 use value::{
+    id_v6::DeveloperDocumentId,
     val,
     ConvexObject,
     ConvexValue,
 };
 
+use crate::cron_jobs::types::CronSchedule;
+
 const EXPORT_RETENTION: u64 = 14 * 24 * 60 * 60 * 1000000000; // 14 days
 
 #[derive(Clone, Debug, PartialEq)]
@@ -73,8 +76,41 @@ pub enum ExportFormat {
     InternalJson,
     /// jsonl format of clean export Json
     CleanJsonl,
-    /// zip file containing a CleanJsonl for each table, and sidecar type info.
-    Zip { include_storage: bool },
+    /// zip file containing a CleanJsonl (or Parquet file, if `parquet` is
+    /// set) for each table, and sidecar type info.
+    Zip {
+        include_storage: bool,
+        parquet: bool,
+        /// If set, only include the documents that changed after this
+        /// timestamp, which is the snapshot timestamp of an earlier export
+        /// this one is an increment over.
+        base_ts: Option<Timestamp>,
+    },
+    /// Parquet file for each table, with columns derived from the table's
+    /// inferred shape.
+    Parquet,
+    /// CSV file for each table, with nested objects flattened into columns.
+    Csv(CsvExportOptions),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct CsvExportOptions {
+    /// Joins the field names along a nested object's path into a column
+    /// header, e.g. `address.city`.
+    pub path_separator: char,
+    /// Objects nested deeper than this are written as JSON in a single
+    /// column. Zero writes every top-level field as its own column.
+    pub max_depth: u32,
+}
+
+impl Default for CsvExportOptions {
+    fn default() -> Self {
+        Self {
+            path_separator: '.',
+            max_depth: 4,
+        }
+    }
 }
 
 impl Export {
@@ -130,6 +166,29 @@ impl Export {
         }
     }
 
+    /// Expire a completed export at `expiration_ts`, if it doesn't already
+    /// expire sooner.
+    pub fn expire(self, expiration_ts: u64) -> Export {
+        match self {
+            Self::Completed {
+                start_ts,
+                complete_ts,
+                expiration_ts: current_expiration_ts,
+                object_keys,
+                format,
+            } => Self::Completed {
+                start_ts,
+                complete_ts,
+                expiration_ts: current_expiration_ts.min(expiration_ts),
+                object_keys,
+                format,
+            },
+            export @ (Self::Requested { .. } | Self::InProgress { .. } | Self::Failed { .. }) => {
+                export
+            },
+        }
+    }
+
     pub fn failed(self, snapshot_ts: Timestamp, failed_ts: Timestamp) -> anyhow::Result<Export> {
         match self {
             Self::InProgress { format, .. } => {
@@ -350,8 +409,28 @@ impl TryFrom<ExportFormat> for ConvexValue {
         let v = match value {
             ExportFormat::InternalJson => val!("internal_json"),
             ExportFormat::CleanJsonl => val!("clean_jsonl"),
-            ExportFormat::Zip { include_storage } => {
-                val!({"format" => "zip", "include_storage" => include_storage})
+            ExportFormat::Zip {
+                include_storage,
+                parquet,
+                base_ts,
+            } => {
+                val!({
+                    "format" => "zip",
+                    "include_storage" => include_storage,
+                    "parquet" => parquet,
+                    "base_ts" => base_ts.map(i64::from)
+                })
+            },
+            ExportFormat::Parquet => val!("parquet"),
+            ExportFormat::Csv(CsvExportOptions {
+                path_separator,
+                max_depth,
+            }) => {
+                val!({
+                    "format" => "csv",
+                    "path_separator" => path_separator.to_string(),
+                    "max_depth" => max_depth as i64
+                })
             },
         };
         Ok(v)
@@ -368,14 +447,47 @@ impl TryFrom<ConvexValue> for ExportFormat {
                 "clean_jsonl" => Self::CleanJsonl,
                 "zip" => Self::Zip {
                     include_storage: false,
+                    parquet: false,
+                    base_ts: None,
                 },
+                "parquet" => Self::Parquet,
                 _ => anyhow::bail!("invalid format {value:?}"),
             },
             ConvexValue::Object(o) => match o.get("format") {
                 Some(ConvexValue::String(format)) => match &**format {
-                    "zip" => match o.get("include_storage") {
-                        Some(ConvexValue::Boolean(include_storage)) => Self::Zip {
-                            include_storage: *include_storage,
+                    "zip" => match (o.get("include_storage"), o.get("parquet"), o.get("base_ts")) {
+                        (Some(ConvexValue::Boolean(include_storage)), parquet, base_ts) => {
+                            Self::Zip {
+                                include_storage: *include_storage,
+                                parquet: match parquet {
+                                    Some(ConvexValue::Boolean(parquet)) => *parquet,
+                                    None => false,
+                                    _ => anyhow::bail!("invalid format {value:?}"),
+                                },
+                                base_ts: match base_ts {
+                                    Some(ConvexValue::Int64(base_ts)) => {
+                                        Some((*base_ts).try_into()?)
+                                    },
+                                    None | Some(ConvexValue::Null) => None,
+                                    _ => anyhow::bail!("invalid format {value:?}"),
+                                },
+                            }
+                        },
+                        _ => anyhow::bail!("invalid format {value:?}"),
+                    },
+                    "csv" => match (o.get("path_separator"), o.get("max_depth")) {
+                        (
+                            Some(ConvexValue::String(path_separator)),
+                            Some(ConvexValue::Int64(max_depth)),
+                        ) => {
+                            let mut chars = path_separator.chars();
+                            let (Some(path_separator), None) = (chars.next(), chars.next()) else {
+                                anyhow::bail!("invalid format {value:?}");
+                            };
+                            Self::Csv(CsvExportOptions {
+                                path_separator,
+                                max_depth: (*max_depth).try_into()?,
+                            })
                         },
                         _ => anyhow::bail!("invalid format {value:?}"),
                     },
@@ -397,13 +509,214 @@ pub enum ExportObjectKeys {
     Zip(ObjectKey),
 }
 
+/// ZIP exports requested on a recurring schedule. The export worker requests
+/// an export at `next_ts` and keeps the most recent `retention_count`
+/// completed exports.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ExportSchedule {
+    pub cron_schedule: CronSchedule,
+    pub include_storage: bool,
+    pub parquet: bool,
+    /// Export only the documents changed since the schedule's previous
+    /// export. A full export starts a new chain of increments once the
+    /// current chain has `retention_count` exports.
+    pub incremental: bool,
+    pub retention_count: u64,
+    pub next_ts: Timestamp,
+    /// The export requested by the most recent run, until it completes.
+    pub pending_export: Option<DeveloperDocumentId>,
+    /// Completed exports from this schedule, oldest first.
+    pub exports: Vec<ScheduledExport>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ScheduledExport {
+    pub export_id: DeveloperDocumentId,
+    pub snapshot_ts: Timestamp,
+    /// The snapshot timestamp of the export this one is an increment over,
+    /// or `None` for a full export.
+    pub base_ts: Option<Timestamp>,
+}
+
+impl ExportSchedule {
+    /// The timestamp the next export should be an increment over, or `None`
+    /// if it should be a full export.
+    pub fn next_base_ts(&self) -> Option<Timestamp> {
+        if !self.incremental {
+            return None;
+        }
+        let last = self.exports.last()?;
+        let chain_len = self
+            .exports
+            .iter()
+            .rev()
+            .position(|export| export.base_ts.is_none())
+            .map_or(self.exports.len(), |i| i + 1);
+        (chain_len < self.retention_count as usize).then_some(last.snapshot_ts)
+    }
+
+    /// Record a completed export, returning the exports that are no longer
+    /// retained. An increment is only dropped along with the full export it
+    /// builds on, so incremental schedules can keep up to twice
+    /// `retention_count` exports.
+    pub fn record_export(&mut self, export: ScheduledExport) -> Vec<DeveloperDocumentId> {
+        self.exports.push(export);
+        let mut dropped = vec![];
+        while let Some(first_chain_len) = self
+            .exports
+            .iter()
+            .skip(1)
+            .position(|export| export.base_ts.is_none())
+            .map(|i| i + 1)
+            && self.exports.len() - first_chain_len >= self.retention_count as usize
+        {
+            dropped.extend(
+                self.exports
+                    .drain(..first_chain_len)
+                    .map(|export| export.export_id),
+            );
+        }
+        dropped
+    }
+}
+
+impl TryFrom<ExportSchedule> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        ExportSchedule {
+            cron_schedule,
+            include_storage,
+            parquet,
+            incremental,
+            retention_count,
+            next_ts,
+            pending_export,
+            exports,
+        }: ExportSchedule,
+    ) -> anyhow::Result<Self> {
+        let exports: Vec<_> = exports
+            .into_iter()
+            .map(
+                |ScheduledExport {
+                     export_id,
+                     snapshot_ts,
+                     base_ts,
+                 }| {
+                    anyhow::Ok(ConvexValue::Object(obj!(
+                        "exportId" => export_id.encode(),
+                        "snapshotTs" => i64::from(snapshot_ts),
+                        "baseTs" => base_ts.map(i64::from),
+                    )?))
+                },
+            )
+            .try_collect()?;
+        obj!(
+            "cronSchedule" => ConvexValue::try_from(cron_schedule)?,
+            "includeStorage" => include_storage,
+            "parquet" => parquet,
+            "incremental" => incremental,
+            "retentionCount" => retention_count as i64,
+            "nextTs" => i64::from(next_ts),
+            "pendingExport" => pending_export.map(|id| id.encode()),
+            "exports" => ConvexValue::Array(exports.try_into()?),
+        )
+    }
+}
+
+impl TryFrom<ConvexObject> for ExportSchedule {
+    type Error = anyhow::Error;
+
+    fn try_from(object: ConvexObject) -> anyhow::Result<Self> {
+        let mut fields = BTreeMap::from(object);
+        let cron_schedule = fields
+            .remove("cronSchedule")
+            .context("Missing cronSchedule field for ExportSchedule")?
+            .try_into()?;
+        let mut remove_bool = |field: &str| match fields.remove(field) {
+            Some(ConvexValue::Boolean(b)) => Ok(b),
+            v => anyhow::bail!("Invalid {field} field for ExportSchedule: {v:?}"),
+        };
+        let include_storage = remove_bool("includeStorage")?;
+        let parquet = remove_bool("parquet")?;
+        let incremental = remove_bool("incremental")?;
+        let retention_count = match fields.remove("retentionCount") {
+            Some(ConvexValue::Int64(n)) => n as u64,
+            v => anyhow::bail!("Invalid retentionCount field for ExportSchedule: {v:?}"),
+        };
+        let next_ts = match fields.remove("nextTs") {
+            Some(ConvexValue::Int64(ts)) => ts.try_into()?,
+            v => anyhow::bail!("Invalid nextTs field for ExportSchedule: {v:?}"),
+        };
+        let pending_export = match fields.remove("pendingExport") {
+            Some(ConvexValue::String(id)) => Some(DeveloperDocumentId::decode(&id)?),
+            None | Some(ConvexValue::Null) => None,
+            v => anyhow::bail!("Invalid pendingExport field for ExportSchedule: {v:?}"),
+        };
+        let exports = match fields.remove("exports") {
+            Some(ConvexValue::Array(exports)) => exports
+                .into_iter()
+                .map(|export| {
+                    let ConvexValue::Object(export) = export else {
+                        anyhow::bail!("Invalid export for ExportSchedule: {export:?}");
+                    };
+                    let export_id = match export.get("exportId") {
+                        Some(ConvexValue::String(id)) => DeveloperDocumentId::decode(id)?,
+                        v => anyhow::bail!("Invalid exportId for ExportSchedule: {v:?}"),
+                    };
+                    let snapshot_ts = match export.get("snapshotTs") {
+                        Some(ConvexValue::Int64(ts)) => (*ts).try_into()?,
+                        v => anyhow::bail!("Invalid snapshotTs for ExportSchedule: {v:?}"),
+                    };
+                    let base_ts = match export.get("baseTs") {
+                        Some(ConvexValue::Int64(ts)) => Some((*ts).try_into()?),
+                        None | Some(ConvexValue::Null) => None,
+                        v => anyhow::bail!("Invalid baseTs for ExportSchedule: {v:?}"),
+                    };
+                    Ok(ScheduledExport {
+                        export_id,
+                        snapshot_ts,
+                        base_ts,
+                    })
+                })
+                .try_collect()?,
+            v => anyhow::bail!("Invalid exports field for ExportSchedule: {v:?}"),
+        };
+        Ok(Self {
+            cron_schedule,
+            include_storage,
+            parquet,
+            incremental,
+            retention_count,
+            next_ts,
+            pending_export,
+            exports,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
-    use sync_types::testing::assert_roundtrips;
-    use value::ConvexObject;
+    use sync_types::{
+        testing::assert_roundtrips,
+        Timestamp,
+    };
+    use value::{
+        id_v6::DeveloperDocumentId,
+        ConvexObject,
+        InternalId,
+        TableNumber,
+    };
 
-    use super::Export;
+    use super::{
+        Export,
+        ExportSchedule,
+        ScheduledExport,
+    };
+    use crate::cron_jobs::types::CronSchedule;
 
     proptest! {
         #![proptest_config(
@@ -414,5 +727,83 @@ mod tests {
         fn test_export_roundtrip(v in any::<Export>()) {
             assert_roundtrips::<Export, ConvexObject>(v);
         }
+
+        #[test]
+        fn test_export_schedule_roundtrip(v in any::<ExportSchedule>()) {
+            assert_roundtrips::<ExportSchedule, ConvexObject>(v);
+        }
+    }
+
+    fn scheduled_export(i: u8, base_ts: Option<i32>) -> ScheduledExport {
+        ScheduledExport {
+            export_id: DeveloperDocumentId::new(
+                TableNumber::try_from(100).unwrap(),
+                InternalId::from([i; 16]),
+            ),
+            snapshot_ts: Timestamp::must(i as i32),
+            base_ts: base_ts.map(Timestamp::must),
+        }
+    }
+
+    #[test]
+    fn test_export_schedule_retention() {
+        let mut schedule = ExportSchedule {
+            cron_schedule: CronSchedule::Interval { seconds: 60 },
+            include_storage: false,
+            parquet: false,
+            incremental: true,
+            retention_count: 3,
+            next_ts: Timestamp::must(0),
+            pending_export: None,
+            exports: vec![],
+        };
+        // The first export is full, and the next two build on it.
+        assert_eq!(schedule.next_base_ts(), None);
+        assert!(schedule.record_export(scheduled_export(1, None)).is_empty());
+        assert_eq!(schedule.next_base_ts(), Some(Timestamp::must(1)));
+        assert!(schedule
+            .record_export(scheduled_export(2, Some(1)))
+            .is_empty());
+        assert_eq!(schedule.next_base_ts(), Some(Timestamp::must(2)));
+        assert!(schedule
+            .record_export(scheduled_export(3, Some(2)))
+            .is_empty());
+
+        // The chain is full, so start another one. The first chain is kept
+        // until the second one has `retention_count` exports.
+        assert_eq!(schedule.next_base_ts(), None);
+        assert!(schedule.record_export(scheduled_export(4, None)).is_empty());
+        assert!(schedule
+            .record_export(scheduled_export(5, Some(4)))
+            .is_empty());
+        let dropped = schedule.record_export(scheduled_export(6, Some(5)));
+        assert_eq!(
+            dropped,
+            vec![
+                scheduled_export(1, None).export_id,
+                scheduled_export(2, Some(1)).export_id,
+                scheduled_export(3, Some(2)).export_id,
+            ]
+        );
+        assert_eq!(schedule.exports.len(), 3);
+
+        // Without increments, every export is a chain of its own.
+        schedule.incremental = false;
+        assert_eq!(schedule.next_base_ts(), None);
+        assert!(schedule.record_export(scheduled_export(7, None)).is_empty());
+        assert!(schedule.record_export(scheduled_export(8, None)).is_empty());
+        let dropped = schedule.record_export(scheduled_export(9, None));
+        assert_eq!(
+            dropped,
+            vec![
+                scheduled_export(4, None).export_id,
+                scheduled_export(5, Some(4)).export_id,
+                scheduled_export(6, Some(5)).export_id,
+            ]
+        );
+        assert_eq!(
+            schedule.record_export(scheduled_export(10, None)),
+            vec![scheduled_export(7, None).export_id]
+        );
     }
 }
@@ -53,6 +53,7 @@ pub use database::defaults::{
     SystemTable,
 };
 use database::{
+    AggregateIndexCountsTable,
     ComponentDefinitionsTable,
     ComponentsTable,
     Database,
@@ -86,17 +87,23 @@ use crate::{
     },
     deployment_audit_log::DeploymentAuditLogsTable,
     environment_variables::EnvironmentVariablesTable,
-    exports::ExportsTable,
+    exports::{
+        ExportSchedulesTable,
+        ExportsTable,
+    },
     external_packages::ExternalPackagesTable,
     file_storage::FileStorageTable,
+    migrations::MigrationsTable,
     modules::{
         ModuleVersionsTable,
         ModulesTable,
     },
+    revoked_admin_keys::RevokedAdminKeysTable,
     scheduled_jobs::ScheduledJobsTable,
     session_requests::SessionRequestsTable,
     snapshot_imports::SnapshotImportsTable,
     source_packages::SourcePackagesTable,
+    table_restores::TableRestoresTable,
     udf_config::UdfConfigTable,
 };
 
@@ -110,11 +117,14 @@ pub mod environment_variables;
 pub mod exports;
 pub mod external_packages;
 pub mod file_storage;
+pub mod migrations;
 pub mod modules;
+pub mod revoked_admin_keys;
 pub mod scheduled_jobs;
 pub mod session_requests;
 pub mod snapshot_imports;
 pub mod source_packages;
+pub mod table_restores;
 pub mod udf_config;
 
 #[cfg(any(test, feature = "testing"))]
@@ -151,9 +161,14 @@ enum DefaultTableNumber {
     IndexWorkerMetadata = 30,
     ComponentDefinitionsTable = 31,
     ComponentsTable = 32,
+    RevokedAdminKeys = 33,
+    Migrations = 34,
+    ExportSchedules = 35,
+    TableRestores = 36,
+    AggregateIndexCounts = 37,
     // Keep this number and your user name up to date. The number makes it easy to know
     // what to use next. The username on the same line detects merge conflicts
-    // Next Number - 33 - lee
+    // Next Number - 38 - agent
 }
 
 impl From<DefaultTableNumber> for TableNumber {
@@ -192,6 +207,11 @@ impl From<DefaultTableNumber> for TableName {
             DefaultTableNumber::IndexWorkerMetadata => IndexWorkerMetadataTable.table_name(),
             DefaultTableNumber::ComponentDefinitionsTable => ComponentDefinitionsTable.table_name(),
             DefaultTableNumber::ComponentsTable => ComponentsTable.table_name(),
+            DefaultTableNumber::RevokedAdminKeys => RevokedAdminKeysTable.table_name(),
+            DefaultTableNumber::Migrations => MigrationsTable.table_name(),
+            DefaultTableNumber::ExportSchedules => ExportSchedulesTable.table_name(),
+            DefaultTableNumber::TableRestores => TableRestoresTable.table_name(),
+            DefaultTableNumber::AggregateIndexCounts => AggregateIndexCountsTable.table_name(),
         }
         .clone()
     }
@@ -327,6 +347,11 @@ pub fn app_system_tables() -> Vec<&'static dyn SystemTable> {
         &BackendStateTable,
         &ExportsTable,
         &SnapshotImportsTable,
+        &RevokedAdminKeysTable,
+        &MigrationsTable,
+        &ExportSchedulesTable,
+        &TableRestoresTable,
+        &AggregateIndexCountsTable,
     ]
 }
 
@@ -0,0 +1,372 @@
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    knobs::TRANSACTION_MAX_NUM_USER_WRITES,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    TableModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use sync_types::CanonicalizedUdfPath;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    Migration,
+    MigrationProgress,
+    MigrationState,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static MIGRATIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_migrations"
+        .parse()
+        .expect("Invalid built-in migrations table")
+});
+
+pub static MIGRATIONS_INDEX_BY_STATE: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&MIGRATIONS_TABLE, "by_state"));
+static STATE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "state".parse().expect("invalid state field"));
+
+pub struct MigrationsTable;
+impl SystemTable for MigrationsTable {
+    fn table_name(&self) -> &'static TableName {
+        &MIGRATIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: MIGRATIONS_INDEX_BY_STATE.clone(),
+            fields: vec![STATE_FIELD.clone()].try_into().unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<Migration>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct MigrationModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> MigrationModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Start migrating `table_name` with `transform`, `batch_size` documents
+    /// at a time. Only one migration can be active on a table at a time.
+    pub async fn start(
+        &mut self,
+        table_name: TableName,
+        transform: CanonicalizedUdfPath,
+        batch_size: u64,
+        dry_run: bool,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        let max_batch_size = TRANSACTION_MAX_NUM_USER_WRITES.get() as u64;
+        if batch_size == 0 || batch_size > max_batch_size {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidMigrationBatchSize",
+                format!("Batch size must be between 1 and {max_batch_size}, got {batch_size}"),
+            ));
+        }
+        if table_name.is_system()
+            || !TableModel::new(self.tx)
+                .table_exists(TableNamespace::by_component_TODO(), &table_name)
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "MigrationTableNotFound",
+                format!("Table \"{table_name}\" doesn't exist"),
+            ));
+        }
+        if let Some(active) = self
+            .active()
+            .await?
+            .into_iter()
+            .find(|migration| migration.table_name == table_name)
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "MigrationAlreadyRunning",
+                format!(
+                    "Migration {} is already running on table \"{table_name}\"",
+                    active.developer_id().encode()
+                ),
+            ));
+        }
+        let migration = Migration {
+            table_name,
+            transform,
+            batch_size,
+            dry_run,
+            state: MigrationState::Running,
+            cursor: None,
+            progress: MigrationProgress::default(),
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&MIGRATIONS_TABLE, migration.try_into()?)
+            .await
+    }
+
+    pub async fn get(
+        &mut self,
+        id: ResolvedDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<Migration>>> {
+        anyhow::ensure!(self
+            .tx
+            .table_mapping()
+            .namespace(TableNamespace::Global)
+            .number_matches_name(id.table().table_number, &MIGRATIONS_TABLE));
+        match self.tx.get(id).await? {
+            None => Ok(None),
+            Some(doc) => Ok(Some(doc.try_into()?)),
+        }
+    }
+
+    /// All migrations, most recently started first.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<Migration>>> {
+        let query = Query::full_table_scan(MIGRATIONS_TABLE.clone(), Order::Desc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut migrations = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            migrations.push(doc.try_into()?);
+        }
+        Ok(migrations)
+    }
+
+    /// Migrations that are running or verifying, oldest first.
+    pub async fn active(&mut self) -> anyhow::Result<Vec<ParsedDocument<Migration>>> {
+        let mut migrations = vec![];
+        for state in [MigrationState::Running, MigrationState::Verifying] {
+            let query = Query::index_range(IndexRange {
+                index_name: MIGRATIONS_INDEX_BY_STATE.clone(),
+                range: vec![IndexRangeExpression::Eq(
+                    STATE_FIELD.clone(),
+                    ConvexValue::try_from(state.as_str())?.into(),
+                )],
+                order: Order::Asc,
+            });
+            let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+            while let Some(doc) = query_stream.next(self.tx, None).await? {
+                migrations.push(doc.try_into()?);
+            }
+        }
+        migrations.sort_by_key(|migration: &ParsedDocument<Migration>| migration.creation_time());
+        Ok(migrations)
+    }
+
+    /// Record that the current phase has processed the documents up to and
+    /// including `cursor`.
+    pub async fn record_batch(
+        &mut self,
+        id: ResolvedDocumentId,
+        cursor: DeveloperDocumentId,
+        progress: MigrationProgress,
+    ) -> anyhow::Result<()> {
+        self.update(id, |migration| {
+            anyhow::ensure!(migration.state.is_active());
+            migration.cursor = Some(cursor);
+            migration.progress = progress;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Start checking the table against the target schema from its first
+    /// document. Invalid documents are counted again from scratch.
+    pub async fn start_verifying(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        self.update(id, |migration| {
+            anyhow::ensure!(migration.state == MigrationState::Running);
+            migration.state = MigrationState::Verifying;
+            migration.cursor = None;
+            migration.progress.documents_invalid = 0;
+            migration.progress.first_invalid = None;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn complete(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        self.update(id, |migration| {
+            anyhow::ensure!(migration.state.is_active());
+            migration.state = MigrationState::Completed;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn fail(&mut self, id: ResolvedDocumentId, error: String) -> anyhow::Result<()> {
+        self.update(id, |migration| {
+            anyhow::ensure!(migration.state.is_active());
+            migration.state = MigrationState::Failed { error };
+            Ok(())
+        })
+        .await
+    }
+
+    /// Stop an active migration. Batches that have already been written stay
+    /// written.
+    pub async fn cancel(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        let migration = self.get(id).await?.context(not_found_error(id))?;
+        if !migration.state.is_active() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "MigrationNotActive",
+                format!(
+                    "Migration {} is already {}",
+                    migration.developer_id().encode(),
+                    migration.state.as_str()
+                ),
+            ));
+        }
+        self.update(id, |migration| {
+            migration.state = MigrationState::Canceled;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update(
+        &mut self,
+        id: ResolvedDocumentId,
+        update: impl FnOnce(&mut Migration) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let mut migration = self
+            .get(id)
+            .await?
+            .context(not_found_error(id))?
+            .into_value();
+        update(&mut migration)?;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, migration.try_into()?)
+            .await?;
+        Ok(())
+    }
+}
+
+fn not_found_error(id: ResolvedDocumentId) -> ErrorMetadata {
+    ErrorMetadata::not_found(
+        "MigrationNotFound",
+        format!(
+            "Migration {} not found",
+            DeveloperDocumentId::from(id).encode()
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use common::types::TableName;
+    use database::{
+        test_helpers::DbFixtures,
+        UserFacingModel,
+    };
+    use keybroker::Identity;
+    use runtime::testing::TestRuntime;
+    use value::assert_obj;
+
+    use crate::{
+        migrations::{
+            types::{
+                MigrationProgress,
+                MigrationState,
+            },
+            MigrationModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_migration_lifecycle(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let table_name: TableName = "messages".parse()?;
+        let mut tx = db.begin(Identity::system()).await?;
+        let message_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(table_name.clone(), assert_obj!("body" => "hi"))
+            .await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = MigrationModel::new(&mut tx);
+        let transform = "migrations:transform".parse()?;
+        assert!(model
+            .start(
+                "missing".parse()?,
+                "migrations:transform".parse()?,
+                10,
+                false
+            )
+            .await
+            .is_err());
+        assert!(model
+            .start(
+                table_name.clone(),
+                "migrations:transform".parse()?,
+                0,
+                false
+            )
+            .await
+            .is_err());
+        let id = model
+            .start(table_name.clone(), transform, 10, false)
+            .await?;
+        assert!(model
+            .start(
+                table_name.clone(),
+                "migrations:transform".parse()?,
+                10,
+                false
+            )
+            .await
+            .is_err());
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = MigrationModel::new(&mut tx);
+        assert_eq!(model.active().await?.len(), 1);
+        let progress = MigrationProgress {
+            documents_processed: 1,
+            documents_changed: 1,
+            ..Default::default()
+        };
+        model.record_batch(id, message_id, progress.clone()).await?;
+        model.start_verifying(id).await?;
+        let migration = model.get(id).await?.unwrap();
+        assert_eq!(migration.state, MigrationState::Verifying);
+        assert_eq!(migration.cursor, None);
+        assert_eq!(migration.progress, progress);
+        model.cancel(id).await?;
+        assert!(model.cancel(id).await.is_err());
+        assert!(model.active().await?.is_empty());
+        assert_eq!(model.list().await?.len(), 1);
+        Ok(())
+    }
+}
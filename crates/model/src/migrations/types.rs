@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use sync_types::CanonicalizedUdfPath;
+use value::{
+    id_v6::DeveloperDocumentId,
+    obj,
+    ConvexObject,
+    ConvexValue,
+    TableName,
+};
+
+/// A migration rewriting every document in `table_name` with the mutation
+/// `transform`. Migrations run in batches in the background and pick up from
+/// `cursor` after a restart.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct Migration {
+    pub table_name: TableName,
+    /// Mutation called with `{ documents }` for each batch, returning an array
+    /// with the replacement for each document, or `null` to leave it as is.
+    pub transform: CanonicalizedUdfPath,
+    pub batch_size: u64,
+    /// Run the transform and check its results without writing them.
+    pub dry_run: bool,
+    pub state: MigrationState,
+    /// The last document the current phase has processed, or `None` if it
+    /// hasn't processed any yet.
+    pub cursor: Option<DeveloperDocumentId>,
+    pub progress: MigrationProgress,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum MigrationState {
+    /// Transforming the table's documents.
+    Running,
+    /// Checking every document in the table against the target schema.
+    Verifying,
+    Completed,
+    Failed {
+        error: String,
+    },
+    Canceled,
+}
+
+impl MigrationState {
+    pub fn is_active(&self) -> bool {
+        matches!(self, MigrationState::Running | MigrationState::Verifying)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationState::Running => "running",
+            MigrationState::Verifying => "verifying",
+            MigrationState::Completed => "completed",
+            MigrationState::Failed { .. } => "failed",
+            MigrationState::Canceled => "canceled",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct MigrationProgress {
+    /// Documents passed to the transform.
+    pub documents_processed: u64,
+    /// Documents the transform returned a replacement for.
+    pub documents_changed: u64,
+    /// Documents checked against the target schema while verifying.
+    pub documents_verified: u64,
+    /// Documents that don't match the target schema: transformed documents
+    /// while running, and documents in the table while verifying.
+    pub documents_invalid: u64,
+    /// Why the first invalid document doesn't match the target schema.
+    pub first_invalid: Option<String>,
+}
+
+impl TryFrom<Migration> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        Migration {
+            table_name,
+            transform,
+            batch_size,
+            dry_run,
+            state,
+            cursor,
+            progress,
+        }: Migration,
+    ) -> anyhow::Result<Self> {
+        let error = match &state {
+            MigrationState::Failed { error } => Some(error.clone()),
+            _ => None,
+        };
+        obj!(
+            "table" => String::from(table_name),
+            "transform" => String::from(transform),
+            "batchSize" => batch_size as i64,
+            "dryRun" => dry_run,
+            "state" => state.as_str(),
+            "error" => error,
+            "cursor" => cursor.map(|id| id.encode()),
+            "documentsProcessed" => progress.documents_processed as i64,
+            "documentsChanged" => progress.documents_changed as i64,
+            "documentsVerified" => progress.documents_verified as i64,
+            "documentsInvalid" => progress.documents_invalid as i64,
+            "firstInvalid" => progress.first_invalid,
+        )
+    }
+}
+
+impl TryFrom<ConvexObject> for Migration {
+    type Error = anyhow::Error;
+
+    fn try_from(object: ConvexObject) -> anyhow::Result<Self> {
+        let mut fields = BTreeMap::from(object);
+        let mut remove_string = |field: &str| match fields.remove(field) {
+            Some(ConvexValue::String(s)) => Ok(Some(String::from(s))),
+            None | Some(ConvexValue::Null) => Ok(None),
+            v => anyhow::bail!("Invalid {field} field for Migration: {v:?}"),
+        };
+        let table_name = remove_string("table")?
+            .context("Missing table field for Migration")?
+            .parse()?;
+        let transform = remove_string("transform")?
+            .context("Missing transform field for Migration")?
+            .parse()?;
+        let state = remove_string("state")?.context("Missing state field for Migration")?;
+        let error = remove_string("error")?;
+        let cursor = remove_string("cursor")?
+            .map(|id| DeveloperDocumentId::decode(&id))
+            .transpose()?;
+        let first_invalid = remove_string("firstInvalid")?;
+        let state = match (&*state, error) {
+            ("running", None) => MigrationState::Running,
+            ("verifying", None) => MigrationState::Verifying,
+            ("completed", None) => MigrationState::Completed,
+            ("failed", Some(error)) => MigrationState::Failed { error },
+            ("canceled", None) => MigrationState::Canceled,
+            (state, error) => {
+                anyhow::bail!("Invalid state {state} for Migration with error {error:?}")
+            },
+        };
+        let dry_run = match fields.remove("dryRun") {
+            Some(ConvexValue::Boolean(b)) => b,
+            v => anyhow::bail!("Invalid dryRun field for Migration: {v:?}"),
+        };
+        let mut remove_count = |field: &str| match fields.remove(field) {
+            Some(ConvexValue::Int64(n)) => Ok(n as u64),
+            v => anyhow::bail!("Invalid {field} field for Migration: {v:?}"),
+        };
+        let batch_size = remove_count("batchSize")?;
+        let progress = MigrationProgress {
+            documents_processed: remove_count("documentsProcessed")?,
+            documents_changed: remove_count("documentsChanged")?,
+            documents_verified: remove_count("documentsVerified")?,
+            documents_invalid: remove_count("documentsInvalid")?,
+            first_invalid,
+        };
+        Ok(Self {
+            table_name,
+            transform,
+            batch_size,
+            dry_run,
+            state,
+            cursor,
+            progress,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+    };
+
+    use super::Migration;
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_migration_to_object_roundtrip(m in any::<Migration>()) {
+            assert_roundtrips::<Migration, ConvexObject>(m);
+        }
+    }
+}
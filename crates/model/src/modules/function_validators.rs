@@ -33,6 +33,22 @@ pub enum ArgsValidator {
 }
 
 impl ArgsValidator {
+    /// Fills in the `default`s of missing arguments. Args that aren't a single
+    /// object are left for `check_args` to reject.
+    pub fn apply_defaults(&self, args: ConvexArray) -> anyhow::Result<ConvexArray> {
+        let ArgsValidator::Validated(object_validator) = self else {
+            return Ok(args);
+        };
+        if !object_validator.has_defaults() {
+            return Ok(args);
+        }
+        let mut args = Vec::from(args);
+        if let [ConvexValue::Object(object_arg)] = &mut args[..] {
+            *object_arg = object_validator.apply_defaults(object_arg.clone())?;
+        }
+        args.try_into()
+    }
+
     pub fn check_args(
         &self,
         args: &ConvexArray,
@@ -0,0 +1,167 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    revoked_admin_keys::types::RevokedAdminKey,
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static REVOKED_ADMIN_KEYS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_revoked_admin_keys"
+        .parse()
+        .expect("Invalid built-in revoked admin keys table")
+});
+
+pub static REVOKED_ADMIN_KEYS_INDEX_BY_KEY_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&REVOKED_ADMIN_KEYS_TABLE, "by_key_id"));
+static KEY_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "keyId".parse().expect("invalid keyId field"));
+
+pub struct RevokedAdminKeysTable;
+impl SystemTable for RevokedAdminKeysTable {
+    fn table_name(&self) -> &'static TableName {
+        &REVOKED_ADMIN_KEYS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: REVOKED_ADMIN_KEYS_INDEX_BY_KEY_ID.clone(),
+            fields: vec![KEY_ID_FIELD.clone()].try_into().unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<RevokedAdminKey>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct RevokedAdminKeysModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> RevokedAdminKeysModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Revoke the admin key with `key_id`. Revoking a key twice is a no-op.
+    pub async fn revoke(&mut self, key_id: String) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() && !self.tx.identity().is_admin() {
+            anyhow::bail!(unauthorized_error("revoke"));
+        }
+        if self.is_revoked(&key_id).await? {
+            return Ok(());
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert(
+                &REVOKED_ADMIN_KEYS_TABLE,
+                RevokedAdminKey { key_id }.try_into()?,
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn is_revoked(&mut self, key_id: &str) -> anyhow::Result<bool> {
+        let query = Query::index_range(IndexRange {
+            index_name: REVOKED_ADMIN_KEYS_INDEX_BY_KEY_ID.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                KEY_ID_FIELD.clone(),
+                ConvexValue::try_from(key_id)?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        Ok(query_stream.expect_at_most_one(self.tx).await?.is_some())
+    }
+
+    /// Fails if `identity` is backed by an admin key that has been revoked.
+    pub async fn check_not_revoked(&mut self, identity: &Identity) -> anyhow::Result<()> {
+        let Some(key_id) = identity.admin_key_id() else {
+            return Ok(());
+        };
+        if self.is_revoked(key_id).await? {
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "AdminKeyRevoked",
+                "The provided admin key has been revoked.",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn unauthorized_error(op: &'static str) -> ErrorMetadata {
+    ErrorMetadata::forbidden(
+        "Unauthorized",
+        format!("You aren't authorized to {op} admin keys"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use common::types::MemberId;
+    use database::test_helpers::DbFixtures;
+    use keybroker::{
+        Identity,
+        KeyBroker,
+    };
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        revoked_admin_keys::RevokedAdminKeysModel,
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_revoke_admin_key(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let kb = KeyBroker::dev();
+        let identity = kb.check_admin_key(&kb.issue_admin_key(MemberId(0)).to_string())?;
+        let other_identity = kb.check_admin_key(&kb.issue_admin_key(MemberId(0)).to_string())?;
+        let key_id = identity.admin_key_id().unwrap().to_string();
+
+        let mut tx = db.begin(identity.clone()).await?;
+        let mut model = RevokedAdminKeysModel::new(&mut tx);
+        model.check_not_revoked(&identity).await?;
+        model.revoke(key_id.clone()).await?;
+        model.revoke(key_id).await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = RevokedAdminKeysModel::new(&mut tx);
+        assert!(model.check_not_revoked(&identity).await.is_err());
+        model.check_not_revoked(&other_identity).await?;
+        model.check_not_revoked(&Identity::system()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+
+use value::{
+    obj,
+    ConvexObject,
+    ConvexValue,
+};
+
+/// A record that the admin key with `key_id` may no longer be used.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct RevokedAdminKey {
+    pub key_id: String,
+}
+
+impl TryFrom<RevokedAdminKey> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(RevokedAdminKey { key_id }: RevokedAdminKey) -> anyhow::Result<ConvexObject> {
+        obj!("keyId" => key_id)
+    }
+}
+
+impl TryFrom<ConvexObject> for RevokedAdminKey {
+    type Error = anyhow::Error;
+
+    fn try_from(obj: ConvexObject) -> anyhow::Result<RevokedAdminKey> {
+        let mut fields = BTreeMap::from(obj);
+        let key_id = match fields.remove("keyId") {
+            Some(ConvexValue::String(s)) => s.into(),
+            v => anyhow::bail!("Invalid keyId field for RevokedAdminKey: {v:?}"),
+        };
+        Ok(Self { key_id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use proptest::prelude::*;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+    };
+
+    use super::RevokedAdminKey;
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_revoked_admin_key_to_object_roundtrip(k in any::<RevokedAdminKey>()) {
+            assert_roundtrips::<RevokedAdminKey, ConvexObject>(k);
+        }
+    }
+}
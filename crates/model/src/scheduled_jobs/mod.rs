@@ -173,25 +173,25 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
     fn check_scheduling_limits(&mut self, args: &ConvexArray) -> anyhow::Result<()> {
         // Limit how much you can schedule from a single transaction.
         anyhow::ensure!(
-            self.tx.scheduled_size.num_writes < *TRANSACTION_MAX_NUM_SCHEDULED,
+            self.tx.scheduled_size.num_writes < TRANSACTION_MAX_NUM_SCHEDULED.get(),
             ErrorMetadata::bad_request(
                 "TooManyFunctionsScheduled",
                 format!(
                     "Too many functions scheduled by this mutation (limit: {})",
-                    *TRANSACTION_MAX_NUM_SCHEDULED,
+                    TRANSACTION_MAX_NUM_SCHEDULED.get(),
                 )
             )
         );
         self.tx.scheduled_size.num_writes += 1;
         anyhow::ensure!(
             self.tx.scheduled_size.size + args.size()
-                <= *TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES,
+                <= TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES.get(),
             ErrorMetadata::bad_request(
                 "ScheduledFunctionsArgumentsTooLarge",
                 format!(
                     "Too large total size of the arguments of scheduled functions from this \
                      mutation (limit: {} bytes)",
-                    *TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES,
+                    TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES.get(),
                 )
             ),
         );
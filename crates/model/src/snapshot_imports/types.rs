@@ -1,4 +1,7 @@
-use std::str::FromStr;
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+};
 
 use anyhow::Context;
 use common::{
@@ -30,12 +33,92 @@ pub struct SnapshotImport {
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub enum ImportFormat {
-    Csv(TableName),
+    Csv(TableName, CsvImportOptions),
     JsonLines(TableName),
     JsonArray(TableName),
+    Parquet(TableName),
     Zip,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[serde(default, rename_all = "camelCase")]
+pub struct CsvImportOptions {
+    /// Must be an ASCII character.
+    pub delimiter: char,
+    /// Must be an ASCII character.
+    pub quote: char,
+    /// Renames headers before they're turned into field paths.
+    pub header_mapping: BTreeMap<String, String>,
+    /// If set, headers are split on this separator into a path of nested
+    /// fields, e.g. `address.city` imports as `{ address: { city } }`.
+    pub path_separator: Option<char>,
+    /// Types for columns, keyed by (unmapped) header. Cells in other columns
+    /// are imported as numbers if they parse as one and as strings otherwise.
+    pub column_types: BTreeMap<String, CsvColumnType>,
+    pub null_handling: CsvNullHandling,
+}
+
+impl Default for CsvImportOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            header_mapping: BTreeMap::new(),
+            path_separator: None,
+            column_types: BTreeMap::new(),
+            null_handling: CsvNullHandling::default(),
+        }
+    }
+}
+
+impl CsvImportOptions {
+    /// The path of (not yet validated) field names a header is imported into.
+    pub fn field_path(&self, header: &str) -> Vec<String> {
+        let header = self
+            .header_mapping
+            .get(header)
+            .map(String::as_str)
+            .unwrap_or(header);
+        match self.path_separator {
+            Some(separator) => header.split(separator).map(str::to_string).collect(),
+            None => vec![header.to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Eq, PartialEq, strum::EnumString, strum::Display)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum CsvColumnType {
+    String,
+    Float64,
+    Int64,
+    Boolean,
+    /// Base64 encoded.
+    Bytes,
+    /// Clean export JSON, as in JSONL imports.
+    Json,
+}
+
+/// How empty cells are imported. CSV can't distinguish an empty string from a
+/// missing value.
+#[derive(
+    Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq, strum::EnumString, strum::Display,
+)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum CsvNullHandling {
+    /// Empty cells are empty strings, except for optional fields in the
+    /// table's schema, which are omitted.
+    #[default]
+    EmptyString,
+    Null,
+    Omit,
+}
+
 /*
       │
       │
@@ -255,8 +338,12 @@ impl TryFrom<ImportFormat> for ConvexObject {
     fn try_from(format: ImportFormat) -> anyhow::Result<ConvexObject> {
         match format {
             ImportFormat::Zip => obj!("format" => "zip"),
-            ImportFormat::Csv(table_name) => {
-                obj!("format" => "csv", "table" => table_name.to_string())
+            ImportFormat::Csv(table_name, options) => {
+                obj!(
+                    "format" => "csv",
+                    "table" => table_name.to_string(),
+                    "csv_options" => ConvexValue::Object(options.try_into()?),
+                )
             },
             ImportFormat::JsonLines(table_name) => {
                 obj!("format" => "jsonl", "table" => table_name.to_string())
@@ -264,6 +351,9 @@ impl TryFrom<ImportFormat> for ConvexObject {
             ImportFormat::JsonArray(table_name) => {
                 obj!("format" => "json_array", "table" => table_name.to_string())
             },
+            ImportFormat::Parquet(table_name) => {
+                obj!("format" => "parquet", "table" => table_name.to_string())
+            },
         }
     }
 }
@@ -280,11 +370,21 @@ impl TryFrom<ConvexObject> for ImportFormat {
         let format = match o.get("format") {
             Some(ConvexValue::String(format_variant)) => match &**format_variant {
                 "zip" => ImportFormat::Zip,
-                "csv" => ImportFormat::Csv(table_name.context("expected table for csv")?),
+                "csv" => ImportFormat::Csv(
+                    table_name.context("expected table for csv")?,
+                    match o.get("csv_options") {
+                        Some(ConvexValue::Object(options)) => options.clone().try_into()?,
+                        None => CsvImportOptions::default(),
+                        _ => anyhow::bail!("invalid csv options: {o:?}"),
+                    },
+                ),
                 "jsonl" => ImportFormat::JsonLines(table_name.context("expected table for jsonl")?),
                 "json_array" => {
                     ImportFormat::JsonArray(table_name.context("expected table for json_array")?)
                 },
+                "parquet" => {
+                    ImportFormat::Parquet(table_name.context("expected table for parquet")?)
+                },
                 _ => anyhow::bail!("invalid format variant: {format_variant}"),
             },
             _ => anyhow::bail!("invalid format variant: {o:?}"),
@@ -293,6 +393,90 @@ impl TryFrom<ConvexObject> for ImportFormat {
     }
 }
 
+impl TryFrom<CsvImportOptions> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(options: CsvImportOptions) -> anyhow::Result<ConvexObject> {
+        let header_mapping: Vec<_> = options
+            .header_mapping
+            .into_iter()
+            .map(|(header, field_path)| val!([header, field_path]))
+            .collect();
+        let column_types: Vec<_> = options
+            .column_types
+            .into_iter()
+            .map(|(header, column_type)| val!([header, column_type.to_string()]))
+            .collect();
+        obj!(
+            "delimiter" => options.delimiter.to_string(),
+            "quote" => options.quote.to_string(),
+            "header_mapping" => ConvexValue::Array(header_mapping.try_into()?),
+            "path_separator" => match options.path_separator {
+                None => val!(null),
+                Some(separator) => val!(separator.to_string()),
+            },
+            "column_types" => ConvexValue::Array(column_types.try_into()?),
+            "null_handling" => options.null_handling.to_string(),
+        )
+    }
+}
+
+impl TryFrom<ConvexObject> for CsvImportOptions {
+    type Error = anyhow::Error;
+
+    fn try_from(o: ConvexObject) -> anyhow::Result<CsvImportOptions> {
+        let parse_char = |field: &str| -> anyhow::Result<char> {
+            match o.get(field) {
+                Some(ConvexValue::String(s)) => {
+                    let mut chars = s.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Ok(c),
+                        _ => anyhow::bail!("invalid {field}: {o:?}"),
+                    }
+                },
+                _ => anyhow::bail!("invalid {field}: {o:?}"),
+            }
+        };
+        let parse_pairs = |field: &str| -> anyhow::Result<Vec<(String, String)>> {
+            match o.get(field) {
+                Some(ConvexValue::Array(pairs)) => pairs
+                    .iter()
+                    .map(|pair| match pair {
+                        ConvexValue::Array(pair) => match (pair.first(), pair.get(1)) {
+                            (Some(ConvexValue::String(k)), Some(ConvexValue::String(v))) => {
+                                Ok((k.to_string(), v.to_string()))
+                            },
+                            _ => anyhow::bail!("invalid {field}: {o:?}"),
+                        },
+                        _ => anyhow::bail!("invalid {field}: {o:?}"),
+                    })
+                    .collect(),
+                _ => anyhow::bail!("invalid {field}: {o:?}"),
+            }
+        };
+        let path_separator = match o.get("path_separator") {
+            Some(ConvexValue::Null) => None,
+            _ => Some(parse_char("path_separator")?),
+        };
+        let column_types: BTreeMap<String, CsvColumnType> = parse_pairs("column_types")?
+            .into_iter()
+            .map(|(header, column_type)| anyhow::Ok((header, column_type.parse()?)))
+            .try_collect()?;
+        let null_handling = match o.get("null_handling") {
+            Some(ConvexValue::String(null_handling)) => null_handling.parse()?,
+            _ => anyhow::bail!("invalid null_handling: {o:?}"),
+        };
+        Ok(Self {
+            delimiter: parse_char("delimiter")?,
+            quote: parse_char("quote")?,
+            header_mapping: parse_pairs("header_mapping")?.into_iter().collect(),
+            path_separator,
+            column_types,
+            null_handling,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
@@ -0,0 +1,298 @@
+use std::{
+    collections::BTreeSet,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        IndexName,
+        Timestamp,
+    },
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    TableRestore,
+    TableRestoreProgress,
+    TableRestoreState,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static TABLE_RESTORES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_table_restores"
+        .parse()
+        .expect("Invalid built-in table restores table")
+});
+
+pub static TABLE_RESTORES_INDEX_BY_STATE: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&TABLE_RESTORES_TABLE, "by_state"));
+static STATE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "state".parse().expect("invalid state field"));
+
+pub struct TableRestoresTable;
+impl SystemTable for TableRestoresTable {
+    fn table_name(&self) -> &'static TableName {
+        &TABLE_RESTORES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: TABLE_RESTORES_INDEX_BY_STATE.clone(),
+            fields: vec![STATE_FIELD.clone()].try_into().unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<TableRestore>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct TableRestoreModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> TableRestoreModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Start restoring `table_names` to their state at `ts`. A table can only
+    /// be restored by one restore at a time.
+    pub async fn start(
+        &mut self,
+        ts: Timestamp,
+        table_names: BTreeSet<TableName>,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        for active in self.active().await? {
+            if let Some(table) = active
+                .tables
+                .iter()
+                .find(|table| table_names.contains(&table.table_name))
+            {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "RestoreAlreadyRunning",
+                    format!(
+                        "Restore {} is already running on table \"{}\"",
+                        active.developer_id().encode(),
+                        table.table_name
+                    ),
+                ));
+            }
+        }
+        let restore = TableRestore {
+            ts,
+            tables: table_names
+                .into_iter()
+                .map(|table_name| TableRestoreProgress {
+                    table_name,
+                    done: false,
+                    inserted: 0,
+                    replaced: 0,
+                    deleted: 0,
+                })
+                .collect(),
+            state: TableRestoreState::Running,
+            cursor: None,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&TABLE_RESTORES_TABLE, restore.try_into()?)
+            .await
+    }
+
+    pub async fn get(
+        &mut self,
+        id: ResolvedDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<TableRestore>>> {
+        anyhow::ensure!(self
+            .tx
+            .table_mapping()
+            .namespace(TableNamespace::Global)
+            .number_matches_name(id.table().table_number, &TABLE_RESTORES_TABLE));
+        match self.tx.get(id).await? {
+            None => Ok(None),
+            Some(doc) => Ok(Some(doc.try_into()?)),
+        }
+    }
+
+    /// All restores, most recently started first.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<TableRestore>>> {
+        let query = Query::full_table_scan(TABLE_RESTORES_TABLE.clone(), Order::Desc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut restores = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            restores.push(doc.try_into()?);
+        }
+        Ok(restores)
+    }
+
+    /// Restores that are still running, oldest first.
+    pub async fn active(&mut self) -> anyhow::Result<Vec<ParsedDocument<TableRestore>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: TABLE_RESTORES_INDEX_BY_STATE.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                STATE_FIELD.clone(),
+                ConvexValue::try_from(TableRestoreState::Running.as_str())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut restores = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            restores.push(doc.try_into()?);
+        }
+        Ok(restores)
+    }
+
+    /// Record a batch of the current table, which has been restored up to and
+    /// including `cursor`, or completely if `cursor` is `None`. Completes the
+    /// restore once every table is done.
+    pub async fn record_batch(
+        &mut self,
+        id: ResolvedDocumentId,
+        cursor: Option<DeveloperDocumentId>,
+        progress: TableRestoreProgress,
+    ) -> anyhow::Result<()> {
+        self.update(id, |restore| {
+            anyhow::ensure!(restore.state == TableRestoreState::Running);
+            let table = restore
+                .tables
+                .iter_mut()
+                .find(|table| !table.done)
+                .ok_or_else(|| anyhow::anyhow!("Restore has no tables left"))?;
+            anyhow::ensure!(table.table_name == progress.table_name);
+            *table = progress;
+            restore.cursor = cursor;
+            if restore.current_table().is_none() {
+                restore.state = TableRestoreState::Completed;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn fail(&mut self, id: ResolvedDocumentId, error: String) -> anyhow::Result<()> {
+        self.update(id, |restore| {
+            anyhow::ensure!(restore.state == TableRestoreState::Running);
+            restore.state = TableRestoreState::Failed { error };
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update(
+        &mut self,
+        id: ResolvedDocumentId,
+        update: impl FnOnce(&mut TableRestore) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let mut restore = self
+            .get(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Restore {id} not found"))?
+            .into_value();
+        update(&mut restore)?;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, restore.try_into()?)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use common::types::TableName;
+    use database::test_helpers::DbFixtures;
+    use keybroker::Identity;
+    use runtime::testing::TestRuntime;
+    use sync_types::Timestamp;
+
+    use crate::{
+        table_restores::{
+            types::{
+                TableRestoreProgress,
+                TableRestoreState,
+            },
+            TableRestoreModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_table_restore_lifecycle(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new_with_model(&rt).await?.db;
+        let messages: TableName = "messages".parse()?;
+        let users: TableName = "users".parse()?;
+        let ts = Timestamp::must(1);
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = TableRestoreModel::new(&mut tx);
+        let id = model
+            .start(ts, BTreeSet::from([messages.clone(), users.clone()]))
+            .await?;
+        assert!(model
+            .start(ts, BTreeSet::from([users.clone()]))
+            .await
+            .is_err());
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = TableRestoreModel::new(&mut tx);
+        let progress = TableRestoreProgress {
+            table_name: messages.clone(),
+            done: true,
+            inserted: 1,
+            replaced: 2,
+            deleted: 3,
+        };
+        model.record_batch(id, None, progress.clone()).await?;
+        let restore = model.get(id).await?.unwrap();
+        assert_eq!(restore.state, TableRestoreState::Running);
+        assert_eq!(restore.tables[0], progress);
+        assert_eq!(restore.current_table().unwrap().table_name, users);
+
+        let progress = TableRestoreProgress {
+            table_name: users,
+            done: true,
+            inserted: 0,
+            replaced: 0,
+            deleted: 0,
+        };
+        model.record_batch(id, None, progress).await?;
+        let restore = model.get(id).await?.unwrap();
+        assert_eq!(restore.state, TableRestoreState::Completed);
+        assert!(model.active().await?.is_empty());
+        assert_eq!(model.list().await?.len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use sync_types::Timestamp;
+use value::{
+    id_v6::DeveloperDocumentId,
+    obj,
+    ConvexObject,
+    ConvexValue,
+    TableName,
+};
+
+/// A restore of `tables` to their state at `ts`. Restores run in batches in
+/// the background, one table at a time, and pick up from `cursor` after a
+/// restart.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TableRestore {
+    pub ts: Timestamp,
+    /// In the order they're restored.
+    pub tables: Vec<TableRestoreProgress>,
+    pub state: TableRestoreState,
+    /// The last document restored in the first table that isn't done yet, or
+    /// `None` if none of its documents have been restored.
+    pub cursor: Option<DeveloperDocumentId>,
+}
+
+impl TableRestore {
+    /// The first table that hasn't been fully restored yet.
+    pub fn current_table(&self) -> Option<&TableRestoreProgress> {
+        self.tables.iter().find(|table| !table.done)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum TableRestoreState {
+    Running,
+    Completed,
+    Failed { error: String },
+}
+
+impl TableRestoreState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TableRestoreState::Running => "running",
+            TableRestoreState::Completed => "completed",
+            TableRestoreState::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// How many documents restoring a table has changed so far.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TableRestoreProgress {
+    pub table_name: TableName,
+    pub done: bool,
+    /// Documents that had been deleted since the timestamp.
+    pub inserted: u64,
+    /// Documents that had been modified since the timestamp.
+    pub replaced: u64,
+    /// Documents that had been inserted since the timestamp.
+    pub deleted: u64,
+}
+
+impl TryFrom<TableRestore> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        TableRestore {
+            ts,
+            tables,
+            state,
+            cursor,
+        }: TableRestore,
+    ) -> anyhow::Result<Self> {
+        let error = match &state {
+            TableRestoreState::Failed { error } => Some(error.clone()),
+            _ => None,
+        };
+        let tables: Vec<_> = tables
+            .into_iter()
+            .map(
+                |TableRestoreProgress {
+                     table_name,
+                     done,
+                     inserted,
+                     replaced,
+                     deleted,
+                 }| {
+                    anyhow::Ok(ConvexValue::Object(obj!(
+                        "table" => String::from(table_name),
+                        "done" => done,
+                        "inserted" => inserted as i64,
+                        "replaced" => replaced as i64,
+                        "deleted" => deleted as i64,
+                    )?))
+                },
+            )
+            .try_collect()?;
+        obj!(
+            "ts" => i64::from(ts),
+            "tables" => ConvexValue::Array(tables.try_into()?),
+            "state" => state.as_str(),
+            "error" => error,
+            "cursor" => cursor.map(|id| id.encode()),
+        )
+    }
+}
+
+impl TryFrom<ConvexObject> for TableRestore {
+    type Error = anyhow::Error;
+
+    fn try_from(object: ConvexObject) -> anyhow::Result<Self> {
+        let mut fields = BTreeMap::from(object);
+        let ts = match fields.remove("ts") {
+            Some(ConvexValue::Int64(ts)) => ts.try_into()?,
+            v => anyhow::bail!("Invalid ts field for TableRestore: {v:?}"),
+        };
+        let tables = match fields.remove("tables") {
+            Some(ConvexValue::Array(tables)) => tables
+                .into_iter()
+                .map(|table| {
+                    let ConvexValue::Object(table) = table else {
+                        anyhow::bail!("Invalid table for TableRestore: {table:?}");
+                    };
+                    let table_name = match table.get("table") {
+                        Some(ConvexValue::String(table_name)) => table_name.parse()?,
+                        v => anyhow::bail!("Invalid table name for TableRestore: {v:?}"),
+                    };
+                    let done = match table.get("done") {
+                        Some(ConvexValue::Boolean(done)) => *done,
+                        v => anyhow::bail!("Invalid done for TableRestore: {v:?}"),
+                    };
+                    let count = |field: &str| match table.get(field) {
+                        Some(ConvexValue::Int64(n)) => Ok(*n as u64),
+                        v => anyhow::bail!("Invalid {field} for TableRestore: {v:?}"),
+                    };
+                    Ok(TableRestoreProgress {
+                        table_name,
+                        done,
+                        inserted: count("inserted")?,
+                        replaced: count("replaced")?,
+                        deleted: count("deleted")?,
+                    })
+                })
+                .try_collect()?,
+            v => anyhow::bail!("Invalid tables field for TableRestore: {v:?}"),
+        };
+        let mut remove_string = |field: &str| match fields.remove(field) {
+            Some(ConvexValue::String(s)) => Ok(Some(String::from(s))),
+            None | Some(ConvexValue::Null) => Ok(None),
+            v => anyhow::bail!("Invalid {field} field for TableRestore: {v:?}"),
+        };
+        let state = remove_string("state")?.context("Missing state field for TableRestore")?;
+        let error = remove_string("error")?;
+        let cursor = remove_string("cursor")?
+            .map(|id| DeveloperDocumentId::decode(&id))
+            .transpose()?;
+        let state = match (&*state, error) {
+            ("running", None) => TableRestoreState::Running,
+            ("completed", None) => TableRestoreState::Completed,
+            ("failed", Some(error)) => TableRestoreState::Failed { error },
+            (state, error) => {
+                anyhow::bail!("Invalid state {state} for TableRestore with error {error:?}")
+            },
+        };
+        Ok(Self {
+            ts,
+            tables,
+            state,
+            cursor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+    };
+
+    use super::TableRestore;
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_table_restore_to_object_roundtrip(r in any::<TableRestore>()) {
+            assert_roundtrips::<TableRestore, ConvexObject>(r);
+        }
+    }
+}
@@ -15,6 +15,7 @@ use common::{
         JsError,
     },
     execution_context::ExecutionContext,
+    http::fetch::egress::EgressPolicy,
     log_lines::LogLine,
     sha256::Sha256Digest,
     types::{
@@ -129,6 +130,7 @@ pub struct Actions {
     executor: Arc<dyn NodeExecutor>,
     convex_origin: ConvexOrigin,
     user_timeout: Duration,
+    egress_policy: Option<EgressPolicy>,
 }
 
 fn construct_js_error(
@@ -186,9 +188,16 @@ impl Actions {
             executor,
             convex_origin,
             user_timeout,
+            egress_policy: None,
         }
     }
 
+    /// Restricts the outbound network access of actions run by the executor.
+    pub fn with_egress_policy(mut self, egress_policy: EgressPolicy) -> Self {
+        self.egress_policy = Some(egress_policy);
+        self
+    }
+
     pub fn enable(&self) -> anyhow::Result<()> {
         self.executor.enable()
     }
@@ -212,6 +221,7 @@ impl Actions {
             // total Node timeout. This allows us to preempt early and give
             // better error message and logs in the common case.
             timeout: self.user_timeout,
+            egress_policy: self.egress_policy.clone(),
         };
         let InvokeResponse {
             response,
@@ -492,11 +502,25 @@ pub enum ExecutorRequest {
         request: ExecuteRequest,
         backend_address: ConvexOrigin,
         timeout: Duration,
+        egress_policy: Option<EgressPolicy>,
     },
     Analyze(AnalyzeRequest),
     BuildDeps(BuildDepsRequest),
 }
 
+impl ExecutorRequest {
+    /// Key of the bundled source package the request loads, if any.
+    pub(crate) fn source_package_key(&self) -> Option<&ObjectKey> {
+        match self {
+            ExecutorRequest::Execute { request, .. } => {
+                Some(&request.source_package.bundled_source.key)
+            },
+            ExecutorRequest::Analyze(request) => Some(&request.source_package.bundled_source.key),
+            ExecutorRequest::BuildDeps(_) => None,
+        }
+    }
+}
+
 impl TryFrom<ExecutorRequest> for JsonValue {
     type Error = anyhow::Error;
 
@@ -506,6 +530,7 @@ impl TryFrom<ExecutorRequest> for JsonValue {
                 request: r,
                 backend_address,
                 timeout,
+                egress_policy,
             } => {
                 let environment_variables: Vec<JsonValue> = r
                     .environment_variables
@@ -538,6 +563,7 @@ impl TryFrom<ExecutorRequest> for JsonValue {
                     "npmVersion": npm_version.map(|v| v.to_string()),
                     "executionContext": JsonValue::from(r.context),
                     "encodedParentTrace": JsonValue::from(r.encoded_parent_trace),
+                    "egressPolicy": egress_policy.map(serde_json::to_value).transpose()?,
                 })
             },
             ExecutorRequest::Analyze(r) => {
@@ -1,25 +1,59 @@
 use std::{
+    collections::{
+        BTreeSet,
+        VecDeque,
+    },
     fs,
-    path::PathBuf,
+    path::{
+        Path,
+        PathBuf,
+    },
+    process::Stdio,
+    sync::Arc,
     time::Duration,
 };
 
 use anyhow::Context;
 use async_trait::async_trait;
-use common::log_lines::LogLine;
+use common::{
+    knobs::{
+        NODE_EXECUTOR_MAX_IDLE_WORKERS,
+        NODE_EXECUTOR_MAX_WORKERS,
+    },
+    log_lines::LogLine,
+    types::ObjectKey,
+};
 use futures::{
     channel::mpsc,
     select_biased,
     FutureExt,
-    StreamExt,
 };
 use isolate::bundled_js::node_executor_file;
-use serde_json::Value as JsonValue;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
 use tempfile::TempDir;
-use tokio::process::Command as TokioCommand;
-use tokio_process_stream::{
-    Item,
-    ProcessLineStream,
+use tokio::{
+    io::{
+        AsyncBufReadExt,
+        AsyncWriteExt,
+        BufReader,
+        Lines,
+    },
+    process::{
+        Child,
+        ChildStdin,
+        ChildStdout,
+        Command as TokioCommand,
+    },
+    sync::{
+        Notify,
+        OwnedSemaphorePermit,
+        Semaphore,
+    },
 };
 
 use crate::executor::{
@@ -35,11 +69,25 @@ use crate::executor::{
 /// we're using older version for CLI.
 const NODE_VERSION: &str = include_str!("../../../.nvmrc");
 
+/// Most lines of a worker's stderr kept to log if its request fails.
+const MAX_STDERR_LINES: usize = 1000;
+
+/// Runs node actions in a pool of long-lived `node` processes. Each process
+/// runs one request at a time and keeps the source packages it has loaded, so
+/// requests are preferably sent to a process that has already loaded their
+/// source package. Once `NODE_EXECUTOR_MAX_WORKERS` processes are running,
+/// requests wait for one of them to free up.
 pub struct LocalNodeExecutor {
     _source_dir: TempDir,
     source_path: PathBuf,
     node_path: String,
     node_process_timeout: Duration,
+    idle_workers: Mutex<Vec<NodeWorker>>,
+    max_idle_workers: usize,
+    // One permit per running worker, held by the worker until it's dropped.
+    live_workers: Arc<Semaphore>,
+    // Notified when a worker is returned to `idle_workers`.
+    worker_checked_in: Notify,
 }
 
 impl LocalNodeExecutor {
@@ -76,9 +124,19 @@ impl LocalNodeExecutor {
             source_path,
             node_path,
             node_process_timeout,
+            idle_workers: Mutex::new(Vec::new()),
+            max_idle_workers: *NODE_EXECUTOR_MAX_IDLE_WORKERS,
+            live_workers: Arc::new(Semaphore::new(*NODE_EXECUTOR_MAX_WORKERS)),
+            worker_checked_in: Notify::new(),
         })
     }
 
+    #[cfg(test)]
+    fn with_max_workers(mut self, max_workers: usize) -> Self {
+        self.live_workers = Arc::new(Semaphore::new(max_workers));
+        self
+    }
+
     async fn check_version(&self) -> anyhow::Result<()> {
         let cmd = TokioCommand::new(&self.node_path)
             .arg("--version")
@@ -94,6 +152,74 @@ impl LocalNodeExecutor {
         );
         Ok(())
     }
+
+    /// Takes an idle worker, preferring one that has already loaded
+    /// `source_package`, or starts a new one. Waits for a worker to be
+    /// checked in or exit if too many are running.
+    async fn checkout_worker(
+        &self,
+        source_package: Option<&ObjectKey>,
+    ) -> anyhow::Result<NodeWorker> {
+        let permit = loop {
+            if let Some(worker) = self.take_idle_worker(source_package) {
+                return Ok(worker);
+            }
+            if let Ok(permit) = self.live_workers.clone().try_acquire_owned() {
+                break permit;
+            }
+            select_biased! {
+                permit = self.live_workers.clone().acquire_owned().fuse() => break permit?,
+                // A worker was checked in, so look at the idle workers again.
+                _ = self.worker_checked_in.notified().fuse() => {},
+            }
+        };
+        self.check_version().await?;
+        NodeWorker::spawn(&self.node_path, &self.source_path, permit)
+    }
+
+    fn take_idle_worker(&self, source_package: Option<&ObjectKey>) -> Option<NodeWorker> {
+        let mut idle_workers = self.idle_workers.lock();
+        // Workers can crash while idle, e.g. from a dangling promise.
+        idle_workers.retain_mut(|worker| {
+            let is_running = worker.is_running();
+            if !is_running {
+                for line in worker.stderr_lines.lock().iter() {
+                    tracing::error!("{line}");
+                }
+            }
+            is_running
+        });
+        let position = source_package.and_then(|key| {
+            idle_workers
+                .iter()
+                .position(|worker| worker.source_packages.contains(key))
+        });
+        match position {
+            Some(i) => Some(idle_workers.swap_remove(i)),
+            None => idle_workers.pop(),
+        }
+    }
+
+    fn checkin_worker(&self, worker: NodeWorker) {
+        {
+            let mut idle_workers = self.idle_workers.lock();
+            if idle_workers.len() >= self.max_idle_workers {
+                // Dropping the worker kills it and frees up its permit.
+                return;
+            }
+            idle_workers.push(worker);
+        }
+        self.worker_checked_in.notify_one();
+    }
+
+    #[cfg(test)]
+    fn idle_worker_pids(&self) -> Vec<Option<u32>> {
+        self.idle_workers
+            .lock()
+            .iter()
+            .map(|worker| worker.child.id())
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -107,69 +233,210 @@ impl NodeExecutor for LocalNodeExecutor {
         request: ExecutorRequest,
         log_line_sender: mpsc::UnboundedSender<LogLine>,
     ) -> anyhow::Result<InvokeResponse> {
+        let source_package = request.source_package_key().cloned();
         let request = JsonValue::try_from(request)?;
-        self.check_version().await?;
-        let request = serde_json::to_string(&request)?;
+        let mut worker = self.checkout_worker(source_package.as_ref()).await?;
+        let id = worker.next_request_id;
+        worker.next_request_id += 1;
+        let frame = serde_json::to_string(&json!({ "id": id, "request": request }))?;
         tracing::info!(
-            "{} {} --request='{}'",
-            &self.node_path,
-            self.source_path.to_str().expect("Must be utf-8"),
-            &request,
+            "Sending request {id} to node worker {:?}: {frame}",
+            worker.child.id()
         );
-        let mut _cmd = TokioCommand::new(&self.node_path);
-        let cmd = _cmd
-            .arg(&self.source_path)
-            .arg("--request")
-            .arg(request)
-            .kill_on_drop(true);
-        let mut result_values = vec![];
-        let mut err_lines = vec![];
+        let result = select_biased! {
+            result = worker.run(id, &frame, &log_line_sender).fuse() => Some(result),
+            _ = tokio::time::sleep(self.node_process_timeout).fuse() => None,
+        };
+        let response = match result {
+            Some(Ok(response)) => response,
+            Some(Err(e)) => {
+                for line in worker.stderr_lines.lock().iter() {
+                    tracing::error!("{line}");
+                }
+                // Dropping the worker kills it, so the next request gets a fresh one.
+                return Err(e);
+            },
+            None => {
+                // The worker may be stuck in a busy loop, so kill it by dropping it.
+                return Ok(InvokeResponse {
+                    response: EXECUTE_TIMEOUT_RESPONSE_JSON.clone(),
+                    memory_used_in_mb: worker.memory_used_in_mb,
+                    aws_request_id: None,
+                });
+            },
+        };
+        if let Some(source_package) = source_package {
+            worker.source_packages.insert(source_package);
+        }
+        let memory_used_in_mb = worker.memory_used_in_mb;
+        self.checkin_worker(worker);
+        Ok(InvokeResponse {
+            response,
+            memory_used_in_mb,
+            aws_request_id: None,
+        })
+    }
 
-        let mut procstream = ProcessLineStream::try_from(cmd)?.fuse();
+    fn shutdown(&self) {
+        self.idle_workers.lock().clear();
+    }
+}
 
-        let response = loop {
-            select_biased! {
-                item = procstream.select_next_some() => {
-                    match item {
-                        Item::Stdout(line) => {
-                            let parts = parse_streamed_response(&line)?;
-                            for part in parts {
-                                match part {
-                                    ResponsePart::LogLine(log_line) => {
-                                        log_line_sender.unbounded_send(log_line)?;
-                                    },
-                                    ResponsePart::Result(result) => result_values.push(result)
-                                }
-                            }
-                        },
-                        Item::Done(status) => {
-                            if !status?.success() {
-                                for line in err_lines {
-                                    tracing::error!("{line}");
-                                }
-                                anyhow::bail!("Local process did not exit successfully");
-                            }
-                            anyhow::ensure!(result_values.len() <= 1, "Received more than one result from lambda response");
-                            let value = result_values.pop().ok_or_else(|| anyhow::anyhow!("Received no result from lambda response"))?;
-                            break value;
+/// Frames written by `local.cjs --worker`, one JSON object per stdout line.
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum WorkerFrame {
+    Part {
+        id: u64,
+        data: String,
+    },
+    Done {
+        id: u64,
+        #[serde(rename = "memoryUsedInMb")]
+        memory_used_in_mb: u64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// A `node` process running `local.cjs --worker`, which reads one request per
+/// stdin line and streams back [`WorkerFrame`]s.
+struct NodeWorker {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    // Stderr since the current request started, logged if it fails. It's read
+    // by a task for the whole life of the process, so the process never
+    // blocks on a full pipe, even while it's idle.
+    stderr_lines: Arc<Mutex<VecDeque<String>>>,
+    _permit: OwnedSemaphorePermit,
+    // Source packages that have been downloaded and imported by this worker.
+    source_packages: BTreeSet<ObjectKey>,
+    next_request_id: u64,
+    // Resident memory after the last completed request.
+    memory_used_in_mb: u64,
+}
+
+impl NodeWorker {
+    fn spawn(
+        node_path: &str,
+        source_path: &Path,
+        permit: OwnedSemaphorePermit,
+    ) -> anyhow::Result<Self> {
+        let mut child = TokioCommand::new(node_path)
+            .arg(source_path)
+            .arg("--worker")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        tracing::info!(
+            "Started node worker {:?}: {node_path} {} --worker",
+            child.id(),
+            source_path.to_str().expect("Must be utf-8"),
+        );
+        let stdin = child.stdin.take().context("Missing node worker stdin")?;
+        let stdout = child.stdout.take().context("Missing node worker stdout")?;
+        let stderr = child.stderr.take().context("Missing node worker stderr")?;
+        let stderr_lines = Arc::new(Mutex::new(VecDeque::new()));
+        // Ends once the process exits and closes its stderr.
+        tokio::spawn({
+            let stderr_lines = stderr_lines.clone();
+            async move {
+                let mut stderr = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = stderr.next_line().await {
+                    let mut stderr_lines = stderr_lines.lock();
+                    if stderr_lines.len() == MAX_STDERR_LINES {
+                        stderr_lines.pop_front();
+                    }
+                    stderr_lines.push_back(line);
+                }
+            }
+        });
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+            stderr_lines,
+            _permit: permit,
+            source_packages: BTreeSet::new(),
+            next_request_id: 0,
+            memory_used_in_mb: 0,
+        })
+    }
+
+    fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    async fn run(
+        &mut self,
+        id: u64,
+        frame: &str,
+        log_line_sender: &mpsc::UnboundedSender<LogLine>,
+    ) -> anyhow::Result<JsonValue> {
+        let Self {
+            stdin,
+            stdout,
+            stderr_lines,
+            memory_used_in_mb,
+            ..
+        } = self;
+        stderr_lines.lock().clear();
+        stdin.write_all(frame.as_bytes()).await?;
+        stdin.write_all(b"\n").await?;
+        stdin.flush().await?;
+
+        let mut result_values = vec![];
+        loop {
+            let line = stdout
+                .next_line()
+                .await?
+                .context("Node worker exited unexpectedly")?;
+            let Ok(frame) = serde_json::from_str::<WorkerFrame>(&line) else {
+                tracing::info!("{line}");
+                continue;
+            };
+            match frame {
+                WorkerFrame::Part { id: frame_id, data } => {
+                    anyhow::ensure!(
+                        frame_id == id,
+                        "Response for request {frame_id} while running {id}"
+                    );
+                    for part in parse_streamed_response(&data)? {
+                        match part {
+                            ResponsePart::LogLine(log_line) => {
+                                log_line_sender.unbounded_send(log_line)?;
+                            },
+                            ResponsePart::Result(result) => result_values.push(result),
                         }
-                        Item::Stderr(line) => err_lines.push(line),
                     }
                 },
-                _ = tokio::time::sleep(self.node_process_timeout).fuse() => {
-                    break EXECUTE_TIMEOUT_RESPONSE_JSON.clone();
+                WorkerFrame::Done {
+                    id: frame_id,
+                    memory_used_in_mb: memory,
+                } => {
+                    anyhow::ensure!(
+                        frame_id == id,
+                        "Response for request {frame_id} while running {id}"
+                    );
+                    *memory_used_in_mb = memory;
+                    anyhow::ensure!(
+                        result_values.len() <= 1,
+                        "Received more than one result from lambda response"
+                    );
+                    return result_values
+                        .pop()
+                        .ok_or_else(|| anyhow::anyhow!("Received no result from lambda response"));
+                },
+                WorkerFrame::Error { message } => {
+                    anyhow::bail!("Node worker failed to run request {id}: {message}");
                 },
             }
-        };
-        Ok(InvokeResponse {
-            response,
-            // constant is good enough for measuring local executor
-            memory_used_in_mb: 512,
-            aws_request_id: None,
-        })
+        }
     }
-
-    fn shutdown(&self) {}
 }
 
 #[cfg(test)]
@@ -808,6 +1075,128 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::prod_rt_test]
+    async fn test_reuses_worker(rt: ProdRuntime) -> anyhow::Result<()> {
+        let storage = Arc::new(LocalDirStorage::new(rt)?);
+        let executor = Arc::new(LocalNodeExecutor::new(TEST_NODE_PROCESS_TIMEOUT)?);
+        let actions = Actions::new(
+            executor.clone(),
+            TEST_BACKEND_ADDRESS.into(),
+            TEST_USER_TIMEOUT,
+        );
+        let source_package = upload_modules(storage.clone(), TEST_SOURCE.clone()).await?;
+        let source_maps = BTreeMap::new();
+        let mut pids = vec![];
+        for _ in 0..2 {
+            let path_and_args = ValidatedPathAndArgs::new_for_tests(
+                CanonicalizedComponentFunctionPath {
+                    component: ComponentPath::root(),
+                    udf_path: "node_actions.js:logHelloWorldAndReturn7".parse()?,
+                },
+                array![],
+                VERSION.clone(),
+            );
+            let (response, log_lines) = execute(
+                &actions,
+                execute_request(path_and_args, source_package.clone()),
+                &source_maps,
+            )
+            .await?;
+            assert_eq!(response.result?, ConvexValue::from(7.));
+            // Log lines from the previous request don't leak into this one.
+            assert_eq!(log_lines.into_iter().count(), 2);
+            pids.push(executor.idle_worker_pids());
+        }
+        assert_eq!(pids[0].len(), 1);
+        assert_eq!(pids[0], pids[1]);
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_waits_for_worker(rt: ProdRuntime) -> anyhow::Result<()> {
+        let storage = Arc::new(LocalDirStorage::new(rt)?);
+        let executor =
+            Arc::new(LocalNodeExecutor::new(TEST_NODE_PROCESS_TIMEOUT)?.with_max_workers(1));
+        let actions = Actions::new(
+            executor.clone(),
+            TEST_BACKEND_ADDRESS.into(),
+            TEST_USER_TIMEOUT,
+        );
+        let source_package = upload_modules(storage.clone(), TEST_SOURCE.clone()).await?;
+        let source_maps = BTreeMap::new();
+        let request = || -> anyhow::Result<ExecuteRequest> {
+            let path_and_args = ValidatedPathAndArgs::new_for_tests(
+                CanonicalizedComponentFunctionPath {
+                    component: ComponentPath::root(),
+                    udf_path: "node_actions.js:logHelloWorldAndReturn7".parse()?,
+                },
+                array![],
+                VERSION.clone(),
+            );
+            Ok(execute_request(path_and_args, source_package.clone()))
+        };
+        // The second request waits for the first to finish with the only
+        // worker.
+        let ((first, _), (second, _)) = futures::try_join!(
+            execute(&actions, request()?, &source_maps),
+            execute(&actions, request()?, &source_maps),
+        )?;
+        assert_eq!(first.result?, ConvexValue::from(7.));
+        assert_eq!(second.result?, ConvexValue::from(7.));
+        assert_eq!(executor.idle_worker_pids().len(), 1);
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_recovers_after_process_timeout(rt: ProdRuntime) -> anyhow::Result<()> {
+        let storage = Arc::new(LocalDirStorage::new(rt)?);
+        let executor = Arc::new(LocalNodeExecutor::new(TEST_NODE_PROCESS_TIMEOUT)?);
+        let actions = Actions::new(
+            executor.clone(),
+            TEST_BACKEND_ADDRESS.into(),
+            TEST_USER_TIMEOUT,
+        );
+        let source_package = upload_modules(storage.clone(), TEST_SOURCE.clone()).await?;
+        let source_maps = BTreeMap::new();
+        let path_and_args = ValidatedPathAndArgs::new_for_tests(
+            CanonicalizedComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: "node_actions.js:workHardForAnHour".parse()?,
+            },
+            array![],
+            VERSION.clone(),
+        );
+        let (response, _log_lines) = execute(
+            &actions,
+            execute_request(path_and_args, source_package.clone()),
+            &source_maps,
+        )
+        .await?;
+        assert!(response.result.is_err());
+        // The stuck worker is killed rather than returned to the pool.
+        assert!(executor.idle_worker_pids().is_empty());
+
+        let numbers: ConvexArray = array![1f64.into(), 7f64.into()]?;
+        let args = create_args(assert_obj!("numbers" => ConvexValue::Array(numbers)))?;
+        let path_and_args = ValidatedPathAndArgs::new_for_tests(
+            CanonicalizedComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: "node_actions.js:addNumbers".parse()?,
+            },
+            args,
+            VERSION.clone(),
+        );
+        let (response, _log_lines) = execute(
+            &actions,
+            execute_request(path_and_args, source_package),
+            &source_maps,
+        )
+        .await?;
+        assert_eq!(response.result?, ConvexValue::from(8.));
+        assert_eq!(executor.idle_worker_pids().len(), 1);
+        Ok(())
+    }
+
     #[convex_macro::prod_rt_test]
     async fn test_deadlock(rt: ProdRuntime) -> anyhow::Result<()> {
         let storage = Arc::new(LocalDirStorage::new(rt)?);
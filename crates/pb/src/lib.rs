@@ -26,6 +26,9 @@ pub mod convex_query_journal {
 pub mod errors {
     include!(concat!(env!("OUT_DIR"), "/errors.rs"));
 }
+pub mod function_runner {
+    include!(concat!(env!("OUT_DIR"), "/function_runner.rs"));
+}
 pub mod outcome {
     include!(concat!(env!("OUT_DIR"), "/outcome.rs"));
 }
@@ -767,6 +767,114 @@ impl QueryReads {
     }
 }
 
+impl From<TextQueryTerm> for pb::searchlight::TextQueryTerm {
+    fn from(term: TextQueryTerm) -> Self {
+        let term_type =
+            match term {
+                TextQueryTerm::Exact(token) => pb::searchlight::text_query_term::TermType::Exact(
+                    pb::searchlight::ExactTextTerm { token },
+                ),
+                TextQueryTerm::Fuzzy {
+                    token,
+                    max_distance,
+                    prefix,
+                } => pb::searchlight::text_query_term::TermType::Fuzzy(
+                    pb::searchlight::FuzzyTextTerm {
+                        token,
+                        max_distance: *max_distance as u32,
+                        prefix,
+                    },
+                ),
+            };
+        Self {
+            term_type: Some(term_type),
+        }
+    }
+}
+
+impl TryFrom<pb::searchlight::TextQueryTerm> for TextQueryTerm {
+    type Error = anyhow::Error;
+
+    fn try_from(term: pb::searchlight::TextQueryTerm) -> anyhow::Result<Self> {
+        let term = match term.term_type.context("Missing `term_type` field")? {
+            pb::searchlight::text_query_term::TermType::Exact(pb::searchlight::ExactTextTerm {
+                token,
+            }) => TextQueryTerm::Exact(token),
+            pb::searchlight::text_query_term::TermType::Fuzzy(pb::searchlight::FuzzyTextTerm {
+                token,
+                max_distance,
+                prefix,
+            }) => TextQueryTerm::Fuzzy {
+                token,
+                max_distance: u8::try_from(max_distance)?.try_into()?,
+                prefix,
+            },
+        };
+        Ok(term)
+    }
+}
+
+impl From<QueryReads> for pb::function_runner::SearchQueryReads {
+    fn from(reads: QueryReads) -> Self {
+        let text_queries = Vec::from(reads.text_queries)
+            .into_iter()
+            .map(|read| pb::function_runner::TextQueryTermRead {
+                field_path: Some(read.field_path.into()),
+                term: Some(read.term.into()),
+            })
+            .collect();
+        let filter_conditions = Vec::from(reads.filter_conditions)
+            .into_iter()
+            .map(|FilterConditionRead::Must(field_path, value)| {
+                pb::function_runner::FilterConditionRead {
+                    field_path: Some(field_path.into()),
+                    value: Some(value),
+                }
+            })
+            .collect();
+        Self {
+            text_queries,
+            filter_conditions,
+        }
+    }
+}
+
+impl TryFrom<pb::function_runner::SearchQueryReads> for QueryReads {
+    type Error = anyhow::Error;
+
+    fn try_from(reads: pb::function_runner::SearchQueryReads) -> anyhow::Result<Self> {
+        let text_queries: Vec<_> = reads
+            .text_queries
+            .into_iter()
+            .map(|read| {
+                anyhow::Ok(TextQueryTermRead::new(
+                    read.field_path
+                        .context("Missing `field_path` field")?
+                        .try_into()?,
+                    read.term.context("Missing `term` field")?.try_into()?,
+                ))
+            })
+            .try_collect()?;
+        let filter_conditions: Vec<_> = reads
+            .filter_conditions
+            .into_iter()
+            .map(|condition| {
+                anyhow::Ok(FilterConditionRead::Must(
+                    condition
+                        .field_path
+                        .context("Missing `field_path` field")?
+                        .try_into()?,
+                    condition.value.context("Missing `value` field")?,
+                ))
+            })
+            .try_collect()?;
+        Ok(QueryReads::new(
+            text_queries.into(),
+            filter_conditions.into(),
+        ))
+    }
+}
+
 #[cfg(any(test, feature = "testing"))]
 impl Arbitrary for QueryReads {
     type Parameters = ();
@@ -1076,6 +1076,16 @@ impl<T: ShapeConfig> GeneratedSchema<T> {
         } else {
             ExportContext::Infer
         };
+        Self::apply_with_context(schema.as_deref(), exported_value, export_context)
+    }
+
+    /// Decodes an exported value with the given export context, for formats
+    /// that carry the context with each object instead of as an override.
+    pub fn apply_with_context(
+        schema: Option<&Self>,
+        exported_value: JsonValue,
+        export_context: ExportContext,
+    ) -> anyhow::Result<ConvexValue> {
         let unknown = StructuralShape::new(ShapeEnum::Unknown);
         let value = export_context.apply(
             exported_value,
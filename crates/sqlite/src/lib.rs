@@ -103,12 +103,15 @@ impl SqlitePersistence {
 
     #[allow(clippy::needless_lifetimes)]
     #[try_stream(ok = T, error = anyhow::Error)]
-    async fn validate_snapshot<T: 'static>(
+    async fn validate_table_snapshot<T: 'static>(
         &self,
+        tablet_id: TabletId,
         ts: Timestamp,
         retention_validator: Arc<dyn RetentionValidator>,
     ) {
-        retention_validator.validate_snapshot(ts).await?;
+        retention_validator
+            .validate_table_snapshot(tablet_id, ts)
+            .await?;
     }
 
     #[allow(clippy::needless_lifetimes)]
@@ -523,7 +526,7 @@ impl PersistenceReader for SqlitePersistence {
     ) -> IndexStream<'_> {
         let triples = self._index_scan_inner(index_id, tablet_id, read_timestamp, interval, order);
         // index_scan isn't async so we have to validate snapshot as part of the stream.
-        let validate = self.validate_snapshot(read_timestamp, retention_validator);
+        let validate = self.validate_table_snapshot(tablet_id, read_timestamp, retention_validator);
         match triples {
             Ok(s) => (validate.chain(stream::iter(s))).boxed(),
             Err(e) => stream::once(async { Err(e) }).boxed(),
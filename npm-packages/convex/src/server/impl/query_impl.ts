@@ -5,7 +5,7 @@ import {
   filterBuilderImpl,
   serializeExpression,
 } from "./filter_builder_impl.js";
-import { Query, QueryInitializer } from "../query.js";
+import { AggregateResult, Query, QueryInitializer } from "../query.js";
 import { ExpressionOrValue, FilterBuilder } from "../filter_builder.js";
 import { GenericTableInfo } from "../data_model.js";
 import {
@@ -109,6 +109,23 @@ export class QueryInitializerImpl
     return syscallResult;
   }
 
+  async aggregate(
+    indexName: string,
+    indexRange?: (q: IndexRangeBuilderImpl) => IndexRangeBuilderImpl,
+  ): Promise<AggregateResult> {
+    validateArg(indexName, 1, "aggregate", "indexName");
+    let rangeBuilder = IndexRangeBuilderImpl.new();
+    if (indexRange !== undefined) {
+      rangeBuilder = indexRange(rangeBuilder);
+    }
+    const syscallJSON = await performAsyncSyscall("1.0/aggregate", {
+      table: this.tableName,
+      index: indexName,
+      range: rangeBuilder.export(),
+    });
+    return jsonToConvex(syscallJSON) as AggregateResult;
+  }
+
   filter(
     predicate: (
       q: FilterBuilder<GenericTableInfo>,
@@ -98,7 +98,12 @@ export {
 } from "./impl/registration_impl.js";
 export type { IndexRange, IndexRangeBuilder } from "./index_range_builder.js";
 export * from "./pagination.js";
-export type { OrderedQuery, Query, QueryInitializer } from "./query.js";
+export type {
+  AggregateResult,
+  OrderedQuery,
+  Query,
+  QueryInitializer,
+} from "./query.js";
 export type {
   ActionBuilder,
   ArgsArray,
@@ -168,9 +173,19 @@ export type { ComponentDefinition } from "./components/index.js";
 /**
  * @internal
  */
-export type { Index, SearchIndex, VectorIndex } from "./schema.js";
+export type {
+  AggregateIndex,
+  Index,
+  Reference,
+  SearchIndex,
+  VectorIndex,
+} from "./schema.js";
 
 export type {
+  AggregateIndexConfig,
+  IndexOptions,
+  OnDelete,
+  ReferenceConfig,
   SearchIndexConfig,
   VectorIndexConfig,
   TableDefinition,
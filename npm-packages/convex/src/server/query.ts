@@ -1,5 +1,6 @@
 import {
   DocumentByInfo,
+  GenericIndexFields,
   GenericTableInfo,
   IndexNames,
   NamedIndex,
@@ -101,8 +102,57 @@ export interface QueryInitializer<TableInfo extends GenericTableInfo>
    * @internal
    */
   count(): Promise<number>;
+
+  /**
+   * Read the aggregate maintained by an aggregate index over the documents
+   * within an index range.
+   *
+   * Aggregate indexes are defined with
+   * {@link TableDefinition.aggregateIndex}. Reading an aggregate only looks
+   * at the distinct values within the range rather than every document, and
+   * the query only reruns when a document within the range changes.
+   *
+   * @param indexName - The name of the aggregate index.
+   * @param indexRange - An optional index range constructed with the supplied
+   * {@link IndexRangeBuilder}, like in {@link QueryInitializer.withIndex}.
+   * Ranges can't include `_id`. If no index range is present, the whole
+   * table is aggregated.
+   * @returns - The {@link AggregateResult} for the range.
+   */
+  aggregate(
+    indexName: string,
+    indexRange?: (
+      q: IndexRangeBuilder<DocumentByInfo<TableInfo>, GenericIndexFields>,
+    ) => IndexRange,
+  ): Promise<AggregateResult>;
 }
 
+/**
+ * The result of reading an aggregate index with
+ * {@link QueryInitializer.aggregate}.
+ *
+ * @public
+ */
+export type AggregateResult = {
+  /**
+   * The number of documents within the range.
+   */
+  count: number;
+  /**
+   * The sum of the index's `valueField` over documents within the range.
+   * Documents where the field isn't a number are counted but not summed.
+   */
+  sum: number;
+  /**
+   * The smallest `valueField`, or `null` if no document has one.
+   */
+  min: number | null;
+  /**
+   * The largest `valueField`, or `null` if no document has one.
+   */
+  max: number | null;
+};
+
 /**
  * The {@link Query} interface allows functions to read values out of the database.
  *
@@ -32,10 +32,16 @@ import {
   GenericDataModel,
   GenericDocument,
   GenericTableIndexes,
+  GenericTableInfo,
   GenericTableSearchIndexes,
   GenericTableVectorIndexes,
   TableNamesInDataModel,
 } from "../server/data_model.js";
+import { ExpressionOrValue, FilterBuilder } from "./filter_builder.js";
+import {
+  filterBuilderImpl,
+  serializeExpression,
+} from "./impl/filter_builder_impl.js";
 import {
   IdField,
   IndexTiebreakerField,
@@ -43,6 +49,7 @@ import {
   SystemIndexes,
 } from "../server/system_fields.js";
 import { Expand } from "../type_utils.js";
+import { JSONValue } from "../values/index.js";
 import { ObjectValidator, v, Validator } from "../values/validator.js";
 
 /**
@@ -69,6 +76,33 @@ type ExtractDocument<T extends Validator<any, any, any>> =
   //the table name) and trick TypeScript into expanding them.
   Expand<SystemFields & T["type"]>;
 
+/**
+ * Options for a database index.
+ *
+ * @public
+ */
+export interface IndexOptions<
+  TableInfo extends GenericTableInfo = GenericTableInfo,
+> {
+  /**
+   * If true, no two documents in the table may have the same values for the
+   * index's fields. Documents missing any of the fields are exempt.
+   *
+   * Pushing a schema that adds a unique index fails if existing documents
+   * have duplicate values.
+   */
+  unique?: boolean;
+  /**
+   * Only index documents matching this predicate, making a partial index.
+   *
+   * Queries can only use a partial index if their index range guarantees
+   * every document matches the filter, e.g. a filter of
+   * `q.eq(q.field("status"), "open")` requires `.eq("status", "open")` in the
+   * index range.
+   */
+  filter?: (q: FilterBuilder<TableInfo>) => ExpressionOrValue<boolean>;
+}
+
 /**
  * The configuration for a full text search index.
  *
@@ -116,6 +150,69 @@ export interface VectorIndexConfig<
   filterFields?: FilterFields[];
 }
 
+/**
+ * The configuration for an aggregate index.
+ *
+ * @public
+ */
+export interface AggregateIndexConfig<
+  FieldPath extends string,
+  ValueField extends string,
+> {
+  /**
+   * The fields to group by, in order. Aggregates can be read for any index
+   * range over these fields, including the whole table.
+   */
+  fields: FieldPath[];
+  /**
+   * A numeric field to maintain the sum, min and max of. If omitted, only the
+   * count of documents is maintained.
+   */
+  valueField?: ValueField;
+}
+
+/**
+ * @internal
+ */
+export type AggregateIndex = {
+  indexDescriptor: string;
+  fields: string[];
+  valueField?: string;
+};
+
+/**
+ * What happens to the documents referencing a document when it's deleted.
+ *
+ * - `"restrict"`: Deleting the referenced document fails.
+ * - `"cascade"`: The referencing documents are deleted too.
+ * - `"setNull"`: The referencing field is set to `null`. The field's validator
+ *   must allow `v.null()`.
+ *
+ * @public
+ */
+export type OnDelete = "restrict" | "cascade" | "setNull";
+
+/**
+ * The configuration for a reference.
+ *
+ * @public
+ */
+export interface ReferenceConfig {
+  /**
+   * What happens to this document when the document it references is
+   * deleted.
+   */
+  onDelete: OnDelete;
+}
+
+/**
+ * @internal
+ */
+export type Reference = {
+  fieldName: string;
+  onDelete: OnDelete;
+};
+
 /**
  * @internal
  */
@@ -132,6 +229,8 @@ export type VectorIndex = {
 export type Index = {
   indexDescriptor: string;
   fields: string[];
+  unique?: boolean;
+  filter?: JSONValue;
 };
 
 /**
@@ -161,6 +260,8 @@ export class TableDefinition<
   private indexes: Index[];
   private searchIndexes: SearchIndex[];
   private vectorIndexes: VectorIndex[];
+  private aggregateIndexes: AggregateIndex[];
+  private references: Reference[];
   // The type of documents stored in this table.
   private documentType: Validator<any, any, any>;
 
@@ -171,6 +272,8 @@ export class TableDefinition<
     this.indexes = [];
     this.searchIndexes = [];
     this.vectorIndexes = [];
+    this.aggregateIndexes = [];
+    this.references = [];
     this.documentType = documentType;
   }
 
@@ -182,6 +285,7 @@ export class TableDefinition<
    * @param name - The name of the index.
    * @param fields - The fields to index, in order. Must specify at least one
    * field.
+   * @param options - Optional {@link IndexOptions}, such as `unique`.
    * @returns A {@link TableDefinition} with this index included.
    */
   index<
@@ -191,6 +295,13 @@ export class TableDefinition<
   >(
     name: IndexName,
     fields: [FirstFieldPath, ...RestFieldPaths],
+    options?: IndexOptions<{
+      document: Document;
+      fieldPaths: FieldPaths;
+      indexes: Indexes;
+      searchIndexes: SearchIndexes;
+      vectorIndexes: VectorIndexes;
+    }>,
   ): TableDefinition<
     Document,
     FieldPaths,
@@ -206,7 +317,14 @@ export class TableDefinition<
     SearchIndexes,
     VectorIndexes
   > {
-    this.indexes.push({ indexDescriptor: name, fields });
+    this.indexes.push({
+      indexDescriptor: name,
+      fields,
+      ...(options?.unique ? { unique: true } : {}),
+      ...(options?.filter
+        ? { filter: serializeExpression(options.filter(filterBuilderImpl)) }
+        : {}),
+    });
     return this;
   }
 
@@ -294,6 +412,70 @@ export class TableDefinition<
     return this;
   }
 
+  /**
+   * Define an aggregate index on this table.
+   *
+   * An aggregate index maintains the count of documents, and optionally the
+   * sum, min and max of a numeric field, for every range of its fields. Read
+   * it with `db.query(table).aggregate(name, q => q.eq(field, value))`.
+   *
+   * @param name - The name of the index.
+   * @param indexConfig - The aggregate index configuration object.
+   * @returns A {@link TableDefinition} with this aggregate index included.
+   */
+  aggregateIndex<
+    FieldPath extends FieldPaths,
+    ValueField extends FieldPaths = never,
+  >(
+    name: string,
+    indexConfig: Expand<AggregateIndexConfig<FieldPath, ValueField>>,
+  ): TableDefinition<
+    Document,
+    FieldPaths,
+    Indexes,
+    SearchIndexes,
+    VectorIndexes
+  > {
+    this.aggregateIndexes.push({
+      indexDescriptor: name,
+      fields: indexConfig.fields,
+      ...(indexConfig.valueField !== undefined
+        ? { valueField: indexConfig.valueField }
+        : {}),
+    });
+    return this;
+  }
+
+  /**
+   * Enforce that a `v.id(...)` field references an existing document.
+   *
+   * Inserts and updates fail if the field holds the ID of a document that
+   * doesn't exist, and `onDelete` decides what happens to this document when
+   * the document it references is deleted. The field must be a top-level
+   * `v.id(...)`, optionally unioned with `v.null()`, and the table must have an
+   * index whose first field is the reference field.
+   *
+   * @param fieldName - The name of the `v.id(...)` field.
+   * @param referenceConfig - The reference configuration object.
+   * @returns A {@link TableDefinition} with this reference included.
+   */
+  reference(
+    fieldName: FieldPaths,
+    referenceConfig: ReferenceConfig,
+  ): TableDefinition<
+    Document,
+    FieldPaths,
+    Indexes,
+    SearchIndexes,
+    VectorIndexes
+  > {
+    this.references.push({
+      fieldName,
+      onDelete: referenceConfig.onDelete,
+    });
+    return this;
+  }
+
   /**
    * Work around for https://github.com/microsoft/TypeScript/issues/57035
    */
@@ -317,6 +499,10 @@ export class TableDefinition<
       indexes: this.indexes,
       searchIndexes: this.searchIndexes,
       vectorIndexes: this.vectorIndexes,
+      ...(this.aggregateIndexes.length > 0
+        ? { aggregateIndexes: this.aggregateIndexes }
+        : {}),
+      ...(this.references.length > 0 ? { references: this.references } : {}),
       documentType: this.documentType.json,
     };
   }
@@ -441,17 +627,10 @@ export class SchemaDefinition<
    */
   export(): string {
     return JSON.stringify({
-      tables: Object.entries(this.tables).map(([tableName, definition]) => {
-        const { indexes, searchIndexes, vectorIndexes, documentType } =
-          definition.export();
-        return {
-          tableName,
-          indexes,
-          searchIndexes,
-          vectorIndexes,
-          documentType,
-        };
-      }),
+      tables: Object.entries(this.tables).map(([tableName, definition]) => ({
+        tableName,
+        ...definition.export(),
+      })),
       schemaValidation: this.schemaValidation,
     });
   }
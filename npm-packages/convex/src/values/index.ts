@@ -18,9 +18,14 @@ export type {
   PropertyValidators,
   ObjectType,
   ObjectValidator,
+  ValueConstraints,
 } from "./validator.js";
 /* @internal */
-export type { ValidatorJSON, ObjectFieldType } from "./validator.js";
+export type {
+  ValidatorJSON,
+  ObjectFieldType,
+  ValueConstraintsJSON,
+} from "./validator.js";
 import * as Base64 from "./base64.js";
 export { Base64 };
 export type { Infer } from "./validator.js";
@@ -0,0 +1,26 @@
+import { describe, expect, test } from "@jest/globals";
+import { v } from "./validator.js";
+
+describe("v.constrained", () => {
+  test("patterns keep their source", () => {
+    const validator = v.constrained(v.string(), { pattern: /^[a-z]+$/ });
+    expect(validator.json).toEqual({
+      type: "string",
+      constraints: { pattern: "^[a-z]+$" },
+    });
+  });
+
+  test("pattern flags are written inline", () => {
+    const validator = v.constrained(v.string(), { pattern: /^a.b$/gimsu });
+    expect(validator.json).toEqual({
+      type: "string",
+      constraints: { pattern: "(?ims)^a.b$" },
+    });
+  });
+
+  test("unsupported pattern flags are rejected", () => {
+    expect(() => v.constrained(v.string(), { pattern: /^a/y })).toThrow(
+      'Unsupported flag "y"',
+    );
+  });
+});
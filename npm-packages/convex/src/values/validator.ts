@@ -1,4 +1,9 @@
-import { JSONValue, convexToJson, Id as GenericId } from "./value.js";
+import {
+  JSONValue,
+  Value,
+  convexToJson,
+  Id as GenericId,
+} from "./value.js";
 import { Expand } from "../type_utils.js";
 
 /**
@@ -48,7 +53,19 @@ export type ObjectFieldType = { fieldType: ValidatorJSON; optional: boolean };
 /**
  * @internal
  */
-export type ValidatorJSON =
+export type ValueConstraintsJSON = {
+  minLength?: number;
+  maxLength?: number;
+  pattern?: string;
+  minimum?: JSONValue;
+  maximum?: JSONValue;
+  default?: JSONValue;
+};
+
+/**
+ * @internal
+ */
+export type ValidatorJSON = (
   | {
       type: "null";
     }
@@ -66,7 +83,35 @@ export type ValidatorJSON =
   | { type: "array"; value: ValidatorJSON }
   | { type: "record"; keys: ValidatorJSON; values: ObjectFieldType }
   | { type: "object"; value: Record<string, ObjectFieldType> }
-  | { type: "union"; value: ValidatorJSON[] };
+  | { type: "union"; value: ValidatorJSON[] }
+) & { constraints?: ValueConstraintsJSON };
+
+/**
+ * Constraints on the values a validator accepts on top of its type.
+ *
+ * Which constraints are allowed depends on the validator:
+ * - `minLength` and `maxLength` apply to `v.string()` (counting characters),
+ *   `v.bytes()` and `v.array()`.
+ * - `pattern` applies to `v.string()`. It's matched with Rust's
+ *   [regex syntax](https://docs.rs/regex/latest/regex/#syntax), which is
+ *   close to JavaScript's but has no lookaround or backreferences. The `i`,
+ *   `m` and `s` flags of a `RegExp` are kept as inline flags like `(?i)`;
+ *   the `y` and `v` flags aren't supported.
+ * - `minimum` and `maximum` are inclusive bounds for `v.float64()` (as
+ *   numbers) and `v.int64()` (as bigints).
+ * - `default` applies to any validator. It's filled in when an object field
+ *   is missing from function arguments or from a document being inserted.
+ *
+ * @public
+ */
+export type ValueConstraints<T> = {
+  minLength?: number;
+  maxLength?: number;
+  pattern?: string | RegExp;
+  minimum?: T extends number | bigint ? T : never;
+  maximum?: T extends number | bigint ? T : never;
+  default?: T;
+};
 
 /**
  * The validator builder.
@@ -173,6 +218,38 @@ export const v = {
   any(): Validator<any, false, string> {
     return new Validator({ type: "any" }, false);
   },
+  /**
+   * Constrain the values `inner` accepts, e.g. a string's length or a
+   * number's range. See {@link ValueConstraints}.
+   */
+  constrained<T extends Validator<any, false, any>>(
+    inner: T,
+    constraints: ValueConstraints<T["type"]>,
+  ): T {
+    const json: ValueConstraintsJSON = {};
+    if (constraints.minLength !== undefined) {
+      json.minLength = constraints.minLength;
+    }
+    if (constraints.maxLength !== undefined) {
+      json.maxLength = constraints.maxLength;
+    }
+    if (constraints.pattern !== undefined) {
+      json.pattern =
+        typeof constraints.pattern === "string"
+          ? constraints.pattern
+          : regExpPattern(constraints.pattern);
+    }
+    if (constraints.minimum !== undefined) {
+      json.minimum = convexToJson(constraints.minimum as Value);
+    }
+    if (constraints.maximum !== undefined) {
+      json.maximum = convexToJson(constraints.maximum as Value);
+    }
+    if (constraints.default !== undefined) {
+      json.default = convexToJson(constraints.default as Value);
+    }
+    return new Validator({ ...inner.json, constraints: json }, false) as T;
+  },
   optional<T extends Validator<any, false, any>>(
     inner: T,
   ): Validator<T["type"] | undefined, true, T["fieldPaths"]> {
@@ -184,6 +261,27 @@ export const v = {
   },
 };
 
+/**
+ * The pattern sent to the server for `pattern`, with the flags that change
+ * what it matches written inline since Rust regexes take no separate flags.
+ */
+function regExpPattern(pattern: RegExp): string {
+  // `g` and `d` don't change whether a string matches, and Rust regexes are
+  // always Unicode-aware, so those flags are dropped.
+  let inlineFlags = "";
+  for (const flag of pattern.flags) {
+    if (flag === "i" || flag === "m" || flag === "s") {
+      inlineFlags += flag;
+    } else if (flag !== "g" && flag !== "u" && flag !== "d") {
+      throw new Error(
+        `Unsupported flag "${flag}" in pattern ${pattern.toString()}: ` +
+          `only the "i", "m" and "s" flags are supported.`,
+      );
+    }
+  }
+  return inlineFlags ? `(?${inlineFlags})${pattern.source}` : pattern.source;
+}
+
 /**
  * Validators for each property of an object.
  *
@@ -0,0 +1,387 @@
+// Enforces a deployment's egress policy on the user code in actions.
+//
+// The policy is bound to each request with `AsyncLocalStorage`, so work an
+// action leaves running after it returns stays subject to that action's
+// policy, and can't pick up the policy of whatever request runs next. Within
+// that, we check every outbound TCP connection and refuse to start child
+// processes, worker threads or UDP sockets, which could otherwise connect
+// without going through this module.
+//
+// This runs in the same process as the user code, so it's a best-effort
+// check rather than a sandbox. In particular it doesn't cover:
+// - Sockets opened under an earlier request's policy, or before any policy,
+//   and reused afterwards, like pooled keep-alive connections.
+// - DNS lookups, which happen before the resolved addresses are checked.
+// - Native addons and internal bindings like `process.binding`.
+// - User code that restores the original `net`, `child_process` or
+//   `worker_threads` functions.
+// Deployments that need a hard guarantee should also restrict egress at the
+// network level.
+import { AsyncLocalStorage } from "node:async_hooks";
+import childProcess from "node:child_process";
+import dgram from "node:dgram";
+import dns from "node:dns";
+import { syncBuiltinESMExports } from "node:module";
+import net from "node:net";
+import workerThreads from "node:worker_threads";
+
+// Mirrors `EgressPolicy` in `common::http::fetch::egress`.
+export type EgressPolicy = {
+  allowedHosts?: string[];
+  deniedHosts?: string[];
+  allowedCidrs?: string[];
+  deniedCidrs?: string[];
+  allowedPorts?: number[];
+  blockPrivateAddresses?: boolean;
+  maxResponseBytes?: number | null;
+  maxRequestsPerMinutePerFunction?: number | null;
+};
+
+// Backend callbacks use this so they're never subject to the user's egress
+// policy.
+export const originalFetch = globalThis.fetch;
+
+type ActivePolicy = {
+  policy: EgressPolicy;
+  functionKey: string;
+  // `host:port` pairs the executor itself needs to reach, like the backend.
+  exemptEndpoints: Set<string>;
+};
+
+const active = new AsyncLocalStorage<ActivePolicy>();
+// Request timestamps per function, for the per-minute rate limit. This is per
+// worker process, so it's an upper bound on each process rather than global.
+const recentRequests = new Map<string, number[]>();
+
+export class EgressPolicyViolation extends Error {
+  constructor(message: string) {
+    super(`EgressPolicyViolation: ${message}`);
+    this.name = "EgressPolicyViolation";
+  }
+}
+
+function violation(reason: string, message: string): EgressPolicyViolation {
+  console.warn(`Blocked outbound request (${reason}): ${message}`);
+  return new EgressPolicyViolation(message);
+}
+
+// Runs `fn` with `policy` applied to everything it does, including async work
+// that outlives it. A `null` policy runs `fn` unrestricted.
+export function runWithEgressPolicy<T>(
+  policy: EgressPolicy | null,
+  functionKey: string,
+  exemptUrls: string[],
+  fn: () => Promise<T>,
+): Promise<T> {
+  if (policy === null) {
+    return active.exit(fn);
+  }
+  const exemptEndpoints = new Set(
+    exemptUrls.map((url) => {
+      const parsed = new URL(url);
+      const port = parsed.port || (parsed.protocol === "https:" ? 443 : 80);
+      // IPv6 hostnames are bracketed in URLs but not when connecting.
+      const host = parsed.hostname.replace(/^\[(.*)\]$/, "$1");
+      return endpoint(host, Number(port));
+    }),
+  );
+  return active.run({ policy, functionKey, exemptEndpoints }, fn);
+}
+
+function endpoint(host: string, port: number): string {
+  return `${host.toLowerCase()}:${port}`;
+}
+
+function matchesHost(pattern: string, host: string): boolean {
+  const p = pattern.toLowerCase();
+  if (p.startsWith("*.")) {
+    return host.endsWith(p.slice(1));
+  }
+  return host === p;
+}
+
+function parseIp(address: string): { bytes: number[]; v4: boolean } | null {
+  if (net.isIPv4(address)) {
+    return { bytes: address.split(".").map((b) => parseInt(b, 10)), v4: true };
+  }
+  if (!net.isIPv6(address)) {
+    return null;
+  }
+  // Expand `::` and a trailing dotted IPv4 part into 16 bytes.
+  let s = address.split("%")[0];
+  const dotted = s.match(/(\d+\.\d+\.\d+\.\d+)$/);
+  let tail: number[] = [];
+  if (dotted) {
+    tail = dotted[1].split(".").map((b) => parseInt(b, 10));
+    s = s.slice(0, -dotted[1].length).replace(/:$/, ":0:0");
+  }
+  const [head, rest] = s.split("::");
+  const headGroups = head ? head.split(":") : [];
+  const restGroups = rest ? rest.split(":") : [];
+  const missing = 8 - headGroups.length - restGroups.length;
+  const groups = [
+    ...headGroups,
+    ...Array(rest !== undefined ? missing : 0).fill("0"),
+    ...restGroups,
+  ].map((g) => parseInt(g, 16));
+  const bytes = groups.flatMap((g) => [g >> 8, g & 0xff]);
+  if (dotted) {
+    bytes.splice(12, 4, ...tail);
+  }
+  // Treat IPv4-mapped addresses as IPv4.
+  const mapped =
+    bytes.slice(0, 10).every((b) => b === 0) &&
+    bytes[10] === 0xff &&
+    bytes[11] === 0xff;
+  return mapped ? { bytes: bytes.slice(12), v4: true } : { bytes, v4: false };
+}
+
+function inCidr(address: string, cidr: string): boolean {
+  const [network, lengthStr] = cidr.split("/");
+  const ip = parseIp(address);
+  const base = parseIp(network);
+  if (ip === null || base === null || ip.v4 !== base.v4) {
+    return false;
+  }
+  let remaining = lengthStr === undefined ? ip.bytes.length * 8 : +lengthStr;
+  for (let i = 0; i < ip.bytes.length && remaining > 0; i++) {
+    const bits = Math.min(8, remaining);
+    const mask = (0xff << (8 - bits)) & 0xff;
+    if ((ip.bytes[i] & mask) !== (base.bytes[i] & mask)) {
+      return false;
+    }
+    remaining -= bits;
+  }
+  return true;
+}
+
+const PRIVATE_CIDRS = [
+  "0.0.0.0/8",
+  "10.0.0.0/8",
+  "100.64.0.0/10",
+  "127.0.0.0/8",
+  "169.254.0.0/16",
+  "172.16.0.0/12",
+  "192.168.0.0/16",
+  "255.255.255.255/32",
+  "::/128",
+  "::1/128",
+  "fc00::/7",
+  "fe80::/10",
+];
+
+function checkHost(policy: EgressPolicy, host: string, port: number): boolean {
+  const allowedPorts = policy.allowedPorts ?? [];
+  if (allowedPorts.length > 0 && !allowedPorts.includes(port)) {
+    throw violation("port", `port ${port} isn't allowed`);
+  }
+  if ((policy.deniedHosts ?? []).some((p) => matchesHost(p, host))) {
+    throw violation("denied_host", `host ${host} is denied`);
+  }
+  return (policy.allowedHosts ?? []).some((p) => matchesHost(p, host));
+}
+
+function checkAddress(
+  policy: EgressPolicy,
+  host: string,
+  address: string,
+  hostAllowed: boolean,
+) {
+  if ((policy.deniedCidrs ?? []).some((c) => inCidr(address, c))) {
+    throw violation("denied_cidr", `${host} resolves to denied ${address}`);
+  }
+  const allowedHosts = policy.allowedHosts ?? [];
+  const allowedCidrs = policy.allowedCidrs ?? [];
+  const cidrAllowed = allowedCidrs.some((c) => inCidr(address, c));
+  if (
+    (allowedHosts.length > 0 || allowedCidrs.length > 0) &&
+    !hostAllowed &&
+    !cidrAllowed
+  ) {
+    throw violation("not_allowed", `host ${host} isn't allowed`);
+  }
+  if (
+    (policy.blockPrivateAddresses ?? true) &&
+    !cidrAllowed &&
+    PRIVATE_CIDRS.some((c) => inCidr(address, c))
+  ) {
+    throw violation(
+      "private_address",
+      `${host} resolves to private address ${address}`,
+    );
+  }
+}
+
+// Checks every outbound TCP connection, which covers `fetch`, `http`, `https`
+// and `net` alike. Hostnames are checked against the addresses they resolve to
+// right before connecting.
+const originalConnect = net.Socket.prototype.connect;
+net.Socket.prototype.connect = function (this: net.Socket, ...args: any[]) {
+  const policy = active.getStore();
+  // `net.connect` and friends pass their already normalized arguments as an
+  // array.
+  const preNormalized = Array.isArray(args[0]);
+  const normalized: any[] = preNormalized
+    ? args[0]
+    : (net as any)._normalizeArgs
+      ? (net as any)._normalizeArgs(args)
+      : args;
+  const options = normalized[0];
+  if (
+    policy === undefined ||
+    typeof options !== "object" ||
+    options === null ||
+    options.path !== undefined
+  ) {
+    return originalConnect.apply(this, args as any);
+  }
+  const host = String(options.host ?? "localhost").toLowerCase();
+  const port = Number(options.port);
+  if (policy.exemptEndpoints.has(endpoint(host, port))) {
+    return originalConnect.apply(this, args as any);
+  }
+  try {
+    const hostAllowed = checkHost(policy.policy, host, port);
+    if (net.isIP(host)) {
+      checkAddress(policy.policy, host, host, hostAllowed);
+      return originalConnect.apply(this, args as any);
+    }
+    const lookup = options.lookup ?? dns.lookup;
+    const checkedLookup = (hostname: string, opts: any, callback: any) => {
+      lookup(hostname, opts, (err: any, address: any, family: any) => {
+        if (err) {
+          return callback(err, address, family);
+        }
+        try {
+          const addresses = Array.isArray(address)
+            ? address.map((a: any) => a.address)
+            : [address];
+          for (const a of addresses) {
+            checkAddress(policy.policy, host, a, hostAllowed);
+          }
+        } catch (e) {
+          return callback(e);
+        }
+        callback(null, address, family);
+      });
+    };
+    normalized[0] = { ...options, lookup: checkedLookup };
+    return originalConnect.apply(
+      this,
+      (preNormalized ? [normalized] : normalized) as any,
+    );
+  } catch (e: any) {
+    process.nextTick(() => this.destroy(e));
+    return this;
+  }
+} as any;
+
+// Child processes, worker threads and UDP sockets would get around the checks
+// above, so they're unavailable while a policy is in effect.
+function blockUnderPolicy(module: any, name: string) {
+  const original = module[name];
+  module[name] = function (this: any, ...args: any[]) {
+    if (active.getStore() !== undefined) {
+      throw violation(
+        "unsupported_api",
+        `${name} isn't available when the deployment has an egress policy`,
+      );
+    }
+    return original.apply(this, args);
+  };
+}
+for (const name of [
+  "exec",
+  "execFile",
+  "execFileSync",
+  "execSync",
+  "fork",
+  "spawn",
+  "spawnSync",
+]) {
+  blockUnderPolicy(childProcess, name);
+}
+blockUnderPolicy(dgram, "createSocket");
+const OriginalWorker = workerThreads.Worker;
+(workerThreads as any).Worker = class Worker extends OriginalWorker {
+  constructor(...args: ConstructorParameters<typeof OriginalWorker>) {
+    if (active.getStore() !== undefined) {
+      throw violation(
+        "unsupported_api",
+        "Worker isn't available when the deployment has an egress policy",
+      );
+    }
+    super(...args);
+  }
+};
+// Update the named exports seen by `import { spawn } from "child_process"`.
+syncBuiltinESMExports();
+
+function checkRateLimit(policy: ActivePolicy) {
+  const limit = policy.policy.maxRequestsPerMinutePerFunction;
+  if (!limit) {
+    return;
+  }
+  const now = Date.now();
+  const recent = (recentRequests.get(policy.functionKey) ?? []).filter(
+    (t) => t > now - 60 * 1000,
+  );
+  if (recent.length >= limit) {
+    throw violation(
+      "rate_limited",
+      `${policy.functionKey} exceeded ${limit} requests per minute`,
+    );
+  }
+  recent.push(now);
+  recentRequests.set(policy.functionKey, recent);
+}
+
+function limitResponseSize(response: Response, maxBytes: number): Response {
+  const contentLength = response.headers.get("content-length");
+  if (contentLength !== null && Number(contentLength) > maxBytes) {
+    throw violation(
+      "response_size",
+      `response of ${contentLength} bytes exceeds limit of ${maxBytes} bytes`,
+    );
+  }
+  if (response.body === null) {
+    return response;
+  }
+  let received = 0;
+  const limited = response.body.pipeThrough(
+    new TransformStream<Uint8Array, Uint8Array>({
+      transform(chunk, controller) {
+        received += chunk.byteLength;
+        if (received > maxBytes) {
+          controller.error(
+            violation(
+              "response_size",
+              `response exceeds limit of ${maxBytes} bytes`,
+            ),
+          );
+          return;
+        }
+        controller.enqueue(chunk);
+      },
+    }),
+  );
+  const result = new Response(limited, {
+    status: response.status,
+    statusText: response.statusText,
+    headers: response.headers,
+  });
+  Object.defineProperty(result, "url", { value: response.url });
+  Object.defineProperty(result, "redirected", { value: response.redirected });
+  return result;
+}
+
+globalThis.fetch = async function (input: any, init?: any) {
+  const policy = active.getStore();
+  if (policy === undefined) {
+    return originalFetch(input, init);
+  }
+  checkRateLimit(policy);
+  const response = await originalFetch(input, init);
+  const maxBytes = policy.policy.maxResponseBytes;
+  return maxBytes ? limitResponseSize(response, maxBytes) : response;
+};
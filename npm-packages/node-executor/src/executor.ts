@@ -28,6 +28,7 @@ import { SourcePackage, maybeDownloadAndLinkPackages } from "./source_package";
 import { buildDeps, BuildDepsRequest } from "./build_deps";
 import { ConvexError, JSONValue } from "convex/values";
 import { logDebug, logDurationMs } from "./log";
+import { EgressPolicy, runWithEgressPolicy } from "./egress";
 
 // When we bundle commonJS modules as ESM with esbuild, the bundled code might still use
 // `require`, exports, module, __dirname or __filename despite being in ESM.
@@ -119,6 +120,7 @@ export type ExecuteRequest = {
   npmVersion: string | null;
   executionContext: ExecutionContext;
   encodedParentTrace: string | null;
+  egressPolicy: EgressPolicy | null;
 };
 
 export type ExecutionContext = {
@@ -194,15 +196,22 @@ export async function execute(
         `Couldn't find module source for ${request.udfPath.canonicalizedPath}`,
       );
     }
-    innerResult = await executeInner(
-      request.requestId,
-      local.dir,
-      request.udfPath.canonicalizedPath,
-      request.udfPath.function ?? "default",
-      request.args,
-      request.environmentVariables,
-      request.timeoutSecs,
-      syscalls,
+    // Only user code run by `execute` is subject to the egress policy.
+    innerResult = await runWithEgressPolicy(
+      request.egressPolicy,
+      `${request.udfPath.canonicalizedPath}:${request.udfPath.function ?? "default"}`,
+      [request.backendAddress],
+      () =>
+        executeInner(
+          request.requestId,
+          local.dir,
+          request.udfPath.canonicalizedPath,
+          request.udfPath.function ?? "default",
+          request.args,
+          request.environmentVariables,
+          request.timeoutSecs,
+          syscalls,
+        ),
     );
   } catch (e: any) {
     innerResult = {
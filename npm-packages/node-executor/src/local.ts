@@ -5,26 +5,33 @@ import { log, setDebugLogging } from "./log";
 import os from "node:os";
 import crypto from "crypto";
 import fs from "node:fs";
+import readline from "node:readline";
 import { Writable } from "node:stream";
 
-async function main(request_str: string, debug: boolean) {
-  let request;
-  setDebugLogging(debug);
+function parseRequest(request_str: string) {
   try {
-    request = JSON.parse(request_str);
+    return JSON.parse(request_str);
   } catch (err: any) {
     throw new Error(
       `Failed to parse request json. Error: ${err.message.toString()}`,
     );
   }
-  request.requestId = uuidv4();
+}
 
-  // Monkey-patch os.tmpdir to avoid filesystem write races
+// Monkey-patch os.tmpdir to avoid filesystem write races
+function patchTempdir() {
   const prevTempdir = os.tmpdir();
   const seed = crypto.randomBytes(20).toString("hex");
   const tempdir = `${prevTempdir}/${seed}`;
   fs.mkdirSync(tempdir);
   os.tmpdir = () => tempdir;
+}
+
+async function main(request_str: string, debug: boolean) {
+  setDebugLogging(debug);
+  const request = parseRequest(request_str);
+  request.requestId = uuidv4();
+  patchTempdir();
 
   const responseStream = new Writable({
     write: (chunk, _encoding, callback) => {
@@ -39,14 +46,72 @@ async function main(request_str: string, debug: boolean) {
   process.exit(0);
 }
 
+type WorkerFrame =
+  | { kind: "part"; id: number; data: string }
+  | { kind: "done"; id: number; memoryUsedInMb: number }
+  | { kind: "error"; id: number; message: string };
+
+function writeFrame(frame: WorkerFrame) {
+  // Frames are single-line JSON, so anything else on stdout (e.g. debug
+  // logging) is skipped by the backend.
+  log(JSON.stringify(frame));
+}
+
+// Runs requests read from stdin, one `{ id, request }` JSON object per line,
+// one at a time. Source packages stay downloaded and imported between
+// requests, like in a warm AWS Lambda.
+async function worker(debug: boolean) {
+  setDebugLogging(debug);
+  patchTempdir();
+  const lines = readline.createInterface({
+    input: process.stdin,
+    crlfDelay: Infinity,
+  });
+  for await (const line of lines) {
+    if (line.trim() === "") {
+      continue;
+    }
+    let id = -1;
+    try {
+      const frame = parseRequest(line);
+      id = frame.id;
+      const request = frame.request;
+      request.requestId = uuidv4();
+      const responseStream = new Writable({
+        write: (chunk, _encoding, callback) => {
+          writeFrame({ kind: "part", id, data: chunk.toString() });
+          callback();
+        },
+      });
+      await invoke(request, responseStream);
+      responseStream.end();
+      writeFrame({
+        kind: "done",
+        id,
+        memoryUsedInMb: Math.ceil(process.memoryUsage().rss / (1024 * 1024)),
+      });
+    } catch (err: any) {
+      writeFrame({ kind: "error", id, message: err?.stack ?? `${err}` });
+    }
+  }
+  process.exit(0);
+}
+
 const program = new Command();
 program
   .name("node-executor")
   .description("node-executor executes a actions locally")
   .usage("command url [options]")
   .option("--debug", "print debug output", false)
-  .requiredOption("--request <json>", "json request serialized as string")
+  .option("--request <json>", "json request serialized as string")
+  .option("--worker", "read requests from stdin until it is closed", false)
   .action(async (options) => {
-    await main(options.request, options.debug);
+    if (options.worker) {
+      await worker(options.debug);
+    } else if (options.request !== undefined) {
+      await main(options.request, options.debug);
+    } else {
+      program.error("Either --request or --worker is required");
+    }
   });
 program.parseAsync(process.argv);
@@ -4,6 +4,7 @@ import { UserIdentity } from "convex/server";
 import { ExecutionContext, SyscallStats } from "./executor";
 import { ConvexError, JSONValue } from "convex/values";
 import { UdfPath } from "./convex";
+import { originalFetch } from "./egress";
 
 const MAX_PENDING_SYSCALLS = 1000;
 
@@ -121,7 +122,7 @@ export class SyscallsImpl {
   }): Promise<z.infer<ResponseValidator>> {
     const headers = this.headers(args.version);
     const url = new URL(args.path, this.backendAddress);
-    const response = await fetch(url, {
+    const response = await originalFetch(url, {
       body: JSON.stringify(args.body),
       method: "POST",
       headers,
@@ -624,7 +625,7 @@ export class SyscallsImpl {
     }
 
     const uploadUrl = await this._storageGenerateUploadUrl(args["version"]);
-    const response = await fetch(uploadUrl, {
+    const response = await originalFetch(uploadUrl, {
       method: "POST",
       body: blob,
       headers: headers,
@@ -660,7 +661,7 @@ export class SyscallsImpl {
     if (getUrl === null) {
       return null;
     }
-    const getResult = await fetch(getUrl);
+    const getResult = await originalFetch(getUrl);
     return await getResult.blob();
   }
 }
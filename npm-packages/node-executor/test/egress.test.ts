@@ -0,0 +1,160 @@
+import childProcess from "node:child_process";
+import http from "node:http";
+import net, { AddressInfo } from "node:net";
+import { Worker } from "node:worker_threads";
+import { EgressPolicyViolation, runWithEgressPolicy } from "../src/egress";
+
+// Serves `size` bytes, in two chunks without a `Content-Length` header for
+// `/chunked`.
+function serve(): Promise<http.Server> {
+  return new Promise((resolve) => {
+    const server = http.createServer((req, res) => {
+      // Don't let `fetch` reuse connections that were checked under an earlier
+      // policy.
+      res.setHeader("Connection", "close");
+      const url = new URL(req.url!, "http://localhost");
+      const size = Number(url.searchParams.get("size") ?? 5);
+      if (url.pathname === "/chunked") {
+        res.write("a".repeat(size / 2));
+        res.end("a".repeat(size / 2));
+      } else {
+        res.end("a".repeat(size));
+      }
+    });
+    server.listen(0, "127.0.0.1", () => resolve(server));
+  });
+}
+
+async function expectBlocked(promise: Promise<unknown>) {
+  let error: any = null;
+  try {
+    await promise;
+  } catch (e: any) {
+    // `fetch` wraps connection errors in a `TypeError`.
+    error = e.cause ?? e;
+  }
+  expect(error).toBeInstanceOf(EgressPolicyViolation);
+}
+
+describe("egress policy", () => {
+  let server: http.Server;
+  let otherServer: http.Server;
+  let port: number;
+  let otherPort: number;
+
+  beforeAll(async () => {
+    jest.spyOn(console, "warn").mockImplementation(() => {});
+    server = await serve();
+    otherServer = await serve();
+    port = (server.address() as AddressInfo).port;
+    otherPort = (otherServer.address() as AddressInfo).port;
+  });
+
+  afterAll(() => {
+    server.close();
+    otherServer.close();
+  });
+
+  test("blocks private addresses by default", async () => {
+    await runWithEgressPolicy({}, "a.js:default", [], async () => {
+      await expectBlocked(fetch(`http://127.0.0.1:${port}/`));
+      // Hostnames are checked against the addresses they resolve to.
+      await expectBlocked(fetch(`http://localhost:${port}/`));
+    });
+  });
+
+  test("blocks net sockets", async () => {
+    const error = await runWithEgressPolicy({}, "a.js:default", [], () => {
+      return new Promise((resolve) => {
+        net.connect(port, "127.0.0.1").on("error", resolve);
+      });
+    });
+    expect(error).toBeInstanceOf(EgressPolicyViolation);
+  });
+
+  test("blocks child processes and workers", async () => {
+    await runWithEgressPolicy({}, "a.js:default", [], async () => {
+      expect(() => childProcess.execSync("true")).toThrow(
+        EgressPolicyViolation,
+      );
+      expect(() => new Worker("", { eval: true })).toThrow(
+        EgressPolicyViolation,
+      );
+    });
+    // They're still available to the executor itself.
+    expect(childProcess.execSync("echo hi").toString()).toEqual("hi\n");
+  });
+
+  test("policy stays with the request that set it", async () => {
+    let later!: Promise<Response>;
+    await runWithEgressPolicy({}, "a.js:default", [], async () => {
+      // Left running after the request returns.
+      later = new Promise((resolve) => setTimeout(resolve, 10)).then(() =>
+        fetch(`http://127.0.0.1:${port}/`),
+      );
+    });
+    const blocked = expectBlocked(later);
+    // A later request without a policy isn't restricted by it...
+    const response = await runWithEgressPolicy(null, "", [], () =>
+      fetch(`http://127.0.0.1:${port}/`),
+    );
+    expect(await response.text()).toEqual("aaaaa");
+    // ...and it still applies to the work left behind.
+    await blocked;
+  });
+
+  test("exempt URLs only exempt their host and port", async () => {
+    const exempt = [`http://127.0.0.1:${port}`];
+    await runWithEgressPolicy({}, "a.js:default", exempt, async () => {
+      const response = await fetch(`http://127.0.0.1:${port}/`);
+      expect(await response.text()).toEqual("aaaaa");
+      await expectBlocked(fetch(`http://127.0.0.1:${otherPort}/`));
+    });
+  });
+
+  test("allowed CIDRs and ports", async () => {
+    const policy = { allowedCidrs: ["127.0.0.0/8"] };
+    await runWithEgressPolicy(policy, "a.js:default", [], async () => {
+      const response = await fetch(`http://localhost:${otherPort}/`);
+      expect(await response.text()).toEqual("aaaaa");
+    });
+
+    await runWithEgressPolicy(
+      { ...policy, allowedPorts: [port] },
+      "a.js:default",
+      [],
+      async () => {
+        await expectBlocked(fetch(`http://127.0.0.1:${otherPort}/`));
+      },
+    );
+  });
+
+  test("rate limits each function", async () => {
+    const policy = {
+      allowedCidrs: ["127.0.0.0/8"],
+      maxRequestsPerMinutePerFunction: 2,
+    };
+    await runWithEgressPolicy(policy, "limited.js:a", [], async () => {
+      await (await fetch(`http://127.0.0.1:${port}/`)).text();
+      await (await fetch(`http://127.0.0.1:${port}/`)).text();
+      await expectBlocked(fetch(`http://127.0.0.1:${port}/`));
+    });
+
+    await runWithEgressPolicy(policy, "limited.js:b", [], async () => {
+      const response = await fetch(`http://127.0.0.1:${port}/`);
+      expect(response.status).toEqual(200);
+    });
+  });
+
+  test("limits response size", async () => {
+    const policy = { allowedCidrs: ["127.0.0.0/8"], maxResponseBytes: 10 };
+    await runWithEgressPolicy(policy, "a.js:default", [], async () => {
+      const small = await fetch(`http://127.0.0.1:${port}/?size=5`);
+      expect(await small.text()).toEqual("aaaaa");
+      await expectBlocked(fetch(`http://127.0.0.1:${port}/?size=100`));
+      // Without a `Content-Length`, the body fails once it's too large.
+      const chunked = await fetch(`http://127.0.0.1:${port}/chunked?size=100`);
+      await expectBlocked(chunked.text());
+    });
+  });
+});
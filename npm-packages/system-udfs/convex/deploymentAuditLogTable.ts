@@ -127,12 +127,32 @@ export const clearTables = v.object({
   metadata: v.object({}),
 });
 
+const csvImportOptions = v.object({
+  delimiter: v.string(),
+  quote: v.string(),
+  // [header, field path] pairs.
+  header_mapping: v.array(v.array(v.string())),
+  path_separator: v.union(v.string(), v.null()),
+  // [header, column type] pairs.
+  column_types: v.array(v.array(v.string())),
+  null_handling: v.union(
+    v.literal("emptyString"),
+    v.literal("null"),
+    v.literal("omit"),
+  ),
+});
+
 export const snapshotImportFormat = v.union(
+  v.object({
+    format: v.literal("csv"),
+    table: v.string(),
+    csv_options: v.optional(csvImportOptions),
+  }),
   v.object({
     format: v.union(
-      v.literal("csv"),
       v.literal("jsonl"),
       v.literal("json_array"),
+      v.literal("parquet"),
     ),
     table: v.string(),
   }),
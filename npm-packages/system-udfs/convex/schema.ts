@@ -143,9 +143,19 @@ export const completedExport = v.object({
       v.literal("internal_json"),
       v.literal("clean_jsonl"),
       v.literal("zip"),
+      v.literal("parquet"),
       v.object({
         format: v.literal("zip"),
         include_storage: v.boolean(),
+        // Tables are written as Parquet files instead of JSONL.
+        parquet: v.optional(v.boolean()),
+        // Incremental exports only hold the changes since this timestamp.
+        base_ts: v.optional(v.union(v.int64(), v.null())),
+      }),
+      v.object({
+        format: v.literal("csv"),
+        path_separator: v.string(),
+        max_depth: v.int64(),
       }),
     ),
   ),
@@ -260,6 +270,8 @@ const snapshotImportsTable = defineTable({
   ),
   format: snapshotImportFormat,
   mode: snapshotImportMode,
+  object_key: v.string(),
+  member_id: v.optional(v.union(v.int64(), v.null())),
 });
 
 export default defineSchema({
@@ -356,4 +368,62 @@ export default defineSchema({
   _log_sinks: logSinksTable,
   _backend_state: backendStateTable,
   _snapshot_imports: snapshotImportsTable,
+  _revoked_admin_keys: defineTable({ keyId: v.string() }).index("by_key_id", [
+    "keyId",
+  ]),
+  _migrations: defineTable({
+    table: v.string(),
+    transform: v.string(),
+    batchSize: v.int64(),
+    dryRun: v.boolean(),
+    state: v.union(
+      v.literal("running"),
+      v.literal("verifying"),
+      v.literal("completed"),
+      v.literal("failed"),
+      v.literal("canceled"),
+    ),
+    error: v.union(v.string(), v.null()),
+    cursor: v.union(v.string(), v.null()),
+    documentsProcessed: v.int64(),
+    documentsChanged: v.int64(),
+    documentsVerified: v.int64(),
+    documentsInvalid: v.int64(),
+    firstInvalid: v.union(v.string(), v.null()),
+  }).index("by_state", ["state"]),
+  _export_schedules: defineTable({
+    cronSchedule: CronSchedule,
+    includeStorage: v.boolean(),
+    parquet: v.boolean(),
+    incremental: v.boolean(),
+    retentionCount: v.int64(),
+    nextTs: v.int64(),
+    pendingExport: v.union(v.string(), v.null()),
+    exports: v.array(
+      v.object({
+        exportId: v.string(),
+        snapshotTs: v.int64(),
+        baseTs: v.union(v.int64(), v.null()),
+      }),
+    ),
+  }).index("by_next_ts", ["nextTs"]),
+  _table_restores: defineTable({
+    ts: v.int64(),
+    tables: v.array(
+      v.object({
+        table: v.string(),
+        done: v.boolean(),
+        inserted: v.int64(),
+        replaced: v.int64(),
+        deleted: v.int64(),
+      }),
+    ),
+    state: v.union(
+      v.literal("running"),
+      v.literal("completed"),
+      v.literal("failed"),
+    ),
+    error: v.union(v.string(), v.null()),
+    cursor: v.union(v.string(), v.null()),
+  }).index("by_state", ["state"]),
 });
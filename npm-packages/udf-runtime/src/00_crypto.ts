@@ -3,7 +3,6 @@
 
 import {
   requiredArguments,
-  throwNotImplementedMethodError,
   throwUncatchableDeveloperError,
 } from "./helpers.js";
 import { performOp } from "./syscall.js";
@@ -13,12 +12,17 @@ import {
   copyBuffer,
 } from "./crypto/helpers.js";
 import {
+  normalizeAlgorithmDecrypt,
   normalizeAlgorithmDeriveBits,
   normalizeAlgorithmDigest,
+  normalizeAlgorithmEncrypt,
+  normalizeAlgorithmGenerateKey,
   normalizeAlgorithmGetKeyLength,
   normalizeAlgorithmImportKey,
   normalizeAlgorithmSign,
+  normalizeAlgorithmUnwrapKey,
   normalizeAlgorithmVerify,
+  normalizeAlgorithmWrapKey,
 } from "./crypto/normalize_algorithm.js";
 import {
   KEY_STORE,
@@ -31,6 +35,8 @@ import {
 import * as ImportKey from "./crypto/import_key.js";
 import * as ExportKey from "./crypto/export_key.js";
 import { deriveBits } from "./crypto/derive_bits.js";
+import { decrypt, encrypt } from "./crypto/encrypt.js";
+import { generateKey } from "./crypto/generate_key.js";
 import getKeyLength from "./crypto/get_key_length.js";
 
 class Crypto {
@@ -102,12 +108,70 @@ class SubtleCrypto {
     return result.buffer;
   }
 
-  async encrypt() {
-    throwNotImplementedMethodError("encrypt", "SubtleCrypto");
+  async encrypt(
+    algorithm: AlgorithmIdentifier | RsaOaepParams | AesCbcParams,
+    key: CryptoKey,
+    data: BufferSource,
+  ): Promise<ArrayBuffer> {
+    const prefix = "Failed to execute 'encrypt' on 'SubtleCrypto'";
+    requiredArguments(arguments.length, 3, prefix);
+
+    // 2.
+    const dataCopy = copyBuffer(data);
+
+    // 3.
+    const normalizedAlgorithm = normalizeAlgorithmEncrypt(algorithm);
+
+    // 8.
+    if (normalizedAlgorithm.name !== key[_algorithm].name) {
+      throw new DOMException(
+        "Encryption algorithm doesn't match key algorithm.",
+        "InvalidAccessError",
+      );
+    }
+
+    // 9.
+    if (!key[_usages].includes("encrypt")) {
+      throw new DOMException(
+        "Key does not support the 'encrypt' operation.",
+        "InvalidAccessError",
+      );
+    }
+
+    return encrypt(normalizedAlgorithm, key, dataCopy);
   }
 
-  async decrypt() {
-    throwNotImplementedMethodError("decrypt", "SubtleCrypto");
+  async decrypt(
+    algorithm: AlgorithmIdentifier | RsaOaepParams | AesCbcParams,
+    key: CryptoKey,
+    data: BufferSource,
+  ): Promise<ArrayBuffer> {
+    const prefix = "Failed to execute 'decrypt' on 'SubtleCrypto'";
+    requiredArguments(arguments.length, 3, prefix);
+
+    // 2.
+    const dataCopy = copyBuffer(data);
+
+    // 3.
+    const normalizedAlgorithm = normalizeAlgorithmDecrypt(algorithm);
+
+    // 8.
+    if (normalizedAlgorithm.name !== key[_algorithm].name) {
+      throw new DOMException(
+        "Decryption algorithm doesn't match key algorithm.",
+        "InvalidAccessError",
+      );
+    }
+
+    // 9.
+    if (!key[_usages].includes("decrypt")) {
+      throw new DOMException(
+        "Key does not support the 'decrypt' operation.",
+        "InvalidAccessError",
+      );
+    }
+
+    return decrypt(normalizedAlgorithm, key, dataCopy);
   }
 
   async sign(
@@ -419,7 +483,7 @@ class SubtleCrypto {
       | HkdfParams
       | Pbkdf2Params,
     baseKey: CryptoKey,
-    length: number,
+    length: number | null,
   ): Promise<ArrayBuffer> {
     const prefix = "Failed to execute 'deriveBits' on 'SubtleCrypto'";
     requiredArguments(arguments.length, 3, prefix);
@@ -645,16 +709,156 @@ class SubtleCrypto {
     throw new TypeError(`Unknown algorithm name ${normalizedAlgorithm.name}`);
   }
 
-  async wrapKey() {
-    throwNotImplementedMethodError("wrapKey", "SubtleCrypto");
+  async wrapKey(
+    format: "jwk" | "pkcs8" | "raw" | "spki",
+    key: CryptoKey,
+    wrappingKey: CryptoKey,
+    wrapAlgorithm: AlgorithmIdentifier | RsaOaepParams | AesCbcParams,
+  ): Promise<ArrayBuffer> {
+    const prefix = "Failed to execute 'wrapKey' on 'SubtleCrypto'";
+    requiredArguments(arguments.length, 4, prefix);
+
+    // 2.
+    const normalizedAlgorithm = normalizeAlgorithmWrapKey(wrapAlgorithm);
+
+    // 8.
+    if (normalizedAlgorithm.name !== wrappingKey[_algorithm].name) {
+      throw new DOMException(
+        "Wrapping algorithm doesn't match key algorithm.",
+        "InvalidAccessError",
+      );
+    }
+
+    // 9.
+    if (!wrappingKey[_usages].includes("wrapKey")) {
+      throw new DOMException(
+        "Key does not support the 'wrapKey' operation.",
+        "InvalidAccessError",
+      );
+    }
+
+    // 11.
+    if (key.extractable === false) {
+      throw new DOMException("Key is not extractable", "InvalidAccessError");
+    }
+
+    // 12.
+    const exportedKey = await this.exportKey(format, key);
+
+    // 13.
+    const bytes =
+      format === "jwk"
+        ? new TextEncoder().encode(JSON.stringify(exportedKey))
+        : new Uint8Array(exportedKey as ArrayBuffer);
+
+    // 14-15.
+    if (normalizedAlgorithm.name === "AES-KW") {
+      const keyData = KEY_STORE.get(wrappingKey[_handle]);
+      const cipherText = performOp(
+        "crypto/wrapKey",
+        { key: keyData, algorithm: normalizedAlgorithm.name },
+        bytes,
+      );
+      return cipherText.buffer;
+    }
+    return encrypt(normalizedAlgorithm, wrappingKey, bytes);
   }
 
-  async unwrapKey() {
-    throwNotImplementedMethodError("unwrapKey", "SubtleCrypto");
+  async unwrapKey(
+    format: "jwk" | "pkcs8" | "raw" | "spki",
+    wrappedKey: BufferSource,
+    unwrappingKey: CryptoKey,
+    unwrapAlgorithm: AlgorithmIdentifier | RsaOaepParams | AesCbcParams,
+    unwrappedKeyAlgorithm:
+      | AlgorithmIdentifier
+      | RsaHashedImportParams
+      | EcKeyImportParams
+      | HmacImportParams
+      | AesKeyAlgorithm,
+    extractable: boolean,
+    keyUsages: KeyUsage[],
+  ): Promise<CryptoKey> {
+    const prefix = "Failed to execute 'unwrapKey' on 'SubtleCrypto'";
+    requiredArguments(arguments.length, 7, prefix);
+
+    // 2.
+    const wrappedKeyCopy = copyBuffer(wrappedKey);
+
+    // 3.
+    const normalizedAlgorithm = normalizeAlgorithmUnwrapKey(unwrapAlgorithm);
+
+    // 11.
+    if (normalizedAlgorithm.name !== unwrappingKey[_algorithm].name) {
+      throw new DOMException(
+        "Unwrapping algorithm doesn't match key algorithm.",
+        "InvalidAccessError",
+      );
+    }
+
+    // 12.
+    if (!unwrappingKey[_usages].includes("unwrapKey")) {
+      throw new DOMException(
+        "Key does not support the 'unwrapKey' operation.",
+        "InvalidAccessError",
+      );
+    }
+
+    // 13.
+    let key: ArrayBuffer;
+    if (normalizedAlgorithm.name === "AES-KW") {
+      const keyData = KEY_STORE.get(unwrappingKey[_handle]);
+      const plainText = performOp(
+        "crypto/unwrapKey",
+        { key: keyData, algorithm: normalizedAlgorithm.name },
+        wrappedKeyCopy,
+      );
+      key = plainText.buffer;
+    } else {
+      key = decrypt(normalizedAlgorithm, unwrappingKey, wrappedKeyCopy);
+    }
+
+    // 14.
+    const bytes =
+      format === "jwk"
+        ? JSON.parse(new TextDecoder().decode(key))
+        : new Uint8Array(key);
+
+    // 15-18.
+    return await this.importKey(
+      format,
+      bytes,
+      unwrappedKeyAlgorithm,
+      extractable,
+      keyUsages,
+    );
   }
 
-  async generateKey() {
-    throwNotImplementedMethodError("generateKey", "SubtleCrypto");
+  async generateKey(
+    algorithm:
+      | AlgorithmIdentifier
+      | RsaHashedKeyGenParams
+      | EcKeyGenParams
+      | AesKeyGenParams
+      | HmacKeyGenParams,
+    extractable: boolean,
+    keyUsages: KeyUsage[],
+  ): Promise<CryptoKey | CryptoKeyPair> {
+    const prefix = "Failed to execute 'generateKey' on 'SubtleCrypto'";
+    requiredArguments(arguments.length, 3, prefix);
+
+    const normalizedAlgorithm = normalizeAlgorithmGenerateKey(algorithm);
+    const result = generateKey(normalizedAlgorithm, extractable, keyUsages);
+
+    if (result instanceof CryptoKey) {
+      const type = result[_type];
+      if ((type === "secret" || type === "private") && keyUsages.length === 0) {
+        throw new DOMException("Invalid key usages", "SyntaxError");
+      }
+    } else if (result.privateKey[_usages].length === 0) {
+      throw new DOMException("Invalid key usages", "SyntaxError");
+    }
+
+    return result;
   }
 
   inspect() {
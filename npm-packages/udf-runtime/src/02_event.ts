@@ -24,7 +24,7 @@ const isTrusted = Object.getOwnPropertyDescriptor(
   "isTrusted",
 )?.get;
 
-class Event {
+export class Event {
   constructor(type: string, eventInitDict?: EventInit | undefined) {
     this[_canceledFlag] = false;
     this[_stopPropagationFlag] = false;
@@ -698,7 +698,7 @@ function invokeEventListeners(tuple, eventImpl) {
   innerInvokeEventListeners(eventImpl, getListeners(tuple.item));
 }
 
-class EventTarget {
+export class EventTarget {
   constructor() {
     this[eventTargetData] = getDefaultTargetData();
   }
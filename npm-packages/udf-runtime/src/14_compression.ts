@@ -0,0 +1,137 @@
+// The initial implementation taken from Deno.
+// Copyright 2018-2024 the Deno authors. All rights reserved. MIT license.
+// https://github.com/denoland/deno/blob/main/ext/web/14_compression.js
+
+import { performOp } from "./syscall.js";
+import { copyBuffer } from "./crypto/helpers.js";
+import inspect from "object-inspect";
+
+const COMPRESSION_FORMATS = ["deflate", "deflate-raw", "gzip"];
+
+function validateFormat(format: string, className: string): string {
+  format = String(format);
+  if (!COMPRESSION_FORMATS.includes(format)) {
+    throw new TypeError(
+      `Failed to construct '${className}': The provided value '${format}' is not a valid enum value of type CompressionFormat.`,
+    );
+  }
+  return format;
+}
+
+function validateChunk(chunk: any): Uint8Array {
+  if (!(chunk instanceof ArrayBuffer) && !ArrayBuffer.isView(chunk)) {
+    throw new TypeError(
+      "The provided value is not of type '(ArrayBuffer or ArrayBufferView)'",
+    );
+  }
+  return copyBuffer(chunk);
+}
+
+function maybeEnqueue(
+  controller: TransformStreamDefaultController<Uint8Array>,
+  output: Uint8Array,
+) {
+  if (output && output.byteLength > 0) {
+    controller.enqueue(output);
+  }
+}
+
+function compressionTransform(
+  format: string,
+  isDecoder: boolean,
+): TransformStream<BufferSource, Uint8Array> {
+  const rid = performOp("compression/new", format, isDecoder);
+  return new TransformStream({
+    transform: (chunk, controller) => {
+      try {
+        const output = performOp(
+          "compression/write",
+          rid,
+          validateChunk(chunk),
+        );
+        maybeEnqueue(controller, output);
+        return Promise.resolve();
+      } catch (err) {
+        return Promise.reject(err);
+      }
+    },
+    flush: (controller) => {
+      try {
+        const output = performOp("compression/finish", rid, true);
+        maybeEnqueue(controller, output);
+        return Promise.resolve();
+      } catch (err) {
+        return Promise.reject(err);
+      }
+    },
+    cancel: (_reason) => {
+      try {
+        performOp("compression/finish", rid, false);
+        return Promise.resolve();
+      } catch (err) {
+        return Promise.reject(err);
+      }
+    },
+  } as Transformer<BufferSource, Uint8Array>);
+}
+
+class CompressionStream {
+  /** @type {TransformStream<BufferSource, Uint8Array>} */
+  #transform;
+
+  constructor(format: CompressionFormat) {
+    format = validateFormat(format, "CompressionStream") as CompressionFormat;
+    this.#transform = compressionTransform(format, false);
+  }
+
+  /** @returns {ReadableStream<Uint8Array>} */
+  get readable() {
+    return this.#transform.readable;
+  }
+
+  /** @returns {WritableStream<BufferSource>} */
+  get writable() {
+    return this.#transform.writable;
+  }
+
+  inspect() {
+    const properties = {
+      readable: this.readable,
+      writable: this.writable,
+    };
+    return `CompressionStream ${inspect(properties)}`;
+  }
+}
+
+class DecompressionStream {
+  /** @type {TransformStream<BufferSource, Uint8Array>} */
+  #transform;
+
+  constructor(format: CompressionFormat) {
+    format = validateFormat(format, "DecompressionStream") as CompressionFormat;
+    this.#transform = compressionTransform(format, true);
+  }
+
+  /** @returns {ReadableStream<Uint8Array>} */
+  get readable() {
+    return this.#transform.readable;
+  }
+
+  /** @returns {WritableStream<BufferSource>} */
+  get writable() {
+    return this.#transform.writable;
+  }
+
+  inspect() {
+    const properties = {
+      readable: this.readable,
+      writable: this.writable,
+    };
+    return `DecompressionStream ${inspect(properties)}`;
+  }
+}
+
+export const setupCompression = (global: any) => {
+  global.CompressionStream = CompressionStream;
+  global.DecompressionStream = DecompressionStream;
+};
@@ -7,6 +7,7 @@ import {
   extractStream,
   ReadableStream,
 } from "./06_streams.js";
+import { setIncomingWebSocketStreamId } from "./27_websocket.js";
 
 function isValidMethod(m: string) {
   return (
@@ -343,6 +344,7 @@ export const requestFromConvexJson = ({
     body: stream,
     method: convexJson.method,
   });
+  setIncomingWebSocketStreamId(convexJson.webSocketStreamId ?? null);
   return request;
 };
 
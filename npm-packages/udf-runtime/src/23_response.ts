@@ -7,6 +7,7 @@ import {
   extractStream,
   ReadableStream,
 } from "./06_streams.js";
+import { WebSocket, constructWebSocketStreamId } from "./27_websocket.js";
 
 const _contentLength = Symbol("[[contentLength]]");
 export const _redirected = Symbol("[[redirected]]");
@@ -19,6 +20,7 @@ export class Response {
   private _bodyStream: ReadableStream | null;
   private _bodyUsed = false;
   private _url: string;
+  private _webSocket: WebSocket | null;
   [_contentLength]: number | null;
   [_redirected]: boolean;
   [_responseType]: ResponseType;
@@ -78,6 +80,7 @@ export class Response {
       statusText?: string;
       headers?: [string, string][] | Headers | Record<string, string>;
       url?: string;
+      webSocket?: WebSocket | null;
     },
   ) {
     let status = options?.status === undefined ? 200 : options.status;
@@ -85,7 +88,19 @@ export class Response {
       // This coerces the string to a number (and is different from `parseInt` which allows trailing characters after a valid number)
       status = +status;
     }
-    if (
+    const webSocket = options?.webSocket ?? null;
+    if (webSocket !== null) {
+      if (!(webSocket instanceof WebSocket)) {
+        throw new TypeError(
+          "Failed to construct 'Response': webSocket must be a WebSocket",
+        );
+      }
+      if (status !== 101) {
+        throw new RangeError(
+          "Failed to construct 'Response': Responses with a webSocket must have status 101.",
+        );
+      }
+    } else if (
       typeof status !== "number" ||
       Number.isNaN(status) ||
       !Number.isInteger(status) ||
@@ -96,6 +111,7 @@ export class Response {
         "Failed to construct 'Response': The status provided is outside the range [200, 599].",
       );
     }
+    this._webSocket = webSocket;
     this._status = status;
     this._statusText = options?.statusText ?? "";
     this._headers = new Headers(options?.headers ?? []);
@@ -233,6 +249,10 @@ export class Response {
     return this._bodyStream;
   }
 
+  get webSocket() {
+    return this._webSocket;
+  }
+
   clone() {
     const clonedHeaderPairs: [string, string][] = [];
     this._headers.forEach((headerValue, headerName) =>
@@ -291,6 +311,14 @@ export const convexJsonFromResponse = ({ response }: { response: any }) => {
   if (!(response instanceof Response)) {
     throw new Error("HTTP actions must return a Response");
   }
+  if (response.webSocket !== null) {
+    return {
+      headerPairs: [...response.headers.entries()],
+      status: response.status,
+      streamId: constructWebSocketStreamId(response.webSocket),
+      webSocket: true,
+    };
+  }
   const streamId = constructStreamId(response.body);
   const headerPairs = [...response.headers.entries()];
   if (
@@ -0,0 +1,233 @@
+import type { ReadableStreamDefaultController } from "web-streams-polyfill";
+import { Event, EventTarget } from "./02_event.js";
+import { ReadableStream, constructStreamId } from "./06_streams.js";
+import { performAsyncOp, performOp } from "./syscall.js";
+
+// Framed messages from the client, if the request being handled by the HTTP
+// action asked to upgrade to a WebSocket. Only one `WebSocket` can accept it.
+let incomingStreamId: string | null = null;
+
+export const setIncomingWebSocketStreamId = (streamId: string | null) => {
+  incomingStreamId = streamId;
+};
+
+export class MessageEvent extends Event {
+  readonly data: string | ArrayBuffer;
+
+  constructor(type: string, init: { data: string | ArrayBuffer }) {
+    super(type);
+    this.data = init.data;
+  }
+}
+
+export class CloseEvent extends Event {
+  readonly code: number;
+  readonly reason: string;
+  readonly wasClean: boolean;
+
+  constructor(
+    type: string,
+    init: { code?: number; reason?: string; wasClean?: boolean },
+  ) {
+    super(type);
+    this.code = init.code ?? 1005;
+    this.reason = init.reason ?? "";
+    this.wasClean = init.wasClean ?? false;
+  }
+}
+
+const _peer = Symbol("[[peer]]");
+const _outgoing = Symbol("[[outgoing]]");
+
+/**
+ * The server end of a `WebSocketPair` is used by the HTTP action to exchange
+ * messages, and the client end is returned in a `101` `Response` to accept
+ * the upgrade.
+ */
+export class WebSocket extends EventTarget {
+  static readonly CONNECTING = 0;
+  static readonly OPEN = 1;
+  static readonly CLOSING = 2;
+  static readonly CLOSED = 3;
+
+  onmessage: ((event: MessageEvent) => void) | null = null;
+  onclose: ((event: CloseEvent) => void) | null = null;
+
+  private _readyState = WebSocket.CONNECTING;
+  private _accepted = false;
+  [_peer]: WebSocket | null = null;
+  [_outgoing]: {
+    stream: ReadableStream;
+    controller: ReadableStreamDefaultController;
+  } | null = null;
+
+  get readyState() {
+    return this._readyState;
+  }
+
+  accept() {
+    if (this[_outgoing] === null) {
+      throw new TypeError(
+        "Only the server end of a WebSocketPair can be accepted",
+      );
+    }
+    if (this._accepted) {
+      throw new TypeError("WebSocket has already been accepted");
+    }
+    if (incomingStreamId === null) {
+      throw new TypeError(
+        "Can't accept a WebSocket because the request didn't ask to upgrade to a WebSocket",
+      );
+    }
+    const streamId = incomingStreamId;
+    incomingStreamId = null;
+    this._accepted = true;
+    this._readyState = WebSocket.OPEN;
+    void this._receive(streamId);
+  }
+
+  send(data: string | ArrayBuffer | ArrayBufferView) {
+    if (this._readyState !== WebSocket.OPEN) {
+      throw new TypeError("WebSocket is not open");
+    }
+    let message;
+    if (typeof data === "string") {
+      message = { type: "text", data };
+    } else if (data instanceof ArrayBuffer) {
+      message = { type: "binary", data: new Uint8Array(data) };
+    } else if (ArrayBuffer.isView(data)) {
+      message = {
+        type: "binary",
+        data: new Uint8Array(data.buffer, data.byteOffset, data.byteLength),
+      };
+    } else {
+      throw new TypeError(
+        "WebSocket messages must be a string, ArrayBuffer or ArrayBufferView",
+      );
+    }
+    this._enqueue(message);
+  }
+
+  close(code?: number, reason?: string) {
+    if (code !== undefined && code !== 1000 && (code < 3000 || code > 4999)) {
+      throw new RangeError(
+        "WebSocket close code must be 1000 or between 3000 and 4999",
+      );
+    }
+    if (
+      this._readyState === WebSocket.CLOSING ||
+      this._readyState === WebSocket.CLOSED
+    ) {
+      return;
+    }
+    if (this._readyState === WebSocket.OPEN) {
+      this._enqueue({
+        type: "close",
+        code: code ?? null,
+        reason: reason ?? "",
+      });
+      this[_outgoing]!.controller.close();
+    }
+    this._readyState = WebSocket.CLOSED;
+  }
+
+  private _enqueue(message: Record<string, any>) {
+    const frame = performOp("webSocket/encodeMessage", message);
+    this[_outgoing]!.controller.enqueue(frame);
+  }
+
+  private _dispatch(event: MessageEvent | CloseEvent) {
+    this.dispatchEvent(event);
+    const handler = event.type === "message" ? this.onmessage : this.onclose;
+    handler?.call(this, event as any);
+  }
+
+  private async _receive(streamId: string) {
+    // eslint-disable-next-line no-constant-condition
+    while (true) {
+      const { value, done } = await performAsyncOp(
+        "stream/readPart",
+        streamId,
+      );
+      if (done) {
+        // The client went away without a close frame.
+        this._closeFromClient(new CloseEvent("close", { code: 1006 }));
+        return;
+      }
+      const message = performOp("webSocket/decodeMessage", value);
+      if (message.type === "close") {
+        this._closeFromClient(
+          new CloseEvent("close", {
+            code: message.code ?? 1005,
+            reason: message.reason,
+            wasClean: true,
+          }),
+        );
+        return;
+      }
+      if (this._readyState !== WebSocket.OPEN) {
+        continue;
+      }
+      const data =
+        message.type === "text"
+          ? message.data
+          : message.data.buffer.slice(
+              message.data.byteOffset,
+              message.data.byteOffset + message.data.byteLength,
+            );
+      this._dispatch(new MessageEvent("message", { data }));
+    }
+  }
+
+  private _closeFromClient(event: CloseEvent) {
+    // Echo the close so the action finishes once the client is gone.
+    this.close();
+    this._dispatch(event);
+  }
+}
+
+export class WebSocketPair {
+  0: WebSocket;
+  1: WebSocket;
+
+  constructor() {
+    const client = new WebSocket();
+    const server = new WebSocket();
+    let controller: ReadableStreamDefaultController;
+    const stream = new ReadableStream({
+      start(c) {
+        controller = c;
+      },
+    });
+    server[_outgoing] = { stream, controller: controller! };
+    client[_peer] = server;
+    this[0] = client;
+    this[1] = server;
+  }
+}
+
+/**
+ * Returns the stream of framed outgoing messages for a `Response` accepting a
+ * WebSocket with the client end of a `WebSocketPair`.
+ */
+export const constructWebSocketStreamId = (client: WebSocket): string => {
+  const server = client[_peer];
+  if (server === null) {
+    throw new TypeError(
+      "Responses must use the client end of a WebSocketPair",
+    );
+  }
+  if (server.readyState === WebSocket.CONNECTING) {
+    throw new TypeError(
+      "Call accept() on the server end of a WebSocketPair before returning the client end",
+    );
+  }
+  return constructStreamId(server[_outgoing]!.stream);
+};
+
+export const setupWebSocket = (global: any) => {
+  global.WebSocket = WebSocket;
+  global.WebSocketPair = WebSocketPair;
+  global.MessageEvent = MessageEvent;
+  global.CloseEvent = CloseEvent;
+};
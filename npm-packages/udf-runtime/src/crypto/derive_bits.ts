@@ -3,15 +3,22 @@
 
 import * as z from "zod";
 import { deriveBits as deriveBitsDef } from "./normalize_algorithm";
-import { CryptoKey, _handle, KEY_STORE } from "./crypto_key";
-import { copyBuffer } from "./helpers";
+import {
+  CryptoKey,
+  _algorithm,
+  _handle,
+  _type,
+  KEY_STORE,
+} from "./crypto_key";
+import { ArrayPrototypeIncludes, copyBuffer } from "./helpers";
+import { supportedNamedCurves } from "./import_key";
 import { performOp } from "../syscall.js";
 import { throwNotImplementedMethodError } from "../helpers";
 
 export async function deriveBits(
   normalizedAlgorithm: z.infer<typeof deriveBitsDef>,
   baseKey: CryptoKey,
-  length: number,
+  length: number | null,
 ) {
   switch (normalizedAlgorithm.name) {
     case "PBKDF2": {
@@ -48,8 +55,81 @@ export async function deriveBits(
 
       return buf.buffer;
     }
-    case "ECDH":
-    case "HKDF":
+    case "ECDH": {
+      // 1.
+      if (baseKey[_type] !== "private") {
+        throw new DOMException("Invalid key type", "InvalidAccessError");
+      }
+      // 2.
+      const publicKey = normalizedAlgorithm.public;
+      // 3.
+      if (publicKey[_type] !== "public") {
+        throw new DOMException("Invalid key type", "InvalidAccessError");
+      }
+      // 4.
+      if (publicKey[_algorithm].name !== baseKey[_algorithm].name) {
+        throw new DOMException("Algorithm mismatch", "InvalidAccessError");
+      }
+      // 5.
+      if (publicKey[_algorithm].namedCurve !== baseKey[_algorithm].namedCurve) {
+        throw new DOMException("namedCurve mismatch", "InvalidAccessError");
+      }
+      // 6.
+      if (
+        !ArrayPrototypeIncludes(
+          supportedNamedCurves,
+          publicKey[_algorithm].namedCurve,
+        )
+      ) {
+        throw new DOMException("Not implemented", "NotSupportedError");
+      }
+      const buf = performOp("crypto/deriveBits", {
+        key: KEY_STORE.get(baseKey[_handle]),
+        publicKey: KEY_STORE.get(publicKey[_handle]),
+        algorithm: "ECDH",
+        namedCurve: publicKey[_algorithm].namedCurve,
+      });
+      // 8.
+      if (length === null || length === undefined) {
+        return buf.buffer;
+      } else if (buf.buffer.byteLength * 8 < length) {
+        throw new DOMException("Invalid length", "OperationError");
+      } else {
+        return buf.buffer.slice(0, Math.ceil(length / 8));
+      }
+    }
+    case "HKDF": {
+      // 1.
+      if (
+        length === null ||
+        length === undefined ||
+        length === 0 ||
+        length % 8 !== 0
+      ) {
+        throw new DOMException("Invalid length", "OperationError");
+      }
+      if (
+        normalizedAlgorithm.salt === undefined ||
+        normalizedAlgorithm.info === undefined
+      ) {
+        throw new TypeError("HKDF requires a salt and info");
+      }
+
+      const buf = performOp(
+        "crypto/deriveBits",
+        {
+          key: KEY_STORE.get(baseKey[_handle]),
+          algorithm: "HKDF",
+          hash: normalizedAlgorithm.hash.name,
+          info: normalizedAlgorithm.info,
+          length,
+        },
+        normalizedAlgorithm.salt,
+      );
+
+      return buf.buffer;
+    }
+    case "X25519":
       return throwNotImplementedMethodError(
         `deriveBits with algorithm ${normalizedAlgorithm.name}`,
         "SubtleCrypto",
@@ -0,0 +1,141 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+// https://github.com/denoland/deno/blob/main/ext/crypto/00_crypto.js
+
+import * as z from "zod";
+import { encrypt as encryptDef } from "./normalize_algorithm";
+import { CryptoKey, _algorithm, _handle, _type, KEY_STORE } from "./crypto_key";
+import { ArrayPrototypeIncludes } from "./helpers";
+import { performOp } from "../syscall.js";
+
+function operationArgs(
+  normalizedAlgorithm: z.infer<typeof encryptDef>,
+  key: CryptoKey,
+  dataLength: number,
+  operation: "encrypt" | "decrypt",
+) {
+  const keyData = KEY_STORE.get(key[_handle]);
+  switch (normalizedAlgorithm.name) {
+    case "RSA-OAEP": {
+      // 1.
+      if (key[_type] !== (operation === "encrypt" ? "public" : "private")) {
+        throw new DOMException("Key type not supported", "InvalidAccessError");
+      }
+      return {
+        key: keyData,
+        algorithm: "RSA-OAEP",
+        hash: key[_algorithm].hash.name,
+        label: normalizedAlgorithm.label ?? new Uint8Array(),
+      };
+    }
+    case "AES-CBC": {
+      // 1.
+      if (normalizedAlgorithm.iv.byteLength !== 16) {
+        throw new DOMException(
+          "Initialization vector must be 16 bytes",
+          "OperationError",
+        );
+      }
+      return {
+        key: keyData,
+        algorithm: "AES-CBC",
+        length: key[_algorithm].length,
+        iv: normalizedAlgorithm.iv,
+      };
+    }
+    case "AES-CTR": {
+      // 1.
+      if (normalizedAlgorithm.counter.byteLength !== 16) {
+        throw new DOMException(
+          "Counter vector must be 16 bytes",
+          "OperationError",
+        );
+      }
+      // 2.
+      if (
+        normalizedAlgorithm.length === 0 ||
+        normalizedAlgorithm.length > 128
+      ) {
+        throw new DOMException(
+          "Counter length must not be 0 or greater than 128",
+          "OperationError",
+        );
+      }
+      return {
+        key: keyData,
+        algorithm: "AES-CTR",
+        keyLength: key[_algorithm].length,
+        counter: normalizedAlgorithm.counter,
+        ctrLength: normalizedAlgorithm.length,
+      };
+    }
+    case "AES-GCM": {
+      // 1.
+      if (dataLength > 2 ** 39 - 256) {
+        throw new DOMException("Data too large", "OperationError");
+      }
+      // 2.
+      if (
+        normalizedAlgorithm.iv.byteLength !== 12 &&
+        normalizedAlgorithm.iv.byteLength !== 16
+      ) {
+        throw new DOMException(
+          "Initialization vector length not supported",
+          "NotSupportedError",
+        );
+      }
+      // 3.
+      const tagLength = normalizedAlgorithm.tagLength ?? 128;
+      if (
+        !ArrayPrototypeIncludes([32, 64, 96, 104, 112, 120, 128], tagLength)
+      ) {
+        throw new DOMException("Invalid tag length", "OperationError");
+      }
+      return {
+        key: keyData,
+        algorithm: "AES-GCM",
+        length: key[_algorithm].length,
+        iv: normalizedAlgorithm.iv,
+        additionalData: normalizedAlgorithm.additionalData ?? null,
+        tagLength,
+      };
+    }
+    default:
+      throw new DOMException("Not implemented", "NotSupportedError");
+  }
+}
+
+export function encrypt(
+  normalizedAlgorithm: z.infer<typeof encryptDef>,
+  key: CryptoKey,
+  data: Uint8Array,
+): ArrayBuffer {
+  const args = operationArgs(
+    normalizedAlgorithm,
+    key,
+    data.byteLength,
+    "encrypt",
+  );
+  const cipherText = performOp("crypto/encrypt", args, data);
+  return cipherText.buffer;
+}
+
+export function decrypt(
+  normalizedAlgorithm: z.infer<typeof encryptDef>,
+  key: CryptoKey,
+  data: Uint8Array,
+): ArrayBuffer {
+  const args = operationArgs(
+    normalizedAlgorithm,
+    key,
+    data.byteLength,
+    "decrypt",
+  );
+  if (
+    normalizedAlgorithm.name === "AES-GCM" &&
+    data.byteLength < (normalizedAlgorithm.tagLength ?? 128) / 8
+  ) {
+    throw new DOMException("Tag length overflows ciphertext", "OperationError");
+  }
+  const plainText = performOp("crypto/decrypt", args, data);
+  return plainText.buffer;
+}
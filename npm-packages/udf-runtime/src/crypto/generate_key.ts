@@ -0,0 +1,191 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+// https://github.com/denoland/deno/blob/main/ext/crypto/00_crypto.js
+
+import { CryptoKey, KEY_STORE } from "./crypto_key";
+import {
+  ArrayPrototypeFind,
+  ArrayPrototypeIncludes,
+  TypedArrayPrototypeGetByteLength,
+  WeakMapPrototypeSet,
+} from "./helpers";
+import { supportedNamedCurves, usageIntersection } from "./import_key";
+import { performOp } from "../syscall.js";
+
+function checkUsages(usages: string[], supportedUsages: string[]) {
+  if (
+    ArrayPrototypeFind(
+      usages,
+      (u) => !ArrayPrototypeIncludes(supportedUsages, u),
+    ) !== undefined
+  ) {
+    throw new DOMException("Invalid key usages", "SyntaxError");
+  }
+}
+
+// Both halves of a generated key pair share the private key, which is how
+// the Rust ops expect them.
+function keyPair(
+  keyData: Uint8Array,
+  algorithm: object,
+  extractable: boolean,
+  publicUsages: string[],
+  privateUsages: string[],
+) {
+  const handle = {};
+  WeakMapPrototypeSet(KEY_STORE, handle, { type: "private", data: keyData });
+  const publicKey = new CryptoKey(
+    "public",
+    true,
+    publicUsages,
+    algorithm,
+    handle,
+  );
+  const privateKey = new CryptoKey(
+    "private",
+    extractable,
+    privateUsages,
+    algorithm,
+    handle,
+  );
+  return { publicKey, privateKey };
+}
+
+function secretKey(
+  keyData: Uint8Array,
+  algorithm: object,
+  extractable: boolean,
+  usages: string[],
+) {
+  const handle = {};
+  WeakMapPrototypeSet(KEY_STORE, handle, { type: "secret", data: keyData });
+  return new CryptoKey("secret", extractable, usages, algorithm, handle);
+}
+
+export function generateKey(
+  normalizedAlgorithm: any,
+  extractable: boolean,
+  usages: string[],
+) {
+  const algorithmName = normalizedAlgorithm.name;
+  switch (algorithmName) {
+    case "RSASSA-PKCS1-v1_5":
+    case "RSA-PSS":
+    case "RSA-OAEP": {
+      const [publicUsages, privateUsages] =
+        algorithmName === "RSA-OAEP"
+          ? [
+              ["encrypt", "wrapKey"],
+              ["decrypt", "unwrapKey"],
+            ]
+          : [["verify"], ["sign"]];
+      // 1.
+      checkUsages(usages, [...publicUsages, ...privateUsages]);
+      // 2.
+      const keyData = performOp("crypto/generateKey", {
+        algorithm: "RSA",
+        modulusLength: normalizedAlgorithm.modulusLength,
+        publicExponent: normalizedAlgorithm.publicExponent,
+      });
+      // 4-8.
+      const algorithm = {
+        name: algorithmName,
+        modulusLength: normalizedAlgorithm.modulusLength,
+        publicExponent: normalizedAlgorithm.publicExponent,
+        hash: normalizedAlgorithm.hash,
+      };
+      // 9-22.
+      return keyPair(
+        keyData,
+        algorithm,
+        extractable,
+        usageIntersection(usages, publicUsages),
+        usageIntersection(usages, privateUsages),
+      );
+    }
+    case "ECDSA":
+    case "ECDH": {
+      const namedCurve = normalizedAlgorithm.namedCurve;
+      const [publicUsages, privateUsages] =
+        algorithmName === "ECDSA"
+          ? [["verify"], ["sign"]]
+          : [[], ["deriveKey", "deriveBits"]];
+      // 1.
+      checkUsages(usages, [...publicUsages, ...privateUsages]);
+      // 2-3.
+      if (!ArrayPrototypeIncludes(supportedNamedCurves, namedCurve)) {
+        throw new DOMException("Curve not supported", "NotSupportedError");
+      }
+      const keyData = performOp("crypto/generateKey", {
+        algorithm: "EC",
+        namedCurve,
+      });
+      // 4-11.
+      return keyPair(
+        keyData,
+        { name: algorithmName, namedCurve },
+        extractable,
+        usageIntersection(usages, publicUsages),
+        usageIntersection(usages, privateUsages),
+      );
+    }
+    case "AES-CTR":
+    case "AES-CBC":
+    case "AES-GCM":
+    case "AES-KW": {
+      // 1.
+      checkUsages(
+        usages,
+        algorithmName === "AES-KW"
+          ? ["wrapKey", "unwrapKey"]
+          : ["encrypt", "decrypt", "wrapKey", "unwrapKey"],
+      );
+      // 2.
+      if (
+        !ArrayPrototypeIncludes([128, 192, 256], normalizedAlgorithm.length)
+      ) {
+        throw new DOMException("Invalid key length", "OperationError");
+      }
+      // 3.
+      const keyData = performOp("crypto/generateKey", {
+        algorithm: "AES",
+        length: normalizedAlgorithm.length,
+      });
+      // 4-11.
+      return secretKey(
+        keyData,
+        { name: algorithmName, length: normalizedAlgorithm.length },
+        extractable,
+        usages,
+      );
+    }
+    case "HMAC": {
+      // 1.
+      checkUsages(usages, ["sign", "verify"]);
+      // 2.
+      let length;
+      if (normalizedAlgorithm.length === undefined) {
+        length = null;
+      } else if (normalizedAlgorithm.length !== 0) {
+        length = normalizedAlgorithm.length;
+      } else {
+        throw new DOMException("Invalid length", "OperationError");
+      }
+      // 3-4.
+      const keyData = performOp("crypto/generateKey", {
+        algorithm: "HMAC",
+        hash: normalizedAlgorithm.hash.name,
+        length,
+      });
+      // 6-10.
+      const algorithm = {
+        name: algorithmName,
+        hash: { name: normalizedAlgorithm.hash.name },
+        length: TypedArrayPrototypeGetByteLength(keyData) * 8,
+      };
+      // 5, 11-13.
+      return secretKey(keyData, algorithm, extractable, usages);
+    }
+    default:
+      throw new DOMException("Not implemented", "NotSupportedError");
+  }
+}
@@ -306,7 +306,7 @@ const aesJwkAlg = {
   },
 };
 
-function usageIntersection(a: string[], b: string[]) {
+export function usageIntersection(a: string[], b: string[]) {
   return a.filter((i) => b.includes(i));
 }
 
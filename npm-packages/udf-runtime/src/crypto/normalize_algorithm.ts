@@ -53,10 +53,21 @@ const digest = z.union([
 
 const rsaHashedKeyGenParams = z.object({
   hash: digest,
+  modulusLength: z.number(),
+  publicExponent: z.instanceof(Uint8Array),
+});
+
+const ecKeyGenParams = z.object({
+  namedCurve: z.string(),
+});
+
+const aesKeyGenParams = z.object({
+  length: z.number(),
 });
 
 const hmacKeyGenParams = z.object({
   hash: digest,
+  length: z.optional(z.number()),
 });
 
 const hmacImportParams = z.object({
@@ -107,13 +118,51 @@ const rsaPssParams = z.object({
   saltLength: z.number(),
 });
 
-const _generateKey = z.union([
+const rsaOaepParams = z.object({
+  label: z.optional(bufferSource),
+});
+
+const aesCbcParams = z.object({
+  iv: bufferSource,
+});
+
+const aesCtrParams = z.object({
+  counter: bufferSource,
+  length: z.number(),
+});
+
+const aesGcmParams = z.object({
+  iv: bufferSource,
+  additionalData: z.optional(bufferSource),
+  tagLength: z.optional(z.number()),
+});
+
+const generateKey = z.union([
   algorithmNameLiteralWithParams("RSASSA-PKCS1-v1_5", rsaHashedKeyGenParams),
   algorithmNameLiteralWithParams("RSA-PSS", rsaHashedKeyGenParams),
   algorithmNameLiteralWithParams("RSA-OAEP", rsaHashedKeyGenParams),
+  algorithmNameLiteralWithParams("ECDSA", ecKeyGenParams),
+  algorithmNameLiteralWithParams("ECDH", ecKeyGenParams),
+  algorithmNameLiteralWithParams("AES-CTR", aesKeyGenParams),
+  algorithmNameLiteralWithParams("AES-CBC", aesKeyGenParams),
+  algorithmNameLiteralWithParams("AES-GCM", aesKeyGenParams),
+  algorithmNameLiteralWithParams("AES-KW", aesKeyGenParams),
   algorithmNameLiteralWithParams("HMAC", hmacKeyGenParams),
 ]);
 
+export const encrypt = z.union([
+  algorithmNameLiteralWithParams("RSA-OAEP", rsaOaepParams),
+  algorithmNameLiteralWithParams("AES-CBC", aesCbcParams),
+  algorithmNameLiteralWithParams("AES-CTR", aesCtrParams),
+  algorithmNameLiteralWithParams("AES-GCM", aesGcmParams),
+]);
+const decrypt = encrypt;
+
+// Wrapping falls back to encrypting the exported key for algorithms that
+// don't have a dedicated key wrapping operation.
+const wrapKey = z.union([algorithmNameLiteralWithoutParams("AES-KW"), encrypt]);
+const unwrapKey = wrapKey;
+
 const importKey = z.union([
   algorithmNameLiteralWithParams("RSASSA-PKCS1-v1_5", rsaHashedImportParams),
   algorithmNameLiteralWithParams("RSA-PSS", rsaHashedImportParams),
@@ -212,6 +261,61 @@ export const normalizeAlgorithmGetKeyLength = (
   }
 };
 
+export const normalizeAlgorithmEncrypt = (
+  input: unknown,
+): z.infer<typeof encrypt> => {
+  const result = encrypt.safeParse(input);
+  if (!result.success) {
+    throw new Error("Unrecognized algorithm");
+  } else {
+    return result.data;
+  }
+};
+
+export const normalizeAlgorithmDecrypt = (
+  input: unknown,
+): z.infer<typeof decrypt> => {
+  const result = decrypt.safeParse(input);
+  if (!result.success) {
+    throw new Error("Unrecognized algorithm");
+  } else {
+    return result.data;
+  }
+};
+
+export const normalizeAlgorithmGenerateKey = (
+  input: unknown,
+): z.infer<typeof generateKey> => {
+  const result = generateKey.safeParse(input);
+  if (!result.success) {
+    throw new Error("Unrecognized algorithm");
+  } else {
+    return result.data;
+  }
+};
+
+export const normalizeAlgorithmWrapKey = (
+  input: unknown,
+): z.infer<typeof wrapKey> => {
+  const result = wrapKey.safeParse(input);
+  if (!result.success) {
+    throw new Error("Unrecognized algorithm");
+  } else {
+    return result.data;
+  }
+};
+
+export const normalizeAlgorithmUnwrapKey = (
+  input: unknown,
+): z.infer<typeof unwrapKey> => {
+  const result = unwrapKey.safeParse(input);
+  if (!result.success) {
+    throw new Error("Unrecognized algorithm");
+  } else {
+    return result.data;
+  }
+};
+
 export const normalizeAlgorithmDigest = (
   input: unknown,
 ): z.infer<typeof digest> => {
@@ -7,12 +7,14 @@ import { setupTimers } from "./02_timers.js";
 import { setupAbortSignal } from "./03_abort_signal.js";
 import { setupStreams } from "./06_streams.js";
 import { setupTextEncoding } from "./08_text_encoding.js";
+import { setupCompression } from "./14_compression.js";
 import { setupBlob } from "./09_file.js";
 import { setupHeaders } from "./20_headers.js";
 import { setupFormData } from "./21_formdata.js";
 import { requestFromConvexJson, setupRequest } from "./23_request.js";
 import { convexJsonFromResponse, setupResponse } from "./23_response.js";
 import { setupFetch } from "./26_fetch.js";
+import { setupWebSocket } from "./27_websocket.js";
 import { setupSourceMapping } from "./errors.js";
 import { throwUncatchableDeveloperError } from "./helpers.js";
 import { getBlob, getResponse, storeBlob, storeRequest } from "./storage.js";
@@ -43,12 +45,14 @@ export function setup(global: any) {
   setupAbortSignal(global);
   setupStreams(global);
   setupTextEncoding(global);
+  setupCompression(global);
   setupBlob(global);
   setupHeaders(global);
   setupFormData(global);
   setupRequest(global);
   setupResponse(global);
   setupFetch(global);
+  setupWebSocket(global);
 
   global.Convex.jsSyscall = (op: string, args: Record<string, any>) => {
     switch (op) {
@@ -25,3 +25,14 @@ export const recordArg = query({
     return arg;
   },
 });
+
+export const constrainedArgs = query({
+  args: {
+    name: v.optional(v.constrained(v.string(), { default: "anonymous" })),
+    count: v.constrained(v.number(), { minimum: 0, maximum: 10 }),
+  },
+
+  handler: (_, { name, count }) => {
+    return { name, count };
+  },
+});
@@ -258,6 +258,25 @@ http.route({
   }),
 });
 
+http.route({
+  path: "/webSocket",
+  method: "GET",
+  handler: httpAction(async () => {
+    const { 0: client, 1: server } = new (globalThis as any).WebSocketPair();
+    server.accept();
+    server.addEventListener("message", (event: any) => {
+      if (event.data === "bye") {
+        server.close(1000, "Goodbye");
+      } else if (typeof event.data === "string") {
+        server.send(`Echo: ${event.data}`);
+      } else {
+        server.send(new Uint8Array(event.data).reverse());
+      }
+    });
+    return new Response(null, { status: 101, webSocket: client } as any);
+  }),
+});
+
 export const erroringQuery = query(() => {
   throw new Error("Oh no! Called erroring query");
 });
@@ -0,0 +1,159 @@
+import { assert, expect } from "chai";
+import { wrapInTests } from "./testHelpers";
+import { query } from "../_generated/server";
+
+export default query(async () => {
+  return await wrapInTests({
+    compressionRoundTrip,
+    compressionChunkedInput,
+    decompressGzip,
+    decompressDeflate,
+    compressionInvalidFormat,
+    decompressCorruptData,
+    compressionStreamPersisted,
+    compressionTooManyStreams,
+  });
+});
+
+async function readAll(stream: ReadableStream<Uint8Array>) {
+  const chunks: Uint8Array[] = [];
+  const reader = stream.getReader();
+  // eslint-disable-next-line no-constant-condition
+  while (true) {
+    const { value, done } = await reader.read();
+    if (done) {
+      break;
+    }
+    chunks.push(value);
+  }
+  const result = new Uint8Array(
+    chunks.reduce((total, chunk) => total + chunk.byteLength, 0),
+  );
+  let offset = 0;
+  for (const chunk of chunks) {
+    result.set(chunk, offset);
+    offset += chunk.byteLength;
+  }
+  return result;
+}
+
+async function transform(
+  transformStream: { readable: ReadableStream; writable: WritableStream },
+  chunks: BufferSource[],
+) {
+  const writer = transformStream.writable.getWriter();
+  const output = readAll(transformStream.readable);
+  for (const chunk of chunks) {
+    await writer.write(chunk);
+  }
+  await writer.close();
+  return await output;
+}
+
+const FORMATS: CompressionFormat[] = ["deflate", "deflate-raw", "gzip"];
+
+async function compressionRoundTrip() {
+  const input = new TextEncoder().encode("hello world ".repeat(1000));
+  for (const format of FORMATS) {
+    const compressed = await transform(new CompressionStream(format), [input]);
+    assert(compressed.byteLength < input.byteLength);
+    const decompressed = await transform(new DecompressionStream(format), [
+      compressed,
+    ]);
+    assert.deepEqual(decompressed, input);
+  }
+}
+
+async function compressionChunkedInput() {
+  const encoder = new TextEncoder();
+  const compressed = await transform(new CompressionStream("gzip"), [
+    encoder.encode("hello "),
+    encoder.encode("world").buffer,
+  ]);
+  // Feed the compressed bytes back one at a time.
+  const chunks = Array.from(compressed, (byte) => new Uint8Array([byte]));
+  const decompressed = await transform(
+    new DecompressionStream("gzip"),
+    chunks,
+  );
+  assert.strictEqual(new TextDecoder().decode(decompressed), "hello world");
+}
+
+async function decompressGzip() {
+  // `gzip` of "hello".
+  // prettier-ignore
+  const compressed = new Uint8Array([
+    0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xcb, 0x48,
+    0xcd, 0xc9, 0xc9, 0x07, 0x00, 0x86, 0xa6, 0x10, 0x36, 0x05, 0x00, 0x00,
+    0x00,
+  ]);
+  const decompressed = await transform(new DecompressionStream("gzip"), [
+    compressed,
+  ]);
+  assert.strictEqual(new TextDecoder().decode(decompressed), "hello");
+}
+
+async function decompressDeflate() {
+  // zlib-wrapped deflate of "hello".
+  // prettier-ignore
+  const compressed = new Uint8Array([
+    0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00, 0x06, 0x2c, 0x02,
+    0x15,
+  ]);
+  const decompressed = await transform(new DecompressionStream("deflate"), [
+    compressed,
+  ]);
+  assert.strictEqual(new TextDecoder().decode(decompressed), "hello");
+}
+
+function compressionInvalidFormat() {
+  assert.throws(
+    () => new CompressionStream("brotli" as CompressionFormat),
+    TypeError,
+    /not a valid enum value/,
+  );
+  assert.throws(
+    () => new DecompressionStream("zstd" as CompressionFormat),
+    TypeError,
+    /not a valid enum value/,
+  );
+}
+
+async function decompressCorruptData() {
+  await expect(
+    transform(new DecompressionStream("deflate"), [
+      new TextEncoder().encode("definitely not deflate"),
+    ]),
+  ).to.be.rejected;
+}
+
+async function compressionStreamPersisted() {
+  // Compress a stream that has been handed to Rust and back, as happens with
+  // request and response bodies.
+  const input = new TextEncoder().encode("hello from rust");
+  const body = (new Blob([input]).stream() as any)["_persist"]();
+  const compressed = await readAll(
+    body.pipeThrough(new CompressionStream("gzip")),
+  );
+  const decompressed = await readAll(
+    new Blob([compressed]).stream().pipeThrough(new DecompressionStream("gzip")),
+  );
+  assert.deepEqual(decompressed, input);
+}
+
+async function compressionTooManyStreams() {
+  const streams: CompressionStream[] = [];
+  assert.throws(() => {
+    for (let i = 0; i < 10000; i++) {
+      streams.push(new CompressionStream("gzip"));
+    }
+  }, /Too many open compression streams/);
+  // Finishing a stream makes room for another.
+  await transform(streams.pop()!, []);
+  const input = new TextEncoder().encode("hello");
+  const compressed = await transform(new CompressionStream("gzip"), [input]);
+  const decompressed = await transform(new DecompressionStream("gzip"), [
+    compressed,
+  ]);
+  assert.deepEqual(decompressed, input);
+}
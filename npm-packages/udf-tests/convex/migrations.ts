@@ -0,0 +1,18 @@
+import { internalMutation } from "./_generated/server";
+
+// Migration transform renaming the field `a` to `b`.
+export const renameAToB = internalMutation(
+  async (_, { documents }: { documents: any[] }) => {
+    return documents.map((document) => {
+      if (document.a === undefined) {
+        return null;
+      }
+      const { _id, _creationTime, a, ...rest } = document;
+      return { ...rest, b: a };
+    });
+  },
+);
+
+export const throwError = internalMutation(async () => {
+  throw new Error("Oh no");
+});